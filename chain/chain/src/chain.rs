@@ -21,6 +21,7 @@ use near_primitives::challenge::{
     MaybeEncodedShardChunk, PartialState, SlashedValidator,
 };
 use near_primitives::checked_feature;
+use near_primitives::congestion_info::BlockCongestionInfo;
 use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::merkle::{
     combine_hash, merklize, verify_path, Direction, MerklePath, MerklePathItem, PartialMerkleTree,
@@ -453,6 +454,9 @@ pub struct Chain {
     last_time_head_updated: Instant,
     /// Used when it is needed to create flat storage in background for some shards.
     flat_storage_creator: Option<FlatStorageCreator>,
+    /// Pool of `ChainConfig::background_migration_threads` threads shared by
+    /// the background work of the client.  Not available to view clients.
+    background_pool: Option<Arc<rayon::ThreadPool>>,
 
     invalid_blocks: LruCache<CryptoHash, ()>,
 
@@ -535,6 +539,7 @@ impl Chain {
             apply_chunks_receiver: rc,
             last_time_head_updated: Clock::instant(),
             flat_storage_creator: None,
+            background_pool: None,
             invalid_blocks: LruCache::new(INVALID_CHUNKS_POOL_SIZE),
             pending_state_patch: Default::default(),
             requested_state_parts: StateRequestTracker::new(),
@@ -653,12 +658,15 @@ impl Chain {
         };
         store_update.commit()?;
 
-        // Create flat storage or initiate migration to flat storage.
-        let flat_storage_creator = FlatStorageCreator::new(
-            runtime_adapter.clone(),
-            &store,
-            chain_config.background_migration_threads,
+        let background_pool = Arc::new(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(chain_config.background_migration_threads)
+                .build()
+                .unwrap(),
         );
+        // Create flat storage or initiate migration to flat storage.
+        let flat_storage_creator =
+            FlatStorageCreator::new(runtime_adapter.clone(), &store, background_pool.clone());
 
         info!(target: "chain", "Init: header head @ #{} {}; block head @ #{} {}",
               header_head.height, header_head.last_block_hash,
@@ -693,11 +701,18 @@ impl Chain {
             apply_chunks_receiver: rc,
             last_time_head_updated: Clock::instant(),
             flat_storage_creator,
+            background_pool: Some(background_pool),
             pending_state_patch: Default::default(),
             requested_state_parts: StateRequestTracker::new(),
         })
     }
 
+    /// Returns the pool for background work, unless this is a view client's
+    /// chain.
+    pub fn background_pool(&self) -> Option<&rayon::ThreadPool> {
+        self.background_pool.as_deref()
+    }
+
     #[cfg(feature = "test_features")]
     pub fn adv_disable_doomslug(&mut self) {
         self.doomslug_threshold_mode = DoomslugThresholdMode::NoApprovals
@@ -933,6 +948,7 @@ impl Chain {
             }
             let blocks_current_height = self.store.get_all_block_hashes_by_height(height);
             let mut chain_store_update = self.store.store_update();
+            chain_store_update.set_gc_retained_accounts(gc_config.retained_accounts.clone());
             if let Ok(blocks_current_height) = blocks_current_height {
                 let blocks_current_height =
                     blocks_current_height.values().flatten().cloned().collect::<Vec<_>>();
@@ -3089,6 +3105,32 @@ impl Chain {
         self.store.get_state_header(shard_id, sync_hash)
     }
 
+    /// Returns hashes of the blocks state sync at `sync_hash` needs to finalize
+    /// the given shards: the previous block of the sync block and every block
+    /// since the last chunk of any of the shards was included. Their chunk
+    /// headers carry the congestion info the chunks are applied with. Shards
+    /// whose state header is not downloaded yet are skipped.
+    pub fn get_state_sync_blocks(
+        &self,
+        sync_hash: &CryptoHash,
+        shard_ids: &[ShardId],
+    ) -> Result<Vec<CryptoHash>, Error> {
+        let sync_header = self.get_block_header(sync_hash)?;
+        let min_height_included = shard_ids
+            .iter()
+            .filter_map(|shard_id| self.get_state_header(*shard_id, *sync_hash).ok())
+            .map(|header| header.chunk_height_included())
+            .min()
+            .unwrap_or(sync_header.height());
+        let mut header = self.get_block_header(sync_header.prev_hash())?;
+        let mut hashes = vec![*header.hash()];
+        while header.height() > min_height_included {
+            header = self.get_block_header(header.prev_hash())?;
+            hashes.push(*header.hash());
+        }
+        Ok(hashes)
+    }
+
     pub fn set_state_part(
         &mut self,
         shard_id: ShardId,
@@ -3184,6 +3226,21 @@ impl Chain {
         let prev_hash = *self.get_block_header(sync_hash)?.prev_hash();
         let state_root = *self.get_chunk_extra(&prev_hash, &shard_uid)?.state_root();
         assert_ne!(shard_layout, next_epoch_shard_layout);
+        // The congestion info and the buffered receipts of a shard can't be
+        // split between the new shards. The protocol features are gated so that
+        // the shard layout never changes once congestion control is enabled, see
+        // `ProtocolFeature::CongestionControl`, so this is just a sanity check.
+        let protocol_version = self.runtime_adapter.get_epoch_protocol_version(&epoch_id)?;
+        if checked_feature!(
+            "protocol_feature_congestion_control",
+            CongestionControl,
+            protocol_version
+        ) {
+            return Err(Error::Other(format!(
+                "resharding is not supported with congestion control, protocol version {}",
+                protocol_version
+            )));
+        }
 
         state_split_scheduler(StateSplitRequest {
             runtime: Arc::clone(&self.runtime_adapter),
@@ -3386,33 +3443,7 @@ impl Chain {
         transaction_hash: &CryptoHash,
     ) -> Result<FinalExecutionOutcomeView, Error> {
         let mut outcomes = self.get_recursive_transaction_results(transaction_hash)?;
-        let mut looking_for_id = *transaction_hash;
-        let num_outcomes = outcomes.len();
-        let status = outcomes
-            .iter()
-            .find_map(|outcome_with_id| {
-                if outcome_with_id.id == looking_for_id {
-                    match &outcome_with_id.outcome.status {
-                        ExecutionStatusView::Unknown if num_outcomes == 1 => {
-                            Some(FinalExecutionStatus::NotStarted)
-                        }
-                        ExecutionStatusView::Unknown => Some(FinalExecutionStatus::Started),
-                        ExecutionStatusView::Failure(e) => {
-                            Some(FinalExecutionStatus::Failure(e.clone()))
-                        }
-                        ExecutionStatusView::SuccessValue(v) => {
-                            Some(FinalExecutionStatus::SuccessValue(v.clone()))
-                        }
-                        ExecutionStatusView::SuccessReceiptId(id) => {
-                            looking_for_id = *id;
-                            None
-                        }
-                    }
-                } else {
-                    None
-                }
-            })
-            .expect("results should resolve to a final outcome");
+        let status = get_final_execution_status(&outcomes);
         let receipts_outcome = outcomes.split_off(1);
         let transaction = self.store.get_transaction(transaction_hash)?.ok_or_else(|| {
             Error::DBNotFoundErr(format!("Transaction {} is not found", transaction_hash))
@@ -3628,6 +3659,7 @@ impl Chain {
         let will_shard_layout_change =
             self.runtime_adapter.will_shard_layout_change_next_epoch(prev_hash)?;
         let prev_chunk_headers = Chain::get_prev_chunk_headers(&*self.runtime_adapter, prev_block)?;
+        let congestion_info = BlockCongestionInfo::from_chunk_headers(block.chunks().iter());
         let mut process_one_chunk = |shard_id: usize,
                                      chunk_header: &ShardChunkHeader,
                                      prev_chunk_header: &ShardChunkHeader|
@@ -3791,6 +3823,7 @@ impl Chain {
                     let random_seed = *block.header().random_value();
                     let height = chunk_header.height_included();
                     let prev_block_hash = chunk_header.prev_block_hash().clone();
+                    let congestion_info = congestion_info.clone();
 
                     Ok(Some(Box::new(move |parent_span| -> Result<ApplyChunkResult, Error> {
                         let _span = tracing::debug_span!(
@@ -3813,6 +3846,7 @@ impl Chain {
                             gas_price,
                             gas_limit,
                             &challenges_result,
+                            &congestion_info,
                             random_seed,
                             true,
                             is_first_block_with_chunk_of_version,
@@ -3853,6 +3887,7 @@ impl Chain {
                     let random_seed = *block.header().random_value();
                     let height = block.header().height();
                     let prev_block_hash = *prev_block.hash();
+                    let congestion_info = congestion_info.clone();
 
                     Ok(Some(Box::new(move |parent_span| -> Result<ApplyChunkResult, Error> {
                         let _span = tracing::debug_span!(
@@ -3874,6 +3909,7 @@ impl Chain {
                             gas_price,
                             new_extra.gas_limit(),
                             &challenges_result,
+                            &congestion_info,
                             random_seed,
                             false,
                             false,
@@ -5284,6 +5320,10 @@ impl<'a> ChainUpdate<'a> {
             &chunk_header.prev_block_hash(),
             shard_id,
         )?;
+        // State sync fetches all blocks since the chunk was included, see
+        // `Chain::get_state_sync_blocks`.
+        let block = self.chain_store_update.get_block(block_header.hash())?;
+        let congestion_info = BlockCongestionInfo::from_chunk_headers(block.chunks().iter());
 
         let apply_result = self.runtime_adapter.apply_transactions(
             shard_id,
@@ -5298,6 +5338,7 @@ impl<'a> ChainUpdate<'a> {
             gas_price,
             gas_limit,
             block_header.challenges_result(),
+            &congestion_info,
             *block_header.random_value(),
             true,
             is_first_block_with_chunk_of_version,
@@ -5369,6 +5410,8 @@ impl<'a> ChainUpdate<'a> {
         let shard_uid = self.runtime_adapter.shard_id_to_uid(shard_id, block_header.epoch_id())?;
         let chunk_extra =
             self.chain_store_update.get_chunk_extra(prev_block_header.hash(), &shard_uid)?;
+        let block = self.chain_store_update.get_block(block_header.hash())?;
+        let congestion_info = BlockCongestionInfo::from_chunk_headers(block.chunks().iter());
 
         let apply_result = self.runtime_adapter.apply_transactions(
             shard_id,
@@ -5383,6 +5426,7 @@ impl<'a> ChainUpdate<'a> {
             prev_block_header.gas_price(),
             chunk_extra.gas_limit(),
             block_header.challenges_result(),
+            &congestion_info,
             *block_header.random_value(),
             false,
             false,
@@ -5424,6 +5468,38 @@ where
     receipt_proofs.into_iter().flat_map(|ReceiptProof(receipts, _)| receipts).cloned().collect()
 }
 
+/// Returns the final status of a transaction given the outcomes of the
+/// transaction and all of its descendant receipts, transaction outcome first.
+pub fn get_final_execution_status(outcomes: &[ExecutionOutcomeWithIdView]) -> FinalExecutionStatus {
+    let mut looking_for_id = outcomes[0].id;
+    let num_outcomes = outcomes.len();
+    outcomes
+        .iter()
+        .find_map(|outcome_with_id| {
+            if outcome_with_id.id == looking_for_id {
+                match &outcome_with_id.outcome.status {
+                    ExecutionStatusView::Unknown if num_outcomes == 1 => {
+                        Some(FinalExecutionStatus::NotStarted)
+                    }
+                    ExecutionStatusView::Unknown => Some(FinalExecutionStatus::Started),
+                    ExecutionStatusView::Failure(e) => {
+                        Some(FinalExecutionStatus::Failure(e.clone()))
+                    }
+                    ExecutionStatusView::SuccessValue(v) => {
+                        Some(FinalExecutionStatus::SuccessValue(v.clone()))
+                    }
+                    ExecutionStatusView::SuccessReceiptId(id) => {
+                        looking_for_id = *id;
+                        None
+                    }
+                }
+            } else {
+                None
+            }
+        })
+        .expect("results should resolve to a final outcome")
+}
+
 pub fn collect_receipts_from_response(
     receipt_proof_response: &[ReceiptProofResponse],
 ) -> Vec<Receipt> {
@@ -362,14 +362,14 @@ impl FlatStorageShardCreator {
 pub struct FlatStorageCreator {
     pub shard_creators: Vec<FlatStorageShardCreator>,
     /// Used to spawn threads for traversing state parts.
-    pub pool: rayon::ThreadPool,
+    pub pool: Arc<rayon::ThreadPool>,
 }
 
 impl FlatStorageCreator {
     pub fn new(
         runtime_adapter: Arc<dyn RuntimeAdapter>,
         chain_store: &ChainStore,
-        num_threads: usize,
+        pool: Arc<rayon::ThreadPool>,
     ) -> Option<Self> {
         let chain_head = chain_store.head().unwrap();
         let num_shards = runtime_adapter.num_shards(&chain_head.epoch_id).unwrap();
@@ -397,10 +397,7 @@ impl FlatStorageCreator {
         }
 
         if creation_needed {
-            Some(Self {
-                shard_creators,
-                pool: rayon::ThreadPoolBuilder::new().num_threads(num_threads).build().unwrap(),
-            })
+            Some(Self { shard_creators, pool })
         } else {
             None
         }
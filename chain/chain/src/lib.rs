@@ -1,10 +1,13 @@
 pub use block_processing_utils::{BlockProcessingArtifact, DoneApplyChunkCallback};
-pub use chain::{check_known, collect_receipts, Chain, MAX_ORPHAN_SIZE};
+pub use chain::{
+    check_known, collect_receipts, get_final_execution_status, Chain, MAX_ORPHAN_SIZE,
+};
 pub use doomslug::{Doomslug, DoomslugBlockProductionReadiness, DoomslugThresholdMode};
 pub use lightclient::{create_light_client_block_view, get_epoch_block_producers_view};
 pub use near_chain_primitives::{self, Error};
 pub use near_primitives::receipt::ReceiptResult;
 pub use store::{ChainStore, ChainStoreAccess, ChainStoreUpdate};
+pub use store_consistency_checker::StoreConsistencyChecker;
 pub use store_validator::{ErrorMessage, StoreValidator};
 pub use types::{Block, BlockHeader, BlockStatus, ChainGenesis, Provenance, RuntimeAdapter};
 
@@ -21,6 +24,7 @@ pub mod migrations;
 pub mod missing_chunks;
 mod state_request_tracker;
 mod store;
+mod store_consistency_checker;
 pub mod store_validator;
 pub mod test_utils;
 #[cfg(test)]
@@ -1,6 +1,7 @@
 use near_o11y::metrics::{
     exponential_buckets, try_create_histogram, try_create_histogram_vec, try_create_int_counter,
-    try_create_int_gauge, Histogram, HistogramVec, IntCounter, IntGauge,
+    try_create_int_counter_vec, try_create_int_gauge, Histogram, HistogramVec, IntCounter,
+    IntCounterVec, IntGauge,
 };
 use once_cell::sync::Lazy;
 
@@ -110,3 +111,18 @@ pub static STATE_PART_ELAPSED: Lazy<HistogramVec> = Lazy::new(|| {
 pub static NUM_INVALID_BLOCKS: Lazy<IntGauge> = Lazy::new(|| {
     try_create_int_gauge("near_num_invalid_blocks", "Number of invalid blocks").unwrap()
 });
+pub static STORE_CONSISTENCY_CHECK_HEIGHT: Lazy<IntGauge> = Lazy::new(|| {
+    try_create_int_gauge(
+        "near_store_consistency_check_height",
+        "Height the background store consistency checker continues from",
+    )
+    .unwrap()
+});
+pub static STORE_CONSISTENCY_CHECK_ERRORS: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_store_consistency_check_errors_total",
+        "Number of violations found by the background store consistency checker",
+        &["col"],
+    )
+    .unwrap()
+});
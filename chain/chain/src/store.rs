@@ -4,6 +4,7 @@ use std::io;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_cache::CellLruCache;
+use near_chain_configs::AccountRetentionFilter;
 use near_primitives::time::Utc;
 
 use near_chain_primitives::error::Error;
@@ -1197,6 +1198,8 @@ pub struct ChainStoreUpdate<'a> {
     add_state_dl_infos: Vec<StateSyncInfo>,
     remove_state_dl_infos: Vec<CryptoHash>,
     challenged_blocks: HashSet<CryptoHash>,
+    // Accounts whose history must survive garbage collection done by this update
+    gc_retained_accounts: AccountRetentionFilter,
 }
 
 impl<'a> ChainStoreUpdate<'a> {
@@ -1221,6 +1224,7 @@ impl<'a> ChainStoreUpdate<'a> {
             add_state_dl_infos: vec![],
             remove_state_dl_infos: vec![],
             challenged_blocks: HashSet::default(),
+            gc_retained_accounts: AccountRetentionFilter::default(),
         }
     }
 }
@@ -1935,6 +1939,36 @@ impl<'a> ChainStoreUpdate<'a> {
         self.chunk_tail = Some(height);
     }
 
+    /// Makes garbage collection done by this update keep state changes,
+    /// receipts and execution outcomes of the given accounts.
+    ///
+    /// Used by partial archival nodes which want full history of selected
+    /// accounts only.  Retained data is never cleared afterwards.
+    pub fn set_gc_retained_accounts(&mut self, retained_accounts: AccountRetentionFilter) {
+        self.gc_retained_accounts = retained_accounts;
+    }
+
+    fn is_retained_receipt(&self, receipt: &Receipt) -> bool {
+        self.gc_retained_accounts.matches(&receipt.receiver_id)
+            || self.gc_retained_accounts.matches(&receipt.predecessor_id)
+    }
+
+    fn is_retained_state_change(&self, key: &[u8]) -> bool {
+        if self.gc_retained_accounts.is_empty() {
+            return false;
+        }
+        let raw_key = &key[std::mem::size_of::<CryptoHash>()..];
+        match trie_key_parsers::parse_account_id_from_raw_key(raw_key) {
+            Ok(Some(account_id)) => self.gc_retained_accounts.matches(&account_id),
+            // Delayed receipts and their indices don't belong to any account.
+            Ok(None) => false,
+            Err(err) => {
+                tracing::error!(target: "chain", ?err, "Can't parse account id from state changes key");
+                false
+            }
+        }
+    }
+
     pub fn clear_chunk_data_and_headers(
         &mut self,
         min_chunk_height: BlockHeight,
@@ -1950,6 +1984,9 @@ impl<'a> ChainStoreUpdate<'a> {
                     self.gc_col(DBCol::Transactions, transaction.get_hash().as_bytes());
                 }
                 for receipt in chunk.receipts() {
+                    if self.is_retained_receipt(receipt) {
+                        continue;
+                    }
                     self.gc_col(DBCol::Receipts, receipt.get_hash().as_bytes());
                 }
 
@@ -2146,6 +2183,9 @@ impl<'a> ChainStoreUpdate<'a> {
             .map(|item| item.map(|(key, _)| key))
             .collect::<io::Result<Vec<_>>>()?;
         for key in stored_state_changes {
+            if self.is_retained_state_change(&key) {
+                continue;
+            }
             self.gc_col(DBCol::StateChanges, &key);
         }
         self.gc_col(DBCol::BlockRefCount, block_hash.as_bytes());
@@ -2270,10 +2310,19 @@ impl<'a> ChainStoreUpdate<'a> {
             let outcome_ids =
                 self.chain_store.get_outcomes_by_block_hash_and_shard_id(block_hash, shard_id)?;
             for outcome_id in outcome_ids {
-                self.gc_col(
-                    DBCol::TransactionResultForBlock,
-                    &get_outcome_id_block_hash(&outcome_id, block_hash),
-                );
+                let key = get_outcome_id_block_hash(&outcome_id, block_hash);
+                if !self.gc_retained_accounts.is_empty() {
+                    let outcome = self.store().get_ser::<ExecutionOutcomeWithProof>(
+                        DBCol::TransactionResultForBlock,
+                        &key,
+                    )?;
+                    if let Some(outcome) = outcome {
+                        if self.gc_retained_accounts.matches(&outcome.outcome.executor_id) {
+                            continue;
+                        }
+                    }
+                }
+                self.gc_col(DBCol::TransactionResultForBlock, &key);
             }
             self.gc_col(DBCol::OutcomeIds, &get_block_shard_id(block_hash, shard_id));
         }
@@ -2398,6 +2447,7 @@ impl<'a> ChainStoreUpdate<'a> {
             | DBCol::PeerComponent
             | DBCol::LastComponentNonce
             | DBCol::ComponentEdges
+            | DBCol::RoutingTableSnapshot
             // https://github.com/nearprotocol/nearcore/pull/2952
             | DBCol::EpochInfo
             | DBCol::EpochStart
@@ -2975,17 +3025,18 @@ mod tests {
 
     use near_primitives::merkle::PartialMerkleTree;
 
-    use near_chain_configs::{GCConfig, GenesisConfig};
+    use near_chain_configs::{AccountRetentionFilter, GCConfig, GenesisConfig};
     use near_crypto::KeyType;
     use near_primitives::block::{Block, Tip};
     use near_primitives::epoch_manager::block_info::BlockInfo;
     use near_primitives::errors::InvalidTxError;
     use near_primitives::hash::hash;
+    use near_primitives::trie_key::TrieKey;
     use near_primitives::types::{BlockHeight, EpochId, NumBlocks};
     use near_primitives::utils::index_to_bytes;
     use near_primitives::validator_signer::InMemoryValidatorSigner;
     use near_store::test_utils::create_test_store;
-    use near_store::DBCol;
+    use near_store::{DBCol, KeyForStateChanges};
 
     use crate::store::{ChainStoreAccess, GCMode};
     use crate::store_validator::StoreValidator;
@@ -3262,6 +3313,68 @@ mod tests {
         }
     }
 
+    /// Test that garbage collection keeps state changes of the accounts
+    /// configured to be retained and removes all the others.
+    #[test]
+    fn test_clear_old_data_retained_accounts() {
+        let mut chain = get_chain_with_epoch_length(1);
+        let runtime_adapter = chain.runtime_adapter.clone();
+        let genesis = chain.get_block_by_height(0).unwrap();
+        let signer = Arc::new(InMemoryValidatorSigner::from_seed(
+            "test1".parse().unwrap(),
+            KeyType::ED25519,
+            "test1",
+        ));
+        let mut prev_block = genesis;
+        let mut blocks = vec![prev_block.clone()];
+        for i in 1..15 {
+            add_block(
+                &mut chain,
+                runtime_adapter.clone(),
+                &mut prev_block,
+                &mut blocks,
+                signer.clone(),
+                i,
+            );
+        }
+
+        let state_changes_key = |block: &Block, account_id: &str| -> Vec<u8> {
+            let trie_key = TrieKey::Account { account_id: account_id.parse().unwrap() };
+            KeyForStateChanges::from_trie_key(block.hash(), &trie_key).into()
+        };
+        let keys: Vec<(Vec<u8>, bool)> = blocks[1..8]
+            .iter()
+            .flat_map(|block| {
+                vec![
+                    (state_changes_key(block, "app.near"), true),
+                    (state_changes_key(block, "aurora.pool.near"), true),
+                    (state_changes_key(block, "other.near"), false),
+                ]
+            })
+            .collect();
+        let store = chain.store().store().clone();
+        let mut store_update = store.store_update();
+        for (key, _) in &keys {
+            store_update.set(DBCol::StateChanges, key, &[]);
+        }
+        store_update.commit().unwrap();
+
+        let retained_accounts =
+            AccountRetentionFilter::new(vec!["app.near".to_string(), "aurora*".to_string()]);
+        let trie = chain.runtime_adapter.get_tries();
+        chain
+            .clear_data(
+                trie,
+                &GCConfig { gc_blocks_limit: 100, retained_accounts, ..GCConfig::default() },
+            )
+            .unwrap();
+
+        for (key, is_retained) in keys {
+            assert_eq!(store.exists(DBCol::StateChanges, &key).unwrap(), is_retained);
+        }
+        assert!(chain.get_block(blocks[7].hash()).is_err());
+    }
+
     // Adds block to the chain at given height after prev_block.
     fn add_block(
         chain: &mut Chain,
@@ -0,0 +1,120 @@
+//! Background validation of the database.
+//!
+//! [`StoreConsistencyChecker`] runs [`StoreValidator::validate_heights`] on the
+//! client's background thread pool, a few heights at a time, walking the
+//! canonical chain from tail to final head over and over again.  Unlike the
+//! full validation used by tests, violations never stop the node: they are
+//! logged and exported as metrics.
+
+use crate::metrics;
+use crate::StoreValidator;
+use near_chain_configs::StoreConsistencyCheckConfig;
+use near_primitives::types::{BlockHeight, BlockHeightDelta};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+
+struct CheckerState {
+    validator: StoreValidator,
+    /// Height the next step starts from.
+    next_height: BlockHeight,
+}
+
+impl CheckerState {
+    fn step(&mut self, heights_per_step: BlockHeightDelta) {
+        let start_height = self.next_height;
+        self.next_height = self.validator.validate_heights(start_height, heights_per_step);
+        debug!(target: "chain", start_height, next_height = self.next_height, tests = self.validator.tests_done(), "Store consistency check step done");
+        metrics::STORE_CONSISTENCY_CHECK_HEIGHT.set(self.next_height as i64);
+        for error in self.validator.errors.iter() {
+            warn!(target: "chain", col = %error.col, key = %error.key, err = %error.err, "Store consistency violation");
+            metrics::STORE_CONSISTENCY_CHECK_ERRORS.with_label_values(&[&error.col]).inc();
+        }
+    }
+}
+
+/// Validates the database incrementally on a thread pool shared with other
+/// background work of the client.
+pub struct StoreConsistencyChecker {
+    state: Arc<Mutex<CheckerState>>,
+    heights_per_step: BlockHeightDelta,
+}
+
+impl StoreConsistencyChecker {
+    pub fn new(validator: StoreValidator, config: &StoreConsistencyCheckConfig) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CheckerState { validator, next_height: 0 })),
+            heights_per_step: config.heights_per_step,
+        }
+    }
+
+    /// Spawns a single validation step on `pool`.  The client calls this every
+    /// `step_period`.  A step is skipped if the previous one is still running,
+    /// so at most one thread of the pool is ever busy with validation.
+    pub fn schedule_step(&self, pool: &rayon::ThreadPool) {
+        let state = self.state.clone();
+        let heights_per_step = self.heights_per_step;
+        pool.spawn(move || {
+            if let Ok(mut state) = state.try_lock() {
+                state.step(heights_per_step);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_chain_configs::GenesisConfig;
+    use near_primitives::epoch_manager::block_info::BlockInfo;
+    use near_store::DBCol;
+
+    use super::CheckerState;
+    use crate::test_utils::setup;
+    use crate::{Block, ChainStoreAccess, StoreValidator};
+
+    #[test]
+    fn test_validate_heights_incrementally() {
+        let (mut chain, _, signer) = setup();
+        let store = chain.store().store().clone();
+        let mut prev_block = chain.get_block_by_height(0).unwrap();
+        let mut blocks = vec![prev_block.clone()];
+        for _ in 0..5 {
+            let block = Block::empty(&prev_block, &*signer);
+            chain.process_block_test(&None, block.clone()).unwrap();
+            blocks.push(block.clone());
+            prev_block = block;
+        }
+        // KeyValueRuntime doesn't maintain BlockInfo.
+        let mut store_update = store.store_update();
+        for block in blocks.iter() {
+            store_update
+                .insert_ser(DBCol::BlockInfo, block.hash().as_ref(), &BlockInfo::default())
+                .unwrap();
+        }
+        store_update.commit().unwrap();
+        let mut genesis = GenesisConfig::default();
+        genesis.genesis_height = 0;
+        let validator =
+            StoreValidator::new(None, genesis, chain.runtime_adapter.clone(), store.clone(), false);
+        let mut state = CheckerState { validator, next_height: 0 };
+
+        state.step(2);
+        assert_eq!(state.next_height, 2);
+        assert!(state.validator.errors.is_empty(), "{:?}", state.validator.errors);
+        assert!(state.validator.tests_done() > 0);
+
+        // Break the header link of a canonical block.
+        let final_height = chain.final_head().unwrap().height;
+        let hash = *chain.get_block_by_height(2).unwrap().hash();
+        let mut store_update = store.store_update();
+        store_update.delete(DBCol::BlockHeader, hash.as_ref());
+        store_update.commit().unwrap();
+
+        state.step(100);
+        assert_eq!(state.next_height, final_height + 1);
+        assert!(!state.validator.errors.is_empty());
+
+        // Once final head is reached the walk starts over from tail.
+        state.step(1);
+        assert_eq!(state.next_height, 1);
+    }
+}
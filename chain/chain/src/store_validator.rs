@@ -7,21 +7,24 @@ use enum_map::Enum;
 use strum::IntoEnumIterator;
 use tracing::warn;
 
-use near_chain_configs::GenesisConfig;
-use near_primitives::block::{Block, BlockHeader};
+use near_chain_configs::{AccountRetentionFilter, GenesisConfig};
+use near_primitives::block::{Block, BlockHeader, Tip};
 use near_primitives::borsh;
 use near_primitives::epoch_manager::block_info::BlockInfo;
 use near_primitives::epoch_manager::epoch_info::EpochInfo;
 use near_primitives::epoch_manager::AGGREGATOR_KEY;
 use near_primitives::hash::CryptoHash;
+use near_primitives::receipt::Receipt;
 use near_primitives::sharding::{ChunkHash, ShardChunk, StateSyncInfo};
 use near_primitives::syncing::{ShardStateSyncResponseHeader, StateHeaderKey, StatePartKey};
 use near_primitives::transaction::ExecutionOutcomeWithProof;
 use near_primitives::types::chunk_extra::ChunkExtra;
 use near_primitives::types::{AccountId, BlockHeight, EpochId};
-use near_primitives::utils::{get_block_shard_id_rev, get_outcome_id_block_hash_rev};
+use near_primitives::utils::{
+    get_block_shard_id_rev, get_outcome_id_block_hash_rev, index_to_bytes,
+};
 use near_store::db::refcount;
-use near_store::{DBCol, Store, TrieChanges};
+use near_store::{DBCol, Store, TrieChanges, FINAL_HEAD_KEY, TAIL_KEY};
 use validate::StoreValidatorError;
 
 use crate::RuntimeAdapter;
@@ -75,6 +78,9 @@ pub struct StoreValidator {
     timeout: Option<u64>,
     start_time: Instant,
     pub is_archival: bool,
+    /// Accounts whose history is kept by a partial archive after the
+    /// rest of the data has been garbage collected.
+    retained_accounts: AccountRetentionFilter,
 
     pub errors: Vec<ErrorMessage>,
     tests: u64,
@@ -97,6 +103,7 @@ impl StoreValidator {
             timeout: None,
             start_time: Clock::instant(),
             is_archival,
+            retained_accounts: AccountRetentionFilter::default(),
             errors: vec![],
             tests: 0,
         }
@@ -104,6 +111,9 @@ impl StoreValidator {
     pub fn set_timeout(&mut self, timeout: u64) {
         self.timeout = Some(timeout)
     }
+    pub fn set_retained_accounts(&mut self, retained_accounts: AccountRetentionFilter) {
+        self.retained_accounts = retained_accounts
+    }
     pub fn is_failed(&self) -> bool {
         self.tests == 0 || self.errors.len() > 0
     }
@@ -285,9 +295,20 @@ impl StoreValidator {
                     self.check(&validate::tx_refcount, &tx_hash, &(rc as u64), col);
                 }
                 DBCol::Receipts => {
-                    let (_value, rc) = refcount::decode_value_with_rc(value_ref);
+                    let (value, rc) = refcount::decode_value_with_rc(value_ref);
                     let receipt_id = CryptoHash::try_from(key_ref)?;
-                    self.check(&validate::receipt_refcount, &receipt_id, &(rc as u64), col);
+                    if self.retained_accounts.is_empty() {
+                        self.check(&validate::receipt_refcount, &receipt_id, &(rc as u64), col);
+                    } else {
+                        let receipt = value.map(Receipt::try_from_slice).transpose()?;
+                        // Receipt is either referenced by existing Chunks or retained by a partial archive
+                        self.check(
+                            &validate::retained_receipt_refcount,
+                            &receipt_id,
+                            &(rc as u64, receipt),
+                            col,
+                        );
+                    }
                 }
                 DBCol::BlockRefCount => {
                     let block_hash = CryptoHash::try_from(key_ref)?;
@@ -361,6 +382,159 @@ impl StoreValidator {
         }
     }
 
+    /// Validates up to `max_heights` heights of the canonical chain starting
+    /// at `from`, and returns the height to continue from on the next call.
+    ///
+    /// Unlike [`Self::validate`], which scans entire columns, this only looks
+    /// at data reachable from the given heights: canonical header links,
+    /// blocks, their chunks and the transactions and receipts those chunks
+    /// reference, including whether refcounts of the deduplicated transactions
+    /// and receipts cover all the references seen.  This makes it cheap enough to run periodically on a live
+    /// node.  Only heights between tail and final head are validated; once
+    /// final head is reached, validation starts over from tail.
+    ///
+    /// Errors found at heights which got garbage collected while validation
+    /// was in progress are discarded.  Remaining errors are stored in
+    /// [`Self::errors`], which is cleared at the beginning of each call.
+    pub fn validate_heights(&mut self, from: BlockHeight, max_heights: u64) -> BlockHeight {
+        self.start_time = Clock::instant();
+        self.inner = StoreValidatorCache::new();
+        self.errors.clear();
+        if let Err(e) = validate::head_tail_validity(self) {
+            self.process_error(e, "HEAD / HEADER_HEAD / TAIL / CHUNK_TAIL", DBCol::BlockMisc);
+            return from;
+        }
+        let final_height = match self.store.get_ser::<Tip>(DBCol::BlockMisc, FINAL_HEAD_KEY) {
+            Ok(Some(final_head)) => final_head.height,
+            Ok(None) => return from,
+            Err(e) => {
+                self.process_error(e.into(), "FINAL_HEAD", DBCol::BlockMisc);
+                return from;
+            }
+        };
+        let tail = self.inner.tail;
+        let mut start = std::cmp::max(from, tail);
+        if start > final_height {
+            start = tail;
+        }
+        let end = std::cmp::min(start.saturating_add(max_heights), final_height + 1);
+
+        let mut errors_by_height = Vec::new();
+        for height in start..end {
+            if let Err(e) = self.validate_height(height) {
+                self.process_error(e, height, DBCol::BlockHeight);
+            }
+            errors_by_height.push((height, std::mem::take(&mut self.errors)));
+        }
+
+        let tail = match self.store.get_ser::<BlockHeight>(DBCol::BlockMisc, TAIL_KEY) {
+            Ok(tail) => tail.unwrap_or(self.config.genesis_height),
+            Err(e) => {
+                self.process_error(e.into(), "TAIL", DBCol::BlockMisc);
+                return end;
+            }
+        };
+        for (height, errors) in errors_by_height {
+            if height >= tail {
+                self.errors.extend(errors);
+            }
+        }
+        end
+    }
+
+    fn validate_height(&mut self, height: BlockHeight) -> Result<(), StoreValidatorError> {
+        let block_hash =
+            match self.store.get_ser::<CryptoHash>(DBCol::BlockHeight, &index_to_bytes(height))? {
+                Some(block_hash) => block_hash,
+                // Height was skipped on the Canonical Chain
+                None => return Ok(()),
+            };
+        let col = DBCol::BlockHeight;
+        // Block on the Canonical Chain is stored properly
+        self.check(&validate::canonical_header_validity, &height, &block_hash, col);
+        // If prev Block exists, it's also on the Canonical Chain and
+        // there are no Blocks in range (prev_height, height) on the Canonical Chain
+        self.check(&validate::canonical_prev_block_validity, &height, &block_hash, col);
+
+        let header =
+            match self.store.get_ser::<BlockHeader>(DBCol::BlockHeader, block_hash.as_ref())? {
+                Some(header) => header,
+                // Already reported by canonical_header_validity
+                None => return Ok(()),
+            };
+        let col = DBCol::BlockHeader;
+        // Block Header Hash is valid
+        self.check(&validate::block_header_hash_validity, &block_hash, &header, col);
+        // Block Header Height is valid
+        self.check(&validate::block_header_height_validity, &block_hash, &header, col);
+
+        let block = match self.store.get_ser::<Block>(DBCol::Block, block_hash.as_ref())? {
+            Some(block) => block,
+            // Only the Header is known, e.g. the node has been state synced past it
+            None => return Ok(()),
+        };
+        let col = DBCol::Block;
+        // Block Hash is valid
+        self.check(&validate::block_hash_validity, &block_hash, &block, col);
+        // Block Height is valid
+        self.check(&validate::block_height_validity, &block_hash, &block, col);
+        // Block can be indexed by its Height
+        self.check(&validate::block_indexed_by_height, &block_hash, &block, col);
+        // Chunks for current Block exist
+        self.check(&validate::block_chunks_exist, &block_hash, &block, col);
+        // Chunks for current Block have Height Created not higher than Block Height
+        self.check(&validate::block_chunks_height_validity, &block_hash, &block, col);
+        // BlockInfo for current Block exists
+        self.check(&validate::block_info_exists, &block_hash, &block, col);
+        // Prev Block is referenced by current Block
+        self.check(&validate::block_prev_refcount_exists, &block_hash, &block, col);
+
+        let col = DBCol::Chunks;
+        for chunk_header in block.chunks().iter() {
+            if chunk_header.height_included() != height {
+                continue;
+            }
+            let chunk_hash = chunk_header.chunk_hash();
+            let shard_chunk =
+                match self.store.get_ser::<ShardChunk>(DBCol::Chunks, chunk_hash.as_ref())? {
+                    Some(shard_chunk) => shard_chunk,
+                    // Reported by block_chunks_exist if the node tracks the shard
+                    None => continue,
+                };
+            // Chunk Hash is valid
+            self.check(&validate::chunk_hash_validity, &chunk_hash, &shard_chunk, col);
+            // Chunk Height Created is not lower than Chunk Tail
+            self.check(&validate::chunk_tail_validity, &chunk_hash, &shard_chunk, col);
+            // Check that all Txs in Chunk exist
+            self.check(&validate::chunk_tx_exists, &chunk_hash, &shard_chunk, col);
+            // Check that all Receipts in Chunk are referenced
+            self.check(&validate::chunk_receipts_exist, &chunk_hash, &shard_chunk, col);
+        }
+
+        // chunk_tx_exists counted references to Txs and Receipts from Chunks
+        // included at this height.  Other references may be outside of the
+        // validated range, so refcounts are only checked to cover these ones.
+        let tx_refcount = std::mem::take(&mut self.inner.tx_refcount);
+        for (tx_hash, references) in tx_refcount.iter() {
+            self.check(
+                &validate::tx_refcount_covers_references,
+                tx_hash,
+                references,
+                DBCol::Transactions,
+            );
+        }
+        let receipt_refcount = std::mem::take(&mut self.inner.receipt_refcount);
+        for (receipt_id, references) in receipt_refcount.iter() {
+            self.check(
+                &validate::receipt_refcount_covers_references,
+                receipt_id,
+                references,
+                DBCol::Receipts,
+            );
+        }
+        Ok(())
+    }
+
     fn check<K: std::fmt::Debug + ?Sized, V: ?Sized>(
         &mut self,
         f: &dyn Fn(&mut StoreValidator, &K, &V) -> Result<(), StoreValidatorError>,
@@ -457,6 +631,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_tx_refcount_covers_references() {
+        let (chain, mut sv) = init();
+        let tx_hash = CryptoHash::hash_bytes(b"tx");
+        let mut store_update = chain.store().store().store_update();
+        store_update.increment_refcount_by(
+            DBCol::Transactions,
+            tx_hash.as_ref(),
+            b"tx",
+            std::num::NonZeroU32::new(2).unwrap(),
+        );
+        store_update.commit().unwrap();
+        // Tx included in two Chunks is stored once with refcount two
+        assert!(validate::tx_refcount_covers_references(&mut sv, &tx_hash, &2).is_ok());
+        match validate::tx_refcount_covers_references(&mut sv, &tx_hash, &3) {
+            Err(StoreValidatorError::ValidationFailed { .. }) => {}
+            _ => assert!(false),
+        }
+        match validate::tx_refcount_covers_references(&mut sv, &CryptoHash::default(), &1) {
+            Err(StoreValidatorError::ValidationFailed { .. }) => {}
+            _ => assert!(false),
+        }
+    }
+
     #[test]
     fn test_validation_failed() {
         let (_chain, mut sv) = init();
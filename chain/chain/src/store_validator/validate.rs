@@ -7,6 +7,7 @@ use near_primitives::block::{Block, BlockHeader, Tip};
 use near_primitives::epoch_manager::block_info::BlockInfo;
 use near_primitives::epoch_manager::epoch_info::EpochInfo;
 use near_primitives::hash::CryptoHash;
+use near_primitives::receipt::Receipt;
 use near_primitives::sharding::{ChunkHash, ShardChunk, StateSyncInfo};
 use near_primitives::syncing::{
     get_num_state_parts, ShardStateSyncResponseHeader, StateHeaderKey, StatePartKey,
@@ -15,6 +16,7 @@ use near_primitives::transaction::{ExecutionOutcomeWithProof, SignedTransaction}
 use near_primitives::types::chunk_extra::ChunkExtra;
 use near_primitives::types::{BlockHeight, EpochId};
 use near_primitives::utils::{get_block_shard_id, get_outcome_id_block_hash, index_to_bytes};
+use near_store::db::refcount;
 use near_store::{
     DBCol, TrieChanges, CHUNK_TAIL_KEY, FORK_TAIL_KEY, HEADER_HEAD_KEY, HEAD_KEY, TAIL_KEY,
 };
@@ -411,6 +413,69 @@ pub(crate) fn block_chunks_exist(
     Ok(())
 }
 
+pub(crate) fn chunk_receipts_exist(
+    sv: &mut StoreValidator,
+    _chunk_hash: &ChunkHash,
+    shard_chunk: &ShardChunk,
+) -> Result<(), StoreValidatorError> {
+    for receipt in shard_chunk.receipts().iter() {
+        let receipt_id = receipt.get_hash();
+        // Receipts with non-positive refcount aren't returned by the Store
+        unwrap_or_err_db!(
+            sv.store.get_ser::<Receipt>(DBCol::Receipts, receipt_id.as_ref()),
+            "Can't get Receipt from storage for Receipt Id {:?}",
+            receipt_id
+        );
+    }
+    Ok(())
+}
+
+/// Returns reference count of an entry of a refcounted column.
+fn get_refcount(
+    sv: &StoreValidator,
+    col: DBCol,
+    key: &CryptoHash,
+) -> Result<i64, StoreValidatorError> {
+    let value = sv.store.get_raw_bytes(col, key.as_ref())?;
+    Ok(value.map_or(0, |value| refcount::decode_value_with_rc(&value).1))
+}
+
+pub(crate) fn tx_refcount_covers_references(
+    sv: &mut StoreValidator,
+    tx_hash: &CryptoHash,
+    references: &u64,
+) -> Result<(), StoreValidatorError> {
+    // Tx included in several Chunks is stored once, with refcount covering all of them
+    let refcount = get_refcount(sv, DBCol::Transactions, tx_hash)?;
+    if refcount < *references as i64 {
+        err!(
+            "Tx {:?} is referenced by {:?} Chunks but its refcount is {:?}",
+            tx_hash,
+            references,
+            refcount
+        );
+    }
+    Ok(())
+}
+
+pub(crate) fn receipt_refcount_covers_references(
+    sv: &mut StoreValidator,
+    receipt_id: &CryptoHash,
+    references: &u64,
+) -> Result<(), StoreValidatorError> {
+    // Receipt included in several Chunks is stored once, with refcount covering all of them
+    let refcount = get_refcount(sv, DBCol::Receipts, receipt_id)?;
+    if refcount < *references as i64 {
+        err!(
+            "Receipt {:?} is referenced by {:?} Chunks but its refcount is {:?}",
+            receipt_id,
+            references,
+            refcount
+        );
+    }
+    Ok(())
+}
+
 pub(crate) fn block_chunks_height_validity(
     _sv: &mut StoreValidator,
     _block_hash: &CryptoHash,
@@ -467,6 +532,28 @@ pub(crate) fn block_increment_refcount(
     Ok(())
 }
 
+pub(crate) fn block_prev_refcount_exists(
+    sv: &mut StoreValidator,
+    _block_hash: &CryptoHash,
+    block: &Block,
+) -> Result<(), StoreValidatorError> {
+    if block.header().height() != sv.config.genesis_height {
+        let prev_hash = block.header().prev_hash();
+        // Refcount is garbage collected together with the Block
+        if sv.store.exists(DBCol::Block, prev_hash.as_ref())? {
+            let refcount = unwrap_or_err_db!(
+                sv.store.get_ser::<u64>(DBCol::BlockRefCount, prev_hash.as_ref()),
+                "Can't get Block Refcount for prev Block {:?} from storage",
+                prev_hash
+            );
+            if refcount == 0 {
+                err!("Prev Block {:?} is referenced but its refcount is zero", prev_hash);
+            }
+        }
+    }
+    Ok(())
+}
+
 pub(crate) fn canonical_header_validity(
     sv: &mut StoreValidator,
     height: &BlockHeight,
@@ -685,8 +772,19 @@ pub(crate) fn outcome_id_block_exists(
 pub(crate) fn outcome_indexed_by_block_hash(
     sv: &mut StoreValidator,
     (outcome_id, block_hash): &(CryptoHash, CryptoHash),
-    _outcome: &ExecutionOutcomeWithProof,
+    outcome: &ExecutionOutcomeWithProof,
 ) -> Result<(), StoreValidatorError> {
+    if sv.retained_accounts.matches(&outcome.outcome.executor_id) {
+        let block_exists = unwrap_or_err!(
+            sv.store.exists(DBCol::Block, block_hash.as_ref()),
+            "Can't check Block {} existence",
+            block_hash
+        );
+        if !block_exists {
+            // Outcome is retained by a partial archive after its Block is garbage collected
+            return Ok(());
+        }
+    }
     let block = unwrap_or_err_db!(
         sv.store.get_ser::<Block>(DBCol::Block, block_hash.as_ref()),
         "Can't get Block {} from DB",
@@ -813,6 +911,25 @@ pub(crate) fn receipt_refcount(
     }
 }
 
+pub(crate) fn retained_receipt_refcount(
+    sv: &mut StoreValidator,
+    receipt_id: &CryptoHash,
+    (refcount, receipt): &(u64, Option<Receipt>),
+) -> Result<(), StoreValidatorError> {
+    let expected = sv.inner.receipt_refcount.get(receipt_id).map(|&rc| rc).unwrap_or_default();
+    if let Some(receipt) = receipt {
+        let is_retained = sv.retained_accounts.matches(&receipt.receiver_id)
+            || sv.retained_accounts.matches(&receipt.predecessor_id);
+        // Garbage collection doesn't decrement refcounts of retained receipts
+        // so they may stay referenced by Chunks which no longer exist.
+        if is_retained && *refcount > expected {
+            sv.inner.receipt_refcount.remove(receipt_id);
+            return Ok(());
+        }
+    }
+    receipt_refcount(sv, receipt_id, refcount)
+}
+
 pub(crate) fn block_refcount(
     sv: &mut StoreValidator,
     block_hash: &CryptoHash,
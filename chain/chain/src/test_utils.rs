@@ -12,7 +12,9 @@ use near_primitives::state_part::PartId;
 use num_rational::Ratio;
 use tracing::debug;
 
-use near_chain_configs::{ProtocolConfig, DEFAULT_GC_NUM_EPOCHS_TO_KEEP};
+use near_chain_configs::{
+    ProtocolConfig, SimulateTransactionConfig, DEFAULT_GC_NUM_EPOCHS_TO_KEEP,
+};
 use near_chain_primitives::Error;
 use near_client_primitives::types::StateSplitApplyingStatus;
 use near_crypto::{KeyType, PublicKey, SecretKey, Signature};
@@ -21,6 +23,7 @@ use near_primitives::account::{AccessKey, Account};
 use near_primitives::block::Block;
 use near_primitives::block_header::{Approval, ApprovalInner};
 use near_primitives::challenge::ChallengesResult;
+use near_primitives::congestion_info::{BlockCongestionInfo, CongestionInfo};
 use near_primitives::epoch_manager::block_info::BlockInfo;
 use near_primitives::epoch_manager::epoch_info::EpochInfo;
 use near_primitives::epoch_manager::EpochConfig;
@@ -57,7 +60,7 @@ use crate::chain::Chain;
 use crate::store::ChainStoreAccess;
 use crate::types::{
     AcceptedBlock, ApplySplitStateResult, ApplyTransactionResult, BlockHeaderInfo, ChainConfig,
-    ChainGenesis,
+    ChainGenesis, SimulateTransactionResult,
 };
 use crate::{BlockHeader, DoomslugThresholdMode, RuntimeAdapter};
 use crate::{BlockProcessingArtifact, Provenance};
@@ -880,6 +883,15 @@ impl RuntimeAdapter for KeyValueRuntime {
         ))
     }
 
+    fn get_congestion_info(
+        &self,
+        _shard_id: ShardId,
+        _prev_hash: &CryptoHash,
+        _state_root: StateRoot,
+    ) -> Result<CongestionInfo, Error> {
+        Ok(CongestionInfo::default())
+    }
+
     fn get_flat_storage_state_for_shard(&self, _shard_id: ShardId) -> Option<FlatStorageState> {
         None
     }
@@ -972,6 +984,7 @@ impl RuntimeAdapter for KeyValueRuntime {
         _shard_id: ShardId,
         _state_root: StateRoot,
         _next_block_height: BlockHeight,
+        _prev_block_timestamp: u64,
         transactions: &mut dyn PoolIterator,
         _chain_validate: &mut dyn FnMut(&SignedTransaction) -> bool,
         _current_protocol_version: ProtocolVersion,
@@ -1004,6 +1017,7 @@ impl RuntimeAdapter for KeyValueRuntime {
         gas_price: Balance,
         _gas_limit: Gas,
         _challenges: &ChallengesResult,
+        _congestion_info: &BlockCongestionInfo,
         _random_seed: CryptoHash,
         generate_storage_proof: bool,
         _is_new_chunk: bool,
@@ -1134,6 +1148,7 @@ impl RuntimeAdapter for KeyValueRuntime {
                         logs: vec![],
                         receipt_ids: new_receipt_hashes,
                         gas_burnt: 0,
+                        compute_usage: Some(0),
                         tokens_burnt: 0,
                         executor_id: to.clone(),
                         metadata: ExecutionMetadata::V1,
@@ -1182,6 +1197,7 @@ impl RuntimeAdapter for KeyValueRuntime {
         _gas_price: Balance,
         _gas_limit: Gas,
         _challenges: &ChallengesResult,
+        _congestion_info: &BlockCongestionInfo,
         _random_value: CryptoHash,
         _is_new_chunk: bool,
         _is_first_block_with_chunk_of_version: bool,
@@ -1369,6 +1385,23 @@ impl RuntimeAdapter for KeyValueRuntime {
         Ok(false)
     }
 
+    fn simulate_transaction(
+        &self,
+        _state_roots: &HashMap<ShardUId, StateRoot>,
+        _block_height: BlockHeight,
+        _block_timestamp: u64,
+        _prev_block_hash: &CryptoHash,
+        _block_hash: &CryptoHash,
+        _epoch_id: &EpochId,
+        _gas_price: Balance,
+        _random_seed: CryptoHash,
+        _transaction: &SignedTransaction,
+        _verify_signature: bool,
+        _limits: &SimulateTransactionConfig,
+    ) -> Result<Result<SimulateTransactionResult, InvalidTxError>, Error> {
+        unreachable!("simulate_transaction should not be called in KeyValueRuntime");
+    }
+
     fn get_protocol_config(&self, _epoch_id: &EpochId) -> Result<ProtocolConfig, Error> {
         unreachable!("get_protocol_config should not be called in KeyValueRuntime");
     }
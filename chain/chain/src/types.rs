@@ -8,12 +8,13 @@ use near_primitives::time::Utc;
 use num_rational::Rational32;
 
 use crate::metrics;
-use near_chain_configs::{Genesis, ProtocolConfig};
+use near_chain_configs::{Genesis, ProtocolConfig, SimulateTransactionConfig};
 use near_chain_primitives::Error;
 use near_client_primitives::types::StateSplitApplyingStatus;
 use near_pool::types::PoolIterator;
 use near_primitives::challenge::{ChallengesResult, SlashedValidator};
 use near_primitives::checked_feature;
+use near_primitives::congestion_info::{BlockCongestionInfo, CongestionInfo};
 use near_primitives::errors::InvalidTxError;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{merklize, MerklePath};
@@ -106,6 +107,19 @@ pub struct ApplyTransactionResult {
     pub processed_delayed_receipts: Vec<Receipt>,
 }
 
+/// Outcomes and receipts of a transaction applied by
+/// [`RuntimeAdapter::simulate_transaction`].
+pub struct SimulateTransactionResult {
+    /// Outcome of the transaction followed by the outcomes of all the receipts
+    /// it produced, in the order they were executed.
+    pub outcomes: Vec<ExecutionOutcomeWithId>,
+    /// All the receipts produced by the transaction.
+    pub receipts: Vec<Receipt>,
+    /// Description of the limit which stopped the simulation before all the
+    /// receipts were processed, if any.
+    pub limit_exceeded: Option<String>,
+}
+
 impl ApplyTransactionResult {
     /// Returns root and paths for all the outcomes in the result.
     pub fn compute_outcomes_proof(
@@ -297,6 +311,15 @@ pub trait RuntimeAdapter: EpochManagerAdapter + Send + Sync {
         state_root: StateRoot,
     ) -> Result<Trie, Error>;
 
+    /// Returns the congestion info stored in the state of the shard, which is
+    /// published in the header of the next chunk.
+    fn get_congestion_info(
+        &self,
+        shard_id: ShardId,
+        prev_hash: &CryptoHash,
+        state_root: StateRoot,
+    ) -> Result<CongestionInfo, Error>;
+
     fn get_flat_storage_state_for_shard(&self, shard_id: ShardId) -> Option<FlatStorageState>;
 
     /// Tries to create flat storage state for given shard, returns the status of creation.
@@ -335,6 +358,8 @@ pub trait RuntimeAdapter: EpochManagerAdapter + Send + Sync {
     /// against the given `chain_validate` closure and runtime's transaction verifier.
     /// If the transaction is valid for both, it's added to the result and the temporary state
     /// update is preserved for validation of next transactions.
+    /// Access key expiries are checked against `next_block_height` and `prev_block_timestamp`
+    /// since the block that will include the transactions doesn't exist yet.
     /// Throws an `Error` with `ErrorKind::StorageError` in case the runtime throws
     /// `RuntimeError::StorageError`.
     fn prepare_transactions(
@@ -345,6 +370,7 @@ pub trait RuntimeAdapter: EpochManagerAdapter + Send + Sync {
         shard_id: ShardId,
         state_root: StateRoot,
         next_block_height: BlockHeight,
+        prev_block_timestamp: u64,
         pool_iterator: &mut dyn PoolIterator,
         chain_validate: &mut dyn FnMut(&SignedTransaction) -> bool,
         current_protocol_version: ProtocolVersion,
@@ -410,6 +436,7 @@ pub trait RuntimeAdapter: EpochManagerAdapter + Send + Sync {
         gas_price: Balance,
         gas_limit: Gas,
         challenges_result: &ChallengesResult,
+        congestion_info: &BlockCongestionInfo,
         random_seed: CryptoHash,
         is_new_chunk: bool,
         is_first_block_with_chunk_of_version: bool,
@@ -436,6 +463,7 @@ pub trait RuntimeAdapter: EpochManagerAdapter + Send + Sync {
             gas_price,
             gas_limit,
             challenges_result,
+            congestion_info,
             random_seed,
             false,
             is_new_chunk,
@@ -459,6 +487,7 @@ pub trait RuntimeAdapter: EpochManagerAdapter + Send + Sync {
         gas_price: Balance,
         gas_limit: Gas,
         challenges_result: &ChallengesResult,
+        congestion_info: &BlockCongestionInfo,
         random_seed: CryptoHash,
         generate_storage_proof: bool,
         is_new_chunk: bool,
@@ -482,6 +511,7 @@ pub trait RuntimeAdapter: EpochManagerAdapter + Send + Sync {
         gas_price: Balance,
         gas_limit: Gas,
         challenges_result: &ChallengesResult,
+        congestion_info: &BlockCongestionInfo,
         random_value: CryptoHash,
         is_new_chunk: bool,
         is_first_block_with_chunk_of_version: bool,
@@ -500,6 +530,27 @@ pub trait RuntimeAdapter: EpochManagerAdapter + Send + Sync {
         request: &QueryRequest,
     ) -> Result<QueryResponse, near_chain_primitives::error::QueryError>;
 
+    /// Applies the transaction and all the receipts it produces on top of the
+    /// state after the given block without committing any changes.  The
+    /// transaction is applied as if it was included in the block following the
+    /// one at `block_height`.  `state_roots` must contain the state roots of all
+    /// the shards touched by the transaction.  Returns `Ok(Err(..))` if the
+    /// transaction is invalid.
+    fn simulate_transaction(
+        &self,
+        state_roots: &HashMap<ShardUId, StateRoot>,
+        block_height: BlockHeight,
+        block_timestamp: u64,
+        prev_block_hash: &CryptoHash,
+        block_hash: &CryptoHash,
+        epoch_id: &EpochId,
+        gas_price: Balance,
+        random_seed: CryptoHash,
+        transaction: &SignedTransaction,
+        verify_signature: bool,
+        limits: &SimulateTransactionConfig,
+    ) -> Result<Result<SimulateTransactionResult, InvalidTxError>, Error>;
+
     /// Get the part of the state from given state root.
     /// `block_hash` is a block whose `prev_state_root` is `state_root`
     fn obtain_state_part(
@@ -636,6 +687,7 @@ mod tests {
                 logs: vec!["outcome1".to_string()],
                 receipt_ids: vec![hash(&[1])],
                 gas_burnt: 100,
+                compute_usage: Some(200),
                 tokens_burnt: 10000,
                 executor_id: "alice".parse().unwrap(),
                 metadata: ExecutionMetadata::V1,
@@ -648,6 +700,7 @@ mod tests {
                 logs: vec!["outcome2".to_string()],
                 receipt_ids: vec![],
                 gas_burnt: 0,
+                compute_usage: Some(0),
                 tokens_burnt: 0,
                 executor_id: "bob".parse().unwrap(),
                 metadata: ExecutionMetadata::V1,
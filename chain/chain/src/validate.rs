@@ -154,6 +154,17 @@ pub fn validate_chunk_with_chunk_extra(
         return Err(Error::InvalidBalanceBurnt);
     }
 
+    if let Some(congestion_info) = chunk_header.congestion_info() {
+        let expected = runtime_adapter.get_congestion_info(
+            chunk_header.shard_id(),
+            prev_block_hash,
+            *prev_chunk_extra.state_root(),
+        )?;
+        if congestion_info != expected {
+            return Err(Error::InvalidCongestionInfo);
+        }
+    }
+
     let outgoing_receipts = chain_store.get_outgoing_receipts_for_shard(
         runtime_adapter,
         *prev_block_hash,
@@ -4,8 +4,10 @@ use near_primitives::hash::CryptoHash;
 use near_primitives::sharding::{
     ChunkHash, PartialEncodedChunkPart, PartialEncodedChunkV2, ReceiptProof, ShardChunkHeader,
 };
+use near_primitives::time::Clock;
 use near_primitives::types::{BlockHeight, BlockHeightDelta, ShardId};
 use std::collections::hash_map::Entry::Occupied;
+use std::time::Instant;
 use tracing::warn;
 
 // This file implements EncodedChunksCache, which provides three main functionalities:
@@ -46,6 +48,8 @@ pub struct EncodedChunksCacheEntry {
     /// validated again to make sure they are fully validated.
     /// See comments in `validate_chunk_header` for more context on partial vs full validation
     pub header_fully_validated: bool,
+    /// When the entry was created, i.e. when we first received any data of the chunk.
+    pub created: Instant,
 }
 
 pub struct EncodedChunksCache {
@@ -76,6 +80,7 @@ impl EncodedChunksCacheEntry {
             complete: false,
             ready_for_inclusion: false,
             header_fully_validated: false,
+            created: Clock::instant(),
         }
     }
 
@@ -51,6 +51,11 @@
 //! or partial chunk requests. Before that, they are temporarily stored in `chunk_forwards_cache`.
 //! After that, they are processed as a PartialEncodedChunk message only containing one part.
 //!
+//! ** Distributing extra parts
+//! Optionally, chunk producers send redundant parts to part owners which often request their
+//! own parts back, and nodes answer requests for parts they didn't have at the time of the
+//! request as soon as the parts arrive.  See `part_distribution` module for details.
+//!
 //! ** Processing chunks
 //! Function `process_partial_encoded_chunk` processes a partial encoded chunk message.
 //! 1) validates the parts and receipts in the message
@@ -96,8 +101,10 @@ use rand::seq::{IteratorRandom, SliceRandom};
 use tracing::{debug, error, warn};
 
 use near_chain::{byzantine_assert, RuntimeAdapter};
+use near_chain_configs::ChunkDistributionConfig;
 use near_network::types::{NetworkRequests, PeerManagerAdapter, PeerManagerMessageRequest};
 use near_primitives::block::Tip;
+use near_primitives::congestion_info::CongestionInfo;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{verify_path, MerklePath};
 use near_primitives::receipt::Receipt;
@@ -119,6 +126,7 @@ use near_primitives::version::ProtocolVersion;
 
 use crate::chunk_cache::{EncodedChunksCache, EncodedChunksCacheEntry};
 use crate::logic::cares_about_shard_this_or_next_epoch;
+use crate::part_distribution::{PartRequestStats, PendingPartRequests};
 use near_chain::near_chain_primitives::error::Error::DBNotFoundErr;
 pub use near_chunks_primitives::Error;
 use near_network::types::{
@@ -132,6 +140,7 @@ mod chunk_cache;
 pub mod client;
 pub mod logic;
 mod metrics;
+mod part_distribution;
 pub mod test_utils;
 
 const CHUNK_PRODUCER_BLACKLIST_SIZE: usize = 100;
@@ -488,6 +497,10 @@ pub struct ShardsManager {
     chain_head: Option<Tip>,
 
     seals_mgr: SealsManager,
+
+    distribution_config: ChunkDistributionConfig,
+    part_request_stats: PartRequestStats,
+    pending_part_requests: PendingPartRequests,
 }
 
 impl ShardsManager {
@@ -498,6 +511,7 @@ impl ShardsManager {
         client_adapter: Arc<dyn ClientAdapterForShardsManager>,
         store: ReadOnlyChunksStore,
         initial_chain_head: Option<Tip>,
+        distribution_config: ChunkDistributionConfig,
     ) -> Self {
         Self {
             me: me.clone(),
@@ -519,6 +533,9 @@ impl ShardsManager {
             chunk_forwards_cache: lru::LruCache::new(CHUNK_FORWARD_CACHE_SIZE),
             chain_head: initial_chain_head,
             seals_mgr: SealsManager::new(me, runtime_adapter),
+            distribution_config,
+            part_request_stats: PartRequestStats::new(),
+            pending_part_requests: PendingPartRequests::new(),
         }
     }
 
@@ -992,6 +1009,9 @@ impl ShardsManager {
             shards = ?request.tracking_shards,
             account = ?self.me.as_ref());
 
+        self.record_part_request(&request);
+        let pending_request =
+            self.distribution_config.forward_parts_to_requesters.then(|| request.clone());
         let (started, key, response) = self.prepare_partial_encoded_chunk_response(request);
 
         let elapsed = started.elapsed().as_secs_f64();
@@ -1001,17 +1021,154 @@ impl ShardsManager {
             .observe(elapsed);
 
         if let Some(response) = response {
-            self.peer_manager_adapter.do_send(
-                PeerManagerMessageRequest::NetworkRequests(
-                    NetworkRequests::PartialEncodedChunkResponse { route_back, response },
-                )
-                .with_span_context(),
-            );
+            self.send_partial_encoded_chunk_response(route_back, response);
             return true;
         }
+        // We can only answer the request later if we know the chunk.
+        if let Some(request) = pending_request {
+            if self.encoded_chunks.get(&request.chunk_hash).map_or(false, |entry| !entry.complete) {
+                self.pending_part_requests.insert(request, route_back);
+            }
+        }
         false
     }
 
+    fn send_partial_encoded_chunk_response(
+        &self,
+        route_back: CryptoHash,
+        response: PartialEncodedChunkResponseMsg,
+    ) {
+        self.peer_manager_adapter.do_send(
+            PeerManagerMessageRequest::NetworkRequests(
+                NetworkRequests::PartialEncodedChunkResponse { route_back, response },
+            )
+            .with_span_context(),
+        );
+    }
+
+    /// If the request is for parts of a chunk we have produced, updates statistics of the
+    /// owner of the requested parts.  Part owners request their own parts from the chunk
+    /// producer only, so such requests mean that our message to the owner got lost or late.
+    /// Requests which don't ask for the parts of a single owner according to the chunk-part
+    /// assignment of the chunk's epoch aren't such requests and are ignored.
+    fn record_part_request(&mut self, request: &PartialEncodedChunkRequestMsg) {
+        let epoch_id = match self.part_request_stats.distributed_chunk_epoch(&request.chunk_hash) {
+            Some(epoch_id) => epoch_id.clone(),
+            None => return,
+        };
+        let num_total_parts = self.rs.total_shard_count() as u64;
+        let mut owner = None;
+        for &part_ord in request.part_ords.iter() {
+            if part_ord >= num_total_parts {
+                return;
+            }
+            let part_owner = match self.runtime_adapter.get_part_owner(&epoch_id, part_ord) {
+                Ok(part_owner) => part_owner,
+                Err(_) => return,
+            };
+            match &owner {
+                None => owner = Some(part_owner),
+                Some(owner) if owner == &part_owner => {}
+                Some(_) => return,
+            }
+        }
+        match owner {
+            Some(owner) if Some(&owner) != self.me.as_ref() => {
+                self.part_request_stats.record_request(owner)
+            }
+            _ => {}
+        }
+    }
+
+    /// Answers the pending requests which ask for any of the newly received parts and which
+    /// can now be answered in full.  Requests which still can't be answered are kept.
+    fn respond_to_pending_part_requests(
+        &mut self,
+        chunk_hash: &ChunkHash,
+        new_part_ords: &HashSet<u64>,
+    ) {
+        let requests = self.pending_part_requests.take_matching(chunk_hash, new_part_ords);
+        for (request, route_back) in requests {
+            let response = match self.encoded_chunks.get(chunk_hash) {
+                Some(entry) => {
+                    Self::prepare_partial_encoded_chunk_response_from_cache(request.clone(), entry)
+                }
+                None => return,
+            };
+            match response {
+                Some(response) => {
+                    metrics::PARTS_FORWARDED_TO_REQUESTERS.inc();
+                    self.send_partial_encoded_chunk_response(route_back, response);
+                }
+                None => {
+                    self.pending_part_requests.insert(request, route_back);
+                }
+            }
+        }
+    }
+
+    /// Answers the requests which were still pending when the chunk got completed.  Parts we
+    /// haven't received are taken from the chunk reconstructed from the ones we have.
+    fn respond_to_pending_part_requests_of_complete_chunk(
+        &mut self,
+        chunk_hash: &ChunkHash,
+        shard_chunk: Option<&ShardChunk>,
+    ) {
+        let mut unanswered = vec![];
+        for (request, route_back) in self.pending_part_requests.take_all(chunk_hash) {
+            let response = self.encoded_chunks.get(chunk_hash).and_then(|entry| {
+                Self::prepare_partial_encoded_chunk_response_from_cache(request.clone(), entry)
+            });
+            match response {
+                Some(response) => {
+                    metrics::PARTS_FORWARDED_TO_REQUESTERS.inc();
+                    self.send_partial_encoded_chunk_response(route_back, response);
+                }
+                None => unanswered.push((request, route_back)),
+            }
+        }
+        let shard_chunk = match shard_chunk {
+            Some(shard_chunk) if !unanswered.is_empty() => shard_chunk,
+            _ => return,
+        };
+
+        // Encoding the chunk is expensive, so it's done once for all the requests.
+        let mut part_ords: Vec<u64> = unanswered
+            .iter()
+            .flat_map(|(request, _)| request.part_ords.iter().copied())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        part_ords.sort();
+        let tracking_shards =
+            unanswered.iter().flat_map(|(request, _)| request.tracking_shards.clone()).collect();
+        let request = PartialEncodedChunkRequestMsg {
+            chunk_hash: chunk_hash.clone(),
+            part_ords,
+            tracking_shards,
+        };
+        let response =
+            match self.prepare_partial_encoded_chunk_response_from_chunk(request, shard_chunk) {
+                Some(response) => response,
+                None => return,
+            };
+        let parts: HashMap<u64, _> =
+            response.parts.into_iter().map(|part| (part.part_ord, part)).collect();
+        let receipts: HashMap<ShardId, _> =
+            response.receipts.into_iter().map(|receipt| (receipt.1.to_shard_id, receipt)).collect();
+        for (request, route_back) in unanswered {
+            let response = Self::prepare_partial_encoded_chunk_response_from_iters(
+                request.chunk_hash,
+                request.part_ords.iter().map(|part_ord| parts.get(part_ord).cloned()),
+                request.tracking_shards.iter().map(|shard_id| receipts.get(shard_id).cloned()),
+            );
+            if let Some(response) = response {
+                metrics::PARTS_FORWARDED_TO_REQUESTERS.inc();
+                self.send_partial_encoded_chunk_response(route_back, response);
+            }
+        }
+    }
+
     fn prepare_partial_encoded_chunk_response(
         &mut self,
         request: PartialEncodedChunkRequestMsg,
@@ -1665,6 +1822,11 @@ impl ShardsManager {
         // into chunk cache
         let new_part_ords =
             self.encoded_chunks.merge_in_partial_encoded_chunk(partial_encoded_chunk);
+        if self.distribution_config.forward_parts_to_requesters
+            && (!new_part_ords.is_empty() || !partial_encoded_chunk.receipts.is_empty())
+        {
+            self.respond_to_pending_part_requests(&chunk_hash, &new_part_ords);
+        }
 
         // 3. Forward my parts to others tracking this chunk's shard
         // It's possible that the previous block has not been processed yet. We will want to
@@ -1879,6 +2041,17 @@ impl ShardsManager {
         shard_chunk: Option<ShardChunk>,
     ) {
         let chunk_hash = partial_chunk.chunk_hash();
+        if let Some(entry) = self.encoded_chunks.get(&chunk_hash) {
+            if !entry.complete {
+                let forward = self.distribution_config.forward_parts_to_requesters;
+                metrics::PARTIAL_ENCODED_CHUNK_COMPLETION_TIME
+                    .with_label_values(&[if forward { "true" } else { "false" }])
+                    .observe(
+                        Clock::instant().saturating_duration_since(entry.created).as_secs_f64(),
+                    );
+            }
+        }
+        self.respond_to_pending_part_requests_of_complete_chunk(&chunk_hash, shard_chunk.as_ref());
         self.encoded_chunks.mark_entry_complete(&chunk_hash);
         self.encoded_chunks.remove_from_cache_if_outside_horizon(&chunk_hash);
         self.requested_partial_encoded_chunks.remove(&chunk_hash);
@@ -2062,6 +2235,7 @@ impl ShardsManager {
         outgoing_receipts: &[Receipt],
         outgoing_receipts_root: CryptoHash,
         tx_root: CryptoHash,
+        congestion_info: CongestionInfo,
         signer: &dyn ValidatorSigner,
         rs: &mut ReedSolomonWrapper,
         protocol_version: ProtocolVersion,
@@ -2081,6 +2255,7 @@ impl ShardsManager {
             transactions,
             outgoing_receipts,
             outgoing_receipts_root,
+            congestion_info,
             signer,
             protocol_version,
         )
@@ -2117,6 +2292,10 @@ impl ShardsManager {
             let entry = block_producer_mapping.entry(to_whom).or_insert_with(Vec::new);
             entry.push(part_ord);
         }
+        if self.distribution_config.max_redundant_parts > 0 {
+            self.add_redundant_parts(&mut block_producer_mapping);
+            self.part_request_stats.record_distributed(chunk_header.chunk_hash(), epoch_id);
+        }
 
         let receipt_proofs = make_outgoing_receipts_proofs(
             &chunk_header,
@@ -2167,6 +2346,33 @@ impl ShardsManager {
 
         Ok(())
     }
+
+    /// Adds random parts owned by others to the parts sent to the part owners which often
+    /// request their own parts back from us.  The more often an owner requests its parts,
+    /// the more parts it gets, but never more than it needs to reconstruct the chunk.
+    fn add_redundant_parts(&self, block_producer_mapping: &mut HashMap<AccountId, Vec<u64>>) {
+        let num_total_parts = self.rs.total_shard_count() as u64;
+        let num_data_parts = self.rs.data_shard_count();
+        let mut rng = rand::thread_rng();
+        for (to_whom, part_ords) in block_producer_mapping.iter_mut() {
+            if Some(to_whom) == self.me.as_ref() {
+                continue;
+            }
+            let num_redundant_parts = self
+                .part_request_stats
+                .num_redundant_parts(to_whom, self.distribution_config.max_redundant_parts)
+                .min(num_data_parts.saturating_sub(part_ords.len()));
+            if num_redundant_parts == 0 {
+                continue;
+            }
+            let owned: HashSet<u64> = part_ords.iter().copied().collect();
+            let extra = (0..num_total_parts)
+                .filter(|part_ord| !owned.contains(part_ord))
+                .choose_multiple(&mut rng, num_redundant_parts);
+            metrics::REDUNDANT_PARTS_SENT.inc_by(extra.len() as u64);
+            part_ords.extend(extra);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -2210,6 +2416,7 @@ mod test {
             client_adapter,
             ReadOnlyChunksStore::new(store),
             None,
+            ChunkDistributionConfig::default(),
         );
         let added = Clock::instant();
         shards_manager.requested_partial_encoded_chunks.insert(
@@ -2266,6 +2473,7 @@ mod test {
             client_adapter,
             chain_store.new_read_only_chunks_store(),
             None,
+            ChunkDistributionConfig::default(),
         );
         let signer =
             InMemoryValidatorSigner::from_seed("test".parse().unwrap(), KeyType::ED25519, "test");
@@ -2285,6 +2493,7 @@ mod test {
             &[],
             merklize(&Chain::build_receipts_hashes(&[], &shard_layout)).0,
             CryptoHash::default(),
+            CongestionInfo::default(),
             &signer,
             &mut rs,
             PROTOCOL_VERSION,
@@ -2429,6 +2638,7 @@ mod test {
             fixture.mock_client_adapter.clone(),
             fixture.chain_store.new_read_only_chunks_store(),
             Some(fixture.mock_chain_head.clone()),
+            ChunkDistributionConfig::default(),
         );
         // process chunk part 0
         let partial_encoded_chunk = fixture.make_partial_encoded_chunk(&[0]);
@@ -2492,6 +2702,7 @@ mod test {
             fixture.mock_client_adapter.clone(),
             fixture.chain_store.new_read_only_chunks_store(),
             None,
+            ChunkDistributionConfig::default(),
         );
 
         // part id > num parts
@@ -2518,6 +2729,7 @@ mod test {
             fixture.mock_client_adapter.clone(),
             fixture.chain_store.new_read_only_chunks_store(),
             None,
+            ChunkDistributionConfig::default(),
         );
         let partial_encoded_chunk = fixture.make_partial_encoded_chunk(&fixture.mock_part_ords);
         let result = shards_manager
@@ -2575,6 +2787,7 @@ mod test {
             fixture.mock_client_adapter.clone(),
             fixture.chain_store.new_read_only_chunks_store(),
             None,
+            ChunkDistributionConfig::default(),
         );
         let count_num_forward_msgs = |fixture: &ChunkTestFixture| {
             fixture
@@ -2646,6 +2859,7 @@ mod test {
             fixture.mock_client_adapter.clone(),
             fixture.chain_store.new_read_only_chunks_store(),
             None,
+            ChunkDistributionConfig::default(),
         );
         shards_manager.insert_header_if_not_exists_and_process_cached_chunk_forwards(
             &fixture.mock_chunk_header,
@@ -2730,6 +2944,7 @@ mod test {
             fixture.mock_client_adapter.clone(),
             fixture.chain_store.new_read_only_chunks_store(),
             None,
+            ChunkDistributionConfig::default(),
         );
         let partial_encoded_chunk = fixture.make_partial_encoded_chunk(&fixture.mock_part_ords);
         let _ = shards_manager
@@ -2812,6 +3027,7 @@ mod test {
             fixture.mock_client_adapter.clone(),
             fixture.chain_store.new_read_only_chunks_store(),
             None,
+            ChunkDistributionConfig::default(),
         );
         let (most_parts, other_parts) = {
             let mut most_parts = fixture.mock_chunk_parts.clone();
@@ -2881,6 +3097,7 @@ mod test {
             fixture.mock_client_adapter.clone(),
             fixture.chain_store.new_read_only_chunks_store(),
             None,
+            ChunkDistributionConfig::default(),
         );
         let forward = PartialEncodedChunkForwardMsg::from_header_and_parts(
             &fixture.mock_chunk_header,
@@ -2942,6 +3159,7 @@ mod test {
             fixture.mock_client_adapter.clone(),
             fixture.chain_store.new_read_only_chunks_store(),
             None,
+            ChunkDistributionConfig::default(),
         );
 
         shards_manager
@@ -2974,6 +3192,7 @@ mod test {
             fixture.mock_client_adapter.clone(),
             fixture.chain_store.new_read_only_chunks_store(),
             None,
+            ChunkDistributionConfig::default(),
         );
 
         shards_manager
@@ -3003,6 +3222,7 @@ mod test {
             fixture.mock_client_adapter.clone(),
             fixture.chain_store.new_read_only_chunks_store(),
             None,
+            ChunkDistributionConfig::default(),
         );
 
         persist_chunk(
@@ -3033,6 +3253,7 @@ mod test {
             fixture.mock_client_adapter.clone(),
             fixture.chain_store.new_read_only_chunks_store(),
             None,
+            ChunkDistributionConfig::default(),
         );
 
         let mut update = fixture.chain_store.store_update();
@@ -3062,6 +3283,7 @@ mod test {
             fixture.mock_client_adapter.clone(),
             fixture.chain_store.new_read_only_chunks_store(),
             None,
+            ChunkDistributionConfig::default(),
         );
         let part = fixture.make_partial_encoded_chunk(&fixture.mock_part_ords);
         shards_manager.process_partial_encoded_chunk(part.clone().into()).unwrap();
@@ -3071,4 +3293,167 @@ mod test {
         shards_manager.process_partial_encoded_chunk(part.into()).unwrap();
         assert_eq!(fixture.count_chunk_ready_for_inclusion_messages(), 0);
     }
+
+    #[test]
+    fn test_redundant_parts_for_frequent_requesters() {
+        let fixture = ChunkTestFixture::default();
+        let header = &fixture.mock_chunk_header;
+        let epoch_id =
+            fixture.mock_runtime.get_epoch_id_from_prev_block(header.prev_block_hash()).unwrap();
+        let chunk_producer = fixture
+            .mock_runtime
+            .get_chunk_producer(&epoch_id, header.height_created(), header.shard_id())
+            .unwrap();
+        let mut shards_manager = ShardsManager::new(
+            Some(chunk_producer),
+            fixture.mock_runtime.clone(),
+            fixture.mock_network.clone(),
+            fixture.mock_client_adapter.clone(),
+            fixture.chain_store.new_read_only_chunks_store(),
+            None,
+            ChunkDistributionConfig { max_redundant_parts: 2, ..Default::default() },
+        );
+        let distribute_and_count_owner_parts = |shards_manager: &mut ShardsManager| {
+            fixture.mock_network.requests.write().unwrap().clear();
+            shards_manager
+                .distribute_encoded_chunk(
+                    fixture.make_partial_encoded_chunk(&fixture.all_part_ords),
+                    fixture.mock_encoded_chunk.clone(),
+                    &fixture.mock_merkle_paths,
+                    fixture.mock_outgoing_receipts.clone(),
+                )
+                .unwrap();
+            let requests = fixture.mock_network.requests.read().unwrap();
+            requests
+                .iter()
+                .find_map(|request| match request.as_network_requests_ref() {
+                    NetworkRequests::PartialEncodedChunkMessage {
+                        account_id,
+                        partial_encoded_chunk,
+                    } if account_id == &fixture.mock_chunk_part_owner => {
+                        Some(partial_encoded_chunk.parts.len())
+                    }
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        // The part owner gets only its own parts until it repeatedly asks for them.
+        let num_owned_parts = fixture.mock_part_ords.len();
+        assert_eq!(num_owned_parts, distribute_and_count_owner_parts(&mut shards_manager));
+        let request_parts =
+            |shards_manager: &mut ShardsManager, part_ords: &[u64], num_requests| {
+                for _ in 0..num_requests {
+                    shards_manager.process_partial_encoded_chunk_request(
+                        PartialEncodedChunkRequestMsg {
+                            chunk_hash: header.chunk_hash(),
+                            part_ords: part_ords.to_vec(),
+                            tracking_shards: HashSet::new(),
+                        },
+                        CryptoHash::default(),
+                    );
+                }
+            };
+        // Requests which mix parts of different owners aren't requests of a part owner.
+        request_parts(&mut shards_manager, &fixture.all_part_ords, 10);
+        assert_eq!(num_owned_parts, distribute_and_count_owner_parts(&mut shards_manager));
+        // The more often the owner asks, the more redundant parts it gets.
+        request_parts(&mut shards_manager, &fixture.mock_part_ords, 2);
+        assert_eq!(num_owned_parts + 1, distribute_and_count_owner_parts(&mut shards_manager));
+        request_parts(&mut shards_manager, &fixture.mock_part_ords, 3);
+        assert_eq!(num_owned_parts + 2, distribute_and_count_owner_parts(&mut shards_manager));
+    }
+
+    #[test]
+    fn test_forward_parts_to_requesters() {
+        let fixture = ChunkTestFixture::default();
+        let mut shards_manager = ShardsManager::new(
+            Some(fixture.mock_chunk_part_owner.clone()),
+            fixture.mock_runtime.clone(),
+            fixture.mock_network.clone(),
+            fixture.mock_client_adapter.clone(),
+            fixture.chain_store.new_read_only_chunks_store(),
+            None,
+            ChunkDistributionConfig { forward_parts_to_requesters: true, ..Default::default() },
+        );
+        // We know the chunk but don't have any parts yet.
+        shards_manager
+            .process_partial_encoded_chunk(fixture.make_partial_encoded_chunk(&[]).into())
+            .unwrap();
+        let route_back = hash(&[42]);
+        let request = PartialEncodedChunkRequestMsg {
+            chunk_hash: fixture.mock_chunk_header.chunk_hash(),
+            part_ords: fixture.mock_part_ords.clone(),
+            tracking_shards: HashSet::new(),
+        };
+        assert!(!shards_manager.process_partial_encoded_chunk_request(request, route_back));
+
+        // The response is sent as soon as the parts arrive.
+        shards_manager
+            .process_partial_encoded_chunk(
+                fixture.make_partial_encoded_chunk(&fixture.mock_part_ords).into(),
+            )
+            .unwrap();
+        let requests = fixture.mock_network.requests.read().unwrap();
+        let response = requests
+            .iter()
+            .find_map(|request| match request.as_network_requests_ref() {
+                NetworkRequests::PartialEncodedChunkResponse { route_back: to, response }
+                    if to == &route_back =>
+                {
+                    Some(response.clone())
+                }
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(fixture.mock_part_ords.len(), response.parts.len());
+    }
+
+    #[test]
+    fn test_forward_reconstructed_parts_to_requesters() {
+        let fixture = ChunkTestFixture::default();
+        let mut shards_manager = ShardsManager::new(
+            Some(fixture.mock_shard_tracker.clone()),
+            fixture.mock_runtime.clone(),
+            fixture.mock_network.clone(),
+            fixture.mock_client_adapter.clone(),
+            fixture.chain_store.new_read_only_chunks_store(),
+            None,
+            ChunkDistributionConfig { forward_parts_to_requesters: true, ..Default::default() },
+        );
+        shards_manager
+            .process_partial_encoded_chunk(fixture.make_partial_encoded_chunk(&[]).into())
+            .unwrap();
+        let route_back = hash(&[42]);
+        let last_part_ord = *fixture.all_part_ords.last().unwrap();
+        let request = PartialEncodedChunkRequestMsg {
+            chunk_hash: fixture.mock_chunk_header.chunk_hash(),
+            part_ords: vec![last_part_ord],
+            tracking_shards: HashSet::new(),
+        };
+        assert!(!shards_manager.process_partial_encoded_chunk_request(request, route_back));
+
+        // We never receive the requested part, but reconstruct it when completing the chunk.
+        let num_data_parts = fixture.mock_runtime.num_data_parts();
+        let result = shards_manager
+            .process_partial_encoded_chunk(
+                fixture.make_partial_encoded_chunk(&fixture.all_part_ords[..num_data_parts]).into(),
+            )
+            .unwrap();
+        assert_matches!(result, ProcessPartialEncodedChunkResult::HaveAllPartsAndReceipts);
+        let requests = fixture.mock_network.requests.read().unwrap();
+        let response = requests
+            .iter()
+            .find_map(|request| match request.as_network_requests_ref() {
+                NetworkRequests::PartialEncodedChunkResponse { route_back: to, response }
+                    if to == &route_back =>
+                {
+                    Some(response.clone())
+                }
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(1, response.parts.len());
+        assert_eq!(last_part_ord, response.parts[0].part_ord);
+    }
 }
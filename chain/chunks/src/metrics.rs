@@ -33,3 +33,36 @@ pub static DISTRIBUTE_ENCODED_CHUNK_TIME: Lazy<near_o11y::metrics::HistogramVec>
         )
         .unwrap()
     });
+
+pub static PARTIAL_ENCODED_CHUNK_COMPLETION_TIME: Lazy<near_o11y::metrics::HistogramVec> =
+    Lazy::new(|| {
+        near_o11y::metrics::try_create_histogram_vec(
+            "near_partial_encoded_chunk_completion_time",
+            concat!(
+                "Time between receiving the first data of a chunk and having all parts and ",
+                "receipts needed to complete it.  The ‘forward_parts_to_requesters’ key ",
+                "describes whether the node forwards parts to peers which requested them ",
+                "before the parts were available, which allows comparing completion time ",
+                "with and without the experimental forwarding."
+            ),
+            &["forward_parts_to_requesters"],
+            Some(exponential_buckets(0.001, 2.0, 16).unwrap()),
+        )
+        .unwrap()
+    });
+
+pub static REDUNDANT_PARTS_SENT: Lazy<near_o11y::metrics::IntCounter> = Lazy::new(|| {
+    near_o11y::metrics::try_create_int_counter(
+        "near_chunk_redundant_parts_sent_total",
+        "Number of chunk parts sent by the chunk producer to validators which don't own them",
+    )
+    .unwrap()
+});
+
+pub static PARTS_FORWARDED_TO_REQUESTERS: Lazy<near_o11y::metrics::IntCounter> = Lazy::new(|| {
+    near_o11y::metrics::try_create_int_counter(
+        "near_chunk_parts_forwarded_to_requesters_total",
+        "Number of responses sent to part requests which arrived before we had the parts",
+    )
+    .unwrap()
+});
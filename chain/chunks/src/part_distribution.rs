@@ -0,0 +1,214 @@
+//! Distribution of chunk parts beyond what part owners are strictly assigned.
+//!
+//! ** Redundant parts
+//! Whenever a part owner misses one of its own parts it requests it back from the chunk
+//! producer (see `request_partial_encoded_chunk`).  The chunk producer therefore knows which
+//! part owners have trouble receiving parts it sends them.  `PartRequestStats` keeps a
+//! decaying score of such requests for every part owner and when distributing a new chunk,
+//! owners receive parts they don't own on top of their own ones: one for every
+//! `REDUNDANT_PARTS_SCORE_THRESHOLD` of their score, up to `max_redundant_parts` and never
+//! more than they need to reconstruct the chunk.  The Reed-Solomon redundancy an owner
+//! effectively gets thus adapts to how unreliable its connection to the chunk producer has
+//! recently been, while the data/parity split of the encoding, which all validators have to
+//! agree on, stays the same.  Those extra parts get the owner closer to being able to
+//! reconstruct the chunk even if some forwards from other part owners get lost.
+//!
+//! ** Forwarding parts to requesters
+//! A request for parts we don't have yet (e.g. a part owner asked for its part before the
+//! chunk producer's message reached it) can't be answered and normally the requester has to
+//! retry.  With `forward_parts_to_requesters` enabled such requests are kept in
+//! `PendingPartRequests` and answered as soon as we receive the parts, or reconstruct them
+//! when completing the chunk, which cuts the time it takes the requester to complete it.
+
+use std::collections::{HashMap, HashSet};
+
+use near_network::types::PartialEncodedChunkRequestMsg;
+use near_primitives::hash::CryptoHash;
+use near_primitives::sharding::ChunkHash;
+use near_primitives::types::{AccountId, EpochId};
+
+/// Number of most recently distributed chunks for which requests are tracked.
+const DISTRIBUTED_CHUNKS_CACHE_SIZE: usize = 100;
+/// Factor by which all scores are multiplied whenever a new chunk is distributed.
+const SCORE_DECAY: f64 = 0.9;
+/// Scores below this value are forgotten.
+const MIN_SCORE: f64 = 0.01;
+/// Part owners receive one redundant part for every this much of their score.
+const REDUNDANT_PARTS_SCORE_THRESHOLD: f64 = 2.0;
+
+/// Number of chunks for which requests can be pending at the same time.
+const PENDING_REQUESTS_CACHE_SIZE: usize = 100;
+/// Maximum number of requests pending for a single chunk.
+const MAX_PENDING_REQUESTS_PER_CHUNK: usize = 32;
+
+/// Statistics of requests for parts of the chunks we have produced.
+pub(crate) struct PartRequestStats {
+    /// Chunks we have distributed along with the epoch used to determine their part owners.
+    distributed: lru::LruCache<ChunkHash, EpochId>,
+    /// Decaying number of requests for their own parts sent by each part owner.
+    scores: HashMap<AccountId, f64>,
+}
+
+impl PartRequestStats {
+    pub fn new() -> Self {
+        Self {
+            distributed: lru::LruCache::new(DISTRIBUTED_CHUNKS_CACHE_SIZE),
+            scores: HashMap::new(),
+        }
+    }
+
+    /// Records that we have distributed the given chunk.  Decays scores of all part owners.
+    pub fn record_distributed(&mut self, chunk_hash: ChunkHash, epoch_id: EpochId) {
+        self.scores.retain(|_, score| {
+            *score *= SCORE_DECAY;
+            *score >= MIN_SCORE
+        });
+        self.distributed.put(chunk_hash, epoch_id);
+    }
+
+    /// Returns epoch of the chunk if it's one of the chunks we have recently distributed.
+    pub fn distributed_chunk_epoch(&self, chunk_hash: &ChunkHash) -> Option<&EpochId> {
+        self.distributed.peek(chunk_hash)
+    }
+
+    /// Records that `owner` has requested its parts of one of the chunks we distributed.
+    pub fn record_request(&mut self, owner: AccountId) {
+        *self.scores.entry(owner).or_default() += 1.0;
+    }
+
+    /// Returns how many redundant parts `owner` should receive, at most `max_redundant_parts`.
+    pub fn num_redundant_parts(&self, owner: &AccountId, max_redundant_parts: usize) -> usize {
+        let score = self.scores.get(owner).copied().unwrap_or_default();
+        ((score / REDUNDANT_PARTS_SCORE_THRESHOLD) as usize).min(max_redundant_parts)
+    }
+}
+
+/// Requests we weren't able to answer yet.
+pub(crate) struct PendingPartRequests {
+    requests: lru::LruCache<ChunkHash, Vec<(PartialEncodedChunkRequestMsg, CryptoHash)>>,
+}
+
+impl PendingPartRequests {
+    pub fn new() -> Self {
+        Self { requests: lru::LruCache::new(PENDING_REQUESTS_CACHE_SIZE) }
+    }
+
+    /// Remembers the request so that it can be answered once we have the requested data.
+    /// Returns false if there are too many requests pending for the chunk already.
+    pub fn insert(
+        &mut self,
+        request: PartialEncodedChunkRequestMsg,
+        route_back: CryptoHash,
+    ) -> bool {
+        let chunk_hash = request.chunk_hash.clone();
+        if self.requests.get(&chunk_hash).is_none() {
+            self.requests.put(chunk_hash.clone(), vec![]);
+        }
+        let pending = self.requests.get_mut(&chunk_hash).unwrap();
+        if pending.len() >= MAX_PENDING_REQUESTS_PER_CHUNK {
+            return false;
+        }
+        pending.push((request, route_back));
+        true
+    }
+
+    /// Removes and returns requests for the given chunk which ask for any of the `part_ords`.
+    /// Requests for receipts only, i.e. without any parts, always match.
+    pub fn take_matching(
+        &mut self,
+        chunk_hash: &ChunkHash,
+        part_ords: &HashSet<u64>,
+    ) -> Vec<(PartialEncodedChunkRequestMsg, CryptoHash)> {
+        let pending = match self.requests.get_mut(chunk_hash) {
+            Some(pending) => pending,
+            None => return vec![],
+        };
+        let (matching, rest) = std::mem::take(pending).into_iter().partition(|(request, _)| {
+            request.part_ords.is_empty()
+                || request.part_ords.iter().any(|part_ord| part_ords.contains(part_ord))
+        });
+        *pending = rest;
+        matching
+    }
+
+    /// Removes and returns all requests for the given chunk.
+    pub fn take_all(
+        &mut self,
+        chunk_hash: &ChunkHash,
+    ) -> Vec<(PartialEncodedChunkRequestMsg, CryptoHash)> {
+        self.requests.pop(chunk_hash).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_primitives::hash::hash;
+
+    fn make_request(chunk_hash: &ChunkHash, part_ords: Vec<u64>) -> PartialEncodedChunkRequestMsg {
+        PartialEncodedChunkRequestMsg {
+            chunk_hash: chunk_hash.clone(),
+            part_ords,
+            tracking_shards: HashSet::new(),
+        }
+    }
+
+    #[test]
+    fn test_part_request_stats() {
+        let mut stats = PartRequestStats::new();
+        let (a, b): (AccountId, AccountId) = ("alice".parse().unwrap(), "bob".parse().unwrap());
+        let chunk_hash = ChunkHash(hash(&[1]));
+        assert_eq!(None, stats.distributed_chunk_epoch(&chunk_hash));
+        stats.record_distributed(chunk_hash.clone(), EpochId::default());
+        assert_eq!(Some(&EpochId::default()), stats.distributed_chunk_epoch(&chunk_hash));
+
+        stats.record_request(a.clone());
+        assert_eq!(0, stats.num_redundant_parts(&a, 3));
+        stats.record_request(a.clone());
+        stats.record_request(b.clone());
+        assert_eq!(1, stats.num_redundant_parts(&a, 3));
+        assert_eq!(0, stats.num_redundant_parts(&b, 3));
+
+        // The more often an owner requests its parts, the more redundant parts it gets.
+        for _ in 0..6 {
+            stats.record_request(a.clone());
+        }
+        assert_eq!(3, stats.num_redundant_parts(&a, 3));
+        assert_eq!(4, stats.num_redundant_parts(&a, 10));
+
+        // Owner which stops requesting its parts no longer gets redundant ones.
+        for i in 2..20 {
+            stats.record_distributed(ChunkHash(hash(&[i])), EpochId::default());
+        }
+        assert_eq!(0, stats.num_redundant_parts(&a, 3));
+    }
+
+    #[test]
+    fn test_pending_part_requests() {
+        let mut pending = PendingPartRequests::new();
+        let chunk_hash = ChunkHash(hash(&[1]));
+        let first = make_request(&chunk_hash, vec![0, 1]);
+        let second = make_request(&chunk_hash, vec![2]);
+        assert!(pending.insert(first.clone(), hash(&[10])));
+        assert!(pending.insert(second.clone(), hash(&[11])));
+
+        assert_eq!(Vec::<(_, _)>::new(), pending.take_matching(&chunk_hash, &HashSet::from([3])));
+        assert_eq!(
+            vec![(first, hash(&[10]))],
+            pending.take_matching(&chunk_hash, &HashSet::from([1, 3]))
+        );
+        let receipts_only = make_request(&chunk_hash, vec![]);
+        assert!(pending.insert(receipts_only.clone(), hash(&[12])));
+        assert_eq!(
+            vec![(receipts_only, hash(&[12]))],
+            pending.take_matching(&chunk_hash, &HashSet::from([5]))
+        );
+        assert_eq!(vec![(second.clone(), hash(&[11]))], pending.take_all(&chunk_hash));
+        assert_eq!(Vec::<(_, _)>::new(), pending.take_matching(&chunk_hash, &HashSet::from([2])));
+
+        for _ in 0..MAX_PENDING_REQUESTS_PER_CHUNK {
+            assert!(pending.insert(second.clone(), hash(&[11])));
+        }
+        assert!(!pending.insert(second, hash(&[11])));
+    }
+}
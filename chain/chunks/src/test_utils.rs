@@ -15,6 +15,7 @@ use near_crypto::KeyType;
 use near_network::test_utils::MockPeerManagerAdapter;
 use near_o11y::WithSpanContext;
 use near_primitives::block::BlockHeader;
+use near_primitives::congestion_info::CongestionInfo;
 use near_primitives::hash::{self, CryptoHash};
 use near_primitives::merkle::{self, MerklePath};
 use near_primitives::sharding::{
@@ -258,6 +259,7 @@ impl ChunkTestFixture {
             &receipts,
             receipts_root,
             MerkleHash::default(),
+            CongestionInfo::default(),
             &signer,
             &mut rs,
             PROTOCOL_VERSION,
@@ -8,10 +8,12 @@ use chrono::DateTime;
 use near_primitives::time::Utc;
 
 use near_chain_configs::ProtocolConfigView;
+use near_primitives::errors::InvalidTxError;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{MerklePath, PartialMerkleTree};
 use near_primitives::network::PeerId;
 use near_primitives::sharding::ChunkHash;
+use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::{
     AccountId, BlockHeight, BlockReference, EpochId, EpochReference, MaybeBlockId, ShardId,
     TransactionOrReceiptId,
@@ -19,9 +21,10 @@ use near_primitives::types::{
 use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
     BlockView, ChunkView, DownloadStatusView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
-    FinalExecutionOutcomeViewEnum, GasPriceView, LightClientBlockLiteView, LightClientBlockView,
-    MaintenanceWindowsView, QueryRequest, QueryResponse, ReceiptView, ShardSyncDownloadView,
-    StateChangesKindsView, StateChangesRequestView, StateChangesView, SyncStatusView,
+    FinalExecutionOutcomeViewEnum, FinalExecutionOutcomeWithReceiptView, GasPriceView,
+    LightClientBlockLiteView, LightClientBlockView, MaintenanceWindowsView, QueryRequest,
+    QueryResponse, ReceiptView, ShardSyncDownloadView, StateChangesKindsView,
+    StateChangesRequestView, StateChangesView, SyncStatusView,
 };
 pub use near_primitives::views::{StatusResponse, StatusSyncInfo};
 use serde::Serialize;
@@ -562,6 +565,8 @@ pub struct PeerInfo {
     pub id: PeerId,
     pub addr: Option<std::net::SocketAddr>,
     pub account_id: Option<AccountId>,
+    /// Reputation score of the peer, rounded.
+    pub score: i64,
 }
 
 #[derive(Clone, Debug)]
@@ -917,6 +922,62 @@ impl From<near_chain_primitives::Error> for GetMaintenanceWindowsError {
     }
 }
 
+/// Applies the transaction and all the receipts it produces on top of the state
+/// at the given block without committing any changes.
+pub struct SimulateTransaction {
+    pub block_reference: BlockReference,
+    pub signed_transaction: SignedTransaction,
+    /// Whether the signature of the transaction should be checked.
+    pub verify_signature: bool,
+}
+
+#[derive(Debug)]
+pub struct SimulateTransactionResponse {
+    pub final_execution_outcome: FinalExecutionOutcomeWithReceiptView,
+    pub block_height: BlockHeight,
+    pub block_hash: CryptoHash,
+}
+
+impl Message for SimulateTransaction {
+    type Result = Result<SimulateTransactionResponse, SimulateTransactionError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SimulateTransactionError {
+    #[error("There are no fully synchronized blocks on the node yet")]
+    NoSyncedBlocks,
+    #[error("Block either has never been observed on the node or has been garbage collected: {block_reference:?}")]
+    UnknownBlock { block_reference: BlockReference },
+    #[error("The node does not track the shard ID {requested_shard_id}")]
+    UnavailableShard { requested_shard_id: ShardId },
+    #[error("Transaction is invalid: {0:?}")]
+    InvalidTransaction(InvalidTxError),
+    #[error("Simulation exceeded the limits configured on the node: {error_message}")]
+    LimitExceeded { error_message: String },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {error_message}")]
+    Unreachable { error_message: String },
+}
+
+impl From<near_chain_primitives::Error> for SimulateTransactionError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error {
+            near_chain_primitives::Error::IOErr(error) => {
+                Self::InternalError { error_message: error.to_string() }
+            }
+            near_chain_primitives::Error::StorageError(error) => {
+                Self::InternalError { error_message: error.to_string() }
+            }
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
 #[cfg(feature = "sandbox")]
 #[derive(Debug)]
 pub enum SandboxMessage {
@@ -32,6 +32,8 @@ use near_network::types::{
 };
 use near_primitives::block::{Approval, ApprovalInner, ApprovalMessage, Block, BlockHeader, Tip};
 use near_primitives::challenge::{Challenge, ChallengeBody};
+use near_primitives::congestion_info::BlockCongestionInfo;
+use near_primitives::errors::InvalidTxError;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{merklize, MerklePath, PartialMerkleTree};
 use near_primitives::receipt::Receipt;
@@ -201,6 +203,7 @@ impl Client {
             client_adapter.clone(),
             chain.store().new_read_only_chunks_store(),
             chain.head().ok(),
+            config.chunk_distribution.clone(),
         );
         let sharded_tx_pool = ShardedTransactionPool::new(rng_seed);
         let sync_status = SyncStatus::AwaitingPeers;
@@ -779,6 +782,11 @@ impl Client {
         let outgoing_receipts_hashes =
             Chain::build_receipts_hashes(&outgoing_receipts, &shard_layout);
         let (outgoing_receipts_root, _) = merklize(&outgoing_receipts_hashes);
+        let congestion_info = self.runtime_adapter.get_congestion_info(
+            shard_id,
+            &prev_block_hash,
+            *chunk_extra.state_root(),
+        )?;
 
         let protocol_version = self.runtime_adapter.get_epoch_protocol_version(epoch_id)?;
         let gas_used = chunk_extra.gas_used();
@@ -798,6 +806,7 @@ impl Client {
             &outgoing_receipts,
             outgoing_receipts_root,
             tx_root,
+            congestion_info,
             &*validator_signer,
             &mut self.rs_for_chunk_production,
             protocol_version,
@@ -854,10 +863,11 @@ impl Client {
         chunk_extra: &ChunkExtra,
         prev_block_header: &BlockHeader,
     ) -> Result<Vec<SignedTransaction>, Error> {
-        let Self { chain, sharded_tx_pool, runtime_adapter, .. } = self;
-
         let next_epoch_id =
-            runtime_adapter.get_epoch_id_from_prev_block(prev_block_header.hash())?;
+            self.runtime_adapter.get_epoch_id_from_prev_block(prev_block_header.hash())?;
+        let congested_shards =
+            self.get_congested_shards(prev_block_header.hash(), &next_epoch_id)?;
+        let Self { chain, sharded_tx_pool, runtime_adapter, .. } = self;
         let protocol_version = runtime_adapter.get_epoch_protocol_version(&next_epoch_id)?;
 
         let transactions = if let Some(mut iter) = sharded_tx_pool.get_pool_iterator(shard_id) {
@@ -870,8 +880,11 @@ impl Client {
                 *chunk_extra.state_root(),
                 // while the height of the next block that includes the chunk might not be prev_height + 1,
                 // passing it will result in a more conservative check and will not accidentally allow
-                // invalid transactions to be included.
+                // invalid transactions to be included. The same doesn't hold for access key expiries,
+                // which are checked against the previous block timestamp too: the runtime drops
+                // transactions whose access key has expired by the time the chunk is applied.
                 prev_block_header.height() + 1,
+                prev_block_header.raw_timestamp(),
                 &mut iter,
                 &mut |tx: &SignedTransaction| -> bool {
                     chain
@@ -882,6 +895,9 @@ impl Client {
                             transaction_validity_period,
                         )
                         .is_ok()
+                        && runtime_adapter
+                            .account_id_to_shard_id(&tx.transaction.receiver_id, &next_epoch_id)
+                            .map_or(false, |shard_id| !congested_shards.contains(&shard_id))
                 },
                 protocol_version,
             )?
@@ -894,6 +910,31 @@ impl Client {
         Ok(transactions)
     }
 
+    /// Returns the shards which are congested according to the chunk headers
+    /// of the given block. New transactions to these shards are not accepted.
+    fn get_congested_shards(
+        &self,
+        block_hash: &CryptoHash,
+        epoch_id: &EpochId,
+    ) -> Result<HashSet<ShardId>, Error> {
+        let block = self.chain.get_block(block_hash)?;
+        let congestion_info = BlockCongestionInfo::from_chunk_headers(block.chunks().iter());
+        if congestion_info.is_empty() {
+            return Ok(HashSet::new());
+        }
+        let config = self
+            .runtime_adapter
+            .get_protocol_config(epoch_id)?
+            .runtime_config
+            .congestion_control_config;
+        Ok(block
+            .chunks()
+            .iter()
+            .map(|chunk_header| chunk_header.shard_id())
+            .filter(|shard_id| congestion_info.is_congested(*shard_id, &config))
+            .collect())
+    }
+
     pub fn send_challenges(&mut self, challenges: Vec<ChallengeBody>) {
         if let Some(validator_signer) = &self.validator_signer {
             for body in challenges {
@@ -1948,6 +1989,19 @@ impl Client {
             return Ok(ProcessTxResponse::InvalidTx(err));
         }
 
+        let receiver_shard_id =
+            self.runtime_adapter.account_id_to_shard_id(&tx.transaction.receiver_id, &epoch_id)?;
+        if self.get_congested_shards(&head.last_block_hash, &epoch_id)?.contains(&receiver_shard_id)
+        {
+            debug!(target: "client", shard_id = receiver_shard_id, "Invalid tx: receiver shard is congested");
+            metrics::TRANSACTION_REJECTED_SHARD_CONGESTED
+                .with_label_values(&[&receiver_shard_id.to_string()])
+                .inc();
+            return Ok(ProcessTxResponse::InvalidTx(InvalidTxError::ShardCongested {
+                shard_id: receiver_shard_id,
+            }));
+        }
+
         let shard_id =
             self.runtime_adapter.account_id_to_shard_id(&tx.transaction.signer_id, &epoch_id)?;
         if self.runtime_adapter.cares_about_shard(me, &head.last_block_hash, shard_id, true)
@@ -32,7 +32,7 @@ use near_chain::test_utils::format_hash;
 use near_chain::ChainStoreAccess;
 use near_chain::{
     byzantine_assert, near_chain_primitives, Block, BlockHeader, BlockProcessingArtifact,
-    ChainGenesis, DoneApplyChunkCallback, Provenance, RuntimeAdapter,
+    ChainGenesis, DoneApplyChunkCallback, Provenance, RuntimeAdapter, StoreConsistencyChecker,
 };
 use near_chain_configs::ClientConfig;
 use near_chunks::client::ShardsManagerResponse;
@@ -58,7 +58,7 @@ use near_primitives::network::{AnnounceAccount, PeerId};
 use near_primitives::state_part::PartId;
 use near_primitives::syncing::StatePartKey;
 use near_primitives::time::{Clock, Utc};
-use near_primitives::types::{BlockHeight, ValidatorInfoIdentifier};
+use near_primitives::types::{BlockHeight, ShardId, ValidatorInfoIdentifier};
 use near_primitives::unwrap_or_return;
 use near_primitives::utils::{from_timestamp, MaybeValidated};
 use near_primitives::validator_signer::ValidatorSigner;
@@ -108,11 +108,14 @@ pub struct ClientActor {
     doomslug_timer_next_attempt: DateTime<Utc>,
     sync_timer_next_attempt: DateTime<Utc>,
     chunk_request_retry_next_attempt: DateTime<Utc>,
+    store_consistency_check_next_attempt: DateTime<Utc>,
     sync_started: bool,
     state_parts_task_scheduler: Box<dyn Fn(ApplyStatePartsRequest)>,
     block_catch_up_scheduler: Box<dyn Fn(BlockCatchUpRequest)>,
     state_split_scheduler: Box<dyn Fn(StateSplitRequest)>,
     state_parts_client_arbiter: Arbiter,
+    /// Validates the database in the background if enabled in config.
+    store_consistency_checker: Option<StoreConsistencyChecker>,
 
     #[cfg(feature = "sandbox")]
     fastforward_delta: near_primitives::types::BlockHeightDelta,
@@ -157,6 +160,7 @@ impl ClientActor {
         ctx: &Context<ClientActor>,
         shutdown_signal: Option<oneshot::Sender<()>>,
         adv: crate::adversarial::Controls,
+        store_consistency_checker: Option<StoreConsistencyChecker>,
     ) -> Result<Self, Error> {
         let state_parts_arbiter = Arbiter::new();
         let self_addr = ctx.address();
@@ -209,6 +213,7 @@ impl ClientActor {
             doomslug_timer_next_attempt: now,
             sync_timer_next_attempt: now,
             chunk_request_retry_next_attempt: now,
+            store_consistency_check_next_attempt: now,
             sync_started: false,
             state_parts_task_scheduler: create_sync_job_scheduler::<ApplyStatePartsRequest>(
                 sync_jobs_actor_addr.clone(),
@@ -220,6 +225,7 @@ impl ClientActor {
                 sync_jobs_actor_addr,
             ),
             state_parts_client_arbiter: state_parts_arbiter,
+            store_consistency_checker,
 
             #[cfg(feature = "sandbox")]
             fastforward_delta: 0,
@@ -383,6 +389,7 @@ impl Handler<WithSpanContext<NetworkAdversarialMessage>> for ClientActor {
                     this.client.chain.store().store().clone(),
                     this.adv.is_archival(),
                 );
+                store_validator.set_retained_accounts(this.client.config.gc.retained_accounts.clone());
                 store_validator.set_timeout(timeout);
                 store_validator.validate();
                 if store_validator.is_failed() {
@@ -878,11 +885,15 @@ impl Handler<WithSpanContext<Status>> for ClientActor {
 }
 
 /// Private to public API conversion.
-fn make_peer_info(from: near_network::types::PeerInfo) -> near_client_primitives::types::PeerInfo {
+fn make_peer_info(
+    from: &near_network::types::ConnectedPeerInfo,
+) -> near_client_primitives::types::PeerInfo {
+    let peer_info = &from.full_peer_info.peer_info;
     near_client_primitives::types::PeerInfo {
-        id: from.id,
-        addr: from.addr,
-        account_id: from.account_id,
+        id: peer_info.id.clone(),
+        addr: peer_info.addr,
+        account_id: peer_info.account_id.clone(),
+        score: from.score.round() as i64,
     }
 }
 
@@ -913,7 +924,7 @@ impl Handler<WithSpanContext<GetNetworkInfo>> for ClientActor {
 
         Ok(NetworkInfoResponse {
             connected_peers: (self.network_info.connected_peers.iter())
-                .map(|fpi| make_peer_info(fpi.full_peer_info.peer_info.clone()))
+                .map(make_peer_info)
                 .collect(),
             num_connected_peers: self.network_info.num_connected_peers,
             peer_max_count: self.network_info.peer_max_count,
@@ -1278,6 +1289,29 @@ impl ClientActor {
                 .unwrap_or(delay),
         );
 
+        if self.store_consistency_checker.is_some() {
+            self.store_consistency_check_next_attempt = self.run_timer(
+                self.client.config.store_consistency_check.step_period,
+                self.store_consistency_check_next_attempt,
+                ctx,
+                |act, _ctx| {
+                    if let (Some(checker), Some(pool)) =
+                        (&act.store_consistency_checker, act.client.chain.background_pool())
+                    {
+                        checker.schedule_step(pool);
+                    }
+                },
+                "store_consistency_check",
+            );
+            delay = core::cmp::min(
+                delay,
+                self.store_consistency_check_next_attempt
+                    .signed_duration_since(now)
+                    .to_std()
+                    .unwrap_or(delay),
+            );
+        }
+
         self.chunk_request_retry_next_attempt = self.run_timer(
             self.client.config.chunk_request_retry_period,
             self.chunk_request_retry_next_attempt,
@@ -1720,7 +1754,7 @@ impl ClientActor {
                 let prev_hash = *block_header.prev_hash();
                 let epoch_id =
                     self.client.chain.get_block_header(&sync_hash).unwrap().epoch_id().clone();
-                let shards_to_sync =
+                let shards_to_sync: Vec<ShardId> =
                     (0..self.client.runtime_adapter.num_shards(&epoch_id).unwrap())
                         .filter(|x| {
                             cares_about_shard_this_or_next_epoch(
@@ -1744,7 +1778,7 @@ impl ClientActor {
                     &mut self.client.chain,
                     &self.client.runtime_adapter,
                     &self.network_info.highest_height_peers,
-                    shards_to_sync,
+                    shards_to_sync.clone(),
                     &self.state_parts_task_scheduler,
                     &self.state_split_scheduler,
                 )) {
@@ -1757,9 +1791,12 @@ impl ClientActor {
                             {
                                 let id = peer_info.peer_info.id.clone();
 
-                                if let Ok(header) = self.client.chain.get_block_header(&sync_hash) {
-                                    for hash in
-                                        vec![*header.prev_hash(), *header.hash()].into_iter()
+                                if let Ok(blocks) = self
+                                    .client
+                                    .chain
+                                    .get_state_sync_blocks(&sync_hash, &shards_to_sync)
+                                {
+                                    for hash in blocks.into_iter().chain(std::iter::once(sync_hash))
                                     {
                                         self.client.request_block(hash, id.clone());
                                     }
@@ -2079,6 +2116,7 @@ pub fn start_client(
     telemetry_actor: Addr<TelemetryActor>,
     sender: Option<oneshot::Sender<()>>,
     adv: crate::adversarial::Controls,
+    store_consistency_checker: Option<StoreConsistencyChecker>,
 ) -> (Addr<ClientActor>, ArbiterHandle) {
     let client_arbiter = Arbiter::new();
     let client_arbiter_handle = client_arbiter.handle();
@@ -2097,6 +2135,7 @@ pub fn start_client(
             ctx,
             sender,
             adv,
+            store_consistency_checker,
         )
         .unwrap()
     });
@@ -292,6 +292,15 @@ pub(crate) static TRANSACTION_RECEIVED_NON_VALIDATOR_FORWARDED: Lazy<IntGauge> =
     .unwrap()
 });
 
+pub(crate) static TRANSACTION_REJECTED_SHARD_CONGESTED: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_transaction_rejected_shard_congested_total",
+        "Number of transactions rejected because the shard of the receiver is congested",
+        &["shard_id"],
+    )
+    .unwrap()
+});
+
 pub(crate) static NODE_PROTOCOL_VERSION: Lazy<IntGauge> = Lazy::new(|| {
     try_create_int_gauge("near_node_protocol_version", "Max protocol version supported by the node")
         .unwrap()
@@ -684,16 +684,20 @@ impl StateSync {
 
     pub fn sync_block_status(
         &mut self,
-        prev_hash: &CryptoHash,
+        blocks: &[CryptoHash],
         chain: &Chain,
         now: DateTime<Utc>,
     ) -> Result<(bool, bool), near_chain::Error> {
-        let (request_block, have_block) = if !chain.block_exists(prev_hash)? {
+        let mut have_blocks = true;
+        for hash in blocks {
+            have_blocks &= chain.block_exists(hash)?;
+        }
+        let (request_block, have_block) = if !have_blocks {
             match self.last_time_block_requested {
                 None => (true, false),
                 Some(last_time) => {
                     if now - last_time >= self.timeout {
-                        error!(target: "sync", "State sync: block request for {} timed out in {} seconds", prev_hash, self.timeout.num_seconds());
+                        error!(target: "sync", "State sync: block request for {:?} timed out in {} seconds", blocks, self.timeout.num_seconds());
                         (true, false)
                     } else {
                         (false, false)
@@ -855,7 +859,17 @@ impl StateSync {
                     }
                 }
                 ShardSyncStatus::StateDownloadApplying => {
-                    let result = self.state_parts_apply_results.remove(&shard_id);
+                    // Finalizing applies the last chunk of the shard with the
+                    // congestion info of the blocks since it was included.
+                    let mut have_blocks = true;
+                    for hash in chain.get_state_sync_blocks(&sync_hash, &[shard_id])? {
+                        have_blocks &= chain.block_exists(&hash)?;
+                    }
+                    let result = if have_blocks {
+                        self.state_parts_apply_results.remove(&shard_id)
+                    } else {
+                        None
+                    };
                     if let Some(result) = result {
                         match chain.set_state_finalize(shard_id, sync_hash, result) {
                             Ok(()) => {
@@ -1229,10 +1243,10 @@ impl StateSync {
     ) -> Result<StateSyncResult, near_chain::Error> {
         let _span = tracing::debug_span!(target: "sync", "run", sync = "StateSync").entered();
         debug!(target: "sync", %sync_hash, ?tracking_shards, "syncing state");
-        let prev_hash = *chain.get_block_header(&sync_hash)?.prev_hash();
+        let blocks = chain.get_state_sync_blocks(&sync_hash, &tracking_shards)?;
         let now = Clock::utc();
 
-        let (request_block, have_block) = self.sync_block_status(&prev_hash, chain, now)?;
+        let (request_block, have_block) = self.sync_block_status(&blocks, chain, now)?;
 
         if tracking_shards.is_empty() {
             // This case is possible if a validator cares about the same shards in the new epoch as
@@ -267,6 +267,7 @@ pub fn setup(
         ctx,
         None,
         adv,
+        None,
     )
     .unwrap();
     (genesis_block, client, view_client_addr)
@@ -677,10 +678,13 @@ pub fn setup_mock_all_validators(
                                 },
                                 received_bytes_per_sec: 0,
                                 sent_bytes_per_sec: 0,
+                                received_bytes_per_sec_by_type: HashMap::new(),
+                                sent_bytes_per_sec_by_type: HashMap::new(),
                                 last_time_peer_requested: near_network::time::Instant::now(),
                                 last_time_received_message: near_network::time::Instant::now(),
                                 connection_established_time: near_network::time::Instant::now(),
                                 peer_type: PeerType::Outbound,
+                                score: 0.,
                             })
                             .collect();
                         let peers2 = peers
@@ -1795,6 +1799,7 @@ pub fn create_chunk(
             transactions,
             decoded_chunk.receipts(),
             header.outgoing_receipts_root(),
+            header.congestion_info().unwrap_or_default(),
             &*signer,
             PROTOCOL_VERSION,
         )
@@ -24,7 +24,8 @@ use near_client_primitives::types::{
     GetMaintenanceWindowsError, GetNextLightClientBlockError, GetProtocolConfig,
     GetProtocolConfigError, GetReceipt, GetReceiptError, GetStateChangesError,
     GetStateChangesWithCauseInBlock, GetStateChangesWithCauseInBlockForTrackedShards,
-    GetValidatorInfoError, Query, QueryError, TxStatus, TxStatusError,
+    GetValidatorInfoError, Query, QueryError, SimulateTransaction, SimulateTransactionError,
+    SimulateTransactionResponse, TxStatus, TxStatusError,
 };
 #[cfg(feature = "test_features")]
 use near_network::types::NetworkAdversarialMessage;
@@ -51,9 +52,9 @@ use near_primitives::types::{
 use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
     BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
-    FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum, GasPriceView, LightClientBlockView,
-    MaintenanceWindowsView, QueryRequest, QueryResponse, ReceiptView, StateChangesKindsView,
-    StateChangesView,
+    FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum, FinalExecutionOutcomeWithReceiptView,
+    GasPriceView, LightClientBlockView, MaintenanceWindowsView, QueryRequest, QueryResponse,
+    ReceiptView, StateChangesKindsView, StateChangesView,
 };
 
 use crate::adapter::{
@@ -412,6 +413,104 @@ impl ViewClientActor {
         }
     }
 
+    fn simulate_transaction(
+        &self,
+        msg: SimulateTransaction,
+    ) -> Result<SimulateTransactionResponse, SimulateTransactionError> {
+        let header = match self.get_block_header_by_reference(&msg.block_reference) {
+            Ok(Some(header)) => header,
+            Ok(None) => return Err(SimulateTransactionError::NoSyncedBlocks),
+            Err(near_chain::near_chain_primitives::Error::DBNotFoundErr(_)) => {
+                return Err(SimulateTransactionError::UnknownBlock {
+                    block_reference: msg.block_reference,
+                })
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        // Receipts may be sent to any shard so the node needs the state of all
+        // of them.
+        let mut state_roots = HashMap::new();
+        for shard_uid in self.runtime_adapter.get_shard_layout(header.epoch_id())?.get_shard_uids()
+        {
+            let chunk_extra =
+                self.chain.get_chunk_extra(header.hash(), &shard_uid).map_err(|err| match err {
+                    near_chain::near_chain_primitives::Error::DBNotFoundErr(_) => {
+                        SimulateTransactionError::UnavailableShard {
+                            requested_shard_id: shard_uid.shard_id as ShardId,
+                        }
+                    }
+                    err => err.into(),
+                })?;
+            state_roots.insert(shard_uid, *chunk_extra.state_root());
+        }
+
+        let result = self
+            .runtime_adapter
+            .simulate_transaction(
+                &state_roots,
+                header.height(),
+                header.raw_timestamp(),
+                header.prev_hash(),
+                header.hash(),
+                header.epoch_id(),
+                header.gas_price(),
+                *header.random_value(),
+                &msg.signed_transaction,
+                msg.verify_signature,
+                &self.config.simulate_transaction,
+            )?
+            .map_err(SimulateTransactionError::InvalidTransaction)?;
+        if let Some(error_message) = result.limit_exceeded {
+            return Err(SimulateTransactionError::LimitExceeded { error_message });
+        }
+
+        // Order the outcomes the same way `Chain::get_final_transaction_result`
+        // does: depth first, starting from the transaction.
+        let mut outcomes: HashMap<_, _> = result
+            .outcomes
+            .into_iter()
+            .map(|outcome_with_id| (outcome_with_id.id, outcome_with_id.outcome))
+            .collect();
+        let mut ordered_outcomes = vec![];
+        let mut pending_ids = vec![msg.signed_transaction.get_hash()];
+        while let Some(id) = pending_ids.pop() {
+            if let Some(outcome) = outcomes.remove(&id) {
+                pending_ids.extend(outcome.receipt_ids.iter().rev());
+                ordered_outcomes.push(ExecutionOutcomeWithIdView {
+                    proof: vec![],
+                    block_hash: *header.hash(),
+                    id,
+                    outcome: outcome.into(),
+                });
+            }
+        }
+        let status = near_chain::get_final_execution_status(&ordered_outcomes);
+        let receipts_outcome = ordered_outcomes.split_off(1);
+        let transaction_outcome = ordered_outcomes.pop().unwrap();
+
+        let mut receipts: HashMap<_, _> =
+            result.receipts.into_iter().map(|receipt| (receipt.receipt_id, receipt)).collect();
+        let receipts = receipts_outcome
+            .iter()
+            .filter_map(|outcome| receipts.remove(&outcome.id).map(ReceiptView::from))
+            .collect();
+
+        Ok(SimulateTransactionResponse {
+            final_execution_outcome: FinalExecutionOutcomeWithReceiptView {
+                final_outcome: FinalExecutionOutcomeView {
+                    status,
+                    transaction: msg.signed_transaction.into(),
+                    transaction_outcome,
+                    receipts_outcome,
+                },
+                receipts,
+            },
+            block_height: header.height(),
+            block_hash: *header.hash(),
+        })
+    }
+
     fn get_tx_status(
         &mut self,
         tx_hash: CryptoHash,
@@ -1422,6 +1521,23 @@ impl Handler<WithSpanContext<GetMaintenanceWindows>> for ViewClientActor {
     }
 }
 
+impl Handler<WithSpanContext<SimulateTransaction>> for ViewClientActor {
+    type Result = Result<SimulateTransactionResponse, SimulateTransactionError>;
+
+    #[perf]
+    fn handle(
+        &mut self,
+        msg: WithSpanContext<SimulateTransaction>,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let (_span, msg) = handler_debug_span!(target: "client", msg);
+        let _timer = metrics::VIEW_CLIENT_MESSAGE_TIME
+            .with_label_values(&["SimulateTransaction"])
+            .start_timer();
+        self.simulate_transaction(msg)
+    }
+}
+
 /// Starts the View Client in a new arbiter (thread).
 pub fn start_view_client(
     validator_account_id: Option<AccountId>,
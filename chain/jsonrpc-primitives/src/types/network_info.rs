@@ -8,6 +8,9 @@ pub struct RpcPeerInfo {
     pub id: PeerId,
     pub addr: Option<SocketAddr>,
     pub account_id: Option<AccountId>,
+    /// Reputation score of the peer, see `near_network::types::PeerScore`.
+    #[serde(default)]
+    pub score: i64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
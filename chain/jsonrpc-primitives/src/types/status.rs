@@ -2,7 +2,7 @@ use near_client_primitives::debug::{
     DebugBlockStatusData, EpochInfoView, TrackedShardsView, ValidatorStatus,
 };
 use near_primitives::views::{
-    CatchupStatusView, ChainProcessingInfo, NetworkGraphView, PeerStoreView,
+    CatchupStatusView, ChainProcessingInfo, NetworkGraphView, PeerStatusView, PeerStoreView,
     RequestedStatePartsView, SyncStatusView,
 };
 use serde::{Deserialize, Serialize};
@@ -29,6 +29,8 @@ pub enum DebugStatusResponse {
     // The state parts already requested.
     RequestedStateParts(Vec<RequestedStatePartsView>),
     NetworkGraph(NetworkGraphView),
+    // Status of the connection to a single peer, None if the peer is not connected.
+    Peer(Option<PeerStatusView>),
 }
 
 #[cfg(feature = "debug_types")]
@@ -251,6 +251,15 @@ impl JsonRpcClient {
         call_method(&self.client, &self.server_addr, "EXPERIMENTAL_receipt", request)
     }
 
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_simulate_transaction(
+        &self,
+        request: serde_json::Value,
+    ) -> RpcRequest<near_jsonrpc_primitives::types::transactions::RpcSimulateTransactionResponse>
+    {
+        call_method(&self.client, &self.server_addr, "EXPERIMENTAL_simulate_transaction", request)
+    }
+
     #[allow(non_snake_case)]
     pub fn EXPERIMENTAL_protocol_config(
         &self,
@@ -224,3 +224,32 @@ fn test_check_invalid_tx() {
         }
     });
 }
+
+/// Test simulating a transaction: it must succeed without changing the state,
+/// so simulating it again gives the same result.
+#[test]
+fn test_simulate_transaction() {
+    test_with_client!(test_utils::NodeType::NonValidator, client, async move {
+        let block_hash = client.block(BlockReference::latest()).await.unwrap().header.hash;
+        let signer = InMemorySigner::from_seed("test1".parse().unwrap(), KeyType::ED25519, "test1");
+        let tx = SignedTransaction::send_money(
+            1,
+            "test1".parse().unwrap(),
+            "test2".parse().unwrap(),
+            &signer,
+            100,
+            block_hash,
+        );
+        let request = serde_json::json!({
+            "finality": "optimistic",
+            "signed_tx_base64": to_base64(&tx.try_to_vec().unwrap()),
+        });
+        for _ in 0..2 {
+            let response = client.EXPERIMENTAL_simulate_transaction(request.clone()).await.unwrap();
+            let final_outcome = response.final_execution_outcome.final_outcome;
+            assert_eq!(final_outcome.status, FinalExecutionStatus::SuccessValue(vec![]));
+            assert_eq!(final_outcome.transaction.hash, tx.get_hash());
+            assert_eq!(final_outcome.transaction_outcome.outcome.receipt_ids.len(), 1);
+        }
+    });
+}
@@ -64,6 +64,9 @@ impl RpcFrom<near_network::debug::DebugStatus>
             near_network::debug::DebugStatus::Graph(x) => {
                 near_jsonrpc_primitives::types::status::DebugStatusResponse::NetworkGraph(x)
             }
+            near_network::debug::DebugStatus::Peer(x) => {
+                near_jsonrpc_primitives::types::status::DebugStatusResponse::Peer(x)
+            }
         }
     }
 }
@@ -1,13 +1,18 @@
 use serde_json::Value;
 
-use near_client_primitives::types::TxStatusError;
+use near_client_primitives::types::{
+    SimulateTransactionError, SimulateTransactionResponse, TxStatusError,
+};
 use near_jsonrpc_primitives::errors::RpcParseError;
 use near_jsonrpc_primitives::types::transactions::{
-    RpcBroadcastTransactionRequest, RpcTransactionError, RpcTransactionResponse,
+    RpcBroadcastTransactionRequest, RpcSimulateTransactionError, RpcSimulateTransactionRequest,
+    RpcSimulateTransactionResponse, RpcTransactionError, RpcTransactionResponse,
     RpcTransactionStatusCommonRequest, TransactionInfo,
 };
+use near_primitives::borsh::BorshDeserialize;
 use near_primitives::hash::CryptoHash;
-use near_primitives::types::AccountId;
+use near_primitives::transaction::SignedTransaction;
+use near_primitives::types::{AccountId, BlockReference};
 use near_primitives::views::FinalExecutionOutcomeViewEnum;
 
 use super::{parse_params, parse_signed_transaction, RpcFrom, RpcRequest};
@@ -58,3 +63,73 @@ impl RpcFrom<FinalExecutionOutcomeViewEnum> for RpcTransactionResponse {
         Self { final_execution_outcome }
     }
 }
+
+impl RpcRequest for RpcSimulateTransactionRequest {
+    fn parse(value: Option<Value>) -> Result<Self, RpcParseError> {
+        #[derive(serde::Deserialize)]
+        struct Params {
+            #[serde(flatten)]
+            block_reference: BlockReference,
+            signed_tx_base64: String,
+            #[serde(default)]
+            skip_signature_verification: bool,
+        }
+
+        let params = parse_params::<Params>(value)?;
+        let bytes = near_primitives::serialize::from_base64(&params.signed_tx_base64)
+            .map_err(|err| RpcParseError(err.to_string()))?;
+        let signed_transaction = SignedTransaction::try_from_slice(&bytes)
+            .map_err(|err| RpcParseError(format!("Failed to decode transaction: {}", err)))?;
+        Ok(Self {
+            block_reference: params.block_reference,
+            signed_transaction,
+            verify_signature: !params.skip_signature_verification,
+        })
+    }
+}
+
+impl RpcFrom<actix::MailboxError> for RpcSimulateTransactionError {
+    fn rpc_from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl RpcFrom<SimulateTransactionError> for RpcSimulateTransactionError {
+    fn rpc_from(error: SimulateTransactionError) -> Self {
+        match error {
+            SimulateTransactionError::NoSyncedBlocks => Self::NoSyncedBlocks,
+            SimulateTransactionError::UnknownBlock { block_reference } => {
+                Self::UnknownBlock { block_reference }
+            }
+            SimulateTransactionError::UnavailableShard { requested_shard_id } => {
+                Self::UnavailableShard { requested_shard_id }
+            }
+            SimulateTransactionError::InvalidTransaction(context) => {
+                Self::InvalidTransaction { context }
+            }
+            SimulateTransactionError::LimitExceeded { error_message } => {
+                Self::LimitExceeded { error_message }
+            }
+            SimulateTransactionError::InternalError { error_message } => {
+                Self::InternalError { error_message }
+            }
+            SimulateTransactionError::Unreachable { ref error_message } => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcSimulateTransactionError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
+impl RpcFrom<SimulateTransactionResponse> for RpcSimulateTransactionResponse {
+    fn rpc_from(response: SimulateTransactionResponse) -> Self {
+        Self {
+            final_execution_outcome: response.final_execution_outcome,
+            block_height: response.block_height,
+            block_hash: response.block_hash,
+        }
+    }
+}
@@ -21,7 +21,8 @@ use near_client::{
     ClientActor, DebugStatus, GetBlock, GetBlockProof, GetChunk, GetExecutionOutcome, GetGasPrice,
     GetMaintenanceWindows, GetNetworkInfo, GetNextLightClientBlock, GetProtocolConfig, GetReceipt,
     GetStateChanges, GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered,
-    ProcessTxRequest, ProcessTxResponse, Query, Status, TxStatus, ViewClientActor,
+    ProcessTxRequest, ProcessTxResponse, Query, SimulateTransaction, Status, TxStatus,
+    ViewClientActor,
 };
 pub use near_jsonrpc_client as client;
 use near_jsonrpc_primitives::errors::RpcError;
@@ -29,6 +30,7 @@ use near_jsonrpc_primitives::message::{Message, Request};
 use near_jsonrpc_primitives::types::config::RpcProtocolConfigResponse;
 use near_o11y::metrics::{prometheus, Encoder, TextEncoder};
 use near_primitives::hash::CryptoHash;
+use near_primitives::network::PeerId;
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::{AccountId, BlockHeight};
 use near_primitives::views::FinalExecutionOutcomeViewEnum;
@@ -340,6 +342,9 @@ impl JsonRpcHandler {
             "EXPERIMENTAL_receipt" => {
                 process_method_call(request, |params| self.receipt(params)).await
             }
+            "EXPERIMENTAL_simulate_transaction" => {
+                process_method_call(request, |params| self.simulate_transaction(params)).await
+            }
             "EXPERIMENTAL_tx_status" => {
                 process_method_call(request, |params| self.tx_status_common(params, true)).await
             }
@@ -830,6 +835,26 @@ impl JsonRpcHandler {
         }
     }
 
+    pub async fn debug_peer_status(
+        &self,
+        peer_id: PeerId,
+    ) -> Result<
+        Option<near_jsonrpc_primitives::types::status::RpcDebugStatusResponse>,
+        near_jsonrpc_primitives::types::status::RpcStatusError,
+    > {
+        if self.enable_debug_rpc {
+            let debug_status = self
+                .peer_manager_send(near_network::debug::GetDebugStatus::Peer(peer_id))
+                .await?
+                .rpc_into();
+            return Ok(Some(near_jsonrpc_primitives::types::status::RpcDebugStatusResponse {
+                status_response: debug_status,
+            }));
+        } else {
+            return Ok(None);
+        }
+    }
+
     pub async fn protocol_config(
         &self,
         request_data: near_jsonrpc_primitives::types::config::RpcProtocolConfigRequest,
@@ -855,6 +880,30 @@ impl JsonRpcHandler {
         Ok(query_response.rpc_into())
     }
 
+    /// Applies the transaction and all the receipts it produces on top of the
+    /// state at the given block without committing any changes.
+    async fn simulate_transaction(
+        &self,
+        request_data: near_jsonrpc_primitives::types::transactions::RpcSimulateTransactionRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::transactions::RpcSimulateTransactionResponse,
+        near_jsonrpc_primitives::types::transactions::RpcSimulateTransactionError,
+    > {
+        let near_jsonrpc_primitives::types::transactions::RpcSimulateTransactionRequest {
+            block_reference,
+            signed_transaction,
+            verify_signature,
+        } = request_data;
+        let response = self
+            .view_client_send(SimulateTransaction {
+                block_reference,
+                signed_transaction,
+                verify_signature,
+            })
+            .await?;
+        Ok(response.rpc_into())
+    }
+
     async fn tx_status_common(
         &self,
         request_data: near_jsonrpc_primitives::types::transactions::RpcTransactionStatusCommonRequest,
@@ -1347,6 +1396,22 @@ async fn debug_block_status_handler(
     }
 }
 
+async fn debug_peer_status_handler(
+    path: web::Path<PeerId>,
+    handler: web::Data<JsonRpcHandler>,
+) -> Result<HttpResponse, HttpError> {
+    match handler.debug_peer_status(path.into_inner()).await {
+        Ok(Some(value)) => match &value.status_response {
+            near_jsonrpc_primitives::types::status::DebugStatusResponse::Peer(None) => {
+                Ok(HttpResponse::NotFound().finish())
+            }
+            _ => Ok(HttpResponse::Ok().json(&value)),
+        },
+        Ok(None) => Ok(HttpResponse::MethodNotAllowed().finish()),
+        Err(_) => Ok(HttpResponse::ServiceUnavailable().finish()),
+    }
+}
+
 fn health_handler(
     handler: web::Data<JsonRpcHandler>,
 ) -> impl Future<Output = Result<HttpResponse, HttpError>> {
@@ -1501,6 +1566,10 @@ pub fn start_http(
                 web::resource("/debug/api/block_status/{starting_height}")
                     .route(web::get().to(debug_block_status_handler)),
             )
+            .service(
+                web::resource("/debug/api/peer/{peer_id}")
+                    .route(web::get().to(debug_peer_status_handler)),
+            )
             .service(debug_html)
             .service(display_debug_html)
     })
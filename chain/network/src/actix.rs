@@ -1,3 +1,6 @@
+use crate::time;
+use actix::fut::ActorFutureExt as _;
+use actix::AsyncContext as _;
 use anyhow::anyhow;
 
 // A system thread which is joined on drop.
@@ -23,10 +26,11 @@ impl Drop for Thread {
 
 pub struct ActixSystem<A: actix::Actor> {
     pub addr: actix::Addr<A>,
-    system: actix::System,
+    // None if the actor runs within the system of the current thread.
+    system: Option<actix::System>,
     // dropping _thread has a side effect of joining the system thread.
     // Still, linter considers it a dead_code, so "_" is needed to silence it.
-    _thread: Thread,
+    _thread: Option<Thread>,
 }
 
 impl<A: actix::Actor> ActixSystem<A> {
@@ -44,12 +48,40 @@ impl<A: actix::Actor> ActixSystem<A> {
             Ok(())
         });
         let (system, addr) = recv.await.unwrap();
-        Self { addr, system, _thread: thread }
+        Self { addr, system: Some(system), _thread: Some(thread) }
+    }
+
+    /// Starts the actor within the actix system of the current thread, rather than
+    /// in a new one. The system is not stopped on drop.
+    pub fn local<F: FnOnce() -> actix::Addr<A>>(f: F) -> Self {
+        Self { addr: f(), system: None, _thread: None }
     }
 }
 
 impl<A: actix::Actor> Drop for ActixSystem<A> {
     fn drop(&mut self) {
-        self.system.stop();
+        if let Some(system) = &self.system {
+            system.stop();
+        }
     }
 }
+
+/// Calls `f` on the actor after `d` has elapsed on `clock`.
+/// In contrast to `near_performance_metrics::actix::run_later`, the delay is measured
+/// by `clock`, so that it can be controlled in tests with a `time::FakeClock`.
+pub(crate) fn run_later<A, F>(
+    ctx: &mut actix::Context<A>,
+    clock: &time::Clock,
+    d: time::Duration,
+    f: F,
+) -> actix::SpawnHandle
+where
+    A: actix::Actor<Context = actix::Context<A>>,
+    F: 'static + FnOnce(&mut A, &mut actix::Context<A>),
+{
+    let clock = clock.clone();
+    ctx.spawn(
+        actix::fut::wrap_future(async move { clock.sleep(d).await })
+            .map(move |(), act, ctx| f(act, ctx)),
+    )
+}
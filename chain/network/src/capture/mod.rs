@@ -0,0 +1,367 @@
+//! Capture and replay of the raw network traffic, for debugging protocol issues.
+//!
+//! When enabled (see `experimental.capture` in the network config), every
+//! PeerActor writes the messages it sends and receives, exactly as they were
+//! encoded on the wire, to `<dir>/<peer_id>/*.capture` files.  A file is
+//! rotated once it grows over `max_file_size`, and only the most recent
+//! `max_files` files are kept for each peer.  Messages exchanged before the
+//! identity of the peer is known (i.e. the handshake on an inbound
+//! connection) are not captured.
+//!
+//! The files are written by a single thread shared by all the connections
+//! (owned by NetworkState), so that disk I/O never blocks the PeerActors.
+//! Records are passed to it over a bounded channel; when the thread can't keep
+//! up, new records are dropped and counted in the
+//! `near_capture_dropped_records_total` metric.  The files are flushed every
+//! `FLUSH_INTERVAL` rather than after every record, and closed once the
+//! connection is closed.
+//!
+//! A capture file is a sequence of borsh-encoded [`Record`]s, each prefixed
+//! with its length as a little-endian u32.  Captures can be loaded with
+//! [`read_dir`] and fed into a node with [`replay`].
+use crate::network_protocol::{Encoding, PeerMessage};
+use crate::raw;
+use crate::stats::metrics;
+use crate::time;
+use anyhow::Context as _;
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_primitives::network::PeerId;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+#[cfg(test)]
+mod tests;
+
+const FILE_EXTENSION: &str = "capture";
+/// Number of records waiting to be written, above which new records are dropped.
+/// The channel is shared by all the connections.
+const CHANNEL_CAPACITY: usize = 4096;
+/// How often the writer thread flushes the capture file.
+const FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+fn default_max_file_size() -> bytesize::ByteSize {
+    bytesize::ByteSize::mib(100)
+}
+
+fn default_max_files() -> usize {
+    10
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// Directory to write the captures to.  A relative path is resolved
+    /// against the node's home directory.
+    pub dir: PathBuf,
+    /// Size above which a capture file is rotated.
+    #[serde(default = "default_max_file_size")]
+    pub max_file_size: bytesize::ByteSize,
+    /// Number of capture files to keep per peer.
+    #[serde(default = "default_max_files")]
+    pub max_files: usize,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Message received from the peer.
+    Inbound,
+    /// Message sent to the peer.
+    Outbound,
+}
+
+/// A single captured message.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// UNIX timestamp in nanos at which the message was sent or received.
+    pub timestamp: u64,
+    pub direction: Direction,
+    pub encoding: Encoding,
+    /// Message as encoded on the wire.
+    pub data: Vec<u8>,
+}
+
+impl Record {
+    pub fn new(
+        clock: &time::Clock,
+        direction: Direction,
+        encoding: Encoding,
+        data: Vec<u8>,
+    ) -> Self {
+        Self { timestamp: clock.now_utc().unix_timestamp_nanos() as u64, direction, encoding, data }
+    }
+
+    pub fn time(&self) -> time::Utc {
+        time::Utc::from_unix_timestamp_nanos(self.timestamp as i128).unwrap()
+    }
+
+    /// Decodes the captured message.
+    pub fn message(&self) -> anyhow::Result<PeerMessage> {
+        PeerMessage::deserialize(self.encoding, &self.data).map_err(|err| anyhow::anyhow!("{err}"))
+    }
+}
+
+/// Commands processed by the writer thread.
+enum Command {
+    Record(PeerId, Record),
+    /// Closes the capture file of the peer.
+    Close(PeerId),
+}
+
+/// Captures the traffic of all the connections.
+///
+/// Records are written by a background thread, which exits once the `Writer`
+/// is dropped and all the records sent before have been written.
+pub(crate) struct Writer {
+    sender: Option<mpsc::SyncSender<Command>>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl Writer {
+    pub fn new(cfg: Config) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(CHANNEL_CAPACITY);
+        let thread = std::thread::Builder::new()
+            .name("capture".to_string())
+            .spawn(move || FileWriter::new(cfg).run(receiver))
+            .expect("failed to spawn the capture thread");
+        Self { sender: Some(sender), thread: Some(thread) }
+    }
+
+    fn send(&self, peer_id: &PeerId, cmd: Command) {
+        let sender = self.sender.as_ref().unwrap();
+        match sender.try_send(cmd) {
+            Ok(()) => {}
+            Err(mpsc::TrySendError::Full(Command::Record(..))) => {
+                metrics::CAPTURE_DROPPED_RECORDS.inc()
+            }
+            Err(mpsc::TrySendError::Full(Command::Close(..))) => {
+                tracing::debug!(target: "network", %peer_id, "capture thread is lagging, capture file left open");
+            }
+            Err(mpsc::TrySendError::Disconnected(_)) => {
+                tracing::warn!(target: "network", %peer_id, "capture thread has stopped");
+            }
+        }
+    }
+
+    /// Captures a message sent to or received from the given peer.
+    /// Never blocks: if the writer thread is lagging behind, the message is
+    /// dropped instead.
+    pub fn record(
+        &self,
+        clock: &time::Clock,
+        peer_id: &PeerId,
+        direction: Direction,
+        encoding: Encoding,
+        data: &[u8],
+    ) {
+        let record = Record::new(clock, direction, encoding, data.to_vec());
+        self.send(peer_id, Command::Record(peer_id.clone(), record));
+    }
+
+    /// Closes the capture file of the peer, once the records sent before are
+    /// written.  Called when the connection is closed, so that the number of
+    /// open files is bounded by the number of connections.  Closing is best
+    /// effort as well: if the channel is full, the file is closed once the
+    /// writer is dropped.
+    pub fn close_peer(&self, peer_id: &PeerId) {
+        self.send(peer_id, Command::Close(peer_id.clone()));
+    }
+
+    /// Waits until all the recorded messages are written and flushed.
+    pub fn close(mut self) {
+        self.sender = None;
+        if let Some(thread) = self.thread.take() {
+            thread.join().expect("capture thread panicked");
+        }
+    }
+}
+
+impl Drop for Writer {
+    fn drop(&mut self) {
+        // Dropping the sender makes the thread exit, there's no need to wait for it.
+        self.sender = None;
+    }
+}
+
+/// Writes the records of all the connections to the capture files.
+struct FileWriter {
+    cfg: Config,
+    /// Currently open capture file of each peer and the number of bytes written to it.
+    files: HashMap<PeerId, (BufWriter<File>, u64)>,
+}
+
+impl FileWriter {
+    fn new(cfg: Config) -> Self {
+        Self { cfg, files: HashMap::new() }
+    }
+
+    fn run(mut self, receiver: mpsc::Receiver<Command>) {
+        let mut last_flush = std::time::Instant::now();
+        loop {
+            match receiver.recv_timeout(FLUSH_INTERVAL) {
+                Ok(Command::Record(peer_id, record)) => self.record(&peer_id, &record),
+                Ok(Command::Close(peer_id)) => {
+                    if let Some((mut file, _)) = self.files.remove(&peer_id) {
+                        Self::flush_file(&peer_id, &mut file);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+            if last_flush.elapsed() >= FLUSH_INTERVAL {
+                self.flush();
+                last_flush = std::time::Instant::now();
+            }
+        }
+        self.flush();
+    }
+
+    /// Failures are logged and otherwise ignored, so that capture never
+    /// affects the connection.
+    fn record(&mut self, peer_id: &PeerId, record: &Record) {
+        if let Err(err) = self.try_record(peer_id, record) {
+            tracing::warn!(target: "network", %peer_id, ?err, "failed to capture a message");
+            // Start a new file on the next attempt.
+            self.files.remove(peer_id);
+        }
+    }
+
+    fn flush_file(peer_id: &PeerId, file: &mut BufWriter<File>) -> bool {
+        if let Err(err) = file.flush() {
+            tracing::warn!(target: "network", %peer_id, ?err, "failed to flush a capture file");
+            return false;
+        }
+        true
+    }
+
+    fn flush(&mut self) {
+        self.files.retain(|peer_id, (file, _)| Self::flush_file(peer_id, file));
+    }
+
+    fn try_record(&mut self, peer_id: &PeerId, record: &Record) -> io::Result<()> {
+        let data = record.try_to_vec()?;
+        let len = 4 + data.len() as u64;
+        let rotate = match self.files.get(peer_id) {
+            None => true,
+            Some((_, written)) => *written > 0 && written + len > self.cfg.max_file_size.as_u64(),
+        };
+        if rotate {
+            if let Some((mut file, _)) = self.files.remove(peer_id) {
+                Self::flush_file(peer_id, &mut file);
+            }
+            let file = self.rotate(peer_id, record.timestamp)?;
+            self.files.insert(peer_id.clone(), (file, 0));
+        }
+        let (file, written) = self.files.get_mut(peer_id).unwrap();
+        file.write_all(&(data.len() as u32).to_le_bytes())?;
+        file.write_all(&data)?;
+        *written += len;
+        Ok(())
+    }
+
+    /// Creates a new capture file, named after the timestamp of its first
+    /// record, and removes the old ones above the limit.
+    fn rotate(&mut self, peer_id: &PeerId, nanos: u64) -> io::Result<BufWriter<File>> {
+        let dir = self.cfg.dir.join(peer_id.to_string());
+        std::fs::create_dir_all(&dir)?;
+        // Names are zero-padded, so that lexicographical order of the files is chronological.
+        let mut seq = 0;
+        let file = loop {
+            let path = dir.join(format!("{nanos:020}-{seq:04}.{FILE_EXTENSION}"));
+            match File::options().write(true).create_new(true).open(path) {
+                Ok(file) => break file,
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => seq += 1,
+                Err(err) => return Err(err),
+            }
+        };
+        let files = list_files(&dir)?;
+        for path in &files[..files.len().saturating_sub(self.cfg.max_files.max(1))] {
+            std::fs::remove_file(path)?;
+        }
+        Ok(BufWriter::new(file))
+    }
+}
+
+/// Lists capture files in the directory, in chronological order.
+fn list_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().map_or(false, |ext| ext == FILE_EXTENSION) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Reads all records from a capture file.
+pub fn read_file(path: &Path) -> anyhow::Result<Vec<Record>> {
+    let mut data = vec![];
+    File::open(path)?.read_to_end(&mut data)?;
+    let mut buf = &data[..];
+    let mut records = vec![];
+    while !buf.is_empty() {
+        let len = buf.get(..4).context("truncated record length")?;
+        let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+        let record = buf.get(4..4 + len).context("truncated record")?;
+        records.push(Record::try_from_slice(record)?);
+        buf = &buf[4 + len..];
+    }
+    Ok(records)
+}
+
+/// Reads all records from a capture of a single peer, i.e. from all the
+/// capture files in the directory, in chronological order.
+pub fn read_dir(dir: &Path) -> anyhow::Result<Vec<Record>> {
+    let mut records = vec![];
+    for path in list_files(dir)? {
+        records.extend(read_file(&path).with_context(|| format!("{}", path.display()))?);
+    }
+    Ok(records)
+}
+
+/// Sends the inbound messages of a capture over the connection, preserving
+/// the original intervals between them.  Returns the number of sent messages.
+///
+/// Handshake messages are skipped, since `conn` has already performed its own
+/// handshake.  Note that routed messages are signed by their authors and
+/// addressed to the node which captured them, so the node under test will
+/// route them further rather than process them itself, unless it runs with the
+/// same node key.  Messages received over `conn` in the meantime are dropped.
+pub async fn replay(
+    clock: &time::Clock,
+    conn: &mut raw::Connection,
+    records: &[Record],
+) -> anyhow::Result<usize> {
+    let start = clock.now();
+    let first = match records.first() {
+        Some(record) => record.time(),
+        None => return Ok(0),
+    };
+    let mut sent = 0;
+    for record in records {
+        if record.direction != Direction::Inbound {
+            continue;
+        }
+        let msg = record.message()?;
+        if matches!(msg, PeerMessage::Handshake(_) | PeerMessage::HandshakeFailure(..)) {
+            continue;
+        }
+        let deadline = start + (record.time() - first);
+        loop {
+            tokio::select! {
+                _ = clock.sleep_until(deadline) => break,
+                res = conn.recv_message() => match res {
+                    Ok(_) => {}
+                    Err(err) if err.kind() == io::ErrorKind::TimedOut => {}
+                    Err(err) => return Err(err).context("recv_message()"),
+                },
+            }
+        }
+        conn.send_message(&msg).await.context("send_message()")?;
+        sent += 1;
+    }
+    Ok(sent)
+}
@@ -0,0 +1,193 @@
+use crate::capture::*;
+use crate::network_protocol::testonly as data;
+use crate::peer_manager::peer_manager_actor::Event as PME;
+use crate::peer_manager::testonly::Event;
+use crate::testonly::make_rng;
+use near_crypto::{KeyType, SecretKey};
+use near_o11y::testonly::init_test_logger;
+use std::sync::Arc;
+
+fn make_peer_id() -> PeerId {
+    PeerId::new(SecretKey::from_random(KeyType::ED25519).public_key())
+}
+
+#[test]
+fn writer_rotates_files() {
+    let clock = time::FakeClock::default();
+    let dir = tempfile::tempdir().unwrap();
+    let peer_id = make_peer_id();
+    let writer = Writer::new(Config {
+        dir: dir.path().to_path_buf(),
+        max_file_size: bytesize::ByteSize::b(100),
+        max_files: 3,
+    });
+    let mut want = vec![];
+    for i in 0..10u8 {
+        let direction = if i % 2 == 0 { Direction::Inbound } else { Direction::Outbound };
+        let data = vec![i; 60];
+        writer.record(&clock.clock(), &peer_id, direction, Encoding::Proto, &data);
+        want.push(Record::new(&clock.clock(), direction, Encoding::Proto, data));
+        clock.advance(time::Duration::seconds(1));
+    }
+    writer.close();
+    // Every record exceeds half of max_file_size, so each one is written to a separate file.
+    let peer_dir = dir.path().join(peer_id.to_string());
+    assert_eq!(3, list_files(&peer_dir).unwrap().len());
+    assert_eq!(want[7..], read_dir(&peer_dir).unwrap()[..]);
+}
+
+#[test]
+fn writer_captures_multiple_peers() {
+    let clock = time::FakeClock::default();
+    let dir = tempfile::tempdir().unwrap();
+    let peers: Vec<_> = (0..3).map(|_| make_peer_id()).collect();
+    let writer = Writer::new(Config {
+        dir: dir.path().to_path_buf(),
+        max_file_size: bytesize::ByteSize::mib(1),
+        max_files: 10,
+    });
+    for (i, peer_id) in peers.iter().enumerate() {
+        writer.record(&clock.clock(), peer_id, Direction::Inbound, Encoding::Proto, &[i as u8]);
+    }
+    // A closed file is not reopened, records after closing go to a new file.
+    writer.close_peer(&peers[0]);
+    clock.advance(time::Duration::seconds(1));
+    writer.record(&clock.clock(), &peers[0], Direction::Outbound, Encoding::Proto, b"again");
+    writer.close();
+    for (i, peer_id) in peers.iter().enumerate() {
+        let peer_dir = dir.path().join(peer_id.to_string());
+        let want_files = if i == 0 { 2 } else { 1 };
+        assert_eq!(want_files, list_files(&peer_dir).unwrap().len());
+        assert_eq!(vec![i as u8], read_dir(&peer_dir).unwrap()[0].data);
+    }
+}
+
+#[test]
+fn read_truncated_file() {
+    let clock = time::FakeClock::default();
+    let dir = tempfile::tempdir().unwrap();
+    let peer_id = make_peer_id();
+    let writer = Writer::new(Config {
+        dir: dir.path().to_path_buf(),
+        max_file_size: bytesize::ByteSize::mib(1),
+        max_files: 1,
+    });
+    writer.record(&clock.clock(), &peer_id, Direction::Inbound, Encoding::Borsh, b"message");
+    writer.close();
+    let path = list_files(&dir.path().join(peer_id.to_string())).unwrap().pop().unwrap();
+    assert_eq!(1, read_file(&path).unwrap().len());
+    let len = std::fs::metadata(&path).unwrap().len();
+    File::options().write(true).open(&path).unwrap().set_len(len - 1).unwrap();
+    assert!(read_file(&path).is_err());
+}
+
+#[test]
+fn writer_drops_records_when_lagging() {
+    let clock = time::FakeClock::default();
+    let dir = tempfile::tempdir().unwrap();
+    let peer_id = make_peer_id();
+    let writer = Writer::new(Config {
+        dir: dir.path().to_path_buf(),
+        max_file_size: bytesize::ByteSize::mib(100),
+        max_files: 1,
+    });
+    let dropped_before = metrics::CAPTURE_DROPPED_RECORDS.get();
+    let total = 10 * CHANNEL_CAPACITY;
+    for _ in 0..total {
+        writer.record(&clock.clock(), &peer_id, Direction::Inbound, Encoding::Borsh, &[0; 1000]);
+    }
+    writer.close();
+    // Recording never blocks, every record is either written or counted as dropped.
+    let written = read_dir(&dir.path().join(peer_id.to_string())).unwrap().len();
+    let dropped = (metrics::CAPTURE_DROPPED_RECORDS.get() - dropped_before) as usize;
+    assert!(written >= CHANNEL_CAPACITY.min(total));
+    assert_eq!(total, written + dropped);
+}
+
+#[tokio::test]
+async fn capture_and_replay() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 5));
+    let dir = tempfile::tempdir().unwrap();
+
+    let mut cfg = chain.make_config(rng);
+    cfg.capture = Some(Config {
+        dir: dir.path().to_path_buf(),
+        max_file_size: bytesize::ByteSize::mib(1),
+        max_files: 1,
+    });
+    let peer_id = cfg.node_id();
+    let addr = cfg.node_addr.unwrap();
+    let mut pm = crate::peer_manager::testonly::start(
+        clock.clock(),
+        near_store::db::TestDB::new(),
+        cfg,
+        chain.clone(),
+    )
+    .await;
+
+    tracing::info!(target:"test", "capture the blocks sent to the node");
+    let records: Vec<_> = (chain.blocks.iter().enumerate())
+        .map(|(i, block)| Record {
+            timestamp: i as u64 * 20_000_000,
+            direction: Direction::Inbound,
+            encoding: Encoding::Borsh,
+            data: PeerMessage::Block(block.clone()).serialize(Encoding::Borsh),
+        })
+        .collect();
+    let mut conn = raw::Connection::connect(
+        addr,
+        peer_id,
+        None,
+        &chain.genesis_id.chain_id,
+        chain.genesis_id.hash,
+        0,
+        time::Duration::SECOND,
+    )
+    .await
+    .unwrap();
+    let real_clock = time::Clock::real();
+    let start = real_clock.now();
+    assert_eq!(records.len(), replay(&real_clock, &mut conn, &records).await.unwrap());
+    assert!(real_clock.now() - start >= time::Duration::milliseconds(80));
+
+    tracing::info!(target:"test", "wait for the blocks to be processed in order");
+    for block in &chain.blocks {
+        let got = pm
+            .events
+            .recv_until(|ev| match ev {
+                Event::PeerManager(PME::MessageProcessed(PeerMessage::Block(b))) => Some(b),
+                _ => None,
+            })
+            .await;
+        assert_eq!(block.hash(), got.hash());
+    }
+
+    tracing::info!(target:"test", "the node has captured the replayed blocks");
+    let peer_dirs: Vec<_> =
+        std::fs::read_dir(dir.path()).unwrap().map(|e| e.unwrap().path()).collect();
+    assert_eq!(1, peer_dirs.len());
+    let want: Vec<_> = chain.blocks.iter().map(|b| *b.hash()).collect();
+    // The capture files are flushed periodically by the writer thread.
+    let deadline = real_clock.now() + time::Duration::seconds(10);
+    loop {
+        // The last record may be partially flushed, so the file may not be readable yet.
+        let captured: Vec<_> = read_dir(&peer_dirs[0])
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|r| r.direction == Direction::Inbound)
+            .filter_map(|r| match r.message().unwrap() {
+                PeerMessage::Block(b) => Some(*b.hash()),
+                _ => None,
+            })
+            .collect();
+        if captured == want {
+            break;
+        }
+        assert!(real_clock.now() < deadline, "captured {captured:?}, want {want:?}");
+        real_clock.sleep(time::Duration::milliseconds(100)).await;
+    }
+}
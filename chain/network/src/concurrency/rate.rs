@@ -1,3 +1,5 @@
+use crate::time;
+
 /// Config of a rate limiter algorithm, which behaves like a semaphore
 /// - with maximal capacity `burst`
 /// - with a new ticket added automatically every 1/qps seconds (qps stands for "queries per
@@ -25,3 +27,36 @@ impl Limit {
         Ok(())
     }
 }
+
+/// Token bucket enforcing a `Limit`, where a single token is an arbitrary unit
+/// (for example a byte).
+///
+/// Requests are never rejected. Instead the bucket goes into debt and the
+/// caller is told how long to wait before using the tokens: a request may
+/// proceed as soon as the bucket would hold at least `min(n, burst)` tokens,
+/// where `min` makes requests for more than `burst` tokens satisfiable.
+/// Since the debt accumulates, waits of consecutive requests are non-decreasing.
+pub(crate) struct TokenBucket {
+    limit: Limit,
+    tokens: f64,
+    updated: time::Instant,
+}
+
+impl TokenBucket {
+    /// Constructs a full bucket.
+    pub fn new(limit: Limit, now: time::Instant) -> Self {
+        Self { limit, tokens: limit.burst as f64, updated: now }
+    }
+
+    /// Takes `n` tokens from the bucket and returns how long the caller has to
+    /// wait before using them.
+    pub fn reserve(&mut self, now: time::Instant, n: u64) -> time::Duration {
+        let elapsed = (now - self.updated).max(time::Duration::ZERO);
+        let burst = self.limit.burst as f64;
+        self.tokens = (self.tokens + elapsed.as_seconds_f64() * self.limit.qps).min(burst);
+        self.updated = now;
+        let missing = ((n as f64).min(burst) - self.tokens).max(0.);
+        self.tokens -= n as f64;
+        time::Duration::seconds_f64(missing / self.limit.qps)
+    }
+}
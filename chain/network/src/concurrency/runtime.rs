@@ -19,12 +19,22 @@ impl Runtime {
         });
         Self { handle, stop, thread: Some(thread) }
     }
+
+    /// Runtime backed by the runtime of the current thread, rather than a dedicated one.
+    /// Futures spawned on it are not stopped on drop.
+    pub fn current() -> Self {
+        let handle = tokio::runtime::Handle::current();
+        Self { handle, stop: Arc::new(tokio::sync::Notify::new()), thread: None }
+    }
 }
 
 impl Drop for Runtime {
     fn drop(&mut self) {
         self.stop.notify_one();
-        let thread = self.thread.take().unwrap();
+        let thread = match self.thread.take() {
+            Some(thread) => thread,
+            None => return,
+        };
         // Await for the thread to stop, unless it is the current thread
         // (i.e. nobody waits for it).
         if std::thread::current().id() != thread.thread().id() {
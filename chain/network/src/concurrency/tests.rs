@@ -2,6 +2,7 @@ use crate::concurrency::arc_mutex::ArcMutex;
 use crate::concurrency::demux;
 use crate::concurrency::rate;
 use crate::concurrency::rayon;
+use crate::time;
 
 // drop a trivial future without completion => panic (in debug mode at least).
 #[tokio::test]
@@ -84,3 +85,23 @@ fn test_arc_mutex() {
     // After update, load() should return the new value.
     assert_eq!(v2, *m.load());
 }
+
+#[test]
+fn test_token_bucket() {
+    let clock = time::FakeClock::default();
+    let mut tb = rate::TokenBucket::new(rate::Limit { qps: 1., burst: 10 }, clock.now());
+    // A full bucket lets through up to `burst` tokens at once.
+    assert_eq!(time::Duration::ZERO, tb.reserve(clock.now(), 6));
+    assert_eq!(time::Duration::ZERO, tb.reserve(clock.now(), 4));
+    assert_eq!(time::Duration::seconds(1), tb.reserve(clock.now(), 1));
+    // Tokens are refilled at `qps` rate, paying off the debt first.
+    clock.advance(time::Duration::seconds(3));
+    assert_eq!(time::Duration::seconds(1), tb.reserve(clock.now(), 3));
+    // Refill is capped at `burst`.
+    clock.advance(time::Duration::seconds(100));
+    assert_eq!(time::Duration::ZERO, tb.reserve(clock.now(), 10));
+    // Requests larger than `burst` wait until the bucket is full,
+    // and have to be paid off before the next request.
+    assert_eq!(time::Duration::seconds(10), tb.reserve(clock.now(), 30));
+    assert_eq!(time::Duration::seconds(31), tb.reserve(clock.now(), 1));
+}
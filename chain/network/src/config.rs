@@ -1,10 +1,14 @@
 use crate::blacklist;
+use crate::capture;
 use crate::concurrency::rate;
+use crate::dns_seed;
 use crate::network_protocol::PeerAddr;
 use crate::network_protocol::PeerInfo;
+use crate::network_protocol::PeerMessage;
 use crate::peer_manager::peer_manager_actor::Event;
 use crate::peer_manager::peer_store;
 use crate::sink::Sink;
+use crate::tcp;
 use crate::time;
 use crate::types::ROUTED_MESSAGE_TTL;
 use anyhow::Context;
@@ -12,6 +16,7 @@ use near_crypto::{KeyType, SecretKey};
 use near_primitives::network::PeerId;
 use near_primitives::types::AccountId;
 use near_primitives::validator_signer::{InMemoryValidatorSigner, ValidatorSigner};
+use std::collections::HashMap;
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
 use std::sync::Arc;
 
@@ -28,6 +33,43 @@ pub const MAX_PEER_ADDRS: usize = 10;
 // TODO(gprusak): turn into a proper struct implementing Display and FromStr.
 pub type StunServerAddr = String;
 
+/// Address of a static proxy, of the format "<PeerId>@<domain/ip>:<port>".
+/// The address is resolved every time the proxy is connected to, so that
+/// proxies changing their IPs are handled without restarting the node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProxyAddr {
+    pub addr: String,
+    pub peer_id: PeerId,
+}
+
+impl From<PeerAddr> for ProxyAddr {
+    fn from(x: PeerAddr) -> Self {
+        Self { addr: x.addr.to_string(), peer_id: x.peer_id }
+    }
+}
+
+impl std::str::FromStr for ProxyAddr {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (peer_id, addr) =
+            s.split_once('@').with_context(|| format!("expected <PeerId>@<addr>, got '{s}'"))?;
+        anyhow::ensure!(addr.contains(':'), "expected <domain/ip>:<port>, got '{addr}'");
+        Ok(Self { addr: addr.to_string(), peer_id: PeerId::new(peer_id.parse()?) })
+    }
+}
+
+impl serde::Serialize for ProxyAddr {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&format!("{}@{}", self.peer_id, self.addr))
+    }
+}
+
+impl<'a> serde::Deserialize<'a> for ProxyAddr {
+    fn deserialize<D: serde::Deserializer<'a>>(d: D) -> Result<Self, D::Error> {
+        <String as serde::Deserialize>::deserialize(d)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 /// ValidatorProxies are nodes with public IP (aka proxies) that this validator trusts to be honest
 /// and willing to forward traffic to this validator. Whenever this node is a TIER1 validator
 /// (i.e. whenever it is a block producer/chunk producer/approver for the given epoch),
@@ -49,7 +91,7 @@ pub type StunServerAddr = String;
 ///   resolve the public IP.
 #[derive(Clone)]
 pub enum ValidatorProxies {
-    Static(Vec<PeerAddr>),
+    Static(Vec<ProxyAddr>),
     Dynamic(Vec<StunServerAddr>),
 }
 
@@ -70,6 +112,12 @@ pub struct Tier1 {
     /// Interval between broacasts of the list of validator's proxies.
     /// Before the broadcast, validator tries to establish all the missing connections to proxies.
     pub advertise_proxies_interval: time::Duration,
+    /// Interval between health checks of the validator's proxies. Static proxies are checked by
+    /// connecting to them, dynamic proxy (i.e. this node's public address) is re-resolved via
+    /// STUN. The list of proxies is re-broadcasted whenever the set of healthy proxies changes.
+    pub check_proxies_interval: time::Duration,
+    /// Whether to close the unencrypted connections to TIER1 peers.
+    pub require_encryption: bool,
 }
 
 /// Validated configuration for the peer-to-peer manager.
@@ -132,6 +180,9 @@ pub struct NetworkConfig {
     pub accounts_data_broadcast_rate_limit: rate::Limit,
     /// Maximal rate at which RoutingTable can be recomputed.
     pub routing_table_update_rate_limit: rate::Limit,
+    /// Maximal upload rate (in bytes per second) per message type, for every connection.
+    /// Keys are the values of `PeerMessage::msg_variant()`.
+    pub send_bandwidth_limits: HashMap<String, rate::Limit>,
     /// Config of the TIER1 network.
     pub tier1: Option<Tier1>,
 
@@ -142,10 +193,28 @@ pub struct NetworkConfig {
     //   * ignoring received deleted edges as well
     pub skip_tombstones: Option<time::Duration>,
 
+    /// If set, the traffic with every peer is captured to files, see `crate::capture`.
+    pub capture: Option<capture::Config>,
+    /// If set, peers are discovered through DNS seeds, see `crate::dns_seed`.
+    pub dns_seeds: Option<dns_seed::Config>,
+    /// Maximal number of connections with metrics labelled by peer,
+    /// see `crate::stats::metrics::PeerMetrics`. 0 disables them.
+    pub per_peer_metrics_limit: usize,
+
     /// TEST-ONLY
     /// TODO(gprusak): make it pub(crate), once all integration tests
     /// are merged into near_network.
     pub event_sink: Sink<Event>,
+    /// Transport used for listening and establishing connections.
+    /// Always TCP outside of tests.
+    pub(crate) transport: tcp::Transport,
+    /// Arbiter on which the actors and background tasks of the node are run.
+    /// It has to be the arbiter of the thread which starts the node.
+    /// If not set, they get dedicated threads, which is always the case outside of tests.
+    pub(crate) arbiter: Option<actix::ArbiterHandle>,
+    /// Seed of the RNG used for the random choices of the node (which peers to connect to,
+    /// which connection to drop, etc.). Chosen randomly if not set.
+    pub(crate) rng_seed: Option<u64>,
 }
 
 impl NetworkConfig {
@@ -165,7 +234,11 @@ impl NetworkConfig {
             anyhow::bail!("you cannot specify both public_addrs and trusted_stun_servers");
         }
         for proxy in &cfg.public_addrs {
-            let ip = proxy.addr.ip();
+            // Domain names can be validated only once resolved.
+            let Ok(addr) = proxy.addr.parse::<SocketAddr>() else {
+                continue;
+            };
+            let ip = addr.ip();
             if cfg.allow_private_ip_in_public_addrs {
                 if ip.is_unspecified() {
                     anyhow::bail!("public_addrs: {ip} is not a valid IP. If you wanted to specify a loopback IP, use 127.0.0.1 instead.");
@@ -184,6 +257,16 @@ impl NetworkConfig {
                 }
             }
         }
+        let dns_seeds = match (cfg.dns_seeds.is_empty(), cfg.dns_seeds_public_key) {
+            (true, _) => None,
+            (false, None) => anyhow::bail!("dns_seeds require dns_seeds_public_key to be set"),
+            (false, Some(public_key)) => Some(dns_seed::Config {
+                names: cfg.dns_seeds,
+                public_key,
+                refresh_period: cfg.dns_seeds_refresh_period.try_into()?,
+                resolver: Arc::new(dns_seed::SystemResolver),
+            }),
+        };
         let this = Self {
             node_key,
             validator: validator_signer.map(|signer| ValidatorConfig {
@@ -253,14 +336,33 @@ impl NetworkConfig {
             archive,
             accounts_data_broadcast_rate_limit: rate::Limit { qps: 0.1, burst: 1 },
             routing_table_update_rate_limit: rate::Limit { qps: 1., burst: 1 },
-            tier1: Some(Tier1 { advertise_proxies_interval: time::Duration::minutes(15) }),
+            send_bandwidth_limits: cfg
+                .send_bandwidth_limits
+                .into_iter()
+                .map(|(msg_type, limit)| {
+                    let limit =
+                        rate::Limit { qps: limit.bytes_per_sec as f64, burst: limit.burst_bytes };
+                    (msg_type, limit)
+                })
+                .collect(),
+            tier1: Some(Tier1 {
+                advertise_proxies_interval: time::Duration::minutes(15),
+                check_proxies_interval: time::Duration::minutes(1),
+                require_encryption: cfg.experimental.tier1_require_encryption,
+            }),
             inbound_disabled: cfg.experimental.inbound_disabled,
             skip_tombstones: if cfg.experimental.skip_sending_tombstones_seconds > 0 {
                 Some(time::Duration::seconds(cfg.experimental.skip_sending_tombstones_seconds))
             } else {
                 None
             },
+            capture: cfg.experimental.capture,
+            dns_seeds,
+            per_peer_metrics_limit: cfg.experimental.per_peer_metrics_limit,
             event_sink: Sink::null(),
+            transport: tcp::Transport::default(),
+            arbiter: None,
+            rng_seed: None,
         };
         Ok(this)
     }
@@ -283,7 +385,8 @@ impl NetworkConfig {
             proxies: ValidatorProxies::Static(vec![PeerAddr {
                 addr: node_addr,
                 peer_id: PeerId::new(node_key.public_key()),
-            }]),
+            }
+            .into()]),
         };
         NetworkConfig {
             node_addr: Some(node_addr),
@@ -318,13 +421,22 @@ impl NetworkConfig {
             archive: false,
             accounts_data_broadcast_rate_limit: rate::Limit { qps: 100., burst: 1000000 },
             routing_table_update_rate_limit: rate::Limit { qps: 10., burst: 1 },
+            send_bandwidth_limits: HashMap::new(),
             tier1: Some(Tier1 {
                 // Interval is very large, so that it doesn't happen spontaneously in tests.
                 // It should rather be triggered manually in tests.
                 advertise_proxies_interval: time::Duration::hours(1000),
+                check_proxies_interval: time::Duration::hours(1000),
+                require_encryption: false,
             }),
             skip_tombstones: None,
+            capture: None,
+            dns_seeds: None,
+            per_peer_metrics_limit: 0,
             event_sink: Sink::null(),
+            transport: tcp::Transport::default(),
+            arbiter: None,
+            rng_seed: None,
         }
     }
 
@@ -364,6 +476,12 @@ impl NetworkConfig {
         self.routing_table_update_rate_limit
             .validate()
             .context("routing_table_update_rate_limit")?;
+        for (msg_type, limit) in &self.send_bandwidth_limits {
+            if !PeerMessage::is_msg_variant(msg_type) {
+                anyhow::bail!("send_bandwidth_limits: unknown message type {msg_type:?}");
+            }
+            limit.validate().with_context(|| format!("send_bandwidth_limits[{msg_type:?}]"))?;
+        }
         Ok(VerifiedConfig { node_id: self.node_id(), inner: self })
     }
 }
@@ -397,6 +515,7 @@ impl std::ops::Deref for VerifiedConfig {
 #[cfg(test)]
 mod test {
     use super::UPDATE_INTERVAL_LAST_TIME_RECEIVED_MESSAGE;
+    use crate::concurrency;
     use crate::config;
     use crate::network_protocol;
     use crate::network_protocol::testonly as data;
@@ -424,6 +543,21 @@ mod test {
         let mut nc = config::NetworkConfig::from_seed("123", 213);
         nc.peer_recent_time_window = UPDATE_INTERVAL_LAST_TIME_RECEIVED_MESSAGE;
         assert!(nc.verify().is_err());
+
+        let mut nc = config::NetworkConfig::from_seed("123", 213);
+        let limit = concurrency::rate::Limit { qps: 1000., burst: 10000 };
+        nc.send_bandwidth_limits.insert("VersionedStateResponse".to_string(), limit);
+        nc.send_bandwidth_limits.insert("BlockHeaders".to_string(), limit);
+        assert!(nc.verify().is_ok());
+
+        let mut nc = config::NetworkConfig::from_seed("123", 213);
+        nc.send_bandwidth_limits.insert("Routed".to_string(), limit);
+        assert!(nc.verify().is_err());
+
+        let mut nc = config::NetworkConfig::from_seed("123", 213);
+        let limit = concurrency::rate::Limit { qps: 0., burst: 10000 };
+        nc.send_bandwidth_limits.insert("BlockHeaders".to_string(), limit);
+        assert!(nc.verify().is_err());
     }
 
     // Check that MAX_PEER_ADDRS limit is consistent with the
@@ -1,5 +1,6 @@
-use crate::network_protocol::PeerAddr;
+use crate::config::ProxyAddr;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Duration;
 
 /// Time to persist Accounts Id in the router without removing them in seconds.
@@ -48,6 +49,11 @@ fn default_monitor_peers_max_period() -> Duration {
     Duration::from_secs(60)
 }
 
+/// Period between resolutions of the DNS seeds.
+fn default_dns_seeds_refresh_period() -> Duration {
+    Duration::from_secs(60 * 60)
+}
+
 /// Remove peers that we didn't hear about for this amount of time.
 fn default_peer_expiration_duration() -> Duration {
     Duration::from_secs(7 * 24 * 60 * 60)
@@ -142,9 +148,10 @@ pub struct Config {
     /// In case the validator doesn't have a public IP (i.e. it is hidden in a private network),
     /// this list should contain public addresses of the trusted nodes which will be routing messages to the
     /// validator - validator will connect to these nodes immediately after startup.
-    /// TODO(gprusak): in case a connection cannot be established (the peer is
-    /// unreachable/down/etc.) validator should probably remove (temporarily) the problematic peer from the list
-    /// and broadcast the new version of the list.
+    /// Only the proxies to which the validator has managed to connect are broadcasted. Proxies are
+    /// health checked periodically and the list is re-broadcasted whenever it changes.
+    /// Proxy addresses may contain a domain name instead of an IP, in which case they are
+    /// resolved again on every health check, so that a proxy changing its IP is followed.
     ///
     /// Less recommended setup (requires exactly one public dynamic/ephemeral or static IP):
     /// If the list is empty, the validator node will query trusted_stun_servers to determine its own IP.
@@ -157,7 +164,7 @@ pub struct Config {
     /// this validator node will natually observe the address of the validator and broadcast it.
     /// This setup is not reliable in presence of byzantine peers.
     #[serde(default)]
-    pub public_addrs: Vec<PeerAddr>,
+    pub public_addrs: Vec<ProxyAddr>,
     /// For local tests only (localnet). Allows specifying IPs from private range
     /// (which are not visible from the public internet) in public_addrs field.
     #[serde(default)]
@@ -167,15 +174,48 @@ pub struct Config {
     /// Used only if this node is a validator and public_ips is empty (see
     /// description of public_ips field).  Format `<domain/ip>:<port>`, for
     /// example `stun.l.google.com:19302`.
-    // TODO: unskip, once the functionality is implemented.
-    #[serde(skip)] // TODO: add a default list.
+    #[serde(default)]
     pub trusted_stun_servers: Vec<String>,
+    /// List of domain names of DNS seeds.  Every unexpired TXT record of a seed
+    /// of the form `near-peer=<node public key>@<IP>:<port> <expires_at> <signature>`,
+    /// with the address and the UNIX timestamp `expires_at` signed by
+    /// `dns_seeds_public_key`, is added to the list of known peers.  Seeds are resolved at startup and then periodically, so that
+    /// a node can join the network even if its `boot_nodes` are stale.
+    ///
+    /// Example:
+    ///   ["seeds.nearnode.com"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dns_seeds: Vec<String>,
+    /// Key which signs the peer records served by `dns_seeds`.
+    /// Required iff `dns_seeds` is not empty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dns_seeds_public_key: Option<near_crypto::PublicKey>,
+    /// Period between resolutions of the `dns_seeds`.
+    #[serde(default = "default_dns_seeds_refresh_period")]
+    pub dns_seeds_refresh_period: Duration,
+    /// Limits of the upload bandwidth for the given message types, enforced
+    /// separately for every connection.  Keys are names of the `PeerMessage`
+    /// variants or, for routed messages, of the `RoutedMessageBody` variants.
+    /// Messages over the limit are queued and sent once the limit allows it.
+    ///
+    /// Example:
+    ///   {"VersionedStateResponse": {"bytes_per_sec": 10000000, "burst_bytes": 50000000}}
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub send_bandwidth_limits: HashMap<String, BandwidthLimit>,
     // Experimental part of the JSON config. Regular users/validators should not have to set any values there.
     // Field names in here can change/disappear at any moment without warning.
     #[serde(default)]
     pub experimental: ExperimentalConfig,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BandwidthLimit {
+    /// Sustained upload rate.
+    pub bytes_per_sec: u64,
+    /// Number of bytes which can be sent at once after a period of inactivity.
+    pub burst_bytes: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ExperimentalConfig {
     // If true - don't allow any inbound connections.
@@ -192,6 +232,25 @@ pub struct ExperimentalConfig {
     // compatibility.
     #[serde(default = "default_skip_tombstones")]
     pub skip_sending_tombstones_seconds: i64,
+
+    // If set, all the messages exchanged with peers are written to files in
+    // the given directory, so that they can be replayed later.
+    // See `crate::capture` for details.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture: Option<crate::capture::Config>,
+
+    // If true, connections to TIER1 peers (validators and their proxies) are
+    // closed unless they are encrypted. See `crate::peer::encryption`.
+    #[serde(default)]
+    pub tier1_require_encryption: bool,
+
+    // Maximal number of connections for which metrics labelled with the
+    // `peer_id` and the announced `account_id` of the peer are exported.
+    // Such metrics give a per-peer breakdown of the traffic, but every
+    // connection adds a few series, so the number is bounded. 0 disables
+    // per-peer metrics.
+    #[serde(default)]
+    pub per_peer_metrics_limit: usize,
 }
 
 impl Default for ExperimentalConfig {
@@ -200,6 +259,9 @@ impl Default for ExperimentalConfig {
             inbound_disabled: false,
             connect_only_to_boot_nodes: false,
             skip_sending_tombstones_seconds: default_skip_tombstones(),
+            capture: None,
+            tier1_require_encryption: false,
+            per_peer_metrics_limit: 0,
         }
     }
 }
@@ -228,6 +290,10 @@ impl Default for Config {
             public_addrs: vec![],
             allow_private_ip_in_public_addrs: false,
             trusted_stun_servers: vec![],
+            dns_seeds: vec![],
+            dns_seeds_public_key: None,
+            dns_seeds_refresh_period: default_dns_seeds_refresh_period(),
+            send_bandwidth_limits: HashMap::new(),
             experimental: Default::default(),
         }
     }
@@ -1,16 +1,20 @@
 use ::actix::Message;
-use near_primitives::views::{NetworkGraphView, PeerStoreView};
+use near_primitives::network::PeerId;
+use near_primitives::views::{NetworkGraphView, PeerStatusView, PeerStoreView};
 
 // Different debug requests that can be sent by HTML pages, via GET.
 pub enum GetDebugStatus {
     PeerStore,
     Graph,
+    Peer(PeerId),
 }
 
 #[derive(actix::MessageResponse, Debug)]
 pub enum DebugStatus {
     PeerStore(PeerStoreView),
     Graph(NetworkGraphView),
+    /// None if the peer is not connected.
+    Peer(Option<PeerStatusView>),
 }
 
 impl Message for GetDebugStatus {
@@ -0,0 +1,375 @@
+//! Peer discovery through DNS seeds.
+//!
+//! A DNS seed is a domain name with TXT records, each containing a
+//! [`PeerRecord`]: an address of a peer signed by a key trusted by the node
+//! operator (see `dns_seeds` and `dns_seeds_public_key` in the network config).
+//! Every record carries a signed expiration time, so that a record can't be
+//! replayed once the seed stops serving it.
+//! Seeds are resolved periodically and the discovered peers are added to the
+//! peer store, in addition to the `boot_nodes`.  That way the list of peers
+//! used to join the network can be updated without changing the config of
+//! every node.
+//!
+//! The TXT records are resolved by a [`Resolver`].  [`SystemResolver`] is a
+//! minimal DNS client, which queries the nameservers from `/etc/resolv.conf`
+//! over UDP, falling back to TCP for truncated responses.  Tests replace it
+//! with a local stub.
+use crate::network_protocol::PeerInfo;
+use crate::time;
+use anyhow::{anyhow, Context as _};
+use near_crypto::{PublicKey, SecretKey, Signature};
+use near_primitives::hash::CryptoHash;
+use near_primitives::network::PeerId;
+use rand::Rng as _;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[cfg(test)]
+mod tests;
+
+/// Prefix of the TXT records containing peer records.
+/// TXT records without this prefix are ignored.
+pub const RECORD_PREFIX: &str = "near-peer=";
+
+/// Timeout of a single DNS query.
+const QUERY_TIMEOUT: time::Duration = time::Duration::seconds(5);
+const RESOLV_CONF: &str = "/etc/resolv.conf";
+const DNS_PORT: u16 = 53;
+const TYPE_TXT: u16 = 16;
+const TYPE_OPT: u16 = 41;
+const CLASS_IN: u16 = 1;
+const HEADER_LEN: usize = 12;
+/// Truncation bit of the header flags, set if the response didn't fit in a UDP datagram.
+const FLAG_TC: u16 = 0x0200;
+/// Maximal UDP payload size advertised via EDNS.
+const MAX_RESPONSE_LEN: usize = 4096;
+
+/// Peer record published by a DNS seed as a TXT record of the form
+/// `near-peer=<peer_id>@<ip>:<port> <expires_at> <signature>`, where
+/// `expires_at` is a UNIX timestamp in seconds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeerRecord {
+    pub peer_id: PeerId,
+    pub addr: SocketAddr,
+    /// Time after which the record is not valid any more.
+    pub expires_at: time::Utc,
+    pub signature: Signature,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ParsePeerRecordError {
+    #[error("missing {RECORD_PREFIX:?} prefix")]
+    Prefix,
+    #[error("invalid format")]
+    Format,
+    #[error("peer_id: {0}")]
+    PeerId(near_crypto::ParseKeyError),
+    #[error("addr: {0}")]
+    Addr(std::net::AddrParseError),
+    #[error("expires_at: invalid timestamp")]
+    ExpiresAt,
+    #[error("signature: {0}")]
+    Signature(near_crypto::ParseSignatureError),
+}
+
+impl PeerRecord {
+    fn hash(peer_id: &PeerId, addr: &SocketAddr, expires_at: time::Utc) -> CryptoHash {
+        let expires_at = expires_at.unix_timestamp();
+        CryptoHash::hash_bytes(format!("{RECORD_PREFIX}{peer_id}@{addr} {expires_at}").as_bytes())
+    }
+
+    /// Signs the address of the peer and the expiration time of the record
+    /// with the key of the DNS seed.  `expires_at` is truncated to seconds.
+    pub fn sign(peer_id: PeerId, addr: SocketAddr, expires_at: time::Utc, key: &SecretKey) -> Self {
+        let expires_at = time::Utc::from_unix_timestamp(expires_at.unix_timestamp()).unwrap();
+        let signature = key.sign(Self::hash(&peer_id, &addr, expires_at).as_ref());
+        Self { peer_id, addr, expires_at, signature }
+    }
+
+    pub fn verify(&self, key: &PublicKey) -> bool {
+        self.signature.verify(Self::hash(&self.peer_id, &self.addr, self.expires_at).as_ref(), key)
+    }
+
+    pub fn is_expired(&self, now: time::Utc) -> bool {
+        self.expires_at <= now
+    }
+
+    pub fn peer_info(&self) -> PeerInfo {
+        PeerInfo { id: self.peer_id.clone(), addr: Some(self.addr), account_id: None }
+    }
+}
+
+impl fmt::Display for PeerRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{RECORD_PREFIX}{}@{} {} {}",
+            self.peer_id,
+            self.addr,
+            self.expires_at.unix_timestamp(),
+            self.signature
+        )
+    }
+}
+
+impl FromStr for PeerRecord {
+    type Err = ParsePeerRecordError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix(RECORD_PREFIX).ok_or(ParsePeerRecordError::Prefix)?;
+        let (peer_info, s) = s.split_once(' ').ok_or(ParsePeerRecordError::Format)?;
+        let (expires_at, signature) = s.split_once(' ').ok_or(ParsePeerRecordError::Format)?;
+        let (peer_id, addr) = peer_info.split_once('@').ok_or(ParsePeerRecordError::Format)?;
+        let expires_at = expires_at.parse().map_err(|_| ParsePeerRecordError::ExpiresAt)?;
+        Ok(Self {
+            peer_id: PeerId::new(peer_id.parse().map_err(ParsePeerRecordError::PeerId)?),
+            addr: addr.parse().map_err(ParsePeerRecordError::Addr)?,
+            expires_at: time::Utc::from_unix_timestamp(expires_at)
+                .map_err(|_| ParsePeerRecordError::ExpiresAt)?,
+            signature: signature.parse().map_err(ParsePeerRecordError::Signature)?,
+        })
+    }
+}
+
+/// Resolves TXT records of a domain name.
+#[async_trait::async_trait]
+pub trait Resolver: Send + Sync + 'static {
+    /// Returns the TXT records of `name`. Every record is returned as a single
+    /// string, i.e. the character-strings it consists of are concatenated.
+    async fn resolve_txt(&self, name: &str) -> anyhow::Result<Vec<String>>;
+}
+
+#[derive(Clone)]
+pub struct Config {
+    /// Domain names of the DNS seeds.
+    pub names: Vec<String>,
+    /// Key which signs the peer records served by the seeds.
+    pub public_key: PublicKey,
+    /// Interval between resolutions of the seeds.
+    pub refresh_period: time::Duration,
+    pub resolver: Arc<dyn Resolver>,
+}
+
+/// Resolves all the DNS seeds and returns the peers from the correctly signed,
+/// unexpired records. Failures are logged and otherwise ignored.
+pub(crate) async fn resolve(clock: &time::Clock, cfg: &Config) -> Vec<PeerInfo> {
+    let results =
+        futures::future::join_all(cfg.names.iter().map(|name| cfg.resolver.resolve_txt(name)))
+            .await;
+    let mut peers = vec![];
+    for (name, res) in cfg.names.iter().zip(results) {
+        let records = match res {
+            Ok(records) => records,
+            Err(err) => {
+                tracing::info!(target: "network", %name, ?err, "failed to resolve DNS seed");
+                continue;
+            }
+        };
+        for record in records {
+            if !record.starts_with(RECORD_PREFIX) {
+                continue;
+            }
+            match record.parse::<PeerRecord>() {
+                Ok(rec) if !rec.verify(&cfg.public_key) => {
+                    tracing::warn!(target: "network", %name, %record, "invalid signature of a DNS seed record")
+                }
+                Ok(rec) if rec.is_expired(clock.now_utc()) => {
+                    tracing::info!(target: "network", %name, %record, "expired DNS seed record")
+                }
+                Ok(rec) => peers.push(rec.peer_info()),
+                Err(err) => {
+                    tracing::warn!(target: "network", %name, %record, ?err, "failed to parse a DNS seed record")
+                }
+            }
+        }
+    }
+    peers
+}
+
+/// Minimal DNS client, querying the nameservers listed in `/etc/resolv.conf`.
+/// Nameservers are tried in order, until one of them responds.  Queries are
+/// sent over UDP and repeated over TCP if the response is truncated.
+pub struct SystemResolver;
+
+#[async_trait::async_trait]
+impl Resolver for SystemResolver {
+    async fn resolve_txt(&self, name: &str) -> anyhow::Result<Vec<String>> {
+        let resolv_conf = tokio::fs::read_to_string(RESOLV_CONF).await.context(RESOLV_CONF)?;
+        let nameservers = parse_nameservers(&resolv_conf);
+        if nameservers.is_empty() {
+            anyhow::bail!("no nameservers in {RESOLV_CONF}");
+        }
+        let mut last_err = None;
+        for server in nameservers {
+            match query_txt(server, name, QUERY_TIMEOUT).await {
+                Ok(records) => return Ok(records),
+                Err(err) => last_err = Some(err.context(server)),
+            }
+        }
+        Err(last_err.unwrap())
+    }
+}
+
+fn parse_nameservers(resolv_conf: &str) -> Vec<SocketAddr> {
+    resolv_conf
+        .lines()
+        .filter_map(|line| line.strip_prefix("nameserver"))
+        .filter_map(|ip| ip.trim().parse::<IpAddr>().ok())
+        .map(|ip| SocketAddr::new(ip, DNS_PORT))
+        .collect()
+}
+
+/// Sends a TXT query for `name` to the given nameserver over UDP.  If the
+/// response is truncated, the query is repeated over TCP.
+pub(crate) async fn query_txt(
+    server: SocketAddr,
+    name: &str,
+    timeout: time::Duration,
+) -> anyhow::Result<Vec<String>> {
+    let id: u16 = rand::thread_rng().gen();
+    let query = encode_txt_query(id, name)?;
+    let response = query_udp(server, &query, timeout).await?;
+    if read_u16(&response, 2).map_or(false, |flags| flags & FLAG_TC != 0) {
+        let response = query_tcp(server, &query, timeout).await.context("TCP")?;
+        return decode_txt_response(&response, id);
+    }
+    decode_txt_response(&response, id)
+}
+
+async fn query_udp(
+    server: SocketAddr,
+    query: &[u8],
+    timeout: time::Duration,
+) -> anyhow::Result<Vec<u8>> {
+    let local_addr: SocketAddr = match server {
+        SocketAddr::V4(_) => (std::net::Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddr::V6(_) => (std::net::Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    let socket = tokio::net::UdpSocket::bind(local_addr).await.context("bind()")?;
+    socket.connect(server).await.context("connect()")?;
+    socket.send(query).await.context("send()")?;
+    let mut buf = vec![0; MAX_RESPONSE_LEN];
+    let n = tokio::time::timeout(timeout.try_into()?, socket.recv(&mut buf))
+        .await
+        .context("timeout")?
+        .context("recv()")?;
+    buf.truncate(n);
+    Ok(buf)
+}
+
+/// Over TCP, messages are prefixed with their length as a big-endian u16 (RFC 1035, 4.2.2).
+async fn query_tcp(
+    server: SocketAddr,
+    query: &[u8],
+    timeout: time::Duration,
+) -> anyhow::Result<Vec<u8>> {
+    use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+    tokio::time::timeout(timeout.try_into()?, async {
+        let mut stream = tokio::net::TcpStream::connect(server).await.context("connect()")?;
+        let mut msg = (query.len() as u16).to_be_bytes().to_vec();
+        msg.extend(query);
+        stream.write_all(&msg).await.context("write()")?;
+        let len = stream.read_u16().await.context("read()")?;
+        let mut buf = vec![0; len as usize];
+        stream.read_exact(&mut buf).await.context("read()")?;
+        Ok(buf)
+    })
+    .await
+    .context("timeout")?
+}
+
+fn encode_txt_query(id: u16, name: &str) -> anyhow::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(HEADER_LEN + name.len() + 2 + 4 + 11);
+    out.extend(id.to_be_bytes());
+    // Flags: standard query with recursion desired.
+    out.extend(0x0100u16.to_be_bytes());
+    // QDCOUNT, ANCOUNT, NSCOUNT, ARCOUNT.
+    for count in [1u16, 0, 0, 1] {
+        out.extend(count.to_be_bytes());
+    }
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            anyhow::bail!("invalid domain name {name:?}");
+        }
+        out.push(label.len() as u8);
+        out.extend(label.as_bytes());
+    }
+    out.push(0);
+    out.extend(TYPE_TXT.to_be_bytes());
+    out.extend(CLASS_IN.to_be_bytes());
+    // EDNS OPT pseudo-record, so that responses up to MAX_RESPONSE_LEN are not truncated.
+    out.push(0);
+    out.extend(TYPE_OPT.to_be_bytes());
+    out.extend((MAX_RESPONSE_LEN as u16).to_be_bytes());
+    out.extend(0u32.to_be_bytes());
+    out.extend(0u16.to_be_bytes());
+    Ok(out)
+}
+
+/// Returns the offset right after the (possibly compressed) domain name starting at `offset`.
+fn skip_name(msg: &[u8], mut offset: usize) -> anyhow::Result<usize> {
+    loop {
+        let len = *msg.get(offset).context("truncated name")? as usize;
+        match len {
+            0 => return Ok(offset + 1),
+            // Compression pointer ends the name.
+            len if len & 0xc0 == 0xc0 => return Ok(offset + 2),
+            len => offset += 1 + len,
+        }
+    }
+}
+
+fn read_u16(msg: &[u8], offset: usize) -> anyhow::Result<u16> {
+    let b = msg.get(offset..offset + 2).context("truncated response")?;
+    Ok(u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn decode_txt_response(msg: &[u8], id: u16) -> anyhow::Result<Vec<String>> {
+    if msg.len() < HEADER_LEN {
+        anyhow::bail!("response too short");
+    }
+    if read_u16(msg, 0)? != id {
+        anyhow::bail!("response doesn't match the request");
+    }
+    let flags = read_u16(msg, 2)?;
+    if flags & 0x8000 == 0 {
+        anyhow::bail!("not a response");
+    }
+    if flags & FLAG_TC != 0 {
+        anyhow::bail!("truncated response");
+    }
+    match flags & 0x000f {
+        0 => {}
+        // NXDOMAIN
+        3 => return Ok(vec![]),
+        rcode => anyhow::bail!("error response, rcode = {rcode}"),
+    }
+    let qdcount = read_u16(msg, 4)?;
+    let ancount = read_u16(msg, 6)?;
+    let mut offset = HEADER_LEN;
+    for _ in 0..qdcount {
+        offset = skip_name(msg, offset)? + 4;
+    }
+    let mut records = vec![];
+    for _ in 0..ancount {
+        offset = skip_name(msg, offset)?;
+        let type_ = read_u16(msg, offset)?;
+        let rdlen = read_u16(msg, offset + 8)? as usize;
+        offset += 10;
+        let mut rdata = msg.get(offset..offset + rdlen).context("truncated record")?;
+        offset += rdlen;
+        // Other answers (like CNAMEs) are skipped.
+        if type_ != TYPE_TXT {
+            continue;
+        }
+        let mut record = vec![];
+        while let Some((len, rest)) = rdata.split_first() {
+            let s = rest.get(..*len as usize).context("truncated character-string")?;
+            record.extend(s);
+            rdata = &rest[*len as usize..];
+        }
+        records.push(String::from_utf8(record).map_err(|_| anyhow!("TXT record is not UTF-8"))?);
+    }
+    Ok(records)
+}
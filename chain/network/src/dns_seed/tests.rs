@@ -0,0 +1,291 @@
+use crate::dns_seed::*;
+use crate::network_protocol::testonly as data;
+use crate::peer_manager;
+use crate::peer_manager::peer_manager_actor::Event as PME;
+use crate::peer_manager::testonly::Event;
+use crate::testonly::make_rng;
+use near_o11y::testonly::init_test_logger;
+use std::collections::HashMap;
+
+fn make_record(rng: &mut impl rand::Rng, key: &SecretKey, expires_at: time::Utc) -> PeerRecord {
+    PeerRecord::sign(data::make_peer_id(rng), data::make_addr(rng), expires_at, key)
+}
+
+/// Expiration time of the records valid for an hour since `clock.now_utc()`.
+fn valid_for_an_hour(clock: &time::FakeClock) -> time::Utc {
+    clock.now_utc() + time::Duration::hours(1)
+}
+
+#[test]
+fn peer_record() {
+    let mut rng = make_rng(9403281);
+    let rng = &mut rng;
+    let clock = time::FakeClock::default();
+    let key = data::make_secret_key(rng);
+    let record = make_record(rng, &key, valid_for_an_hour(&clock));
+    let got: PeerRecord = record.to_string().parse().unwrap();
+    assert_eq!(record, got);
+    assert!(got.verify(&key.public_key()));
+    // Signed by another key.
+    assert!(!got.verify(&data::make_secret_key(rng).public_key()));
+    // Modified address.
+    let mut modified = got.clone();
+    modified.addr.set_port(got.addr.port().wrapping_add(1));
+    assert!(!modified.verify(&key.public_key()));
+    // Extended expiration time.
+    let mut modified = got.clone();
+    modified.expires_at += time::Duration::hours(1);
+    assert!(!modified.verify(&key.public_key()));
+    // Expiration.
+    assert!(!got.is_expired(clock.now_utc()));
+    clock.advance(time::Duration::hours(1));
+    assert!(got.is_expired(clock.now_utc()));
+
+    for invalid in [
+        "v=spf1 -all",
+        "near-peer=",
+        "near-peer=ed25519:abc@1.2.3.4:24567 ed25519:abc",
+        &record.to_string().replacen(' ', " x", 1),
+        &record.to_string().replace(' ', ""),
+    ] {
+        assert!(invalid.parse::<PeerRecord>().is_err(), "{invalid}");
+    }
+}
+
+/// Resolver serving the predefined TXT records.
+struct StubResolver(HashMap<String, Vec<String>>);
+
+#[async_trait::async_trait]
+impl Resolver for StubResolver {
+    async fn resolve_txt(&self, name: &str) -> anyhow::Result<Vec<String>> {
+        self.0.get(name).cloned().ok_or(anyhow!("{name} not found"))
+    }
+}
+
+/// Resolver which waits for `gate` to be notified before every resolution.
+struct GatedResolver<R> {
+    inner: R,
+    gate: Arc<tokio::sync::Notify>,
+}
+
+#[async_trait::async_trait]
+impl<R: Resolver> Resolver for GatedResolver<R> {
+    async fn resolve_txt(&self, name: &str) -> anyhow::Result<Vec<String>> {
+        self.gate.notified().await;
+        self.inner.resolve_txt(name).await
+    }
+}
+
+#[tokio::test]
+async fn resolve_seeds() {
+    let mut rng = make_rng(487325);
+    let rng = &mut rng;
+    let clock = time::FakeClock::default();
+    let key = data::make_secret_key(rng);
+    let records: Vec<_> =
+        (0..4).map(|_| make_record(rng, &key, valid_for_an_hour(&clock))).collect();
+    let other_key = data::make_secret_key(rng);
+    let forged = make_record(rng, &other_key, valid_for_an_hour(&clock));
+    let expired = make_record(rng, &key, clock.now_utc());
+    let resolver = StubResolver(HashMap::from([
+        (
+            "a.seeds.test".to_string(),
+            vec![
+                records[0].to_string(),
+                "v=spf1 -all".to_string(),
+                records[1].to_string(),
+                forged.to_string(),
+                expired.to_string(),
+            ],
+        ),
+        (
+            "b.seeds.test".to_string(),
+            vec![records[2].to_string(), "near-peer=garbage".to_string(), records[3].to_string()],
+        ),
+    ]));
+    let cfg = Config {
+        names: vec!["a.seeds.test".into(), "missing.seeds.test".into(), "b.seeds.test".into()],
+        public_key: key.public_key(),
+        refresh_period: time::Duration::hours(1),
+        resolver: Arc::new(resolver),
+    };
+    let want: Vec<_> = records.iter().map(|r| r.peer_info()).collect();
+    assert_eq!(want, resolve(&clock.clock(), &cfg).await);
+}
+
+/// Encodes a response with the given TXT records, split into character-strings of
+/// at most `chunk` bytes.
+fn make_txt_response(query: &[u8], records: &[String], chunk: usize) -> Vec<u8> {
+    let qname_end = skip_name(query, HEADER_LEN).unwrap();
+    let mut out = vec![];
+    out.extend(&query[0..2]);
+    // Response with recursion available.
+    out.extend(0x8180u16.to_be_bytes());
+    for count in [1u16, records.len() as u16, 0, 0] {
+        out.extend(count.to_be_bytes());
+    }
+    out.extend(&query[HEADER_LEN..qname_end + 4]);
+    for record in records {
+        // Pointer to the name in the question section.
+        out.extend(0xc00cu16.to_be_bytes());
+        out.extend(TYPE_TXT.to_be_bytes());
+        out.extend(CLASS_IN.to_be_bytes());
+        out.extend(300u32.to_be_bytes());
+        let mut rdata = vec![];
+        for s in record.as_bytes().chunks(chunk) {
+            rdata.push(s.len() as u8);
+            rdata.extend(s);
+        }
+        out.extend((rdata.len() as u16).to_be_bytes());
+        out.extend(rdata);
+    }
+    out
+}
+
+#[tokio::test]
+async fn query_stub_nameserver() {
+    let mut rng = make_rng(1239871);
+    let clock = time::FakeClock::default();
+    let key = data::make_secret_key(&mut rng);
+    let records: Vec<_> = (0..3)
+        .map(|_| make_record(&mut rng, &key, valid_for_an_hour(&clock)).to_string())
+        .collect();
+    let server = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let server_addr = server.local_addr().unwrap();
+    let handle = tokio::spawn({
+        let records = records.clone();
+        async move {
+            let mut buf = vec![0; MAX_RESPONSE_LEN];
+            let (n, from) = server.recv_from(&mut buf).await.unwrap();
+            let query = &buf[..n];
+            // Single TXT question for the requested name.
+            assert_eq!(1, read_u16(query, 4).unwrap());
+            let qname_end = skip_name(query, HEADER_LEN).unwrap();
+            assert_eq!(b"\x05seeds\x04near\x04test\x00", &query[HEADER_LEN..qname_end]);
+            assert_eq!(TYPE_TXT, read_u16(query, qname_end).unwrap());
+            let response = make_txt_response(query, &records, 100);
+            server.send_to(&response, from).await.unwrap();
+        }
+    });
+    let got = query_txt(server_addr, "seeds.near.test", time::Duration::seconds(5)).await.unwrap();
+    handle.await.unwrap();
+    assert_eq!(records, got);
+}
+
+#[tokio::test]
+async fn query_stub_nameserver_truncated() {
+    use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+    let mut rng = make_rng(5532901);
+    let clock = time::FakeClock::default();
+    let key = data::make_secret_key(&mut rng);
+    let records: Vec<_> = (0..3)
+        .map(|_| make_record(&mut rng, &key, valid_for_an_hour(&clock)).to_string())
+        .collect();
+    let udp = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let server_addr = udp.local_addr().unwrap();
+    let tcp = tokio::net::TcpListener::bind(server_addr).await.unwrap();
+    let handle = tokio::spawn({
+        let records = records.clone();
+        async move {
+            // Over UDP, respond with no records and the truncation bit set.
+            let mut buf = vec![0; MAX_RESPONSE_LEN];
+            let (n, from) = udp.recv_from(&mut buf).await.unwrap();
+            let mut response = make_txt_response(&buf[..n], &[], 100);
+            response[2] |= 0x02;
+            udp.send_to(&response, from).await.unwrap();
+            // Over TCP, respond with all the records.
+            let (mut stream, _) = tcp.accept().await.unwrap();
+            let len = stream.read_u16().await.unwrap();
+            let mut query = vec![0; len as usize];
+            stream.read_exact(&mut query).await.unwrap();
+            let response = make_txt_response(&query, &records, 100);
+            stream.write_u16(response.len() as u16).await.unwrap();
+            stream.write_all(&response).await.unwrap();
+        }
+    });
+    let got = query_txt(server_addr, "seeds.near.test", time::Duration::seconds(5)).await.unwrap();
+    handle.await.unwrap();
+    assert_eq!(records, got);
+}
+
+#[test]
+fn decode_invalid_response() {
+    let query = encode_txt_query(7, "seeds.near.test").unwrap();
+    let response = make_txt_response(&query, &["near-peer=x".to_string()], 255);
+    assert_eq!(vec!["near-peer=x".to_string()], decode_txt_response(&response, 7).unwrap());
+    // ID mismatch.
+    assert!(decode_txt_response(&response, 8).is_err());
+    // Truncated response.
+    assert!(decode_txt_response(&response[..response.len() - 1], 7).is_err());
+    // Truncated flag set.
+    let mut truncated = response.clone();
+    truncated[2] |= 0x02;
+    assert!(decode_txt_response(&truncated, 7).is_err());
+    // NXDOMAIN.
+    let mut nxdomain = response.clone();
+    nxdomain[3] |= 0x03;
+    assert_eq!(Vec::<String>::new(), decode_txt_response(&nxdomain, 7).unwrap());
+    // Invalid names.
+    assert!(encode_txt_query(7, "seeds..test").is_err());
+    assert!(encode_txt_query(7, &"x".repeat(64)).is_err());
+}
+
+#[test]
+fn nameservers_from_resolv_conf() {
+    let conf =
+        "# comment\nsearch example.com\nnameserver 10.0.0.1\nnameserver ::1\nnameserver bad\n";
+    assert_eq!(
+        vec!["10.0.0.1:53".parse::<SocketAddr>().unwrap(), "[::1]:53".parse().unwrap()],
+        parse_nameservers(conf)
+    );
+}
+
+#[tokio::test]
+async fn peers_from_dns_seeds_are_added_to_peer_store() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let key = data::make_secret_key(rng);
+    let records: Vec<_> =
+        (0..3).map(|_| make_record(rng, &key, valid_for_an_hour(&clock))).collect();
+    // Resolution is blocked until the PeerManager has started, so that the event is not
+    // emitted before ServerStarted.
+    let gate = Arc::new(tokio::sync::Notify::new());
+    let mut cfg = chain.make_config(rng);
+    cfg.dns_seeds = Some(Config {
+        names: vec!["seeds.near.test".into()],
+        public_key: key.public_key(),
+        refresh_period: time::Duration::hours(1),
+        resolver: Arc::new(GatedResolver {
+            inner: StubResolver(HashMap::from([(
+                "seeds.near.test".to_string(),
+                records.iter().map(|r| r.to_string()).collect(),
+            )])),
+            gate: gate.clone(),
+        }),
+    });
+    let mut pm = peer_manager::testonly::start(
+        clock.clock(),
+        near_store::db::TestDB::new(),
+        cfg,
+        chain.clone(),
+    )
+    .await;
+    gate.notify_one();
+    let want: Vec<_> = records.iter().map(|r| r.peer_info()).collect();
+    let got = pm
+        .events
+        .recv_until(|ev| match ev {
+            Event::PeerManager(PME::DnsSeedsResolved(peers)) => Some(peers),
+            _ => None,
+        })
+        .await;
+    assert_eq!(want, got);
+    let known = pm.with_state(|s| async move { s.peer_store.load() }).await;
+    for peer_info in &want {
+        assert_eq!(peer_info.addr, known.get(&peer_info.id).unwrap().peer_info.addr);
+    }
+}
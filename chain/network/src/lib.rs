@@ -9,13 +9,16 @@ mod peer_manager;
 mod private_actix;
 mod stats;
 mod store;
+mod stun;
 
 pub mod actix;
 pub mod blacklist;
+pub mod capture;
 pub mod client;
 pub mod config;
 pub mod config_json;
 pub mod debug;
+pub mod dns_seed;
 pub mod raw;
 pub mod routing;
 pub mod tcp;
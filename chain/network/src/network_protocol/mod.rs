@@ -216,6 +216,18 @@ pub struct Handshake {
     pub(crate) sender_chain_info: PeerChainInfoV2,
     /// Represents new `edge`. Contains only `none` and `Signature` from the sender.
     pub(crate) partial_edge_info: PartialEdgeInfo,
+    /// Offer to encrypt the connection, see `crate::peer::encryption`.
+    /// Supported only in the proto encoding.
+    pub(crate) encryption_offer: Option<EncryptionOffer>,
+}
+
+/// See EncryptionOffer in network_protocol/network.proto.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct EncryptionOffer {
+    pub(crate) version: u32,
+    /// Ephemeral X25519 public key.
+    pub(crate) ephemeral_key: [u8; 32],
+    pub(crate) signature: Signature,
 }
 
 #[derive(PartialEq, Eq, Clone, Debug, strum::IntoStaticStr)]
@@ -269,7 +281,17 @@ impl fmt::Display for PeerMessage {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, strum::IntoStaticStr)]
+#[derive(
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Debug,
+    Hash,
+    strum::IntoStaticStr,
+    borsh::BorshSerialize,
+    borsh::BorshDeserialize,
+)]
 pub enum Encoding {
     Borsh,
     Proto,
@@ -329,11 +351,39 @@ impl PeerMessage {
             _ => self.into(),
         }
     }
+
+    /// Checks whether `name` is a possible value of `msg_variant()`.
+    pub(crate) fn is_msg_variant(name: &str) -> bool {
+        use strum::VariantNames as _;
+        (name != "Routed" && PeerMessage::VARIANTS.contains(&name))
+            || RoutedMessageBody::VARIANTS.contains(&name)
+    }
+
+    /// Whether the message is a part of a bulk transfer, i.e. a large response
+    /// (headers or state parts), which can be delayed in favour of other
+    /// messages when the connection is saturated. Chunk parts are not bulk,
+    /// since block production depends on their timely delivery.
+    pub(crate) fn is_bulk(&self) -> bool {
+        match self {
+            PeerMessage::BlockHeaders(_) => true,
+            PeerMessage::Routed(msg) => matches!(
+                msg.body,
+                RoutedMessageBody::StateResponse(_) | RoutedMessageBody::VersionedStateResponse(_)
+            ),
+            _ => false,
+        }
+    }
 }
 
 // TODO(#1313): Use Box
 #[derive(
-    borsh::BorshSerialize, borsh::BorshDeserialize, PartialEq, Eq, Clone, strum::IntoStaticStr,
+    borsh::BorshSerialize,
+    borsh::BorshDeserialize,
+    PartialEq,
+    Eq,
+    Clone,
+    strum::IntoStaticStr,
+    strum::EnumVariantNames,
 )]
 pub enum RoutedMessageBody {
     BlockApproval(Approval),
@@ -2,7 +2,7 @@
 use super::*;
 
 use crate::network_protocol::proto;
-use crate::network_protocol::{Handshake, HandshakeFailureReason};
+use crate::network_protocol::{EncryptionOffer, Handshake, HandshakeFailureReason};
 use crate::network_protocol::{PeerChainInfoV2, PeerInfo};
 use near_primitives::block::GenesisId;
 use protobuf::MessageField as MF;
@@ -63,6 +63,42 @@ impl TryFrom<&proto::PeerChainInfo> for PeerChainInfoV2 {
 
 //////////////////////////////////////////
 
+#[derive(thiserror::Error, Debug)]
+pub enum ParseEncryptionOfferError {
+    #[error("ephemeral_key: expected 32 bytes, got {0}")]
+    EphemeralKey(usize),
+    #[error("signature {0}")]
+    Signature(ParseRequiredError<ParseSignatureError>),
+}
+
+impl From<&EncryptionOffer> for proto::EncryptionOffer {
+    fn from(x: &EncryptionOffer) -> Self {
+        Self {
+            version: x.version,
+            ephemeral_key: x.ephemeral_key.to_vec(),
+            signature: MF::some((&x.signature).into()),
+            ..Self::default()
+        }
+    }
+}
+
+impl TryFrom<&proto::EncryptionOffer> for EncryptionOffer {
+    type Error = ParseEncryptionOfferError;
+    fn try_from(p: &proto::EncryptionOffer) -> Result<Self, Self::Error> {
+        Ok(Self {
+            version: p.version,
+            ephemeral_key: p
+                .ephemeral_key
+                .as_slice()
+                .try_into()
+                .map_err(|_| Self::Error::EphemeralKey(p.ephemeral_key.len()))?,
+            signature: try_from_required(&p.signature).map_err(Self::Error::Signature)?,
+        })
+    }
+}
+
+//////////////////////////////////////////
+
 #[derive(thiserror::Error, Debug)]
 pub enum ParseHandshakeError {
     #[error("sender_peer_id {0}")]
@@ -75,6 +111,8 @@ pub enum ParseHandshakeError {
     SenderChainInfo(ParseRequiredError<ParsePeerChainInfoV2Error>),
     #[error("partial_edge_info {0}")]
     PartialEdgeInfo(ParseRequiredError<ParsePartialEdgeInfoError>),
+    #[error("encryption_offer {0}")]
+    EncryptionOffer(ParseEncryptionOfferError),
 }
 
 impl From<&Handshake> for proto::Handshake {
@@ -87,6 +125,7 @@ impl From<&Handshake> for proto::Handshake {
             sender_listen_port: x.sender_listen_port.unwrap_or(0).into(),
             sender_chain_info: MF::some((&x.sender_chain_info).into()),
             partial_edge_info: MF::some((&x.partial_edge_info).into()),
+            encryption_offer: MF::from_option(x.encryption_offer.as_ref().map(Into::into)),
             ..Self::default()
         }
     }
@@ -115,6 +154,12 @@ impl TryFrom<&proto::Handshake> for Handshake {
                 .map_err(Self::Error::SenderChainInfo)?,
             partial_edge_info: try_from_required(&p.partial_edge_info)
                 .map_err(Self::Error::PartialEdgeInfo)?,
+            encryption_offer: p
+                .encryption_offer
+                .as_ref()
+                .map(EncryptionOffer::try_from)
+                .transpose()
+                .map_err(Self::Error::EncryptionOffer)?,
         })
     }
 }
@@ -0,0 +1,157 @@
+//! Authenticated encryption of the peer connections.
+//!
+//! Each endpoint generates an ephemeral X25519 key for the connection and
+//! includes an `EncryptionOffer` in its Handshake: the ephemeral public key
+//! signed by the node key, i.e. the key behind the PeerId of the endpoint.
+//! If both Handshakes contain an offer of the same version, the endpoints
+//! derive the symmetric keys from the X25519 shared secret and from then on
+//! exchange frames encrypted with ChaCha20-Poly1305 (see `peer::stream`).
+//! Peers which don't support encryption ignore the offer, in which case the
+//! connection stays unencrypted.
+//!
+//! Since the ephemeral keys are signed by the node keys, a man-in-the-middle
+//! cannot impersonate either endpoint. It can however strip the offers from
+//! the Handshakes, so that the connection stays unencrypted. That's why
+//! encryption can be made mandatory for TIER1 connections, see
+//! `config::Tier1::require_encryption`.
+use crate::network_protocol::EncryptionOffer;
+use chacha20poly1305::aead::{Aead, NewAead};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use curve25519_dalek::constants::X25519_BASEPOINT;
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use curve25519_dalek::scalar::Scalar;
+use near_crypto::SecretKey;
+use near_primitives::hash::CryptoHash;
+use near_primitives::network::PeerId;
+use rand::Rng as _;
+
+#[cfg(test)]
+mod tests;
+
+/// Version of the encrypted transport that this node supports.
+/// Bump it whenever the key derivation or the framing changes.
+pub(crate) const VERSION: u32 = 1;
+
+/// Domain separator of the signed offers and of the derived keys.
+const DOMAIN: &[u8] = b"near-network-encryption";
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub(crate) enum Error {
+    #[error("unsupported version {0}")]
+    UnsupportedVersion(u32),
+    #[error("invalid offer signature")]
+    InvalidSignature,
+    #[error("invalid ephemeral key")]
+    InvalidKey,
+}
+
+/// Ephemeral X25519 key of a single connection.
+pub(crate) struct EphemeralKey {
+    secret: Scalar,
+    public: MontgomeryPoint,
+}
+
+impl EphemeralKey {
+    pub fn new() -> Self {
+        let mut bytes: [u8; 32] = rand::thread_rng().gen();
+        // Clamping as specified in RFC 7748.
+        bytes[0] &= 248;
+        bytes[31] &= 127;
+        bytes[31] |= 64;
+        let secret = Scalar::from_bits(bytes);
+        Self { public: &X25519_BASEPOINT * &secret, secret }
+    }
+
+    /// Signs the ephemeral public key with the node key.
+    /// The signature is bound to both `sender` and `target`, so that the offer
+    /// cannot be replayed on another connection.
+    pub fn offer(&self, node_key: &SecretKey, sender: &PeerId, target: &PeerId) -> EncryptionOffer {
+        let ephemeral_key = self.public.to_bytes();
+        EncryptionOffer {
+            version: VERSION,
+            ephemeral_key,
+            signature: node_key.sign(offer_hash(VERSION, &ephemeral_key, sender, target).as_ref()),
+        }
+    }
+
+    /// Verifies the offer received from `sender` and derives the ciphers for
+    /// both directions of the connection: (send cipher, recv cipher).
+    pub fn accept(
+        &self,
+        offer: &EncryptionOffer,
+        sender: &PeerId,
+        target: &PeerId,
+    ) -> Result<(Cipher, Cipher), Error> {
+        if offer.version != VERSION {
+            return Err(Error::UnsupportedVersion(offer.version));
+        }
+        let hash = offer_hash(offer.version, &offer.ephemeral_key, sender, target);
+        if !offer.signature.verify(hash.as_ref(), sender.public_key()) {
+            return Err(Error::InvalidSignature);
+        }
+        let mine = self.public.to_bytes();
+        let theirs = offer.ephemeral_key;
+        // Reflecting our own offer back at us is not a valid key exchange.
+        if mine == theirs {
+            return Err(Error::InvalidKey);
+        }
+        let shared = (&MontgomeryPoint(theirs) * &self.secret).to_bytes();
+        // Low order points result in an all-zero shared secret.
+        if shared == [0; 32] {
+            return Err(Error::InvalidKey);
+        }
+        // Both endpoints order the ephemeral keys the same way, so that they
+        // agree on which derived key is used for which direction.
+        let (lo, hi) = if mine < theirs { (mine, theirs) } else { (theirs, mine) };
+        let salt = [lo, hi].concat();
+        let mut okm = [0; 64];
+        hkdf::Hkdf::<sha2::Sha256>::new(Some(&salt), &shared)
+            .expand(DOMAIN, &mut okm)
+            .expect("64 bytes is a valid HKDF-SHA256 output length");
+        let (k_lo, k_hi) = (Cipher::new(&okm[..32]), Cipher::new(&okm[32..]));
+        Ok(if mine == lo { (k_lo, k_hi) } else { (k_hi, k_lo) })
+    }
+}
+
+fn offer_hash(
+    version: u32,
+    ephemeral_key: &[u8; 32],
+    sender: &PeerId,
+    target: &PeerId,
+) -> CryptoHash {
+    CryptoHash::hash_borsh(&(DOMAIN, version, ephemeral_key, sender, target))
+}
+
+/// Encrypts/decrypts the frames sent in one direction of the connection.
+/// Each frame uses the next nonce, so frames have to be decrypted in the
+/// order they were encrypted. Any reordering, replay or modification of the
+/// frames makes the decryption fail.
+pub(crate) struct Cipher {
+    aead: ChaCha20Poly1305,
+    nonce: u64,
+}
+
+impl Cipher {
+    fn new(key: &[u8]) -> Self {
+        Self { aead: ChaCha20Poly1305::new(Key::from_slice(key)), nonce: 0 }
+    }
+
+    fn next_nonce(&mut self) -> [u8; 12] {
+        let mut nonce = [0; 12];
+        nonce[4..].copy_from_slice(&self.nonce.to_le_bytes());
+        self.nonce += 1;
+        nonce
+    }
+
+    pub fn encrypt(&mut self, data: &[u8]) -> Vec<u8> {
+        let nonce = self.next_nonce();
+        self.aead
+            .encrypt(Nonce::from_slice(&nonce), data)
+            .expect("ChaCha20Poly1305 encryption cannot fail for frames below 256GiB")
+    }
+
+    pub fn decrypt(&mut self, data: &[u8]) -> Option<Vec<u8>> {
+        let nonce = self.next_nonce();
+        self.aead.decrypt(Nonce::from_slice(&nonce), data).ok()
+    }
+}
@@ -0,0 +1,79 @@
+use crate::peer::encryption::*;
+use crate::testonly::make_rng;
+use near_crypto::KeyType;
+
+fn make_node_key() -> SecretKey {
+    SecretKey::from_random(KeyType::ED25519)
+}
+
+#[test]
+fn key_exchange() {
+    let (a_key, b_key) = (make_node_key(), make_node_key());
+    let (a_id, b_id) = (PeerId::new(a_key.public_key()), PeerId::new(b_key.public_key()));
+    let (a, b) = (EphemeralKey::new(), EphemeralKey::new());
+    let a_offer = a.offer(&a_key, &a_id, &b_id);
+    let b_offer = b.offer(&b_key, &b_id, &a_id);
+    let (mut a_send, mut a_recv) = a.accept(&b_offer, &b_id, &a_id).unwrap();
+    let (mut b_send, mut b_recv) = b.accept(&a_offer, &a_id, &b_id).unwrap();
+
+    let mut rng = make_rng(3429187);
+    for n in [0, 1, 100, 10000] {
+        let data: Vec<u8> = (0..n).map(|_| rng.gen()).collect();
+        let ct = a_send.encrypt(&data);
+        assert_ne!(data, ct);
+        assert_eq!(Some(data.clone()), b_recv.decrypt(&ct));
+        let ct = b_send.encrypt(&data);
+        assert_eq!(Some(data), a_recv.decrypt(&ct));
+    }
+}
+
+#[test]
+fn tampered_frames() {
+    let (a_key, b_key) = (make_node_key(), make_node_key());
+    let (a_id, b_id) = (PeerId::new(a_key.public_key()), PeerId::new(b_key.public_key()));
+    let (a, b) = (EphemeralKey::new(), EphemeralKey::new());
+    let (mut send, _) = a.accept(&b.offer(&b_key, &b_id, &a_id), &b_id, &a_id).unwrap();
+    let (_, mut recv) = b.accept(&a.offer(&a_key, &a_id, &b_id), &a_id, &b_id).unwrap();
+
+    // Modified frame.
+    let mut ct = send.encrypt(b"hello");
+    ct[0] ^= 1;
+    assert_eq!(None, recv.decrypt(&ct));
+
+    // Reordered frames.
+    let (mut send, _) = a.accept(&b.offer(&b_key, &b_id, &a_id), &b_id, &a_id).unwrap();
+    let (_, mut recv) = b.accept(&a.offer(&a_key, &a_id, &b_id), &a_id, &b_id).unwrap();
+    let ct1 = send.encrypt(b"first");
+    let ct2 = send.encrypt(b"second");
+    assert_eq!(None, recv.decrypt(&ct2));
+    assert_eq!(None, recv.decrypt(&ct1));
+}
+
+#[test]
+fn invalid_offers() {
+    let (a_key, b_key) = (make_node_key(), make_node_key());
+    let (a_id, b_id) = (PeerId::new(a_key.public_key()), PeerId::new(b_key.public_key()));
+    let c_id = PeerId::new(make_node_key().public_key());
+    let (a, b) = (EphemeralKey::new(), EphemeralKey::new());
+    let offer = b.offer(&b_key, &b_id, &a_id);
+
+    // Offer signed by a different key than the one of the sender.
+    assert_eq!(Err(Error::InvalidSignature), a.accept(&offer, &c_id, &a_id).map(|_| ()));
+    // Offer addressed to a different node.
+    assert_eq!(Err(Error::InvalidSignature), a.accept(&offer, &b_id, &c_id).map(|_| ()));
+    // Unsupported version.
+    let mut bad = offer.clone();
+    bad.version = VERSION + 1;
+    assert_eq!(
+        Err(Error::UnsupportedVersion(VERSION + 1)),
+        a.accept(&bad, &b_id, &a_id).map(|_| ())
+    );
+    // Our own ephemeral key reflected back.
+    let reflected = a.offer(&b_key, &b_id, &a_id);
+    assert_eq!(Err(Error::InvalidKey), a.accept(&reflected, &b_id, &a_id).map(|_| ()));
+    // Low order point.
+    let mut low = b.offer(&b_key, &b_id, &a_id);
+    low.ephemeral_key = [0; 32];
+    low.signature = b_key.sign(offer_hash(VERSION, &low.ephemeral_key, &b_id, &a_id).as_ref());
+    assert_eq!(Err(Error::InvalidKey), a.accept(&low, &b_id, &a_id).map(|_| ()));
+}
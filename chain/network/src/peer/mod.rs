@@ -1,3 +1,4 @@
+mod encryption;
 pub(crate) mod peer_actor;
 mod stream;
 mod tracker;
@@ -1,15 +1,20 @@
 use crate::accounts_data;
+use crate::actix::run_later;
+use crate::capture;
 use crate::concurrency::atomic_cell::AtomicCell;
 use crate::concurrency::demux;
+use crate::concurrency::rate;
 use crate::network_protocol::{
     Edge, EdgeState, Encoding, ParsePeerMessageError, PartialEdgeInfo, PeerChainInfoV2, PeerInfo,
     RawRoutedMessage, RoutedMessageBody, RoutingTableUpdate, SyncAccountsData,
 };
+use crate::peer::encryption;
 use crate::peer::stream;
 use crate::peer::tracker::Tracker;
 use crate::peer_manager::connection;
 use crate::peer_manager::network_state::NetworkState;
 use crate::peer_manager::peer_manager_actor::Event;
+use crate::peer_manager::peer_store::score::ScoreEvent;
 use crate::private_actix::{RegisterPeerError, SendMessage};
 use crate::routing::edge::verify_nonce;
 use crate::stats::metrics;
@@ -32,6 +37,7 @@ use near_primitives::version::{
     ProtocolVersion, PEER_MIN_ALLOWED_PROTOCOL_VERSION, PROTOCOL_VERSION,
 };
 use parking_lot::Mutex;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::io;
 use std::net::SocketAddr;
@@ -55,6 +61,9 @@ const ROUTED_MESSAGE_CACHE_SIZE: usize = 1000;
 const DROP_DUPLICATED_MESSAGES_PERIOD: time::Duration = time::Duration::milliseconds(50);
 /// How often to send the latest block to peers.
 const SYNC_LATEST_BLOCK_INTERVAL: time::Duration = time::Duration::seconds(60);
+/// Block and chunk requests not responded to within that time count as failed
+/// and lower the score of the peer.
+const REQUEST_TIMEOUT: time::Duration = time::Duration::seconds(10);
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ConnectionClosedEvent {
@@ -84,6 +93,8 @@ pub(crate) enum ClosingReason {
     Ban(ReasonForBan),
     #[error("handshake failed")]
     HandshakeFailed,
+    #[error("connection to a TIER1 peer is not encrypted")]
+    EncryptionRequired,
     #[error("rejected by PeerManager: {0:?}")]
     RejectedByPeerManager(RegisterPeerError),
     #[error("stream error")]
@@ -113,11 +124,18 @@ pub(crate) struct PeerActor {
 
     /// Framed wrapper to send messages through the TCP connection.
     framed: stream::FramedStream<PeerActor>,
+    /// Ephemeral key offered in the Handshake, to encrypt the connection.
+    encryption_key: encryption::EphemeralKey,
 
     /// Tracker for requests and responses.
     tracker: Arc<Mutex<Tracker>>,
+    /// Token buckets enforcing `send_bandwidth_limits` from the config, by message type.
+    send_bandwidth: Mutex<HashMap<&'static str, rate::TokenBucket>>,
     /// Network bandwidth stats.
     stats: Arc<connection::Stats>,
+    /// Metrics labelled by this peer, present if the connection got
+    /// one of the `per_peer_metrics_limit` slots.
+    peer_metrics: Arc<Mutex<Option<metrics::PeerMetrics>>>,
     /// Cache of recently routed messages, this allows us to drop duplicates
     routed_message_cache: LruCache<(PeerId, PeerIdOrHash, Signature), time::Instant>,
     /// Whether we detected support for protocol buffers during handshake.
@@ -201,13 +219,15 @@ impl PeerActor {
             addr: network_state.config.node_addr.clone(),
             account_id: network_state.config.validator.as_ref().map(|v| v.account_id()),
         };
-        // Start PeerActor on separate thread.
-        Ok(Self::start_in_arbiter(&actix::Arbiter::new().handle(), move |ctx| {
+        // Start PeerActor on separate thread, unless the arbiter is provided in the config.
+        let arbiter = (network_state.config.arbiter.clone())
+            .unwrap_or_else(|| actix::Arbiter::new().handle());
+        Ok(Self::start_in_arbiter(&arbiter, move |ctx| {
             let stats = Arc::new(connection::Stats::default());
             let stream_id = stream.id();
             let peer_addr = stream.peer_addr;
             let stream_type = stream.type_.clone();
-            let framed = stream::FramedStream::spawn(ctx, stream, stats.clone());
+            let framed = stream::FramedStream::spawn(ctx, &clock, stream, stats.clone());
             Self {
                 closing_reason: None,
                 clock,
@@ -220,8 +240,11 @@ impl PeerActor {
                 },
                 peer_status: PeerStatus::Connecting(connecting_status),
                 framed,
+                encryption_key: encryption::EphemeralKey::new(),
                 tracker: Default::default(),
+                send_bandwidth: Mutex::new(HashMap::new()),
                 stats,
+                peer_metrics: Default::default(),
                 routed_message_cache: LruCache::new(ROUTED_MESSAGE_CACHE_SIZE),
                 protocol_buffers_supported: false,
                 force_encoding,
@@ -289,27 +312,62 @@ impl PeerActor {
             "send_message_with_encoding",
             msg_type)
         .entered();
+        let bytes = msg.serialize(enc);
         // Skip sending block and headers if we received it or header from this peer.
-        // Record block requests in tracker.
+        // Record block and chunk requests in tracker.
         match msg {
             // Temporarily disable this check because now the node needs to send block to its
             // peers to update its height at the peer. In the future we will introduce a new
             // peer message type for that and then we can enable this check again.
             //PeerMessage::Block(b) if self.tracker.lock().has_received(b.hash()) => return,
-            PeerMessage::BlockRequest(h) => self.tracker.lock().push_request(*h),
+            PeerMessage::BlockRequest(h) => self.tracker.lock().push_request(&self.clock, *h),
+            PeerMessage::Routed(r) if &r.author == self.my_node_id() => {
+                if let RoutedMessageBody::PartialEncodedChunkRequest(req) = &r.body {
+                    self.tracker.lock().push_request(&self.clock, req.chunk_hash.0);
+                }
+            }
             _ => (),
         };
 
-        let bytes = msg.serialize(enc);
-        self.tracker.lock().increment_sent(&self.clock, bytes.len() as u64);
+        if let (Some(capture), Some(peer_id)) = (&self.network_state.capture, self.other_peer_id())
+        {
+            capture.record(&self.clock, peer_id, capture::Direction::Outbound, enc, &bytes);
+        }
+        self.tracker.lock().increment_sent(&self.clock, msg_type, bytes.len() as u64);
         let bytes_len = bytes.len();
         tracing::trace!(target: "network", msg_len = bytes_len);
-        self.framed.send(stream::Frame(bytes));
+        let priority =
+            if msg.is_bulk() { stream::Priority::Bulk } else { stream::Priority::Normal };
+        let delay = self.reserve_send_bandwidth(msg_type, bytes_len);
+        if delay > time::Duration::ZERO {
+            tracing::debug!(target: "network", msg_type, msg_len = bytes_len, ?delay, "bandwidth limit exceeded, delaying message");
+            metrics::PEER_MESSAGE_DELAYED_BY_TYPE_TOTAL.with_label_values(&[msg_type]).inc();
+            self.framed.send_delayed(delay, stream::Frame(bytes), priority);
+        } else {
+            self.framed.send(stream::Frame(bytes), priority);
+        }
         metrics::PEER_DATA_SENT_BYTES.inc_by(bytes_len as u64);
         metrics::PEER_MESSAGE_SENT_BY_TYPE_TOTAL.with_label_values(&[msg_type]).inc();
         metrics::PEER_MESSAGE_SENT_BY_TYPE_BYTES
             .with_label_values(&[msg_type])
             .inc_by(bytes_len as u64);
+        if let Some(m) = &*self.peer_metrics.lock() {
+            m.sent_bytes.inc_by(bytes_len as u64);
+            m.inc_messages(metrics::Direction::Sent, msg_type);
+        }
+    }
+
+    /// Reserves `len` bytes of the configured bandwidth limit for the given
+    /// message type and returns how long the message has to be delayed to fit in it.
+    fn reserve_send_bandwidth(&self, msg_type: &'static str, len: usize) -> time::Duration {
+        let limit = match self.network_state.config.send_bandwidth_limits.get(msg_type) {
+            Some(limit) => *limit,
+            None => return time::Duration::ZERO,
+        };
+        let now = self.clock.now();
+        let mut buckets = self.send_bandwidth.lock();
+        let bucket = buckets.entry(msg_type).or_insert_with(|| rate::TokenBucket::new(limit, now));
+        bucket.reserve(now, len as u64)
     }
 
     fn send_handshake(&self, spec: HandshakeSpec) {
@@ -319,6 +377,11 @@ impl PeerActor {
             } else {
                 (0, vec![])
             };
+        let encryption_offer = self.encryption_key.offer(
+            &self.network_state.config.node_key,
+            self.my_node_id(),
+            &spec.peer_id,
+        );
         let handshake = Handshake {
             protocol_version: spec.protocol_version,
             oldest_supported_version: PEER_MIN_ALLOWED_PROTOCOL_VERSION,
@@ -333,6 +396,7 @@ impl PeerActor {
                 archival: self.network_state.config.archive,
             },
             partial_edge_info: spec.partial_edge_info,
+            encryption_offer: Some(encryption_offer),
         };
         let msg = PeerMessage::Handshake(handshake);
         self.send_message_or_log(&msg);
@@ -341,11 +405,28 @@ impl PeerActor {
     fn stop(&mut self, ctx: &mut actix::Context<PeerActor>, reason: ClosingReason) {
         // Only the first call to stop sets the closing_reason.
         if self.closing_reason.is_none() {
+            if let (ClosingReason::Ban(_), Some(peer_id)) = (&reason, self.other_peer_id()) {
+                self.network_state.peer_store.record_score_event(
+                    &self.clock,
+                    peer_id,
+                    ScoreEvent::InvalidData,
+                );
+            }
             self.closing_reason = Some(reason);
         }
         ctx.stop();
     }
 
+    fn record_score_events(&self, conn: &connection::Connection, events: &[ScoreEvent]) {
+        for event in events {
+            self.network_state.peer_store.record_score_event(
+                &self.clock,
+                &conn.peer_info.id,
+                *event,
+            );
+        }
+    }
+
     /// `PeerId` of the current node.
     fn my_node_id(&self) -> &PeerId {
         &self.my_node_info.id
@@ -362,6 +443,16 @@ impl PeerActor {
         metrics::PEER_MESSAGE_RECEIVED_TOTAL.inc();
         tracing::trace!(target: "network", msg_len);
         self.tracker.lock().increment_received(&self.clock, msg_len as u64);
+        if let Some(m) = &*self.peer_metrics.lock() {
+            m.received_bytes.inc_by(msg_len as u64);
+        }
+    }
+
+    /// Records the latency of a response to our request in the per-peer metrics.
+    fn observe_rtt(&self, latency: time::Duration) {
+        if let Some(m) = &*self.peer_metrics.lock() {
+            m.rtt.observe(latency.as_seconds_f64());
+        }
     }
 
     fn process_handshake(
@@ -463,6 +554,43 @@ impl PeerActor {
             return;
         }
 
+        // Negotiate encryption of the connection.
+        let mut ciphers = match &handshake.encryption_offer {
+            None => None,
+            Some(offer) => match self.encryption_key.accept(
+                offer,
+                &handshake.sender_peer_id,
+                self.my_node_id(),
+            ) {
+                Ok(ciphers) => Some(ciphers),
+                Err(encryption::Error::UnsupportedVersion(version)) => {
+                    tracing::debug!(target: "network", version, "unsupported encryption version, the connection won't be encrypted");
+                    None
+                }
+                Err(err) => {
+                    tracing::warn!(target: "network", ?err, "invalid encryption offer, disconnecting");
+                    self.stop(ctx, ClosingReason::Ban(ReasonForBan::InvalidSignature));
+                    return;
+                }
+            },
+        };
+        if ciphers.is_none()
+            && self.network_state.config.tier1.as_ref().map_or(false, |t| t.require_encryption)
+            && self.network_state.is_tier1_peer(&handshake.sender_peer_id)
+        {
+            tracing::info!(target: "network", peer_id = %handshake.sender_peer_id, "TIER1 peer doesn't support encryption, disconnecting");
+            self.stop(ctx, ClosingReason::EncryptionRequired);
+            return;
+        }
+        let encrypted = ciphers.is_some();
+        // The outbound endpoint starts encrypting as soon as it receives the Handshake of the
+        // inbound endpoint. The inbound endpoint does that after sending its Handshake, see below.
+        if self.peer_type == PeerType::Outbound {
+            if let Some((send, recv)) = ciphers.take() {
+                self.framed.enable_encryption(send, recv);
+            }
+        }
+
         // Merge partial edges.
         let nonce = handshake.partial_edge_info.nonce;
         let partial_edge_info = match cs {
@@ -505,6 +633,7 @@ impl PeerActor {
             archival: handshake.sender_chain_info.archival,
             last_block: Default::default(),
             peer_type: self.peer_type,
+            encrypted,
             stats: self.stats.clone(),
             _peer_connections_metric: metrics::PEER_CONNECTIONS.new_point(&metrics::Connection {
                 type_: self.peer_type,
@@ -519,20 +648,64 @@ impl PeerActor {
         });
 
         let tracker = self.tracker.clone();
+        let peer_metrics = self.peer_metrics.clone();
+        // The permit is held by the loop below, so it is released once the actor stops.
+        let peer_metrics_permit =
+            self.network_state.per_peer_metrics_permits.clone().try_acquire_owned().ok();
         let clock = self.clock.clone();
         let mut interval =
             time::Interval::new(clock.now(), self.network_state.config.peer_stats_period);
         ctx.spawn({
             let conn = conn.clone();
+            let network_state = self.network_state.clone();
             wrap_future(async move {
                 loop {
                     interval.tick(&clock).await;
+                    let timed_out = tracker.lock().expire_requests(&clock, REQUEST_TIMEOUT);
+                    if peer_metrics_permit.is_some() {
+                        // The peer may announce its account only after the connection has
+                        // been established, so the labels are refreshed periodically.
+                        let account_id =
+                            network_state.graph.routing_table.announced_account(&conn.peer_info.id);
+                        let mut peer_metrics = peer_metrics.lock();
+                        if peer_metrics.as_ref().map(|m| &m.account_id) != Some(&account_id) {
+                            // Drop the old series before creating the new ones.
+                            *peer_metrics = None;
+                            *peer_metrics =
+                                Some(metrics::PeerMetrics::new(&conn.peer_info.id, account_id));
+                        }
+                        if let Some(m) = &*peer_metrics {
+                            m.request_timeouts.inc_by(timed_out as u64);
+                        }
+                        conn.stats.per_peer_metrics.store(true, Ordering::Relaxed);
+                    }
+                    for _ in 0..timed_out {
+                        network_state.peer_store.record_score_event(
+                            &clock,
+                            &conn.peer_info.id,
+                            ScoreEvent::RequestTimeout,
+                        );
+                    }
                     let sent = tracker.lock().sent_bytes.minute_stats(&clock);
                     let received = tracker.lock().received_bytes.minute_stats(&clock);
                     conn.stats
                         .received_bytes_per_sec
                         .store(received.bytes_per_min / 60, Ordering::Relaxed);
                     conn.stats.sent_bytes_per_sec.store(sent.bytes_per_min / 60, Ordering::Relaxed);
+                    *conn.stats.received_bytes_per_sec_by_type.lock() =
+                        tracker.lock().received_bytes_per_sec_by_type(&clock);
+                    *conn.stats.sent_bytes_per_sec_by_type.lock() =
+                        tracker.lock().sent_bytes_per_sec_by_type(&clock);
+                    {
+                        let tracker = tracker.lock();
+                        *conn.stats.rtt.lock() = tracker.rtt;
+                        conn.stats
+                            .pending_requests
+                            .store(tracker.pending_requests() as u64, Ordering::Relaxed);
+                        conn.stats
+                            .timed_out_requests
+                            .store(tracker.timed_out_requests, Ordering::Relaxed);
+                    }
                     // Whether the peer is considered abusive due to sending too many messages.
                     // I am allowing this for now because I assume `MAX_PEER_MSG_PER_MIN` will
                     // some day be less than `u64::MAX`.
@@ -578,6 +751,9 @@ impl PeerActor {
                                 protocol_version: handshake.protocol_version,
                                 partial_edge_info: partial_edge_info,
                             });
+                            if let Some((send, recv)) = ciphers {
+                                act.framed.enable_encryption(send, recv);
+                            }
                         } else {
                             // Outbound peer triggers the inital full accounts data sync.
                             // TODO(gprusak): implement triggering the periodic full sync.
@@ -838,8 +1014,36 @@ impl PeerActor {
                 });
                 let mut tracker = self.tracker.lock();
                 tracker.push_received(hash);
+                let latency = tracker.finish_request(&self.clock, &hash);
+                if let Some(latency) = latency {
+                    self.observe_rtt(latency);
+                    self.record_score_events(
+                        conn,
+                        &[ScoreEvent::Response { latency }, ScoreEvent::UsefulBlock],
+                    );
+                }
                 tracker.has_request(&hash)
             }
+            PeerMessage::Routed(msg) => {
+                match &msg.body {
+                    RoutedMessageBody::PartialEncodedChunkResponse(response) => {
+                        let latency =
+                            self.tracker.lock().finish_request(&self.clock, &response.chunk_hash.0);
+                        if let Some(latency) = latency {
+                            self.observe_rtt(latency);
+                            self.record_score_events(
+                                conn,
+                                &[ScoreEvent::Response { latency }, ScoreEvent::UsefulChunk],
+                            );
+                        }
+                    }
+                    RoutedMessageBody::VersionedPartialEncodedChunk(_) => {
+                        self.record_score_events(conn, &[ScoreEvent::UsefulChunk]);
+                    }
+                    _ => {}
+                }
+                false
+            }
             _ => false,
         };
         let clock = self.clock.clone();
@@ -918,10 +1122,10 @@ impl PeerActor {
                 tracing::debug!(target: "network", "Duplicate handshake from {}", self.peer_info);
             }
             PeerMessage::PeersRequest => {
-                let peers = self
-                    .network_state
-                    .peer_store
-                    .healthy_peers(self.network_state.config.max_send_peers as usize);
+                let peers = self.network_state.peer_store.healthy_peers(
+                    &mut *self.network_state.rng.lock(),
+                    self.network_state.config.max_send_peers as usize,
+                );
                 if !peers.is_empty() {
                     tracing::debug!(target: "network", "Peers request from {}: sending {} peers.", self.peer_info, peers.len());
                     self.send_message_or_log(&PeerMessage::PeersResponse(peers));
@@ -1062,6 +1266,11 @@ impl PeerActor {
                     if msg.decrease_ttl() {
                         self.network_state.send_message_to_peer(&self.clock, msg);
                     } else {
+                        // Only the author is responsible for the TTL being too small, the peer
+                        // which just forwarded the message is not.
+                        if &msg.author == from {
+                            self.record_score_events(&conn, &[ScoreEvent::UselessMessage]);
+                        }
                         self.network_state.config.event_sink.push(Event::RoutedMessageDropped);
                         tracing::warn!(target: "network", ?msg, ?from, "Message dropped because TTL reached 0.");
                         metrics::ROUTED_MESSAGE_DROPPED
@@ -1115,9 +1324,10 @@ impl actix::Actor for PeerActor {
         tracing::debug!(target: "network", "{:?}: Peer {:?} {:?} started", self.my_node_info.id, self.peer_addr, self.peer_type);
         // Set Handshake timeout for stopping actor if peer is not ready after given period of time.
 
-        near_performance_metrics::actix::run_later(
+        run_later(
             ctx,
-            self.network_state.config.handshake_timeout.try_into().unwrap(),
+            &self.clock,
+            self.network_state.config.handshake_timeout,
             move |act, ctx| match act.peer_status {
                 PeerStatus::Connecting { .. } => {
                     tracing::info!(target: "network", "Handshake timeout expired for {}", act.peer_info);
@@ -1147,6 +1357,10 @@ impl actix::Actor for PeerActor {
         // closing_reason may be None in case the whole actix system is stopped.
         // It happens a lot in tests.
         metrics::PEER_CONNECTIONS_TOTAL.dec();
+        if let (Some(capture), Some(peer_id)) = (&self.network_state.capture, self.other_peer_id())
+        {
+            capture.close_peer(peer_id);
+        }
         tracing::debug!(target: "network", "{:?}: [status = {:?}] Peer {} disconnected.", self.my_node_info.id, self.peer_status, self.peer_info);
         if self.closing_reason.is_none() {
             // Due to Actix semantics, sometimes closing reason may be not set.
@@ -1181,7 +1395,10 @@ impl actix::Actor for PeerActor {
                 );
             }
         }
-        actix::Arbiter::current().stop();
+        // The arbiter is dedicated to this actor, unless it was provided in the config.
+        if self.network_state.config.arbiter.is_none() {
+            actix::Arbiter::current().stop();
+        }
     }
 }
 
@@ -1195,6 +1412,8 @@ impl actix::Handler<stream::Error> for PeerActor {
             }
             // It is expected in a sense that the peer might be just slow.
             stream::Error::Send(stream::SendError::QueueOverflow { .. }) => true,
+            // The frame might have been modified by a third party, so we don't ban the peer.
+            stream::Error::Recv(stream::RecvError::Decrypt) => true,
             stream::Error::Recv(stream::RecvError::IO(err))
             | stream::Error::Send(stream::SendError::IO(err)) => match err.kind() {
                 // Connection has been closed.
@@ -1244,6 +1463,12 @@ impl actix::Handler<stream::Frame> for PeerActor {
                 return;
             }
         };
+        if let (Some(capture), Some(peer_id)) = (&self.network_state.capture, self.other_peer_id())
+        {
+            // If the encoding is still unknown, the message has been parsed as borsh.
+            let enc = self.encoding().unwrap_or(Encoding::Borsh);
+            capture.record(&self.clock, peer_id, capture::Direction::Inbound, enc, &msg);
+        }
 
         match &peer_msg {
             PeerMessage::Routed(msg) => {
@@ -1281,6 +1506,14 @@ impl actix::Handler<stream::Frame> for PeerActor {
             metrics::PEER_MESSAGE_RECEIVED_BY_TYPE_BYTES
                 .with_label_values(&labels)
                 .inc_by(msg.len() as u64);
+            self.tracker.lock().increment_received_by_type(
+                &self.clock,
+                peer_msg.msg_variant(),
+                msg.len() as u64,
+            );
+            if let Some(m) = &*self.peer_metrics.lock() {
+                m.inc_messages(metrics::Direction::Received, peer_msg.msg_variant());
+            }
         }
         match &self.peer_status {
             PeerStatus::Connecting { .. } => self.handle_msg_connecting(ctx, peer_msg),
@@ -1,6 +1,8 @@
+use crate::peer::encryption;
 use crate::peer_manager::connection;
 use crate::stats::metrics;
 use crate::tcp;
+use crate::time;
 use actix::fut::future::wrap_future;
 use actix::AsyncContext as _;
 use bytesize::{GIB, MIB};
@@ -16,9 +18,13 @@ use tokio::io::AsyncWriteExt as _;
 const NETWORK_MESSAGE_MAX_SIZE_BYTES: usize = 512 * MIB as usize;
 /// Maximum capacity of write buffer in bytes.
 const MAX_WRITE_BUFFER_CAPACITY_BYTES: usize = GIB as usize;
+/// Bit of the encoded frame length, which indicates that the frame is encrypted.
+/// It is never set for unencrypted frames, since they are smaller than
+/// `NETWORK_MESSAGE_MAX_SIZE_BYTES`.
+const ENCRYPTED_FRAME_FLAG: u32 = 1 << 31;
 
-type ReadHalf = tokio::io::ReadHalf<tokio::net::TcpStream>;
-type WriteHalf = tokio::io::WriteHalf<tokio::net::TcpStream>;
+type ReadHalf = tokio::io::ReadHalf<tcp::RawStream>;
+type WriteHalf = tokio::io::WriteHalf<tcp::RawStream>;
 
 #[derive(thiserror::Error, Debug)]
 pub(crate) enum SendError {
@@ -34,12 +40,25 @@ pub(crate) enum RecvError {
     IO(#[source] io::Error),
     #[error("message too large: got {got_bytes}B, want <={want_max_bytes}B")]
     MessageTooLarge { got_bytes: usize, want_max_bytes: usize },
+    #[error("failed to decrypt a frame")]
+    Decrypt,
 }
 
 #[derive(actix::Message, PartialEq, Eq, Clone, Debug)]
 #[rtype(result = "()")]
 pub(crate) struct Frame(pub Vec<u8>);
 
+/// Priority of an outbound frame.
+/// Frames are sent in the order they were queued, except that `Bulk` frames
+/// are sent only when there are no `Normal` frames waiting. This way, when the
+/// connection is saturated, bulk transfers (like state parts) don't delay the
+/// messages critical for block production (like blocks, approvals and chunks).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Priority {
+    Normal,
+    Bulk,
+}
+
 /// Stream critical error.
 /// Actor is responsible for calling ctx.stop() after receiving stream::Error.
 /// Actor might receive more than 1 stream::Error, but should call ctx.stop() just after the
@@ -56,8 +75,28 @@ pub(crate) enum Error {
     Recv(#[source] RecvError),
 }
 
+/// Item of the send queues.
+enum Queued {
+    Frame(Frame),
+    /// Frames written after this item are encrypted with the cipher.
+    /// Whether a frame is encrypted is decided when it is written rather than
+    /// when it is queued, so that no frame is sent unencrypted after the
+    /// encryption has been enabled, even if it has been delayed.
+    EnableEncryption(encryption::Cipher),
+}
+
+type QueueSender = tokio::sync::mpsc::UnboundedSender<Queued>;
+type QueueReceiver = tokio::sync::mpsc::UnboundedReceiver<Queued>;
+
+/// Frame waiting to be pushed to the send queue of the given priority once the deadline passes.
+type DelayedFrame = (time::Instant, Frame, Priority);
+
 pub(crate) struct FramedStream<Actor: actix::Actor> {
-    queue_send: tokio::sync::mpsc::UnboundedSender<Frame>,
+    queue_send: QueueSender,
+    bulk_queue_send: QueueSender,
+    delayed_queue_send: tokio::sync::mpsc::UnboundedSender<DelayedFrame>,
+    recv_cipher: Arc<parking_lot::Mutex<Option<encryption::Cipher>>>,
+    clock: time::Clock,
     stats: Arc<connection::Stats>,
     send_buf_size_metric: Arc<metrics::IntGaugeGuard>,
     addr: actix::Addr<Actor>,
@@ -71,11 +110,15 @@ where
 {
     pub fn spawn(
         ctx: &mut actix::Context<Actor>,
+        clock: &time::Clock,
         stream: tcp::Stream,
         stats: Arc<connection::Stats>,
     ) -> Self {
         let (tcp_recv, tcp_send) = tokio::io::split(stream.stream);
         let (queue_send, queue_recv) = tokio::sync::mpsc::unbounded_channel();
+        let (bulk_queue_send, bulk_queue_recv) = tokio::sync::mpsc::unbounded_channel();
+        let (delayed_queue_send, delayed_queue_recv) = tokio::sync::mpsc::unbounded_channel();
+        let recv_cipher = Arc::new(parking_lot::Mutex::new(None));
         let send_buf_size_metric = Arc::new(metrics::MetricGuard::new(
             &*metrics::PEER_DATA_WRITE_BUFFER_SIZE,
             vec![stream.peer_addr.to_string()],
@@ -85,30 +128,77 @@ where
             let stats = stats.clone();
             let m = send_buf_size_metric.clone();
             async move {
-                if let Err(err) = Self::run_send_loop(tcp_send, queue_recv, stats, m).await {
+                if let Err(err) =
+                    Self::run_send_loop(tcp_send, queue_recv, bulk_queue_recv, stats, m).await
+                {
                     addr.do_send(Error::Send(SendError::IO(err)));
                 }
             }
         }));
+        ctx.spawn(wrap_future(Self::run_delay_loop(
+            clock.clone(),
+            delayed_queue_recv,
+            queue_send.clone(),
+            bulk_queue_send.clone(),
+        )));
         ctx.spawn(wrap_future({
             let addr = ctx.address();
             let stats = stats.clone();
+            let cipher = recv_cipher.clone();
             async move {
                 if let Err(err) =
-                    Self::run_recv_loop(stream.peer_addr, tcp_recv, addr.clone(), stats).await
+                    Self::run_recv_loop(stream.peer_addr, tcp_recv, addr.clone(), cipher, stats)
+                        .await
                 {
                     addr.do_send(Error::Recv(err));
                 }
             }
         }));
-        Self { queue_send, stats, send_buf_size_metric, addr: ctx.address() }
+        Self {
+            queue_send,
+            bulk_queue_send,
+            delayed_queue_send,
+            recv_cipher,
+            clock: clock.clone(),
+            stats,
+            send_buf_size_metric,
+            addr: ctx.address(),
+        }
+    }
+
+    /// Enables encryption of the stream.
+    /// All the frames queued after this call, including the delayed ones, are
+    /// encrypted with `send`. The frames queued before may be sent unencrypted.
+    /// All the frames received from now on are expected to be encrypted with
+    /// `recv`; the unencrypted ones are dropped, which covers the frames the
+    /// peer has sent before it enabled the encryption on its side.
+    pub fn enable_encryption(&self, send: encryption::Cipher, recv: encryption::Cipher) {
+        // Bulk frames are written only when the normal queue is empty, so the
+        // cipher takes effect before any frame queued after this call is written.
+        let _ = self.queue_send.send(Queued::EnableEncryption(send));
+        *self.recv_cipher.lock() = Some(recv);
     }
 
-    /// Pushes `msg` to the send queue.
+    /// Pushes `msg` to the send queue of the given priority.
     /// Silently drops message if the connection has been closed.
     /// If the message is too large, it will be silently dropped inside run_send_loop.
     /// Emits a critical error to Actor if send queue is full.
-    pub fn send(&self, frame: Frame) {
+    pub fn send(&self, frame: Frame, priority: Priority) {
+        self.account(&frame);
+        let _ = queue(&self.queue_send, &self.bulk_queue_send, priority).send(Queued::Frame(frame));
+    }
+
+    /// Like `send`, but pushes `msg` to the send queue only after `delay`.
+    /// Delayed frames are pushed in the order of `send_delayed` calls, so a
+    /// frame is held back also until all the frames delayed before it are pushed.
+    /// The message counts towards the send buffer capacity immediately.
+    pub fn send_delayed(&self, delay: time::Duration, frame: Frame, priority: Priority) {
+        self.account(&frame);
+        let _ = self.delayed_queue_send.send((self.clock.now() + delay, frame, priority));
+    }
+
+    /// Accounts the frame in the send buffer stats.
+    fn account(&self, frame: &Frame) {
         let msg = &frame.0;
         let mut buf_size =
             self.stats.bytes_to_send.fetch_add(msg.len() as u64, Ordering::Acquire) as usize;
@@ -125,7 +215,19 @@ where
                 want_max_bytes: MAX_WRITE_BUFFER_CAPACITY_BYTES,
             }));
         }
-        let _ = self.queue_send.send(frame);
+    }
+
+    /// Pushes the delayed frames to the send queues once their deadlines pass, in FIFO order.
+    async fn run_delay_loop(
+        clock: time::Clock,
+        mut delayed_queue_recv: tokio::sync::mpsc::UnboundedReceiver<DelayedFrame>,
+        queue_send: QueueSender,
+        bulk_queue_send: QueueSender,
+    ) {
+        while let Some((deadline, frame, priority)) = delayed_queue_recv.recv().await {
+            clock.sleep_until(deadline).await;
+            let _ = queue(&queue_send, &bulk_queue_send, priority).send(Queued::Frame(frame));
+        }
     }
 
     /// Event loop receiving and processing messages.
@@ -140,6 +242,7 @@ where
         peer_addr: SocketAddr,
         read: ReadHalf,
         addr: actix::Addr<Actor>,
+        cipher: Arc<parking_lot::Mutex<Option<encryption::Cipher>>>,
         stats: Arc<connection::Stats>,
     ) -> Result<(), RecvError> {
         const READ_BUFFER_CAPACITY: usize = 8 * 1024;
@@ -152,7 +255,9 @@ where
             vec![peer_addr.to_string()],
         );
         loop {
-            let n = read.read_u32_le().await.map_err(RecvError::IO)? as usize;
+            let n = read.read_u32_le().await.map_err(RecvError::IO)?;
+            let encrypted = n & ENCRYPTED_FRAME_FLAG != 0;
+            let n = (n & !ENCRYPTED_FRAME_FLAG) as usize;
             if n > NETWORK_MESSAGE_MAX_SIZE_BYTES {
                 return Err(RecvError::MessageTooLarge {
                     got_bytes: n,
@@ -168,6 +273,15 @@ where
             buf_size_metric.set(0);
             stats.received_messages.fetch_add(1, Ordering::Relaxed);
             stats.received_bytes.fetch_add(n as u64, Ordering::Relaxed);
+            let buf = match (cipher.lock().as_mut(), encrypted) {
+                (Some(cipher), true) => cipher.decrypt(&buf).ok_or(RecvError::Decrypt)?,
+                (None, true) => return Err(RecvError::Decrypt),
+                (Some(_), false) => {
+                    tracing::debug!(target: "network", %peer_addr, "dropping an unencrypted frame");
+                    continue;
+                }
+                (None, false) => buf,
+            };
             if let Err(_) = addr.send(Frame(buf)).await {
                 // We got mailbox error, which means that Actor has stopped,
                 // so we should just close the stream.
@@ -177,19 +291,43 @@ where
     }
     async fn run_send_loop(
         tcp_send: WriteHalf,
-        mut queue_recv: tokio::sync::mpsc::UnboundedReceiver<Frame>,
+        mut queue_recv: QueueReceiver,
+        mut bulk_queue_recv: QueueReceiver,
         stats: Arc<connection::Stats>,
         buf_size_metric: Arc<metrics::IntGaugeGuard>,
     ) -> io::Result<()> {
         const WRITE_BUFFER_CAPACITY: usize = 8 * 1024;
         let mut writer = tokio::io::BufWriter::with_capacity(WRITE_BUFFER_CAPACITY, tcp_send);
-        while let Some(Frame(mut msg)) = queue_recv.recv().await {
+        let mut cipher: Option<encryption::Cipher> = None;
+        loop {
+            let mut next = tokio::select! {
+                biased;
+                frame = queue_recv.recv() => frame,
+                frame = bulk_queue_recv.recv() => frame,
+            };
+            if next.is_none() {
+                break;
+            }
             // Try writing a batch of messages and flush once at the end.
-            loop {
+            while let Some(queued) = next {
+                let msg = match queued {
+                    Queued::Frame(Frame(msg)) => msg,
+                    Queued::EnableEncryption(send) => {
+                        cipher = Some(send);
+                        next = queue_recv.try_recv().or_else(|_| bulk_queue_recv.try_recv()).ok();
+                        continue;
+                    }
+                };
                 // TODO(gprusak): sending a too large message should probably be treated as a bug,
                 // since dropping messages may lead to hard-to-debug high-level issues.
                 if msg.len() > NETWORK_MESSAGE_MAX_SIZE_BYTES {
                     metrics::MessageDropped::InputTooLong.inc_unknown_msg();
+                } else if let Some(cipher) = &mut cipher {
+                    // Frames are encrypted in the order they are written, so that
+                    // the nonces match on the receiving side.
+                    let data = cipher.encrypt(&msg);
+                    writer.write_u32_le(data.len() as u32 | ENCRYPTED_FRAME_FLAG).await?;
+                    writer.write_all(&data[..]).await?;
                 } else {
                     writer.write_u32_le(msg.len() as u32).await?;
                     writer.write_all(&msg[..]).await?;
@@ -197,10 +335,8 @@ where
                 stats.messages_to_send.fetch_sub(1, Ordering::Release);
                 stats.bytes_to_send.fetch_sub(msg.len() as u64, Ordering::Release);
                 buf_size_metric.sub(msg.len() as i64);
-                msg = match queue_recv.try_recv() {
-                    Ok(Frame(it)) => it,
-                    Err(_) => break,
-                };
+                // Frames of normal priority go first.
+                next = queue_recv.try_recv().or_else(|_| bulk_queue_recv.try_recv()).ok();
             }
             // This is an unconditional flush, which means that even if new messages
             // will be added to the queue in the meantime, we will wait for the buffer
@@ -213,3 +349,14 @@ where
         Ok(())
     }
 }
+
+fn queue<'a>(
+    normal: &'a QueueSender,
+    bulk: &'a QueueSender,
+    priority: Priority,
+) -> &'a QueueSender {
+    match priority {
+        Priority::Normal => normal,
+        Priority::Bulk => bulk,
+    }
+}
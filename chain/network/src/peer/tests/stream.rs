@@ -1,10 +1,13 @@
 use crate::actix::ActixSystem;
 use crate::network_protocol::testonly as data;
+use crate::peer::encryption;
 use crate::peer::stream;
 use crate::tcp;
 use crate::testonly::make_rng;
+use crate::time;
 use actix::Actor as _;
 use actix::ActorContext as _;
+use near_primitives::network::PeerId;
 use rand::Rng as _;
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -20,12 +23,34 @@ impl actix::Actor for Actor {
 
 #[derive(actix::Message)]
 #[rtype("()")]
-struct SendFrame(stream::Frame);
+struct SendFrame(stream::Frame, stream::Priority);
 
 impl actix::Handler<SendFrame> for Actor {
     type Result = ();
-    fn handle(&mut self, SendFrame(frame): SendFrame, _ctx: &mut Self::Context) {
-        self.stream.send(frame);
+    fn handle(&mut self, SendFrame(frame, priority): SendFrame, _ctx: &mut Self::Context) {
+        self.stream.send(frame, priority);
+    }
+}
+
+#[derive(actix::Message)]
+#[rtype("()")]
+struct SendDelayedFrame(time::Duration, stream::Frame);
+
+impl actix::Handler<SendDelayedFrame> for Actor {
+    type Result = ();
+    fn handle(&mut self, SendDelayedFrame(delay, frame): SendDelayedFrame, _: &mut Self::Context) {
+        self.stream.send_delayed(delay, frame, stream::Priority::Normal);
+    }
+}
+
+#[derive(actix::Message)]
+#[rtype("()")]
+struct EnableEncryption(encryption::Cipher, encryption::Cipher);
+
+impl actix::Handler<EnableEncryption> for Actor {
+    type Result = ();
+    fn handle(&mut self, EnableEncryption(send, recv): EnableEncryption, _: &mut Self::Context) {
+        self.stream.enable_encryption(send, recv);
     }
 }
 
@@ -49,13 +74,14 @@ struct Handler {
 }
 
 impl Actor {
-    async fn spawn(s: tcp::Stream) -> Handler {
+    async fn spawn(clock: &time::Clock, s: tcp::Stream) -> Handler {
         let (queue_send, queue_recv) = mpsc::unbounded_channel();
+        let clock = clock.clone();
         Handler {
             queue_recv,
-            system: ActixSystem::spawn(|| {
+            system: ActixSystem::spawn(move || {
                 Actor::create(|ctx| {
-                    let stream = stream::FramedStream::spawn(ctx, s, Arc::default());
+                    let stream = stream::FramedStream::spawn(ctx, &clock, s, Arc::default());
                     Self { stream, queue_send }
                 })
             })
@@ -67,9 +93,10 @@ impl Actor {
 #[tokio::test]
 async fn send_recv() {
     let mut rng = make_rng(98324532);
+    let clock = time::FakeClock::default();
     let (s1, s2) = tcp::Stream::loopback(data::make_peer_id(&mut rng)).await;
-    let a1 = Actor::spawn(s1).await;
-    let mut a2 = Actor::spawn(s2).await;
+    let a1 = Actor::spawn(&clock.clock(), s1).await;
+    let mut a2 = Actor::spawn(&clock.clock(), s2).await;
 
     for _ in 0..5 {
         let n = rng.gen_range(1..10);
@@ -82,7 +109,7 @@ async fn send_recv() {
             })
             .collect();
         for msg in &msgs {
-            a1.system.addr.send(SendFrame(msg.clone())).await.unwrap();
+            a1.system.addr.send(SendFrame(msg.clone(), stream::Priority::Normal)).await.unwrap();
         }
         for want in &msgs {
             let got = a2.queue_recv.recv().await.unwrap();
@@ -90,3 +117,141 @@ async fn send_recv() {
         }
     }
 }
+
+#[tokio::test]
+async fn send_recv_priority() {
+    let mut rng = make_rng(3452234);
+    let clock = time::FakeClock::default();
+    let (s1, s2) = tcp::Stream::loopback(data::make_peer_id(&mut rng)).await;
+    let a1 = Actor::spawn(&clock.clock(), s1).await;
+    let mut a2 = Actor::spawn(&clock.clock(), s2).await;
+
+    let mut normal = vec![];
+    let mut bulk = vec![];
+    for i in 0..100u32 {
+        let msg = stream::Frame(i.to_le_bytes().to_vec());
+        let priority = if rng.gen() { stream::Priority::Normal } else { stream::Priority::Bulk };
+        match priority {
+            stream::Priority::Normal => normal.push(msg.clone()),
+            stream::Priority::Bulk => bulk.push(msg.clone()),
+        }
+        a1.system.addr.send(SendFrame(msg, priority)).await.unwrap();
+    }
+    // Frames may be reordered between the queues, but not within a queue.
+    let mut got_normal = vec![];
+    let mut got_bulk = vec![];
+    for _ in 0..normal.len() + bulk.len() {
+        let got = a2.queue_recv.recv().await.unwrap();
+        if normal.contains(&got) {
+            got_normal.push(got);
+        } else {
+            got_bulk.push(got);
+        }
+    }
+    assert_eq!(normal, got_normal);
+    assert_eq!(bulk, got_bulk);
+}
+
+#[tokio::test]
+async fn send_delayed() {
+    let mut rng = make_rng(7723411);
+    let clock = time::FakeClock::default();
+    let (s1, s2) = tcp::Stream::loopback(data::make_peer_id(&mut rng)).await;
+    let a1 = Actor::spawn(&clock.clock(), s1).await;
+    let mut a2 = Actor::spawn(&clock.clock(), s2).await;
+
+    let delayed = stream::Frame(vec![1, 2, 3]);
+    let other = stream::Frame(vec![4, 5, 6]);
+    let delay = time::Duration::seconds(1);
+    a1.system.addr.send(SendDelayedFrame(delay, delayed.clone())).await.unwrap();
+    a1.system.addr.send(SendFrame(other.clone(), stream::Priority::Normal)).await.unwrap();
+    // The delayed frame is held back until the delay elapses.
+    assert_eq!(other, a2.queue_recv.recv().await.unwrap());
+    clock.advance(delay);
+    assert_eq!(delayed, a2.queue_recv.recv().await.unwrap());
+}
+
+/// Returns the ciphers of both endpoints of an encrypted connection.
+fn make_ciphers(
+    rng: &mut impl rand::Rng,
+) -> ((encryption::Cipher, encryption::Cipher), (encryption::Cipher, encryption::Cipher)) {
+    let (k1, k2) = (data::make_secret_key(rng), data::make_secret_key(rng));
+    let (id1, id2) = (PeerId::new(k1.public_key()), PeerId::new(k2.public_key()));
+    let (e1, e2) = (encryption::EphemeralKey::new(), encryption::EphemeralKey::new());
+    (
+        e1.accept(&e2.offer(&k2, &id2, &id1), &id2, &id1).unwrap(),
+        e2.accept(&e1.offer(&k1, &id1, &id2), &id1, &id2).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn send_recv_encrypted() {
+    let mut rng = make_rng(67454567);
+    let clock = time::FakeClock::default();
+    let (s1, s2) = tcp::Stream::loopback(data::make_peer_id(&mut rng)).await;
+    let a1 = Actor::spawn(&clock.clock(), s1).await;
+    let mut a2 = Actor::spawn(&clock.clock(), s2).await;
+    let ((send1, recv1), (send2, recv2)) = make_ciphers(&mut rng);
+    let frame = |i: u32| stream::Frame(i.to_le_bytes().to_vec());
+
+    // Frames sent before the encryption was enabled are delivered.
+    a1.system.addr.send(SendFrame(frame(0), stream::Priority::Normal)).await.unwrap();
+    assert_eq!(frame(0), a2.queue_recv.recv().await.unwrap());
+
+    // Once a2 enables the encryption, it drops the unencrypted frames.
+    a2.system.addr.send(EnableEncryption(send2, recv2)).await.unwrap();
+    a1.system.addr.send(SendFrame(frame(1), stream::Priority::Normal)).await.unwrap();
+    a1.system.addr.send(EnableEncryption(send1, recv1)).await.unwrap();
+    for i in 2..100 {
+        let priority = if rng.gen() { stream::Priority::Normal } else { stream::Priority::Bulk };
+        a1.system.addr.send(SendFrame(frame(i), priority)).await.unwrap();
+    }
+    let mut got = vec![];
+    for _ in 2..100 {
+        got.push(a2.queue_recv.recv().await.unwrap());
+    }
+    got.sort_by_key(|f| u32::from_le_bytes(f.0[..].try_into().unwrap()));
+    assert_eq!((2..100).map(frame).collect::<Vec<_>>(), got);
+}
+
+#[tokio::test]
+async fn send_delayed_in_order() {
+    let mut rng = make_rng(2348911);
+    let clock = time::FakeClock::default();
+    let (s1, s2) = tcp::Stream::loopback(data::make_peer_id(&mut rng)).await;
+    let a1 = Actor::spawn(&clock.clock(), s1).await;
+    let mut a2 = Actor::spawn(&clock.clock(), s2).await;
+
+    let first = stream::Frame(vec![1]);
+    let second = stream::Frame(vec![2]);
+    a1.system.addr.send(SendDelayedFrame(time::Duration::seconds(2), first.clone())).await.unwrap();
+    a1.system
+        .addr
+        .send(SendDelayedFrame(time::Duration::seconds(1), second.clone()))
+        .await
+        .unwrap();
+    // A frame with a shorter delay doesn't overtake the frame delayed before it.
+    clock.advance(time::Duration::seconds(2));
+    assert_eq!(first, a2.queue_recv.recv().await.unwrap());
+    assert_eq!(second, a2.queue_recv.recv().await.unwrap());
+}
+
+#[tokio::test]
+async fn send_delayed_encrypted() {
+    let mut rng = make_rng(8923471);
+    let clock = time::FakeClock::default();
+    let (s1, s2) = tcp::Stream::loopback(data::make_peer_id(&mut rng)).await;
+    let a1 = Actor::spawn(&clock.clock(), s1).await;
+    let mut a2 = Actor::spawn(&clock.clock(), s2).await;
+    let ((send1, recv1), (send2, recv2)) = make_ciphers(&mut rng);
+
+    // A frame delayed across enabling the encryption is sent encrypted,
+    // otherwise a2 would drop it.
+    let delayed = stream::Frame(vec![1, 2, 3]);
+    let delay = time::Duration::seconds(1);
+    a1.system.addr.send(SendDelayedFrame(delay, delayed.clone())).await.unwrap();
+    a1.system.addr.send(EnableEncryption(send1, recv1)).await.unwrap();
+    a2.system.addr.send(EnableEncryption(send2, recv2)).await.unwrap();
+    clock.advance(delay);
+    assert_eq!(delayed, a2.queue_recv.recv().await.unwrap());
+}
@@ -1,6 +1,7 @@
 use crate::peer::transfer_stats::TransferStats;
 use crate::time;
 use near_primitives::hash::CryptoHash;
+use std::collections::HashMap;
 
 /// Maximum number of requests and responses to track.
 const MAX_TRACK_SIZE: usize = 30;
@@ -45,10 +46,20 @@ pub(crate) struct Tracker {
     pub(crate) sent_bytes: TransferStats,
     /// Bytes we've received.
     pub(crate) received_bytes: TransferStats,
+    /// Bytes we've sent, by message type (see `PeerMessage::msg_variant()`).
+    sent_bytes_by_type: HashMap<&'static str, TransferStats>,
+    /// Bytes we've received and parsed successfully, by message type.
+    received_bytes_by_type: HashMap<&'static str, TransferStats>,
     /// Sent requests.
     requested: CircularUniqueQueue,
     /// Received elements.
     received: CircularUniqueQueue,
+    /// Requests still waiting for a response, with the time they were sent.
+    pending: HashMap<CryptoHash, time::Instant>,
+    /// Smoothed latency of the responses, computed like SRTT in TCP (RFC 6298).
+    pub(crate) rtt: Option<time::Duration>,
+    /// Number of requests which haven't been responded to in time.
+    pub(crate) timed_out_requests: u64,
 }
 
 impl Default for Tracker {
@@ -56,8 +67,13 @@ impl Default for Tracker {
         Tracker {
             sent_bytes: TransferStats::default(),
             received_bytes: TransferStats::default(),
+            sent_bytes_by_type: HashMap::new(),
+            received_bytes_by_type: HashMap::new(),
             requested: CircularUniqueQueue::new(MAX_TRACK_SIZE),
             received: CircularUniqueQueue::new(MAX_TRACK_SIZE),
+            pending: HashMap::new(),
+            rtt: None,
+            timed_out_requests: 0,
         }
     }
 }
@@ -67,8 +83,39 @@ impl Tracker {
         self.received_bytes.record(clock, size);
     }
 
-    pub(crate) fn increment_sent(&mut self, clock: &time::Clock, size: u64) {
+    pub(crate) fn increment_received_by_type(
+        &mut self,
+        clock: &time::Clock,
+        msg_type: &'static str,
+        size: u64,
+    ) {
+        self.received_bytes_by_type.entry(msg_type).or_default().record(clock, size);
+    }
+
+    pub(crate) fn increment_sent(
+        &mut self,
+        clock: &time::Clock,
+        msg_type: &'static str,
+        size: u64,
+    ) {
         self.sent_bytes.record(clock, size);
+        self.sent_bytes_by_type.entry(msg_type).or_default().record(clock, size);
+    }
+
+    /// Avg sent bytes/s over the last minute, by message type.
+    pub(crate) fn sent_bytes_per_sec_by_type(
+        &mut self,
+        clock: &time::Clock,
+    ) -> HashMap<&'static str, u64> {
+        per_sec_by_type(&mut self.sent_bytes_by_type, clock)
+    }
+
+    /// Avg received bytes/s over the last minute, by message type.
+    pub(crate) fn received_bytes_per_sec_by_type(
+        &mut self,
+        clock: &time::Clock,
+    ) -> HashMap<&'static str, u64> {
+        per_sec_by_type(&mut self.received_bytes_by_type, clock)
     }
 
     // TODO: uncomment this once we add a new message type to sync block height
@@ -86,17 +133,108 @@ impl Tracker {
         self.requested.contains(hash)
     }
 
-    pub(crate) fn push_request(&mut self, hash: CryptoHash) {
+    pub(crate) fn push_request(&mut self, clock: &time::Clock, hash: CryptoHash) {
         self.requested.push(hash);
+        self.pending.entry(hash).or_insert(clock.now());
+    }
+
+    /// Marks the request as responded to and returns the latency of the response,
+    /// or None if there was no pending request with the given hash.
+    pub(crate) fn finish_request(
+        &mut self,
+        clock: &time::Clock,
+        hash: &CryptoHash,
+    ) -> Option<time::Duration> {
+        let latency = clock.now() - self.pending.remove(hash)?;
+        self.rtt = Some(match self.rtt {
+            None => latency,
+            Some(rtt) => rtt * 7 / 8 + latency / 8,
+        });
+        Some(latency)
+    }
+
+    /// Number of requests still waiting for a response.
+    pub(crate) fn pending_requests(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Drops the requests which haven't been responded to within `timeout`
+    /// and returns their number.
+    pub(crate) fn expire_requests(
+        &mut self,
+        clock: &time::Clock,
+        timeout: time::Duration,
+    ) -> usize {
+        let now = clock.now();
+        let before = self.pending.len();
+        self.pending.retain(|_, sent| now - *sent < timeout);
+        let timed_out = before - self.pending.len();
+        self.timed_out_requests += timed_out as u64;
+        timed_out
     }
 }
 
+fn per_sec_by_type(
+    stats: &mut HashMap<&'static str, TransferStats>,
+    clock: &time::Clock,
+) -> HashMap<&'static str, u64> {
+    let mut res = HashMap::new();
+    stats.retain(|msg_type, stats| {
+        let stats = stats.minute_stats(clock);
+        if stats.count_per_min == 0 {
+            return false;
+        }
+        res.insert(*msg_type, stats.bytes_per_min / 60);
+        true
+    });
+    res
+}
+
 #[cfg(test)]
 mod tests {
     use near_primitives::hash::hash;
 
     use super::*;
 
+    #[test]
+    fn test_pending_requests() {
+        let clock = time::FakeClock::default();
+        let mut tracker = Tracker::default();
+        tracker.push_request(&clock.clock(), hash(&[1]));
+        tracker.push_request(&clock.clock(), hash(&[2]));
+        clock.advance(time::Duration::seconds(1));
+        tracker.push_request(&clock.clock(), hash(&[3]));
+        assert_eq!(
+            Some(time::Duration::seconds(1)),
+            tracker.finish_request(&clock.clock(), &hash(&[1]))
+        );
+        assert_eq!(None, tracker.finish_request(&clock.clock(), &hash(&[1])));
+        assert!(tracker.has_request(&hash(&[1])));
+        assert_eq!(1, tracker.expire_requests(&clock.clock(), time::Duration::seconds(1)));
+        assert_eq!(0, tracker.expire_requests(&clock.clock(), time::Duration::seconds(1)));
+        assert!(tracker.finish_request(&clock.clock(), &hash(&[3])).is_some());
+        assert_eq!(0, tracker.pending_requests());
+        assert_eq!(1, tracker.timed_out_requests);
+    }
+
+    #[test]
+    fn test_rtt() {
+        let clock = time::FakeClock::default();
+        let mut tracker = Tracker::default();
+        assert_eq!(None, tracker.rtt);
+        tracker.push_request(&clock.clock(), hash(&[1]));
+        clock.advance(time::Duration::milliseconds(800));
+        tracker.finish_request(&clock.clock(), &hash(&[1]));
+        assert_eq!(Some(time::Duration::milliseconds(800)), tracker.rtt);
+        tracker.push_request(&clock.clock(), hash(&[2]));
+        clock.advance(time::Duration::milliseconds(1600));
+        tracker.finish_request(&clock.clock(), &hash(&[2]));
+        assert_eq!(Some(time::Duration::milliseconds(900)), tracker.rtt);
+        // Responses to unknown requests don't affect the RTT.
+        tracker.finish_request(&clock.clock(), &hash(&[3]));
+        assert_eq!(Some(time::Duration::milliseconds(900)), tracker.rtt);
+    }
+
     #[test]
     #[should_panic]
     fn test_circular_queue_zero_capacity() {
@@ -165,4 +303,32 @@ mod tests {
         }
         assert!(q.contains(&hash(&[5])));
     }
+
+    #[test]
+    fn test_bytes_by_type() {
+        let clock = time::FakeClock::default();
+        let mut tracker = Tracker::default();
+        tracker.increment_sent(&clock.clock(), "Block", 6000);
+        tracker.increment_sent(&clock.clock(), "Block", 600);
+        tracker.increment_sent(&clock.clock(), "VersionedStateResponse", 60000);
+        tracker.increment_received_by_type(&clock.clock(), "BlockRequest", 60);
+        assert_eq!(66600, tracker.sent_bytes.minute_stats(&clock.clock()).bytes_per_min);
+        assert_eq!(
+            HashMap::from([("Block", 110), ("VersionedStateResponse", 1000)]),
+            tracker.sent_bytes_per_sec_by_type(&clock.clock())
+        );
+        assert_eq!(
+            HashMap::from([("BlockRequest", 1)]),
+            tracker.received_bytes_per_sec_by_type(&clock.clock())
+        );
+        // Types without recent traffic are dropped.
+        clock.advance(time::Duration::seconds(30));
+        tracker.increment_sent(&clock.clock(), "Block", 60);
+        clock.advance(time::Duration::seconds(31));
+        assert_eq!(
+            HashMap::from([("Block", 1)]),
+            tracker.sent_bytes_per_sec_by_type(&clock.clock())
+        );
+        assert_eq!(HashMap::new(), tracker.received_bytes_per_sec_by_type(&clock.clock()));
+    }
 }
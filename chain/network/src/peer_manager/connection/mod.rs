@@ -16,7 +16,7 @@ use near_primitives::types::ShardId;
 use std::collections::{hash_map::Entry, HashMap};
 use std::fmt;
 use std::future::Future;
-use std::sync::atomic::AtomicU64;
+use std::sync::atomic::{AtomicBool, AtomicU64};
 use std::sync::{Arc, Weak};
 
 #[cfg(test)]
@@ -32,11 +32,24 @@ pub(crate) struct Stats {
     pub received_bytes_per_sec: AtomicU64,
     /// Avg sent bytes/s, based on the last few minutes of traffic.
     pub sent_bytes_per_sec: AtomicU64,
+    /// Avg received bytes/s by message type, based on the last minute of traffic.
+    pub received_bytes_per_sec_by_type: parking_lot::Mutex<HashMap<&'static str, u64>>,
+    /// Avg sent bytes/s by message type, based on the last minute of traffic.
+    pub sent_bytes_per_sec_by_type: parking_lot::Mutex<HashMap<&'static str, u64>>,
 
     /// Number of messages in the buffer to send.
     pub messages_to_send: AtomicU64,
     /// Number of bytes (sum of message sizes) in the buffer to send.
     pub bytes_to_send: AtomicU64,
+
+    /// Smoothed latency of responses to block and chunk requests.
+    pub rtt: parking_lot::Mutex<Option<time::Duration>>,
+    /// Number of block and chunk requests waiting for a response.
+    pub pending_requests: AtomicU64,
+    /// Number of block and chunk requests which haven't been responded to in time.
+    pub timed_out_requests: AtomicU64,
+    /// Whether metrics labelled with this peer are exported.
+    pub per_peer_metrics: AtomicBool,
 }
 
 /// Contains information relevant to a connected peer.
@@ -57,6 +70,8 @@ pub(crate) struct Connection {
 
     /// Who started connection. Inbound (other) or Outbound (us).
     pub peer_type: PeerType,
+    /// Whether encryption of the connection was negotiated in the handshake.
+    pub encrypted: bool,
     /// Time where the connection was established.
     pub connection_established_time: time::Instant,
 
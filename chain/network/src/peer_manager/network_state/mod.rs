@@ -23,6 +23,8 @@ use near_primitives::hash::CryptoHash;
 use near_primitives::network::PeerId;
 use near_primitives::types::AccountId;
 use parking_lot::Mutex;
+use rand::rngs::StdRng;
+use rand::SeedableRng as _;
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -74,6 +76,7 @@ pub(crate) struct NetworkState {
     /// as it will be automatically closed only when the NetworkState is dropped.
     /// WARNING: actix actors can be spawned only when actix::System::current() is set.
     /// DO NOT spawn actors from a task on this runtime.
+    /// If `config.arbiter` is set, the runtime of the arbiter's thread is used instead.
     runtime: Runtime,
     /// PeerManager config.
     pub config: config::VerifiedConfig,
@@ -91,6 +94,8 @@ pub(crate) struct NetworkState {
     pub tier2: connection::Pool,
     /// Semaphore limiting inflight inbound handshakes.
     pub inbound_handshake_permits: Arc<tokio::sync::Semaphore>,
+    /// Semaphore limiting the number of connections with per-peer metrics.
+    pub per_peer_metrics_permits: Arc<tokio::sync::Semaphore>,
     /// Peer store that provides read/write access to peers.
     pub peer_store: peer_store::PeerStore,
     /// A graph of the whole NEAR network.
@@ -116,6 +121,12 @@ pub(crate) struct NetworkState {
     /// Mutex serializing calls to set_chain_info(), which mutates a bunch of stuff non-atomically.
     /// TODO(gprusak): make it use synchronization primitives in some more canonical way.
     set_chain_info_mutex: Mutex<()>,
+
+    /// RNG for the random choices of the node, seeded with `config.rng_seed` if set.
+    pub rng: Mutex<StdRng>,
+
+    /// Writer of the captured traffic of all the connections, if capture is enabled.
+    pub capture: Option<crate::capture::Writer>,
 }
 
 impl NetworkState {
@@ -128,21 +139,33 @@ impl NetworkState {
         client: Arc<dyn client::Client>,
         whitelist_nodes: Vec<WhitelistNode>,
     ) -> Self {
+        let graph = Arc::new(crate::routing::Graph::new(
+            crate::routing::GraphConfig {
+                node_id: config.node_id(),
+                prune_unreachable_peers_after: PRUNE_UNREACHABLE_PEERS_AFTER,
+                prune_edges_after: Some(PRUNE_EDGES_AFTER),
+            },
+            store.clone(),
+        ));
+        graph.load_snapshot(clock, &config.node_key);
         Self {
-            runtime: Runtime::new(),
-            graph: Arc::new(crate::routing::Graph::new(
-                crate::routing::GraphConfig {
-                    node_id: config.node_id(),
-                    prune_unreachable_peers_after: PRUNE_UNREACHABLE_PEERS_AFTER,
-                    prune_edges_after: Some(PRUNE_EDGES_AFTER),
-                },
-                store.clone(),
-            )),
+            runtime: match &config.arbiter {
+                Some(_) => Runtime::current(),
+                None => Runtime::new(),
+            },
+            rng: Mutex::new(match config.rng_seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            }),
+            graph,
             genesis_id,
             client,
             chain_info: Default::default(),
             tier2: connection::Pool::new(config.node_id()),
             inbound_handshake_permits: Arc::new(tokio::sync::Semaphore::new(LIMIT_PENDING_PEERS)),
+            per_peer_metrics_permits: Arc::new(tokio::sync::Semaphore::new(
+                config.per_peer_metrics_limit,
+            )),
             peer_store,
             accounts_data: Arc::new(accounts_data::Cache::new()),
             txns_since_last_block: AtomicUsize::new(0),
@@ -150,6 +173,7 @@ impl NetworkState {
             max_num_peers: AtomicU32::new(config.max_num_peers),
             add_edges_demux: demux::Demux::new(config.routing_table_update_rate_limit),
             set_chain_info_mutex: Mutex::new(()),
+            capture: config.capture.clone().map(crate::capture::Writer::new),
             config,
             created_at: clock.now(),
         }
@@ -1,10 +1,22 @@
 use crate::accounts_data;
 use crate::config;
-use crate::network_protocol::{AccountData, PeerMessage, SignedAccountData, SyncAccountsData};
+use crate::network_protocol::{
+    AccountData, PeerAddr, PeerInfo, PeerMessage, SignedAccountData, SyncAccountsData,
+};
+use crate::peer::peer_actor::PeerActor;
 use crate::peer_manager::peer_manager_actor::Event;
+use crate::stun;
 use crate::time;
+use anyhow::Context as _;
+use near_primitives::network::PeerId;
 use std::sync::Arc;
 
+/// How often to check whether the handshake with a proxy has completed.
+const PROXY_HANDSHAKE_POLL_PERIOD: time::Duration = time::Duration::milliseconds(100);
+
+/// Timeout of a single STUN query.
+const STUN_QUERY_TIMEOUT: time::Duration = time::Duration::seconds(5);
+
 impl super::NetworkState {
     // Returns ValidatorConfig of this node iff it belongs to TIER1 according to `accounts_data`.
     pub fn tier1_validator_config(
@@ -20,6 +32,142 @@ impl super::NetworkState {
             .filter(|cfg| accounts_data.keys.contains(&cfg.signer.public_key()))
     }
 
+    /// Checks whether the peer belongs to the TIER1 network, i.e. whether it is a TIER1 validator
+    /// or a proxy of a TIER1 validator (including the proxies of this node).
+    pub fn is_tier1_peer(&self, peer_id: &PeerId) -> bool {
+        let accounts_data = self.accounts_data.load();
+        if let Some(vc) = self.tier1_validator_config(&accounts_data) {
+            if let config::ValidatorProxies::Static(proxies) = &vc.proxies {
+                if proxies.iter().any(|p| &p.peer_id == peer_id) {
+                    return true;
+                }
+            }
+        }
+        accounts_data
+            .data
+            .values()
+            .any(|d| &d.peer_id == peer_id || d.proxies.iter().any(|p| &p.peer_id == peer_id))
+    }
+
+    /// Checks whether the handshake with the proxy has completed and the connection satisfies
+    /// the TIER1 requirements, i.e. it is encrypted if `tier1.require_encryption` is set.
+    fn tier1_proxy_ready(&self, peer_id: &PeerId) -> bool {
+        let require_encryption =
+            self.config.tier1.as_ref().map_or(false, |cfg| cfg.require_encryption);
+        self.tier2
+            .load()
+            .ready
+            .get(peer_id)
+            .map_or(false, |conn| conn.encrypted || !require_encryption)
+    }
+
+    /// Checks that this node accepts connections on the given address, before it is advertised
+    /// as a proxy. The node cannot complete a handshake with itself, so an accepted connection
+    /// is the best evidence of reachability available.
+    async fn tier1_check_own_addr(&self, addr: &PeerAddr) -> bool {
+        let peer_info =
+            PeerInfo { id: addr.peer_id.clone(), addr: Some(addr.addr), account_id: None };
+        match self.config.transport.connect(&peer_info).await {
+            Ok(_) => true,
+            Err(err) => {
+                tracing::info!(target: "network", ?addr, ?err, "own TIER1 proxy address is not reachable");
+                false
+            }
+        }
+    }
+
+    /// Resolves the proxy address, connects to the proxy (unless already connected) and waits
+    /// for the handshake to complete. Returns the resolved address iff the proxy is connected.
+    async fn tier1_connect_to_proxy(
+        self: &Arc<Self>,
+        clock: &time::Clock,
+        proxy: &config::ProxyAddr,
+    ) -> Option<PeerAddr> {
+        // The address is resolved on every call, so that a proxy which has changed its IP is
+        // reconnected to and advertised with the new IP.
+        let res = async {
+            tokio::net::lookup_host(&proxy.addr)
+                .await
+                .context("lookup_host()")?
+                .next()
+                .context("no addresses found")
+        }
+        .await;
+        let addr = match res {
+            Ok(addr) => PeerAddr { addr, peer_id: proxy.peer_id.clone() },
+            Err(err) => {
+                tracing::info!(target: "network", ?proxy, ?err, "failed to resolve TIER1 proxy");
+                return None;
+            }
+        };
+        // A node can be its own proxy. It cannot complete a handshake with itself, so only
+        // reachability of the address is checked.
+        if proxy.peer_id == self.config.node_id() {
+            return self.tier1_check_own_addr(&addr).await.then_some(addr);
+        }
+        let is_ready = || self.tier1_proxy_ready(&proxy.peer_id);
+        if is_ready() {
+            return Some(addr);
+        }
+        let peer_info =
+            PeerInfo { id: proxy.peer_id.clone(), addr: Some(addr.addr), account_id: None };
+        let res = async {
+            let stream = self.config.transport.connect(&peer_info).await?;
+            PeerActor::spawn(clock.clone(), stream, None, self.clone())?;
+            anyhow::Ok(())
+        }
+        .await;
+        if let Err(err) = res {
+            tracing::info!(target: "network", ?proxy, ?err, "failed to connect to TIER1 proxy");
+            return None;
+        }
+        let deadline = clock.now() + self.config.handshake_timeout;
+        while clock.now() < deadline {
+            if is_ready() {
+                return Some(addr);
+            }
+            clock.sleep(PROXY_HANDSHAKE_POLL_PERIOD).await;
+        }
+        is_ready().then_some(addr)
+    }
+
+    /// Resolves the list of proxies of this validator which are healthy at the moment:
+    /// * static proxies are those to which this node has managed to establish a connection,
+    ///   with their addresses resolved at the time of the check.
+    /// * dynamic proxy is this node itself, with the public IP resolved via STUN, as long as
+    ///   the node accepts connections on that IP.
+    async fn tier1_healthy_proxies(
+        self: &Arc<Self>,
+        clock: &time::Clock,
+        vc: &config::ValidatorConfig,
+    ) -> Vec<PeerAddr> {
+        match &vc.proxies {
+            config::ValidatorProxies::Dynamic(stun_servers) => {
+                let Some(node_addr) = self.config.node_addr else {
+                    return vec![];
+                };
+                let Some(ip) = stun::resolve_public_ip(stun_servers, STUN_QUERY_TIMEOUT).await else {
+                    return vec![];
+                };
+                let addr = PeerAddr {
+                    addr: std::net::SocketAddr::new(ip, node_addr.port()),
+                    peer_id: self.config.node_id(),
+                };
+                if !self.tier1_check_own_addr(&addr).await {
+                    return vec![];
+                }
+                vec![addr]
+            }
+            config::ValidatorProxies::Static(proxies) => futures::future::join_all(
+                proxies.iter().map(|proxy| self.tier1_connect_to_proxy(clock, proxy)),
+            )
+            .await
+            .into_iter()
+            .flatten()
+            .collect(),
+        }
+    }
+
     /// Tries to connect to ALL trusted proxies from the config, then broadcasts AccountData with
     /// the set of proxies it managed to connect to. This way other TIER1 nodes can just connect
     /// to ANY proxy of this node.
@@ -28,15 +176,38 @@ impl super::NetworkState {
         clock: &time::Clock,
     ) -> Vec<Arc<SignedAccountData>> {
         let accounts_data = self.accounts_data.load();
-        let Some(vc) = self.tier1_validator_config(&accounts_data) else {
+        let Some(vc) = self.tier1_validator_config(&accounts_data).cloned() else {
             return vec![];
         };
-        // TODO(gprusak): for now we just blindly broadcast the static list of proxies, however
-        // here we should try to connect to the TIER1 proxies, before broadcasting them.
-        let my_proxies = match &vc.proxies {
-            config::ValidatorProxies::Dynamic(_) => vec![],
-            config::ValidatorProxies::Static(proxies) => proxies.clone(),
+        let my_proxies = self.tier1_healthy_proxies(clock, &vc).await;
+        self.tier1_broadcast_proxies(clock, &vc, my_proxies).await
+    }
+
+    /// Checks the health of the trusted proxies and broadcasts AccountData iff the set of
+    /// healthy proxies differs from the one advertised most recently.
+    pub async fn tier1_check_proxies(
+        self: &Arc<Self>,
+        clock: &time::Clock,
+    ) -> Vec<Arc<SignedAccountData>> {
+        let accounts_data = self.accounts_data.load();
+        let Some(vc) = self.tier1_validator_config(&accounts_data).cloned() else {
+            return vec![];
         };
+        let my_proxies = self.tier1_healthy_proxies(clock, &vc).await;
+        let advertised = self.accounts_data.load().data.get(&vc.signer.public_key()).cloned();
+        if advertised.map_or(false, |d| d.proxies == my_proxies) {
+            return vec![];
+        }
+        tracing::info!(target: "network", ?my_proxies, "set of healthy TIER1 proxies has changed");
+        self.tier1_broadcast_proxies(clock, &vc, my_proxies).await
+    }
+
+    async fn tier1_broadcast_proxies(
+        self: &Arc<Self>,
+        clock: &time::Clock,
+        vc: &config::ValidatorConfig,
+        my_proxies: Vec<PeerAddr>,
+    ) -> Vec<Arc<SignedAccountData>> {
         let now = clock.now_utc();
         let version =
             self.accounts_data.load().data.get(&vc.signer.public_key()).map_or(0, |d| d.version)
@@ -47,7 +218,7 @@ impl super::NetworkState {
             AccountData {
                 peer_id: self.config.node_id(),
                 account_key: vc.signer.public_key(),
-                proxies: my_proxies.clone(),
+                proxies: my_proxies,
                 timestamp: now,
                 version,
             }
@@ -1,9 +1,11 @@
+use crate::actix::run_later;
 use crate::client;
 use crate::config;
 use crate::debug::{DebugStatus, GetDebugStatus};
+use crate::dns_seed;
 use crate::network_protocol::{
-    AccountOrPeerIdOrHash, Edge, PeerMessage, Ping, Pong, RawRoutedMessage, RoutedMessageBody,
-    SignedAccountData, StateResponseInfo,
+    AccountOrPeerIdOrHash, Edge, PeerInfo, PeerMessage, Ping, Pong, RawRoutedMessage,
+    RoutedMessageBody, SignedAccountData, StateResponseInfo,
 };
 use crate::peer::peer_actor::PeerActor;
 use crate::peer_manager::connection;
@@ -12,7 +14,6 @@ use crate::peer_manager::peer_store;
 use crate::routing;
 use crate::stats::metrics;
 use crate::store;
-use crate::tcp;
 use crate::time;
 use crate::types::{
     ConnectedPeerInfo, GetNetworkInfo, HighestHeightPeerInfo, KnownProducer, NetworkInfo,
@@ -28,12 +29,11 @@ use near_primitives::block::GenesisId;
 use near_primitives::network::{AnnounceAccount, PeerId};
 use near_primitives::views::EdgeView;
 use near_primitives::views::NetworkGraphView;
-use near_primitives::views::{KnownPeerStateView, PeerStoreView};
-use rand::seq::IteratorRandom;
-use rand::thread_rng;
+use near_primitives::views::{KnownPeerStateView, PeerStatusView, PeerStoreView};
+use rand::seq::{IteratorRandom, SliceRandom};
 use rand::Rng;
 use std::cmp::min;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use tracing::Instrument as _;
@@ -47,6 +47,8 @@ const MONITOR_PEERS_INITIAL_DURATION: time::Duration = time::Duration::milliseco
 const FIX_LOCAL_EDGES_INTERVAL: time::Duration = time::Duration::seconds(60);
 /// How much time we give fix_local_edges() to resolve the discrepancies, before forcing disconnect.
 const FIX_LOCAL_EDGES_TIMEOUT: time::Duration = time::Duration::seconds(6);
+/// How often the routing table snapshot is persisted (it is also persisted on shutdown).
+const SAVE_ROUTING_TABLE_SNAPSHOT_INTERVAL: time::Duration = time::Duration::minutes(5);
 
 /// How often to report bandwidth stats.
 const REPORT_BANDWIDTH_STATS_TRIGGER_INTERVAL: time::Duration =
@@ -111,6 +113,8 @@ pub enum Event {
     MessageProcessed(PeerMessage),
     // Reported every time a new list of proxies has been constructed.
     Tier1AdvertiseProxies(Vec<Arc<SignedAccountData>>),
+    // Reported every time the DNS seeds have been resolved.
+    DnsSeedsResolved(Vec<PeerInfo>),
     // Reported when a handshake has been started.
     HandshakeStarted(crate::peer::peer_actor::HandshakeStartedEvent),
     // Reported when a handshake has been successfully completed.
@@ -129,7 +133,7 @@ impl actix::Actor for PeerManagerActor {
             let clock = self.clock.clone();
             let state = self.state.clone();
             ctx.spawn(wrap_future(async move {
-                let mut listener = match tcp::Listener::bind(server_addr).await {
+                let mut listener = match state.config.transport.bind(server_addr).await {
                     Ok(it) => it,
                     Err(e) => {
                         panic!("failed to start listening on server_addr={server_addr:?} e={e:?}")
@@ -177,19 +181,37 @@ impl actix::Actor for PeerManagerActor {
             }
         }));
 
+        // Periodically persist the routing table, so that it can be restored after restart.
+        let clock = self.clock.clone();
+        let state = self.state.clone();
+        ctx.spawn(wrap_future(async move {
+            let mut interval = time::Interval::new(
+                clock.now() + SAVE_ROUTING_TABLE_SNAPSHOT_INTERVAL,
+                SAVE_ROUTING_TABLE_SNAPSHOT_INTERVAL,
+            );
+            loop {
+                interval.tick(&clock).await;
+                state.graph.save_snapshot();
+            }
+        }));
+
         // Periodically prints bandwidth stats for each peer.
         self.report_bandwidth_stats_trigger(ctx, REPORT_BANDWIDTH_STATS_TRIGGER_INTERVAL);
     }
 
-    /// Try to gracefully disconnect from connected peers.
+    /// Try to gracefully disconnect from connected peers and persist the routing table.
     fn stopping(&mut self, _ctx: &mut Self::Context) -> actix::Running {
         tracing::warn!("PeerManager: stopping");
         self.state.tier2.broadcast_message(Arc::new(PeerMessage::Disconnect));
+        self.state.graph.save_snapshot();
         actix::Running::Stop
     }
 
     fn stopped(&mut self, _ctx: &mut Self::Context) {
-        actix::Arbiter::current().stop();
+        // The arbiter is dedicated to this actor, unless it was provided in the config.
+        if self.state.config.arbiter.is_none() {
+            actix::Arbiter::current().stop();
+        }
     }
 }
 
@@ -221,7 +243,7 @@ impl PeerManagerActor {
             }
             v
         };
-        let arbiter = actix::Arbiter::new().handle();
+        let arbiter = config.arbiter.clone().unwrap_or_else(|| actix::Arbiter::new().handle());
         let state = Arc::new(NetworkState::new(
             &clock,
             store.clone(),
@@ -244,6 +266,45 @@ impl PeerManagerActor {
                     }
                 }
             });
+            // Check health of TIER1 proxies periodically and re-broadcast the list whenever
+            // the set of healthy proxies changes.
+            arbiter.spawn({
+                let clock = clock.clone();
+                let state = state.clone();
+                let mut interval = time::Interval::new(clock.now(), cfg.check_proxies_interval);
+                async move {
+                    loop {
+                        interval.tick(&clock).await;
+                        state.tier1_check_proxies(&clock).await;
+                    }
+                }
+            });
+        }
+        if let Some(cfg) = state.config.dns_seeds.clone() {
+            // Resolve DNS seeds periodically and add the discovered peers to the PeerStore.
+            arbiter.spawn({
+                let clock = clock.clone();
+                let state = state.clone();
+                let mut interval = time::Interval::new(clock.now(), cfg.refresh_period);
+                async move {
+                    loop {
+                        interval.tick(&clock).await;
+                        let node_id = state.config.node_id();
+                        let peers: Vec<_> = dns_seed::resolve(&clock, &cfg)
+                            .await
+                            .into_iter()
+                            .filter(|peer_info| peer_info.id != node_id)
+                            .collect();
+                        tracing::debug!(target: "network", "Resolved {} peers from DNS seeds.", peers.len());
+                        if let Err(err) =
+                            state.peer_store.add_indirect_peers(&clock, peers.iter().cloned())
+                        {
+                            tracing::error!(target: "network", ?err, "Fail to update peer store");
+                        }
+                        state.config.event_sink.push(Event::DnsSeedsResolved(peers));
+                    }
+                }
+            });
         }
         Ok(Self::start_in_arbiter(&arbiter, move |_ctx| Self {
             my_peer_id: my_peer_id.clone(),
@@ -287,13 +348,9 @@ impl PeerManagerActor {
             total_msg_received_count, "Bandwidth stats"
         );
 
-        near_performance_metrics::actix::run_later(
-            ctx,
-            every.try_into().unwrap(),
-            move |act, ctx| {
-                act.report_bandwidth_stats_trigger(ctx, every);
-            },
-        );
+        run_later(ctx, &self.clock, every, move |act, ctx| {
+            act.report_bandwidth_stats_trigger(ctx, every);
+        });
     }
 
     /// Check if it is needed to create a new outbound connection.
@@ -372,16 +429,16 @@ impl PeerManagerActor {
     }
 
     /// Check if the number of connections (excluding whitelisted ones) exceeds ideal_connections_hi.
-    /// If so, constructs a safe set of peers and selects one random peer outside of that set
-    /// and sends signal to stop connection to it gracefully.
+    /// If so, constructs a safe set of peers and selects the peer with the lowest score outside
+    /// of that set and sends signal to stop connection to it gracefully.
     ///
     /// Safe set contruction process:
     /// 1. Add all whitelisted peers to the safe set.
     /// 2. If the number of outbound connections is less or equal than minimum_outbound_connections,
     ///    add all outbound connections to the safe set.
     /// 3. Find all peers who sent us a message within the last peer_recent_time_window,
-    ///    and add them one by one to the safe_set (starting from earliest connection time)
-    ///    until safe set has safe_set_size elements.
+    ///    and add them one by one to the safe_set (starting from the highest score, then
+    ///    earliest connection time) until safe set has safe_set_size elements.
     fn maybe_stop_active_connection(&self) {
         let tier2 = self.state.tier2.load();
         let filter_peers = |predicate: &dyn Fn(&connection::Connection) -> bool| -> Vec<_> {
@@ -435,8 +492,12 @@ impl PeerManagerActor {
             .cloned()
             .collect();
 
-        // Sort by established time.
+        // Sort by score (descending), then by established time.
+        // Scores are computed upfront, so that the peer store is locked only once.
+        let scores = self.state.peer_store.peer_scores(&self.clock, tier2.ready.keys());
+        let score = |p: &connection::Connection| scores.get(&p.peer_info.id).copied().unwrap_or(0.);
         active_peers.sort_by_key(|p| p.connection_established_time);
+        active_peers.sort_by(|a, b| score(b).total_cmp(&score(a)));
         // Saturate safe set with recently active peers.
         let set_limit = self.state.config.safe_set_size as usize;
         for p in active_peers {
@@ -447,8 +508,11 @@ impl PeerManagerActor {
         }
 
         // Build valid candidate list to choose the peer to be removed. All peers outside the safe set.
-        let candidates = tier2.ready.values().filter(|p| !safe_set.contains(&p.peer_info.id));
-        if let Some(p) = candidates.choose(&mut rand::thread_rng()) {
+        // Remove the one with the lowest score, choosing randomly among the ones with equal score.
+        let mut candidates: Vec<_> =
+            tier2.ready.values().filter(|p| !safe_set.contains(&p.peer_info.id)).collect();
+        candidates.shuffle(&mut *self.state.rng.lock());
+        if let Some(p) = candidates.into_iter().min_by(|a, b| score(a).total_cmp(&score(b))) {
             tracing::debug!(target: "network", id = ?p.peer_info.id,
                 tier2_len = tier2.ready.len(),
                 ideal_connections_hi = self.state.config.ideal_connections_hi,
@@ -488,9 +552,11 @@ impl PeerManagerActor {
         if self.is_outbound_bootstrap_needed() {
             let tier2 = self.state.tier2.load();
             // With some odds - try picking one of the 'NotConnected' peers -- these are the ones that we were able to connect to in the past.
-            let prefer_previously_connected_peer =
-                thread_rng().gen_bool(PREFER_PREVIOUSLY_CONNECTED_PEER);
+            let mut rng = self.state.rng.lock();
+            let prefer_previously_connected_peer = rng.gen_bool(PREFER_PREVIOUSLY_CONNECTED_PEER);
             if let Some(peer_info) = self.state.peer_store.unconnected_peer(
+                &self.clock,
+                &mut *rng,
                 |peer_state| {
                     // Ignore connecting to ourself
                     self.my_peer_id == peer_state.peer_info.id
@@ -510,7 +576,7 @@ impl PeerManagerActor {
                     let clock = self.clock.clone();
                     async move {
                         let result = async {
-                            let stream = tcp::Stream::connect(&peer_info).await.context("tcp::Stream::connect()")?;
+                            let stream = state.config.transport.connect(&peer_info).await.context("Transport::connect()")?;
                             PeerActor::spawn(clock.clone(),stream,None,state.clone()).context("PeerActor::spawn()")?;
                             anyhow::Ok(())
                         }.await;
@@ -540,13 +606,9 @@ impl PeerManagerActor {
 
         let new_interval = min(max_interval, interval * EXPONENTIAL_BACKOFF_RATIO);
 
-        near_performance_metrics::actix::run_later(
-            ctx,
-            interval.try_into().unwrap(),
-            move |act, ctx| {
-                act.monitor_peers_trigger(ctx, new_interval, (default_interval, max_interval));
-            },
-        );
+        run_later(ctx, &self.clock, interval, move |act, ctx| {
+            act.monitor_peers_trigger(ctx, new_interval, (default_interval, max_interval));
+        });
     }
 
     /// Return whether the message is sent or not.
@@ -579,6 +641,12 @@ impl PeerManagerActor {
                     full_peer_info: cp.full_peer_info(),
                     received_bytes_per_sec: cp.stats.received_bytes_per_sec.load(Ordering::Relaxed),
                     sent_bytes_per_sec: cp.stats.sent_bytes_per_sec.load(Ordering::Relaxed),
+                    received_bytes_per_sec_by_type: cp
+                        .stats
+                        .received_bytes_per_sec_by_type
+                        .lock()
+                        .clone(),
+                    sent_bytes_per_sec_by_type: cp.stats.sent_bytes_per_sec_by_type.lock().clone(),
                     last_time_peer_requested: cp
                         .last_time_peer_requested
                         .load()
@@ -586,6 +654,7 @@ impl PeerManagerActor {
                     last_time_received_message: cp.last_time_received_message.load(),
                     connection_established_time: cp.connection_established_time,
                     peer_type: cp.peer_type,
+                    score: self.state.peer_store.peer_score(&self.clock, &cp.peer_info.id),
                 })
                 .collect(),
             num_connected_peers: tier2.ready.len(),
@@ -633,13 +702,9 @@ impl PeerManagerActor {
             ),
         ));
 
-        near_performance_metrics::actix::run_later(
-            ctx,
-            interval.try_into().unwrap(),
-            move |act, ctx| {
-                act.push_network_info_trigger(ctx, interval);
-            },
-        );
+        run_later(ctx, &self.clock, interval, move |act, ctx| {
+            act.push_network_info_trigger(ctx, interval);
+        });
     }
 
     #[perf]
@@ -770,8 +835,9 @@ impl PeerManagerActor {
                             }
                         }
 
-                        if let Some(matching_peer) = matching_peers.iter().choose(&mut thread_rng())
-                        {
+                        let matching_peer =
+                            matching_peers.iter().choose(&mut *self.state.rng.lock()).cloned();
+                        if let Some(matching_peer) = matching_peer {
                             if self.state.send_message_to_peer(
                                 &self.clock,
                                 self.state.sign_message(
@@ -985,6 +1051,44 @@ impl actix::Handler<WithSpanContext<PeerManagerMessageRequest>> for PeerManagerA
     }
 }
 
+impl PeerManagerActor {
+    /// Status of the connection to the given peer, None if the peer is not connected.
+    fn peer_status_view(&self, peer_id: &PeerId) -> Option<PeerStatusView> {
+        let conn = self.state.tier2.load().ready.get(peer_id)?.clone();
+        let now = self.clock.now();
+        let stats = &conn.stats;
+        let last_block = conn.last_block.load().as_ref().clone();
+        let by_type = |m: &parking_lot::Mutex<HashMap<&'static str, u64>>| {
+            m.lock().iter().map(|(k, v)| (k.to_string(), *v)).collect()
+        };
+        let established = now - conn.connection_established_time;
+        let last_received = now - conn.last_time_received_message.load();
+        Some(PeerStatusView {
+            peer_id: peer_id.clone(),
+            account_id: self.state.graph.routing_table.announced_account(peer_id),
+            addr: format!("{:?}", conn.peer_info.addr),
+            is_outbound_peer: conn.peer_type == PeerType::Outbound,
+            tracked_shards: conn.tracked_shards.clone(),
+            archival: conn.archival,
+            height: last_block.map(|b| b.height),
+            block_hash: last_block.map(|b| b.hash),
+            connection_established_time_millis: established.whole_milliseconds() as u64,
+            last_time_received_message_millis: last_received.whole_milliseconds() as u64,
+            received_bytes_per_sec: stats.received_bytes_per_sec.load(Ordering::Relaxed),
+            sent_bytes_per_sec: stats.sent_bytes_per_sec.load(Ordering::Relaxed),
+            received_bytes_per_sec_by_type: by_type(&stats.received_bytes_per_sec_by_type),
+            sent_bytes_per_sec_by_type: by_type(&stats.sent_bytes_per_sec_by_type),
+            messages_to_send: stats.messages_to_send.load(Ordering::Relaxed),
+            bytes_to_send: stats.bytes_to_send.load(Ordering::Relaxed),
+            rtt_millis: stats.rtt.lock().map(|d| d.whole_milliseconds() as u64),
+            pending_requests: stats.pending_requests.load(Ordering::Relaxed),
+            timed_out_requests: stats.timed_out_requests.load(Ordering::Relaxed),
+            score: self.state.peer_store.peer_score(&self.clock, peer_id).round() as i64,
+            per_peer_metrics: stats.per_peer_metrics.load(Ordering::Relaxed),
+        })
+    }
+}
+
 impl actix::Handler<GetDebugStatus> for PeerManagerActor {
     type Result = DebugStatus;
     fn handle(&mut self, msg: GetDebugStatus, _ctx: &mut actix::Context<Self>) -> Self::Result {
@@ -1010,6 +1114,7 @@ impl actix::Handler<GetDebugStatus> for PeerManagerActor {
                                 (attempt_time.unix_timestamp(), foo)
                             },
                         ),
+                        score: known_peer_state.score.value(self.clock.now_utc()).round() as i64,
                     })
                     .collect::<Vec<_>>();
 
@@ -1034,6 +1139,7 @@ impl actix::Handler<GetDebugStatus> for PeerManagerActor {
                     })
                     .collect(),
             }),
+            GetDebugStatus::Peer(peer_id) => DebugStatus::Peer(self.peer_status_view(&peer_id)),
         }
     }
 }
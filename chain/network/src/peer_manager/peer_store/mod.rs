@@ -8,16 +8,29 @@ use im::hashmap::Entry;
 use im::{HashMap, HashSet};
 use near_primitives::network::PeerId;
 use parking_lot::Mutex;
-use rand::seq::IteratorRandom;
-use rand::thread_rng;
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::Rng;
+use score::ScoreEvent;
 use std::net::SocketAddr;
 use std::ops::Not;
 
+pub(crate) mod score;
 #[cfg(test)]
 mod testonly;
 #[cfg(test)]
 mod tests;
 
+/// Number of random candidates among which the one with the highest score
+/// is chosen.  Choosing among a few random peers rather than all of them
+/// makes sure that peers with low score still get a chance to redeem themselves.
+const SCORE_SAMPLE_SIZE: usize = 3;
+
+/// Maximal number of peers with `Unknown` status, i.e. peers we have only
+/// heard about from other peers or failed to connect to.  Once exceeded, the
+/// least recently seen of them are forgotten.  Banned peers are never
+/// forgotten this way: a ban is lifted only once `ban_window` passes.
+const MAX_UNKNOWN_PEERS: usize = 10000;
+
 /// Level of trust we have about a new (PeerId, Addr) pair.
 #[derive(Eq, PartialEq, Debug, Clone, Copy)]
 enum TrustLevel {
@@ -155,6 +168,47 @@ impl Inner {
         Ok(())
     }
 
+    fn count_banned(&self) -> usize {
+        self.peer_states.values().filter(|st| st.status.is_banned()).count()
+    }
+
+    /// Lifts the bans older than `ban_window`.
+    fn unban_expired(&mut self, now: time::Utc) {
+        let mut to_unban = vec![];
+        for (peer_id, peer_state) in &self.peer_states {
+            if let KnownPeerStatus::Banned(_, ban_time) = peer_state.status {
+                if now < ban_time + self.config.ban_window {
+                    continue;
+                }
+                tracing::info!(target: "network", unbanned = ?peer_id, ?ban_time, "unbanning a peer");
+                to_unban.push(peer_id.clone());
+            }
+        }
+        for peer_id in &to_unban {
+            if let Err(err) = self.peer_unban(&peer_id) {
+                tracing::error!(target: "network", ?err, "Failed to unban a peer");
+            }
+        }
+    }
+
+    /// Forgets the least recently seen `Unknown` peers beyond MAX_UNKNOWN_PEERS.
+    fn remove_excess_unknown(&mut self) -> anyhow::Result<()> {
+        let mut unknown: Vec<_> = (self.peer_states.iter())
+            .filter(|(_, s)| s.status == KnownPeerStatus::Unknown)
+            .map(|(peer_id, s)| (s.last_seen, peer_id.clone()))
+            .collect();
+        if unknown.len() <= MAX_UNKNOWN_PEERS {
+            return Ok(());
+        }
+        unknown.sort_by_key(|(last_seen, _)| *last_seen);
+        let to_remove: Vec<_> = unknown[..unknown.len() - MAX_UNKNOWN_PEERS]
+            .iter()
+            .map(|(_, peer_id)| peer_id.clone())
+            .collect();
+        tracing::debug!(target: "network", count = to_remove.len(), "too many unknown peers, removing the least recently seen");
+        self.delete_peers(&to_remove)
+    }
+
     /// Deletes peers from the internal cache and the persistent store.
     fn delete_peers(&mut self, peer_ids: &[PeerId]) -> anyhow::Result<()> {
         for peer_id in peer_ids {
@@ -168,18 +222,34 @@ impl Inner {
     }
 
     /// Find a random subset of peers based on filter.
-    fn find_peers<F>(&self, filter: F, count: usize) -> Vec<PeerInfo>
+    fn find_peers<F>(&self, rng: &mut impl Rng, filter: F, count: usize) -> Vec<PeerInfo>
     where
         F: FnMut(&&KnownPeerState) -> bool,
     {
         (self.peer_states.values())
             .filter(filter)
-            .choose_multiple(&mut thread_rng(), count)
+            .choose_multiple(rng, count)
             .into_iter()
             .map(|kps| kps.peer_info.clone())
             .collect()
     }
 
+    /// Find the peer with the highest score among a random sample of
+    /// SCORE_SAMPLE_SIZE peers matching the filter.
+    fn find_best_peer<F>(&self, rng: &mut impl Rng, now: time::Utc, filter: F) -> Option<PeerInfo>
+    where
+        F: FnMut(&&KnownPeerState) -> bool,
+    {
+        let mut sample =
+            (self.peer_states.values()).filter(filter).choose_multiple(rng, SCORE_SAMPLE_SIZE);
+        // Order of the sample is not random, shuffle it to break ties randomly.
+        sample.shuffle(rng);
+        sample
+            .into_iter()
+            .max_by(|a, b| a.score.value(now).total_cmp(&b.score.value(now)))
+            .map(|kps| kps.peer_info.clone())
+    }
+
     /// Create new pair between peer_info.id and peer_addr removing
     /// old pairs if necessary.
     fn update_peer_info(
@@ -294,6 +364,7 @@ impl PeerStore {
                 last_seen: peer_state.last_seen,
                 status,
                 last_outbound_attempt: None,
+                score: Default::default(),
             };
 
             let is_blacklisted =
@@ -354,7 +425,7 @@ impl PeerStore {
     }
 
     pub(crate) fn count_banned(&self) -> usize {
-        self.0.lock().peer_states.values().filter(|st| st.status.is_banned()).count()
+        self.0.lock().count_banned()
     }
 
     #[allow(dead_code)]
@@ -446,8 +517,8 @@ impl PeerStore {
         tracing::warn!(target: "network", "Banning peer {} for {:?}", peer_id, ban_reason);
         let mut inner = self.0.lock();
         let mut store = inner.store.clone();
+        let now = clock.now_utc();
         if let Some(peer_state) = inner.peer_states.get_mut(peer_id) {
-            let now = clock.now_utc();
             peer_state.last_seen = now;
             peer_state.status = KnownPeerStatus::Banned(ban_reason, now);
             store.set_peer_state(peer_id, peer_state)?;
@@ -457,16 +528,57 @@ impl PeerStore {
         Ok(())
     }
 
+    /// Records an event affecting the score of the peer.
+    /// Scores are not persisted, so this doesn't touch the DB.
+    pub(crate) fn record_score_event(
+        &self,
+        clock: &time::Clock,
+        peer_id: &PeerId,
+        event: ScoreEvent,
+    ) {
+        if let Some(peer_state) = self.0.lock().peer_states.get_mut(peer_id) {
+            peer_state.score.record(clock.now_utc(), event);
+        }
+    }
+
+    /// Returns the current score of the peer, 0 if the peer is unknown.
+    pub(crate) fn peer_score(&self, clock: &time::Clock, peer_id: &PeerId) -> f64 {
+        self.0.lock().peer_states.get(peer_id).map_or(0., |s| s.score.value(clock.now_utc()))
+    }
+
+    /// Returns the current scores of the given peers, 0 for the unknown ones.
+    /// Scores are read under a single lock, so that they are consistent with each other.
+    pub(crate) fn peer_scores<'a>(
+        &self,
+        clock: &time::Clock,
+        peer_ids: impl Iterator<Item = &'a PeerId>,
+    ) -> std::collections::HashMap<PeerId, f64> {
+        let inner = self.0.lock();
+        let now = clock.now_utc();
+        peer_ids
+            .map(|peer_id| {
+                let score = inner.peer_states.get(peer_id).map_or(0., |s| s.score.value(now));
+                (peer_id.clone(), score)
+            })
+            .collect()
+    }
+
     /// Return unconnected or peers with unknown status that we can try to connect to.
     /// Peers with unknown addresses are filtered out.
+    /// Peers with higher score are preferred.
     pub(crate) fn unconnected_peer(
         &self,
+        clock: &time::Clock,
+        rng: &mut impl Rng,
         ignore_fn: impl Fn(&KnownPeerState) -> bool,
         prefer_previously_connected_peer: bool,
     ) -> Option<PeerInfo> {
         let inner = self.0.lock();
+        let now = clock.now_utc();
         if prefer_previously_connected_peer {
-            let preferred_peer = inner.find_peers(
+            let preferred_peer = inner.find_best_peer(
+                rng,
+                now,
                 |p| {
                     (p.status == KnownPeerStatus::NotConnected)
                         && !ignore_fn(p)
@@ -474,35 +586,29 @@ impl PeerStore {
                         // if we're connecting only to the boot nodes - filter out the nodes that are not bootnodes.
                         && (!inner.config.connect_only_to_boot_nodes || inner.boot_nodes.contains(&p.peer_info.id))
                 },
-                1,
-            )
-            .get(0)
-            .cloned();
+            );
             // If we found a preferred peer - return it.
             if preferred_peer.is_some() {
                 return preferred_peer;
             };
             // otherwise, pick a peer from the wider pool below.
         }
-        inner.find_peers(
-            |p| {
+        inner.find_best_peer(rng, now, |p| {
                 (p.status == KnownPeerStatus::NotConnected || p.status == KnownPeerStatus::Unknown)
                     && !ignore_fn(p)
                     && p.peer_info.addr.is_some()
                     // If we're connecting only to the boot nodes - filter out the nodes that are not boot nodes.
                     && (!inner.config.connect_only_to_boot_nodes || inner.boot_nodes.contains(&p.peer_info.id))
-            },
-            1,
-        )
-        .get(0)
-        .cloned()
+        })
     }
 
     /// Return healthy known peers up to given amount.
-    pub(crate) fn healthy_peers(&self, max_count: usize) -> Vec<PeerInfo> {
-        self.0
-            .lock()
-            .find_peers(|p| matches!(p.status, KnownPeerStatus::Banned(_, _)).not(), max_count)
+    pub(crate) fn healthy_peers(&self, rng: &mut impl Rng, max_count: usize) -> Vec<PeerInfo> {
+        self.0.lock().find_peers(
+            rng,
+            |p| matches!(p.status, KnownPeerStatus::Banned(_, _)).not(),
+            max_count,
+        )
     }
 
     /// Removes peers that are not responding for expiration period.
@@ -551,6 +657,7 @@ impl PeerStore {
             tracing::info!(target: "network", "Ignored {} blacklisted peers out of {} indirect peer(s)",
                   blacklisted, total);
         }
+        inner.remove_excess_unknown()?;
         Ok(())
     }
 
@@ -570,23 +677,7 @@ impl PeerStore {
     }
 
     pub fn unban(&self, clock: &time::Clock) {
-        let mut inner = self.0.lock();
-        let now = clock.now_utc();
-        let mut to_unban = vec![];
-        for (peer_id, peer_state) in &inner.peer_states {
-            if let KnownPeerStatus::Banned(_, ban_time) = peer_state.status {
-                if now < ban_time + inner.config.ban_window {
-                    continue;
-                }
-                tracing::info!(target: "network", unbanned = ?peer_id, ?ban_time, "unbanning a peer");
-                to_unban.push(peer_id.clone());
-            }
-        }
-        for peer_id in &to_unban {
-            if let Err(err) = inner.peer_unban(&peer_id) {
-                tracing::error!(target: "network", ?err, "Failed to unban a peer");
-            }
-        }
+        self.0.lock().unban_expired(clock.now_utc());
     }
 
     pub fn load(&self) -> HashMap<PeerId, KnownPeerState> {
@@ -0,0 +1,75 @@
+use crate::time;
+
+/// Score of a peer is kept within [-MAX_SCORE, MAX_SCORE].
+pub const MAX_SCORE: f64 = 100.;
+
+/// Past behaviour of a peer matters less the older it is: the score decays
+/// exponentially towards 0 with this half-life.
+const SCORE_HALF_LIFE: time::Duration = time::Duration::hours(1);
+
+/// Responses faster than that are rewarded.
+const FAST_RESPONSE: time::Duration = time::Duration::milliseconds(500);
+/// Responses slower than that are penalised.
+const SLOW_RESPONSE: time::Duration = time::Duration::seconds(2);
+
+/// Events observed by PeerActor which affect the score of the peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ScoreEvent {
+    /// Peer responded to our block or chunk request.
+    Response { latency: time::Duration },
+    /// Peer didn't respond to our block or chunk request in time.
+    RequestTimeout,
+    /// Peer sent us invalid data and the connection has been closed with a ban.
+    InvalidData,
+    /// Peer authored a message which we had to drop.
+    UselessMessage,
+    /// Peer delivered a block we have requested.
+    UsefulBlock,
+    /// Peer delivered chunk parts we have requested or which were pushed to us.
+    UsefulChunk,
+}
+
+impl ScoreEvent {
+    fn delta(&self) -> f64 {
+        match self {
+            Self::Response { latency } if *latency <= FAST_RESPONSE => 1.,
+            Self::Response { latency } if *latency <= SLOW_RESPONSE => 0.,
+            Self::Response { .. } => -1.,
+            Self::RequestTimeout => -5.,
+            Self::InvalidData => -50.,
+            Self::UselessMessage => -1.,
+            Self::UsefulBlock => 2.,
+            Self::UsefulChunk => 1.,
+        }
+    }
+}
+
+/// Reputation of a peer, built from the events observed on connections to it.
+///
+/// Score starts at 0, and decays back to 0 over time, so that both good and
+/// bad reputation has to be maintained.  Peers with higher score are preferred
+/// when choosing which peers to connect to and which connections to keep.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PeerScore {
+    value: f64,
+    /// Time at which `value` was last updated.
+    updated: Option<time::Utc>,
+}
+
+impl PeerScore {
+    /// Returns the score at the given time.
+    pub fn value(&self, now: time::Utc) -> f64 {
+        match self.updated {
+            None => self.value,
+            Some(updated) => {
+                let elapsed = (now - updated).max(time::Duration::ZERO);
+                self.value * 0.5f64.powf(elapsed / SCORE_HALF_LIFE)
+            }
+        }
+    }
+
+    pub(crate) fn record(&mut self, now: time::Utc, event: ScoreEvent) {
+        self.value = (self.value(now) + event.delta()).clamp(-MAX_SCORE, MAX_SCORE);
+        self.updated = Some(now);
+    }
+}
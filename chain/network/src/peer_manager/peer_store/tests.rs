@@ -3,6 +3,7 @@ use crate::blacklist::Blacklist;
 use crate::time;
 use near_crypto::{KeyType, SecretKey};
 use near_store::{Mode, NodeStorage, StoreOpener};
+use rand::thread_rng;
 use std::collections::HashSet;
 use std::net::{Ipv4Addr, SocketAddrV4};
 
@@ -55,9 +56,9 @@ fn ban_store() {
             store,
         )
         .unwrap();
-        assert_eq!(peer_store.healthy_peers(3).len(), 2);
+        assert_eq!(peer_store.healthy_peers(&mut thread_rng(), 3).len(), 2);
         peer_store.peer_ban(&clock.clock(), &peer_info_to_ban.id, ReasonForBan::Abusive).unwrap();
-        assert_eq!(peer_store.healthy_peers(3).len(), 1);
+        assert_eq!(peer_store.healthy_peers(&mut thread_rng(), 3).len(), 1);
     }
     {
         let store_new = store::Store::from(opener.open().unwrap());
@@ -67,7 +68,7 @@ fn ban_store() {
             store_new,
         )
         .unwrap();
-        assert_eq!(peer_store_new.healthy_peers(3).len(), 1);
+        assert_eq!(peer_store_new.healthy_peers(&mut thread_rng(), 3).len(), 1);
     }
 }
 
@@ -85,8 +86,12 @@ fn test_unconnected_peer() {
             store,
         )
         .unwrap();
-        assert!(peer_store.unconnected_peer(|_| false, false).is_some());
-        assert!(peer_store.unconnected_peer(|_| true, false).is_none());
+        assert!(peer_store
+            .unconnected_peer(&clock.clock(), &mut thread_rng(), |_| false, false)
+            .is_some());
+        assert!(peer_store
+            .unconnected_peer(&clock.clock(), &mut thread_rng(), |_| true, false)
+            .is_none());
     }
 }
 
@@ -162,7 +167,10 @@ fn test_unknown_vs_not_connected() {
         // if we prefer 'previously connected' peers - we should keep picking 'b'.
         assert_eq!(
             (0..10)
-                .map(|_| peer_store.unconnected_peer(|_| false, true).unwrap().id)
+                .map(|_| peer_store
+                    .unconnected_peer(&clock.clock(), &mut thread_rng(), |_| false, true)
+                    .unwrap()
+                    .id)
                 .collect::<HashSet<PeerId>>(),
             [peer_info_b.id.clone()].into_iter().collect()
         );
@@ -170,7 +178,10 @@ fn test_unknown_vs_not_connected() {
         // if we don't care, we should pick either 'b' or 'boot'.
         assert_eq!(
             (0..100)
-                .map(|_| peer_store.unconnected_peer(|_| false, false).unwrap().id)
+                .map(|_| peer_store
+                    .unconnected_peer(&clock.clock(), &mut thread_rng(), |_| false, false)
+                    .unwrap()
+                    .id)
                 .collect::<HashSet<PeerId>>(),
             [peer_info_b.id.clone(), peer_info_boot_node.id.clone()].into_iter().collect()
         );
@@ -209,7 +220,10 @@ fn test_unknown_vs_not_connected() {
         // After restart - we should try to connect to 'a' (if we prefer previously connected nodes).
         assert_eq!(
             (0..10)
-                .map(|_| peer_store.unconnected_peer(|_| false, true).unwrap().id)
+                .map(|_| peer_store
+                    .unconnected_peer(&clock.clock(), &mut thread_rng(), |_| false, true)
+                    .unwrap()
+                    .id)
                 .collect::<HashSet<PeerId>>(),
             [peer_info_a.id.clone()].into_iter().collect()
         );
@@ -236,7 +250,10 @@ fn test_unconnected_peer_only_boot_nodes() {
         .unwrap();
         peer_store.add_direct_peer(&clock.clock(), peer_in_store.clone()).unwrap();
         peer_store.peer_connected(&clock.clock(), &peer_info_a).unwrap();
-        assert_eq!(peer_store.unconnected_peer(|_| false, false), Some(peer_in_store.clone()));
+        assert_eq!(
+            peer_store.unconnected_peer(&clock.clock(), &mut thread_rng(), |_| false, false),
+            Some(peer_in_store.clone())
+        );
     }
 
     // 1 boot node (peer_info_a) that we're already connected to.
@@ -252,7 +269,10 @@ fn test_unconnected_peer_only_boot_nodes() {
         .unwrap();
         peer_store.add_direct_peer(&clock.clock(), peer_in_store.clone()).unwrap();
         peer_store.peer_connected(&clock.clock(), &peer_info_a).unwrap();
-        assert_eq!(peer_store.unconnected_peer(|_| false, false), None);
+        assert_eq!(
+            peer_store.unconnected_peer(&clock.clock(), &mut thread_rng(), |_| false, false),
+            None
+        );
     }
 
     // 1 boot node (peer_info_a) is in the store.
@@ -266,7 +286,10 @@ fn test_unconnected_peer_only_boot_nodes() {
         )
         .unwrap();
         peer_store.add_direct_peer(&clock.clock(), peer_info_a.clone()).unwrap();
-        assert_eq!(peer_store.unconnected_peer(|_| false, false), Some(peer_info_a.clone()));
+        assert_eq!(
+            peer_store.unconnected_peer(&clock.clock(), &mut thread_rng(), |_| false, false),
+            Some(peer_info_a.clone())
+        );
     }
 }
 
@@ -608,3 +631,90 @@ fn test_delete_peers() {
     }
     assert_peers_in_store(&opener, &[]);
 }
+
+#[test]
+fn test_peer_score() {
+    let clock = time::FakeClock::default();
+    let peer_info_a = gen_peer_info(0);
+    let peer_info_b = gen_peer_info(1);
+    let boot_nodes = vec![peer_info_a.clone(), peer_info_b.clone()];
+    let store = store::Store::from(near_store::db::TestDB::new());
+    let peer_store = PeerStore::new(
+        &clock.clock(),
+        make_config(&boot_nodes, Blacklist::default(), false),
+        store,
+    )
+    .unwrap();
+
+    peer_store.record_score_event(&clock.clock(), &peer_info_a.id, ScoreEvent::UsefulBlock);
+    peer_store.record_score_event(&clock.clock(), &peer_info_b.id, ScoreEvent::RequestTimeout);
+    assert_eq!(2., peer_store.peer_score(&clock.clock(), &peer_info_a.id));
+    assert_eq!(-5., peer_store.peer_score(&clock.clock(), &peer_info_b.id));
+
+    // Peer with the higher score is preferred.
+    for _ in 0..10 {
+        assert_eq!(
+            Some(peer_info_a.clone()),
+            peer_store.unconnected_peer(&clock.clock(), &mut thread_rng(), |_| false, false)
+        );
+    }
+
+    // Scores decay towards 0.
+    clock.advance(time::Duration::hours(1));
+    assert_eq!(1., peer_store.peer_score(&clock.clock(), &peer_info_a.id));
+    assert_eq!(-2.5, peer_store.peer_score(&clock.clock(), &peer_info_b.id));
+
+    // Scores are bounded.
+    for _ in 0..10 {
+        peer_store.record_score_event(&clock.clock(), &peer_info_b.id, ScoreEvent::InvalidData);
+    }
+    assert_eq!(-score::MAX_SCORE, peer_store.peer_score(&clock.clock(), &peer_info_b.id));
+
+    // Scores are not persisted.
+    let peer_state = peer_store.get_peer_state(&peer_info_a.id).unwrap();
+    let mut store = peer_store.0.lock().store.clone();
+    store.set_peer_state(&peer_info_a.id, &peer_state).unwrap();
+    let (_, loaded) = store
+        .list_peer_states()
+        .unwrap()
+        .into_iter()
+        .find(|(id, _)| id == &peer_info_a.id)
+        .unwrap();
+    assert_eq!(0., loaded.score.value(clock.now_utc()));
+}
+
+#[test]
+fn test_max_unknown_peers() {
+    let clock = time::FakeClock::default();
+    let store = store::Store::from(near_store::db::TestDB::new());
+    let peer_store =
+        PeerStore::new(&clock.clock(), make_config(&[], Blacklist::default(), false), store)
+            .unwrap();
+    let banned: Vec<_> = (0..10).map(gen_peer_info).collect();
+    peer_store.add_indirect_peers(&clock.clock(), banned.iter().cloned()).unwrap();
+    for peer in &banned {
+        peer_store.peer_ban(&clock.clock(), &peer.id, ReasonForBan::Abusive).unwrap();
+    }
+
+    clock.advance(time::Duration::seconds(1));
+    let old: Vec<_> = (10..20).map(gen_peer_info).collect();
+    peer_store.add_indirect_peers(&clock.clock(), old.iter().cloned()).unwrap();
+
+    // Flooding the store with unknown peers forgets the least recently seen
+    // unknown peers, but not the banned ones.
+    clock.advance(time::Duration::seconds(1));
+    let new: Vec<_> = (20..MAX_UNKNOWN_PEERS as u16 + 20).map(gen_peer_info).collect();
+    peer_store.add_indirect_peers(&clock.clock(), new.iter().cloned()).unwrap();
+    assert_eq!(banned.len() + MAX_UNKNOWN_PEERS, peer_store.len());
+    for peer in &banned {
+        assert!(peer_store.is_banned(&peer.id));
+    }
+    for peer in &old {
+        assert!(peer_store.get_peer_state(&peer.id).is_none());
+    }
+
+    // Bans are lifted only once they expire.
+    assert_eq!(banned.len(), peer_store.count_banned());
+    peer_store.unban(&clock.clock());
+    assert_eq!(0, peer_store.count_banned());
+}
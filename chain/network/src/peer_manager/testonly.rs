@@ -139,8 +139,9 @@ impl ActorHandler {
         let addr = self.actix.addr.clone();
         let events = self.events.clone();
         let peer_info = peer_info.clone();
+        let transport = self.cfg.transport.clone();
         async move {
-            let stream = tcp::Stream::connect(&peer_info).await.unwrap();
+            let stream = transport.connect(&peer_info).await.unwrap();
             let mut events = events.from_now();
             let stream_id = stream.id();
             addr.do_send(PeerManagerMessageRequest::OutboundTcpConnect(stream).with_span_context());
@@ -306,6 +307,11 @@ impl ActorHandler {
         self.with_state(move |s| async move { s.tier1_advertise_proxies(&clock).await }).await
     }
 
+    pub async fn tier1_check_proxies(&self, clock: &time::Clock) -> Vec<Arc<SignedAccountData>> {
+        let clock = clock.clone();
+        self.with_state(move |s| async move { s.tier1_check_proxies(&clock).await }).await
+    }
+
     pub async fn announce_account(&self, aa: AnnounceAccount) {
         self.actix
             .addr
@@ -386,17 +392,42 @@ pub(crate) async fn start(
 ) -> ActorHandler {
     let (send, recv) = broadcast::unbounded_channel();
     let actix = ActixSystem::spawn({
-        let mut cfg = cfg.clone();
+        let cfg = cfg.clone();
         let chain = chain.clone();
-        move || {
-            let genesis_id = chain.genesis_id.clone();
-            let fc = Arc::new(fake_client::Fake { event_sink: send.sink().compose(Event::Client) });
-            cfg.event_sink = send.sink().compose(Event::PeerManager);
-            PeerManagerActor::spawn(clock, store, cfg, fc, genesis_id).unwrap()
-        }
+        move || spawn_actor(clock, store, cfg, &chain, send)
     })
     .await;
-    let mut h = ActorHandler { cfg, actix, events: recv };
+    finish_start(ActorHandler { cfg, actix, events: recv }, &chain).await
+}
+
+/// Like `start`, but the PeerManagerActor and all its PeerActors run on the arbiter
+/// of the current thread, rather than on dedicated threads.
+pub(crate) async fn start_local(
+    clock: time::Clock,
+    store: Arc<dyn near_store::db::Database>,
+    mut cfg: config::NetworkConfig,
+    chain: Arc<data::Chain>,
+) -> ActorHandler {
+    cfg.arbiter = Some(actix::Arbiter::current());
+    let (send, recv) = broadcast::unbounded_channel();
+    let actix = ActixSystem::local(|| spawn_actor(clock, store, cfg.clone(), &chain, send));
+    finish_start(ActorHandler { cfg, actix, events: recv }, &chain).await
+}
+
+fn spawn_actor(
+    clock: time::Clock,
+    store: Arc<dyn near_store::db::Database>,
+    mut cfg: config::NetworkConfig,
+    chain: &data::Chain,
+    send: broadcast::Sender<Event>,
+) -> actix::Addr<PeerManagerActor> {
+    let genesis_id = chain.genesis_id.clone();
+    let fc = Arc::new(fake_client::Fake { event_sink: send.sink().compose(Event::Client) });
+    cfg.event_sink = send.sink().compose(Event::PeerManager);
+    PeerManagerActor::spawn(clock, store, cfg, fc, genesis_id).unwrap()
+}
+
+async fn finish_start(mut h: ActorHandler, chain: &data::Chain) -> ActorHandler {
     // Wait for the server to start.
     assert_eq!(Event::PeerManager(PME::ServerStarted), h.events.recv().await);
     h.actix.addr.send(SetChainInfo(chain.get_chain_info()).with_span_context()).await.unwrap();
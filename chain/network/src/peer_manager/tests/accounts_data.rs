@@ -1,6 +1,7 @@
 use crate::concurrency::rate;
+use crate::config;
 use crate::network_protocol::testonly as data;
-use crate::network_protocol::SyncAccountsData;
+use crate::network_protocol::{PeerAddr, SyncAccountsData};
 use crate::peer;
 use crate::peer_manager;
 use crate::peer_manager::peer_manager_actor::Event as PME;
@@ -254,3 +255,82 @@ async fn rate_limiting() {
     println!("got {msgs}, want <= {want_max}");
     assert!(msgs <= want_max, "got {msgs} messages, want at most {want_max}");
 }
+
+#[tokio::test]
+async fn check_proxies() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+    let clock = clock.clock();
+
+    tracing::info!(target:"test", "Configure the node to be its own proxy (by domain name) + an unreachable proxy.");
+    let unreachable_addr = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    };
+    let mut cfg = chain.make_config(rng);
+    let proxies = match &mut cfg.validator.as_mut().unwrap().proxies {
+        config::ValidatorProxies::Static(proxies) => proxies,
+        config::ValidatorProxies::Dynamic(_) => panic!("expected static proxies"),
+    };
+    let own_proxy = proxies[0].clone();
+    let own_port = own_proxy.addr.parse::<std::net::SocketAddr>().unwrap().port();
+    proxies[0].addr = format!("localhost:{own_port}");
+    proxies.push(PeerAddr { addr: unreachable_addr, peer_id: data::make_peer_id(rng) }.into());
+    let pm = peer_manager::testonly::start(
+        clock.clone(),
+        near_store::db::TestDB::new(),
+        cfg,
+        chain.clone(),
+    )
+    .await;
+    pm.set_chain_info(testonly::make_chain_info(&chain, &[&pm])).await;
+
+    tracing::info!(target:"test", "Only the reachable proxy should be advertised, with a resolved address.");
+    let got = pm.tier1_advertise_proxies(&clock).await;
+    assert_eq!(1, got.len());
+    assert_eq!(1, got[0].proxies.len());
+    assert_eq!(own_proxy.peer_id, got[0].proxies[0].peer_id);
+    assert!(got[0].proxies[0].addr.ip().is_loopback());
+    assert_eq!(own_port, got[0].proxies[0].addr.port());
+
+    tracing::info!(target:"test", "Set of healthy proxies hasn't changed, expect no broadcast.");
+    assert!(pm.tier1_check_proxies(&clock).await.is_empty());
+}
+
+#[tokio::test]
+async fn unreachable_own_proxy() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+    let clock = clock.clock();
+
+    tracing::info!(target:"test", "Configure the node to be its own proxy, on a port it doesn't listen on.");
+    let unreachable_addr = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    };
+    let mut cfg = chain.make_config(rng);
+    let proxies = match &mut cfg.validator.as_mut().unwrap().proxies {
+        config::ValidatorProxies::Static(proxies) => proxies,
+        config::ValidatorProxies::Dynamic(_) => panic!("expected static proxies"),
+    };
+    proxies[0].addr = unreachable_addr.to_string();
+    let pm = peer_manager::testonly::start(
+        clock.clone(),
+        near_store::db::TestDB::new(),
+        cfg,
+        chain.clone(),
+    )
+    .await;
+    pm.set_chain_info(testonly::make_chain_info(&chain, &[&pm])).await;
+
+    tracing::info!(target:"test", "The own proxy is not reachable, expect no proxies to be advertised.");
+    let got = pm.tier1_advertise_proxies(&clock).await;
+    assert_eq!(1, got.len());
+    assert!(got[0].proxies.is_empty());
+}
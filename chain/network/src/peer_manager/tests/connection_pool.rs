@@ -99,6 +99,7 @@ async fn loop_connection() {
                 1,
                 &pm.cfg.node_key,
             ),
+            encryption_offer: None,
         }))
         .await;
     let reason = events
@@ -0,0 +1,75 @@
+use crate::debug::{DebugStatus, GetDebugStatus};
+use crate::network_protocol::testonly as data;
+use crate::peer_manager;
+use crate::peer_manager::testonly::ActorHandler;
+use crate::testonly::make_rng;
+use crate::time;
+use near_o11y::testonly::init_test_logger;
+use near_primitives::network::{AnnounceAccount, PeerId};
+use near_primitives::views::PeerStatusView;
+use std::sync::Arc;
+
+async fn peer_status(pm: &ActorHandler, peer_id: &PeerId) -> Option<PeerStatusView> {
+    match pm.actix.addr.send(GetDebugStatus::Peer(peer_id.clone())).await.unwrap() {
+        DebugStatus::Peer(status) => status,
+        status => panic!("unexpected response {status:?}"),
+    }
+}
+
+#[tokio::test]
+async fn per_peer_metrics_limit() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let mut cfg = chain.make_config(rng);
+    cfg.per_peer_metrics_limit = 1;
+    let pm = peer_manager::testonly::start(
+        clock.clock(),
+        near_store::db::TestDB::new(),
+        cfg,
+        chain.clone(),
+    )
+    .await;
+
+    tracing::info!(target:"test", "a peer which is not connected has no status");
+    assert_eq!(None, peer_status(&pm, &data::make_peer_id(rng)).await);
+
+    tracing::info!(target:"test", "connect 2 peers, one of which has announced an account");
+    let peer1 = pm
+        .start_inbound(chain.clone(), chain.make_config(rng))
+        .await
+        .handshake(&clock.clock())
+        .await;
+    let peer2 = pm
+        .start_inbound(chain.clone(), chain.make_config(rng))
+        .await
+        .handshake(&clock.clock())
+        .await;
+    let aa = AnnounceAccount { peer_id: peer1.cfg.id(), ..data::make_announce_account(rng) };
+    pm.with_state({
+        let aa = aa.clone();
+        move |s| async move {
+            s.graph.routing_table.add_accounts(vec![aa]);
+        }
+    })
+    .await;
+
+    tracing::info!(target:"test", "only one of the peers gets per-peer metrics");
+    loop {
+        let status1 = peer_status(&pm, &peer1.cfg.id()).await.unwrap();
+        let status2 = peer_status(&pm, &peer2.cfg.id()).await.unwrap();
+        assert_eq!(peer1.cfg.id(), status1.peer_id);
+        assert_eq!(Some(aa.account_id.clone()), status1.account_id);
+        assert_eq!(None, status2.account_id);
+        assert!(!status1.is_outbound_peer);
+        assert!(!(status1.per_peer_metrics && status2.per_peer_metrics));
+        if status1.per_peer_metrics || status2.per_peer_metrics {
+            break;
+        }
+        clock.advance(pm.cfg.peer_stats_period);
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+}
@@ -0,0 +1,89 @@
+use crate::network_protocol::testonly as data;
+use crate::network_protocol::{Encoding, Handshake, PartialEdgeInfo, PeerMessage};
+use crate::peer::peer_actor::ClosingReason;
+use crate::peer_manager;
+use crate::peer_manager::peer_manager_actor::Event as PME;
+use crate::peer_manager::testonly::Event;
+use crate::tcp;
+use crate::testonly::make_rng;
+use crate::testonly::stream::Stream;
+use crate::time;
+use near_o11y::testonly::init_test_logger;
+use near_primitives::network::PeerId;
+use near_primitives::version::PROTOCOL_VERSION;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn tier1_require_encryption() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+    let clock = clock.clock();
+
+    let mut cfg = chain.make_config(rng);
+    cfg.tier1.as_mut().unwrap().require_encryption = true;
+    let pm = peer_manager::testonly::start(
+        clock.clone(),
+        near_store::db::TestDB::new(),
+        cfg,
+        chain.clone(),
+    )
+    .await;
+
+    tracing::info!(target:"test", "make 2 peers belong to TIER1");
+    let peer1_cfg = chain.make_config(rng);
+    let peer2_key = data::make_secret_key(rng);
+    let peer2_id = PeerId::new(peer2_key.public_key());
+    let tier1_data: HashSet<_> = [peer1_cfg.node_id(), peer2_id.clone()]
+        .into_iter()
+        .zip(&chain.tier1_accounts)
+        .map(|(peer_id, signer)| {
+            let data =
+                data::make_account_data(rng, 1, clock.now_utc(), signer.public_key(), peer_id);
+            Arc::new(data.sign(signer).unwrap())
+        })
+        .collect();
+    pm.with_state({
+        let tier1_data = tier1_data.clone();
+        move |s| async move { s.accounts_data.insert(tier1_data.into_iter().collect()).await }
+    })
+    .await;
+    pm.wait_for_accounts_data(&tier1_data).await;
+
+    tracing::info!(target:"test", "encrypted connection is accepted");
+    pm.start_inbound(chain.clone(), peer1_cfg).await.handshake(&clock).await;
+
+    tracing::info!(target:"test", "unencrypted connection is rejected");
+    let stream = tcp::Stream::connect(&pm.peer_info()).await.unwrap();
+    let stream_id = stream.id();
+    let port = stream.local_addr.port();
+    let mut events = pm.events.from_now();
+    let mut stream = Stream::new(Some(Encoding::Proto), stream);
+    stream
+        .write(&PeerMessage::Handshake(Handshake {
+            protocol_version: PROTOCOL_VERSION,
+            oldest_supported_version: PROTOCOL_VERSION,
+            sender_peer_id: peer2_id.clone(),
+            target_peer_id: pm.cfg.node_id(),
+            sender_listen_port: Some(port),
+            sender_chain_info: chain.get_peer_chain_info(),
+            partial_edge_info: PartialEdgeInfo::new(&peer2_id, &pm.cfg.node_id(), 1, &peer2_key),
+            encryption_offer: None,
+        }))
+        .await;
+    let reason = events
+        .recv_until(|ev| match ev {
+            Event::PeerManager(PME::ConnectionClosed(ev)) if ev.stream_id == stream_id => {
+                Some(ev.reason)
+            }
+            Event::PeerManager(PME::HandshakeCompleted(ev)) if ev.stream_id == stream_id => {
+                panic!("PeerManager accepted the handshake")
+            }
+            _ => None,
+        })
+        .await;
+    assert_eq!(ClosingReason::EncryptionRequired, reason);
+}
@@ -1,4 +1,6 @@
 mod accounts_data;
 mod connection_pool;
+mod debug;
+mod encryption;
 mod nonce;
 mod routing;
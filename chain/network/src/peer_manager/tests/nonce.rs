@@ -72,6 +72,7 @@ async fn test_nonces() {
             sender_listen_port: Some(24567),
             sender_chain_info: chain.get_peer_chain_info(),
             partial_edge_info: PartialEdgeInfo::new(&peer_id, &pm.cfg.node_id(), test.0, &peer_key),
+            encryption_offer: None,
         });
         stream.write(&handshake).await;
         if test.1 {
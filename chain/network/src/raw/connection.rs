@@ -149,6 +149,7 @@ impl Connection {
                 1,
                 &self.secret_key,
             ),
+            encryption_offer: None,
         });
 
         self.write_message(&handshake).await.map_err(ConnectError::IO)?;
@@ -212,7 +213,7 @@ impl Connection {
     }
 
     // Reads from the socket until there is at least one full PeerMessage available.
-    async fn recv_message(&mut self) -> io::Result<(PeerMessage, Instant)> {
+    pub(crate) async fn recv_message(&mut self) -> io::Result<(PeerMessage, Instant)> {
         let (msg_length, first_byte_time) = self.read_msg_length().await?;
 
         while self.buf.remaining() < msg_length + 4 {
@@ -251,6 +252,11 @@ impl Connection {
         }
     }
 
+    /// Sends an arbitrary message to the peer.
+    pub async fn send_message(&mut self, msg: &PeerMessage) -> io::Result<()> {
+        self.write_message(msg).await
+    }
+
     /// Try to send a Ping message to the given target, with the given nonce and ttl
     pub async fn send_ping(&mut self, target: &PeerId, nonce: u64, ttl: u8) -> anyhow::Result<()> {
         let body = RoutedMessageBody::Ping(Ping { nonce, source: self.my_peer_id.clone() });
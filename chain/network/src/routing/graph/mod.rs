@@ -7,9 +7,10 @@ use crate::stats::metrics;
 use crate::store;
 use crate::time;
 use arc_swap::ArcSwap;
+use near_crypto::SecretKey;
 use near_primitives::network::PeerId;
 use parking_lot::Mutex;
-use rayon::iter::ParallelBridge;
+use rayon::iter::{IntoParallelIterator as _, ParallelBridge, ParallelIterator as _};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
@@ -289,6 +290,68 @@ impl Graph {
         .await
     }
 
+    /// Persists the edges of the graph, so that they can be restored by `load_snapshot`
+    /// after restart.
+    pub fn save_snapshot(&self) {
+        let edges: Vec<Edge> = self.load().edges.values().cloned().collect();
+        let mut store = self.inner.lock().store.clone();
+        if let Err(err) = store.set_routing_table_snapshot(&edges) {
+            tracing::warn!(target: "network", ?err, "Failed to save routing table snapshot");
+        }
+    }
+
+    /// Restores the edges persisted by `save_snapshot` and recomputes the routing table,
+    /// so that we have routes as soon as we connect to any peer, instead of waiting for
+    /// the routing table sync with the whole network.
+    /// The persisted edges are not trusted: the signatures are verified again, edges with
+    /// invalid nonces are dropped, and so are the expired ones (see `prune_edges_after`).
+    /// Our own active edges are replaced with tombstones signed with `node_key`, since the
+    /// connections they represent didn't survive the restart.
+    pub fn load_snapshot(&self, clock: &time::Clock, node_key: &SecretKey) {
+        let mut inner = self.inner.lock();
+        let edges = match inner.store.get_routing_table_snapshot() {
+            Ok(Some(edges)) => edges,
+            Ok(None) => return,
+            Err(err) => {
+                tracing::warn!(target: "network", ?err, "Failed to load routing table snapshot");
+                return;
+            }
+        };
+        let total = edges.len();
+        let node_id = inner.config.node_id.clone();
+        let expired_before = inner.config.prune_edges_after.map(|d| clock.now_utc() - d);
+        let edges: Vec<Edge> = Edge::deduplicate(edges)
+            .into_par_iter()
+            .filter(|e| Edge::nonce_to_utc(e.nonce()).is_ok())
+            .filter(|e| expired_before.map_or(true, |t| !e.is_edge_older_than(t)))
+            .filter(|e| e.verify())
+            .map(|e| {
+                if e.contains_peer(&node_id) && e.edge_type() == EdgeState::Active {
+                    e.remove_edge(node_id.clone(), node_key)
+                } else {
+                    e
+                }
+            })
+            .collect();
+        // Peers from the snapshot are considered reachable as of now, so that they are not
+        // pruned before we manage to reconnect to the network.
+        let now = clock.now();
+        for e in &edges {
+            inner.peer_reachable_at.insert(e.key().0.clone(), now);
+            inner.peer_reachable_at.insert(e.key().1.clone(), now);
+        }
+        let (edges, snapshot) = inner.update(clock, edges, &self.unreliable_peers.load());
+        tracing::info!(
+            target: "network",
+            "Loaded {}/{} edges from the routing table snapshot",
+            edges.len(),
+            total
+        );
+        let snapshot = Arc::new(snapshot);
+        self.routing_table.update(snapshot.next_hops.clone());
+        self.snapshot.store(snapshot);
+    }
+
     /// Adds edges to the graph and recomputes the routing table.
     /// Returns the edges which were actually new and should be broadcasted.
     pub async fn update(self: &Arc<Self>, clock: &time::Clock, edges: Vec<Edge>) -> Vec<Edge> {
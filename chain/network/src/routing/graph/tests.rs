@@ -260,3 +260,53 @@ async fn expired_edges() {
     g.update(&clock.clock(), vec![]).await;
     g.check(&[], &[]).await;
 }
+
+#[tokio::test]
+async fn snapshot() {
+    let clock = time::FakeClock::default();
+    clock.set_utc(*EDGE_MIN_TIMESTAMP_NONCE + time::Duration::days(2));
+    let mut rng = make_rng(87927345);
+    let rng = &mut rng;
+    let node_key = data::make_secret_key(rng);
+    let cfg = GraphConfig {
+        node_id: PeerId::new(node_key.public_key()),
+        prune_unreachable_peers_after: time::Duration::hours(100),
+        prune_edges_after: Some(110 * SEC),
+    };
+    let mut store = store();
+    let g = Arc::new(Graph::new(cfg.clone(), store.clone()));
+
+    let keys: Vec<_> = (0..4).map(|_| data::make_secret_key(rng)).collect();
+    let now = clock.now_utc();
+    let local = data::make_edge(&node_key, &keys[0], to_active_nonce(now));
+    let e01 = data::make_edge(&keys[0], &keys[1], to_active_nonce(now));
+    let e12 = data::make_edge(&keys[1], &keys[2], to_active_nonce(now));
+    let old_e23 = data::make_edge(&keys[2], &keys[3], to_active_nonce(now - 100 * SEC));
+    let edges = vec![local.clone(), e01.clone(), e12.clone(), old_e23.clone()];
+    g.update(&clock.clock(), edges.clone()).await;
+    g.check(&edges, &[]).await;
+    g.save_snapshot();
+
+    // Restart after old_e23 has expired.
+    // Our own edge is replaced with a tombstone, since the connection is gone.
+    clock.advance(20 * SEC);
+    let g = Arc::new(Graph::new(cfg.clone(), store.clone()));
+    g.load_snapshot(&clock.clock(), &node_key);
+    let tombstone = local.remove_edge(cfg.node_id.clone(), &node_key);
+    g.check(&[tombstone.clone(), e01.clone(), e12.clone()], &[]).await;
+    assert!(g.load().next_hops.is_empty());
+
+    // Reconnecting to a single peer makes the whole restored graph reachable.
+    let reconnected = data::make_edge(&node_key, &keys[0], tombstone.next());
+    g.update(&clock.clock(), vec![reconnected]).await;
+    let want: HashSet<_> = keys[0..3].iter().map(|k| PeerId::new(k.public_key())).collect();
+    assert_eq!(want, g.load().next_hops.keys().cloned().collect());
+
+    // Edges with invalid signatures are not loaded.
+    let p2 = PeerId::new(keys[2].public_key());
+    let p3 = PeerId::new(keys[3].public_key());
+    store.set_routing_table_snapshot(&vec![edge(&p2, &p3, to_active_nonce(now))]).unwrap();
+    let g = Graph::new(cfg.clone(), store.clone());
+    g.load_snapshot(&clock.clock(), &node_key);
+    g.check(&[], &[]).await;
+}
@@ -134,6 +134,12 @@ impl RoutingTableView {
         self.0.lock().get_announce(account_id).map(|announce_account| announce_account.peer_id)
     }
 
+    /// Find an AccountId announced by the given peer.
+    pub(crate) fn announced_account(&self, peer_id: &PeerId) -> Option<AccountId> {
+        let inner = self.0.lock();
+        inner.account_peers.iter().find(|(_, aa)| &aa.peer_id == peer_id).map(|(id, _)| id.clone())
+    }
+
     /// Adds accounts to the routing table.
     /// Returns the diff: new values that should be broadcasted.
     /// Note: There is at most one peer id per account id.
@@ -68,6 +68,8 @@ fn announcement_same_epoch() {
     );
     assert_eq!(routing_table.get_announce_accounts(), vec![announce0.clone()]);
     assert_eq!(routing_table.account_owner(&announce0.account_id).unwrap(), peer_id0);
+    assert_eq!(routing_table.announced_account(&peer_id0), Some(announce0.account_id.clone()));
+    assert_eq!(routing_table.announced_account(&peer_id1), None);
 
     // Adding a conflicting announcement later. Should be a noop.
     assert_eq!(routing_table.add_accounts(vec![announce1.clone()]), vec![]);
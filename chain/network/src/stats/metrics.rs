@@ -9,7 +9,10 @@ use near_o11y::metrics::{
     try_create_int_gauge, try_create_int_gauge_vec, Histogram, HistogramVec, IntCounter,
     IntCounterVec, IntGauge, IntGaugeVec, MetricVec, MetricVecBuilder,
 };
+use near_primitives::network::PeerId;
+use near_primitives::types::AccountId;
 use once_cell::sync::Lazy;
+use std::collections::HashMap;
 
 /// Labels represents a schema of an IntGaugeVec metric.
 pub trait Labels: 'static {
@@ -199,6 +202,14 @@ pub(crate) static PEER_MESSAGE_SENT_BY_TYPE_TOTAL: Lazy<IntCounterVec> = Lazy::n
     )
     .unwrap()
 });
+pub(crate) static PEER_MESSAGE_DELAYED_BY_TYPE_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_peer_message_delayed_by_type_total",
+        "Number of messages delayed because of send_bandwidth_limits, by message types",
+        &["type"],
+    )
+    .unwrap()
+});
 pub(crate) static REQUEST_COUNT_BY_TYPE_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
     try_create_int_counter_vec(
         "near_requests_count_by_type_total",
@@ -209,6 +220,13 @@ pub(crate) static REQUEST_COUNT_BY_TYPE_TOTAL: Lazy<IntCounterVec> = Lazy::new(|
 });
 
 // Routing table metrics
+pub(crate) static CAPTURE_DROPPED_RECORDS: Lazy<IntCounter> = Lazy::new(|| {
+    try_create_int_counter(
+        "near_capture_dropped_records_total",
+        "Number of captured messages dropped because the capture thread was lagging behind",
+    )
+    .unwrap()
+});
 pub(crate) static ROUTING_TABLE_RECALCULATIONS: Lazy<IntCounter> = Lazy::new(|| {
     try_create_int_counter(
         "near_routing_table_recalculations_total",
@@ -395,3 +413,120 @@ impl MessageDropped {
         DROPPED_MESSAGE_COUNT.with_label_values(&[msg_type, reason]).inc();
     }
 }
+
+// Per-peer metrics. They are exported only for a bounded number of
+// connections (see `NetworkConfig::per_peer_metrics_limit`), so that the
+// cardinality of the series stays under control.
+static PEER_RTT: Lazy<HistogramVec> = Lazy::new(|| {
+    try_create_histogram_vec(
+        "near_peer_rtt",
+        "Time between sending a block or chunk request to the peer and receiving the response",
+        &["peer_id", "account_id"],
+        Some(exponential_buckets(0.001, 2., 16).unwrap()),
+    )
+    .unwrap()
+});
+static PEER_RECEIVED_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_peer_received_bytes",
+        "Data received from the peer",
+        &["peer_id", "account_id"],
+    )
+    .unwrap()
+});
+static PEER_SENT_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_peer_sent_bytes",
+        "Data sent to the peer",
+        &["peer_id", "account_id"],
+    )
+    .unwrap()
+});
+static PEER_MESSAGES_BY_TYPE: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_peer_messages_by_type",
+        "Number of messages exchanged with the peer, by direction and message type",
+        &["peer_id", "account_id", "direction", "type"],
+    )
+    .unwrap()
+});
+static PEER_REQUEST_TIMEOUTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_peer_request_timeouts",
+        "Number of block and chunk requests which the peer didn't respond to in time",
+        &["peer_id", "account_id"],
+    )
+    .unwrap()
+});
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, strum::AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub(crate) enum Direction {
+    Received,
+    Sent,
+}
+
+/// Metrics of a single connection, labelled with the `peer_id` and the
+/// `account_id` announced by the peer (empty if the peer didn't announce any).
+/// The series are removed when PeerMetrics is dropped.
+pub(crate) struct PeerMetrics {
+    pub account_id: Option<AccountId>,
+    labels: [String; 2],
+    pub rtt: Histogram,
+    pub received_bytes: IntCounter,
+    pub sent_bytes: IntCounter,
+    pub request_timeouts: IntCounter,
+    messages: parking_lot::Mutex<HashMap<(Direction, &'static str), IntCounter>>,
+}
+
+impl PeerMetrics {
+    pub fn new(peer_id: &PeerId, account_id: Option<AccountId>) -> Self {
+        let labels =
+            [peer_id.to_string(), account_id.as_ref().map(|a| a.to_string()).unwrap_or_default()];
+        let l = [labels[0].as_str(), labels[1].as_str()];
+        Self {
+            account_id,
+            rtt: PEER_RTT.with_label_values(&l),
+            received_bytes: PEER_RECEIVED_BYTES.with_label_values(&l),
+            sent_bytes: PEER_SENT_BYTES.with_label_values(&l),
+            request_timeouts: PEER_REQUEST_TIMEOUTS.with_label_values(&l),
+            messages: Default::default(),
+            labels,
+        }
+    }
+
+    pub fn inc_messages(&self, direction: Direction, msg_type: &'static str) {
+        self.messages
+            .lock()
+            .entry((direction, msg_type))
+            .or_insert_with(|| {
+                PEER_MESSAGES_BY_TYPE.with_label_values(&[
+                    &self.labels[0],
+                    &self.labels[1],
+                    direction.as_ref(),
+                    msg_type,
+                ])
+            })
+            .inc();
+    }
+}
+
+impl Drop for PeerMetrics {
+    fn drop(&mut self) {
+        // This can return an error in tests, when multiple PeerManagerActors
+        // connect to the same peer.
+        let l = [self.labels[0].as_str(), self.labels[1].as_str()];
+        let _ = PEER_RTT.remove_label_values(&l);
+        let _ = PEER_RECEIVED_BYTES.remove_label_values(&l);
+        let _ = PEER_SENT_BYTES.remove_label_values(&l);
+        let _ = PEER_REQUEST_TIMEOUTS.remove_label_values(&l);
+        for (direction, msg_type) in self.messages.get_mut().keys() {
+            let _ = PEER_MESSAGES_BY_TYPE.remove_label_values(&[
+                l[0],
+                l[1],
+                direction.as_ref(),
+                msg_type,
+            ]);
+        }
+    }
+}
@@ -118,6 +118,22 @@ impl Store {
     }
 }
 
+// Routing table snapshot storage.
+impl Store {
+    /// Overwrites the snapshot of the routing graph edges.
+    pub fn set_routing_table_snapshot(&mut self, edges: &Vec<Edge>) -> Result<(), Error> {
+        let mut update = self.0.new_update();
+        update.set::<schema::RoutingTableSnapshot>(&(), edges);
+        self.0.commit(update).map_err(Error)
+    }
+
+    /// Reads the snapshot of the routing graph edges.
+    /// Returns Ok(None) if no snapshot has been stored yet.
+    pub fn get_routing_table_snapshot(&self) -> Result<Option<Vec<Edge>>, Error> {
+        self.0.get::<schema::RoutingTableSnapshot>(&()).map_err(Error)
+    }
+}
+
 // PeerStore storage.
 impl Store {
     /// Inserts (peer_id,peer_state) to Peers column.
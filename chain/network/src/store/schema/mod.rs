@@ -97,6 +97,7 @@ impl BorshRepr for KnownPeerStateRepr {
             last_seen: time::Utc::from_unix_timestamp_nanos(s.last_seen as i128)
                 .map_err(invalid_data)?,
             last_outbound_attempt: None,
+            score: Default::default(),
         })
     }
 }
@@ -166,6 +167,13 @@ impl Column for LastComponentNonce {
     type Value = Borsh<u64>;
 }
 
+pub struct RoutingTableSnapshot;
+impl Column for RoutingTableSnapshot {
+    const COL: DBCol = DBCol::RoutingTableSnapshot;
+    type Key = Borsh<()>;
+    type Value = Vec<EdgeRepr>;
+}
+
 ////////////////////////////////////////////////////
 // Storage
 
@@ -0,0 +1,147 @@
+//! Minimal [STUN](https://datatracker.ietf.org/doc/html/rfc8489) client.
+//!
+//! A TIER1 validator configured with `trusted_stun_servers` (see
+//! [`crate::config::ValidatorProxies::Dynamic`]) acts as its own proxy and needs
+//! to learn its public IP to advertise it.  We only implement the Binding
+//! request over UDP, which is all that is needed for that.
+//!
+//! Server addresses are resolved on every query, so that servers changing
+//! their IPs are handled transparently.
+use crate::config::StunServerAddr;
+use crate::time;
+use anyhow::{anyhow, Context as _};
+use rand::Rng as _;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+#[cfg(test)]
+mod tests;
+
+const MAGIC_COOKIE: u32 = 0x2112A442;
+const BINDING_REQUEST: u16 = 0x0001;
+const BINDING_SUCCESS_RESPONSE: u16 = 0x0101;
+const ATTR_MAPPED_ADDRESS: u16 = 0x0001;
+const ATTR_XOR_MAPPED_ADDRESS: u16 = 0x0020;
+const HEADER_LEN: usize = 20;
+const FAMILY_IPV4: u8 = 0x01;
+const FAMILY_IPV6: u8 = 0x02;
+/// Responses are small, anything larger than that is not a valid response anyway.
+const MAX_RESPONSE_LEN: usize = 1024;
+
+type TransactionId = [u8; 12];
+
+fn encode_binding_request(tid: &TransactionId) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_LEN);
+    out.extend(BINDING_REQUEST.to_be_bytes());
+    out.extend(0u16.to_be_bytes());
+    out.extend(MAGIC_COOKIE.to_be_bytes());
+    out.extend(tid);
+    out
+}
+
+fn decode_address(value: &[u8], xor: bool, tid: &TransactionId) -> anyhow::Result<SocketAddr> {
+    if value.len() < 4 {
+        anyhow::bail!("address attribute too short");
+    }
+    let mut port = u16::from_be_bytes([value[2], value[3]]);
+    let mut ip = value[4..].to_vec();
+    if xor {
+        port ^= (MAGIC_COOKIE >> 16) as u16;
+        let mut mask = MAGIC_COOKIE.to_be_bytes().to_vec();
+        mask.extend(tid);
+        for (b, m) in ip.iter_mut().zip(mask) {
+            *b ^= m;
+        }
+    }
+    let ip = match (value[1], ip.len()) {
+        (FAMILY_IPV4, 4) => IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(&ip[..]).unwrap())),
+        (FAMILY_IPV6, 16) => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(&ip[..]).unwrap())),
+        (family, len) => anyhow::bail!("unsupported address family {family} of length {len}"),
+    };
+    Ok(SocketAddr::new(ip, port))
+}
+
+/// Extracts the mapped address from a Binding success response.
+/// XOR-MAPPED-ADDRESS takes precedence over MAPPED-ADDRESS, which is sent
+/// only by old servers.
+fn decode_binding_response(msg: &[u8], tid: &TransactionId) -> anyhow::Result<SocketAddr> {
+    if msg.len() < HEADER_LEN {
+        anyhow::bail!("response too short");
+    }
+    let type_ = u16::from_be_bytes([msg[0], msg[1]]);
+    let len = u16::from_be_bytes([msg[2], msg[3]]) as usize;
+    if type_ != BINDING_SUCCESS_RESPONSE {
+        anyhow::bail!("unexpected message type {type_:#06x}");
+    }
+    if msg[4..8] != MAGIC_COOKIE.to_be_bytes() || &msg[8..HEADER_LEN] != tid {
+        anyhow::bail!("response doesn't match the request");
+    }
+    let mut attrs = msg.get(HEADER_LEN..HEADER_LEN + len).context("truncated response")?;
+    let mut mapped = None;
+    while attrs.len() >= 4 {
+        let attr_type = u16::from_be_bytes([attrs[0], attrs[1]]);
+        let attr_len = u16::from_be_bytes([attrs[2], attrs[3]]) as usize;
+        let value = attrs.get(4..4 + attr_len).context("truncated attribute")?;
+        match attr_type {
+            ATTR_XOR_MAPPED_ADDRESS => return decode_address(value, true, tid),
+            ATTR_MAPPED_ADDRESS => mapped = Some(decode_address(value, false, tid)?),
+            _ => {}
+        }
+        // Attributes are padded to a multiple of 4 bytes.
+        let padded_len = (4 + attr_len + 3) & !3;
+        attrs = attrs.get(padded_len..).unwrap_or(&[]);
+    }
+    mapped.ok_or(anyhow!("no mapped address in the response"))
+}
+
+/// Sends a Binding request to the given STUN server and returns the public
+/// address of this node as observed by the server.
+pub(crate) async fn query(
+    server: &StunServerAddr,
+    timeout: time::Duration,
+) -> anyhow::Result<SocketAddr> {
+    let server_addr = tokio::net::lookup_host(server)
+        .await
+        .context("lookup_host()")?
+        .next()
+        .context("no addresses found")?;
+    let local_addr: SocketAddr = match server_addr {
+        SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    let socket = tokio::net::UdpSocket::bind(local_addr).await.context("bind()")?;
+    socket.connect(server_addr).await.context("connect()")?;
+    let tid: TransactionId = rand::thread_rng().gen();
+    socket.send(&encode_binding_request(&tid)).await.context("send()")?;
+    let mut buf = [0; MAX_RESPONSE_LEN];
+    let n = tokio::time::timeout(timeout.try_into()?, socket.recv(&mut buf))
+        .await
+        .context("timeout")?
+        .context("recv()")?;
+    decode_binding_response(&buf[..n], &tid)
+}
+
+/// Queries all the servers in parallel and returns the public IP of this node
+/// iff the answer is unambiguous: at least one server responded and all the
+/// responses contain the same IP.
+pub(crate) async fn resolve_public_ip(
+    servers: &[StunServerAddr],
+    timeout: time::Duration,
+) -> Option<IpAddr> {
+    let results =
+        futures::future::join_all(servers.iter().map(|server| query(server, timeout))).await;
+    let mut ip = None;
+    for (server, res) in servers.iter().zip(results) {
+        match res {
+            Ok(addr) => match ip {
+                None => ip = Some(addr.ip()),
+                Some(ip) if ip == addr.ip() => {}
+                Some(ip) => {
+                    tracing::warn!(target: "network", %server, %ip, other = %addr.ip(), "STUN servers returned conflicting IPs");
+                    return None;
+                }
+            },
+            Err(err) => tracing::info!(target: "network", %server, ?err, "STUN query failed"),
+        }
+    }
+    ip
+}
@@ -0,0 +1,78 @@
+use crate::stun::*;
+use crate::time;
+use std::net::SocketAddr;
+
+const TID: TransactionId = [7; 12];
+
+/// Encodes a Binding success response with a single attribute.
+fn make_response(tid: &TransactionId, attr_type: u16, value: &[u8]) -> Vec<u8> {
+    let padded_len = (value.len() + 3) & !3;
+    let mut out = vec![];
+    out.extend(BINDING_SUCCESS_RESPONSE.to_be_bytes());
+    out.extend(((4 + padded_len) as u16).to_be_bytes());
+    out.extend(MAGIC_COOKIE.to_be_bytes());
+    out.extend(tid);
+    out.extend(attr_type.to_be_bytes());
+    out.extend((value.len() as u16).to_be_bytes());
+    out.extend(value);
+    out.resize(HEADER_LEN + 4 + padded_len, 0);
+    out
+}
+
+fn xor_mapped_address(tid: &TransactionId, addr: SocketAddr) -> Vec<u8> {
+    let mut mask = MAGIC_COOKIE.to_be_bytes().to_vec();
+    mask.extend(tid);
+    let (family, ip) = match addr.ip() {
+        IpAddr::V4(ip) => (FAMILY_IPV4, ip.octets().to_vec()),
+        IpAddr::V6(ip) => (FAMILY_IPV6, ip.octets().to_vec()),
+    };
+    let mut out = vec![0, family];
+    out.extend((addr.port() ^ (MAGIC_COOKIE >> 16) as u16).to_be_bytes());
+    out.extend(ip.iter().zip(mask).map(|(b, m)| b ^ m));
+    out
+}
+
+#[test]
+fn decode_xor_mapped_address() {
+    for addr in ["1.2.3.4:5678", "[2001:db8::1]:443"] {
+        let addr: SocketAddr = addr.parse().unwrap();
+        let msg = make_response(&TID, ATTR_XOR_MAPPED_ADDRESS, &xor_mapped_address(&TID, addr));
+        assert_eq!(addr, decode_binding_response(&msg, &TID).unwrap());
+    }
+}
+
+#[test]
+fn decode_mapped_address() {
+    let msg = make_response(&TID, ATTR_MAPPED_ADDRESS, &[0, FAMILY_IPV4, 0x1f, 0x90, 10, 0, 0, 1]);
+    assert_eq!(
+        "10.0.0.1:8080".parse::<SocketAddr>().unwrap(),
+        decode_binding_response(&msg, &TID).unwrap()
+    );
+}
+
+#[test]
+fn decode_invalid_response() {
+    let addr: SocketAddr = "1.2.3.4:5678".parse().unwrap();
+    let msg = make_response(&TID, ATTR_XOR_MAPPED_ADDRESS, &xor_mapped_address(&TID, addr));
+    // Transaction ID mismatch.
+    assert!(decode_binding_response(&msg, &[8; 12]).is_err());
+    // Truncated message.
+    assert!(decode_binding_response(&msg[..msg.len() - 4], &TID).is_err());
+    // Missing address.
+    assert!(decode_binding_response(&make_response(&TID, 0x8022, b"server"), &TID).is_err());
+}
+
+#[tokio::test]
+async fn query_loopback_server() {
+    let server = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let server_addr = server.local_addr().unwrap().to_string();
+    tokio::spawn(async move {
+        let mut buf = [0; MAX_RESPONSE_LEN];
+        let (n, from) = server.recv_from(&mut buf).await.unwrap();
+        let tid: TransactionId = buf[8..n].try_into().unwrap();
+        let resp = make_response(&tid, ATTR_XOR_MAPPED_ADDRESS, &xor_mapped_address(&tid, from));
+        server.send_to(&resp, from).await.unwrap();
+    });
+    let ip = resolve_public_ip(&[server_addr], time::Duration::seconds(5)).await;
+    assert_eq!(Some(IpAddr::V4(Ipv4Addr::LOCALHOST)), ip);
+}
@@ -1,6 +1,11 @@
 use crate::network_protocol::PeerInfo;
+#[cfg(test)]
+use crate::testonly::sim;
 use anyhow::{anyhow, Context as _};
 use near_primitives::network::PeerId;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
 #[derive(Clone, Debug)]
 pub(crate) enum StreamType {
@@ -8,9 +13,62 @@ pub(crate) enum StreamType {
     Outbound { peer_id: PeerId },
 }
 
+/// Byte stream underlying a connection: either a real TCP stream or a link
+/// of a simulated network.
+#[derive(Debug)]
+pub(crate) enum RawStream {
+    Tcp(tokio::net::TcpStream),
+    #[cfg(test)]
+    Simulated(sim::link::Conn),
+}
+
+impl AsyncRead for RawStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(s) => Pin::new(s).poll_read(cx, buf),
+            #[cfg(test)]
+            Self::Simulated(s) => Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for RawStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp(s) => Pin::new(s).poll_write(cx, buf),
+            #[cfg(test)]
+            Self::Simulated(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(s) => Pin::new(s).poll_flush(cx),
+            #[cfg(test)]
+            Self::Simulated(s) => Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(s) => Pin::new(s).poll_shutdown(cx),
+            #[cfg(test)]
+            Self::Simulated(s) => Pin::new(s).poll_shutdown(cx),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Stream {
-    pub(crate) stream: tokio::net::TcpStream,
+    pub(crate) stream: RawStream,
     pub(crate) type_: StreamType,
     /// cached stream.local_addr()
     pub(crate) local_addr: std::net::SocketAddr,
@@ -54,7 +112,22 @@ impl Socket {
 
 impl Stream {
     fn new(stream: tokio::net::TcpStream, type_: StreamType) -> std::io::Result<Self> {
-        Ok(Self { peer_addr: stream.peer_addr()?, local_addr: stream.local_addr()?, stream, type_ })
+        Ok(Self {
+            peer_addr: stream.peer_addr()?,
+            local_addr: stream.local_addr()?,
+            stream: RawStream::Tcp(stream),
+            type_,
+        })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn simulated(conn: sim::link::Conn, type_: StreamType) -> Self {
+        Self {
+            local_addr: conn.local_addr(),
+            peer_addr: conn.peer_addr(),
+            stream: RawStream::Simulated(conn),
+            type_,
+        }
     }
 
     pub async fn connect(peer_info: &PeerInfo) -> anyhow::Result<Stream> {
@@ -83,11 +156,8 @@ impl Stream {
     pub async fn loopback(peer_id: PeerId) -> (Stream, Stream) {
         let localhost = std::net::SocketAddr::new(std::net::Ipv4Addr::LOCALHOST.into(), 0);
         let mut listener = Listener::bind(localhost).await.unwrap();
-        let peer_info = PeerInfo {
-            id: peer_id,
-            addr: Some(listener.0.local_addr().unwrap()),
-            account_id: None,
-        };
+        let peer_info =
+            PeerInfo { id: peer_id, addr: Some(listener.local_addr()), account_id: None };
         let (outbound, inbound) = tokio::join!(Stream::connect(&peer_info), listener.accept(),);
         (outbound.unwrap(), inbound.unwrap())
     }
@@ -103,17 +173,75 @@ impl Stream {
     }
 }
 
-pub(crate) struct Listener(tokio::net::TcpListener);
+pub(crate) enum Listener {
+    Tcp(tokio::net::TcpListener),
+    #[cfg(test)]
+    Simulated(sim::link::Listener),
+}
 
 impl Listener {
     // TODO(gprusak): this shouldn't be async. It is only
     // because TcpListener accepts anything that asynchronously resolves to SocketAddr.
     pub async fn bind(addr: std::net::SocketAddr) -> std::io::Result<Self> {
-        Ok(Self(tokio::net::TcpListener::bind(addr).await?))
+        Ok(Self::Tcp(tokio::net::TcpListener::bind(addr).await?))
     }
 
     pub async fn accept(&mut self) -> std::io::Result<Stream> {
-        let (stream, _) = self.0.accept().await?;
-        Stream::new(stream, StreamType::Inbound)
+        match self {
+            Self::Tcp(l) => {
+                let (stream, _) = l.accept().await?;
+                Stream::new(stream, StreamType::Inbound)
+            }
+            #[cfg(test)]
+            Self::Simulated(l) => Ok(Stream::simulated(l.accept().await?, StreamType::Inbound)),
+        }
+    }
+
+    #[cfg(test)]
+    fn local_addr(&self) -> std::net::SocketAddr {
+        match self {
+            Self::Tcp(l) => l.local_addr().unwrap(),
+            Self::Simulated(l) => l.local_addr(),
+        }
+    }
+}
+
+/// Transport over which the node listens for and establishes connections.
+#[derive(Clone, Debug)]
+pub enum Transport {
+    Tcp,
+    /// TEST-ONLY: in-memory links of a simulated network, see `crate::testonly::sim`.
+    #[cfg(test)]
+    Simulated(sim::link::Endpoint),
+}
+
+impl Default for Transport {
+    fn default() -> Self {
+        Self::Tcp
+    }
+}
+
+impl Transport {
+    pub(crate) async fn bind(&self, addr: std::net::SocketAddr) -> std::io::Result<Listener> {
+        match self {
+            Self::Tcp => Listener::bind(addr).await,
+            #[cfg(test)]
+            Self::Simulated(endpoint) => Ok(Listener::Simulated(endpoint.bind(addr)?)),
+        }
+    }
+
+    pub(crate) async fn connect(&self, peer_info: &PeerInfo) -> anyhow::Result<Stream> {
+        match self {
+            Self::Tcp => Stream::connect(peer_info).await,
+            #[cfg(test)]
+            Self::Simulated(endpoint) => {
+                let addr = peer_info
+                    .addr
+                    .ok_or(anyhow!("Trying to connect to peer with no public address"))?;
+                let conn =
+                    endpoint.connect(addr).await.context("sim::link::Endpoint::connect()")?;
+                Ok(Stream::simulated(conn, StreamType::Outbound { peer_id: peer_info.id.clone() }))
+            }
+        }
     }
 }
@@ -6,6 +6,7 @@ use std::hash::Hash;
 
 pub use super::actix;
 pub mod fake_client;
+pub mod sim;
 pub mod stream;
 
 pub type Rng = rand_xorshift::XorShiftRng;
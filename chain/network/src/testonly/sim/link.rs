@@ -0,0 +1,387 @@
+//! In-memory links between the nodes of a simulated network.
+//!
+//! Connections are reliable, ordered byte streams (like TCP), but the data sent over them
+//! is delivered only after the latency of the link has passed, as measured by the fake
+//! clock shared by all the nodes. Loss of a chunk of data is modelled as its retransmission:
+//! the chunk (and everything sent after it) is delayed by `RETRANSMISSION_TIMEOUT`.
+//! Randomness of every connection is derived from the seed of the network, so that the
+//! delays are reproducible.
+//!
+//! Nodes can be partitioned into groups which cannot communicate with each other:
+//! connections crossing a partition are reset and new ones cannot be established
+//! until the partition is healed.
+use crate::testonly::{make_rng, Rng};
+use crate::time;
+use rand::Rng as _;
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt as _, DuplexStream, ReadBuf};
+use tokio::sync::{mpsc, watch};
+
+/// Delay of a chunk of data which got lost and had to be retransmitted.
+pub const RETRANSMISSION_TIMEOUT: time::Duration = time::Duration::milliseconds(200);
+/// Size of the buffer of the data delivered to, but not yet read by the receiver.
+const RECV_BUFFER_SIZE: usize = 1 << 20;
+/// Ports of the outbound connections are allocated starting from this one.
+const FIRST_EPHEMERAL_PORT: u16 = 32768;
+
+#[derive(Clone, Copy, Debug)]
+pub struct LinkConfig {
+    /// One-way delay of the data sent over the link.
+    pub latency: time::Duration,
+    /// Probability that a chunk of data sent over the link gets lost
+    /// (and has to be retransmitted).
+    pub loss: f64,
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        Self { latency: time::Duration::milliseconds(50), loss: 0. }
+    }
+}
+
+struct Inner {
+    default_link: LinkConfig,
+    /// Overrides of the default link config, keyed by an ordered pair of IPs.
+    links: HashMap<(IpAddr, IpAddr), LinkConfig>,
+    listeners: HashMap<SocketAddr, mpsc::UnboundedSender<Conn>>,
+    /// Partition group of every node. Nodes not assigned explicitly belong to group 0.
+    groups: HashMap<IpAddr, usize>,
+    next_port: HashMap<IpAddr, u16>,
+    /// Number of connections established so far from one node to another.
+    /// Used to derive the randomness of the connections.
+    connections: HashMap<(IpAddr, IpAddr), u64>,
+}
+
+fn link_key(a: IpAddr, b: IpAddr) -> (IpAddr, IpAddr) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+impl Inner {
+    fn link(&self, a: IpAddr, b: IpAddr) -> LinkConfig {
+        self.links.get(&link_key(a, b)).copied().unwrap_or(self.default_link)
+    }
+
+    fn reachable(&self, a: IpAddr, b: IpAddr) -> bool {
+        let group = |ip| self.groups.get(&ip).copied().unwrap_or(0);
+        group(a) == group(b)
+    }
+}
+
+/// Simulated network. Nodes attach to it via `Endpoint`s.
+pub struct Network {
+    clock: time::Clock,
+    seed: u64,
+    /// Runtime on which the tasks delivering the data are spawned.
+    /// Nodes may run on other runtimes, which may be stopped independently.
+    runtime: tokio::runtime::Handle,
+    inner: Mutex<Inner>,
+    /// Notified whenever the partition changes, so that the broken connections get reset.
+    partition_changed: watch::Sender<()>,
+}
+
+impl Network {
+    /// Creates a new network. Has to be called within a tokio runtime.
+    pub fn new(clock: time::Clock, seed: u64, default_link: LinkConfig) -> Arc<Self> {
+        Arc::new(Self {
+            clock,
+            seed,
+            runtime: tokio::runtime::Handle::current(),
+            inner: Mutex::new(Inner {
+                default_link,
+                links: HashMap::new(),
+                listeners: HashMap::new(),
+                groups: HashMap::new(),
+                next_port: HashMap::new(),
+                connections: HashMap::new(),
+            }),
+            partition_changed: watch::channel(()).0,
+        })
+    }
+
+    pub fn endpoint(self: &Arc<Self>, ip: IpAddr) -> Endpoint {
+        Endpoint { network: self.clone(), ip }
+    }
+
+    /// Overrides config of the link between `a` and `b`.
+    /// Affects only the connections established afterwards.
+    pub fn set_link(&self, a: IpAddr, b: IpAddr, link: LinkConfig) {
+        self.inner.lock().unwrap().links.insert(link_key(a, b), link);
+    }
+
+    /// Splits the network into the given groups of nodes. Nodes not listed in any group
+    /// together form one more group. Connections between the groups are reset.
+    pub fn partition(&self, groups: &[Vec<IpAddr>]) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.groups.clear();
+        for (i, group) in groups.iter().enumerate() {
+            for ip in group {
+                inner.groups.insert(*ip, i + 1);
+            }
+        }
+        drop(inner);
+        self.partition_changed.send_replace(());
+    }
+
+    /// Removes the partition. Connections which have been reset are not restored.
+    pub fn heal(&self) {
+        self.inner.lock().unwrap().groups.clear();
+        self.partition_changed.send_replace(());
+    }
+
+    fn reachable(&self, a: IpAddr, b: IpAddr) -> bool {
+        self.inner.lock().unwrap().reachable(a, b)
+    }
+
+    /// Spawns a task delivering the data sent from `from` to `to` over the returned channel.
+    /// Returns the channel and the stream from which the receiver reads the data.
+    fn pipe(
+        self: &Arc<Self>,
+        from: IpAddr,
+        to: IpAddr,
+    ) -> (mpsc::UnboundedSender<(time::Instant, Vec<u8>)>, DuplexStream) {
+        let (send, mut queue) = mpsc::unbounded_channel::<(time::Instant, Vec<u8>)>();
+        let (mut w, r) = tokio::io::duplex(RECV_BUFFER_SIZE);
+        let network = self.clone();
+        let mut partition_changed = self.partition_changed.subscribe();
+        self.runtime.spawn(async move {
+            loop {
+                tokio::select! {
+                    res = partition_changed.changed() => {
+                        if res.is_err() || !network.reachable(from, to) {
+                            return;
+                        }
+                    }
+                    msg = queue.recv() => {
+                        let (deliver_at, data) = match msg {
+                            Some(msg) => msg,
+                            // Sender has shut down the connection.
+                            None => return,
+                        };
+                        network.clock.sleep_until(deliver_at).await;
+                        if !network.reachable(from, to) || w.write_all(&data).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+        (send, r)
+    }
+}
+
+/// Node's access point to the network.
+#[derive(Clone)]
+pub struct Endpoint {
+    network: Arc<Network>,
+    ip: IpAddr,
+}
+
+impl std::fmt::Debug for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Endpoint").field("ip", &self.ip).finish()
+    }
+}
+
+impl Endpoint {
+    pub fn bind(&self, addr: SocketAddr) -> io::Result<Listener> {
+        if addr.ip() != self.ip {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrNotAvailable,
+                format!("{addr} is not an address of {}", self.ip),
+            ));
+        }
+        let mut inner = self.network.inner.lock().unwrap();
+        if inner.listeners.contains_key(&addr) {
+            return Err(io::ErrorKind::AddrInUse.into());
+        }
+        let (send, conns) = mpsc::unbounded_channel();
+        inner.listeners.insert(addr, send);
+        Ok(Listener { network: self.network.clone(), addr, conns })
+    }
+
+    /// Establishes a connection to `addr`. Takes a round trip of the link.
+    pub async fn connect(&self, addr: SocketAddr) -> io::Result<Conn> {
+        let network = &self.network;
+        let (local_addr, link, n) = {
+            let mut inner = network.inner.lock().unwrap();
+            if !inner.reachable(self.ip, addr.ip()) {
+                return Err(io::ErrorKind::TimedOut.into());
+            }
+            if !inner.listeners.contains_key(&addr) {
+                return Err(io::ErrorKind::ConnectionRefused.into());
+            }
+            let port = inner.next_port.entry(self.ip).or_insert(FIRST_EPHEMERAL_PORT);
+            let local_addr = SocketAddr::new(self.ip, *port);
+            *port = port.wrapping_add(1);
+            let n = inner.connections.entry((self.ip, addr.ip())).or_default();
+            *n += 1;
+            let n = *n;
+            (local_addr, inner.link(self.ip, addr.ip()), n)
+        };
+        network.clock.sleep(link.latency * 2).await;
+        // The listener might have been closed, or a partition might have happened
+        // in the meantime.
+        let listener = {
+            let inner = network.inner.lock().unwrap();
+            match inner.listeners.get(&addr) {
+                Some(listener) if inner.reachable(self.ip, addr.ip()) => listener.clone(),
+                _ => return Err(io::ErrorKind::ConnectionRefused.into()),
+            }
+        };
+        let seed = near_primitives::hash::hash(
+            format!("{}:{}:{}:{}", network.seed, self.ip, addr.ip(), n).as_bytes(),
+        );
+        let mut rng = make_rng(u64::from_le_bytes(seed.as_ref()[..8].try_into().unwrap()));
+        let (out_send, in_recv) = network.pipe(self.ip, addr.ip());
+        let (in_send, out_recv) = network.pipe(addr.ip(), self.ip);
+        let outbound = Conn {
+            local_addr,
+            peer_addr: addr,
+            recv: out_recv,
+            send: Sender::new(network.clock.clone(), link, make_rng(rng.gen()), out_send),
+        };
+        let inbound = Conn {
+            local_addr: addr,
+            peer_addr: local_addr,
+            recv: in_recv,
+            send: Sender::new(network.clock.clone(), link, make_rng(rng.gen()), in_send),
+        };
+        listener.send(inbound).map_err(|_| io::Error::from(io::ErrorKind::ConnectionRefused))?;
+        Ok(outbound)
+    }
+}
+
+pub struct Listener {
+    network: Arc<Network>,
+    addr: SocketAddr,
+    conns: mpsc::UnboundedReceiver<Conn>,
+}
+
+impl Listener {
+    pub async fn accept(&mut self) -> io::Result<Conn> {
+        self.conns.recv().await.ok_or_else(|| io::ErrorKind::NotConnected.into())
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        self.network.inner.lock().unwrap().listeners.remove(&self.addr);
+    }
+}
+
+/// Sending half of a connection: assigns delivery times to the written data.
+struct Sender {
+    clock: time::Clock,
+    link: LinkConfig,
+    rng: Rng,
+    /// Delivery time of the last chunk. Data is delivered in order.
+    last: Option<time::Instant>,
+    /// None after shutdown.
+    queue: Option<mpsc::UnboundedSender<(time::Instant, Vec<u8>)>>,
+}
+
+impl Sender {
+    fn new(
+        clock: time::Clock,
+        link: LinkConfig,
+        rng: Rng,
+        queue: mpsc::UnboundedSender<(time::Instant, Vec<u8>)>,
+    ) -> Self {
+        Self { clock, link, rng, last: None, queue: Some(queue) }
+    }
+
+    fn next_delivery_time(&mut self) -> time::Instant {
+        let mut t = self.clock.now() + self.link.latency;
+        while self.rng.gen_bool(self.link.loss) {
+            t += RETRANSMISSION_TIMEOUT;
+        }
+        let t = std::cmp::max(t, self.last.unwrap_or(t));
+        self.last = Some(t);
+        t
+    }
+}
+
+/// Endpoint of a connection, see `Endpoint::connect` and `Listener::accept`.
+pub struct Conn {
+    local_addr: SocketAddr,
+    peer_addr: SocketAddr,
+    recv: DuplexStream,
+    send: Sender,
+}
+
+impl std::fmt::Debug for Conn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Conn")
+            .field("local_addr", &self.local_addr)
+            .field("peer_addr", &self.peer_addr)
+            .finish()
+    }
+}
+
+impl Conn {
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn peer_addr(&self) -> SocketAddr {
+        self.peer_addr
+    }
+
+    /// TEST-ONLY: computes the delivery time of the next chunk of data, as if it was
+    /// written now.
+    pub(super) fn next_delivery_time(&mut self) -> time::Instant {
+        self.send.next_delivery_time()
+    }
+}
+
+impl AsyncRead for Conn {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().recv).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for Conn {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let send = &mut self.get_mut().send;
+        if send.queue.is_none() {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        let deliver_at = send.next_delivery_time();
+        let res = match send.queue.as_ref().unwrap().send((deliver_at, buf.to_vec())) {
+            Ok(()) => Ok(buf.len()),
+            // The connection has been reset.
+            Err(_) => Err(io::ErrorKind::BrokenPipe.into()),
+        };
+        Poll::Ready(res)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().send.queue = None;
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,178 @@
+//! Simulation of a network of many nodes within a single process.
+//!
+//! Every node is a full PeerManagerActor, but instead of TCP the nodes communicate over
+//! in-memory links of a simulated network (see `link`), which allows to configure latency
+//! and loss of the links and to partition the network.
+//! All the nodes and links share a single FakeClock, which drives all the timers of the nodes
+//! and is advanced by the harness in small steps while the test awaits the expected state
+//! of the network, so that minutes of network time pass in seconds of real time.
+//!
+//! The simulation runs on a single thread (see `run`): the harness, the links and the actors
+//! and background tasks of all the nodes share a single-threaded executor. Topology, link
+//! delays, the nodes' keys and the random choices of the nodes are derived from the seed.
+//! The remaining sources of nondeterminism are the real time that the processing of events
+//! takes between the steps of the clock and the work offloaded to other threads
+//! (e.g. signature verification).
+use crate::config;
+use crate::network_protocol::testonly as data;
+use crate::network_protocol::PeerAddr;
+use crate::peer_manager;
+use crate::peer_manager::network_state::NetworkState;
+use crate::peer_manager::testonly::ActorHandler;
+use crate::tcp;
+use crate::testonly::{make_rng, Rng};
+use crate::time;
+use rand::Rng as _;
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+
+pub mod link;
+mod tests;
+
+/// Virtual time by which the clock is advanced in a single step of the simulation.
+const STEP: time::Duration = time::Duration::milliseconds(5);
+/// Real time given to the actors to process the events between the steps.
+const STEP_REAL_TIME: std::time::Duration = std::time::Duration::from_millis(1);
+/// Port on which all the simulated nodes listen.
+const PORT: u16 = 24567;
+
+/// Runs the simulation `f` on a single-threaded actix system.
+pub fn run<F: Future>(f: F) -> F::Output {
+    actix::System::new().block_on(f)
+}
+
+/// Advances the clock in small steps until `fut` completes.
+pub async fn advance_while<F: Future>(clock: &time::FakeClock, fut: F) -> F::Output {
+    tokio::pin!(fut);
+    loop {
+        tokio::select! {
+            biased;
+            out = &mut fut => return out,
+            () = tokio::time::sleep(STEP_REAL_TIME) => clock.advance(STEP),
+        }
+    }
+}
+
+pub(crate) struct Simulation {
+    pub clock: time::FakeClock,
+    pub rng: Rng,
+    pub chain: Arc<data::Chain>,
+    pub network: Arc<link::Network>,
+    pub nodes: Vec<ActorHandler>,
+}
+
+impl Simulation {
+    /// Creates a simulation without any nodes. Has to be called within `run`.
+    pub fn new(seed: u64, default_link: link::LinkConfig) -> Self {
+        let mut rng = make_rng(seed);
+        let mut clock = time::FakeClock::default();
+        let chain = Arc::new(data::Chain::make(&mut clock, &mut rng, 10));
+        let network = link::Network::new(clock.clock(), seed, default_link);
+        Self { clock, rng, chain, network, nodes: vec![] }
+    }
+
+    /// Virtual IP address of the i-th node.
+    pub fn ip(i: usize) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::from(0x0a00_0001 + i as u32))
+    }
+
+    /// Returns the config of the next node to be started.
+    pub fn make_config(&mut self) -> config::NetworkConfig {
+        let mut cfg = self.chain.make_config(&mut self.rng);
+        let addr = SocketAddr::new(Self::ip(self.nodes.len()), PORT);
+        cfg.node_addr = Some(addr);
+        if let Some(validator) = &mut cfg.validator {
+            validator.proxies = config::ValidatorProxies::Static(vec![PeerAddr {
+                addr,
+                peer_id: near_primitives::network::PeerId::new(cfg.node_key.public_key()),
+            }
+            .into()]);
+        }
+        cfg.transport = tcp::Transport::Simulated(self.network.endpoint(addr.ip()));
+        cfg.rng_seed = Some(self.rng.gen());
+        // Actors process the events in real time, while the clock advances a few times faster,
+        // so timeouts have to accommodate the processing delays of many nodes
+        // running in a single process.
+        cfg.handshake_timeout = time::Duration::seconds(60);
+        cfg
+    }
+
+    /// Starts a node with the given config, which should come from `make_config`.
+    pub async fn start_node(&mut self, cfg: config::NetworkConfig) -> usize {
+        let node = peer_manager::testonly::start_local(
+            self.clock.clock(),
+            near_store::db::TestDB::new(),
+            cfg,
+            self.chain.clone(),
+        )
+        .await;
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// Starts `n` nodes with the default config.
+    pub async fn add_nodes(&mut self, n: usize) {
+        for _ in 0..n {
+            let cfg = self.make_config();
+            self.start_node(cfg).await;
+        }
+    }
+
+    /// Advances the clock until `fut` completes.
+    pub async fn advance_while<F: Future>(&self, fut: F) -> F::Output {
+        advance_while(&self.clock, fut).await
+    }
+
+    /// Establishes a connection from node `a` to node `b`.
+    pub async fn connect(&self, a: usize, b: usize) {
+        let peer_info = self.nodes[b].peer_info();
+        self.advance_while(self.nodes[a].connect_to(&peer_info)).await
+    }
+
+    /// Advances the clock until `pred` holds for the state of every node.
+    /// Panics if it doesn't happen within `timeout` of virtual time.
+    pub async fn wait_for_all<F, Fut>(&self, timeout: time::Duration, pred: F)
+    where
+        F: 'static + Send + Clone + Fn(usize, Arc<NetworkState>) -> Fut,
+        Fut: 'static + Send + Future<Output = bool>,
+    {
+        let deadline = self.clock.now() + timeout;
+        self.advance_while(async {
+            for (i, node) in self.nodes.iter().enumerate() {
+                loop {
+                    let pred = pred.clone();
+                    if node.with_state(move |s| pred(i, s)).await {
+                        break;
+                    }
+                    assert!(
+                        self.clock.now() < deadline,
+                        "node {i} didn't reach the expected state"
+                    );
+                    self.clock.sleep(STEP).await;
+                }
+            }
+        })
+        .await
+    }
+
+    /// Advances the clock until every node has a route to every other node.
+    pub async fn wait_for_full_routing(&self, timeout: time::Duration) {
+        let n = self.nodes.len();
+        self.wait_for_all(timeout, move |_, s| async move {
+            s.graph.routing_table.info().next_hops.len() == n - 1
+        })
+        .await
+    }
+
+    /// Splits the nodes into the given groups, see `link::Network::partition`.
+    pub fn partition(&self, groups: &[Vec<usize>]) {
+        let groups: Vec<Vec<IpAddr>> =
+            groups.iter().map(|g| g.iter().map(|i| Self::ip(*i)).collect()).collect();
+        self.network.partition(&groups);
+    }
+
+    pub fn heal(&self) {
+        self.network.heal();
+    }
+}
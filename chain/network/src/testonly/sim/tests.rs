@@ -0,0 +1,240 @@
+use crate::testonly::sim::link::{LinkConfig, Network, RETRANSMISSION_TIMEOUT};
+use crate::testonly::sim::*;
+use crate::time;
+use futures::FutureExt as _;
+use near_o11y::testonly::init_test_logger;
+use rand::seq::SliceRandom as _;
+use rand::Rng as _;
+use std::collections::HashSet;
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+fn addr(i: usize) -> SocketAddr {
+    SocketAddr::new(Simulation::ip(i), PORT)
+}
+
+/// Gives the tasks delivering the data a chance to run.
+async fn settle() {
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+}
+
+#[tokio::test]
+async fn link_latency() {
+    let clock = time::FakeClock::default();
+    let latency = time::Duration::milliseconds(100);
+    let network = Network::new(clock.clock(), 7, LinkConfig::default());
+    network.set_link(addr(1).ip(), addr(0).ip(), LinkConfig { latency, loss: 0. });
+    let mut listener = network.endpoint(addr(1).ip()).bind(addr(1)).unwrap();
+    let start = clock.now();
+    let mut outbound =
+        advance_while(&clock, network.endpoint(addr(0).ip()).connect(addr(1))).await.unwrap();
+    assert!(clock.now() >= start + latency * 2);
+    let mut inbound = listener.accept().await.unwrap();
+    assert_eq!(outbound.local_addr(), inbound.peer_addr());
+    assert_eq!(addr(1), outbound.peer_addr());
+
+    let start = clock.now();
+    outbound.write_all(b"hello").await.unwrap();
+    let mut buf = [0; 5];
+    clock.advance(latency - time::Duration::milliseconds(1));
+    settle().await;
+    assert!(inbound.read(&mut buf).now_or_never().is_none());
+    clock.advance_until(start + latency);
+    inbound.read_exact(&mut buf).await.unwrap();
+    assert_eq!(b"hello", &buf);
+
+    // Shutdown is observed by the other side once the data sent before is delivered.
+    inbound.write_all(b"bye").await.unwrap();
+    inbound.shutdown().await.unwrap();
+    let mut got = vec![];
+    advance_while(&clock, outbound.read_to_end(&mut got)).await.unwrap();
+    assert_eq!(b"bye".to_vec(), got);
+}
+
+#[tokio::test]
+async fn link_loss_is_reproducible() {
+    let link = LinkConfig { latency: time::Duration::milliseconds(10), loss: 0.3 };
+    let delays = |seed| async move {
+        let clock = time::FakeClock::default();
+        let network = Network::new(clock.clock(), seed, link);
+        let _listener = network.endpoint(addr(1).ip()).bind(addr(1)).unwrap();
+        let mut conn =
+            advance_while(&clock, network.endpoint(addr(0).ip()).connect(addr(1))).await.unwrap();
+        let mut delays = vec![];
+        for _ in 0..100 {
+            clock.advance(time::Duration::milliseconds(1000));
+            delays.push(conn.next_delivery_time() - clock.now());
+        }
+        delays
+    };
+    let got = delays(1).await;
+    assert_eq!(got, delays(1).await);
+    assert_ne!(got, delays(2).await);
+    // Every delay is the latency plus some number of retransmissions.
+    let rto = RETRANSMISSION_TIMEOUT.whole_milliseconds();
+    assert!(got.iter().all(|d| (*d - link.latency).whole_milliseconds() % rto == 0));
+    assert!(got.iter().any(|d| *d == link.latency));
+    assert!(got.iter().any(|d| *d > link.latency));
+}
+
+#[tokio::test]
+async fn lossy_link_is_reliable() {
+    let clock = time::FakeClock::default();
+    let link = LinkConfig { latency: time::Duration::milliseconds(30), loss: 0.5 };
+    let network = Network::new(clock.clock(), 3, link);
+    let mut listener = network.endpoint(addr(1).ip()).bind(addr(1)).unwrap();
+    let mut outbound =
+        advance_while(&clock, network.endpoint(addr(0).ip()).connect(addr(1))).await.unwrap();
+    let mut inbound = listener.accept().await.unwrap();
+    let mut rng = make_rng(12);
+    let want: Vec<u8> = (0..10000).map(|_| rng.gen()).collect();
+    for chunk in want.chunks(100) {
+        outbound.write_all(chunk).await.unwrap();
+        clock.advance(time::Duration::milliseconds(1));
+    }
+    outbound.shutdown().await.unwrap();
+    let mut got = vec![];
+    advance_while(&clock, inbound.read_to_end(&mut got)).await.unwrap();
+    assert_eq!(want, got);
+}
+
+#[tokio::test]
+async fn partition_resets_connections() {
+    let clock = time::FakeClock::default();
+    let network = Network::new(clock.clock(), 5, LinkConfig::default());
+    let (a, b) = (network.endpoint(addr(0).ip()), network.endpoint(addr(1).ip()));
+    let mut listener = b.bind(addr(1)).unwrap();
+    let mut outbound = advance_while(&clock, a.connect(addr(1))).await.unwrap();
+    let mut inbound = listener.accept().await.unwrap();
+
+    network.partition(&[vec![addr(0).ip()]]);
+    let mut buf = vec![];
+    assert_eq!(0, advance_while(&clock, inbound.read_to_end(&mut buf)).await.unwrap());
+    settle().await;
+    assert!(outbound.write_all(b"x").await.is_err());
+    assert!(advance_while(&clock, a.connect(addr(1))).await.is_err());
+
+    network.heal();
+    advance_while(&clock, a.connect(addr(1))).await.unwrap();
+    listener.accept().await.unwrap();
+    // Nobody listens on this address.
+    assert!(advance_while(&clock, a.connect(addr(2))).await.is_err());
+}
+
+/// Connects the nodes into a random connected graph: every node connects to
+/// up to `degree` nodes started before it.
+async fn connect_randomly(sim: &mut Simulation, degree: usize) {
+    for i in 1..sim.nodes.len() {
+        let peers: Vec<usize> = (0..i).collect();
+        let peers: Vec<usize> =
+            peers.choose_multiple(&mut sim.rng, std::cmp::min(i, degree)).copied().collect();
+        for j in peers {
+            sim.connect(i, j).await;
+        }
+    }
+}
+
+#[test]
+fn routing_converges() {
+    run(async {
+        init_test_logger();
+        let mut sim = Simulation::new(
+            84213,
+            LinkConfig { latency: time::Duration::milliseconds(30), loss: 0.02 },
+        );
+        sim.add_nodes(30).await;
+        connect_randomly(&mut sim, 2).await;
+        sim.wait_for_full_routing(time::Duration::minutes(10)).await;
+    })
+}
+
+#[test]
+#[cfg_attr(not(feature = "expensive_tests"), ignore)]
+fn routing_converges_at_scale() {
+    run(async {
+        init_test_logger();
+        let mut sim = Simulation::new(
+            61729,
+            LinkConfig { latency: time::Duration::milliseconds(30), loss: 0.01 },
+        );
+        sim.add_nodes(300).await;
+        connect_randomly(&mut sim, 2).await;
+        sim.wait_for_full_routing(time::Duration::minutes(30)).await;
+    })
+}
+
+#[test]
+fn partition_and_heal() {
+    run(async {
+        init_test_logger();
+        let mut sim = Simulation::new(
+            9012,
+            LinkConfig { latency: time::Duration::milliseconds(20), loss: 0. },
+        );
+        let n = 10;
+        sim.add_nodes(2 * n).await;
+        // Two chains of nodes, connected by a bridge between their first nodes.
+        for i in 1..n {
+            sim.connect(i, i - 1).await;
+            sim.connect(n + i, n + i - 1).await;
+        }
+        sim.connect(0, n).await;
+        sim.wait_for_full_routing(time::Duration::minutes(10)).await;
+
+        tracing::info!(target:"test", "partition the network");
+        let left: Vec<usize> = (0..n).collect();
+        sim.partition(&[left.clone()]);
+        let left: HashSet<_> = left.into_iter().collect();
+        let peer_ids: Vec<_> = sim.nodes.iter().map(|node| node.peer_info().id).collect();
+        sim.wait_for_all(time::Duration::minutes(10), move |i, s| {
+            let left = left.clone();
+            let peer_ids = peer_ids.clone();
+            async move {
+                let next_hops = s.graph.routing_table.info().next_hops;
+                // Every node has routes exactly to the nodes on its side of the partition.
+                next_hops.len() == n - 1
+                    && next_hops.keys().all(|peer_id| {
+                        let j = peer_ids.iter().position(|p| p == peer_id).unwrap();
+                        left.contains(&i) == left.contains(&j)
+                    })
+            }
+        })
+        .await;
+
+        tracing::info!(target:"test", "heal the network and rebuild the bridge");
+        sim.heal();
+        sim.connect(n, 0).await;
+        sim.wait_for_full_routing(time::Duration::minutes(10)).await;
+    })
+}
+
+#[test]
+fn accounts_data_propagation() {
+    run(async {
+        init_test_logger();
+        let mut sim = Simulation::new(
+            55123,
+            LinkConfig { latency: time::Duration::milliseconds(50), loss: 0.01 },
+        );
+        sim.add_nodes(20).await;
+        connect_randomly(&mut sim, 2).await;
+        let chain_info = peer_manager::testonly::make_chain_info(
+            &sim.chain,
+            &sim.nodes.iter().collect::<Vec<_>>()[..],
+        );
+        let mut want = HashSet::new();
+        for node in &sim.nodes {
+            node.set_chain_info(chain_info.clone()).await;
+        }
+        for node in &sim.nodes {
+            // Every node is its own proxy, so advertising requires a connection to itself.
+            let clock = sim.clock.clock();
+            want.extend(sim.advance_while(node.tier1_advertise_proxies(&clock)).await);
+        }
+        assert_eq!(sim.nodes.len(), want.len());
+        sim.wait_for_all(time::Duration::minutes(10), move |_, s| {
+            let want = want.clone();
+            async move { s.accounts_data.load().data.values().cloned().collect::<HashSet<_>>() == want }
+        })
+        .await;
+    })
+}
@@ -3,6 +3,7 @@ pub use crate::network_protocol::{
     AccountOrPeerIdOrHash, Encoding, Handshake, HandshakeFailureReason, PeerMessage,
     RoutingTableUpdate, SignedAccountData,
 };
+pub use crate::peer_manager::peer_store::score::PeerScore;
 use crate::routing::routing_table_view::RoutingTableInfo;
 use crate::time;
 use futures::future::BoxFuture;
@@ -103,6 +104,9 @@ pub struct KnownPeerState {
     // Last time we tried to connect to this peer.
     // This data is not persisted in storage.
     pub last_outbound_attempt: Option<(time::Utc, Result<(), String>)>,
+    // Reputation of this peer.
+    // This data is not persisted in storage.
+    pub score: PeerScore,
 }
 
 impl KnownPeerState {
@@ -113,6 +117,7 @@ impl KnownPeerState {
             first_seen: now,
             last_seen: now,
             last_outbound_attempt: None,
+            score: PeerScore::default(),
         }
     }
 }
@@ -339,10 +344,13 @@ impl From<&FullPeerInfo> for ConnectedPeerInfo {
             full_peer_info: full_peer_info.clone(),
             received_bytes_per_sec: 0,
             sent_bytes_per_sec: 0,
+            received_bytes_per_sec_by_type: HashMap::new(),
+            sent_bytes_per_sec_by_type: HashMap::new(),
             last_time_peer_requested: time::Instant::now(),
             last_time_received_message: time::Instant::now(),
             connection_established_time: time::Instant::now(),
             peer_type: PeerType::Outbound,
+            score: 0.,
         }
     }
 }
@@ -355,6 +363,10 @@ pub struct ConnectedPeerInfo {
     pub received_bytes_per_sec: u64,
     /// Number of bytes we've sent to the peer.
     pub sent_bytes_per_sec: u64,
+    /// Number of bytes we've received from the peer, by message type.
+    pub received_bytes_per_sec_by_type: HashMap<&'static str, u64>,
+    /// Number of bytes we've sent to the peer, by message type.
+    pub sent_bytes_per_sec_by_type: HashMap<&'static str, u64>,
     /// Last time requested peers.
     pub last_time_peer_requested: time::Instant,
     /// Last time we received a message from this peer.
@@ -363,6 +375,8 @@ pub struct ConnectedPeerInfo {
     pub connection_established_time: time::Instant,
     /// Who started connection. Inbound (other) or Outbound (us).
     pub peer_type: PeerType,
+    /// Current reputation score of the peer, see `PeerScore`.
+    pub score: f64,
 }
 
 #[derive(Debug, Clone, actix::MessageResponse)]
@@ -419,6 +419,28 @@ impl From<NearActions> for Vec<crate::models::Operation> {
                     );
                     operations.push(deploy_contract_operation);
                 }
+
+                near_primitives::transaction::Action::Delegate(action) => {
+                    let initiate_delegate_action_operation_id =
+                        crate::models::OperationIdentifier::new(&operations);
+                    operations.push(
+                        validated_operations::InitiateDelegateActionOperation {
+                            sender_account: sender_account_identifier.clone(),
+                        }
+                        .into_operation(initiate_delegate_action_operation_id.clone()),
+                    );
+
+                    operations.push(
+                        validated_operations::DelegateActionOperation {
+                            account: receiver_account_identifier.clone(),
+                            signed_delegate_action: action,
+                        }
+                        .into_related_operation(
+                            crate::models::OperationIdentifier::new(&operations),
+                            vec![initiate_delegate_action_operation_id],
+                        ),
+                    );
+                }
             }
         }
         operations
@@ -649,12 +671,28 @@ impl TryFrom<Vec<crate::models::Operation>> for NearActions {
                     )
                 }
 
+                crate::models::OperationType::DelegateAction => {
+                    let delegate_action_operation =
+                        validated_operations::DelegateActionOperation::try_from(tail_operation)?;
+                    receiver_account_id.try_set(&delegate_action_operation.account)?;
+
+                    let initiate_delegate_action_operation =
+                        validated_operations::InitiateDelegateActionOperation::try_from_option(
+                            operations.next(),
+                        )?;
+                    sender_account_id
+                        .try_set(&initiate_delegate_action_operation.sender_account)?;
+
+                    actions.push(delegate_action_operation.signed_delegate_action.into())
+                }
+
                 crate::models::OperationType::InitiateCreateAccount
                 | crate::models::OperationType::InitiateDeleteAccount
                 | crate::models::OperationType::InitiateAddKey
                 | crate::models::OperationType::InitiateDeleteKey
                 | crate::models::OperationType::InitiateDeployContract
                 | crate::models::OperationType::InitiateFunctionCall
+                | crate::models::OperationType::InitiateDelegateAction
                 | crate::models::OperationType::DeleteAccount => {
                     return Err(crate::errors::ErrorKind::InvalidInput(format!(
                         "Unexpected operation `{:?}`",
@@ -876,6 +914,31 @@ mod tests {
                 deposit: near_primitives::types::Balance::MAX,
             }
             .into()];
+        let delegate_action_signer = near_crypto::InMemorySigner::from_seed(
+            "receiver.near".parse().unwrap(),
+            near_crypto::KeyType::ED25519,
+            "receiver.near",
+        );
+        let delegate_action = near_primitives::transaction::DelegateAction {
+            sender_id: "receiver.near".parse().unwrap(),
+            receiver_id: "contract.near".parse().unwrap(),
+            actions: vec![near_primitives::transaction::Action::from(
+                near_primitives::transaction::TransferAction { deposit: 123 },
+            )
+            .try_into()
+            .unwrap()],
+            nonce: 1,
+            max_block_height: 100,
+            public_key: delegate_action_signer.public_key.clone(),
+        };
+        let delegate_actions = vec![near_primitives::transaction::SignedDelegateAction {
+            signature: near_crypto::Signer::sign(
+                &delegate_action_signer,
+                delegate_action.get_hash().as_ref(),
+            ),
+            delegate_action,
+        }
+        .into()];
 
         let wallet_style_create_account_actions =
             [create_account_actions.to_vec(), add_key_actions.to_vec(), transfer_actions.to_vec()]
@@ -908,6 +971,7 @@ mod tests {
             create_account_and_stake_immediately_actions,
             deploy_contract_and_call_it_actions,
             two_factor_auth_actions,
+            delegate_actions,
         ];
 
         for actions in non_sir_compatible_actions.clone() {
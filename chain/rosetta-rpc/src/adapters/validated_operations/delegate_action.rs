@@ -0,0 +1,51 @@
+use super::ValidatedOperation;
+
+pub(crate) struct DelegateActionOperation {
+    pub(crate) account: crate::models::AccountIdentifier,
+    pub(crate) signed_delegate_action: near_primitives::transaction::SignedDelegateAction,
+}
+
+impl ValidatedOperation for DelegateActionOperation {
+    const OPERATION_TYPE: crate::models::OperationType =
+        crate::models::OperationType::DelegateAction;
+
+    fn into_operation(
+        self,
+        operation_identifier: crate::models::OperationIdentifier,
+    ) -> crate::models::Operation {
+        crate::models::Operation {
+            operation_identifier,
+
+            account: self.account,
+            amount: None,
+            metadata: Some(crate::models::OperationMetadata {
+                signed_delegate_action: Some(self.signed_delegate_action.into()),
+                ..Default::default()
+            }),
+
+            related_operations: None,
+            type_: Self::OPERATION_TYPE,
+            status: None,
+        }
+    }
+}
+
+fn required_fields_error() -> crate::errors::ErrorKind {
+    crate::errors::ErrorKind::InvalidInput(
+        "DELEGATE_ACTION operation requires `signed_delegate_action` being passed in the metadata"
+            .into(),
+    )
+}
+
+impl TryFrom<crate::models::Operation> for DelegateActionOperation {
+    type Error = crate::errors::ErrorKind;
+
+    fn try_from(operation: crate::models::Operation) -> Result<Self, Self::Error> {
+        Self::validate_operation_type(operation.type_)?;
+        let metadata = operation.metadata.ok_or_else(required_fields_error)?;
+        let signed_delegate_action =
+            metadata.signed_delegate_action.ok_or_else(required_fields_error)?.into_inner();
+
+        Ok(Self { account: operation.account, signed_delegate_action })
+    }
+}
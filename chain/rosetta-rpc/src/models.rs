@@ -720,6 +720,8 @@ pub(crate) enum OperationType {
     DeployContract,
     InitiateFunctionCall,
     FunctionCall,
+    InitiateDelegateAction,
+    DelegateAction,
 }
 
 #[derive(
@@ -785,6 +787,10 @@ pub(crate) struct OperationMetadata {
     pub attached_gas: Option<crate::utils::SignedDiff<near_primitives::types::Gas>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub predecessor_id: Option<AccountIdentifier>,
+    /// Has to be specified for DELEGATE_ACTION operation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed_delegate_action:
+        Option<BorshInHexString<near_primitives::transaction::SignedDelegateAction>>,
 }
 
 impl OperationMetadata {
@@ -1152,6 +1158,9 @@ impl TryFrom<&PublicKey> for near_crypto::PublicKey {
             CurveType::Secp256k1 => {
                 near_crypto::PublicKey::SECP256K1((hex_bytes.as_ref() as &[u8]).try_into()?)
             }
+            CurveType::Secp256r1 => {
+                near_crypto::PublicKey::SECP256R1((hex_bytes.as_ref() as &[u8]).try_into()?)
+            }
         })
     }
 }
@@ -1164,6 +1173,8 @@ pub(crate) enum CurveType {
     Edwards25519,
     /// SEC compressed - 33 bytes (<https://secg.org/sec1-v2.pdf#subsubsection.2.3.3>)
     Secp256k1,
+    /// SEC compressed - 33 bytes (<https://secg.org/sec1-v2.pdf#subsubsection.2.3.3>)
+    Secp256r1,
 }
 
 impl From<near_crypto::KeyType> for CurveType {
@@ -1171,6 +1182,7 @@ impl From<near_crypto::KeyType> for CurveType {
         match key_type {
             near_crypto::KeyType::ED25519 => Self::Edwards25519,
             near_crypto::KeyType::SECP256K1 => Self::Secp256k1,
+            near_crypto::KeyType::SECP256R1 => Self::Secp256r1,
         }
     }
 }
@@ -1215,10 +1227,12 @@ impl TryFrom<&Signature> for near_crypto::Signature {
 pub(crate) enum SignatureType {
     /// `R (32-byte) || s (32-bytes)` - `64 bytes`
     Ed25519,
+    /// `r (32-bytes) || s (32-bytes)` - `64 bytes`
+    ///
+    /// Used for signatures of secp256r1 keys.
+    Ecdsa,
     /* Rosetta Spec also provides:
      *
-     * /// `r (32-bytes) || s (32-bytes)` - `64 bytes`
-     * ECDSA,
      * /// `r (32-bytes) || s (32-bytes) || v (1-byte)` - `65 bytes`
      * #[serde(rename = "ecdsa_recovery")]
      * ECDSARecovery,
@@ -1236,6 +1250,7 @@ impl From<near_crypto::KeyType> for SignatureType {
             near_crypto::KeyType::SECP256K1 => {
                 unimplemented!("SECP256K1 keys are not implemented in Rosetta yet")
             }
+            near_crypto::KeyType::SECP256R1 => Self::Ecdsa,
         }
     }
 }
@@ -1244,6 +1259,7 @@ impl From<SignatureType> for near_crypto::KeyType {
     fn from(signature_type: SignatureType) -> Self {
         match signature_type {
             SignatureType::Ed25519 => Self::ED25519,
+            SignatureType::Ecdsa => Self::SECP256R1,
         }
     }
 }
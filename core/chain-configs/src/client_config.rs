@@ -40,6 +40,12 @@ pub struct GCConfig {
     /// Number of epochs for which we keep store data.
     #[serde(default = "default_gc_num_epochs_to_keep")]
     pub gc_num_epochs_to_keep: u64,
+
+    /// Accounts whose state changes, receipts and execution outcomes are kept
+    /// forever even though the rest of the data is garbage collected.  Turns
+    /// the node into a partial archive; empty by default.
+    #[serde(default, skip_serializing_if = "AccountRetentionFilter::is_empty")]
+    pub retained_accounts: AccountRetentionFilter,
 }
 
 impl Default for GCConfig {
@@ -48,6 +54,7 @@ impl Default for GCConfig {
             gc_blocks_limit: 2,
             gc_fork_clean_step: 100,
             gc_num_epochs_to_keep: DEFAULT_GC_NUM_EPOCHS_TO_KEEP,
+            retained_accounts: AccountRetentionFilter::default(),
         }
     }
 }
@@ -70,6 +77,110 @@ impl GCConfig {
     }
 }
 
+/// List of account patterns whose history survives garbage collection.
+///
+/// Each pattern is either an exact account id (`"app.near"`) or a prefix
+/// terminated with an asterisk (`"aurora*"`) which matches every account
+/// starting with that prefix.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct AccountRetentionFilter(Vec<String>);
+
+impl AccountRetentionFilter {
+    pub fn new(patterns: Vec<String>) -> Self {
+        Self(patterns)
+    }
+
+    /// Returns whether no account is retained, i.e. garbage collection
+    /// behaves as on a regular node.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns whether history of the given account should be kept.
+    pub fn matches(&self, account_id: &AccountId) -> bool {
+        let account_id = account_id.as_ref();
+        self.0.iter().any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => account_id.starts_with(prefix),
+            None => account_id == pattern,
+        })
+    }
+}
+
+/// Configuration for the store consistency checker which validates the
+/// database incrementally in the background.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct StoreConsistencyCheckConfig {
+    /// Whether the checker is running.  Disabled by default.
+    pub enabled: bool,
+    /// Maximum number of heights validated during a single step.
+    pub heights_per_step: BlockHeightDelta,
+    /// How often a step is scheduled.
+    pub step_period: Duration,
+}
+
+impl Default for StoreConsistencyCheckConfig {
+    fn default() -> Self {
+        Self { enabled: false, heights_per_step: 10, step_period: Duration::from_secs(1) }
+    }
+}
+
+impl StoreConsistencyCheckConfig {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// Limits of a single `EXPERIMENTAL_simulate_transaction` request.  The
+/// simulation stops with an error once either of them is exceeded.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SimulateTransactionConfig {
+    /// Maximum total gas burnt by the transaction and all its receipts.
+    pub max_gas_burnt: Gas,
+    /// Maximum wall time spent on applying the transaction and its receipts.
+    pub timeout: Duration,
+}
+
+impl Default for SimulateTransactionConfig {
+    fn default() -> Self {
+        Self { max_gas_burnt: 1_000_000_000_000_000, timeout: Duration::from_secs(5) }
+    }
+}
+
+impl SimulateTransactionConfig {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// Configuration of how chunk parts are distributed beyond the part owners.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ChunkDistributionConfig {
+    /// Maximum number of extra parts a chunk producer sends to part owners
+    /// which frequently request their own parts back from it.  The more often
+    /// an owner requests its parts, the more extra parts it gets, up to this
+    /// limit.  Zero disables sending redundant parts.
+    pub max_redundant_parts: usize,
+    /// Experimental: remember part requests which could not be answered yet
+    /// and forward the parts to the requesting peers as soon as they arrive.
+    pub forward_parts_to_requesters: bool,
+}
+
+impl Default for ChunkDistributionConfig {
+    fn default() -> Self {
+        Self { max_redundant_parts: 0, forward_parts_to_requesters: false }
+    }
+}
+
+impl ChunkDistributionConfig {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ClientConfig {
     /// Version of the binary.
@@ -154,10 +265,16 @@ pub struct ClientConfig {
     /// genesis file.  The value only affects the RPCs without influencing the
     /// protocol thus changing it per-node doesn’t affect the blockchain.
     pub max_gas_burnt_view: Option<Gas>,
+    /// Limits of transaction simulation requests.
+    pub simulate_transaction: SimulateTransactionConfig,
     /// Re-export storage layer statistics as prometheus metrics.
     pub enable_statistics_export: bool,
     /// Number of threads to execute background migration work in client.
     pub client_background_migration_threads: usize,
+    /// Background store consistency checker configuration.
+    pub store_consistency_check: StoreConsistencyCheckConfig,
+    /// Redundant distribution of chunk parts.
+    pub chunk_distribution: ChunkDistributionConfig,
 }
 
 impl ClientConfig {
@@ -216,8 +333,27 @@ impl ClientConfig {
             view_client_throttle_period: Duration::from_secs(1),
             trie_viewer_state_size_limit: None,
             max_gas_burnt_view: None,
+            simulate_transaction: SimulateTransactionConfig::default(),
             enable_statistics_export: true,
             client_background_migration_threads: 1,
+            store_consistency_check: StoreConsistencyCheckConfig::default(),
+            chunk_distribution: ChunkDistributionConfig::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::AccountRetentionFilter;
+
+    #[test]
+    fn test_account_retention_filter() {
+        let filter = AccountRetentionFilter::new(vec!["app.near".into(), "aurora*".into()]);
+        assert!(filter.matches(&"app.near".parse().unwrap()));
+        assert!(!filter.matches(&"sub.app.near".parse().unwrap()));
+        assert!(filter.matches(&"aurora".parse().unwrap()));
+        assert!(filter.matches(&"aurora.pool.near".parse().unwrap()));
+        assert!(!filter.matches(&"near".parse().unwrap()));
+        assert!(!AccountRetentionFilter::default().matches(&"app.near".parse().unwrap()));
+    }
+}
@@ -3,7 +3,8 @@ mod genesis_config;
 pub mod genesis_validate;
 
 pub use client_config::{
-    ClientConfig, GCConfig, LogSummaryStyle, DEFAULT_GC_NUM_EPOCHS_TO_KEEP,
+    AccountRetentionFilter, ChunkDistributionConfig, ClientConfig, GCConfig, LogSummaryStyle,
+    SimulateTransactionConfig, StoreConsistencyCheckConfig, DEFAULT_GC_NUM_EPOCHS_TO_KEEP,
     MIN_GC_NUM_EPOCHS_TO_KEEP, TEST_STATE_SYNC_TIMEOUT,
 };
 pub use genesis_config::{
@@ -77,6 +77,23 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_secp256r1_key_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("key-file");
+
+        let account_id = ACCOUNT_ID.parse().unwrap();
+        let secret_key = SecretKey::from_seed(crate::KeyType::SECP256R1, "test");
+        let public_key = secret_key.public_key();
+        let key = KeyFile { account_id, public_key, secret_key };
+        key.write_to_file(&path).unwrap();
+
+        let loaded = KeyFile::from_file(&path).unwrap();
+        assert_eq!(key.secret_key, loaded.secret_key);
+        assert_eq!(key.public_key, loaded.public_key);
+        assert_eq!(loaded.secret_key.public_key(), loaded.public_key);
+    }
+
     #[test]
     fn test_from_file() {
         fn load(contents: &[u8]) -> io::Result<()> {
@@ -2,7 +2,7 @@ pub use errors::{ParseKeyError, ParseKeyTypeError, ParseSignatureError};
 pub use key_file::KeyFile;
 pub use signature::{
     ED25519PublicKey, ED25519SecretKey, KeyType, PublicKey, Secp256K1PublicKey, Secp256K1Signature,
-    SecretKey, Signature,
+    Secp256R1PublicKey, Secp256R1Signature, SecretKey, Signature,
 };
 pub use signer::{EmptySigner, InMemorySigner, Signer};
 
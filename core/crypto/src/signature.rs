@@ -19,6 +19,7 @@ pub static SECP256K1: Lazy<secp256k1::Secp256k1<secp256k1::All>> =
 pub enum KeyType {
     ED25519 = 0,
     SECP256K1 = 1,
+    SECP256R1 = 2,
 }
 
 impl Display for KeyType {
@@ -26,6 +27,7 @@ impl Display for KeyType {
         f.write_str(match self {
             KeyType::ED25519 => "ed25519",
             KeyType::SECP256K1 => "secp256k1",
+            KeyType::SECP256R1 => "secp256r1",
         })
     }
 }
@@ -38,6 +40,7 @@ impl FromStr for KeyType {
         match lowercase_key_type.as_str() {
             "ed25519" => Ok(KeyType::ED25519),
             "secp256k1" => Ok(KeyType::SECP256K1),
+            "secp256r1" => Ok(KeyType::SECP256R1),
             _ => Err(Self::Err::UnknownKeyType { unknown_key_type: lowercase_key_type }),
         }
     }
@@ -50,6 +53,7 @@ impl TryFrom<u8> for KeyType {
         match value {
             0 => Ok(KeyType::ED25519),
             1 => Ok(KeyType::SECP256K1),
+            2 => Ok(KeyType::SECP256R1),
             unknown_key_type => {
                 Err(Self::Error::UnknownKeyType { unknown_key_type: unknown_key_type.to_string() })
             }
@@ -88,6 +92,37 @@ impl std::fmt::Debug for Secp256K1PublicKey {
     }
 }
 
+/// Uncompressed P-256 public key, i.e. the `x` and `y` coordinates of the
+/// point without the SEC1 `0x04` tag.
+#[derive(Clone, Eq, Ord, PartialEq, PartialOrd, derive_more::AsRef, derive_more::From)]
+#[as_ref(forward)]
+pub struct Secp256R1PublicKey([u8; 64]);
+
+impl Secp256R1PublicKey {
+    fn to_verifying_key(&self) -> Option<p256::ecdsa::VerifyingKey> {
+        let mut sec1 = [4u8; 65];
+        sec1[1..].copy_from_slice(&self.0);
+        p256::ecdsa::VerifyingKey::from_sec1_bytes(&sec1).ok()
+    }
+}
+
+impl TryFrom<&[u8]> for Secp256R1PublicKey {
+    type Error = crate::errors::ParseKeyError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        data.try_into().map(Self).map_err(|_| Self::Error::InvalidLength {
+            expected_length: 64,
+            received_length: data.len(),
+        })
+    }
+}
+
+impl std::fmt::Debug for Secp256R1PublicKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        Display::fmt(&Bs58(&self.0), f)
+    }
+}
+
 #[derive(Clone, Eq, Ord, PartialEq, PartialOrd, derive_more::AsRef, derive_more::From)]
 #[as_ref(forward)]
 pub struct ED25519PublicKey(pub [u8; ed25519_dalek::PUBLIC_KEY_LENGTH]);
@@ -116,13 +151,15 @@ pub enum PublicKey {
     ED25519(ED25519PublicKey),
     /// 512 bit elliptic curve based public-key used in Bitcoin's public-key cryptography.
     SECP256K1(Secp256K1PublicKey),
+    /// 512 bit NIST P-256 public-key, as used by passkeys and secure enclaves.
+    SECP256R1(Secp256R1PublicKey),
 }
 
 impl PublicKey {
     pub fn len(&self) -> usize {
         match self {
             Self::ED25519(_) => ed25519_dalek::PUBLIC_KEY_LENGTH + 1,
-            Self::SECP256K1(_) => 65,
+            Self::SECP256K1(_) | Self::SECP256R1(_) => 65,
         }
     }
 
@@ -132,6 +169,7 @@ impl PublicKey {
                 PublicKey::ED25519(ED25519PublicKey([0u8; ed25519_dalek::PUBLIC_KEY_LENGTH]))
             }
             KeyType::SECP256K1 => PublicKey::SECP256K1(Secp256K1PublicKey([0u8; 64])),
+            KeyType::SECP256R1 => PublicKey::SECP256R1(Secp256R1PublicKey([0u8; 64])),
         }
     }
 
@@ -139,6 +177,7 @@ impl PublicKey {
         match self {
             Self::ED25519(_) => KeyType::ED25519,
             Self::SECP256K1(_) => KeyType::SECP256K1,
+            Self::SECP256R1(_) => KeyType::SECP256R1,
         }
     }
 
@@ -146,13 +185,14 @@ impl PublicKey {
         match self {
             Self::ED25519(key) => key.as_ref(),
             Self::SECP256K1(key) => key.as_ref(),
+            Self::SECP256R1(key) => key.as_ref(),
         }
     }
 
     pub fn unwrap_as_ed25519(&self) -> &ED25519PublicKey {
         match self {
             Self::ED25519(key) => key,
-            Self::SECP256K1(_) => panic!(),
+            Self::SECP256K1(_) | Self::SECP256R1(_) => panic!(),
         }
     }
 }
@@ -171,6 +211,10 @@ impl Hash for PublicKey {
                 state.write_u8(1u8);
                 state.write(&public_key.0);
             }
+            PublicKey::SECP256R1(public_key) => {
+                state.write_u8(2u8);
+                state.write(&public_key.0);
+            }
         }
     }
 }
@@ -180,6 +224,7 @@ impl Display for PublicKey {
         let (key_type, key_data) = match self {
             PublicKey::ED25519(public_key) => (KeyType::ED25519, &public_key.0[..]),
             PublicKey::SECP256K1(public_key) => (KeyType::SECP256K1, &public_key.0[..]),
+            PublicKey::SECP256R1(public_key) => (KeyType::SECP256R1, &public_key.0[..]),
         };
         write!(fmt, "{}:{}", key_type, Bs58(key_data))
     }
@@ -202,6 +247,10 @@ impl BorshSerialize for PublicKey {
                 BorshSerialize::serialize(&1u8, writer)?;
                 writer.write_all(&public_key.0)?;
             }
+            PublicKey::SECP256R1(public_key) => {
+                BorshSerialize::serialize(&2u8, writer)?;
+                writer.write_all(&public_key.0)?;
+            }
         }
         Ok(())
     }
@@ -218,6 +267,9 @@ impl BorshDeserialize for PublicKey {
             KeyType::SECP256K1 => {
                 Ok(PublicKey::SECP256K1(Secp256K1PublicKey(BorshDeserialize::deserialize(buf)?)))
             }
+            KeyType::SECP256R1 => {
+                Ok(PublicKey::SECP256R1(Secp256R1PublicKey(BorshDeserialize::deserialize(buf)?)))
+            }
         }
     }
 }
@@ -253,6 +305,7 @@ impl FromStr for PublicKey {
         Ok(match key_type {
             KeyType::ED25519 => Self::ED25519(ED25519PublicKey(decode_bs58(key_data)?)),
             KeyType::SECP256K1 => Self::SECP256K1(Secp256K1PublicKey(decode_bs58(key_data)?)),
+            KeyType::SECP256R1 => Self::SECP256R1(Secp256R1PublicKey(decode_bs58(key_data)?)),
         })
     }
 }
@@ -269,6 +322,12 @@ impl From<Secp256K1PublicKey> for PublicKey {
     }
 }
 
+impl From<Secp256R1PublicKey> for PublicKey {
+    fn from(secp256r1: Secp256R1PublicKey) -> Self {
+        Self::SECP256R1(secp256r1)
+    }
+}
+
 #[derive(Clone, Eq)]
 // This is actually a keypair, because ed25519_dalek api only has keypair.sign
 // From ed25519_dalek doc: The first SECRET_KEY_LENGTH of bytes is the SecretKey
@@ -292,6 +351,7 @@ impl std::fmt::Debug for ED25519SecretKey {
 pub enum SecretKey {
     ED25519(ED25519SecretKey),
     SECP256K1(secp256k1::SecretKey),
+    SECP256R1(p256::ecdsa::SigningKey),
 }
 
 impl SecretKey {
@@ -299,6 +359,7 @@ impl SecretKey {
         match self {
             SecretKey::ED25519(_) => KeyType::ED25519,
             SecretKey::SECP256K1(_) => KeyType::SECP256K1,
+            SecretKey::SECP256R1(_) => KeyType::SECP256R1,
         }
     }
 
@@ -311,6 +372,9 @@ impl SecretKey {
             KeyType::SECP256K1 => {
                 SecretKey::SECP256K1(secp256k1::SecretKey::new(&mut secp256k1::rand::rngs::OsRng))
             }
+            KeyType::SECP256R1 => SecretKey::SECP256R1(p256::ecdsa::SigningKey::random(
+                &mut secp256k1::rand::rngs::OsRng,
+            )),
         }
     }
 
@@ -332,6 +396,13 @@ impl SecretKey {
                 buf[64] = rec_id.to_i32() as u8;
                 Signature::SECP256K1(Secp256K1Signature(buf))
             }
+
+            SecretKey::SECP256R1(secret_key) => {
+                let signature: p256::ecdsa::Signature = secret_key.sign(data);
+                let mut buf = [0; SECP256R1_SIGNATURE_LENGTH];
+                buf.copy_from_slice(signature.as_ref());
+                Signature::SECP256R1(Secp256R1Signature(buf))
+            }
         }
     }
 
@@ -347,22 +418,33 @@ impl SecretKey {
                 public_key.0.copy_from_slice(&serialized[1..65]);
                 PublicKey::SECP256K1(public_key)
             }
+            SecretKey::SECP256R1(secret_key) => {
+                let point = secret_key.verifying_key().to_encoded_point(false);
+                let mut public_key = Secp256R1PublicKey([0; 64]);
+                public_key.0.copy_from_slice(&point.as_bytes()[1..65]);
+                PublicKey::SECP256R1(public_key)
+            }
         }
     }
 
     pub fn unwrap_as_ed25519(&self) -> &ED25519SecretKey {
         match self {
             SecretKey::ED25519(key) => key,
-            SecretKey::SECP256K1(_) => panic!(),
+            SecretKey::SECP256K1(_) | SecretKey::SECP256R1(_) => panic!(),
         }
     }
 }
 
 impl std::fmt::Display for SecretKey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let secp256r1_data;
         let (key_type, key_data) = match self {
             SecretKey::ED25519(secret_key) => (KeyType::ED25519, &secret_key.0[..]),
             SecretKey::SECP256K1(secret_key) => (KeyType::SECP256K1, &secret_key[..]),
+            SecretKey::SECP256R1(secret_key) => {
+                secp256r1_data = secret_key.to_bytes();
+                (KeyType::SECP256R1, &secp256r1_data[..])
+            }
         };
         write!(f, "{}:{}", key_type, Bs58(key_data))
     }
@@ -381,6 +463,12 @@ impl FromStr for SecretKey {
                     .map_err(|err| Self::Err::InvalidData { error_message: err.to_string() })?;
                 Self::SECP256K1(sk)
             }
+            KeyType::SECP256R1 => {
+                let data = decode_bs58::<32>(key_data)?;
+                let sk = p256::ecdsa::SigningKey::from_bytes(&data)
+                    .map_err(|err| Self::Err::InvalidData { error_message: err.to_string() })?;
+                Self::SECP256R1(sk)
+            }
         })
     }
 }
@@ -483,11 +571,35 @@ impl Debug for Secp256K1Signature {
     }
 }
 
+const SECP256R1_SIGNATURE_LENGTH: usize = 64;
+
+/// ECDSA signature over the NIST P-256 curve, i.e. the `r` and `s` scalars.
+#[derive(Clone, Eq, PartialEq, Hash, derive_more::From, derive_more::Into)]
+pub struct Secp256R1Signature([u8; SECP256R1_SIGNATURE_LENGTH]);
+
+impl TryFrom<&[u8]> for Secp256R1Signature {
+    type Error = crate::errors::ParseSignatureError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self(data.try_into().map_err(|_| Self::Error::InvalidLength {
+            expected_length: SECP256R1_SIGNATURE_LENGTH,
+            received_length: data.len(),
+        })?))
+    }
+}
+
+impl Debug for Secp256R1Signature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        Display::fmt(&Bs58(&self.0), f)
+    }
+}
+
 /// Signature container supporting different curves.
 #[derive(Clone, PartialEq, Eq)]
 pub enum Signature {
     ED25519(ed25519_dalek::Signature),
     SECP256K1(Secp256K1Signature),
+    SECP256R1(Secp256R1Signature),
 }
 
 impl Hash for Signature {
@@ -495,6 +607,7 @@ impl Hash for Signature {
         match self {
             Signature::ED25519(sig) => sig.to_bytes().hash(state),
             Signature::SECP256K1(sig) => sig.hash(state),
+            Signature::SECP256R1(sig) => sig.hash(state),
         };
     }
 }
@@ -520,6 +633,13 @@ impl Signature {
                     },
                 )?))
             }
+            KeyType::SECP256R1 => {
+                Ok(Signature::SECP256R1(Secp256R1Signature::try_from(signature_data).map_err(
+                    |_| crate::errors::ParseSignatureError::InvalidData {
+                        error_message: "invalid Secp256r1 signature length".to_string(),
+                    },
+                )?))
+            }
         }
     }
 
@@ -554,6 +674,13 @@ impl Signature {
                     )
                     .is_ok()
             }
+            (Signature::SECP256R1(signature), PublicKey::SECP256R1(public_key)) => {
+                let Some(public_key) = public_key.to_verifying_key() else { return false };
+                match p256::ecdsa::Signature::try_from(&signature.0[..]) {
+                    Err(_) => false,
+                    Ok(signature) => public_key.verify(data, &signature).is_ok(),
+                }
+            }
             _ => false,
         }
     }
@@ -562,6 +689,7 @@ impl Signature {
         match self {
             Signature::ED25519(_) => KeyType::ED25519,
             Signature::SECP256K1(_) => KeyType::SECP256K1,
+            Signature::SECP256R1(_) => KeyType::SECP256R1,
         }
     }
 }
@@ -583,6 +711,10 @@ impl BorshSerialize for Signature {
                 BorshSerialize::serialize(&1u8, writer)?;
                 writer.write_all(&signature.0)?;
             }
+            Signature::SECP256R1(signature) => {
+                BorshSerialize::serialize(&2u8, writer)?;
+                writer.write_all(&signature.0)?;
+            }
         }
         Ok(())
     }
@@ -605,6 +737,10 @@ impl BorshDeserialize for Signature {
                 let array: [u8; 65] = BorshDeserialize::deserialize(buf)?;
                 Ok(Signature::SECP256K1(Secp256K1Signature(array)))
             }
+            KeyType::SECP256R1 => {
+                let array: [u8; SECP256R1_SIGNATURE_LENGTH] = BorshDeserialize::deserialize(buf)?;
+                Ok(Signature::SECP256R1(Secp256R1Signature(array)))
+            }
         }
     }
 }
@@ -614,6 +750,7 @@ impl Display for Signature {
         let (key_type, key_data) = match self {
             Signature::ED25519(signature) => (KeyType::ED25519, signature.as_ref()),
             Signature::SECP256K1(signature) => (KeyType::SECP256K1, &signature.0[..]),
+            Signature::SECP256R1(signature) => (KeyType::SECP256R1, &signature.0[..]),
         };
         write!(f, "{}:{}", key_type, Bs58(key_data))
     }
@@ -650,6 +787,7 @@ impl FromStr for Signature {
                 Signature::ED25519(sig)
             }
             KeyType::SECP256K1 => Signature::SECP256K1(Secp256K1Signature(decode_bs58(sig_data)?)),
+            KeyType::SECP256R1 => Signature::SECP256R1(Secp256R1Signature(decode_bs58(sig_data)?)),
         })
     }
 }
@@ -745,7 +883,7 @@ mod tests {
 
     #[test]
     fn test_sign_verify() {
-        for key_type in vec![KeyType::ED25519, KeyType::SECP256K1] {
+        for key_type in vec![KeyType::ED25519, KeyType::SECP256K1, KeyType::SECP256R1] {
             let secret_key = SecretKey::from_random(key_type);
             let public_key = secret_key.public_key();
             use sha2::Digest;
@@ -808,11 +946,46 @@ mod tests {
         assert_eq!(signature, signature2);
     }
 
+    #[test]
+    fn test_json_serialize_secp256r1() {
+        let sk = SecretKey::from_seed(KeyType::SECP256R1, "test");
+        let pk = sk.public_key();
+        let pk_str = serde_json::to_string(&pk).unwrap();
+        assert!(pk_str.starts_with("\"secp256r1:"));
+        assert_eq!(pk, serde_json::from_str(&pk_str).unwrap());
+        let pk2: PublicKey = pk.to_string().parse().unwrap();
+        assert_eq!(pk, pk2);
+
+        let sk_str = serde_json::to_string(&sk).unwrap();
+        assert_eq!(sk, serde_json::from_str(&sk_str).unwrap());
+
+        // Signing is deterministic (RFC 6979).
+        let signature = sk.sign(b"123");
+        assert_eq!(signature, sk.sign(b"123"));
+        assert!(signature.verify(b"123", &pk));
+        assert!(!signature.verify(b"124", &pk));
+        let signature_str = serde_json::to_string(&signature).unwrap();
+        assert_eq!(signature, serde_json::from_str(&signature_str).unwrap());
+        let signature2: Signature = signature.to_string().parse().unwrap();
+        assert_eq!(signature, signature2);
+    }
+
+    #[test]
+    fn test_secp256r1_invalid_public_key() {
+        let sk = SecretKey::from_seed(KeyType::SECP256R1, "test");
+        let signature = sk.sign(b"123");
+        // A point that is not on the curve must not verify nor panic.
+        assert!(!signature.verify(b"123", &PublicKey::empty(KeyType::SECP256R1)));
+        // Signatures for other curves never verify against a P-256 key.
+        let signature = SecretKey::from_seed(KeyType::ED25519, "test").sign(b"123");
+        assert!(!signature.verify(b"123", &sk.public_key()));
+    }
+
     #[test]
     fn test_borsh_serialization() {
         use sha2::Digest;
         let data = sha2::Sha256::digest(b"123").to_vec();
-        for key_type in vec![KeyType::ED25519, KeyType::SECP256K1] {
+        for key_type in vec![KeyType::ED25519, KeyType::SECP256K1, KeyType::SECP256R1] {
             let sk = SecretKey::from_seed(key_type, "test");
             let pk = sk.public_key();
             let bytes = pk.try_to_vec().unwrap();
@@ -14,13 +14,20 @@ fn ed25519_key_pair_from_seed(seed: &str) -> ed25519_dalek::Keypair {
     ed25519_dalek::Keypair { secret, public }
 }
 
-fn secp256k1_secret_key_from_seed(seed: &str) -> secp256k1::SecretKey {
+fn rng_from_seed(seed: &str) -> secp256k1::rand::rngs::StdRng {
     let seed_bytes = seed.as_bytes();
     let len = std::cmp::min(32, seed_bytes.len());
     let mut seed: [u8; 32] = [b' '; 32];
     seed[..len].copy_from_slice(&seed_bytes[..len]);
-    let mut rng = secp256k1::rand::rngs::StdRng::from_seed(seed);
-    secp256k1::SecretKey::new(&mut rng)
+    secp256k1::rand::rngs::StdRng::from_seed(seed)
+}
+
+fn secp256k1_secret_key_from_seed(seed: &str) -> secp256k1::SecretKey {
+    secp256k1::SecretKey::new(&mut rng_from_seed(seed))
+}
+
+fn secp256r1_secret_key_from_seed(seed: &str) -> p256::ecdsa::SigningKey {
+    p256::ecdsa::SigningKey::random(&mut rng_from_seed(seed))
 }
 
 impl PublicKey {
@@ -42,6 +49,7 @@ impl SecretKey {
                 let keypair = ed25519_key_pair_from_seed(seed);
                 SecretKey::ED25519(ED25519SecretKey(keypair.to_bytes()))
             }
+            KeyType::SECP256R1 => SecretKey::SECP256R1(secp256r1_secret_key_from_seed(seed)),
             _ => SecretKey::SECP256K1(secp256k1_secret_key_from_seed(seed)),
         }
     }
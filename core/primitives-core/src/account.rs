@@ -6,7 +6,7 @@ pub use near_account_id as id;
 
 use crate::hash::CryptoHash;
 use crate::serialize::dec_format;
-use crate::types::{Balance, Nonce, StorageUsage};
+use crate::types::{Balance, BlockHeight, EpochHeight, Nonce, StorageUsage};
 #[derive(
     BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy,
 )]
@@ -175,6 +175,31 @@ pub enum AccessKeyPermission {
     /// Grants full access to the account.
     /// NOTE: It's used to replace account-level public keys.
     FullAccess,
+
+    /// Same as `FunctionCall`, but the key can additionally expire and be
+    /// limited in the number of transactions per epoch.
+    FunctionCallV2(FunctionCallPermissionV2),
+}
+
+impl AccessKeyPermission {
+    /// Returns the function call restrictions of the key, or `None` for full
+    /// access keys.
+    pub fn function_call_permission(&self) -> Option<&FunctionCallPermission> {
+        match self {
+            AccessKeyPermission::FunctionCall(permission) => Some(permission),
+            AccessKeyPermission::FunctionCallV2(permission) => Some(&permission.function_call),
+            AccessKeyPermission::FullAccess => None,
+        }
+    }
+
+    /// Mutable version of [`Self::function_call_permission`].
+    pub fn function_call_permission_mut(&mut self) -> Option<&mut FunctionCallPermission> {
+        match self {
+            AccessKeyPermission::FunctionCall(permission) => Some(permission),
+            AccessKeyPermission::FunctionCallV2(permission) => Some(&mut permission.function_call),
+            AccessKeyPermission::FullAccess => None,
+        }
+    }
 }
 
 /// Grants limited permission to make transactions with FunctionCallActions
@@ -206,6 +231,85 @@ pub struct FunctionCallPermission {
     pub method_names: Vec<String>,
 }
 
+/// Grants the same permission as [`FunctionCallPermission`] for a limited time
+/// and a limited number of transactions per epoch.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug,
+)]
+pub struct FunctionCallPermissionV2 {
+    /// Allowance, receiver and method names the key is restricted to.
+    pub function_call: FunctionCallPermission,
+
+    /// The access key can't be used anymore once the chain reaches the expiry.
+    /// `None` means the key never expires.
+    pub expiry: Option<AccessKeyExpiry>,
+
+    /// Limits the number of transactions signed with the key in one epoch.
+    /// `None` means the number of transactions is unlimited.
+    pub usage_limit: Option<AccessKeyUsageLimit>,
+}
+
+/// Point of the chain from which on an access key can't be used anymore.
+///
+/// Chunk producers check the expiry before the block including a transaction
+/// exists, so they don't know its exact height (blocks may be skipped) nor its
+/// timestamp. They check against the height of the next block and the
+/// timestamp of the previous one, and a transaction whose key has expired by
+/// the time the chunk is applied is dropped without invalidating the chunk.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug,
+)]
+pub enum AccessKeyExpiry {
+    /// The key expires at the block with the given height.
+    BlockHeight(BlockHeight),
+    /// The key expires at the first block whose timestamp, in nanoseconds
+    /// since the Unix epoch, is at or after the given one.
+    Timestamp(u64),
+}
+
+impl AccessKeyExpiry {
+    /// Whether a key with this expiry can't be used in a block with the given
+    /// height and timestamp.
+    pub fn is_expired(&self, block_height: BlockHeight, block_timestamp: u64) -> bool {
+        match *self {
+            AccessKeyExpiry::BlockHeight(height) => block_height >= height,
+            AccessKeyExpiry::Timestamp(timestamp) => block_timestamp >= timestamp,
+        }
+    }
+}
+
+/// Number of transactions an access key may sign per epoch, together with the
+/// count of transactions it signed so far. The counter is reset when the key
+/// is added, whatever `epoch_height` and `uses` the `AddKey` action specified.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug,
+)]
+pub struct AccessKeyUsageLimit {
+    /// Maximum number of transactions the key can sign in one epoch.
+    pub max_uses_per_epoch: u64,
+    /// Height of the epoch in which `uses` were counted.
+    pub epoch_height: EpochHeight,
+    /// Number of transactions signed with the key in the epoch `epoch_height`.
+    /// The counter starts over in every new epoch.
+    pub uses: u64,
+}
+
+impl AccessKeyUsageLimit {
+    /// Counts one more use of the key in the given epoch. Returns `false`
+    /// without changing the counter if the limit has been reached already.
+    pub fn try_use(&mut self, epoch_height: EpochHeight) -> bool {
+        if self.epoch_height != epoch_height {
+            self.epoch_height = epoch_height;
+            self.uses = 0;
+        }
+        if self.uses >= self.max_uses_per_epoch {
+            return false;
+        }
+        self.uses += 1;
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use borsh::BorshSerialize;
@@ -241,4 +345,29 @@ mod tests {
             <Account as BorshDeserialize>::deserialize(&mut new_bytes).unwrap();
         assert_eq!(deserialized_account, new_account);
     }
+
+    #[test]
+    fn test_access_key_expiry() {
+        let by_height = AccessKeyExpiry::BlockHeight(100);
+        assert!(!by_height.is_expired(99, u64::MAX));
+        assert!(by_height.is_expired(100, 0));
+        assert!(by_height.is_expired(101, 0));
+
+        let by_timestamp = AccessKeyExpiry::Timestamp(1_000);
+        assert!(!by_timestamp.is_expired(u64::MAX, 999));
+        assert!(by_timestamp.is_expired(0, 1_000));
+        assert!(by_timestamp.is_expired(0, 1_001));
+    }
+
+    #[test]
+    fn test_access_key_usage_limit() {
+        let mut limit = AccessKeyUsageLimit { max_uses_per_epoch: 2, epoch_height: 0, uses: 0 };
+        assert!(limit.try_use(5));
+        assert!(limit.try_use(5));
+        assert!(!limit.try_use(5));
+        assert_eq!(limit, AccessKeyUsageLimit { max_uses_per_epoch: 2, epoch_height: 5, uses: 2 });
+        // The counter starts over in a new epoch.
+        assert!(limit.try_use(6));
+        assert_eq!(limit, AccessKeyUsageLimit { max_uses_per_epoch: 2, epoch_height: 6, uses: 1 });
+    }
 }
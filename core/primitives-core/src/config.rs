@@ -1,7 +1,8 @@
-use crate::types::Gas;
+use crate::types::{Compute, Gas};
 
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
 use std::hash::{Hash, Hasher};
 use strum::{Display, EnumCount};
 
@@ -316,6 +317,13 @@ pub struct ExtCostsConfig {
     #[cfg(feature = "protocol_feature_ed25519_verify")]
     pub ed25519_verify_byte: Gas,
 
+    /// Cost of verifying a P-256 signature
+    #[cfg(feature = "protocol_feature_secp256r1")]
+    pub p256_verify_base: Gas,
+    /// Cost of verifying a P-256 signature per byte of the message
+    #[cfg(feature = "protocol_feature_secp256r1")]
+    pub p256_verify_byte: Gas,
+
     /// Cost of calling ecrecover
     pub ecrecover_base: Gas,
 
@@ -422,6 +430,71 @@ pub struct ExtCostsConfig {
     pub alt_bn128_pairing_check_base: Gas,
     /// Per element cost for pairing check
     pub alt_bn128_pairing_check_element: Gas,
+
+    // #############
+    // # BLS12-381 #
+    // #############
+    /// Base cost for sum of G1 points (without subgroup check)
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_p1_sum_base: Gas,
+    /// Per element cost for sum of G1 points (without subgroup check)
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_p1_sum_element: Gas,
+    /// Base cost for sum of G2 points (without subgroup check)
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_p2_sum_base: Gas,
+    /// Per element cost for sum of G2 points (without subgroup check)
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_p2_sum_element: Gas,
+    /// Base cost for G1 multiexp
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_g1_multiexp_base: Gas,
+    /// Per element cost for G1 multiexp
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_g1_multiexp_element: Gas,
+    /// Base cost for G2 multiexp
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_g2_multiexp_base: Gas,
+    /// Per element cost for G2 multiexp
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_g2_multiexp_element: Gas,
+    /// Base cost for mapping an Fp element to G1
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_map_fp_to_g1_base: Gas,
+    /// Per element cost for mapping an Fp element to G1
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_map_fp_to_g1_element: Gas,
+    /// Base cost for mapping an Fp2 element to G2
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_map_fp2_to_g2_base: Gas,
+    /// Per element cost for mapping an Fp2 element to G2
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_map_fp2_to_g2_element: Gas,
+    /// Base cost for pairing check
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_pairing_base: Gas,
+    /// Per element cost for pairing check
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_pairing_element: Gas,
+    /// Base cost for G1 point decompression
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_p1_decompress_base: Gas,
+    /// Per element cost for G1 point decompression
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_p1_decompress_element: Gas,
+    /// Base cost for G2 point decompression
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_p2_decompress_base: Gas,
+    /// Per element cost for G2 point decompression
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub bls12381_p2_decompress_element: Gas,
+
+    /// Compute usage of the costs for which it differs from the gas cost.
+    /// Compute usage limits the execution of a chunk while gas is charged to
+    /// users, so it allows to reprice the time of a host function without
+    /// breaking existing contracts.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub compute_costs: BTreeMap<ExtCosts, Compute>,
 }
 
 fn default_read_cached_trie_node() -> Gas {
@@ -463,6 +536,11 @@ impl ExtCostsConfig {
             ed25519_verify_base: SAFETY_MULTIPLIER * 1513656750,
             #[cfg(feature = "protocol_feature_ed25519_verify")]
             ed25519_verify_byte: SAFETY_MULTIPLIER * 7157035,
+            // Same as `wasm_p256_verify_base` and `wasm_p256_verify_byte` in parameters.txt.
+            #[cfg(feature = "protocol_feature_secp256r1")]
+            p256_verify_base: SAFETY_MULTIPLIER * 150000000000,
+            #[cfg(feature = "protocol_feature_secp256r1")]
+            p256_verify_byte: SAFETY_MULTIPLIER * 3000000,
             // Cost per byte is 3542227. There are 64 bytes in a block.
             ripemd160_block: SAFETY_MULTIPLIER * 226702528,
             ecrecover_base: SAFETY_MULTIPLIER * 1121789875000,
@@ -503,6 +581,43 @@ impl ExtCostsConfig {
             alt_bn128_pairing_check_element: 5_102_000_000_000,
             alt_bn128_g1_sum_base: 3_000_000_000,
             alt_bn128_g1_sum_element: 5_000_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_sum_base: 16_500_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_sum_element: 6_000_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_sum_base: 18_600_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_sum_element: 15_000_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g1_multiexp_base: 16_500_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g1_multiexp_element: 930_000_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g2_multiexp_base: 18_600_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g2_multiexp_element: 1_995_000_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp_to_g1_base: 1_500_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp_to_g1_element: 252_000_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp2_to_g2_base: 1_500_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp2_to_g2_element: 900_000_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_pairing_base: 2_130_000_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_pairing_element: 2_130_000_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_decompress_base: 15_000_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_decompress_element: 81_000_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_decompress_base: 15_000_000_000,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_decompress_element: 165_000_000_000,
+            compute_costs: BTreeMap::new(),
         }
     }
 
@@ -535,6 +650,10 @@ impl ExtCostsConfig {
             ed25519_verify_base: 0,
             #[cfg(feature = "protocol_feature_ed25519_verify")]
             ed25519_verify_byte: 0,
+            #[cfg(feature = "protocol_feature_secp256r1")]
+            p256_verify_base: 0,
+            #[cfg(feature = "protocol_feature_secp256r1")]
+            p256_verify_byte: 0,
             ecrecover_base: 0,
             log_base: 0,
             log_byte: 0,
@@ -573,13 +692,62 @@ impl ExtCostsConfig {
             alt_bn128_pairing_check_element: 0,
             alt_bn128_g1_sum_base: 0,
             alt_bn128_g1_sum_element: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_sum_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_sum_element: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_sum_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_sum_element: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g1_multiexp_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g1_multiexp_element: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g2_multiexp_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g2_multiexp_element: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp_to_g1_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp_to_g1_element: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp2_to_g2_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp2_to_g2_element: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_pairing_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_pairing_element: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_decompress_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_decompress_element: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_decompress_base: 0,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_decompress_element: 0,
+            compute_costs: BTreeMap::new(),
         }
     }
 }
 
 /// Strongly-typed representation of the fees for counting.
 #[derive(
-    Copy, Clone, Hash, PartialEq, Eq, Debug, PartialOrd, Ord, EnumCount, Display, strum::EnumIter,
+    Copy,
+    Clone,
+    Hash,
+    PartialEq,
+    Eq,
+    Debug,
+    PartialOrd,
+    Ord,
+    EnumCount,
+    Display,
+    strum::EnumIter,
+    Serialize,
+    Deserialize,
 )]
 #[allow(non_camel_case_types)]
 pub enum ExtCosts {
@@ -610,6 +778,10 @@ pub enum ExtCosts {
     ed25519_verify_base,
     #[cfg(feature = "protocol_feature_ed25519_verify")]
     ed25519_verify_byte,
+    #[cfg(feature = "protocol_feature_secp256r1")]
+    p256_verify_base,
+    #[cfg(feature = "protocol_feature_secp256r1")]
+    p256_verify_byte,
     ecrecover_base,
     log_base,
     log_byte,
@@ -646,6 +818,42 @@ pub enum ExtCosts {
     alt_bn128_pairing_check_element,
     alt_bn128_g1_sum_base,
     alt_bn128_g1_sum_element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_p1_sum_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_p1_sum_element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_p2_sum_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_p2_sum_element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_g1_multiexp_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_g1_multiexp_element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_g2_multiexp_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_g2_multiexp_element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_map_fp_to_g1_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_map_fp_to_g1_element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_map_fp2_to_g2_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_map_fp2_to_g2_element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_pairing_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_pairing_element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_p1_decompress_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_p1_decompress_element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_p2_decompress_base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    bls12381_p2_decompress_element,
 }
 
 // Type of an action, used in fees logic.
@@ -702,6 +910,10 @@ impl ExtCosts {
             ed25519_verify_base => config.ed25519_verify_base,
             #[cfg(feature = "protocol_feature_ed25519_verify")]
             ed25519_verify_byte => config.ed25519_verify_byte,
+            #[cfg(feature = "protocol_feature_secp256r1")]
+            p256_verify_base => config.p256_verify_base,
+            #[cfg(feature = "protocol_feature_secp256r1")]
+            p256_verify_byte => config.p256_verify_byte,
             ecrecover_base => config.ecrecover_base,
             log_base => config.log_base,
             log_byte => config.log_byte,
@@ -738,6 +950,48 @@ impl ExtCosts {
             alt_bn128_pairing_check_element => config.alt_bn128_pairing_check_element,
             alt_bn128_g1_sum_base => config.alt_bn128_g1_sum_base,
             alt_bn128_g1_sum_element => config.alt_bn128_g1_sum_element,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_sum_base => config.bls12381_p1_sum_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_sum_element => config.bls12381_p1_sum_element,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_sum_base => config.bls12381_p2_sum_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_sum_element => config.bls12381_p2_sum_element,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g1_multiexp_base => config.bls12381_g1_multiexp_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g1_multiexp_element => config.bls12381_g1_multiexp_element,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g2_multiexp_base => config.bls12381_g2_multiexp_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_g2_multiexp_element => config.bls12381_g2_multiexp_element,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp_to_g1_base => config.bls12381_map_fp_to_g1_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp_to_g1_element => config.bls12381_map_fp_to_g1_element,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp2_to_g2_base => config.bls12381_map_fp2_to_g2_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_map_fp2_to_g2_element => config.bls12381_map_fp2_to_g2_element,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_pairing_base => config.bls12381_pairing_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_pairing_element => config.bls12381_pairing_element,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_decompress_base => config.bls12381_p1_decompress_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p1_decompress_element => config.bls12381_p1_decompress_element,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_decompress_base => config.bls12381_p2_decompress_base,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            bls12381_p2_decompress_element => config.bls12381_p2_decompress_element,
         }
     }
+
+    /// Compute usage of the cost, which is its gas cost unless configured
+    /// otherwise.
+    pub fn compute(self, config: &ExtCostsConfig) -> Compute {
+        config.compute_costs.get(&self).copied().unwrap_or_else(|| self.value(config))
+    }
 }
@@ -30,6 +30,10 @@ pub enum Parameter {
     StorageNumBytesAccount,
     StorageNumExtraBytesRecord,
 
+    // Congestion control config
+    MaxCongestionDelayedReceiptsGas,
+    MaxCongestionDelayedReceiptsBytes,
+
     // Static action costs
     // send_sir / send_not_sir is burned when creating a receipt on the signer shard.
     // (SIR = signer is receiver, which guarantees the receipt is local.)
@@ -79,6 +83,9 @@ pub enum Parameter {
     ActionDeleteKeySendSir,
     ActionDeleteKeySendNotSir,
     ActionDeleteKeyExecution,
+    ActionDelegateSendSir,
+    ActionDelegateSendNotSir,
+    ActionDelegateExecution,
 
     // Smart contract dynamic gas costs
     WasmRegularOpCost,
@@ -110,6 +117,8 @@ pub enum Parameter {
     WasmEcrecoverBase,
     WasmEd25519VerifyBase,
     WasmEd25519VerifyByte,
+    WasmP256VerifyBase,
+    WasmP256VerifyByte,
     WasmLogBase,
     WasmLogByte,
     WasmStorageWriteBase,
@@ -145,6 +154,24 @@ pub enum Parameter {
     WasmAltBn128PairingCheckElement,
     WasmAltBn128G1SumBase,
     WasmAltBn128G1SumElement,
+    WasmBls12381P1SumBase,
+    WasmBls12381P1SumElement,
+    WasmBls12381P2SumBase,
+    WasmBls12381P2SumElement,
+    WasmBls12381G1MultiexpBase,
+    WasmBls12381G1MultiexpElement,
+    WasmBls12381G2MultiexpBase,
+    WasmBls12381G2MultiexpElement,
+    WasmBls12381MapFpToG1Base,
+    WasmBls12381MapFpToG1Element,
+    WasmBls12381MapFp2ToG2Base,
+    WasmBls12381MapFp2ToG2Element,
+    WasmBls12381PairingBase,
+    WasmBls12381PairingElement,
+    WasmBls12381P1DecompressBase,
+    WasmBls12381P1DecompressElement,
+    WasmBls12381P2DecompressBase,
+    WasmBls12381P2DecompressElement,
 
     // Smart contract limits
     MaxGasBurnt,
@@ -183,12 +210,17 @@ pub enum Parameter {
     Eq,
     PartialOrd,
     Ord,
+    Hash,
     strum::IntoStaticStr,
     strum::EnumString,
     Debug,
     strum::Display,
+    strum::EnumIter,
+    serde::Serialize,
+    serde::Deserialize,
 )]
 #[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
 pub enum FeeParameter {
     ActionReceiptCreation,
     DataReceiptCreationBase,
@@ -205,6 +237,7 @@ pub enum FeeParameter {
     ActionAddFunctionCallKey,
     ActionAddFunctionCallKeyPerByte,
     ActionDeleteKey,
+    ActionDelegate,
 }
 
 impl Parameter {
@@ -240,6 +273,8 @@ impl Parameter {
             Parameter::WasmEcrecoverBase,
             Parameter::WasmEd25519VerifyBase,
             Parameter::WasmEd25519VerifyByte,
+            Parameter::WasmP256VerifyBase,
+            Parameter::WasmP256VerifyByte,
             Parameter::WasmLogBase,
             Parameter::WasmLogByte,
             Parameter::WasmStorageWriteBase,
@@ -275,6 +310,24 @@ impl Parameter {
             Parameter::WasmAltBn128PairingCheckElement,
             Parameter::WasmAltBn128G1SumBase,
             Parameter::WasmAltBn128G1SumElement,
+            Parameter::WasmBls12381P1SumBase,
+            Parameter::WasmBls12381P1SumElement,
+            Parameter::WasmBls12381P2SumBase,
+            Parameter::WasmBls12381P2SumElement,
+            Parameter::WasmBls12381G1MultiexpBase,
+            Parameter::WasmBls12381G1MultiexpElement,
+            Parameter::WasmBls12381G2MultiexpBase,
+            Parameter::WasmBls12381G2MultiexpElement,
+            Parameter::WasmBls12381MapFpToG1Base,
+            Parameter::WasmBls12381MapFpToG1Element,
+            Parameter::WasmBls12381MapFp2ToG2Base,
+            Parameter::WasmBls12381MapFp2ToG2Element,
+            Parameter::WasmBls12381PairingBase,
+            Parameter::WasmBls12381PairingElement,
+            Parameter::WasmBls12381P1DecompressBase,
+            Parameter::WasmBls12381P1DecompressElement,
+            Parameter::WasmBls12381P2DecompressBase,
+            Parameter::WasmBls12381P2DecompressElement,
         ]
         .iter()
     }
@@ -1,4 +1,5 @@
 use crate::config::{ActionCosts, ExtCosts};
+use crate::types::{Compute, Gas};
 use borsh::{BorshDeserialize, BorshSerialize};
 use std::fmt;
 use std::ops::{Index, IndexMut};
@@ -11,8 +12,22 @@ use strum::IntoEnumIterator;
 #[derive(Clone, PartialEq, Eq)]
 pub struct DataArray(Box<[u64; Self::LEN]>);
 
+impl Default for DataArray {
+    fn default() -> Self {
+        Self(Box::new([0; Self::LEN]))
+    }
+}
+
 impl DataArray {
-    pub const LEN: usize = if cfg!(feature = "protocol_feature_ed25519_verify") { 72 } else { 70 };
+    pub const LEN: usize = if cfg!(feature = "protocol_feature_bls12381") {
+        92
+    } else if cfg!(feature = "protocol_feature_secp256r1") {
+        74
+    } else if cfg!(feature = "protocol_feature_ed25519_verify") {
+        72
+    } else {
+        70
+    };
 }
 
 impl Index<usize> for DataArray {
@@ -51,6 +66,10 @@ impl BorshSerialize for DataArray {
 #[derive(Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 pub struct ProfileData {
     data: DataArray,
+    /// Compute usage of each cost, indexed like `data`. Unlike gas it is not
+    /// stored in the database, so profiles of stored outcomes don't have it.
+    #[borsh_skip]
+    compute: DataArray,
 }
 
 impl Default for ProfileData {
@@ -62,27 +81,32 @@ impl Default for ProfileData {
 impl ProfileData {
     #[inline]
     pub fn new() -> Self {
-        let costs = DataArray(Box::new([0; DataArray::LEN]));
-        ProfileData { data: costs }
+        ProfileData { data: DataArray::default(), compute: DataArray::default() }
     }
 
     #[inline]
     pub fn merge(&mut self, other: &ProfileData) {
         for i in 0..DataArray::LEN {
             self.data[i] = self.data[i].saturating_add(other.data[i]);
+            self.compute[i] = self.compute[i].saturating_add(other.compute[i]);
         }
     }
 
     #[inline]
-    pub fn add_action_cost(&mut self, action: ActionCosts, value: u64) {
-        self[Cost::ActionCost { action_cost_kind: action }] =
-            self[Cost::ActionCost { action_cost_kind: action }].saturating_add(value);
+    pub fn add_action_cost(&mut self, action: ActionCosts, gas: Gas, compute: Compute) {
+        self.add_cost(Cost::ActionCost { action_cost_kind: action }, gas, compute)
+    }
+
+    #[inline]
+    pub fn add_ext_cost(&mut self, ext: ExtCosts, gas: Gas, compute: Compute) {
+        self.add_cost(Cost::ExtCost { ext_cost_kind: ext }, gas, compute)
     }
 
     #[inline]
-    pub fn add_ext_cost(&mut self, ext: ExtCosts, value: u64) {
-        self[Cost::ExtCost { ext_cost_kind: ext }] =
-            self[Cost::ExtCost { ext_cost_kind: ext }].saturating_add(value);
+    fn add_cost(&mut self, cost: Cost, gas: Gas, compute: Compute) {
+        let index = cost.profile_index();
+        self.data[index] = self.data[index].saturating_add(gas);
+        self.compute[index] = self.compute[index].saturating_add(compute);
     }
 
     /// WasmInstruction is the only cost we don't explicitly account for.
@@ -98,6 +122,17 @@ impl ProfileData {
             total_gas_burnt.saturating_sub(self.action_gas()).saturating_sub(self.host_gas());
     }
 
+    /// Total compute usage of the profiled execution. WASM instructions use
+    /// their gas as compute, actions and host functions the compute usage
+    /// charged for them.
+    ///
+    /// Must be called after `compute_wasm_instruction_cost`.
+    pub fn total_compute_usage(&self) -> Compute {
+        (0..DataArray::LEN)
+            .map(|i| self.compute[i])
+            .fold(self[Cost::WasmInstruction], u64::saturating_add)
+    }
+
     fn get_action_cost(&self, action: ActionCosts) -> u64 {
         self[Cost::ActionCost { action_cost_kind: action }]
     }
@@ -106,6 +141,16 @@ impl ProfileData {
         self[Cost::ExtCost { ext_cost_kind: ext }]
     }
 
+    /// Compute usage of the profile entry of the action cost. Like gas, it is
+    /// shared with the other action costs mapped to the same entry.
+    pub fn get_action_cost_compute(&self, action: ActionCosts) -> Compute {
+        self.compute[Cost::ActionCost { action_cost_kind: action }.profile_index()]
+    }
+
+    pub fn get_ext_cost_compute(&self, ext: ExtCosts) -> Compute {
+        self.compute[Cost::ExtCost { ext_cost_kind: ext }.profile_index()]
+    }
+
     fn host_gas(&self) -> u64 {
         ExtCosts::iter().map(|a| self.get_ext_cost(a)).fold(0, u64::saturating_add)
     }
@@ -260,6 +305,46 @@ impl Cost {
             Cost::ExtCost { ext_cost_kind: ExtCosts::ed25519_verify_base } => 70,
             #[cfg(feature = "protocol_feature_ed25519_verify")]
             Cost::ExtCost { ext_cost_kind: ExtCosts::ed25519_verify_byte } => 71,
+            #[cfg(feature = "protocol_feature_secp256r1")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::p256_verify_base } => 72,
+            #[cfg(feature = "protocol_feature_secp256r1")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::p256_verify_byte } => 73,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_p1_sum_base } => 74,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_p1_sum_element } => 75,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_p2_sum_base } => 76,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_p2_sum_element } => 77,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_g1_multiexp_base } => 78,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_g1_multiexp_element } => 79,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_g2_multiexp_base } => 80,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_g2_multiexp_element } => 81,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_map_fp_to_g1_base } => 82,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_map_fp_to_g1_element } => 83,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_map_fp2_to_g2_base } => 84,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_map_fp2_to_g2_element } => 85,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_pairing_base } => 86,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_pairing_element } => 87,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_p1_decompress_base } => 88,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_p1_decompress_element } => 89,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_p2_decompress_base } => 90,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::bls12381_p2_decompress_element } => 91,
         }
     }
 }
@@ -297,26 +382,45 @@ mod test {
     #[test]
     fn test_no_panic_on_overflow() {
         let mut profile_data = ProfileData::new();
-        profile_data.add_action_cost(ActionCosts::add_full_access_key, u64::MAX);
-        profile_data.add_action_cost(ActionCosts::add_full_access_key, u64::MAX);
+        profile_data.add_action_cost(ActionCosts::add_full_access_key, u64::MAX, u64::MAX);
+        profile_data.add_action_cost(ActionCosts::add_full_access_key, u64::MAX, u64::MAX);
 
         let res = profile_data.get_action_cost(ActionCosts::add_full_access_key);
         assert_eq!(res, u64::MAX);
+        let res = profile_data.get_action_cost_compute(ActionCosts::add_full_access_key);
+        assert_eq!(res, u64::MAX);
     }
 
     #[test]
     fn test_merge() {
         let mut profile_data = ProfileData::new();
-        profile_data.add_action_cost(ActionCosts::add_full_access_key, 111);
-        profile_data.add_ext_cost(ExtCosts::storage_read_base, 11);
+        profile_data.add_action_cost(ActionCosts::add_full_access_key, 111, 111);
+        profile_data.add_ext_cost(ExtCosts::storage_read_base, 11, 110);
 
         let mut profile_data2 = ProfileData::new();
-        profile_data2.add_action_cost(ActionCosts::add_full_access_key, 222);
-        profile_data2.add_ext_cost(ExtCosts::storage_read_base, 22);
+        profile_data2.add_action_cost(ActionCosts::add_full_access_key, 222, 222);
+        profile_data2.add_ext_cost(ExtCosts::storage_read_base, 22, 220);
 
         profile_data.merge(&profile_data2);
         assert_eq!(profile_data.get_action_cost(ActionCosts::add_full_access_key), 333);
+        assert_eq!(profile_data.get_action_cost_compute(ActionCosts::add_full_access_key), 333);
         assert_eq!(profile_data.get_ext_cost(ExtCosts::storage_read_base), 33);
+        assert_eq!(profile_data.get_ext_cost_compute(ExtCosts::storage_read_base), 330);
+    }
+
+    #[test]
+    fn test_total_compute_usage() {
+        let mut profile_data = ProfileData::new();
+        profile_data.add_action_cost(ActionCosts::add_full_access_key, 100, 50);
+        profile_data.add_ext_cost(ExtCosts::storage_read_base, 10, 40);
+
+        let mut profile_data2 = ProfileData::new();
+        profile_data2.add_ext_cost(ExtCosts::storage_read_base, 20, 20);
+
+        profile_data.merge(&profile_data2);
+        profile_data.compute_wasm_instruction_cost(1000);
+        // 870 for WASM instructions, 50 for the action and 60 for the host function.
+        assert_eq!(profile_data.total_compute_usage(), 980);
     }
 
     #[test]
@@ -324,6 +428,8 @@ mod test {
         let mut indices: Vec<_> = Cost::iter().map(|i| i.profile_index()).collect();
         indices.sort();
         indices.dedup();
-        assert_eq!(indices.len(), DataArray::LEN);
+        // Indices of costs behind a disabled feature are left unused, so only
+        // the largest index has to fit.
+        assert_eq!(indices.last().unwrap() + 1, DataArray::LEN);
     }
 }
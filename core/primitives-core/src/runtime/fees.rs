@@ -4,8 +4,11 @@
 //!   to not be cross-shard which is cheaper than cross-shard. Conversely, when sender is not a
 //!   receiver it might or might not be a cross-shard communication.
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 
 use crate::num_rational::Rational;
+use crate::parameter::FeeParameter;
 use crate::types::Gas;
 
 /// Costs associated with an object that can only be sent over the network (and executed
@@ -63,6 +66,12 @@ pub struct RuntimeFeesConfig {
 
     /// Pessimistic gas price inflation ratio.
     pub pessimistic_gas_price_inflation_ratio: Rational,
+
+    /// Compute usage of the fees for which it differs from the gas cost, see
+    /// `ExtCostsConfig::compute_costs`. Each component of the fee holds its
+    /// compute usage instead of gas.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub compute_costs: BTreeMap<FeeParameter, Fee>,
 }
 
 /// Describes the cost of creating a data receipt, `DataReceipt`.
@@ -113,6 +122,15 @@ pub struct ActionCreationConfig {
 
     /// Base cost of deleting an account.
     pub delete_account_cost: Fee,
+
+    /// Base cost of a delegate action, excluding the costs of the inner
+    /// actions.
+    #[serde(default = "default_delegate_cost")]
+    pub delegate_cost: Fee,
+}
+
+fn default_delegate_cost() -> Fee {
+    Fee { send_sir: 200_000_000_000, send_not_sir: 200_000_000_000, execution: 200_000_000_000 }
 }
 
 /// Describes the cost of creating an access key.
@@ -219,6 +237,11 @@ impl RuntimeFeesConfig {
                     send_not_sir: 147489000000,
                     execution: 147489000000,
                 },
+                delegate_cost: Fee {
+                    send_sir: 200000000000,
+                    send_not_sir: 200000000000,
+                    execution: 200000000000,
+                },
             },
             storage_usage_config: StorageUsageConfig {
                 // See Account in core/primitives/src/account.rs for the data structure.
@@ -228,6 +251,7 @@ impl RuntimeFeesConfig {
             },
             burnt_gas_reward: Rational::new(3, 10),
             pessimistic_gas_price_inflation_ratio: Rational::new(103, 100),
+            compute_costs: BTreeMap::new(),
         }
     }
 
@@ -253,7 +277,8 @@ impl RuntimeFeesConfig {
                     function_call_cost_per_byte: free.clone(),
                 },
                 delete_key_cost: free.clone(),
-                delete_account_cost: free,
+                delete_account_cost: free.clone(),
+                delegate_cost: free,
             },
             storage_usage_config: StorageUsageConfig {
                 num_bytes_account: 0,
@@ -261,6 +286,7 @@ impl RuntimeFeesConfig {
             },
             burnt_gas_reward: Rational::from_integer(0),
             pessimistic_gas_price_inflation_ratio: Rational::from_integer(0),
+            compute_costs: BTreeMap::new(),
         }
     }
 
@@ -272,6 +298,76 @@ impl RuntimeFeesConfig {
         self.action_receipt_creation_config.min_send_and_exec_fee()
             + self.action_creation_config.function_call_cost.min_send_and_exec_fee()
     }
+
+    /// The fees with their compute usage in place of the gas cost.
+    ///
+    /// Compute usage of actions is computed by the same functions as their gas
+    /// cost, applied to this config.
+    pub fn compute_fees(&self) -> Cow<'_, RuntimeFeesConfig> {
+        if self.compute_costs.is_empty() {
+            return Cow::Borrowed(self);
+        }
+        let mut res = self.clone();
+        for (&param, compute) in &self.compute_costs {
+            *res.fee_mut(param) = compute.clone();
+        }
+        res.compute_costs.clear();
+        Cow::Owned(res)
+    }
+
+    pub fn fee(&self, param: FeeParameter) -> &Fee {
+        let cfg = &self.action_creation_config;
+        match param {
+            FeeParameter::ActionReceiptCreation => &self.action_receipt_creation_config,
+            FeeParameter::DataReceiptCreationBase => &self.data_receipt_creation_config.base_cost,
+            FeeParameter::DataReceiptCreationPerByte => {
+                &self.data_receipt_creation_config.cost_per_byte
+            }
+            FeeParameter::ActionCreateAccount => &cfg.create_account_cost,
+            FeeParameter::ActionDeleteAccount => &cfg.delete_account_cost,
+            FeeParameter::ActionDeployContract => &cfg.deploy_contract_cost,
+            FeeParameter::ActionDeployContractPerByte => &cfg.deploy_contract_cost_per_byte,
+            FeeParameter::ActionFunctionCall => &cfg.function_call_cost,
+            FeeParameter::ActionFunctionCallPerByte => &cfg.function_call_cost_per_byte,
+            FeeParameter::ActionTransfer => &cfg.transfer_cost,
+            FeeParameter::ActionStake => &cfg.stake_cost,
+            FeeParameter::ActionAddFullAccessKey => &cfg.add_key_cost.full_access_cost,
+            FeeParameter::ActionAddFunctionCallKey => &cfg.add_key_cost.function_call_cost,
+            FeeParameter::ActionAddFunctionCallKeyPerByte => {
+                &cfg.add_key_cost.function_call_cost_per_byte
+            }
+            FeeParameter::ActionDeleteKey => &cfg.delete_key_cost,
+            FeeParameter::ActionDelegate => &cfg.delegate_cost,
+        }
+    }
+
+    fn fee_mut(&mut self, param: FeeParameter) -> &mut Fee {
+        let cfg = &mut self.action_creation_config;
+        match param {
+            FeeParameter::ActionReceiptCreation => &mut self.action_receipt_creation_config,
+            FeeParameter::DataReceiptCreationBase => {
+                &mut self.data_receipt_creation_config.base_cost
+            }
+            FeeParameter::DataReceiptCreationPerByte => {
+                &mut self.data_receipt_creation_config.cost_per_byte
+            }
+            FeeParameter::ActionCreateAccount => &mut cfg.create_account_cost,
+            FeeParameter::ActionDeleteAccount => &mut cfg.delete_account_cost,
+            FeeParameter::ActionDeployContract => &mut cfg.deploy_contract_cost,
+            FeeParameter::ActionDeployContractPerByte => &mut cfg.deploy_contract_cost_per_byte,
+            FeeParameter::ActionFunctionCall => &mut cfg.function_call_cost,
+            FeeParameter::ActionFunctionCallPerByte => &mut cfg.function_call_cost_per_byte,
+            FeeParameter::ActionTransfer => &mut cfg.transfer_cost,
+            FeeParameter::ActionStake => &mut cfg.stake_cost,
+            FeeParameter::ActionAddFullAccessKey => &mut cfg.add_key_cost.full_access_cost,
+            FeeParameter::ActionAddFunctionCallKey => &mut cfg.add_key_cost.function_call_cost,
+            FeeParameter::ActionAddFunctionCallKeyPerByte => {
+                &mut cfg.add_key_cost.function_call_cost_per_byte
+            }
+            FeeParameter::ActionDeleteKey => &mut cfg.delete_key_cost,
+            FeeParameter::ActionDelegate => &mut cfg.delegate_cost,
+        }
+    }
 }
 
 /// Helper functions for computing Transfer fees.
@@ -24,6 +24,9 @@ pub type ShardId = u64;
 pub type Balance = u128;
 /// Gas is a type for storing amount of gas.
 pub type Gas = u64;
+/// Compute is a type for storing the amount of compute time used to execute
+/// something, measured in the same units as gas.
+pub type Compute = u64;
 
 /// Weight of unused gas to distribute to scheduled function call actions.
 /// Used in `promise_batch_action_function_call_weight` host function.
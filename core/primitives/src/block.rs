@@ -14,6 +14,7 @@ use crate::block::BlockValidityError::{
 };
 pub use crate::block_header::*;
 use crate::challenge::{Challenges, ChallengesResult};
+use crate::congestion_info::CongestionInfo;
 use crate::hash::{hash, CryptoHash};
 use crate::merkle::{merklize, verify_path, MerklePath};
 use crate::num_rational::Rational32;
@@ -102,6 +103,7 @@ pub fn genesis_chunks(
                 vec![],
                 &[],
                 CryptoHash::default(),
+                CongestionInfo::default(),
                 &EmptyValidatorSigner::default(),
                 genesis_protocol_version,
             )
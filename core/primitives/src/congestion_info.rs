@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::errors::IntegerOverflowError;
+use crate::runtime::config::CongestionControlConfig;
+use crate::sharding::ShardChunkHeader;
+use crate::types::{Gas, ShardId};
+
+/// Size of the delayed receipts queue of a shard.
+///
+/// It is stored in the state of the shard under `TrieKey::CongestionInfo` and
+/// published in the chunk header, so that other shards can stop sending new
+/// receipts to the shard while it is congested.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CongestionInfo {
+    /// Total gas attached to the receipts in the delayed receipts queue.
+    pub delayed_receipts_gas: u128,
+    /// Total size of the borsh serialized receipts in the delayed receipts queue.
+    pub delayed_receipts_bytes: u64,
+}
+
+impl CongestionInfo {
+    pub fn add_delayed_receipt(
+        &mut self,
+        gas: Gas,
+        bytes: u64,
+    ) -> Result<(), IntegerOverflowError> {
+        self.delayed_receipts_gas =
+            self.delayed_receipts_gas.checked_add(gas as u128).ok_or(IntegerOverflowError)?;
+        self.delayed_receipts_bytes =
+            self.delayed_receipts_bytes.checked_add(bytes).ok_or(IntegerOverflowError)?;
+        Ok(())
+    }
+
+    pub fn remove_delayed_receipt(&mut self, gas: Gas, bytes: u64) {
+        // Receipts delayed before congestion control was enabled were never
+        // added, so removing them must not underflow.
+        self.delayed_receipts_gas = self.delayed_receipts_gas.saturating_sub(gas as u128);
+        self.delayed_receipts_bytes = self.delayed_receipts_bytes.saturating_sub(bytes);
+    }
+
+    /// Whether the shard should not receive any new receipts.
+    pub fn is_congested(&self, config: &CongestionControlConfig) -> bool {
+        self.delayed_receipts_gas > config.max_delayed_receipts_gas as u128
+            || self.delayed_receipts_bytes > config.max_delayed_receipts_bytes
+    }
+}
+
+/// Congestion info of all shards as published in the chunk headers of a block.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockCongestionInfo {
+    shards_congestion_info: HashMap<ShardId, CongestionInfo>,
+}
+
+impl BlockCongestionInfo {
+    pub fn new(shards_congestion_info: HashMap<ShardId, CongestionInfo>) -> Self {
+        Self { shards_congestion_info }
+    }
+
+    /// Collects the congestion info of the given chunk headers. Headers created
+    /// before congestion control was enabled don't carry any and are skipped.
+    pub fn from_chunk_headers<'a>(
+        chunk_headers: impl IntoIterator<Item = &'a ShardChunkHeader>,
+    ) -> Self {
+        let shards_congestion_info = chunk_headers
+            .into_iter()
+            .filter_map(|header| Some((header.shard_id(), header.congestion_info()?)))
+            .collect();
+        Self { shards_congestion_info }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shards_congestion_info.is_empty()
+    }
+
+    pub fn get(&self, shard_id: ShardId) -> Option<&CongestionInfo> {
+        self.shards_congestion_info.get(&shard_id)
+    }
+
+    /// Whether the shard is congested. Shards without known congestion info
+    /// are never considered congested.
+    pub fn is_congested(&self, shard_id: ShardId, config: &CongestionControlConfig) -> bool {
+        self.get(shard_id).map_or(false, |info| info.is_congested(config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_congestion_info_is_congested() {
+        let config = CongestionControlConfig {
+            max_delayed_receipts_gas: 100,
+            max_delayed_receipts_bytes: 1000,
+        };
+        let mut info = CongestionInfo::default();
+        info.add_delayed_receipt(100, 1000).unwrap();
+        assert!(!info.is_congested(&config));
+        info.add_delayed_receipt(1, 0).unwrap();
+        assert!(info.is_congested(&config));
+        info.remove_delayed_receipt(1, 0);
+        info.add_delayed_receipt(0, 1).unwrap();
+        assert!(info.is_congested(&config));
+        info.remove_delayed_receipt(200, 2000);
+        assert_eq!(info, CongestionInfo::default());
+    }
+
+    #[test]
+    fn test_block_congestion_info_unknown_shard() {
+        let config =
+            CongestionControlConfig { max_delayed_receipts_gas: 0, max_delayed_receipts_bytes: 0 };
+        let congested = CongestionInfo { delayed_receipts_gas: 1, delayed_receipts_bytes: 1 };
+        let info = BlockCongestionInfo::new([(0, congested)].into_iter().collect());
+        assert!(info.is_congested(0, &config));
+        assert!(!info.is_congested(1, &config));
+    }
+}
@@ -94,6 +94,8 @@ impl AllEpochConfig {
         Self { use_production_config, genesis_epoch_config }
     }
 
+    /// NOTE: the shard layout must not change at or above the protocol version
+    /// of `ProtocolFeature::CongestionControl`, see its documentation.
     pub fn for_protocol_version(&self, protocol_version: ProtocolVersion) -> EpochConfig {
         // if SimpleNightshade is enabled, we override genesis shard config with
         // the simple nightshade shard config
@@ -966,3 +968,49 @@ pub enum SlashState {
     /// All other cases (tokens should be entirely slashed),
     Other,
 }
+
+#[cfg(all(test, feature = "protocol_feature_congestion_control"))]
+mod tests {
+    use super::{AllEpochConfig, EpochConfig, ValidatorSelectionConfig};
+    use crate::num_rational::Rational32;
+    use crate::shard_layout::ShardLayout;
+    use crate::version::{ProtocolFeature, PROTOCOL_VERSION};
+
+    fn genesis_epoch_config() -> EpochConfig {
+        EpochConfig {
+            epoch_length: 100,
+            num_block_producer_seats: 100,
+            num_block_producer_seats_per_shard: vec![100],
+            avg_hidden_validator_seats_per_shard: vec![0],
+            block_producer_kickout_threshold: 90,
+            chunk_producer_kickout_threshold: 90,
+            validator_max_kickout_stake_perc: 100,
+            online_min_threshold: Rational32::new(90, 100),
+            online_max_threshold: Rational32::new(99, 100),
+            fishermen_threshold: 0,
+            minimum_stake_divisor: 10,
+            protocol_upgrade_stake_threshold: Rational32::new(8, 10),
+            protocol_upgrade_num_epochs: 2,
+            shard_layout: ShardLayout::v0_single_shard(),
+            validator_selection_config: ValidatorSelectionConfig::default(),
+        }
+    }
+
+    /// Congestion control doesn't support resharding, so the shard layout must
+    /// not change once it is enabled.
+    #[test]
+    fn test_no_resharding_with_congestion_control() {
+        let first = ProtocolFeature::CongestionControl.protocol_version();
+        for use_production_config in [false, true] {
+            let config = AllEpochConfig::new(use_production_config, genesis_epoch_config());
+            let want = config.for_protocol_version(first).shard_layout;
+            for protocol_version in first..=PROTOCOL_VERSION.max(first) {
+                assert_eq!(
+                    want,
+                    config.for_protocol_version(protocol_version).shard_layout,
+                    "shard layout changes at protocol version {protocol_version}"
+                );
+            }
+        }
+    }
+}
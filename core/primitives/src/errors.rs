@@ -1,5 +1,7 @@
+use crate::account::AccessKeyExpiry;
 use crate::serialize::dec_format;
-use crate::types::{AccountId, Balance, EpochId, Gas, Nonce};
+use crate::types::{AccountId, Balance, EpochId, Gas, Nonce, ShardId};
+use crate::version::ProtocolVersion;
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::PublicKey;
 use serde::{Deserialize, Serialize};
@@ -140,6 +142,8 @@ pub enum InvalidTxError {
     ActionsValidation(ActionsValidationError),
     /// The size of serialized transaction exceeded the limit.
     TransactionSizeExceeded { size: u64, limit: u64 },
+    /// The shard of the transaction receiver is congested and doesn't accept new transactions.
+    ShardCongested { shard_id: ShardId },
 }
 
 impl std::error::Error for InvalidTxError {}
@@ -167,6 +171,10 @@ pub enum InvalidAccessKeyError {
     },
     /// Having a deposit with a function call action is not allowed with a function call access key.
     DepositWithFunctionCall,
+    /// The access key has expired and can't be used anymore.
+    AccessKeyExpired { expiry: AccessKeyExpiry },
+    /// The access key has already been used the maximum number of times in the current epoch.
+    UsageLimitExceeded { max_uses_per_epoch: u64 },
 }
 
 /// Describes the error for validating a list of actions.
@@ -198,6 +206,18 @@ pub enum ActionsValidationError {
     UnsuitableStakingKey { public_key: PublicKey },
     /// The attached amount of gas in a FunctionCall action has to be a positive number.
     FunctionCallZeroAttachedGas,
+    /// There should be the only one DelegateAction
+    DelegateActionMustBeOnlyOne,
+    /// The actions include a feature that is not supported by the protocol
+    /// `version` they were validated against.
+    ///
+    /// Note: we stringify the protocol feature name instead of using
+    /// `ProtocolFeature` here because we don't want to leak the internals of
+    /// that type into observable borsh serialization.
+    UnsupportedProtocolFeature { protocol_feature: String, version: ProtocolVersion },
+    /// The usage limit of a `FunctionCallV2` access key allows no uses per
+    /// epoch, or counts more uses than allowed.
+    InvalidAccessKeyUsageLimit { max_uses_per_epoch: u64, uses: u64 },
 }
 
 /// Describes the error for validating a receipt.
@@ -314,6 +334,22 @@ impl Display for ActionsValidationError {
                 f,
                 "The attached amount of gas in a FunctionCall action has to be a positive number",
             ),
+            ActionsValidationError::DelegateActionMustBeOnlyOne => write!(
+                f,
+                "The actions can contain the only one DelegateAction"
+            ),
+            ActionsValidationError::UnsupportedProtocolFeature { protocol_feature, version } => write!(
+                f,
+                "Protocol feature {} is not supported by the protocol version {}",
+                protocol_feature,
+                version,
+            ),
+            ActionsValidationError::InvalidAccessKeyUsageLimit { max_uses_per_epoch, uses } => write!(
+                f,
+                "Access key usage limit of {} uses per epoch is invalid with {} uses",
+                max_uses_per_epoch,
+                uses,
+            ),
         }
     }
 }
@@ -397,6 +433,18 @@ pub enum ActionErrorKind {
     OnlyImplicitAccountCreationAllowed { account_id: AccountId },
     /// Delete account whose state is large is temporarily banned.
     DeleteAccountWithLargeState { account_id: AccountId },
+    /// Signature does not match the provided actions and given signer public key.
+    DelegateActionInvalidSignature,
+    /// Receiver of the transaction doesn't match Sender of the delegate action
+    DelegateActionSenderDoesNotMatchTxReceiver { sender_id: AccountId, receiver_id: AccountId },
+    /// Delegate action has expired. `max_block_height` is less than actual block height.
+    DelegateActionExpired,
+    /// The given public key doesn't exist for Sender account
+    DelegateActionAccessKeyError(InvalidAccessKeyError),
+    /// DelegateAction nonce must be greater sender[public_key].nonce
+    DelegateActionInvalidNonce { delegate_nonce: Nonce, ak_nonce: Nonce },
+    /// DelegateAction nonce is larger than the upper bound given by the block height
+    DelegateActionNonceTooLarge { delegate_nonce: Nonce, upper_bound: Nonce },
 }
 
 impl From<ActionErrorKind> for ActionError {
@@ -458,6 +506,9 @@ impl Display for InvalidTxError {
             InvalidTxError::TransactionSizeExceeded { size, limit } => {
                 write!(f, "Size of serialized transaction {} exceeded the limit {}", size, limit)
             }
+            InvalidTxError::ShardCongested { shard_id } => {
+                write!(f, "Shard {} is congested and doesn't accept new transactions", shard_id)
+            }
         }
     }
 }
@@ -502,6 +553,14 @@ impl Display for InvalidAccessKeyError {
             InvalidAccessKeyError::DepositWithFunctionCall => {
                 write!(f, "Having a deposit with a function call action is not allowed with a function call access key.")
             }
+            InvalidAccessKeyError::AccessKeyExpired { expiry } => {
+                write!(f, "Access key has expired at {:?}", expiry)
+            }
+            InvalidAccessKeyError::UsageLimitExceeded { max_uses_per_epoch } => write!(
+                f,
+                "Access key has already been used the maximum of {} times in this epoch",
+                max_uses_per_epoch
+            ),
         }
     }
 }
@@ -707,6 +766,12 @@ impl Display for ActionErrorKind {
             ActionErrorKind::InsufficientStake { account_id, stake, minimum_stake } => write!(f, "Account {} tries to stake {} but minimum required stake is {}", account_id, stake, minimum_stake),
             ActionErrorKind::OnlyImplicitAccountCreationAllowed { account_id } => write!(f, "CreateAccount action is called on hex-characters account of length 64 {}", account_id),
             ActionErrorKind::DeleteAccountWithLargeState { account_id } => write!(f, "The state of account {} is too large and therefore cannot be deleted", account_id),
+            ActionErrorKind::DelegateActionInvalidSignature => write!(f, "DelegateAction is not signed with the given public key"),
+            ActionErrorKind::DelegateActionSenderDoesNotMatchTxReceiver { sender_id, receiver_id } => write!(f, "Transaction receiver {} doesn't match DelegateAction sender {}", receiver_id, sender_id),
+            ActionErrorKind::DelegateActionExpired => write!(f, "DelegateAction has expired"),
+            ActionErrorKind::DelegateActionAccessKeyError(access_key_error) => Display::fmt(&access_key_error, f),
+            ActionErrorKind::DelegateActionInvalidNonce { delegate_nonce, ak_nonce } => write!(f, "DelegateAction nonce {} must be larger than nonce of the used access key {}", delegate_nonce, ak_nonce),
+            ActionErrorKind::DelegateActionNonceTooLarge { delegate_nonce, upper_bound } => write!(f, "DelegateAction nonce {} must be smaller than the access key nonce upper bound {}", delegate_nonce, upper_bound),
         }
     }
 }
@@ -1,4 +1,6 @@
+use crate::congestion_info::BlockCongestionInfo;
 use crate::runtime::migration_data::{MigrationData, MigrationFlags};
+use crate::shard_layout::ShardLayout;
 use crate::{
     hash::CryptoHash,
     runtime::config::RuntimeConfig,
@@ -41,4 +43,9 @@ pub struct ApplyState {
     pub migration_data: Arc<MigrationData>,
     /// Flags for migrations indicating whether they can be applied at this block
     pub migration_flags: MigrationFlags,
+    /// Congestion info of all shards from the chunk headers of the block. Receipts are not
+    /// sent to the congested shards.
+    pub congestion_info: BlockCongestionInfo,
+    /// Shard layout of the current epoch, used to find the receiving shard of the receipts.
+    pub shard_layout: ShardLayout,
 }
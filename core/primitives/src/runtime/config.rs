@@ -6,7 +6,7 @@ use crate::runtime::config_store::INITIAL_TESTNET_CONFIG;
 use crate::runtime::fees::RuntimeFeesConfig;
 use crate::runtime::parameter_table::ParameterTable;
 use crate::serialize::dec_format;
-use crate::types::{AccountId, Balance};
+use crate::types::{AccountId, Balance, Gas};
 
 use super::parameter_table::InvalidConfigError;
 
@@ -24,6 +24,8 @@ pub struct RuntimeConfig {
     pub wasm_config: VMConfig,
     /// Config that defines rules for account creation.
     pub account_creation_config: AccountCreationConfig,
+    /// Config that defines when a shard is considered congested.
+    pub congestion_control_config: CongestionControlConfig,
 }
 
 impl RuntimeConfig {
@@ -46,6 +48,7 @@ impl RuntimeConfig {
             transaction_costs: RuntimeFeesConfig::test(),
             wasm_config: VMConfig::test(),
             account_creation_config: AccountCreationConfig::default(),
+            congestion_control_config: CongestionControlConfig::default(),
         }
     }
 
@@ -55,6 +58,7 @@ impl RuntimeConfig {
             transaction_costs: RuntimeFeesConfig::free(),
             wasm_config: VMConfig::free(),
             account_creation_config: AccountCreationConfig::default(),
+            congestion_control_config: CongestionControlConfig::default(),
         }
     }
 }
@@ -77,3 +81,22 @@ impl Default for AccountCreationConfig {
         }
     }
 }
+
+/// The structure describes the limits of the delayed receipts queue after
+/// which a shard is considered congested.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CongestionControlConfig {
+    /// The maximum total gas attached to the delayed receipts of a shard.
+    pub max_delayed_receipts_gas: Gas,
+    /// The maximum total size in bytes of the delayed receipts of a shard.
+    pub max_delayed_receipts_bytes: u64,
+}
+
+impl Default for CongestionControlConfig {
+    fn default() -> Self {
+        Self {
+            max_delayed_receipts_gas: 20_000_000_000_000_000,
+            max_delayed_receipts_bytes: 10_000_000,
+        }
+    }
+}
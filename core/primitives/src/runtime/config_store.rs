@@ -1,6 +1,7 @@
 use crate::runtime::config::RuntimeConfig;
 use crate::runtime::parameter_table::{ParameterTable, ParameterTableDiff};
 use crate::types::ProtocolVersion;
+use near_primitives_core::parameter::Parameter;
 use std::collections::BTreeMap;
 use std::ops::Bound;
 use std::sync::Arc;
@@ -33,6 +34,67 @@ static CONFIG_DIFFS: &[(ProtocolVersion, &str)] = &[
 /// Testnet parameters for versions <= 29, which (incorrectly) differed from mainnet parameters
 pub static INITIAL_TESTNET_CONFIG: &str = include_config!("parameters_testnet.txt");
 
+/// Protocol version for which `RuntimeConfigStore::new` lowers the storage cost
+/// of the genesis runtime config.
+const LOWER_STORAGE_COST_VERSION: ProtocolVersion = 42;
+
+/// Value of a runtime parameter together with the config file that last set it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedParameter {
+    pub value: serde_json::Value,
+    /// Name of the base parameter file or of the diff file, e.g. `53.txt`.
+    pub file: String,
+}
+
+/// Resolves the values of all runtime parameters for `protocol_version` on the
+/// chain `chain_id` by applying the diffs to the base parameter file.
+///
+/// The result matches the config returned by `RuntimeConfigStore`.  In
+/// particular, testnet uses its genesis runtime config (with the lower storage
+/// cost from version 42 on) until the first config change after version 42,
+/// and the mainnet parameters afterwards.
+pub fn resolve_parameters(
+    chain_id: &str,
+    protocol_version: ProtocolVersion,
+) -> BTreeMap<Parameter, ResolvedParameter> {
+    let uses_testnet_genesis_config = chain_id == "testnet"
+        && !CONFIG_DIFFS.iter().any(|(version, _)| {
+            *version > LOWER_STORAGE_COST_VERSION && *version <= protocol_version
+        });
+    let (base_file, base_config) = if uses_testnet_genesis_config {
+        ("parameters_testnet.txt", INITIAL_TESTNET_CONFIG)
+    } else {
+        ("parameters.txt", BASE_CONFIG)
+    };
+
+    let mut params: ParameterTable = base_config
+        .parse()
+        .unwrap_or_else(|err| panic!("Failed parsing {base_file}. Error: {err}"));
+    let mut files: BTreeMap<Parameter, String> =
+        params.iter().map(|(param, _)| (*param, base_file.to_owned())).collect();
+    for (version, diff_bytes) in CONFIG_DIFFS {
+        if *version > protocol_version {
+            break;
+        }
+        let diff: ParameterTableDiff = diff_bytes.parse().unwrap_or_else(|err| {
+            panic!("Failed parsing runtime parameters diff for version {version}. Error: {err}")
+        });
+        for param in diff.changed_parameters() {
+            files.insert(*param, format!("{version}.txt"));
+        }
+        params.apply_diff(diff).unwrap_or_else(|err| {
+            panic!("Failed applying diff for version {version}. Error: {err}")
+        });
+    }
+
+    params
+        .iter()
+        .map(|(param, value)| {
+            (*param, ResolvedParameter { value: value.clone(), file: files[param].clone() })
+        })
+        .collect()
+}
+
 /// Stores runtime config for each protocol version where it was updated.
 #[derive(Debug)]
 pub struct RuntimeConfigStore {
@@ -70,7 +132,7 @@ impl RuntimeConfigStore {
             store.insert(0, Arc::new(config.clone()));
 
             config.storage_amount_per_byte = 10u128.pow(19);
-            store.insert(42, Arc::new(config));
+            store.insert(LOWER_STORAGE_COST_VERSION, Arc::new(config));
         }
 
         Self { store }
@@ -233,6 +295,98 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_fee_compute_costs() {
+        use near_primitives_core::parameter::FeeParameter;
+
+        let params: ParameterTable = BASE_CONFIG
+            .replace(
+                "action_transfer_execution: 115_123_062_500",
+                "action_transfer_execution: { gas: 115_123_062_500, compute: 1_000_000_000_000 }",
+            )
+            .parse()
+            .unwrap();
+        let fees = RuntimeConfig::new(&params).unwrap().transaction_costs;
+        let transfer_cost = fees.fee(FeeParameter::ActionTransfer);
+        assert_eq!(transfer_cost.execution, 115_123_062_500);
+        let compute_fees = fees.compute_fees();
+        let transfer_compute = compute_fees.fee(FeeParameter::ActionTransfer);
+        assert_eq!(transfer_compute.execution, 1_000_000_000_000);
+        assert_eq!(transfer_compute.send_sir, transfer_cost.send_sir);
+        assert_eq!(
+            compute_fees.fee(FeeParameter::ActionStake),
+            fees.fee(FeeParameter::ActionStake)
+        );
+    }
+
+    /// Writes resolved parameters back in the format of the parameter files.
+    fn resolved_parameters_txt(params: &BTreeMap<Parameter, ResolvedParameter>) -> String {
+        let mut txt = String::new();
+        for (param, resolved) in params {
+            let value = match &resolved.value {
+                serde_json::Value::Null => String::new(),
+                serde_json::Value::String(value) => value.clone(),
+                serde_json::Value::Object(cost) => {
+                    format!("{{ gas: {}, compute: {} }}", cost["gas"], cost["compute"])
+                }
+                value => value.to_string(),
+            };
+            txt.push_str(&format!("{param}: {value}\n"));
+        }
+        txt
+    }
+
+    #[test]
+    fn test_resolve_parameters_matches_store() {
+        let mainnet_store = RuntimeConfigStore::new(None);
+        let testnet_store = RuntimeConfigStore::new(Some(&RuntimeConfig::initial_testnet_config()));
+        for (chain_id, store) in [("mainnet", &mainnet_store), ("testnet", &testnet_store)] {
+            for version in store.store.keys().flat_map(|version| [*version, version + 1]) {
+                let params = resolve_parameters(chain_id, version);
+                let params: ParameterTable = resolved_parameters_txt(&params).parse().unwrap();
+                assert_eq!(
+                    &RuntimeConfig::new(&params).unwrap(),
+                    store.get_config(version).as_ref(),
+                    "resolved parameters differ from the config store for {chain_id} at version {version}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_resolve_parameters_files() {
+        let resolved = |chain_id, version, param| {
+            let resolved = &resolve_parameters(chain_id, version)[&param];
+            (resolved.value.clone(), resolved.file.clone())
+        };
+        let expected = |value: serde_json::Value, file: &str| (value, file.to_owned());
+
+        assert_eq!(
+            resolved("mainnet", 52, Parameter::MaxLengthStorageKey),
+            expected(4_194_304.into(), "parameters.txt")
+        );
+        assert_eq!(
+            resolved("mainnet", 53, Parameter::MaxLengthStorageKey),
+            expected(2_048.into(), "53.txt")
+        );
+        assert_eq!(
+            resolved("mainnet", 57, Parameter::StorageAmountPerByte),
+            expected("10000000000000000000".into(), "42.txt")
+        );
+        assert_eq!(
+            resolved("testnet", 41, Parameter::MinAllowedTopLevelAccountLength),
+            expected(0.into(), "parameters_testnet.txt")
+        );
+        assert_eq!(
+            resolved("testnet", 47, Parameter::StorageAmountPerByte),
+            expected("10000000000000000000".into(), "42.txt")
+        );
+        assert_eq!(
+            resolved("testnet", 48, Parameter::MinAllowedTopLevelAccountLength),
+            expected(32.into(), "parameters.txt")
+        );
+    }
+
     /// Use snapshot testing to check that the JSON representation of the
     /// configurations of each version is unchanged.
     /// If tests fail after an intended change, run `cargo insta review` accept
@@ -1,6 +1,7 @@
 use near_primitives_core::parameter::{FeeParameter, Parameter};
 use serde_json::json;
 use std::collections::BTreeMap;
+use strum::IntoEnumIterator;
 
 pub(crate) struct ParameterTable {
     parameters: BTreeMap<Parameter, serde_json::Value>,
@@ -31,6 +32,10 @@ pub(crate) enum InvalidConfigError {
     NoOldValueExists(Parameter, String),
     #[error("expected old value `{1}` but found `{2}` for parameter `{0}` in config diff")]
     WrongOldValue(Parameter, String, String),
+    #[error("expected a cost of the form `{{ gas: <value>, compute: <value> }}` but found `{0}`")]
+    InvalidCost(String),
+    #[error("parameter `{0}` does not support a compute usage different from its gas cost")]
+    ComputeNotSupported(Parameter),
 }
 
 impl std::str::FromStr for ParameterTable {
@@ -39,7 +44,7 @@ impl std::str::FromStr for ParameterTable {
         let parameters = txt_to_key_values(arg)
             .map(|result| {
                 let (typed_key, value) = result?;
-                Ok((typed_key, parse_parameter_txt_value(value.trim())?))
+                Ok((typed_key, parse_parameter_value(typed_key, value.trim())?))
             })
             .collect::<Result<BTreeMap<_, _>, _>>()?;
         Ok(ParameterTable { parameters })
@@ -55,7 +60,7 @@ impl ParameterTable {
             "storage_amount_per_byte": storage_amount_per_byte,
             "transaction_costs": transaction_costs,
             "wasm_config": {
-                "ext_costs": self.json_map(Parameter::ext_costs(), "wasm_"),
+                "ext_costs": self.ext_costs_json(),
                 "grow_mem_cost": self.get(Parameter::WasmGrowMemCost),
                 "regular_op_cost": self.get(Parameter::WasmRegularOpCost),
                 "limit_config": self.json_map(Parameter::vm_limits(), ""),
@@ -63,6 +68,10 @@ impl ParameterTable {
             "account_creation_config": {
                 "min_allowed_top_level_account_length": self.get(Parameter::MinAllowedTopLevelAccountLength),
                 "registrar_account_id": self.get(Parameter::RegistrarAccountId),
+            },
+            "congestion_control_config": {
+                "max_delayed_receipts_gas": self.get(Parameter::MaxCongestionDelayedReceiptsGas),
+                "max_delayed_receipts_bytes": self.get(Parameter::MaxCongestionDelayedReceiptsBytes),
             }
         })
     }
@@ -125,7 +134,9 @@ impl ParameterTable {
                 },
                 "delete_key_cost": self.fee_json(FeeParameter::ActionDeleteKey),
                 "delete_account_cost": self.fee_json(FeeParameter::ActionDeleteAccount),
+                "delegate_cost": self.fee_json(FeeParameter::ActionDelegate),
             },
+            "compute_costs": self.fees_compute_json(),
             "storage_usage_config": {
                 "num_bytes_account": self.get(Parameter::StorageNumBytesAccount),
                 "num_extra_bytes_record": self.get(Parameter::StorageNumExtraBytesRecord),
@@ -157,17 +168,87 @@ impl ParameterTable {
         json.into()
     }
 
+    /// Host function costs in the format of `ExtCostsConfig`. Costs with a
+    /// compute usage different from their gas are listed in `compute_costs`.
+    fn ext_costs_json(&self) -> serde_json::Value {
+        let mut json = serde_json::Map::new();
+        let mut compute_costs = serde_json::Map::new();
+        for param in Parameter::ext_costs() {
+            let mut key: &'static str = param.into();
+            key = key.strip_prefix("wasm_").unwrap_or(key);
+            match self.get(*param) {
+                Some(serde_json::Value::Object(cost)) => {
+                    json.insert(key.to_owned(), cost["gas"].clone());
+                    compute_costs.insert(key.to_owned(), cost["compute"].clone());
+                }
+                Some(value) => {
+                    json.insert(key.to_owned(), value.clone());
+                }
+                None => {}
+            }
+        }
+        json.insert("compute_costs".to_owned(), compute_costs.into());
+        json.into()
+    }
+
     fn get(&self, key: Parameter) -> Option<&serde_json::Value> {
         self.parameters.get(&key)
     }
 
+    /// Iterates over all parameters in the table and their values.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&Parameter, &serde_json::Value)> {
+        self.parameters.iter()
+    }
+
     fn fee_json(&self, key: FeeParameter) -> serde_json::Value {
+        let gas = |component: &str| {
+            self.get(format!("{key}_{component}").parse().unwrap()).map(|value| match value {
+                serde_json::Value::Object(cost) => &cost["gas"],
+                value => value,
+            })
+        };
         json!( {
-            "send_sir": self.get(format!("{key}_send_sir").parse().unwrap()),
-            "send_not_sir": self.get(format!("{key}_send_not_sir").parse().unwrap()),
-            "execution": self.get(format!("{key}_execution").parse().unwrap()),
+            "send_sir": gas("send_sir"),
+            "send_not_sir": gas("send_not_sir"),
+            "execution": gas("execution"),
         })
     }
+
+    /// Compute usage of the fees in the format of
+    /// `RuntimeFeesConfig::compute_costs`. A fee is listed if any of its
+    /// components has a compute usage different from its gas, the other
+    /// components use their gas as compute.
+    fn fees_compute_json(&self) -> serde_json::Value {
+        let mut json = serde_json::Map::new();
+        for key in FeeParameter::iter() {
+            let mut has_compute = false;
+            let mut compute = |component: &str| {
+                self.get(format!("{key}_{component}").parse().unwrap()).map(|value| match value {
+                    serde_json::Value::Object(cost) => {
+                        has_compute = true;
+                        &cost["compute"]
+                    }
+                    value => value,
+                })
+            };
+            let fee = json!( {
+                "send_sir": compute("send_sir"),
+                "send_not_sir": compute("send_not_sir"),
+                "execution": compute("execution"),
+            });
+            if has_compute {
+                json.insert(key.to_string(), fee);
+            }
+        }
+        json.into()
+    }
+}
+
+impl ParameterTableDiff {
+    /// Parameters whose value is set by the diff.
+    pub(crate) fn changed_parameters(&self) -> impl Iterator<Item = &Parameter> {
+        self.parameters.keys()
+    }
 }
 
 impl std::str::FromStr for ParameterTableDiff {
@@ -180,14 +261,14 @@ impl std::str::FromStr for ParameterTableDiff {
                     Ok((
                         typed_key,
                         (
-                            parse_parameter_txt_value(before.trim())?,
-                            parse_parameter_txt_value(after.trim())?,
+                            parse_parameter_value(typed_key, before.trim())?,
+                            parse_parameter_value(typed_key, after.trim())?,
                         ),
                     ))
                 } else {
                     Ok((
                         typed_key,
-                        (serde_json::Value::Null, parse_parameter_txt_value(value.trim())?),
+                        (serde_json::Value::Null, parse_parameter_value(typed_key, value.trim())?),
                     ))
                 }
             })
@@ -222,6 +303,48 @@ fn txt_to_key_values(
         })
 }
 
+/// Parses the value of a parameter, which is either a plain value or a cost
+/// written as `{ gas: <value>, compute: <value> }`. Only host function costs
+/// and action fees can have a compute usage different from their gas cost.
+fn parse_parameter_value(
+    key: Parameter,
+    value: &str,
+) -> Result<serde_json::Value, InvalidConfigError> {
+    let Some(fields) = value.strip_prefix('{').and_then(|value| value.strip_suffix('}')) else {
+        return parse_parameter_txt_value(value);
+    };
+    if !supports_compute(key) {
+        return Err(InvalidConfigError::ComputeNotSupported(key));
+    }
+    let mut cost = serde_json::Map::new();
+    for field in fields.split(',') {
+        let (name, field_value) = field
+            .split_once(':')
+            .ok_or_else(|| InvalidConfigError::InvalidCost(value.to_owned()))?;
+        let name = name.trim();
+        if !matches!(name, "gas" | "compute") || cost.contains_key(name) {
+            return Err(InvalidConfigError::InvalidCost(value.to_owned()));
+        }
+        cost.insert(name.to_owned(), parse_parameter_txt_value(field_value.trim())?);
+    }
+    if cost.len() != 2 {
+        return Err(InvalidConfigError::InvalidCost(value.to_owned()));
+    }
+    Ok(cost.into())
+}
+
+/// Whether the parameter is a cost, i.e. a host function cost or a component
+/// of an action fee, and thus can have a compute usage.
+fn supports_compute(key: Parameter) -> bool {
+    if Parameter::ext_costs().any(|param| *param == key) {
+        return true;
+    }
+    let name: &'static str = key.into();
+    ["_send_sir", "_send_not_sir", "_execution"].iter().any(|component| {
+        name.strip_suffix(component).map_or(false, |fee| fee.parse::<FeeParameter>().is_ok())
+    })
+}
+
 /// Parses a value from the custom format for runtime parameter definitions.
 ///
 /// A value can be a positive integer or a string, both written without quotes.
@@ -513,6 +636,87 @@ max_memory_pages: 512
         );
     }
 
+    #[test]
+    fn test_parameter_table_with_compute() {
+        check_parameter_table(
+            "wasm_storage_read_base: 100\nwasm_storage_write_base: 200",
+            &["wasm_storage_read_base: 100 -> { gas: 100, compute: 1_000 }"],
+            [
+                (Parameter::WasmStorageReadBase, r#"{"gas": 100, "compute": 1000}"#),
+                (Parameter::WasmStorageWriteBase, "200"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_parameter_table_compute_in_runtime_config() {
+        let params: ParameterTable =
+            "wasm_storage_read_base: { gas: 100, compute: 1_000 }\nwasm_storage_write_base: 200"
+                .parse()
+                .unwrap();
+        let ext_costs = &params.runtime_config_json()["wasm_config"]["ext_costs"];
+        assert_eq!(ext_costs["storage_read_base"], 100);
+        assert_eq!(ext_costs["storage_write_base"], 200);
+        assert_eq!(ext_costs["compute_costs"], serde_json::json!({ "storage_read_base": 1000 }));
+    }
+
+    #[test]
+    fn test_parameter_table_fee_compute_in_runtime_config() {
+        let params: ParameterTable = r#"
+action_transfer_send_sir: { gas: 100, compute: 1_000 }
+action_transfer_send_not_sir: 200
+action_transfer_execution: 300
+action_stake_send_sir: 400
+"#
+        .parse()
+        .unwrap();
+        let transaction_costs = &params.runtime_config_json()["transaction_costs"];
+        assert_eq!(
+            transaction_costs["action_creation_config"]["transfer_cost"],
+            serde_json::json!({ "send_sir": 100, "send_not_sir": 200, "execution": 300 })
+        );
+        assert_eq!(
+            transaction_costs["compute_costs"],
+            serde_json::json!({
+                "action_transfer": { "send_sir": 1000, "send_not_sir": 200, "execution": 300 }
+            })
+        );
+    }
+
+    #[test]
+    fn test_parameter_table_invalid_cost() {
+        assert_matches!(
+            check_invalid_parameter_table("wasm_storage_read_base: { gas: 100 }", &[]),
+            InvalidConfigError::InvalidCost(_)
+        );
+        assert_matches!(
+            check_invalid_parameter_table(
+                "wasm_storage_read_base: { gas: 100, compute: 100, gas: 1 }",
+                &[]
+            ),
+            InvalidConfigError::InvalidCost(_)
+        );
+        assert_matches!(
+            check_invalid_parameter_table("wasm_storage_read_base: { gas: 100, time: 100 }", &[]),
+            InvalidConfigError::InvalidCost(_)
+        );
+    }
+
+    #[test]
+    fn test_parameter_table_compute_not_supported() {
+        assert_matches!(
+            check_invalid_parameter_table(
+                "min_allowed_top_level_account_length: { gas: 32, compute: 64 }",
+                &[]
+            ),
+            InvalidConfigError::ComputeNotSupported(Parameter::MinAllowedTopLevelAccountLength)
+        );
+        assert_matches!(
+            check_invalid_parameter_table("wasm_regular_op_cost: { gas: 32, compute: 64 }", &[]),
+            InvalidConfigError::ComputeNotSupported(Parameter::WasmRegularOpCost)
+        );
+    }
+
     #[test]
     fn test_parameter_table_old_parameter_undefined() {
         assert_matches!(
@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 
 use near_crypto::Signature;
 
+use crate::checked_feature;
+use crate::congestion_info::CongestionInfo;
 use crate::hash::{hash, CryptoHash};
 use crate::merkle::{combine_hash, merklize, MerklePath};
 use crate::receipt::Receipt;
@@ -70,6 +72,7 @@ pub struct StateSyncInfo {
 pub mod shard_chunk_header_inner;
 pub use shard_chunk_header_inner::{
     ShardChunkHeaderInner, ShardChunkHeaderInnerV1, ShardChunkHeaderInnerV2,
+    ShardChunkHeaderInnerV3,
 };
 
 #[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq, Debug)]
@@ -190,23 +193,43 @@ impl ShardChunkHeaderV3 {
         outgoing_receipts_root: CryptoHash,
         tx_root: CryptoHash,
         validator_proposals: Vec<ValidatorStake>,
+        congestion_info: Option<CongestionInfo>,
         signer: &dyn ValidatorSigner,
     ) -> Self {
-        let inner = ShardChunkHeaderInner::V2(ShardChunkHeaderInnerV2 {
-            prev_block_hash,
-            prev_state_root,
-            outcome_root,
-            encoded_merkle_root,
-            encoded_length,
-            height_created: height,
-            shard_id,
-            gas_used,
-            gas_limit,
-            balance_burnt,
-            outgoing_receipts_root,
-            tx_root,
-            validator_proposals,
-        });
+        let inner = if let Some(congestion_info) = congestion_info {
+            ShardChunkHeaderInner::V3(ShardChunkHeaderInnerV3 {
+                prev_block_hash,
+                prev_state_root,
+                outcome_root,
+                encoded_merkle_root,
+                encoded_length,
+                height_created: height,
+                shard_id,
+                gas_used,
+                gas_limit,
+                balance_burnt,
+                outgoing_receipts_root,
+                tx_root,
+                validator_proposals,
+                congestion_info,
+            })
+        } else {
+            ShardChunkHeaderInner::V2(ShardChunkHeaderInnerV2 {
+                prev_block_hash,
+                prev_state_root,
+                outcome_root,
+                encoded_merkle_root,
+                encoded_length,
+                height_created: height,
+                shard_id,
+                gas_used,
+                gas_limit,
+                balance_burnt,
+                outgoing_receipts_root,
+                tx_root,
+                validator_proposals,
+            })
+        };
         let hash = Self::compute_hash(&inner);
         let signature = signer.sign_chunk_hash(&hash);
         Self { inner, height_included: 0, signature, hash }
@@ -383,6 +406,14 @@ impl ShardChunkHeader {
         }
     }
 
+    #[inline]
+    pub fn congestion_info(&self) -> Option<CongestionInfo> {
+        match &self {
+            ShardChunkHeader::V1(_) | ShardChunkHeader::V2(_) => None,
+            ShardChunkHeader::V3(header) => header.inner.congestion_info(),
+        }
+    }
+
     #[inline]
     pub fn chunk_hash(&self) -> ChunkHash {
         match &self {
@@ -401,7 +432,15 @@ impl ShardChunkHeader {
             ShardChunkHeader::V2(_) => {
                 SHARD_CHUNK_HEADER_UPGRADE_VERSION <= version && version < BLOCK_HEADER_V3_VERSION
             }
-            ShardChunkHeader::V3(_) => BLOCK_HEADER_V3_VERSION <= version,
+            ShardChunkHeader::V3(header) => {
+                BLOCK_HEADER_V3_VERSION <= version
+                    && header.inner.congestion_info().is_some()
+                        == checked_feature!(
+                            "protocol_feature_congestion_control",
+                            CongestionControl,
+                            version
+                        )
+            }
         }
     }
 }
@@ -967,6 +1006,7 @@ impl EncodedShardChunk {
         transactions: Vec<SignedTransaction>,
         outgoing_receipts: &[Receipt],
         outgoing_receipts_root: CryptoHash,
+        congestion_info: CongestionInfo,
         signer: &dyn ValidatorSigner,
         protocol_version: ProtocolVersion,
     ) -> Result<(Self, Vec<MerklePath>), std::io::Error> {
@@ -1024,6 +1064,12 @@ impl EncodedShardChunk {
             let chunk = EncodedShardChunkV2 { header: ShardChunkHeader::V2(header), content };
             Ok((Self::V2(chunk), merkle_paths))
         } else {
+            let congestion_info = checked_feature!(
+                "protocol_feature_congestion_control",
+                CongestionControl,
+                protocol_version
+            )
+            .then_some(congestion_info);
             let header = ShardChunkHeaderV3::new(
                 prev_block_hash,
                 prev_state_root,
@@ -1038,6 +1084,7 @@ impl EncodedShardChunk {
                 outgoing_receipts_root,
                 tx_root,
                 validator_proposals,
+                congestion_info,
                 signer,
             );
             let chunk = EncodedShardChunkV2 { header: ShardChunkHeader::V3(header), content };
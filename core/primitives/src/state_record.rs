@@ -14,8 +14,9 @@ use crate::trie_key::trie_key_parsers::{
     parse_account_id_from_contract_code_key, parse_account_id_from_contract_data_key,
     parse_account_id_from_received_data_key, parse_data_id_from_received_data_key,
     parse_data_key_from_contract_data_key, parse_public_key_from_access_key_key,
+    parse_receiving_shard_from_buffered_receipt_key,
 };
-use crate::types::AccountId;
+use crate::types::{AccountId, ShardId};
 
 /// Record in the state storage.
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -50,6 +51,9 @@ pub enum StateRecord {
     /// Delayed Receipt.
     /// The receipt was delayed because the shard was overwhelmed.
     DelayedReceipt(Box<Receipt>),
+    /// Receipt buffered by the sending shard for the given receiving shard.
+    /// The receipt was buffered because the receiving shard was congested.
+    BufferedReceipt { receiving_shard: ShardId, receipt: Box<Receipt> },
 }
 
 impl StateRecord {
@@ -95,6 +99,19 @@ impl StateRecord {
                 Some(StateRecord::DelayedReceipt(Box::new(receipt)))
             }
             col::DELAYED_RECEIPT_INDICES => None,
+            // Deduplicated code isn't tied to an account. Callers that need a
+            // `StateRecord::Contract` for such accounts should resolve it through the
+            // account's `code_hash`.
+            col::CONTRACT_CODE_BY_HASH => None,
+            col::CONTRACT_CODE_REFCOUNT => None,
+            col::CONGESTION_INFO => None,
+            col::BUFFERED_RECEIPT_INDICES => None,
+            col::BUFFERED_RECEIPT => {
+                let receiving_shard =
+                    parse_receiving_shard_from_buffered_receipt_key(&key).unwrap();
+                let receipt = Receipt::try_from_slice(&value).unwrap();
+                Some(StateRecord::BufferedReceipt { receiving_shard, receipt: Box::new(receipt) })
+            }
             _ => unreachable!(),
         }
     }
@@ -128,6 +145,9 @@ impl Display for StateRecord {
             ),
             StateRecord::PostponedReceipt(receipt) => write!(f, "Postponed receipt {:?}", receipt),
             StateRecord::DelayedReceipt(receipt) => write!(f, "Delayed receipt {:?}", receipt),
+            StateRecord::BufferedReceipt { receiving_shard, receipt } => {
+                write!(f, "Buffered receipt for shard {}: {:?}", receiving_shard, receipt)
+            }
         }
     }
 }
@@ -154,18 +174,32 @@ pub fn state_record_to_account_id(state_record: &StateRecord) -> &AccountId {
         | StateRecord::Contract { account_id, .. }
         | StateRecord::ReceivedData { account_id, .. }
         | StateRecord::Data { account_id, .. } => account_id,
-        StateRecord::PostponedReceipt(receipt) | StateRecord::DelayedReceipt(receipt) => {
-            &receipt.receiver_id
-        }
+        StateRecord::PostponedReceipt(receipt)
+        | StateRecord::DelayedReceipt(receipt)
+        | StateRecord::BufferedReceipt { receipt, .. } => &receipt.receiver_id,
     }
 }
 
 pub fn is_contract_code_key(key: &[u8]) -> bool {
     debug_assert!(!key.is_empty());
-    key[0] == col::CONTRACT_CODE
+    key[0] == col::CONTRACT_CODE || key[0] == col::CONTRACT_CODE_BY_HASH
+}
+
+/// Returns whether `key` belongs to contract code deduplicated by hash, which
+/// isn't owned by any single account.
+pub fn is_shared_contract_code_key(key: &[u8]) -> bool {
+    debug_assert!(!key.is_empty());
+    key[0] == col::CONTRACT_CODE_BY_HASH || key[0] == col::CONTRACT_CODE_REFCOUNT
 }
 
 pub fn is_delayed_receipt_key(key: &[u8]) -> bool {
     debug_assert!(!key.is_empty());
     key[0] == col::DELAYED_RECEIPT || key[0] == col::DELAYED_RECEIPT_INDICES
 }
+
+pub fn is_congestion_control_key(key: &[u8]) -> bool {
+    debug_assert!(!key.is_empty());
+    key[0] == col::CONGESTION_INFO
+        || key[0] == col::BUFFERED_RECEIPT_INDICES
+        || key[0] == col::BUFFERED_RECEIPT
+}
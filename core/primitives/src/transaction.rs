@@ -14,7 +14,7 @@ use crate::errors::TxExecutionError;
 use crate::hash::{hash, CryptoHash};
 use crate::merkle::MerklePath;
 use crate::serialize::{base64_format, dec_format};
-use crate::types::{AccountId, Balance, Gas, Nonce};
+use crate::types::{AccountId, Balance, BlockHeight, Compute, Gas, Nonce};
 
 pub type LogEntry = String;
 
@@ -68,6 +68,9 @@ pub enum Action {
     AddKey(AddKeyAction),
     DeleteKey(DeleteKeyAction),
     DeleteAccount(DeleteAccountAction),
+    /// Executes the signed inner actions on behalf of `DelegateAction::sender_id`
+    /// while the signer of the outer transaction pays for gas.
+    Delegate(SignedDelegateAction),
 }
 
 impl Action {
@@ -210,6 +213,105 @@ impl From<DeleteAccountAction> for Action {
     }
 }
 
+/// A list of actions signed by `sender_id` to be executed on `receiver_id`.
+/// It is submitted to the chain by a relayer which pays for the gas, see
+/// <https://github.com/near/NEPs/pull/366>.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct DelegateAction {
+    /// Signer of the delegated actions
+    pub sender_id: AccountId,
+    /// Receiver of the delegated actions.
+    pub receiver_id: AccountId,
+    /// List of actions to be executed.
+    ///
+    /// With the meta transactions MVP defined in NEP-366, nested
+    /// DelegateActions are not allowed. A separate type is used to enforce it.
+    pub actions: Vec<NonDelegateAction>,
+    /// Nonce to ensure that the same delegate action is not sent twice by a
+    /// relayer and should match for given account's `public_key`.
+    /// After this action is processed it will increment.
+    pub nonce: Nonce,
+    /// The maximal height of the block in the blockchain below which the given
+    /// DelegateAction is valid.
+    pub max_block_height: BlockHeight,
+    /// Public key used to sign this delegated action.
+    pub public_key: PublicKey,
+}
+
+impl DelegateAction {
+    pub fn get_actions(&self) -> Vec<Action> {
+        self.actions.iter().map(|a| a.clone().into()).collect()
+    }
+
+    /// Computes the hash which is signed by `sender_id`.
+    pub fn get_hash(&self) -> CryptoHash {
+        let bytes = self.try_to_vec().expect("Failed to serialize");
+        hash(&bytes)
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct SignedDelegateAction {
+    pub delegate_action: DelegateAction,
+    pub signature: Signature,
+}
+
+impl SignedDelegateAction {
+    pub fn verify(&self) -> bool {
+        let delegate_action = &self.delegate_action;
+        self.signature.verify(delegate_action.get_hash().as_ref(), &delegate_action.public_key)
+    }
+}
+
+impl From<SignedDelegateAction> for Action {
+    fn from(delegate_action: SignedDelegateAction) -> Self {
+        Self::Delegate(delegate_action)
+    }
+}
+
+/// An `Action` which is guaranteed not to be a `Delegate`, both when it is
+/// constructed and when it is deserialized.
+#[derive(BorshSerialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(try_from = "Action")]
+pub struct NonDelegateAction(Action);
+
+/// Borsh discriminant of `Action::Delegate`.
+const ACTION_DELEGATE_NUMBER: u8 = 8;
+
+impl From<NonDelegateAction> for Action {
+    fn from(action: NonDelegateAction) -> Self {
+        action.0
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("attempted to construct a NonDelegateAction from a Delegate action")]
+pub struct IsDelegateAction;
+
+impl TryFrom<Action> for NonDelegateAction {
+    type Error = IsDelegateAction;
+
+    fn try_from(action: Action) -> Result<Self, IsDelegateAction> {
+        if matches!(action, Action::Delegate(_)) {
+            Err(IsDelegateAction)
+        } else {
+            Ok(Self(action))
+        }
+    }
+}
+
+impl BorshDeserialize for NonDelegateAction {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        if buf.first() == Some(&ACTION_DELEGATE_NUMBER) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "DelegateAction mustn't contain a nested one",
+            ));
+        }
+        <Action as BorshDeserialize>::deserialize(buf).map(Self)
+    }
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Eq, Debug, Clone)]
 #[borsh_init(init)]
 pub struct SignedTransaction {
@@ -348,6 +450,11 @@ pub struct ExecutionOutcome {
     pub receipt_ids: Vec<CryptoHash>,
     /// The amount of the gas burnt by the given transaction or receipt.
     pub gas_burnt: Gas,
+    /// The compute time used by the given transaction or receipt. It's only
+    /// set by the runtime and is not stored in the database, so outcomes read
+    /// from the database don't have it.
+    #[borsh_skip]
+    pub compute_usage: Option<Compute>,
     /// The amount of tokens burnt corresponding to the burnt gas amount.
     /// This value doesn't always equal to the `gas_burnt` multiplied by the gas price, because
     /// the prepaid gas price might be lower than the actual gas price and it creates a deficit.
@@ -526,6 +633,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_delegate_action() {
+        let signer = InMemorySigner::from_random("alice.near".parse().unwrap(), KeyType::ED25519);
+        let delegate_action = DelegateAction {
+            sender_id: "alice.near".parse().unwrap(),
+            receiver_id: "bob.near".parse().unwrap(),
+            actions: vec![Action::Transfer(TransferAction { deposit: 1 }).try_into().unwrap()],
+            nonce: 1,
+            max_block_height: 100,
+            public_key: signer.public_key(),
+        };
+        let signed_delegate_action = SignedDelegateAction {
+            signature: signer.sign(delegate_action.get_hash().as_ref()),
+            delegate_action,
+        };
+        assert!(signed_delegate_action.verify());
+
+        let action = Action::Delegate(signed_delegate_action.clone());
+        let bytes = action.try_to_vec().unwrap();
+        assert_eq!(Action::try_from_slice(&bytes).unwrap(), action);
+
+        // Nested delegate actions are rejected both on construction and on
+        // deserialization.
+        assert!(NonDelegateAction::try_from(action).is_err());
+        let mut nested = signed_delegate_action.clone();
+        nested
+            .delegate_action
+            .actions
+            .push(NonDelegateAction(Action::Delegate(signed_delegate_action.clone())));
+        let bytes = nested.try_to_vec().unwrap();
+        assert!(SignedDelegateAction::try_from_slice(&bytes).is_err());
+
+        let mut tampered = signed_delegate_action;
+        tampered.delegate_action.nonce = 2;
+        assert!(!tampered.verify());
+    }
+
     #[test]
     fn test_outcome_to_hashes() {
         let outcome = ExecutionOutcome {
@@ -533,6 +677,7 @@ mod tests {
             logs: vec!["123".to_string(), "321".to_string()],
             receipt_ids: vec![],
             gas_burnt: 123,
+            compute_usage: Some(456),
             tokens_burnt: 1234000,
             executor_id: "alice".parse().unwrap(),
             metadata: ExecutionMetadata::V1,
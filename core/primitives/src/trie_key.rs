@@ -1,5 +1,5 @@
 use crate::hash::CryptoHash;
-use crate::types::AccountId;
+use crate::types::{AccountId, ShardId};
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::PublicKey;
 use std::mem::size_of;
@@ -35,8 +35,23 @@ pub(crate) mod col {
     pub const DELAYED_RECEIPT: u8 = 8;
     /// This column id is used when storing Key-Value data from a contract on an `account_id`.
     pub const CONTRACT_DATA: u8 = 9;
+    /// This column id is used when storing a contract blob shared by all accounts which deployed
+    /// it, for a given code hash.
+    pub const CONTRACT_CODE_BY_HASH: u8 = 10;
+    /// This column id is used when storing the number of accounts referencing a contract blob
+    /// stored under `CONTRACT_CODE_BY_HASH`.
+    pub const CONTRACT_CODE_REFCOUNT: u8 = 11;
+    /// This column id is used when storing `primitives::congestion_info::CongestionInfo` of the
+    /// delayed receipts queue.
+    /// NOTE: It is a singleton per shard.
+    pub const CONGESTION_INFO: u8 = 12;
+    /// This column id is used when storing the indices of the queue of receipts buffered for
+    /// a given receiving shard, because that shard is congested.
+    pub const BUFFERED_RECEIPT_INDICES: u8 = 13;
+    /// This column id is used when storing receipts buffered for a congested receiving shard.
+    pub const BUFFERED_RECEIPT: u8 = 14;
     /// All columns
-    pub const NON_DELAYED_RECEIPT_COLUMNS: [(u8, &str); 8] = [
+    pub const NON_DELAYED_RECEIPT_COLUMNS: [(u8, &str); 13] = [
         (ACCOUNT, "Account"),
         (CONTRACT_CODE, "ContractCode"),
         (ACCESS_KEY, "AccessKey"),
@@ -45,6 +60,11 @@ pub(crate) mod col {
         (PENDING_DATA_COUNT, "PendingDataCount"),
         (POSTPONED_RECEIPT, "PostponedReceipt"),
         (CONTRACT_DATA, "ContractData"),
+        (CONTRACT_CODE_BY_HASH, "ContractCodeByHash"),
+        (CONTRACT_CODE_REFCOUNT, "ContractCodeRefcount"),
+        (CONGESTION_INFO, "CongestionInfo"),
+        (BUFFERED_RECEIPT_INDICES, "BufferedReceiptIndices"),
+        (BUFFERED_RECEIPT, "BufferedReceipt"),
     ];
 }
 
@@ -83,6 +103,23 @@ pub enum TrieKey {
     /// Used to store a key-value record `Vec<u8>` within a contract deployed on a given `AccountId`
     /// and a given key.
     ContractData { account_id: AccountId, key: Vec<u8> },
+    /// Used to store `Vec<u8>` contract code for a given code hash. Accounts reference it via
+    /// `Account::code_hash`.
+    /// NOTE: Only used since `ProtocolFeature::ContractCodeDeduplication`.
+    ContractCodeByHash { code_hash: CryptoHash },
+    /// Used to store the number of accounts `u64` referencing the code stored under
+    /// `ContractCodeByHash` with the same `code_hash`.
+    ContractCodeRefcount { code_hash: CryptoHash },
+    /// Used to store `primitives::congestion_info::CongestionInfo` of the delayed receipts queue.
+    /// NOTE: It is a singleton per shard.
+    /// NOTE: Only used since `ProtocolFeature::CongestionControl`.
+    CongestionInfo,
+    /// Used to store indices of the queue of receipts that wait until a given `receiving_shard`
+    /// is no longer congested.
+    BufferedReceiptIndices { receiving_shard: ShardId },
+    /// Used to store a buffered receipt `primitives::receipt::Receipt` for a given index `u64`
+    /// in the queue of a given `receiving_shard`.
+    BufferedReceipt { receiving_shard: ShardId, index: u64 },
 }
 
 /// Provides `len` function.
@@ -140,6 +177,19 @@ impl TrieKey {
                     + ACCOUNT_DATA_SEPARATOR.len()
                     + key.len()
             }
+            TrieKey::ContractCodeByHash { code_hash } => {
+                col::CONTRACT_CODE_BY_HASH.len() + code_hash.as_ref().len()
+            }
+            TrieKey::ContractCodeRefcount { code_hash } => {
+                col::CONTRACT_CODE_REFCOUNT.len() + code_hash.as_ref().len()
+            }
+            TrieKey::CongestionInfo => col::CONGESTION_INFO.len(),
+            TrieKey::BufferedReceiptIndices { .. } => {
+                col::BUFFERED_RECEIPT_INDICES.len() + size_of::<ShardId>()
+            }
+            TrieKey::BufferedReceipt { .. } => {
+                col::BUFFERED_RECEIPT.len() + size_of::<ShardId>() + size_of::<u64>()
+            }
         }
     }
 
@@ -199,6 +249,26 @@ impl TrieKey {
                 buf.push(ACCOUNT_DATA_SEPARATOR);
                 buf.extend(key);
             }
+            TrieKey::ContractCodeByHash { code_hash } => {
+                buf.push(col::CONTRACT_CODE_BY_HASH);
+                buf.extend(code_hash.as_ref());
+            }
+            TrieKey::ContractCodeRefcount { code_hash } => {
+                buf.push(col::CONTRACT_CODE_REFCOUNT);
+                buf.extend(code_hash.as_ref());
+            }
+            TrieKey::CongestionInfo => {
+                buf.push(col::CONGESTION_INFO);
+            }
+            TrieKey::BufferedReceiptIndices { receiving_shard } => {
+                buf.push(col::BUFFERED_RECEIPT_INDICES);
+                buf.extend(&receiving_shard.to_le_bytes());
+            }
+            TrieKey::BufferedReceipt { receiving_shard, index } => {
+                buf.push(col::BUFFERED_RECEIPT);
+                buf.extend(&receiving_shard.to_le_bytes());
+                buf.extend(&index.to_le_bytes());
+            }
         };
         debug_assert_eq!(expected_len, buf.len() - start_len);
     }
@@ -337,7 +407,6 @@ pub mod trie_key_parsers {
         Ok(TrieKey::AccessKey { account_id, public_key })
     }
 
-    #[allow(unused)]
     pub fn parse_account_id_from_raw_key(
         raw_key: &[u8],
     ) -> Result<Option<AccountId>, std::io::Error> {
@@ -349,6 +418,13 @@ pub mod trie_key_parsers {
                 col::ACCOUNT => parse_account_id_from_account_key(raw_key)?,
                 col::CONTRACT_CODE => parse_account_id_from_contract_code_key(raw_key)?,
                 col::ACCESS_KEY => parse_account_id_from_access_key_key(raw_key)?,
+                // Deduplicated contract code and the congestion control state
+                // aren't owned by any account.
+                col::CONTRACT_CODE_BY_HASH
+                | col::CONTRACT_CODE_REFCOUNT
+                | col::CONGESTION_INFO
+                | col::BUFFERED_RECEIPT_INDICES
+                | col::BUFFERED_RECEIPT => return Ok(None),
                 _ => parse_account_id_from_trie_key_with_separator(col, raw_key, col_name)?,
             };
             return Ok(Some(account_id));
@@ -397,6 +473,23 @@ pub mod trie_key_parsers {
         })
     }
 
+    pub fn parse_receiving_shard_from_buffered_receipt_key(
+        raw_key: &[u8],
+    ) -> Result<ShardId, std::io::Error> {
+        let tail = parse_account_id_prefix(col::BUFFERED_RECEIPT, raw_key)?;
+        let receiving_shard = tail.get(..size_of::<ShardId>()).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "raw key is too short for TrieKey::BufferedReceipt",
+            )
+        })?;
+        Ok(ShardId::from_le_bytes(receiving_shard.try_into().unwrap()))
+    }
+
+    pub fn get_raw_prefix_for_accounts() -> Vec<u8> {
+        vec![col::ACCOUNT]
+    }
+
     pub fn get_raw_prefix_for_access_keys(account_id: &AccountId) -> Vec<u8> {
         let mut res = Vec::with_capacity(col::ACCESS_KEY.len() * 2 + account_id.len());
         res.push(col::ACCESS_KEY);
@@ -454,6 +547,19 @@ mod tests {
         "near.a",
     ];
 
+    #[test]
+    fn test_non_delayed_receipt_columns() {
+        let columns: Vec<u8> =
+            col::NON_DELAYED_RECEIPT_COLUMNS.iter().map(|(col, _)| *col).collect();
+        let expected: Vec<u8> = (col::ACCOUNT..=col::BUFFERED_RECEIPT)
+            .filter(|col| *col != col::DELAYED_RECEIPT_INDICES && *col != col::DELAYED_RECEIPT)
+            .collect();
+        assert_eq!(columns, expected);
+        let names: std::collections::HashSet<&str> =
+            col::NON_DELAYED_RECEIPT_COLUMNS.iter().map(|(_, name)| *name).collect();
+        assert_eq!(names.len(), col::NON_DELAYED_RECEIPT_COLUMNS.len());
+    }
+
     #[test]
     fn test_key_for_account_consistency() {
         for account_id in OK_ACCOUNT_IDS.iter().map(|x| x.parse::<AccountId>().unwrap()) {
@@ -624,4 +730,34 @@ mod tests {
         let raw_key = key.to_vec();
         assert!(trie_key_parsers::parse_account_id_from_raw_key(&raw_key).unwrap().is_none());
     }
+
+    #[test]
+    fn test_key_for_contract_code_by_hash_consistency() {
+        let code_hash = crate::hash::hash(b"code");
+        for key in
+            [TrieKey::ContractCodeByHash { code_hash }, TrieKey::ContractCodeRefcount { code_hash }]
+        {
+            let raw_key = key.to_vec();
+            assert_eq!(raw_key.len(), key.len());
+            assert!(trie_key_parsers::parse_account_id_from_raw_key(&raw_key).unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn test_key_for_congestion_control_consistency() {
+        for key in [
+            TrieKey::CongestionInfo,
+            TrieKey::BufferedReceiptIndices { receiving_shard: 3 },
+            TrieKey::BufferedReceipt { receiving_shard: 3, index: 7 },
+        ] {
+            let raw_key = key.to_vec();
+            assert_eq!(raw_key.len(), key.len());
+            assert!(trie_key_parsers::parse_account_id_from_raw_key(&raw_key).unwrap().is_none());
+        }
+        let raw_key = TrieKey::BufferedReceipt { receiving_shard: 3, index: 7 }.to_vec();
+        assert_eq!(
+            trie_key_parsers::parse_receiving_shard_from_buffered_receipt_key(&raw_key).unwrap(),
+            3
+        );
+    }
 }
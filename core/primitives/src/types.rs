@@ -345,6 +345,11 @@ impl StateChanges {
                 TrieKey::PostponedReceipt { .. } => {}
                 TrieKey::DelayedReceiptIndices => {}
                 TrieKey::DelayedReceipt { .. } => {}
+                TrieKey::ContractCodeByHash { .. } => {}
+                TrieKey::ContractCodeRefcount { .. } => {}
+                TrieKey::CongestionInfo => {}
+                TrieKey::BufferedReceiptIndices { .. } => {}
+                TrieKey::BufferedReceipt { .. } => {}
             }
         }
 
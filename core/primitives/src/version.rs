@@ -151,6 +151,43 @@ pub enum ProtocolFeature {
     RejectBlocksWithOutdatedProtocolVersions,
     #[cfg(feature = "shardnet")]
     ShardnetShardLayoutUpgrade,
+    /// Store contract code once per code hash and make accounts reference it
+    /// through `Account::code_hash` instead of keeping a copy per account.
+    #[cfg(feature = "protocol_feature_contract_code_dedup")]
+    ContractCodeDeduplication,
+    /// Allow a relayer to submit and pay for a list of actions signed by
+    /// another account, see <https://github.com/near/NEPs/pull/366>.
+    #[cfg(feature = "protocol_feature_nep366_delegate_action")]
+    DelegateAction,
+    /// Track the size of the delayed receipts queue of every shard, publish it
+    /// in the chunk headers and stop sending new receipts to congested shards.
+    ///
+    /// Can't be combined with resharding: the congestion info and the buffered
+    /// receipts are keyed by the shard ids of the current layout and are not
+    /// split between the child shards.  Hence all the shard layout changes
+    /// (see `AllEpochConfig::for_protocol_version`) must happen at lower
+    /// protocol versions, which is checked by a test in `epoch_manager`.  The
+    /// state of a resharded epoch then never contains congestion control data,
+    /// even if a single upgrade enables both the new layout and this feature.
+    #[cfg(feature = "protocol_feature_congestion_control")]
+    CongestionControl,
+    /// Limit the execution of a chunk by the compute usage of its receipts
+    /// instead of their burnt gas, see `ExtCostsConfig::compute_costs` and
+    /// `RuntimeFeesConfig::compute_costs`.
+    #[cfg(feature = "protocol_feature_compute_costs")]
+    ComputeCosts,
+    /// Allow `secp256r1` (NIST P-256) access keys and transactions signed with
+    /// them, and add the `p256_verify` host function.
+    #[cfg(feature = "protocol_feature_secp256r1")]
+    Secp256r1,
+    /// Add host functions for operations on the BLS12-381 curve: point
+    /// addition, multiexp, pairing check, map-to-curve and decompression.
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381,
+    /// Allow `FunctionCallV2` access keys, which can expire at a block height
+    /// or timestamp and be limited to a number of transactions per epoch.
+    #[cfg(feature = "protocol_feature_bounded_access_keys")]
+    BoundedAccessKeys,
 }
 
 /// Both, outgoing and incoming tcp connections to peers, will be rejected if `peer's`
@@ -166,7 +203,7 @@ const STABLE_PROTOCOL_VERSION: ProtocolVersion = 57;
 /// Largest protocol version supported by the current binary.
 pub const PROTOCOL_VERSION: ProtocolVersion = if cfg!(feature = "nightly_protocol") {
     // On nightly, pick big enough version to support all features.
-    132
+    139
 } else if cfg!(feature = "shardnet") {
     102
 } else {
@@ -254,6 +291,20 @@ impl ProtocolFeature {
             }
             #[cfg(feature = "shardnet")]
             ProtocolFeature::ShardnetShardLayoutUpgrade => 102,
+            #[cfg(feature = "protocol_feature_contract_code_dedup")]
+            ProtocolFeature::ContractCodeDeduplication => 133,
+            #[cfg(feature = "protocol_feature_nep366_delegate_action")]
+            ProtocolFeature::DelegateAction => 134,
+            #[cfg(feature = "protocol_feature_congestion_control")]
+            ProtocolFeature::CongestionControl => 135,
+            #[cfg(feature = "protocol_feature_compute_costs")]
+            ProtocolFeature::ComputeCosts => 136,
+            #[cfg(feature = "protocol_feature_secp256r1")]
+            ProtocolFeature::Secp256r1 => 137,
+            #[cfg(feature = "protocol_feature_bls12381")]
+            ProtocolFeature::Bls12381 => 138,
+            #[cfg(feature = "protocol_feature_bounded_access_keys")]
+            ProtocolFeature::BoundedAccessKeys => 139,
         }
     }
 }
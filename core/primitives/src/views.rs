@@ -3,7 +3,7 @@
 //! These types should only change when we cannot avoid this. Thus, when the counterpart internal
 //! type gets changed, the view should preserve the old shape and only re-map the necessary bits
 //! from the source structure in the relevant `From<SourceStruct>` impl.
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::ops::Range;
 use std::sync::Arc;
@@ -16,13 +16,17 @@ use serde::{Deserialize, Serialize};
 use near_crypto::{PublicKey, Signature};
 use near_o11y::pretty;
 
-use crate::account::{AccessKey, AccessKeyPermission, Account, FunctionCallPermission};
+use crate::account::{
+    AccessKey, AccessKeyExpiry, AccessKeyPermission, AccessKeyUsageLimit, Account,
+    FunctionCallPermission, FunctionCallPermissionV2,
+};
 use crate::block::{Block, BlockHeader, Tip};
 use crate::block_header::{
     BlockHeaderInnerLite, BlockHeaderInnerRest, BlockHeaderInnerRestV2, BlockHeaderInnerRestV3,
     BlockHeaderV1, BlockHeaderV2, BlockHeaderV3,
 };
 use crate::challenge::{Challenge, ChallengesResult};
+use crate::congestion_info::CongestionInfo;
 use crate::contract::ContractCode;
 use crate::errors::TxExecutionError;
 use crate::hash::{hash, CryptoHash};
@@ -33,13 +37,13 @@ use crate::receipt::{ActionReceipt, DataReceipt, DataReceiver, Receipt, ReceiptE
 use crate::serialize::{base64_format, dec_format, option_base64_format};
 use crate::sharding::{
     ChunkHash, ShardChunk, ShardChunkHeader, ShardChunkHeaderInner, ShardChunkHeaderInnerV2,
-    ShardChunkHeaderV3,
+    ShardChunkHeaderInnerV3, ShardChunkHeaderV3,
 };
 use crate::transaction::{
-    Action, AddKeyAction, CreateAccountAction, DeleteAccountAction, DeleteKeyAction,
-    DeployContractAction, ExecutionMetadata, ExecutionOutcome, ExecutionOutcomeWithIdAndProof,
-    ExecutionStatus, FunctionCallAction, PartialExecutionOutcome, PartialExecutionStatus,
-    SignedTransaction, StakeAction, TransferAction,
+    Action, AddKeyAction, CreateAccountAction, DelegateAction, DeleteAccountAction,
+    DeleteKeyAction, DeployContractAction, ExecutionMetadata, ExecutionOutcome,
+    ExecutionOutcomeWithIdAndProof, ExecutionStatus, FunctionCallAction, PartialExecutionOutcome,
+    PartialExecutionStatus, SignedDelegateAction, SignedTransaction, StakeAction, TransferAction,
 };
 use crate::types::{
     AccountId, AccountWithPublicKey, Balance, BlockHeight, CompiledContractCache, EpochHeight,
@@ -146,6 +150,14 @@ pub enum AccessKeyPermissionView {
         method_names: Vec<String>,
     },
     FullAccess,
+    FunctionCallV2 {
+        #[serde(with = "dec_format")]
+        allowance: Option<Balance>,
+        receiver_id: String,
+        method_names: Vec<String>,
+        expiry: Option<AccessKeyExpiry>,
+        usage_limit: Option<AccessKeyUsageLimit>,
+    },
 }
 
 impl From<AccessKeyPermission> for AccessKeyPermissionView {
@@ -157,6 +169,15 @@ impl From<AccessKeyPermission> for AccessKeyPermissionView {
                 method_names: func_call.method_names,
             },
             AccessKeyPermission::FullAccess => AccessKeyPermissionView::FullAccess,
+            AccessKeyPermission::FunctionCallV2(permission) => {
+                AccessKeyPermissionView::FunctionCallV2 {
+                    allowance: permission.function_call.allowance,
+                    receiver_id: permission.function_call.receiver_id,
+                    method_names: permission.function_call.method_names,
+                    expiry: permission.expiry,
+                    usage_limit: permission.usage_limit,
+                }
+            }
         }
     }
 }
@@ -172,6 +193,17 @@ impl From<AccessKeyPermissionView> for AccessKeyPermission {
                 })
             }
             AccessKeyPermissionView::FullAccess => AccessKeyPermission::FullAccess,
+            AccessKeyPermissionView::FunctionCallV2 {
+                allowance,
+                receiver_id,
+                method_names,
+                expiry,
+                usage_limit,
+            } => AccessKeyPermission::FunctionCallV2(FunctionCallPermissionV2 {
+                function_call: FunctionCallPermission { allowance, receiver_id, method_names },
+                expiry,
+                usage_limit,
+            }),
         }
     }
 }
@@ -255,6 +287,8 @@ pub struct KnownPeerStateView {
     pub first_seen: i64,
     pub last_seen: i64,
     pub last_attempt: Option<(i64, String)>,
+    /// Reputation score of the peer, rounded.
+    pub score: i64,
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(deepsize::DeepSizeOf))]
@@ -347,6 +381,8 @@ pub struct PeerInfoView {
     pub last_time_received_message_millis: u64,
     pub connection_established_time_millis: u64,
     pub is_outbound_peer: bool,
+    /// Reputation score of the peer, rounded.
+    pub score: i64,
 }
 
 /// Information about a Producer: its account name, peer_id and a list of connected peers that
@@ -407,6 +443,37 @@ pub struct NetworkGraphView {
     pub edges: Vec<EdgeView>,
 }
 
+/// Status of a single connection, as seen by the PeerActor.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct PeerStatusView {
+    pub peer_id: PeerId,
+    /// Account announced by the peer, if any.
+    pub account_id: Option<AccountId>,
+    pub addr: String,
+    pub is_outbound_peer: bool,
+    pub tracked_shards: Vec<ShardId>,
+    pub archival: bool,
+    pub height: Option<BlockHeight>,
+    pub block_hash: Option<CryptoHash>,
+    pub connection_established_time_millis: u64,
+    pub last_time_received_message_millis: u64,
+    pub received_bytes_per_sec: u64,
+    pub sent_bytes_per_sec: u64,
+    pub received_bytes_per_sec_by_type: BTreeMap<String, u64>,
+    pub sent_bytes_per_sec_by_type: BTreeMap<String, u64>,
+    /// Number and total size of the messages waiting to be sent.
+    pub messages_to_send: u64,
+    pub bytes_to_send: u64,
+    /// Smoothed latency of responses to block and chunk requests.
+    pub rtt_millis: Option<u64>,
+    pub pending_requests: u64,
+    pub timed_out_requests: u64,
+    /// Reputation score of the peer, rounded.
+    pub score: i64,
+    /// Whether metrics labelled with this peer are exported.
+    pub per_peer_metrics: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct ShardSyncDownloadView {
     pub downloads: Vec<DownloadStatusView>,
@@ -914,6 +981,8 @@ pub struct ChunkHeaderView {
     pub outgoing_receipts_root: CryptoHash,
     pub tx_root: CryptoHash,
     pub validator_proposals: Vec<ValidatorStakeView>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub congestion_info: Option<CongestionInfoView>,
     pub signature: Signature,
 }
 
@@ -941,6 +1010,7 @@ impl From<ShardChunkHeader> for ChunkHeaderView {
             outgoing_receipts_root: *inner.outgoing_receipts_root(),
             tx_root: *inner.tx_root(),
             validator_proposals: inner.validator_proposals().map(Into::into).collect(),
+            congestion_info: inner.congestion_info().map(Into::into),
             signature,
         }
     }
@@ -948,8 +1018,25 @@ impl From<ShardChunkHeader> for ChunkHeaderView {
 
 impl From<ChunkHeaderView> for ShardChunkHeader {
     fn from(view: ChunkHeaderView) -> Self {
-        let mut header = ShardChunkHeaderV3 {
-            inner: ShardChunkHeaderInner::V2(ShardChunkHeaderInnerV2 {
+        let validator_proposals = view.validator_proposals.into_iter().map(Into::into).collect();
+        let inner = match view.congestion_info {
+            Some(congestion_info) => ShardChunkHeaderInner::V3(ShardChunkHeaderInnerV3 {
+                prev_block_hash: view.prev_block_hash,
+                prev_state_root: view.prev_state_root,
+                outcome_root: view.outcome_root,
+                encoded_merkle_root: view.encoded_merkle_root,
+                encoded_length: view.encoded_length,
+                height_created: view.height_created,
+                shard_id: view.shard_id,
+                gas_used: view.gas_used,
+                gas_limit: view.gas_limit,
+                balance_burnt: view.balance_burnt,
+                outgoing_receipts_root: view.outgoing_receipts_root,
+                tx_root: view.tx_root,
+                validator_proposals,
+                congestion_info: congestion_info.into(),
+            }),
+            None => ShardChunkHeaderInner::V2(ShardChunkHeaderInnerV2 {
                 prev_block_hash: view.prev_block_hash,
                 prev_state_root: view.prev_state_root,
                 outcome_root: view.outcome_root,
@@ -962,8 +1049,11 @@ impl From<ChunkHeaderView> for ShardChunkHeader {
                 balance_burnt: view.balance_burnt,
                 outgoing_receipts_root: view.outgoing_receipts_root,
                 tx_root: view.tx_root,
-                validator_proposals: view.validator_proposals.into_iter().map(Into::into).collect(),
+                validator_proposals,
             }),
+        };
+        let mut header = ShardChunkHeaderV3 {
+            inner,
             height_included: view.height_included,
             signature: view.signature,
             hash: ChunkHash::default(),
@@ -973,6 +1063,31 @@ impl From<ChunkHeaderView> for ShardChunkHeader {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CongestionInfoView {
+    #[serde(with = "dec_format")]
+    pub delayed_receipts_gas: u128,
+    pub delayed_receipts_bytes: u64,
+}
+
+impl From<CongestionInfo> for CongestionInfoView {
+    fn from(congestion_info: CongestionInfo) -> Self {
+        Self {
+            delayed_receipts_gas: congestion_info.delayed_receipts_gas,
+            delayed_receipts_bytes: congestion_info.delayed_receipts_bytes,
+        }
+    }
+}
+
+impl From<CongestionInfoView> for CongestionInfo {
+    fn from(view: CongestionInfoView) -> Self {
+        Self {
+            delayed_receipts_gas: view.delayed_receipts_gas,
+            delayed_receipts_bytes: view.delayed_receipts_bytes,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct BlockView {
     pub author: AccountId,
@@ -1051,6 +1166,10 @@ pub enum ActionView {
     DeleteAccount {
         beneficiary_id: AccountId,
     },
+    Delegate {
+        delegate_action: DelegateAction,
+        signature: Signature,
+    },
 }
 
 impl From<Action> for ActionView {
@@ -1079,6 +1198,10 @@ impl From<Action> for ActionView {
             Action::DeleteAccount(action) => {
                 ActionView::DeleteAccount { beneficiary_id: action.beneficiary_id }
             }
+            Action::Delegate(action) => ActionView::Delegate {
+                delegate_action: action.delegate_action,
+                signature: action.signature,
+            },
         }
     }
 }
@@ -1108,6 +1231,9 @@ impl TryFrom<ActionView> for Action {
             ActionView::DeleteAccount { beneficiary_id } => {
                 Action::DeleteAccount(DeleteAccountAction { beneficiary_id })
             }
+            ActionView::Delegate { delegate_action, signature } => {
+                Action::Delegate(SignedDelegateAction { delegate_action, signature })
+            }
         })
     }
 }
@@ -242,6 +242,12 @@ pub enum DBCol {
     /// *Rows*: OutcomeId (CryptoHash) || BlockHash (CryptoHash)
     /// *Column type*: ExecutionOutcomeWithProof
     TransactionResultForBlock,
+    /// Network storage: snapshot of the validated edges of the routing graph.
+    /// It is persisted periodically and on shutdown, so that the node has usable
+    /// routes right after restart.
+    /// - *Rows*: single row (empty row name)
+    /// - *Column type*: `Vec<near_network::routing::Edge>`
+    RoutingTableSnapshot,
     /// Flat state contents. Used to get `ValueRef` by trie key faster than doing a trie lookup.
     /// - *Rows*: trie key (Vec<u8>)
     /// - *Column type*: ValueRef
@@ -268,7 +274,7 @@ pub enum DBCol {
 /// Currently only used in cold storage continuous migration.
 #[derive(PartialEq, Copy, Clone, Debug, Hash, Eq, strum::EnumIter)]
 pub enum DBKeyType {
-    /// Empty row name. Used in DBCol::LastComponentNonce.
+    /// Empty row name. Used in DBCol::LastComponentNonce and DBCol::RoutingTableSnapshot.
     Empty,
     /// Set of predetermined strings. Used, for example, in DBCol::BlockMisc
     StringLiteral,
@@ -456,6 +462,7 @@ impl DBCol {
             DBCol::HeaderHashesByHeight => &[DBKeyType::BlockHeight],
             DBCol::StateChangesForSplitStates => &[DBKeyType::BlockHash, DBKeyType::ShardId],
             DBCol::TransactionResultForBlock => &[DBKeyType::OutcomeId, DBKeyType::BlockHash],
+            DBCol::RoutingTableSnapshot => &[DBKeyType::Empty],
             #[cfg(feature = "protocol_feature_flat_state")]
             DBCol::FlatState => &[DBKeyType::TrieKey],
             #[cfg(feature = "protocol_feature_flat_state")]
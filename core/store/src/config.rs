@@ -87,6 +87,50 @@ pub struct StoreConfig {
     /// Needed to create flat storage which need to happen in parallel
     /// with block processing.
     pub background_migration_threads: usize,
+
+    /// Per-column overrides of RocksDB column family options.
+    ///
+    /// Keys are column names as used in [`crate::DBCol`], e.g. `"State"` or
+    /// `"TrieChanges"`.  Options which aren’t specified keep their default
+    /// values.  Opening the database fails if an unknown column is given.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub column_options: HashMap<String, ColumnConfig>,
+}
+
+/// Tuning options of a single RocksDB column family.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ColumnConfig {
+    /// Compression algorithm used on all levels of the column.  By default LZ4
+    /// is used for upper levels and ZSTD with a dictionary for the bottommost
+    /// level.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compression: Option<Compression>,
+
+    /// Number of bits per key of the bloom filter.  Default value: 10.
+    /// Setting it to zero disables the bloom filter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bloom_filter_bits: Option<f64>,
+
+    /// Size of the block cache of the column.  Overrides
+    /// `col_state_cache_size` for [`crate::DBCol::State`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_cache_size: Option<bytesize::ByteSize>,
+
+    /// Size of a single memtable of the column.  Default value: 32MiB.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub write_buffer_size: Option<bytesize::ByteSize>,
+}
+
+/// Compression algorithm of a RocksDB column family.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    None,
+    Snappy,
+    Zlib,
+    Lz4,
+    Zstd,
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
@@ -145,8 +189,16 @@ impl StoreConfig {
         Self { max_open_files: 512, ..Self::default() }
     }
 
+    /// Returns configured overrides of RocksDB options for given column.
+    pub fn col_config(&self, col: crate::DBCol) -> Option<&ColumnConfig> {
+        self.column_options.get(<&str>::from(col))
+    }
+
     /// Returns cache size for given column.
-    pub const fn col_cache_size(&self, col: crate::DBCol) -> bytesize::ByteSize {
+    pub fn col_cache_size(&self, col: crate::DBCol) -> bytesize::ByteSize {
+        if let Some(size) = self.col_config(col).and_then(|config| config.block_cache_size) {
+            return size;
+        }
         match col {
             crate::DBCol::State => self.col_state_cache_size,
             #[cfg(feature = "protocol_feature_flat_state")]
@@ -219,6 +271,8 @@ impl Default for StoreConfig {
             // We checked that this number of threads doesn't impact
             // regular block processing significantly.
             background_migration_threads: 8,
+
+            column_options: HashMap::new(),
         }
     }
 }
@@ -144,6 +144,11 @@ pub trait Database: Sync + Send {
     /// is blocking until compaction finishes. Otherwise, this is a no-op.
     fn compact(&self) -> io::Result<()>;
 
+    /// Compact database representation of a single column.
+    ///
+    /// Like [`Self::compact`] but limited to given column.
+    fn compact_column(&self, col: DBCol) -> io::Result<()>;
+
     /// Returns statistics about the database if available.
     fn get_store_statistics(&self) -> Option<StoreStatistics>;
 }
@@ -201,6 +201,10 @@ impl<D: Database> super::Database for ColdDB<D> {
         self.cold.compact()
     }
 
+    fn compact_column(&self, col: DBCol) -> std::io::Result<()> {
+        self.cold.compact_column(col)
+    }
+
     fn flush(&self) -> std::io::Result<()> {
         self.cold.flush()
     }
@@ -7,7 +7,7 @@ use ::rocksdb::{
 use strum::IntoEnumIterator;
 use tracing::warn;
 
-use crate::config::Mode;
+use crate::config::{ColumnConfig, Compression, Mode};
 use crate::db::{refcount, DBIterator, DBOp, DBSlice, DBTransaction, Database, StatsValue};
 use crate::{metadata, metrics, DBCol, StoreConfig, StoreStatistics, Temperature};
 
@@ -17,8 +17,11 @@ pub(crate) mod snapshot;
 /// List of integer RocskDB properties we’re reading when collecting statistics.
 ///
 /// In the end, they are exported as Prometheus metrics.
-const CF_PROPERTY_NAMES: [&'static std::ffi::CStr; 1] =
-    [::rocksdb::properties::LIVE_SST_FILES_SIZE];
+const CF_PROPERTY_NAMES: [&'static std::ffi::CStr; 3] = [
+    ::rocksdb::properties::LIVE_SST_FILES_SIZE,
+    ::rocksdb::properties::TOTAL_SST_FILES_SIZE,
+    ::rocksdb::properties::ESTIMATE_PENDING_COMPACTION_BYTES,
+];
 
 pub struct RocksDB {
     db: DB,
@@ -107,6 +110,7 @@ impl RocksDB {
         temp: Temperature,
         columns: &[DBCol],
     ) -> io::Result<(DB, Options)> {
+        check_column_options(store_config)?;
         let options = rocksdb_options(store_config, mode);
         let cf_descriptors = columns
             .iter()
@@ -321,13 +325,18 @@ impl Database for RocksDB {
     }
 
     fn compact(&self) -> io::Result<()> {
-        let none = Option::<&[u8]>::None;
         for col in DBCol::iter() {
-            self.db.compact_range_cf(self.cf_handle(col)?, none, none);
+            self.compact_column(col)?;
         }
         Ok(())
     }
 
+    fn compact_column(&self, col: DBCol) -> io::Result<()> {
+        let none = Option::<&[u8]>::None;
+        self.db.compact_range_cf(self.cf_handle(col)?, none, none);
+        Ok(())
+    }
+
     fn flush(&self) -> io::Result<()> {
         // Need to iterator over all CFs because the normal `flush()` only
         // flushes the default column family.
@@ -407,6 +416,7 @@ fn rocksdb_read_options() -> ReadOptions {
 fn rocksdb_block_based_options(
     block_size: bytesize::ByteSize,
     cache_size: bytesize::ByteSize,
+    bloom_filter_bits: f64,
 ) -> BlockBasedOptions {
     let mut block_opts = BlockBasedOptions::default();
     block_opts.set_block_size(block_size.as_u64().try_into().unwrap());
@@ -415,7 +425,9 @@ fn rocksdb_block_based_options(
         .set_block_cache(&Cache::new_lru_cache(cache_size.as_u64().try_into().unwrap()).unwrap());
     block_opts.set_pin_l0_filter_and_index_blocks_in_cache(true);
     block_opts.set_cache_index_and_filter_blocks(true);
-    block_opts.set_bloom_filter(10.0, true);
+    if bloom_filter_bits > 0.0 {
+        block_opts.set_bloom_filter(bloom_filter_bits, true);
+    }
     block_opts
 }
 
@@ -423,10 +435,13 @@ fn rocksdb_column_options(col: DBCol, store_config: &StoreConfig, temp: Temperat
     let mut opts = Options::default();
     set_compression_options(&mut opts);
     opts.set_level_compaction_dynamic_level_bytes(true);
+    let col_config = store_config.col_config(col);
     let cache_size = store_config.col_cache_size(col);
+    let bloom_filter_bits = col_config.and_then(|config| config.bloom_filter_bits).unwrap_or(10.0);
     opts.set_block_based_table_factory(&rocksdb_block_based_options(
         store_config.block_size,
         cache_size,
+        bloom_filter_bits,
     ));
 
     // Note that this function changes a lot of rustdb parameters including:
@@ -445,6 +460,9 @@ fn rocksdb_column_options(col: DBCol, store_config: &StoreConfig, temp: Temperat
     opts.optimize_level_style_compaction(memtable_memory_budget);
 
     opts.set_target_file_size_base(64 * bytesize::MIB);
+    if let Some(col_config) = col_config {
+        set_column_config_overrides(&mut opts, col_config);
+    }
     if temp == Temperature::Hot && col.is_rc() {
         opts.set_merge_operator("refcount merge", RocksDB::refcount_merge, RocksDB::refcount_merge);
         opts.set_compaction_filter("empty value filter", RocksDB::empty_value_compaction_filter);
@@ -452,6 +470,43 @@ fn rocksdb_column_options(col: DBCol, store_config: &StoreConfig, temp: Temperat
     opts
 }
 
+/// Applies options configured for a column in `store.column_options`.
+///
+/// Must be called after [`Options::optimize_level_style_compaction`] since
+/// that method overrides write buffer size and per-level compression.
+fn set_column_config_overrides(opts: &mut Options, col_config: &ColumnConfig) {
+    if let Some(compression) = col_config.compression {
+        let compression = match compression {
+            Compression::None => rocksdb::DBCompressionType::None,
+            Compression::Snappy => rocksdb::DBCompressionType::Snappy,
+            Compression::Zlib => rocksdb::DBCompressionType::Zlib,
+            Compression::Lz4 => rocksdb::DBCompressionType::Lz4,
+            Compression::Zstd => rocksdb::DBCompressionType::Zstd,
+        };
+        // Per-level compression takes precedence over compression type so it
+        // needs to be cleared for the override to take effect.
+        opts.set_compression_per_level(&[]);
+        opts.set_compression_type(compression);
+        opts.set_bottommost_compression_type(compression);
+    }
+    if let Some(write_buffer_size) = col_config.write_buffer_size {
+        opts.set_write_buffer_size(write_buffer_size.as_u64().try_into().unwrap());
+    }
+}
+
+/// Verifies that `store.column_options` refers to existing columns only.
+fn check_column_options(store_config: &StoreConfig) -> io::Result<()> {
+    for name in store_config.column_options.keys() {
+        if !DBCol::iter().any(|col| <&str>::from(col) == name.as_str()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("store.column_options: unknown column {name}"),
+            ));
+        }
+    }
+    Ok(())
+}
+
 fn set_compression_options(opts: &mut Options) {
     opts.set_compression_type(rocksdb::DBCompressionType::Lz4);
     opts.set_bottommost_compression_type(rocksdb::DBCompressionType::Zstd);
@@ -707,6 +762,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_column_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = StoreConfig::test_config();
+        config.column_options.insert(
+            "State".to_string(),
+            ColumnConfig {
+                compression: Some(Compression::Zstd),
+                bloom_filter_bits: Some(0.0),
+                block_cache_size: Some(bytesize::ByteSize::mib(8)),
+                write_buffer_size: Some(bytesize::ByteSize::mib(4)),
+            },
+        );
+        assert_eq!(config.col_cache_size(DBCol::State), bytesize::ByteSize::mib(8));
+        assert_eq!(config.col_cache_size(DBCol::Block), bytesize::ByteSize::mib(32));
+        let db = RocksDB::open(dir.path(), &config, Mode::ReadWrite, Temperature::Hot).unwrap();
+        let mut transaction = DBTransaction::new();
+        transaction.set(DBCol::Block, vec![1], vec![2]);
+        db.write(transaction).unwrap();
+        // single_thread_rocksdb makes compact hang forever
+        if !cfg!(feature = "single_thread_rocksdb") {
+            db.compact_column(DBCol::Block).unwrap();
+        }
+        assert_eq!(db.get_raw_bytes(DBCol::Block, &[1]).unwrap().as_deref(), Some(&[2][..]));
+        drop(db);
+
+        config.column_options.insert("NoSuchColumn".to_string(), ColumnConfig::default());
+        let err = RocksDB::open(dir.path(), &config, Mode::ReadWrite, Temperature::Hot);
+        assert_eq!(err.err().map(|err| err.kind()), Some(io::ErrorKind::InvalidInput));
+    }
+
     #[test]
     fn test_parse_statistics() {
         let statistics = "rocksdb.cold.file.read.count COUNT : 999\n\
@@ -93,6 +93,10 @@ impl Database for TestDB {
         Ok(())
     }
 
+    fn compact_column(&self, _col: DBCol) -> io::Result<()> {
+        Ok(())
+    }
+
     fn get_store_statistics(&self) -> Option<StoreStatistics> {
         None
     }
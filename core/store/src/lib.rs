@@ -31,9 +31,9 @@ use crate::db::{
 pub use crate::trie::iterator::{TrieIterator, TrieTraversalItem};
 pub use crate::trie::update::{TrieUpdate, TrieUpdateIterator, TrieUpdateValuePtr};
 pub use crate::trie::{
-    estimator, split_state, ApplyStatePartResult, KeyForStateChanges, KeyLookupMode, NibbleSlice,
-    PartialStorage, PrefetchApi, RawTrieNode, RawTrieNodeWithSize, ShardTries, Trie, TrieAccess,
-    TrieCache, TrieCachingStorage, TrieChanges, TrieConfig, TrieDBStorage, TrieStorage,
+    estimator, split_state, verify, ApplyStatePartResult, KeyForStateChanges, KeyLookupMode,
+    NibbleSlice, PartialStorage, PrefetchApi, RawTrieNode, RawTrieNodeWithSize, ShardTries, Trie,
+    TrieAccess, TrieCache, TrieCachingStorage, TrieChanges, TrieConfig, TrieDBStorage, TrieStorage,
     WrappedTrieChanges,
 };
 pub use flat_state::FlatStateDelta;
@@ -51,7 +51,7 @@ mod opener;
 pub mod test_utils;
 mod trie;
 
-pub use crate::config::{Mode, StoreConfig};
+pub use crate::config::{ColumnConfig, Compression, Mode, StoreConfig};
 pub use crate::opener::{StoreMigrator, StoreOpener, StoreOpenerError};
 
 /// Specifies temperature of a storage.
@@ -288,6 +288,15 @@ impl Store {
         self.storage.iter_raw_bytes(column)
     }
 
+    /// Fetches raw value from the database.
+    ///
+    /// Like [`Self::iter_raw_bytes`] this includes rc in the value for rc
+    /// columns and is meant for auxilary code only, e.g. store validation
+    /// checking reference counts of individual entries.
+    pub fn get_raw_bytes(&self, column: DBCol, key: &[u8]) -> io::Result<Option<DBSlice<'_>>> {
+        self.storage.get_raw_bytes(column, key)
+    }
+
     pub fn iter_prefix<'a>(&'a self, column: DBCol, key_prefix: &'a [u8]) -> DBIterator<'a> {
         self.storage.iter_prefix(column, key_prefix)
     }
@@ -347,6 +356,11 @@ impl Store {
         self.storage.compact()
     }
 
+    /// Blocking compaction request of a single column if supported by storage.
+    pub fn compact_column(&self, col: DBCol) -> io::Result<()> {
+        self.storage.compact_column(col)
+    }
+
     pub fn get_store_statistics(&self) -> Option<StoreStatistics> {
         self.storage.get_store_statistics()
     }
@@ -750,13 +764,109 @@ pub fn set_code(state_update: &mut TrieUpdate, account_id: AccountId, code: &Con
     state_update.set(TrieKey::ContractCode { account_id }, code.code().to_vec());
 }
 
+/// Stores `code` once under its hash and makes `account_id` reference it
+/// instead of the code it used to reference by `prev_code_hash`.
+///
+/// Used since `ProtocolFeature::ContractCodeDeduplication`.  The code blob is
+/// only written when no other account references it yet.
+///
+/// Every account referencing the code pays storage staking for the full code
+/// size, as if it stored its own copy.  The shared code and its refcount are
+/// not charged to anyone on top of that.  The refcount is per shard: when a
+/// shard is split, every new shard keeps the code referenced by its own
+/// accounts and counts only those.
+pub fn set_code_by_hash(
+    state_update: &mut TrieUpdate,
+    account_id: &AccountId,
+    prev_code_hash: CryptoHash,
+    code: &ContractCode,
+) -> Result<(), StorageError> {
+    remove_code(state_update, account_id, prev_code_hash)?;
+    let code_hash = *code.hash();
+    let refcount_key = TrieKey::ContractCodeRefcount { code_hash };
+    let refcount: u64 = get(state_update, &refcount_key)?.unwrap_or_default();
+    if refcount == 0 {
+        state_update.set(TrieKey::ContractCodeByHash { code_hash }, code.code().to_vec());
+    }
+    set(state_update, refcount_key, &(refcount + 1));
+    Ok(())
+}
+
+/// Drops the reference `account_id` holds to its contract code.
+///
+/// Handles both code stored per account under `TrieKey::ContractCode` and
+/// code deduplicated by `code_hash`.  Deduplicated code is removed once the
+/// last account referencing it goes away.
+pub fn remove_code(
+    state_update: &mut TrieUpdate,
+    account_id: &AccountId,
+    code_hash: CryptoHash,
+) -> Result<(), StorageError> {
+    let key = TrieKey::ContractCode { account_id: account_id.clone() };
+    if state_update.get_ref(&key, KeyLookupMode::Trie)?.is_some() {
+        state_update.remove(key);
+        return Ok(());
+    }
+    if code_hash == CryptoHash::default() {
+        return Ok(());
+    }
+    let refcount_key = TrieKey::ContractCodeRefcount { code_hash };
+    match get::<u64>(state_update, &refcount_key)? {
+        None => {}
+        Some(refcount) if refcount > 1 => set(state_update, refcount_key, &(refcount - 1)),
+        Some(_) => {
+            state_update.remove(refcount_key);
+            state_update.remove(TrieKey::ContractCodeByHash { code_hash });
+        }
+    }
+    Ok(())
+}
+
+/// Returns contract code of `account_id`.
+///
+/// Code stored per account takes precedence.  Otherwise, if `code_hash` is
+/// given, the code deduplicated under that hash is returned.
 pub fn get_code(
     trie: &dyn TrieAccess,
     account_id: &AccountId,
     code_hash: Option<CryptoHash>,
 ) -> Result<Option<ContractCode>, StorageError> {
     let key = TrieKey::ContractCode { account_id: account_id.clone() };
-    trie.get(&key).map(|opt| opt.map(|code| ContractCode::new(code, code_hash)))
+    if let Some(code) = trie.get(&key)? {
+        return Ok(Some(ContractCode::new(code, code_hash)));
+    }
+    match code_hash {
+        Some(code_hash) if code_hash != CryptoHash::default() => get_code_by_hash(trie, code_hash),
+        _ => Ok(None),
+    }
+}
+
+/// Returns contract code of `account_id` if the account references code
+/// deduplicated under `code_hash` rather than storing its own copy.
+///
+/// Such code doesn't show up as a per-account record when iterating over the
+/// trie, so tools reconstructing state records need to look it up explicitly.
+pub fn get_deduplicated_code(
+    trie: &dyn TrieAccess,
+    account_id: &AccountId,
+    code_hash: CryptoHash,
+) -> Result<Option<ContractCode>, StorageError> {
+    if code_hash == CryptoHash::default() {
+        return Ok(None);
+    }
+    if trie.get(&TrieKey::ContractCode { account_id: account_id.clone() })?.is_some() {
+        return Ok(None);
+    }
+    get_code_by_hash(trie, code_hash)
+}
+
+/// Returns contract code deduplicated under `code_hash`, if any.
+pub fn get_code_by_hash(
+    trie: &dyn TrieAccess,
+    code_hash: CryptoHash,
+) -> Result<Option<ContractCode>, StorageError> {
+    let key = TrieKey::ContractCodeByHash { code_hash };
+    trie.get(&key).map(|opt| opt.map(|code| ContractCode::new(code, Some(code_hash))))
 }
 
 /// Removes account, code and all access keys associated to it.
@@ -992,4 +1102,41 @@ mod tests {
         assert_eq!(Some(record), cache.get(&key).unwrap());
         assert_eq!(true, cache.has(&key).unwrap());
     }
+
+    /// Check that deduplicated contract code is shared between accounts and
+    /// removed together with its last reference.
+    #[test]
+    fn test_code_by_hash_refcount() {
+        use near_primitives::contract::ContractCode;
+        use near_primitives::shard_layout::ShardUId;
+        use near_primitives::trie_key::TrieKey;
+        use near_primitives::types::AccountId;
+
+        let tries = crate::test_utils::create_tries();
+        let mut state_update =
+            tries.new_trie_update(ShardUId::single_shard(), CryptoHash::default());
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+        let code = ContractCode::new(b"code".to_vec(), None);
+        let code_hash = *code.hash();
+        let refcount_key = TrieKey::ContractCodeRefcount { code_hash };
+
+        super::set_code_by_hash(&mut state_update, &alice, CryptoHash::default(), &code).unwrap();
+        super::set_code_by_hash(&mut state_update, &bob, CryptoHash::default(), &code).unwrap();
+        assert_eq!(Some(2u64), super::get(&state_update, &refcount_key).unwrap());
+        for account_id in [&alice, &bob] {
+            let stored = super::get_code(&state_update, account_id, Some(code_hash)).unwrap();
+            assert_eq!(stored.unwrap().code(), code.code());
+        }
+
+        // Redeploying the same code keeps a single reference per account.
+        super::set_code_by_hash(&mut state_update, &alice, code_hash, &code).unwrap();
+        assert_eq!(Some(2u64), super::get(&state_update, &refcount_key).unwrap());
+
+        super::remove_code(&mut state_update, &alice, code_hash).unwrap();
+        assert_eq!(Some(1u64), super::get(&state_update, &refcount_key).unwrap());
+        super::remove_code(&mut state_update, &bob, code_hash).unwrap();
+        assert_eq!(None, super::get::<u64>(&state_update, &refcount_key).unwrap());
+        assert!(super::get_code_by_hash(&state_update, code_hash).unwrap().is_none());
+    }
 }
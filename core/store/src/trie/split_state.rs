@@ -1,20 +1,25 @@
 use crate::trie::iterator::TrieItem;
 use crate::{
-    get, get_delayed_receipt_indices, set, ShardTries, StoreUpdate, Trie, TrieChanges, TrieUpdate,
+    get, get_account, get_code_by_hash, get_delayed_receipt_indices, set, ShardTries, StoreUpdate,
+    Trie, TrieAccess, TrieChanges, TrieUpdate,
 };
 use borsh::BorshDeserialize;
 use bytesize::ByteSize;
 use near_primitives::account::id::AccountId;
 use near_primitives::errors::StorageError;
+use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::Receipt;
 use near_primitives::shard_layout::ShardUId;
 use near_primitives::state_part::PartId;
-use near_primitives::trie_key::trie_key_parsers::parse_account_id_from_raw_key;
+use near_primitives::state_record::{is_congestion_control_key, is_shared_contract_code_key};
+use near_primitives::trie_key::trie_key_parsers::{
+    get_raw_prefix_for_accounts, parse_account_id_from_account_key, parse_account_id_from_raw_key,
+};
 use near_primitives::trie_key::TrieKey;
 use near_primitives::types::{
     ConsolidatedStateChange, StateChangeCause, StateChangesForSplitStates, StateRoot,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 impl Trie {
     /// Computes the set of trie items (nodes with keys and values) for a state part.
@@ -48,6 +53,23 @@ impl ShardTries {
         account_id_to_shard_id: &dyn Fn(&AccountId) -> ShardUId,
     ) -> Result<HashMap<ShardUId, TrieChanges>, StorageError> {
         let mut trie_updates: HashMap<_, _> = self.get_trie_updates(state_roots);
+        // Deduplicated contract code is referenced by accounts that may end
+        // up in different split shards, so its refcount is tracked per split
+        // shard from the code references of the accounts changed here.
+        let mut code_references = Vec::new();
+        let mut accounts = HashSet::new();
+        for ConsolidatedStateChange { trie_key, .. } in &changes.changes {
+            if let TrieKey::Account { account_id } | TrieKey::ContractCode { account_id } = trie_key
+            {
+                if accounts.insert(account_id.clone()) {
+                    let shard_uid = account_id_to_shard_id(account_id);
+                    let prev_code_hash =
+                        get_contract_code_reference(&trie_updates[&shard_uid], account_id)?;
+                    code_references.push((account_id.clone(), shard_uid, prev_code_hash));
+                }
+            }
+        }
+        let mut new_contract_code = HashMap::new();
         let mut insert_receipts = Vec::new();
         for ConsolidatedStateChange { trie_key, value } in changes.changes {
             match &trie_key {
@@ -81,12 +103,88 @@ impl ShardTries {
                         None => trie_update.remove(trie_key),
                     }
                 }
+                // The refcounts of the parent shard don't apply to the split
+                // shards, they are updated below instead.
+                TrieKey::ContractCodeByHash { code_hash } => {
+                    if let Some(value) = value {
+                        new_contract_code.insert(*code_hash, value);
+                    }
+                }
+                TrieKey::ContractCodeRefcount { .. } => {}
+                // Congestion control doesn't support resharding: the congestion
+                // info and the buffered receipts, which are keyed by the shard
+                // ids of the old layout, can't be split. The protocol features
+                // are gated so that they are never combined, see
+                // `ProtocolFeature::CongestionControl`.
+                TrieKey::CongestionInfo
+                | TrieKey::BufferedReceiptIndices { .. }
+                | TrieKey::BufferedReceipt { .. } => {
+                    return Err(resharding_with_congestion_control_error(&trie_key));
+                }
             }
         }
         for (_, update) in trie_updates.iter_mut() {
             update.commit(StateChangeCause::Resharding);
         }
 
+        let mut refcount_changes: HashMap<_, i64> = HashMap::new();
+        for (account_id, shard_uid, prev_code_hash) in code_references {
+            let code_hash = get_contract_code_reference(&trie_updates[&shard_uid], &account_id)?;
+            if code_hash != prev_code_hash {
+                if let Some(prev_code_hash) = prev_code_hash {
+                    *refcount_changes.entry((shard_uid, prev_code_hash)).or_default() -= 1;
+                }
+                if let Some(code_hash) = code_hash {
+                    *refcount_changes.entry((shard_uid, code_hash)).or_default() += 1;
+                }
+            }
+        }
+        let mut contract_code_updates = Vec::new();
+        for ((shard_uid, code_hash), change) in refcount_changes {
+            let trie_update = &trie_updates[&shard_uid];
+            let refcount: u64 =
+                get(trie_update, &TrieKey::ContractCodeRefcount { code_hash })?.unwrap_or_default();
+            let new_refcount = refcount as i64 + change;
+            if new_refcount <= 0 {
+                contract_code_updates.push((shard_uid, code_hash, None));
+                continue;
+            }
+            let code = if refcount > 0 {
+                None
+            } else if let Some(code) = new_contract_code.get(&code_hash) {
+                Some(code.clone())
+            } else {
+                // The code was in the parent shard before this change, so
+                // one of the split shards holds an account referencing it.
+                let mut code = None;
+                for trie_update in trie_updates.values() {
+                    code = get_code_by_hash(&**trie_update.trie(), code_hash)?;
+                    if code.is_some() {
+                        break;
+                    }
+                }
+                let code = code.ok_or_else(|| missing_contract_code_error(&code_hash))?;
+                Some(code.code().to_vec())
+            };
+            contract_code_updates.push((shard_uid, code_hash, Some((new_refcount as u64, code))));
+        }
+        for (shard_uid, code_hash, update) in contract_code_updates {
+            let trie_update = trie_updates.get_mut(&shard_uid).unwrap();
+            match update {
+                Some((refcount, code)) => {
+                    if let Some(code) = code {
+                        trie_update.set(TrieKey::ContractCodeByHash { code_hash }, code);
+                    }
+                    set(trie_update, TrieKey::ContractCodeRefcount { code_hash }, &refcount);
+                }
+                None => {
+                    trie_update.remove(TrieKey::ContractCodeRefcount { code_hash });
+                    trie_update.remove(TrieKey::ContractCodeByHash { code_hash });
+                }
+            }
+            trie_update.commit(StateChangeCause::Resharding);
+        }
+
         insert_receipts.sort_by_key(|it| it.0);
 
         let insert_receipts: Vec<_> =
@@ -144,6 +242,15 @@ impl ShardTries {
     ) -> Result<(StoreUpdate, HashMap<ShardUId, StateRoot>), StorageError> {
         let mut changes_by_shard: HashMap<_, Vec<_>> = HashMap::new();
         for (raw_key, value) in values.into_iter() {
+            if is_congestion_control_key(&raw_key) {
+                return Err(resharding_with_congestion_control_error(&raw_key));
+            }
+            if is_shared_contract_code_key(&raw_key) {
+                // Deduplicated contract code is added by
+                // `add_contract_code_to_split_states` once all accounts are in
+                // the split states, as its refcount depends on the shard.
+                continue;
+            }
             if let Some(new_shard_uid) = key_to_shard_id(&raw_key)? {
                 changes_by_shard.entry(new_shard_uid).or_default().push((raw_key, value));
             }
@@ -160,6 +267,51 @@ impl ShardTries {
         Ok((store_update, new_state_roots))
     }
 
+    /// Adds deduplicated contract code from `parent_trie` to the states built
+    /// by `add_values_to_split_states`.  Every split shard gets the code
+    /// referenced by its own accounts, with the refcount set to the number of
+    /// those accounts.  The accounts of the parent shard are read in a single
+    /// pass.
+    /// Returns `store_update` and the new state_roots for split states
+    pub fn add_contract_code_to_split_states(
+        &self,
+        state_roots: &HashMap<ShardUId, StateRoot>,
+        parent_trie: &Trie,
+        account_id_to_shard_id: &dyn Fn(&AccountId) -> ShardUId,
+    ) -> Result<(StoreUpdate, HashMap<ShardUId, StateRoot>), StorageError> {
+        let mut refcounts: HashMap<ShardUId, HashMap<CryptoHash, u64>> = HashMap::new();
+        let mut iter = parent_trie.iter()?;
+        iter.seek_prefix(&get_raw_prefix_for_accounts())?;
+        for item in iter {
+            let (raw_key, _) = item?;
+            let account_id = parse_account_id_from_account_key(&raw_key).map_err(|e| {
+                let err = format!("error parsing account id from key {:?}: {:?}", raw_key, e);
+                StorageError::StorageInconsistentState(err)
+            })?;
+            if let Some(code_hash) = get_contract_code_reference(parent_trie, &account_id)? {
+                let shard_uid = account_id_to_shard_id(&account_id);
+                *refcounts.entry(shard_uid).or_default().entry(code_hash).or_default() += 1;
+            }
+        }
+
+        let mut trie_updates = self.get_trie_updates(state_roots);
+        for (shard_uid, trie_update) in trie_updates.iter_mut() {
+            let refcounts = refcounts.remove(shard_uid).unwrap_or_default();
+            for (code_hash, refcount) in refcounts {
+                // Accounts can have a code hash without code, e.g. from
+                // genesis records.  The parent has no refcount for those.
+                let code = match get_code_by_hash(parent_trie, code_hash)? {
+                    Some(code) => code,
+                    None => continue,
+                };
+                trie_update.set(TrieKey::ContractCodeByHash { code_hash }, code.code().to_vec());
+                set(trie_update, TrieKey::ContractCodeRefcount { code_hash }, &refcount);
+            }
+            trie_update.commit(StateChangeCause::Resharding);
+        }
+        self.finalize_and_apply_trie_updates(trie_updates)
+    }
+
     fn get_trie_updates(
         &self,
         state_roots: &HashMap<ShardUId, StateRoot>,
@@ -203,6 +355,38 @@ impl ShardTries {
     }
 }
 
+/// Returns the hash of the deduplicated contract code `account_id` references,
+/// i.e. unless the account has no code or stores its own copy of it.
+fn get_contract_code_reference(
+    trie: &dyn TrieAccess,
+    account_id: &AccountId,
+) -> Result<Option<CryptoHash>, StorageError> {
+    let code_hash = match get_account(trie, account_id)? {
+        Some(account) => account.code_hash(),
+        None => return Ok(None),
+    };
+    if code_hash == CryptoHash::default()
+        || trie.get(&TrieKey::ContractCode { account_id: account_id.clone() })?.is_some()
+    {
+        return Ok(None);
+    }
+    Ok(Some(code_hash))
+}
+
+fn missing_contract_code_error(code_hash: &CryptoHash) -> StorageError {
+    StorageError::StorageInconsistentState(format!(
+        "contract code {} is referenced but not stored",
+        code_hash
+    ))
+}
+
+fn resharding_with_congestion_control_error(key: &dyn std::fmt::Debug) -> StorageError {
+    StorageError::StorageInconsistentState(format!(
+        "resharding is not supported with congestion control, found key {:?}",
+        key
+    ))
+}
+
 fn apply_delayed_receipts_to_split_states_impl(
     trie_updates: &mut HashMap<ShardUId, TrieUpdate>,
     insert_receipts: &[Receipt],
@@ -336,7 +520,7 @@ mod tests {
     use near_primitives::trie_key::trie_key_parsers::parse_account_id_from_raw_key;
     use near_primitives::trie_key::TrieKey;
     use near_primitives::types::{
-        NumShards, StateChangeCause, StateChangesForSplitStates, StateRoot,
+        ConsolidatedStateChange, NumShards, StateChangeCause, StateChangesForSplitStates, StateRoot,
     };
     use rand::seq::SliceRandom;
     use rand::Rng;
@@ -502,6 +686,125 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_split_states_with_congestion_control() {
+        let tries = create_tries();
+        let state_roots: HashMap<_, _> =
+            (0..2).map(|x| (ShardUId { version: 1, shard_id: x }, Trie::EMPTY_ROOT)).collect();
+        for key in [
+            TrieKey::CongestionInfo,
+            TrieKey::BufferedReceiptIndices { receiving_shard: 0 },
+            TrieKey::BufferedReceipt { receiving_shard: 0, index: 0 },
+        ] {
+            let values = vec![(key.to_vec(), Some(vec![1]))];
+            assert!(tries
+                .add_values_to_split_states(&state_roots, values, &|_| ShardUId::single_shard())
+                .is_err());
+        }
+    }
+
+    /// Deduplicated contract code ends up in the split shards whose accounts
+    /// reference it, with the refcount counting only those accounts.
+    #[test]
+    fn test_split_states_with_deduplicated_contract_code() {
+        let tries = create_tries();
+        let code1 = b"code1".to_vec();
+        let code2 = b"code2".to_vec();
+        let (hash1, hash2) = (hash(&code1), hash(&code2));
+        let account = |code_hash| Account::new(0, 0, code_hash, 0).try_to_vec().unwrap();
+        let account_key = |account_id: &str| {
+            TrieKey::Account { account_id: account_id.parse().unwrap() }.to_vec()
+        };
+        let refcount = |refcount: u64| Some(refcount.try_to_vec().unwrap());
+        let changes = vec![
+            (account_key("alice"), Some(account(hash1))),
+            (account_key("bob"), Some(account(hash1))),
+            (account_key("carol"), Some(account(hash2))),
+            (TrieKey::ContractCodeByHash { code_hash: hash1 }.to_vec(), Some(code1.clone())),
+            (TrieKey::ContractCodeRefcount { code_hash: hash1 }.to_vec(), refcount(2)),
+            (TrieKey::ContractCodeByHash { code_hash: hash2 }.to_vec(), Some(code2.clone())),
+            (TrieKey::ContractCodeRefcount { code_hash: hash2 }.to_vec(), refcount(1)),
+        ];
+        let state_root =
+            test_populate_trie(&tries, &Trie::EMPTY_ROOT, ShardUId::single_shard(), changes);
+        let trie = tries.get_view_trie_for_shard(ShardUId::single_shard(), state_root);
+
+        let (shard0, shard1) =
+            (ShardUId { version: 1, shard_id: 0 }, ShardUId { version: 1, shard_id: 1 });
+        let account_id_to_shard_id = |account_id: &AccountId| {
+            if account_id.as_ref() < "b" || account_id.as_ref() >= "d" {
+                shard0
+            } else {
+                shard1
+            }
+        };
+        let state_roots: HashMap<_, _> =
+            [shard0, shard1].into_iter().map(|shard_uid| (shard_uid, Trie::EMPTY_ROOT)).collect();
+        let trie_items = trie.iter().unwrap().map(|item| {
+            let (key, value) = item.unwrap();
+            (key, Some(value))
+        });
+        let (store_update, state_roots) = tries
+            .add_values_to_split_states(&state_roots, trie_items.collect(), &account_id_to_shard_id)
+            .unwrap();
+        store_update.commit().unwrap();
+        let (store_update, state_roots) = tries
+            .add_contract_code_to_split_states(&state_roots, &trie, &account_id_to_shard_id)
+            .unwrap();
+        store_update.commit().unwrap();
+
+        let get_contract_code = |state_roots: &HashMap<ShardUId, StateRoot>, shard_uid| {
+            let state_update = tries.new_trie_update(shard_uid, state_roots[&shard_uid]);
+            let mut contract_code = vec![];
+            for code_hash in [hash1, hash2] {
+                let refcount: Option<u64> =
+                    get(&state_update, &TrieKey::ContractCodeRefcount { code_hash }).unwrap();
+                let code = state_update.get(&TrieKey::ContractCodeByHash { code_hash }).unwrap();
+                assert_eq!(refcount.is_some(), code.is_some());
+                if let Some(refcount) = refcount {
+                    contract_code.push((code_hash, refcount, code.unwrap()));
+                }
+            }
+            contract_code
+        };
+        assert_eq!(get_contract_code(&state_roots, shard0), vec![(hash1, 1, code1.clone())]);
+        assert_eq!(
+            get_contract_code(&state_roots, shard1),
+            vec![(hash1, 1, code1), (hash2, 1, code2.clone())]
+        );
+
+        // alice is deleted, bob switches to code2 and dave, who is in the
+        // shard without code2, deploys code2 as well.
+        let changes = vec![
+            (TrieKey::Account { account_id: "alice".parse().unwrap() }, None),
+            (TrieKey::Account { account_id: "bob".parse().unwrap() }, Some(account(hash2))),
+            (TrieKey::Account { account_id: "dave".parse().unwrap() }, Some(account(hash2))),
+            (TrieKey::ContractCodeByHash { code_hash: hash1 }, None),
+            (TrieKey::ContractCodeRefcount { code_hash: hash1 }, None),
+            (TrieKey::ContractCodeRefcount { code_hash: hash2 }, refcount(3)),
+        ];
+        let changes = StateChangesForSplitStates {
+            changes: changes
+                .into_iter()
+                .map(|(trie_key, value)| ConsolidatedStateChange { trie_key, value })
+                .collect(),
+            processed_delayed_receipts: vec![],
+        };
+        let trie_changes = tries
+            .apply_state_changes_to_split_states(&state_roots, changes, &account_id_to_shard_id)
+            .unwrap();
+        let mut store_update = tries.store_update();
+        let state_roots: HashMap<_, _> = trie_changes
+            .into_iter()
+            .map(|(shard_uid, trie_changes)| {
+                (shard_uid, tries.apply_all(&trie_changes, shard_uid, &mut store_update))
+            })
+            .collect();
+        store_update.commit().unwrap();
+        assert_eq!(get_contract_code(&state_roots, shard0), vec![(hash2, 1, code2.clone())]);
+        assert_eq!(get_contract_code(&state_roots, shard1), vec![(hash2, 2, code2)]);
+    }
+
     #[test]
     fn test_get_delayed_receipts() {
         let mut rng = rand::thread_rng();
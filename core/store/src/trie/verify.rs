@@ -0,0 +1,407 @@
+//! Offline verification of a state trie stored in the database.
+//!
+//! [`verify_state_root`] walks the whole trie under given state root reading
+//! raw `State` column entries, i.e. including their reference counts, and
+//! recomputes every node hash and memory usage bottom-up.  Unlike regular trie
+//! reads it doesn’t stop at the first problem but reports all of them so that
+//! the extent of database corruption can be assessed.
+//!
+//! The same node or value may be referenced many times within a trie (e.g.
+//! identical values stored under different keys).  Such shared entries are
+//! verified only once and their reference count is checked against the number
+//! of references found.  To do so, hashes of all the visited entries are kept
+//! in memory, which takes 50 to 100 bytes per distinct node and value
+//! depending on hash map occupancy, e.g. up to 10 GB for a trie with 100
+//! million nodes and values.  The map is split
+//! into [`VISITED_SHARDS`] parts by the first byte of the hash so that the
+//! threads walking the trie in parallel rarely contend for the same lock.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::StateRoot;
+
+use crate::db::refcount::decode_value_with_rc;
+use crate::db::Database;
+use crate::trie::trie_storage::TrieCachingStorage;
+use crate::trie::{RawTrieNode, RawTrieNodeWithSize, TrieNode};
+use crate::{DBCol, ShardUId, Store, Trie};
+
+/// Nodes at depth smaller than this have their children verified in parallel.
+const PARALLEL_DEPTH: usize = 3;
+
+/// Number of independently locked parts of the map of visited entries.
+const VISITED_SHARDS: usize = 256;
+
+/// A single problem found in the `State` column.
+#[derive(Debug, PartialEq, Eq)]
+pub enum StateIssue {
+    /// Node or value referenced by its parent is not in the database.
+    Missing { hash: CryptoHash },
+    /// Entry is present but its reference count isn’t positive.
+    NonPositiveRefcount { hash: CryptoHash, rc: i64 },
+    /// Entry is referenced within the trie more times than its reference
+    /// count.
+    RefcountTooLow { hash: CryptoHash, rc: i64, references: u64 },
+    /// Hash of the stored data doesn’t match the key it is stored under.
+    HashMismatch { hash: CryptoHash, actual: CryptoHash },
+    /// Stored data cannot be decoded as a trie node.
+    MalformedNode { hash: CryptoHash, err: String },
+    /// Length of the stored value doesn’t match length recorded in the node.
+    ValueLengthMismatch { hash: CryptoHash, expected: u32, actual: usize },
+    /// Memory usage recorded in the node doesn’t match one computed from its
+    /// subtree.
+    MemoryUsageMismatch { hash: CryptoHash, stored: u64, computed: u64 },
+}
+
+impl std::fmt::Display for StateIssue {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing { hash } => write!(fmt, "{hash}: missing"),
+            Self::NonPositiveRefcount { hash, rc } => {
+                write!(fmt, "{hash}: non-positive refcount {rc}")
+            }
+            Self::RefcountTooLow { hash, rc, references } => {
+                write!(fmt, "{hash}: refcount {rc}, but referenced {references} times")
+            }
+            Self::HashMismatch { hash, actual } => {
+                write!(fmt, "{hash}: data hashes to {actual}")
+            }
+            Self::MalformedNode { hash, err } => write!(fmt, "{hash}: malformed node: {err}"),
+            Self::ValueLengthMismatch { hash, expected, actual } => {
+                write!(fmt, "{hash}: value length {actual}, expected {expected}")
+            }
+            Self::MemoryUsageMismatch { hash, stored, computed } => {
+                write!(fmt, "{hash}: memory usage {stored}, computed {computed}")
+            }
+        }
+    }
+}
+
+/// Result of [`verify_state_root`].
+#[derive(Debug, Default)]
+pub struct StateVerificationReport {
+    /// Number of distinct trie nodes read from the database.
+    pub nodes: u64,
+    /// Number of distinct values read from the database.
+    pub values: u64,
+    /// Number of references to nodes and values which were already visited.
+    pub shared_references: u64,
+    /// Memory usage of the root node computed from the whole trie, if it could
+    /// be computed (that is, no node was missing).
+    pub memory_usage: Option<u64>,
+    pub issues: Vec<StateIssue>,
+}
+
+impl StateVerificationReport {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+struct Verifier<'a> {
+    store: &'a Store,
+    shard_uid: ShardUId,
+    nodes: AtomicU64,
+    values: AtomicU64,
+    shared_references: AtomicU64,
+    /// All the visited nodes and values, sharded by the first byte of the hash.
+    visited: Vec<Mutex<HashMap<CryptoHash, Visit>>>,
+    issues: Mutex<Vec<StateIssue>>,
+}
+
+struct Visit {
+    /// Reference count stored in the database, 0 if the entry is missing.
+    rc: i64,
+    /// Number of references to the entry found in the trie.
+    references: u64,
+}
+
+impl Verifier<'_> {
+    fn report(&self, issue: StateIssue) {
+        self.issues.lock().unwrap().push(issue);
+    }
+
+    fn visited_shard(&self, hash: &CryptoHash) -> &Mutex<HashMap<CryptoHash, Visit>> {
+        &self.visited[hash.as_ref()[0] as usize % VISITED_SHARDS]
+    }
+
+    /// Records a reference to given node or value.  Returns whether it is the
+    /// first one, i.e. whether the entry should be verified.
+    fn visit(&self, hash: &CryptoHash) -> bool {
+        match self.visited_shard(hash).lock().unwrap().entry(*hash) {
+            Entry::Occupied(mut entry) => {
+                entry.get_mut().references += 1;
+                self.shared_references.fetch_add(1, Ordering::Relaxed);
+                false
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(Visit { rc: 0, references: 1 });
+                true
+            }
+        }
+    }
+
+    /// Reports entries referenced more times than their reference counts.
+    /// Entries with non-positive reference counts were reported when read.
+    fn check_refcounts(&self) {
+        for shard in &self.visited {
+            let visited = std::mem::take(&mut *shard.lock().unwrap());
+            for (hash, visit) in visited {
+                if visit.rc > 0 && (visit.rc as u64) < visit.references {
+                    self.report(StateIssue::RefcountTooLow {
+                        hash,
+                        rc: visit.rc,
+                        references: visit.references,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Reads entry from the `State` column checking that it exists, has
+    /// positive refcount and that its data hashes to the key.
+    fn read(&self, hash: &CryptoHash) -> std::io::Result<Option<Vec<u8>>> {
+        let key = TrieCachingStorage::get_key_from_shard_uid_and_hash(self.shard_uid, hash);
+        let raw = match self.store.storage.get_raw_bytes(DBCol::State, &key)? {
+            Some(raw) => raw,
+            None => {
+                self.report(StateIssue::Missing { hash: *hash });
+                return Ok(None);
+            }
+        };
+        let (data, rc) = decode_value_with_rc(&raw);
+        self.visited_shard(hash).lock().unwrap().get_mut(hash).unwrap().rc = rc;
+        let data = match data {
+            Some(data) => data,
+            None => {
+                self.report(StateIssue::NonPositiveRefcount { hash: *hash, rc });
+                return Ok(None);
+            }
+        };
+        let actual = near_primitives::hash::hash(data);
+        if actual != *hash {
+            self.report(StateIssue::HashMismatch { hash: *hash, actual });
+        }
+        Ok(Some(data.to_vec()))
+    }
+
+    fn verify_value(&self, value_length: u32, value_hash: &CryptoHash) -> std::io::Result<()> {
+        if !self.visit(value_hash) {
+            return Ok(());
+        }
+        self.values.fetch_add(1, Ordering::Relaxed);
+        if let Some(value) = self.read(value_hash)? {
+            if value.len() != value_length as usize {
+                self.report(StateIssue::ValueLengthMismatch {
+                    hash: *value_hash,
+                    expected: value_length,
+                    actual: value.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifies subtree rooted at given node and returns its memory usage
+    /// computed bottom-up, or `None` if some of the nodes couldn’t be read.
+    ///
+    /// If the node has already been visited, its subtree isn’t verified again
+    /// and the memory usage stored in the node is returned instead.
+    fn verify_node(&self, hash: &CryptoHash, depth: usize) -> std::io::Result<Option<u64>> {
+        if !self.visit(hash) {
+            return self.stored_memory_usage(hash);
+        }
+        self.nodes.fetch_add(1, Ordering::Relaxed);
+        let bytes = match self.read(hash)? {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+        let node = match RawTrieNodeWithSize::decode(&bytes) {
+            Ok(node) => node,
+            Err(err) => {
+                self.report(StateIssue::MalformedNode { hash: *hash, err: err.to_string() });
+                return Ok(None);
+            }
+        };
+        let children: Vec<CryptoHash> = match &node.node {
+            RawTrieNode::Leaf(_, value_length, value_hash) => {
+                self.verify_value(*value_length, value_hash)?;
+                Vec::new()
+            }
+            RawTrieNode::Branch(children, value) => {
+                if let Some((value_length, value_hash)) = value {
+                    self.verify_value(*value_length, value_hash)?;
+                }
+                children.iter().flatten().copied().collect()
+            }
+            RawTrieNode::Extension(_, child) => vec![*child],
+        };
+        let children_usage: Vec<Option<u64>> = if depth < PARALLEL_DEPTH {
+            use rayon::prelude::*;
+            children
+                .par_iter()
+                .map(|child| self.verify_node(child, depth + 1))
+                .collect::<std::io::Result<_>>()?
+        } else {
+            children
+                .iter()
+                .map(|child| self.verify_node(child, depth + 1))
+                .collect::<std::io::Result<_>>()?
+        };
+        let stored = node.memory_usage;
+        let direct = TrieNode::new(node.node).memory_usage_direct_no_memory();
+        let computed = match children_usage.into_iter().sum::<Option<u64>>() {
+            Some(usage) => direct + usage,
+            None => return Ok(None),
+        };
+        if computed != stored {
+            self.report(StateIssue::MemoryUsageMismatch { hash: *hash, stored, computed });
+        }
+        Ok(Some(computed))
+    }
+
+    /// Returns memory usage stored in given node, or `None` if it cannot be
+    /// read.  Doesn’t report any issues, since the node is verified when
+    /// visited for the first time.
+    fn stored_memory_usage(&self, hash: &CryptoHash) -> std::io::Result<Option<u64>> {
+        let key = TrieCachingStorage::get_key_from_shard_uid_and_hash(self.shard_uid, hash);
+        let raw = match self.store.storage.get_raw_bytes(DBCol::State, &key)? {
+            Some(raw) => raw,
+            None => return Ok(None),
+        };
+        Ok(match decode_value_with_rc(&raw) {
+            (Some(data), _) => RawTrieNodeWithSize::decode(data).ok().map(|node| node.memory_usage),
+            (None, _) => None,
+        })
+    }
+}
+
+/// Verifies the whole trie of given shard under given state root.
+///
+/// Subtrees near the root are verified in parallel on the current rayon thread
+/// pool.  Returns an error only if reading from the database fails;
+/// inconsistencies are collected in the returned report.
+pub fn verify_state_root(
+    store: &Store,
+    shard_uid: ShardUId,
+    state_root: &StateRoot,
+) -> std::io::Result<StateVerificationReport> {
+    if state_root == &Trie::EMPTY_ROOT {
+        return Ok(StateVerificationReport { memory_usage: Some(0), ..Default::default() });
+    }
+    let verifier = Verifier {
+        store,
+        shard_uid,
+        nodes: AtomicU64::new(0),
+        values: AtomicU64::new(0),
+        shared_references: AtomicU64::new(0),
+        visited: (0..VISITED_SHARDS).map(|_| Mutex::new(HashMap::new())).collect(),
+        issues: Mutex::new(Vec::new()),
+    };
+    let memory_usage = verifier.verify_node(state_root, 0)?;
+    verifier.check_refcounts();
+    Ok(StateVerificationReport {
+        nodes: verifier.nodes.into_inner(),
+        values: verifier.values.into_inner(),
+        shared_references: verifier.shared_references.into_inner(),
+        memory_usage,
+        issues: verifier.issues.into_inner().unwrap(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{verify_state_root, StateIssue};
+    use crate::db::refcount::decode_value_with_rc;
+    use crate::test_utils::{create_tries, test_populate_trie};
+    use crate::trie::trie_storage::TrieCachingStorage;
+    use crate::{DBCol, ShardUId, Trie};
+
+    #[test]
+    fn test_verify_state_root() {
+        let tries = create_tries();
+        let shard_uid = ShardUId::single_shard();
+        let changes = (0..50u32)
+            .map(|i| (format!("key{i}").into_bytes(), Some(format!("value{i}").into_bytes())))
+            .collect();
+        let root = test_populate_trie(&tries, &Trie::EMPTY_ROOT, shard_uid, changes);
+        let store = tries.get_store();
+
+        let report = verify_state_root(&store, shard_uid, &root).unwrap();
+        assert!(report.is_ok(), "{:?}", report.issues);
+        assert_eq!(report.values, 50);
+        let root_node = tries.get_trie_for_shard(shard_uid, root).retrieve_root_node().unwrap();
+        assert_eq!(report.memory_usage, Some(root_node.memory_usage));
+
+        // Remove some entry other than the root.
+        let root_key = TrieCachingStorage::get_key_from_shard_uid_and_hash(shard_uid, &root);
+        let (key, value) = store
+            .iter_raw_bytes(DBCol::State)
+            .map(Result::unwrap)
+            .find(|(key, _)| key[..] != root_key[..])
+            .unwrap();
+        let (_, rc) = decode_value_with_rc(&value);
+        let mut store_update = store.store_update();
+        store_update.decrement_refcount_by(
+            DBCol::State,
+            &key,
+            std::num::NonZeroU32::new(rc as u32).unwrap(),
+        );
+        store_update.commit().unwrap();
+
+        let report = verify_state_root(&store, shard_uid, &root).unwrap();
+        assert_eq!(report.memory_usage, None);
+        assert!(matches!(
+            report.issues[..],
+            [StateIssue::Missing { .. }] | [StateIssue::NonPositiveRefcount { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_verify_shared_values() {
+        let tries = create_tries();
+        let shard_uid = ShardUId::single_shard();
+        let value = b"shared".to_vec();
+        let changes =
+            (0..20u32).map(|i| (format!("key{i}").into_bytes(), Some(value.clone()))).collect();
+        let root = test_populate_trie(&tries, &Trie::EMPTY_ROOT, shard_uid, changes);
+        let store = tries.get_store();
+
+        let report = verify_state_root(&store, shard_uid, &root).unwrap();
+        assert!(report.is_ok(), "{:?}", report.issues);
+        assert_eq!(report.values, 1);
+        assert!(report.shared_references >= 19);
+
+        let value_hash = near_primitives::hash::hash(&value);
+        let key = TrieCachingStorage::get_key_from_shard_uid_and_hash(shard_uid, &value_hash);
+        let decrement = |by| {
+            let mut store_update = store.store_update();
+            store_update.decrement_refcount_by(
+                DBCol::State,
+                &key,
+                std::num::NonZeroU32::new(by).unwrap(),
+            );
+            store_update.commit().unwrap();
+        };
+
+        decrement(1);
+        let report = verify_state_root(&store, shard_uid, &root).unwrap();
+        assert_eq!(
+            report.issues,
+            [StateIssue::RefcountTooLow { hash: value_hash, rc: 19, references: 20 }]
+        );
+
+        // The value is reported once even though it is referenced many times.
+        decrement(19);
+        let report = verify_state_root(&store, shard_uid, &root).unwrap();
+        assert!(matches!(
+            report.issues[..],
+            [StateIssue::Missing { hash }] | [StateIssue::NonPositiveRefcount { hash, .. }]
+                if hash == value_hash
+        ));
+    }
+}
@@ -15,6 +15,7 @@ use near_o11y::testonly::init_test_logger;
 use near_primitives::challenge::{
     BlockDoubleSign, Challenge, ChallengeBody, ChunkProofs, MaybeEncodedShardChunk, StateItem,
 };
+use near_primitives::congestion_info::CongestionInfo;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{merklize, MerklePath, PartialMerkleTree};
 use near_primitives::num_rational::Ratio;
@@ -372,6 +373,7 @@ fn test_verify_chunk_invalid_state_challenge() {
         &[],
         last_block.chunks()[0].outgoing_receipts_root(),
         CryptoHash::default(),
+        CongestionInfo::default(),
         &validator_signer,
         &mut rs,
         PROTOCOL_VERSION,
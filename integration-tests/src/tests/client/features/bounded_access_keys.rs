@@ -0,0 +1,96 @@
+use crate::tests::client::process_blocks::{
+    create_nightshade_runtimes, produce_blocks_from_height,
+};
+use assert_matches::assert_matches;
+use near_chain::ChainGenesis;
+use near_chain_configs::Genesis;
+use near_client::test_utils::TestEnv;
+use near_client::ProcessTxResponse;
+use near_crypto::{InMemorySigner, KeyType, Signer};
+use near_primitives::account::{
+    AccessKey, AccessKeyExpiry, AccessKeyPermission, FunctionCallPermission,
+    FunctionCallPermissionV2,
+};
+use near_primitives::transaction::{Action, AddKeyAction, FunctionCallAction, SignedTransaction};
+use near_primitives::types::AccountId;
+use near_primitives::views::FinalExecutionStatus;
+use nearcore::config::GenesisExt;
+
+/// Transactions signed with an expiring key are checked against the height of
+/// the chunk they are prepared for, which is the height the chunk is applied
+/// at. A transaction prepared for a chunk before the expiry is applied, one
+/// for a chunk at the expiry is left out instead of invalidating the chunk.
+#[test]
+fn test_access_key_expiry_between_prepare_and_apply() {
+    let mut genesis = Genesis::test(vec!["test0".parse().unwrap(), "test1".parse().unwrap()], 1);
+    genesis.config.epoch_length = 100;
+    let mut env = TestEnv::builder(ChainGenesis::new(&genesis))
+        .runtime_adapters(create_nightshade_runtimes(&genesis, 1))
+        .build();
+
+    let account_id: AccountId = "test0".parse().unwrap();
+    let signer = InMemorySigner::from_seed(account_id.clone(), KeyType::ED25519, "test0");
+    let session_signer = InMemorySigner::from_seed(account_id.clone(), KeyType::ED25519, "session");
+    let tip = env.clients[0].chain.head().unwrap();
+    let expiry_height = tip.height + 10;
+    let add_key = env.tx_from_actions(
+        vec![Action::AddKey(AddKeyAction {
+            public_key: session_signer.public_key(),
+            access_key: AccessKey {
+                nonce: 0,
+                permission: AccessKeyPermission::FunctionCallV2(FunctionCallPermissionV2 {
+                    function_call: FunctionCallPermission {
+                        allowance: None,
+                        receiver_id: "test1".to_string(),
+                        method_names: vec![],
+                    },
+                    expiry: Some(AccessKeyExpiry::BlockHeight(expiry_height)),
+                    usage_limit: None,
+                }),
+            },
+        })],
+        &signer,
+        account_id.clone(),
+    );
+    assert_matches!(env.execute_tx(add_key).status, FinalExecutionStatus::SuccessValue(_));
+
+    let session_tx = |env: &TestEnv| {
+        let tip = env.clients[0].chain.head().unwrap();
+        SignedTransaction::from_actions(
+            tip.height * AccessKey::ACCESS_KEY_NONCE_RANGE_MULTIPLIER,
+            account_id.clone(),
+            "test1".parse().unwrap(),
+            &session_signer,
+            vec![Action::FunctionCall(FunctionCallAction {
+                method_name: "main".to_string(),
+                args: vec![],
+                gas: 10u64.pow(12),
+                deposit: 0,
+            })],
+            tip.last_block_hash,
+        )
+    };
+
+    // Transactions added to the pool now are prepared for the chunk at the
+    // height after the next one.
+    let mut height = env.clients[0].chain.head().unwrap().height + 1;
+    height = produce_blocks_from_height(&mut env, expiry_height - 2 - height, height);
+    let valid_tx = session_tx(&env);
+    assert_eq!(
+        env.clients[0].process_tx(valid_tx.clone(), false, false),
+        ProcessTxResponse::ValidTx
+    );
+    height = produce_blocks_from_height(&mut env, 1, height);
+    let expired_tx = session_tx(&env);
+    assert_eq!(
+        env.clients[0].process_tx(expired_tx.clone(), false, false),
+        ProcessTxResponse::ValidTx
+    );
+    produce_blocks_from_height(&mut env, 5, height);
+
+    let outcome = env.clients[0].chain.get_execution_outcome(&valid_tx.get_hash()).unwrap();
+    let block = env.clients[0].chain.get_block(&outcome.block_hash).unwrap();
+    assert_eq!(block.header().height(), expiry_height - 1);
+    assert!(env.clients[0].chain.get_execution_outcome(&expired_tx.get_hash()).is_err());
+    assert!(env.clients[0].chain.head().unwrap().height > expiry_height);
+}
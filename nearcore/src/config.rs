@@ -15,8 +15,8 @@ use tempfile::tempdir;
 use tracing::{info, warn};
 
 use near_chain_configs::{
-    get_initial_supply, ClientConfig, GCConfig, Genesis, GenesisConfig, GenesisValidationMode,
-    LogSummaryStyle,
+    get_initial_supply, ChunkDistributionConfig, ClientConfig, GCConfig, Genesis, GenesisConfig,
+    GenesisValidationMode, LogSummaryStyle, SimulateTransactionConfig, StoreConsistencyCheckConfig,
 };
 use near_crypto::{InMemorySigner, KeyFile, KeyType, PublicKey, Signer};
 #[cfg(feature = "json_rpc")]
@@ -321,8 +321,17 @@ pub struct Config {
     /// If set, overrides value in genesis configuration.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_gas_burnt_view: Option<Gas>,
+    /// Limits of `EXPERIMENTAL_simulate_transaction` requests.
+    #[serde(default, skip_serializing_if = "SimulateTransactionConfig::is_default")]
+    pub simulate_transaction: SimulateTransactionConfig,
     /// Different parameters to configure underlying storage.
     pub store: near_store::StoreConfig,
+    /// Background store consistency checker.
+    #[serde(default, skip_serializing_if = "StoreConsistencyCheckConfig::is_default")]
+    pub store_consistency_check: StoreConsistencyCheckConfig,
+    /// Redundant distribution of chunk parts.
+    #[serde(default, skip_serializing_if = "ChunkDistributionConfig::is_default")]
+    pub chunk_distribution: ChunkDistributionConfig,
     /// Different parameters to configure underlying cold storage.
     #[cfg(feature = "cold_store")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -369,9 +378,12 @@ impl Default for Config {
             view_client_throttle_period: default_view_client_throttle_period(),
             trie_viewer_state_size_limit: default_trie_viewer_state_size_limit(),
             max_gas_burnt_view: None,
+            simulate_transaction: SimulateTransactionConfig::default(),
             db_migration_snapshot_path: None,
             use_db_migration_snapshot: None,
             store: near_store::StoreConfig::default(),
+            store_consistency_check: StoreConsistencyCheckConfig::default(),
+            chunk_distribution: ChunkDistributionConfig::default(),
             #[cfg(feature = "cold_store")]
             cold_store: None,
         }
@@ -604,8 +616,11 @@ impl NearConfig {
                 view_client_throttle_period: config.view_client_throttle_period,
                 trie_viewer_state_size_limit: config.trie_viewer_state_size_limit,
                 max_gas_burnt_view: config.max_gas_burnt_view,
+                simulate_transaction: config.simulate_transaction,
                 enable_statistics_export: config.store.enable_statistics_export,
                 client_background_migration_threads: config.store.background_migration_threads,
+                store_consistency_check: config.store_consistency_check,
+                chunk_distribution: config.chunk_distribution,
             },
             network_config: NetworkConfig::new(
                 config.network,
@@ -1283,7 +1298,10 @@ pub fn load_config(
     dir: &Path,
     genesis_validation: GenesisValidationMode,
 ) -> anyhow::Result<NearConfig> {
-    let config = Config::from_file(&dir.join(CONFIG_FILENAME))?;
+    let mut config = Config::from_file(&dir.join(CONFIG_FILENAME))?;
+    if let Some(capture) = &mut config.network.experimental.capture {
+        capture.dir = dir.join(&capture.dir);
+    }
     let genesis_file = dir.join(&config.genesis_file);
     let validator_file = dir.join(&config.validator_key_file);
     let validator_signer = if validator_file.exists() {
@@ -1409,9 +1427,19 @@ fn test_config_from_file() {
         // values is probably not worth it but there may be some other defaults
         // we want to ensure that they happen.
         let want_gc = if has_gc {
-            GCConfig { gc_blocks_limit: 42, gc_fork_clean_step: 420, gc_num_epochs_to_keep: 24 }
+            GCConfig {
+                gc_blocks_limit: 42,
+                gc_fork_clean_step: 420,
+                gc_num_epochs_to_keep: 24,
+                ..GCConfig::default()
+            }
         } else {
-            GCConfig { gc_blocks_limit: 2, gc_fork_clean_step: 100, gc_num_epochs_to_keep: 5 }
+            GCConfig {
+                gc_blocks_limit: 2,
+                gc_fork_clean_step: 100,
+                gc_num_epochs_to_keep: 5,
+                ..GCConfig::default()
+            }
         };
         assert_eq!(want_gc, config.gc);
 
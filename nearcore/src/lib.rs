@@ -5,7 +5,7 @@ use actix::{Actor, Addr};
 use actix_rt::ArbiterHandle;
 use actix_web;
 use anyhow::Context;
-use near_chain::{Chain, ChainGenesis};
+use near_chain::{Chain, ChainGenesis, StoreConsistencyChecker, StoreValidator};
 use near_client::{start_client, start_view_client, ClientActor, ViewClientActor};
 use near_network::time;
 use near_network::types::NetworkRecipient;
@@ -185,6 +185,25 @@ pub fn start_with_config_and_synchronization(
     let network_adapter = Arc::new(NetworkRecipient::default());
     let adv = near_client::adversarial::Controls::new(config.client_config.archive);
 
+    let store_consistency_checker =
+        config.client_config.store_consistency_check.enabled.then(|| {
+            // Validates against the genesis config loaded from home_dir, so that
+            // checks relying on e.g. genesis height work for any chain.
+            let mut store_validator = StoreValidator::new(
+                config.validator_signer.as_ref().map(|signer| signer.validator_id().clone()),
+                config.genesis.config.clone(),
+                runtime.clone(),
+                store.get_store(Temperature::Hot),
+                config.client_config.archive,
+            );
+            store_validator
+                .set_retained_accounts(config.client_config.gc.retained_accounts.clone());
+            StoreConsistencyChecker::new(
+                store_validator,
+                &config.client_config.store_consistency_check,
+            )
+        });
+
     let view_client = start_view_client(
         config.validator_signer.as_ref().map(|signer| signer.validator_id().clone()),
         chain_genesis.clone(),
@@ -203,6 +222,7 @@ pub fn start_with_config_and_synchronization(
         telemetry,
         shutdown_signal,
         adv,
+        store_consistency_checker,
     );
 
     #[allow(unused_mut)]
@@ -1,4 +1,6 @@
-use near_o11y::metrics::{linear_buckets, try_create_histogram_vec, HistogramVec};
+use near_o11y::metrics::{
+    linear_buckets, try_create_histogram_vec, try_create_int_gauge_vec, HistogramVec, IntGaugeVec,
+};
 use once_cell::sync::Lazy;
 
 pub static APPLY_CHUNK_DELAY: Lazy<HistogramVec> = Lazy::new(|| {
@@ -24,3 +26,21 @@ pub static SECONDS_PER_PETAGAS: Lazy<HistogramVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static DELAYED_RECEIPTS_GAS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    try_create_int_gauge_vec(
+        "near_delayed_receipts_gas",
+        "Total gas attached to the delayed receipts of the shard, as tracked by congestion control",
+        &["shard_id"],
+    )
+    .unwrap()
+});
+
+pub static DELAYED_RECEIPTS_BYTES: Lazy<IntGaugeVec> = Lazy::new(|| {
+    try_create_int_gauge_vec(
+        "near_delayed_receipts_bytes",
+        "Total size of the delayed receipts of the shard, as tracked by congestion control",
+        &["shard_id"],
+    )
+    .unwrap()
+});
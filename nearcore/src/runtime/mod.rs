@@ -5,11 +5,13 @@ use crate::NearConfig;
 use borsh::ser::BorshSerialize;
 use borsh::BorshDeserialize;
 use errors::FromStateViewerErrors;
-use near_chain::types::{ApplySplitStateResult, ApplyTransactionResult, BlockHeaderInfo};
+use near_chain::types::{
+    ApplySplitStateResult, ApplyTransactionResult, BlockHeaderInfo, SimulateTransactionResult,
+};
 use near_chain::{Error, RuntimeAdapter};
 use near_chain_configs::{
-    Genesis, GenesisConfig, ProtocolConfig, DEFAULT_GC_NUM_EPOCHS_TO_KEEP,
-    MIN_GC_NUM_EPOCHS_TO_KEEP,
+    Genesis, GenesisConfig, ProtocolConfig, SimulateTransactionConfig,
+    DEFAULT_GC_NUM_EPOCHS_TO_KEEP, MIN_GC_NUM_EPOCHS_TO_KEEP,
 };
 use near_client_primitives::types::StateSplitApplyingStatus;
 use near_crypto::PublicKey;
@@ -18,6 +20,7 @@ use near_o11y::log_assert;
 use near_pool::types::PoolIterator;
 use near_primitives::account::{AccessKey, Account};
 use near_primitives::challenge::ChallengesResult;
+use near_primitives::congestion_info::{BlockCongestionInfo, CongestionInfo};
 use near_primitives::contract::ContractCode;
 use near_primitives::epoch_manager::block_info::BlockInfo;
 use near_primitives::epoch_manager::EpochConfig;
@@ -34,6 +37,7 @@ use near_primitives::state_part::PartId;
 use near_primitives::state_record::{state_record_to_account_id, StateRecord};
 use near_primitives::syncing::{get_num_state_parts, STATE_PART_MEMORY_LIMIT};
 use near_primitives::transaction::SignedTransaction;
+use near_primitives::trie_key::TrieKey;
 use near_primitives::types::validator_stake::ValidatorStakeIter;
 use near_primitives::types::{
     AccountId, Balance, BlockHeight, CompiledContractCache, EpochHeight, EpochId,
@@ -51,7 +55,7 @@ use near_store::flat_state::{
 };
 use near_store::split_state::get_delayed_receipts;
 use near_store::{
-    get_genesis_hash, get_genesis_state_roots, set_genesis_hash, set_genesis_state_roots,
+    get, get_genesis_hash, get_genesis_state_roots, set_genesis_hash, set_genesis_state_roots,
     ApplyStatePartResult, DBCol, PartialStorage, ShardTries, Store, StoreCompiledContractCache,
     StoreUpdate, Trie, TrieConfig, WrappedTrieChanges,
 };
@@ -60,7 +64,7 @@ use node_runtime::adapter::ViewRuntimeAdapter;
 use node_runtime::config::RuntimeConfig;
 use node_runtime::state_viewer::TrieViewer;
 use node_runtime::{
-    validate_transaction, verify_and_charge_transaction, ApplyState, Runtime,
+    validate_transaction, verify_and_charge_transaction, ApplyState, Runtime, TransactionBlockInfo,
     ValidatorAccountsUpdate,
 };
 use std::collections::{HashMap, HashSet};
@@ -384,6 +388,7 @@ impl NightshadeRuntime {
         gas_price: Balance,
         gas_limit: Gas,
         challenges_result: &ChallengesResult,
+        congestion_info: &BlockCongestionInfo,
         random_seed: CryptoHash,
         is_new_chunk: bool,
         is_first_block_with_chunk_of_version: bool,
@@ -391,9 +396,9 @@ impl NightshadeRuntime {
     ) -> Result<ApplyTransactionResult, Error> {
         let _span = tracing::debug_span!(target: "runtime", "process_state_update").entered();
         let epoch_id = self.get_epoch_id_from_prev_block(prev_block_hash)?;
+        let shard_layout = self.epoch_manager.read().get_shard_layout(&epoch_id)?;
         let validator_accounts_update = {
             let epoch_manager = self.epoch_manager.read();
-            let shard_layout = epoch_manager.get_shard_layout(&epoch_id)?.clone();
             debug!(target: "runtime",
                    "block height: {}, is next_block_epoch_start {}",
                    block_height,
@@ -495,6 +500,8 @@ impl NightshadeRuntime {
                 is_first_block_of_version,
                 is_first_block_with_chunk_of_version,
             },
+            congestion_info: congestion_info.clone(),
+            shard_layout,
         };
 
         let instant = Instant::now();
@@ -537,6 +544,15 @@ impl NightshadeRuntime {
                 .with_label_values(&[])
                 .observe(elapsed.as_secs_f64() * 1e15 / total_gas_burnt as f64);
         }
+        if let Some(congestion_info) = apply_result.congestion_info {
+            let shard_label = shard_id.to_string();
+            metrics::DELAYED_RECEIPTS_GAS
+                .with_label_values(&[&shard_label])
+                .set(congestion_info.delayed_receipts_gas.try_into().unwrap_or(i64::MAX));
+            metrics::DELAYED_RECEIPTS_BYTES
+                .with_label_values(&[&shard_label])
+                .set(congestion_info.delayed_receipts_bytes.try_into().unwrap_or(i64::MAX));
+        }
         let total_balance_burnt = apply_result
             .stats
             .tx_burnt_amount
@@ -699,6 +715,16 @@ impl RuntimeAdapter for NightshadeRuntime {
         Ok(self.tries.get_view_trie_for_shard(shard_uid, state_root))
     }
 
+    fn get_congestion_info(
+        &self,
+        shard_id: ShardId,
+        prev_hash: &CryptoHash,
+        state_root: StateRoot,
+    ) -> Result<CongestionInfo, Error> {
+        let trie = self.get_view_trie_for_shard(shard_id, prev_hash, state_root)?;
+        Ok(get(&trie, &TrieKey::CongestionInfo)?.unwrap_or_default())
+    }
+
     fn get_flat_storage_state_for_shard(&self, shard_id: ShardId) -> Option<FlatStorageState> {
         self.flat_state_factory.get_flat_storage_state_for_shard(shard_id)
     }
@@ -765,7 +791,8 @@ impl RuntimeAdapter for NightshadeRuntime {
                 transaction,
                 verify_signature,
                 // here we do not know which block the transaction will be included
-                // and therefore skip the check on the nonce upper bound.
+                // and therefore skip the checks on the nonce upper bound and the
+                // access key expiry and usage limit.
                 None,
                 current_protocol_version,
             ) {
@@ -805,6 +832,7 @@ impl RuntimeAdapter for NightshadeRuntime {
         shard_id: ShardId,
         state_root: StateRoot,
         next_block_height: BlockHeight,
+        prev_block_timestamp: u64,
         pool_iterator: &mut dyn PoolIterator,
         chain_validate: &mut dyn FnMut(&SignedTransaction) -> bool,
         current_protocol_version: ProtocolVersion,
@@ -821,6 +849,11 @@ impl RuntimeAdapter for NightshadeRuntime {
         let mut num_checked_transactions = 0;
 
         let runtime_config = self.runtime_config_store.get_config(current_protocol_version);
+        let block_info = TransactionBlockInfo {
+            height: next_block_height,
+            timestamp: prev_block_timestamp,
+            epoch_height: self.epoch_manager.read().get_epoch_info(epoch_id)?.epoch_height(),
+        };
 
         // In general, we limit the number of transactions via send_fees.
         // However, as a second line of defense, we want to limit the byte size
@@ -846,7 +879,7 @@ impl RuntimeAdapter for NightshadeRuntime {
                             gas_price,
                             &tx,
                             false,
-                            Some(next_block_height),
+                            Some(block_info),
                             current_protocol_version,
                         ) {
                             Ok(verification_result) => {
@@ -962,6 +995,7 @@ impl RuntimeAdapter for NightshadeRuntime {
         gas_price: Balance,
         gas_limit: Gas,
         challenges: &ChallengesResult,
+        congestion_info: &BlockCongestionInfo,
         random_seed: CryptoHash,
         generate_storage_proof: bool,
         is_new_chunk: bool,
@@ -994,6 +1028,7 @@ impl RuntimeAdapter for NightshadeRuntime {
             gas_price,
             gas_limit,
             challenges,
+            congestion_info,
             random_seed,
             is_new_chunk,
             is_first_block_with_chunk_of_version,
@@ -1025,6 +1060,7 @@ impl RuntimeAdapter for NightshadeRuntime {
         gas_price: Balance,
         gas_limit: Gas,
         challenges: &ChallengesResult,
+        congestion_info: &BlockCongestionInfo,
         random_value: CryptoHash,
         is_new_chunk: bool,
         is_first_block_with_chunk_of_version: bool,
@@ -1043,6 +1079,7 @@ impl RuntimeAdapter for NightshadeRuntime {
             gas_price,
             gas_limit,
             challenges,
+            congestion_info,
             random_value,
             is_new_chunk,
             is_first_block_with_chunk_of_version,
@@ -1193,6 +1230,76 @@ impl RuntimeAdapter for NightshadeRuntime {
         }
     }
 
+    fn simulate_transaction(
+        &self,
+        state_roots: &HashMap<ShardUId, StateRoot>,
+        block_height: BlockHeight,
+        block_timestamp: u64,
+        prev_block_hash: &CryptoHash,
+        block_hash: &CryptoHash,
+        epoch_id: &EpochId,
+        gas_price: Balance,
+        random_seed: CryptoHash,
+        transaction: &SignedTransaction,
+        verify_signature: bool,
+        limits: &SimulateTransactionConfig,
+    ) -> Result<Result<SimulateTransactionResult, InvalidTxError>, Error> {
+        let (shard_layout, epoch_height, current_protocol_version) = {
+            let epoch_manager = self.epoch_manager.read();
+            let epoch_info = epoch_manager.get_epoch_info(epoch_id)?;
+            (
+                epoch_manager.get_shard_layout(epoch_id)?,
+                epoch_info.epoch_height(),
+                epoch_info.protocol_version(),
+            )
+        };
+        let apply_state = ApplyState {
+            // The transaction could be included in the next block at the earliest.
+            block_height: block_height + 1,
+            prev_block_hash: *prev_block_hash,
+            block_hash: *block_hash,
+            epoch_id: epoch_id.clone(),
+            epoch_height,
+            gas_price,
+            block_timestamp,
+            gas_limit: None,
+            random_seed,
+            current_protocol_version,
+            config: self.runtime_config_store.get_config(current_protocol_version).clone(),
+            cache: Some(Box::new(StoreCompiledContractCache::new(&self.store))),
+            is_new_chunk: true,
+            migration_data: Arc::clone(&self.migration_data),
+            migration_flags: MigrationFlags::default(),
+            congestion_info: BlockCongestionInfo::default(),
+            shard_layout: shard_layout.clone(),
+        };
+        let get_trie = |shard_uid| match state_roots.get(&shard_uid) {
+            Some(state_root) => Ok(self.tries.get_view_trie_for_shard(shard_uid, *state_root)),
+            None => Err(StorageError::StorageInconsistentState(format!(
+                "state root of shard {:?} is not available",
+                shard_uid
+            ))),
+        };
+        match self.runtime.simulate_transaction(
+            &get_trie,
+            &shard_layout,
+            &apply_state,
+            transaction,
+            verify_signature,
+            &self.epoch_manager,
+            limits,
+        ) {
+            Ok(result) => Ok(Ok(SimulateTransactionResult {
+                outcomes: result.outcomes,
+                receipts: result.receipts,
+                limit_exceeded: result.limit_exceeded.map(|limit| limit.to_string()),
+            })),
+            Err(RuntimeError::InvalidTxError(err)) => Ok(Err(err)),
+            Err(RuntimeError::StorageError(err)) => Err(Error::StorageError(err)),
+            Err(err) => Err(Error::Other(err.to_string())),
+        }
+    }
+
     /// Returns StorageError when storage is inconsistent.
     /// This is possible with the used isolation level + running ViewClient in a separate thread
     /// `block_hash` is a block whose `prev_state_root` is `state_root`
@@ -1317,6 +1424,13 @@ impl RuntimeAdapter for NightshadeRuntime {
             store_update.commit()?;
             state_split_status.done_parts.fetch_add(1, core::sync::atomic::Ordering::Relaxed);
         }
+        let (store_update, new_state_roots) = self.tries.add_contract_code_to_split_states(
+            &state_roots,
+            &trie,
+            &checked_account_id_to_shard_id,
+        )?;
+        state_roots = new_state_roots;
+        store_update.commit()?;
         state_roots = apply_delayed_receipts(
             &self.tries,
             shard_uid,
@@ -1599,6 +1713,7 @@ mod test {
                     gas_price,
                     gas_limit,
                     challenges,
+                    &BlockCongestionInfo::default(),
                     CryptoHash::default(),
                     true,
                     false,
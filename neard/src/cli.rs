@@ -9,6 +9,7 @@ use near_amend_genesis::AmendGenesisCommand;
 use near_chain_configs::GenesisValidationMode;
 #[cfg(feature = "cold_store")]
 use near_cold_store_tool::ColdStoreCommand;
+use near_database_tool::DatabaseCommand;
 use near_jsonrpc_primitives::types::light_client::RpcLightClientExecutionProofResponse;
 use near_mirror::MirrorCommand;
 use near_o11y::tracing_subscriber::EnvFilter;
@@ -20,6 +21,8 @@ use near_ping::PingCommand;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::compute_root_from_path;
 use near_primitives::types::{Gas, NumSeats, NumShards};
+use near_replay_capture::ReplayCaptureCommand;
+use near_runtime_params_tool::RuntimeParamsCommand;
 use near_state_viewer::StateViewerSubCommand;
 use near_store::db::RocksDB;
 use near_store::Mode;
@@ -93,7 +96,7 @@ impl NeardCmd {
 
             NeardSubCommand::StateViewer(cmd) => {
                 let mode = if cmd.readwrite { Mode::ReadWrite } else { Mode::ReadOnly };
-                cmd.subcmd.run(&home_dir, genesis_validation, mode);
+                cmd.subcmd.run(&home_dir, genesis_validation, mode)?;
             }
 
             NeardSubCommand::RecompressStorage(cmd) => {
@@ -105,6 +108,9 @@ impl NeardCmd {
             NeardSubCommand::Ping(cmd) => {
                 cmd.run()?;
             }
+            NeardSubCommand::ReplayCapture(cmd) => {
+                cmd.run()?;
+            }
             NeardSubCommand::Mirror(cmd) => {
                 cmd.run()?;
             }
@@ -115,6 +121,12 @@ impl NeardCmd {
             NeardSubCommand::ColdStore(cmd) => {
                 cmd.run(&home_dir);
             }
+            NeardSubCommand::Database(cmd) => {
+                cmd.run(&home_dir)?;
+            }
+            NeardSubCommand::RuntimeParams(cmd) => {
+                cmd.run()?;
+            }
         };
         Ok(())
     }
@@ -208,6 +220,10 @@ pub(super) enum NeardSubCommand {
     /// us after the handshake is completed, printing stats to stdout.
     Ping(PingCommand),
 
+    /// Connects to a NEAR node and sends it the messages from a network
+    /// traffic capture, preserving their original timing.
+    ReplayCapture(ReplayCaptureCommand),
+
     /// Mirror transactions from a source chain to a test chain with state forked
     /// from it, reproducing traffic and state as closely as possible.
     Mirror(MirrorCommand),
@@ -218,6 +234,13 @@ pub(super) enum NeardSubCommand {
     #[cfg(feature = "cold_store")]
     /// Testing tool for cold storage
     ColdStore(ColdStoreCommand),
+
+    /// Set of commands to maintain the database, e.g. run manual compaction.
+    Database(DatabaseCommand),
+
+    /// Show the runtime parameters of a protocol version, or the changes of
+    /// runtime parameters between two protocol versions.
+    RuntimeParams(RuntimeParamsCommand),
 }
 
 #[derive(Parser)]
@@ -62,6 +62,33 @@ extern "C" {
         pub_key_len: u64,
         pub_key_ptr: u64,
     ) -> u64;
+    #[cfg(feature = "protocol_feature_secp256r1")]
+    fn p256_verify(
+        sig_len: u64,
+        sig_ptr: u64,
+        msg_len: u64,
+        msg_ptr: u64,
+        pub_key_len: u64,
+        pub_key_ptr: u64,
+    ) -> u64;
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_p1_sum(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_p2_sum(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_g1_multiexp(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_g2_multiexp(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_map_fp_to_g1(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_map_fp2_to_g2(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_pairing_check(value_len: u64, value_ptr: u64) -> u64;
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_p1_decompress(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_p2_decompress(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
     // #####################
     // # Miscellaneous API #
     // #####################
@@ -553,6 +580,77 @@ pub unsafe fn ed25519_verify_16kib_64() {
     }
 }
 
+// private key: secp256r1:CaUgp4qeh45kfEY6VcsqZ4dKL2dg7hXrGLrGKVork7Q7
+#[cfg(feature = "protocol_feature_secp256r1")]
+const P256_PUBLIC_KEY: [u8; 64] = [
+    82, 26, 101, 111, 231, 64, 129, 159, 207, 225, 29, 197, 192, 128, 81, 181, 14, 147, 190, 70,
+    245, 26, 198, 121, 226, 31, 171, 129, 43, 51, 5, 112, 36, 237, 50, 228, 230, 217, 73, 191, 168,
+    116, 152, 126, 128, 244, 178, 127, 116, 121, 170, 209, 233, 5, 138, 69, 217, 31, 19, 86, 177,
+    24, 165, 201,
+];
+
+/// Function to measure `p256_verify_base`. Also measures `base`,
+/// `write_register_base`, and `write_register_byte`. However
+/// `p256_verify_base` computation is more expensive than register writing so
+/// we are okay overcharging it.
+#[no_mangle]
+#[cfg(feature = "protocol_feature_secp256r1")]
+pub unsafe fn p256_verify_32b_500() {
+    // 32 bytes message ("kajdlfkjalkfjaklfjdkladjfkljadsk")
+    let message: [u8; 32] = [
+        107, 97, 106, 100, 108, 102, 107, 106, 97, 108, 107, 102, 106, 97, 107, 108, 102, 106, 100,
+        107, 108, 97, 100, 106, 102, 107, 108, 106, 97, 100, 115, 107,
+    ];
+
+    let signature: [u8; 64] = [
+        73, 137, 75, 246, 251, 204, 24, 167, 240, 105, 255, 222, 1, 92, 55, 120, 21, 68, 230, 255,
+        177, 6, 23, 113, 108, 32, 228, 186, 213, 140, 162, 103, 236, 245, 194, 167, 133, 253, 47,
+        96, 251, 67, 187, 199, 126, 114, 136, 91, 76, 177, 173, 125, 128, 65, 190, 145, 209, 252,
+        190, 103, 28, 155, 56, 40,
+    ];
+
+    for _ in 0..500 {
+        let result = p256_verify(
+            signature.len() as _,
+            signature.as_ptr() as _,
+            message.len() as _,
+            message.as_ptr() as _,
+            P256_PUBLIC_KEY.len() as _,
+            P256_PUBLIC_KEY.as_ptr() as _,
+        );
+        // check that result was positive, as negative results could have exited
+        // early and do not reflect the full cost.
+        assert!(result == 1);
+    }
+}
+
+/// Function to measure `p256_verify_byte`.
+#[no_mangle]
+#[cfg(feature = "protocol_feature_secp256r1")]
+pub unsafe fn p256_verify_16kib_64() {
+    // 16kB bytes message
+    let message = [b'a'; 16384];
+
+    let signature: [u8; 64] = [
+        53, 98, 229, 68, 227, 213, 191, 190, 249, 246, 153, 72, 184, 9, 223, 207, 240, 196, 106,
+        181, 121, 105, 239, 33, 79, 12, 189, 45, 35, 153, 228, 128, 42, 237, 154, 88, 20, 1, 13,
+        162, 170, 31, 192, 211, 213, 33, 52, 209, 158, 130, 172, 197, 184, 43, 125, 10, 124, 70,
+        17, 219, 63, 87, 213, 174,
+    ];
+
+    for _ in 0..64 {
+        let result = p256_verify(
+            signature.len() as _,
+            signature.as_ptr() as _,
+            message.len() as _,
+            message.as_ptr() as _,
+            P256_PUBLIC_KEY.len() as _,
+            P256_PUBLIC_KEY.as_ptr() as _,
+        );
+        assert!(result == 1);
+    }
+}
+
 #[repr(C)]
 struct MultiexpElem([u8; 64], [u8; 32]);
 
@@ -721,6 +819,283 @@ pub unsafe fn alt_bn128_pairing_check_10_10() {
     }
 }
 
+// #############
+// # BLS12-381 #
+// #############
+
+// The inputs for the BLS12-381 functions are derived from these points, which
+// are multiples of the generators of G1 and G2 in the uncompressed ZCash
+// encoding. The first coordinate of a point is a valid Fp (Fp2) element and
+// becomes a valid compressed point by setting the compression flag. The
+// second coordinate serves as a full-width scalar.
+#[cfg(feature = "protocol_feature_bls12381")]
+#[rustfmt::skip]
+const G1_POINTS: [[u8; 96]; 10] = [
+    [8, 58, 78, 141, 56, 191, 204, 26, 208, 173, 205, 151, 179, 196, 137, 77, 205, 61, 171, 97, 14, 209, 38, 75, 248, 2, 146, 19, 113, 31, 216, 59, 0, 12, 151, 24, 177, 186, 96, 186, 122, 91, 128, 40, 172, 232, 5, 171, 6, 66, 78, 105, 126, 29, 175, 250, 108, 249, 45, 94, 82, 181, 62, 88, 7, 99, 233, 34, 250, 161, 194, 104, 105, 209, 255, 100, 217, 79, 21, 139, 79, 246, 232, 221, 244, 194, 228, 172, 58, 102, 153, 87, 65, 68, 201, 144],
+    [22, 220, 59, 164, 232, 208, 73, 22, 96, 156, 156, 162, 34, 230, 9, 188, 45, 251, 29, 17, 223, 142, 222, 5, 177, 72, 160, 205, 78, 99, 253, 22, 154, 45, 29, 170, 117, 80, 50, 49, 187, 113, 88, 220, 100, 196, 48, 51, 24, 209, 174, 228, 121, 97, 79, 19, 159, 219, 81, 67, 114, 62, 108, 119, 95, 58, 104, 97, 51, 104, 142, 171, 215, 98, 29, 117, 179, 171, 148, 181, 6, 136, 166, 212, 159, 167, 210, 148, 79, 228, 175, 225, 115, 56, 180, 51],
+    [17, 44, 73, 126, 93, 143, 227, 52, 1, 34, 91, 86, 118, 255, 234, 84, 90, 192, 224, 83, 77, 70, 59, 110, 74, 127, 26, 73, 254, 105, 131, 211, 156, 220, 3, 72, 204, 12, 177, 202, 90, 228, 40, 0, 195, 189, 174, 82, 16, 181, 191, 74, 65, 189, 243, 244, 91, 73, 25, 224, 14, 97, 125, 114, 127, 54, 36, 157, 202, 148, 28, 223, 105, 168, 106, 53, 254, 146, 75, 28, 167, 6, 139, 196, 144, 16, 17, 187, 227, 117, 180, 38, 146, 76, 28, 80],
+    [5, 122, 56, 43, 35, 174, 133, 204, 0, 54, 24, 11, 62, 99, 217, 230, 214, 213, 133, 120, 32, 175, 163, 234, 51, 118, 237, 139, 132, 98, 82, 140, 191, 254, 15, 180, 187, 217, 48, 168, 66, 114, 130, 183, 196, 55, 129, 165, 20, 237, 97, 225, 213, 255, 151, 223, 128, 39, 87, 230, 201, 197, 228, 94, 178, 61, 33, 241, 89, 114, 178, 249, 128, 85, 22, 184, 83, 199, 104, 7, 221, 160, 69, 114, 9, 234, 117, 24, 163, 31, 140, 133, 18, 116, 201, 60],
+    [20, 151, 245, 32, 125, 245, 200, 178, 220, 209, 99, 96, 1, 139, 106, 130, 27, 21, 81, 25, 0, 16, 179, 214, 132, 56, 164, 243, 28, 59, 138, 150, 85, 187, 146, 93, 159, 51, 203, 238, 95, 186, 135, 42, 179, 236, 68, 109, 19, 166, 230, 156, 121, 95, 66, 63, 164, 87, 48, 56, 11, 2, 27, 155, 138, 11, 241, 191, 66, 160, 7, 163, 201, 43, 28, 50, 170, 35, 189, 220, 229, 26, 132, 159, 179, 1, 161, 35, 28, 122, 248, 89, 111, 37, 74, 106],
+    [16, 145, 77, 20, 132, 106, 122, 32, 211, 170, 41, 249, 112, 63, 170, 225, 29, 15, 167, 140, 18, 13, 112, 237, 22, 131, 52, 71, 6, 4, 154, 23, 171, 54, 234, 94, 145, 48, 224, 188, 183, 223, 182, 16, 11, 43, 53, 249, 23, 120, 78, 125, 216, 95, 147, 236, 59, 121, 242, 223, 93, 202, 194, 145, 12, 197, 37, 71, 168, 92, 177, 63, 198, 28, 96, 52, 148, 68, 10, 133, 11, 48, 190, 58, 170, 69, 89, 160, 63, 246, 22, 95, 251, 209, 70, 50],
+    [13, 138, 234, 19, 106, 197, 243, 113, 42, 114, 166, 254, 236, 71, 71, 23, 160, 25, 96, 201, 205, 157, 237, 67, 184, 15, 194, 70, 160, 64, 237, 175, 2, 86, 229, 46, 136, 59, 65, 198, 97, 151, 246, 34, 107, 174, 115, 52, 14, 89, 230, 79, 133, 175, 236, 156, 196, 28, 35, 51, 189, 115, 230, 234, 207, 89, 197, 189, 194, 242, 195, 194, 55, 47, 227, 150, 125, 17, 198, 150, 194, 252, 24, 153, 250, 128, 132, 94, 89, 251, 72, 162, 211, 198, 11, 61],
+    [12, 200, 224, 9, 242, 170, 247, 115, 148, 124, 180, 2, 68, 237, 11, 126, 103, 17, 104, 112, 62, 106, 127, 192, 177, 62, 237, 68, 147, 196, 216, 16, 252, 134, 128, 6, 100, 80, 58, 90, 61, 36, 165, 130, 166, 32, 114, 164, 1, 235, 199, 17, 14, 89, 151, 32, 227, 247, 208, 157, 183, 159, 5, 159, 251, 15, 99, 235, 127, 137, 253, 132, 135, 153, 163, 109, 170, 118, 252, 112, 185, 181, 182, 11, 230, 31, 50, 91, 222, 117, 215, 50, 78, 24, 180, 101],
+    [22, 155, 171, 65, 150, 0, 109, 97, 53, 226, 11, 37, 228, 200, 168, 55, 75, 188, 206, 44, 31, 140, 127, 142, 59, 135, 12, 162, 167, 124, 80, 90, 42, 176, 43, 212, 154, 254, 70, 87, 237, 178, 7, 131, 250, 250, 147, 255, 4, 196, 236, 176, 91, 205, 237, 97, 48, 187, 21, 208, 81, 224, 19, 223, 110, 134, 13, 144, 254, 109, 224, 226, 47, 204, 67, 64, 38, 86, 149, 165, 8, 112, 147, 150, 36, 155, 117, 97, 136, 29, 162, 197, 204, 183, 142, 28],
+    [21, 16, 87, 142, 94, 46, 151, 13, 6, 111, 91, 165, 20, 3, 182, 52, 95, 67, 69, 200, 196, 158, 41, 189, 109, 228, 94, 210, 182, 91, 175, 44, 77, 63, 8, 165, 90, 165, 21, 139, 121, 61, 156, 237, 212, 112, 217, 194, 9, 226, 19, 104, 69, 13, 123, 94, 23, 118, 186, 79, 102, 222, 220, 125, 249, 216, 222, 16, 91, 171, 228, 211, 172, 45, 101, 217, 220, 111, 18, 153, 29, 221, 71, 6, 211, 252, 99, 226, 215, 183, 134, 150, 247, 124, 4, 182],
+];
+
+#[cfg(feature = "protocol_feature_bls12381")]
+#[rustfmt::skip]
+const G2_POINTS: [[u8; 192]; 10] = [
+    [3, 129, 128, 234, 74, 100, 243, 199, 85, 247, 104, 187, 21, 246, 42, 230, 226, 234, 130, 70, 38, 2, 3, 87, 52, 140, 147, 182, 95, 66, 218, 178, 88, 205, 213, 233, 72, 0, 167, 119, 21, 124, 104, 109, 71, 97, 213, 33, 13, 139, 105, 240, 11, 125, 145, 26, 105, 80, 242, 245, 95, 112, 54, 203, 81, 5, 13, 143, 71, 140, 111, 225, 82, 58, 63, 219, 192, 105, 154, 164, 27, 26, 155, 215, 254, 59, 121, 52, 79, 70, 138, 108, 124, 22, 15, 205, 8, 67, 161, 84, 3, 61, 66, 56, 18, 59, 176, 37, 144, 80, 80, 12, 120, 242, 115, 66, 23, 141, 36, 61, 213, 26, 194, 255, 15, 208, 182, 132, 189, 46, 162, 56, 21, 237, 64, 32, 110, 40, 255, 16, 123, 148, 24, 103, 4, 198, 195, 58, 98, 144, 149, 225, 245, 13, 183, 111, 109, 160, 90, 232, 51, 243, 242, 174, 178, 155, 53, 202, 178, 203, 172, 86, 82, 112, 226, 75, 18, 110, 36, 20, 121, 142, 139, 100, 22, 51, 70, 95, 167, 149, 243, 156],
+    [13, 79, 64, 186, 75, 97, 131, 114, 68, 119, 133, 182, 31, 100, 232, 213, 10, 107, 235, 242, 177, 228, 200, 143, 68, 172, 91, 70, 235, 95, 232, 175, 164, 149, 194, 55, 149, 171, 178, 144, 7, 137, 16, 39, 68, 185, 24, 133, 23, 185, 199, 1, 29, 92, 185, 34, 113, 188, 219, 78, 183, 106, 210, 32, 230, 82, 50, 66, 97, 113, 51, 44, 80, 51, 123, 31, 164, 119, 152, 104, 197, 103, 90, 25, 219, 18, 208, 153, 250, 149, 203, 166, 101, 58, 229, 102, 3, 194, 230, 40, 190, 83, 161, 64, 128, 57, 251, 170, 46, 206, 230, 38, 187, 208, 200, 98, 100, 90, 167, 116, 63, 247, 124, 240, 165, 45, 203, 170, 46, 209, 68, 106, 120, 209, 111, 226, 100, 90, 185, 9, 208, 145, 23, 171, 3, 47, 9, 197, 119, 14, 189, 110, 140, 103, 255, 197, 255, 11, 113, 238, 72, 230, 145, 64, 13, 68, 121, 23, 38, 180, 118, 247, 70, 243, 8, 200, 105, 102, 249, 142, 255, 147, 55, 7, 16, 73, 107, 122, 73, 252, 231, 19],
+    [20, 66, 234, 123, 7, 160, 191, 95, 173, 94, 10, 235, 209, 50, 225, 178, 127, 59, 71, 86, 228, 51, 45, 187, 134, 216, 227, 250, 109, 165, 228, 85, 9, 144, 141, 62, 55, 75, 64, 251, 232, 148, 80, 84, 182, 227, 132, 85, 22, 170, 42, 179, 54, 164, 231, 163, 44, 92, 187, 231, 8, 130, 215, 238, 154, 33, 7, 222, 99, 228, 65, 228, 186, 238, 48, 30, 225, 243, 84, 243, 147, 87, 52, 216, 191, 229, 24, 114, 201, 43, 80, 148, 96, 159, 162, 158, 9, 215, 36, 83, 193, 2, 59, 106, 141, 36, 134, 44, 208, 115, 229, 62, 107, 217, 75, 56, 162, 117, 75, 112, 195, 190, 134, 57, 179, 230, 10, 117, 154, 174, 200, 129, 173, 116, 227, 16, 132, 202, 182, 20, 242, 110, 209, 101, 5, 248, 125, 223, 160, 127, 154, 196, 107, 156, 176, 6, 172, 226, 226, 28, 73, 30, 132, 31, 102, 140, 37, 139, 196, 18, 68, 104, 22, 243, 59, 217, 246, 60, 228, 147, 52, 211, 147, 221, 238, 16, 153, 170, 15, 194, 65, 183],
+    [11, 147, 177, 186, 205, 220, 70, 4, 203, 170, 111, 106, 84, 199, 137, 56, 19, 84, 41, 101, 205, 107, 238, 216, 255, 48, 36, 169, 114, 174, 30, 108, 62, 222, 82, 100, 70, 109, 111, 79, 101, 140, 134, 197, 103, 90, 194, 251, 20, 116, 26, 104, 115, 58, 198, 113, 223, 220, 151, 32, 174, 80, 18, 105, 94, 53, 139, 73, 166, 234, 152, 176, 134, 95, 46, 198, 172, 64, 96, 221, 216, 233, 208, 228, 23, 118, 66, 54, 71, 251, 128, 249, 75, 187, 58, 169, 12, 44, 17, 118, 247, 36, 252, 101, 162, 72, 64, 83, 38, 182, 118, 206, 101, 231, 249, 170, 178, 186, 101, 0, 166, 183, 221, 78, 23, 183, 81, 175, 86, 54, 152, 94, 188, 129, 83, 202, 143, 126, 26, 169, 126, 110, 127, 202, 24, 7, 243, 194, 164, 232, 59, 12, 42, 232, 115, 57, 240, 83, 34, 232, 135, 156, 14, 66, 60, 17, 120, 229, 56, 34, 189, 124, 157, 112, 228, 66, 31, 150, 84, 183, 24, 97, 28, 235, 225, 244, 117, 208, 225, 83, 162, 179],
+    [1, 169, 230, 150, 192, 42, 129, 41, 205, 4, 37, 5, 147, 186, 110, 188, 205, 42, 27, 167, 198, 45, 131, 236, 6, 67, 57, 142, 81, 90, 67, 18, 66, 56, 23, 140, 17, 239, 25, 91, 207, 153, 29, 207, 201, 137, 208, 181, 8, 20, 65, 111, 48, 30, 5, 25, 161, 99, 205, 60, 126, 147, 217, 245, 163, 243, 132, 26, 200, 146, 44, 204, 221, 249, 34, 15, 23, 203, 119, 26, 198, 2, 120, 185, 245, 66, 69, 137, 184, 123, 211, 90, 251, 110, 119, 217, 25, 224, 185, 140, 17, 173, 187, 180, 146, 219, 133, 223, 224, 79, 228, 124, 63, 117, 0, 10, 123, 101, 20, 181, 85, 77, 63, 202, 100, 173, 6, 26, 174, 97, 227, 250, 123, 83, 108, 177, 104, 41, 101, 90, 130, 145, 154, 249, 8, 85, 118, 240, 222, 24, 100, 82, 250, 198, 36, 208, 199, 252, 114, 15, 77, 177, 130, 55, 215, 236, 200, 103, 167, 221, 158, 218, 208, 156, 221, 149, 62, 17, 232, 58, 165, 188, 236, 66, 182, 158, 180, 248, 70, 105, 211, 183],
+    [18, 19, 16, 249, 100, 42, 205, 133, 245, 219, 23, 58, 21, 116, 167, 251, 156, 221, 11, 79, 44, 231, 138, 148, 106, 108, 79, 19, 219, 87, 175, 37, 48, 3, 182, 97, 223, 20, 236, 92, 90, 216, 35, 188, 253, 230, 145, 145, 12, 154, 74, 135, 149, 124, 206, 135, 105, 209, 145, 1, 40, 238, 120, 12, 170, 68, 127, 115, 128, 139, 41, 141, 78, 28, 250, 74, 12, 27, 255, 40, 233, 9, 166, 52, 107, 207, 255, 189, 25, 56, 57, 188, 227, 6, 233, 170, 16, 125, 238, 32, 170, 189, 26, 26, 203, 164, 202, 61, 7, 223, 49, 197, 190, 154, 192, 48, 18, 198, 48, 112, 70, 132, 207, 29, 85, 210, 179, 26, 17, 228, 141, 86, 37, 240, 255, 63, 23, 176, 238, 141, 0, 108, 5, 69, 15, 114, 180, 45, 59, 153, 161, 94, 183, 151, 76, 25, 187, 142, 2, 113, 158, 168, 23, 169, 104, 245, 47, 94, 208, 231, 194, 224, 218, 229, 47, 196, 73, 211, 181, 53, 107, 127, 236, 103, 26, 179, 110, 230, 68, 207, 223, 138],
+    [25, 77, 140, 151, 8, 54, 154, 163, 83, 252, 77, 151, 151, 162, 25, 1, 66, 172, 160, 31, 179, 149, 119, 146, 111, 211, 240, 228, 224, 194, 201, 150, 164, 128, 116, 227, 53, 118, 69, 68, 140, 52, 122, 120, 93, 202, 165, 44, 17, 50, 73, 164, 221, 59, 135, 203, 13, 182, 133, 54, 155, 103, 46, 152, 194, 121, 58, 55, 62, 230, 213, 31, 36, 60, 152, 187, 2, 138, 82, 84, 0, 2, 22, 30, 202, 114, 200, 130, 48, 226, 33, 50, 251, 168, 97, 94, 18, 12, 144, 17, 225, 103, 59, 168, 89, 191, 128, 16, 30, 122, 132, 59, 214, 84, 247, 55, 220, 77, 76, 84, 82, 180, 138, 144, 188, 19, 191, 241, 59, 237, 176, 10, 224, 126, 151, 46, 63, 67, 164, 128, 65, 82, 21, 176, 1, 199, 147, 250, 19, 35, 242, 72, 99, 39, 93, 243, 235, 11, 145, 119, 17, 112, 44, 198, 229, 247, 125, 31, 162, 121, 144, 125, 216, 219, 17, 48, 78, 221, 168, 111, 74, 153, 64, 225, 237, 86, 219, 234, 184, 118, 174, 122],
+    [5, 148, 216, 93, 167, 134, 12, 245, 0, 115, 102, 60, 2, 121, 28, 241, 150, 161, 1, 41, 180, 109, 89, 202, 204, 140, 126, 120, 63, 152, 231, 221, 3, 61, 121, 19, 234, 197, 168, 202, 21, 0, 52, 96, 175, 115, 227, 139, 25, 12, 84, 124, 165, 197, 179, 120, 213, 216, 19, 227, 40, 34, 32, 180, 159, 184, 190, 250, 93, 139, 29, 203, 4, 206, 252, 114, 181, 116, 33, 137, 162, 227, 202, 4, 53, 137, 234, 249, 197, 49, 189, 3, 131, 56, 153, 18, 24, 8, 62, 199, 6, 135, 105, 127, 246, 160, 88, 142, 120, 217, 209, 25, 12, 241, 247, 186, 168, 209, 200, 53, 224, 104, 212, 186, 120, 43, 69, 85, 56, 156, 137, 56, 217, 102, 1, 148, 78, 72, 169, 169, 245, 87, 47, 139, 19, 171, 50, 165, 124, 214, 27, 248, 162, 111, 88, 210, 240, 210, 28, 47, 68, 199, 100, 45, 60, 75, 223, 36, 60, 238, 187, 210, 165, 33, 70, 73, 250, 144, 63, 171, 12, 229, 219, 81, 83, 163, 13, 229, 192, 66, 219, 194],
+    [22, 203, 80, 244, 156, 126, 240, 128, 21, 82, 131, 28, 106, 106, 72, 88, 243, 169, 123, 227, 224, 240, 130, 155, 106, 9, 247, 122, 127, 111, 64, 9, 22, 26, 108, 145, 155, 175, 12, 129, 207, 97, 50, 67, 67, 189, 9, 207, 13, 106, 168, 53, 16, 251, 225, 20, 60, 120, 225, 240, 47, 33, 48, 15, 131, 69, 216, 127, 89, 17, 26, 153, 117, 55, 239, 48, 216, 211, 129, 82, 79, 52, 18, 27, 176, 192, 50, 86, 160, 173, 70, 72, 247, 54, 7, 247, 0, 51, 13, 101, 196, 82, 31, 83, 239, 214, 135, 3, 53, 21, 3, 122, 37, 158, 245, 235, 243, 226, 134, 198, 161, 119, 228, 68, 171, 251, 176, 245, 49, 157, 112, 50, 199, 9, 211, 104, 53, 43, 81, 150, 64, 76, 117, 209, 15, 195, 30, 30, 25, 28, 155, 2, 26, 38, 150, 195, 51, 35, 240, 31, 86, 189, 57, 111, 76, 5, 66, 24, 17, 110, 16, 78, 205, 5, 27, 14, 6, 30, 118, 71, 158, 149, 236, 41, 199, 120, 102, 238, 57, 54, 214, 99],
+    [22, 188, 110, 195, 123, 137, 60, 100, 74, 143, 205, 110, 159, 182, 144, 105, 215, 93, 206, 226, 139, 5, 161, 172, 10, 105, 70, 87, 77, 207, 89, 136, 243, 86, 60, 39, 137, 144, 216, 36, 152, 244, 147, 191, 141, 30, 164, 149, 20, 25, 88, 185, 70, 243, 17, 211, 135, 8, 14, 242, 172, 62, 248, 214, 13, 145, 154, 81, 80, 108, 228, 39, 140, 94, 144, 110, 18, 51, 3, 182, 101, 249, 20, 137, 155, 147, 189, 86, 16, 149, 77, 254, 146, 186, 36, 40, 10, 4, 57, 199, 8, 2, 78, 184, 250, 81, 46, 130, 239, 108, 78, 66, 50, 126, 31, 175, 222, 155, 94, 79, 233, 216, 255, 200, 140, 128, 79, 225, 239, 55, 56, 118, 115, 9, 37, 6, 220, 238, 14, 107, 49, 245, 172, 47, 8, 122, 226, 153, 219, 236, 124, 85, 63, 184, 163, 120, 254, 202, 141, 46, 244, 146, 231, 24, 64, 4, 248, 29, 112, 17, 225, 67, 9, 201, 67, 230, 178, 75, 94, 252, 92, 176, 201, 123, 44, 244, 115, 199, 242, 43, 76, 53],
+];
+
+/// Number of elements in the largest input of a BLS12-381 function.
+#[cfg(feature = "protocol_feature_bls12381")]
+const BLS12381_MAX_ELEMENTS: usize = 10;
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_p1_sum_input() -> [u8; BLS12381_MAX_ELEMENTS * 97] {
+    let mut res = [0u8; BLS12381_MAX_ELEMENTS * 97];
+    for (i, chunk) in res.chunks_mut(97).enumerate() {
+        chunk[0] = (i % 2) as u8;
+        chunk[1..].copy_from_slice(&G1_POINTS[i]);
+    }
+    res
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_p2_sum_input() -> [u8; BLS12381_MAX_ELEMENTS * 193] {
+    let mut res = [0u8; BLS12381_MAX_ELEMENTS * 193];
+    for (i, chunk) in res.chunks_mut(193).enumerate() {
+        chunk[0] = (i % 2) as u8;
+        chunk[1..].copy_from_slice(&G2_POINTS[i]);
+    }
+    res
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_g1_multiexp_input() -> [u8; BLS12381_MAX_ELEMENTS * 128] {
+    let mut res = [0u8; BLS12381_MAX_ELEMENTS * 128];
+    for (i, chunk) in res.chunks_mut(128).enumerate() {
+        chunk[..96].copy_from_slice(&G1_POINTS[i]);
+        chunk[96..].copy_from_slice(&G1_POINTS[i][64..]);
+    }
+    res
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_g2_multiexp_input() -> [u8; BLS12381_MAX_ELEMENTS * 224] {
+    let mut res = [0u8; BLS12381_MAX_ELEMENTS * 224];
+    for (i, chunk) in res.chunks_mut(224).enumerate() {
+        chunk[..192].copy_from_slice(&G2_POINTS[i]);
+        chunk[192..].copy_from_slice(&G2_POINTS[i][160..]);
+    }
+    res
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_map_fp_to_g1_input() -> [u8; BLS12381_MAX_ELEMENTS * 48] {
+    let mut res = [0u8; BLS12381_MAX_ELEMENTS * 48];
+    for (i, chunk) in res.chunks_mut(48).enumerate() {
+        chunk.copy_from_slice(&G1_POINTS[i][..48]);
+    }
+    res
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_map_fp2_to_g2_input() -> [u8; BLS12381_MAX_ELEMENTS * 96] {
+    let mut res = [0u8; BLS12381_MAX_ELEMENTS * 96];
+    for (i, chunk) in res.chunks_mut(96).enumerate() {
+        chunk.copy_from_slice(&G2_POINTS[i][..96]);
+    }
+    res
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_pairing_input() -> [u8; BLS12381_MAX_ELEMENTS * 288] {
+    let mut res = [0u8; BLS12381_MAX_ELEMENTS * 288];
+    for (i, chunk) in res.chunks_mut(288).enumerate() {
+        chunk[..96].copy_from_slice(&G1_POINTS[i]);
+        chunk[96..].copy_from_slice(&G2_POINTS[i]);
+    }
+    res
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_p1_decompress_input() -> [u8; BLS12381_MAX_ELEMENTS * 48] {
+    let mut res = bls12381_map_fp_to_g1_input();
+    for chunk in res.chunks_mut(48) {
+        chunk[0] |= 0x80;
+    }
+    res
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_p2_decompress_input() -> [u8; BLS12381_MAX_ELEMENTS * 96] {
+    let mut res = bls12381_map_fp2_to_g2_input();
+    for chunk in res.chunks_mut(96) {
+        chunk[0] |= 0x80;
+    }
+    res
+}
+
+// Defines a function to measure the base or element cost of a BLS12-381
+// function. Also measures `base`, `read_memory_base`, `read_memory_byte`,
+// `write_register_base` and `write_register_byte`. However the curve
+// operations are more expensive than those so we are okay overcharging them.
+// Calls `$host_fn` with the first `$elements` elements of the input
+// `$repetitions` times.
+macro_rules! bls12381_bench {
+    ($name:ident, $host_fn:ident, $input:ident, $element_size:expr, $elements:expr, $repetitions:expr) => {
+        #[no_mangle]
+        #[cfg(feature = "protocol_feature_bls12381")]
+        pub unsafe fn $name() {
+            let input = $input();
+            let input = &input[..$elements * $element_size];
+            for _ in 0..$repetitions {
+                let result = $host_fn(input.len() as _, input.as_ptr() as _, 0);
+                // Invalid input exits early and does not reflect the full cost.
+                assert!(result == 0);
+            }
+        }
+    };
+}
+
+bls12381_bench!(bls12381_p1_sum_1_100, bls12381_p1_sum, bls12381_p1_sum_input, 97, 1, 100);
+bls12381_bench!(bls12381_p1_sum_10_100, bls12381_p1_sum, bls12381_p1_sum_input, 97, 10, 100);
+bls12381_bench!(bls12381_p2_sum_1_100, bls12381_p2_sum, bls12381_p2_sum_input, 193, 1, 100);
+bls12381_bench!(bls12381_p2_sum_10_100, bls12381_p2_sum, bls12381_p2_sum_input, 193, 10, 100);
+bls12381_bench!(
+    bls12381_g1_multiexp_1_10,
+    bls12381_g1_multiexp,
+    bls12381_g1_multiexp_input,
+    128,
+    1,
+    10
+);
+bls12381_bench!(
+    bls12381_g1_multiexp_10_10,
+    bls12381_g1_multiexp,
+    bls12381_g1_multiexp_input,
+    128,
+    10,
+    10
+);
+bls12381_bench!(
+    bls12381_g2_multiexp_1_10,
+    bls12381_g2_multiexp,
+    bls12381_g2_multiexp_input,
+    224,
+    1,
+    10
+);
+bls12381_bench!(
+    bls12381_g2_multiexp_10_10,
+    bls12381_g2_multiexp,
+    bls12381_g2_multiexp_input,
+    224,
+    10,
+    10
+);
+bls12381_bench!(
+    bls12381_map_fp_to_g1_1_10,
+    bls12381_map_fp_to_g1,
+    bls12381_map_fp_to_g1_input,
+    48,
+    1,
+    10
+);
+bls12381_bench!(
+    bls12381_map_fp_to_g1_10_10,
+    bls12381_map_fp_to_g1,
+    bls12381_map_fp_to_g1_input,
+    48,
+    10,
+    10
+);
+bls12381_bench!(
+    bls12381_map_fp2_to_g2_1_10,
+    bls12381_map_fp2_to_g2,
+    bls12381_map_fp2_to_g2_input,
+    96,
+    1,
+    10
+);
+bls12381_bench!(
+    bls12381_map_fp2_to_g2_10_10,
+    bls12381_map_fp2_to_g2,
+    bls12381_map_fp2_to_g2_input,
+    96,
+    10,
+    10
+);
+bls12381_bench!(
+    bls12381_p1_decompress_1_100,
+    bls12381_p1_decompress,
+    bls12381_p1_decompress_input,
+    48,
+    1,
+    100
+);
+bls12381_bench!(
+    bls12381_p1_decompress_10_100,
+    bls12381_p1_decompress,
+    bls12381_p1_decompress_input,
+    48,
+    10,
+    100
+);
+bls12381_bench!(
+    bls12381_p2_decompress_1_100,
+    bls12381_p2_decompress,
+    bls12381_p2_decompress_input,
+    96,
+    1,
+    100
+);
+bls12381_bench!(
+    bls12381_p2_decompress_10_100,
+    bls12381_p2_decompress,
+    bls12381_p2_decompress_input,
+    96,
+    10,
+    100
+);
+
+// Function to measure `bls12381_pairing_base`. Computes the pairing check on
+// one element 10 times.
+#[no_mangle]
+#[cfg(feature = "protocol_feature_bls12381")]
+pub unsafe fn bls12381_pairing_check_1_10() {
+    let input = bls12381_pairing_input();
+    let input = &input[..288];
+    for _ in 0..10 {
+        // The check fails for the given points, but only after the full
+        // computation.
+        assert!(bls12381_pairing_check(input.len() as _, input.as_ptr() as _) == 2);
+    }
+}
+
+// Function to measure `bls12381_pairing_element`. Computes the pairing check
+// on 10 elements 10 times.
+#[no_mangle]
+#[cfg(feature = "protocol_feature_bls12381")]
+pub unsafe fn bls12381_pairing_check_10_10() {
+    let input = bls12381_pairing_input();
+    for _ in 0..10 {
+        assert!(bls12381_pairing_check(input.len() as _, input.as_ptr() as _) == 2);
+    }
+}
+
 // ###############
 // # Storage API #
 // ###############
@@ -168,6 +168,27 @@ extern "C" {
     fn alt_bn128_g1_sum(value_len: u64, value_ptr: u64, register_id: u64);
     #[cfg(feature = "latest_protocol")]
     fn alt_bn128_pairing_check(value_len: u64, value_ptr: u64) -> u64;
+    // #################
+    // # BLS12-381 API #
+    // #################
+    #[cfg(feature = "nightly")]
+    fn bls12381_p1_sum(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    #[cfg(feature = "nightly")]
+    fn bls12381_p2_sum(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    #[cfg(feature = "nightly")]
+    fn bls12381_g1_multiexp(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    #[cfg(feature = "nightly")]
+    fn bls12381_g2_multiexp(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    #[cfg(feature = "nightly")]
+    fn bls12381_map_fp_to_g1(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    #[cfg(feature = "nightly")]
+    fn bls12381_map_fp2_to_g2(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    #[cfg(feature = "nightly")]
+    fn bls12381_pairing_check(value_len: u64, value_ptr: u64) -> u64;
+    #[cfg(feature = "nightly")]
+    fn bls12381_p1_decompress(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    #[cfg(feature = "nightly")]
+    fn bls12381_p2_decompress(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
 }
 
 macro_rules! ext_test {
@@ -231,6 +252,48 @@ pub unsafe fn ext_sha256() {
     value_return(result.len() as u64, result.as_ptr() as *const u64 as u64);
 }
 
+/// Calls a BLS12-381 function on the input and returns its result as a
+/// little-endian `u64`, followed by the written register if it succeeded.
+macro_rules! ext_bls12381 {
+    ($export_func:ident, $call_ext:ident) => {
+        #[no_mangle]
+        #[cfg(feature = "nightly")]
+        pub unsafe fn $export_func() {
+            input(0);
+            let bytes = vec![0; register_len(0) as usize];
+            read_register(0, bytes.as_ptr() as *const u64 as u64);
+            let res = $call_ext(bytes.len() as u64, bytes.as_ptr() as *const u64 as u64, 0);
+            let mut result = res.to_le_bytes().to_vec();
+            if res == 0 {
+                let value = vec![0; register_len(0) as usize];
+                read_register(0, value.as_ptr() as *const u64 as u64);
+                result.extend(value);
+            }
+            value_return(result.len() as u64, result.as_ptr() as *const u64 as u64);
+        }
+    };
+}
+
+ext_bls12381!(ext_bls12381_p1_sum, bls12381_p1_sum);
+ext_bls12381!(ext_bls12381_p2_sum, bls12381_p2_sum);
+ext_bls12381!(ext_bls12381_g1_multiexp, bls12381_g1_multiexp);
+ext_bls12381!(ext_bls12381_g2_multiexp, bls12381_g2_multiexp);
+ext_bls12381!(ext_bls12381_map_fp_to_g1, bls12381_map_fp_to_g1);
+ext_bls12381!(ext_bls12381_map_fp2_to_g2, bls12381_map_fp2_to_g2);
+ext_bls12381!(ext_bls12381_p1_decompress, bls12381_p1_decompress);
+ext_bls12381!(ext_bls12381_p2_decompress, bls12381_p2_decompress);
+
+#[no_mangle]
+#[cfg(feature = "nightly")]
+pub unsafe fn ext_bls12381_pairing_check() {
+    input(0);
+    let bytes = vec![0; register_len(0) as usize];
+    read_register(0, bytes.as_ptr() as *const u64 as u64);
+    let res = bls12381_pairing_check(bytes.len() as u64, bytes.as_ptr() as *const u64 as u64);
+    let result = res.to_le_bytes();
+    value_return(result.len() as u64, result.as_ptr() as *const u64 as u64);
+}
+
 #[no_mangle]
 pub unsafe fn ext_used_gas() {
     let initial_used_gas = used_gas();
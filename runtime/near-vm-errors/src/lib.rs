@@ -287,6 +287,12 @@ pub enum HostError {
     /// Invalid input to ed25519 signature verification function (e.g. signature cannot be
     /// derived from bytes).
     Ed25519VerifyInvalidInput { msg: String },
+    /// Invalid input to the P-256 signature verification function (e.g. the
+    /// signature or the public key has the wrong length).
+    P256VerifyInvalidInput { msg: String },
+    /// Invalid input to a BLS12-381 function (e.g. the input length is not a
+    /// multiple of the element size).
+    Bls12381InvalidInput { msg: String },
 }
 
 #[derive(Debug, PartialEq)]
@@ -492,6 +498,8 @@ impl std::fmt::Display for HostError {
             AltBn128InvalidInput { msg } => write!(f, "AltBn128 invalid input: {}", msg),
             ECRecoverError { msg } => write!(f, "ECDSA recover error: {}", msg),
             Ed25519VerifyInvalidInput { msg } => write!(f, "ED25519 signature verification error: {}", msg),
+            P256VerifyInvalidInput { msg } => write!(f, "P-256 signature verification error: {}", msg),
+            Bls12381InvalidInput { msg } => write!(f, "BLS12-381 invalid input: {}", msg),
         }
     }
 }
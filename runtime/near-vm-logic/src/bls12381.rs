@@ -0,0 +1,304 @@
+//! Operations on the BLS12-381 curve.
+//!
+//! Points are encoded as in the ZCash serialization format used by most
+//! BLS12-381 libraries: field elements are big-endian, the uncompressed G1
+//! point is `x || y` and the three most significant bits of the first byte are
+//! flags (compression, infinity and sign of `y`). A G2 point has coordinates in
+//! Fp2, which are encoded as `c1 || c0`. Scalars are 32-byte little-endian
+//! integers.
+//!
+//! Input which is malformed as a whole (its length is not a multiple of the
+//! element size) is reported as [`InvalidInput`]. An element which does not
+//! decode to a point on the curve is not an error: the functions return `None`
+//! and the host function reports the failure to the contract.
+
+use crate::array_utils::ArrayChunks;
+use blst::{
+    blst_fp, blst_fp12, blst_fp2, blst_p1, blst_p1_affine, blst_p2, blst_p2_affine, BLST_ERROR,
+};
+use near_vm_errors::{HostError, VMLogicError};
+
+const BOOL_SIZE: usize = 1;
+const SCALAR_SIZE: usize = 256 / 8;
+const FP_SIZE: usize = 48;
+const FP2_SIZE: usize = 2 * FP_SIZE;
+const G1_SIZE: usize = 2 * FP_SIZE;
+const G2_SIZE: usize = 2 * FP2_SIZE;
+const G1_COMPRESSED_SIZE: usize = FP_SIZE;
+const G2_COMPRESSED_SIZE: usize = FP2_SIZE;
+
+/// The field modulus `p`, big-endian.
+const MODULUS: [u8; FP_SIZE] = [
+    0x1a, 0x01, 0x11, 0xea, 0x39, 0x7f, 0xe6, 0x9a, 0x4b, 0x1b, 0xa7, 0xb6, 0x43, 0x4b, 0xac, 0xd7,
+    0x64, 0x77, 0x4b, 0x84, 0xf3, 0x85, 0x12, 0xbf, 0x67, 0x30, 0xd2, 0xa0, 0xf6, 0xb0, 0xf6, 0x24,
+    0x1e, 0xab, 0xff, 0xfe, 0xb1, 0x53, 0xff, 0xff, 0xb9, 0xfe, 0xff, 0xff, 0xff, 0xff, 0xaa, 0xab,
+];
+
+/// Flag in the first byte of an encoded point marking the compressed form.
+const COMPRESSION_FLAG: u8 = 0x80;
+
+pub(crate) struct InvalidInput {
+    pub(crate) msg: String,
+}
+
+impl From<InvalidInput> for VMLogicError {
+    fn from(err: InvalidInput) -> Self {
+        HostError::Bls12381InvalidInput { msg: err.msg }.into()
+    }
+}
+
+pub(crate) fn split_elements<const ELEMENT_SIZE: usize>(
+    data: &[u8],
+) -> Result<ArrayChunks<'_, ELEMENT_SIZE>, InvalidInput> {
+    ArrayChunks::new(data).map_err(|()| {
+        let msg =
+            format!("invalid array, byte length {}, element size {}", data.len(), ELEMENT_SIZE);
+        InvalidInput { msg }
+    })
+}
+
+const P1_SUM_ELEMENT_SIZE: usize = BOOL_SIZE + G1_SIZE;
+
+/// Computes `\sum_i (-1)^{sign_i} p_i` for points on the G1 curve. The points
+/// are not required to be in the prime order subgroup.
+pub(crate) fn p1_sum(elements: ArrayChunks<'_, P1_SUM_ELEMENT_SIZE>) -> Option<[u8; G1_SIZE]> {
+    let mut res = blst_p1::default();
+    for chunk in elements {
+        let (sign, point) = stdx::split_array(chunk);
+        let sign = decode_bool(sign)?;
+        let mut point = p1_from_affine(&decode_p1(point)?);
+        unsafe {
+            blst::blst_p1_cneg(&mut point, sign);
+            let res_ptr: *mut blst_p1 = &mut res;
+            blst::blst_p1_add_or_double(res_ptr, res_ptr, &point);
+        }
+    }
+    Some(encode_p1(&res))
+}
+
+const P2_SUM_ELEMENT_SIZE: usize = BOOL_SIZE + G2_SIZE;
+
+/// Computes `\sum_i (-1)^{sign_i} p_i` for points on the G2 curve. The points
+/// are not required to be in the prime order subgroup.
+pub(crate) fn p2_sum(elements: ArrayChunks<'_, P2_SUM_ELEMENT_SIZE>) -> Option<[u8; G2_SIZE]> {
+    let mut res = blst_p2::default();
+    for chunk in elements {
+        let (sign, point) = stdx::split_array(chunk);
+        let sign = decode_bool(sign)?;
+        let mut point = p2_from_affine(&decode_p2(point)?);
+        unsafe {
+            blst::blst_p2_cneg(&mut point, sign);
+            let res_ptr: *mut blst_p2 = &mut res;
+            blst::blst_p2_add_or_double(res_ptr, res_ptr, &point);
+        }
+    }
+    Some(encode_p2(&res))
+}
+
+const G1_MULTIEXP_ELEMENT_SIZE: usize = G1_SIZE + SCALAR_SIZE;
+
+/// Computes `\sum_i s_i p_i` for points in the G1 subgroup.
+pub(crate) fn g1_multiexp(
+    elements: ArrayChunks<'_, G1_MULTIEXP_ELEMENT_SIZE>,
+) -> Option<[u8; G1_SIZE]> {
+    let mut res = blst_p1::default();
+    for chunk in elements {
+        let (point, scalar): (_, &[u8; SCALAR_SIZE]) = stdx::split_array(chunk);
+        let point = decode_p1(point)?;
+        if !unsafe { blst::blst_p1_affine_in_g1(&point) } {
+            return None;
+        }
+        let point = p1_from_affine(&point);
+        let mut product = blst_p1::default();
+        unsafe {
+            blst::blst_p1_mult(&mut product, &point, scalar.as_ptr(), 8 * SCALAR_SIZE);
+            let res_ptr: *mut blst_p1 = &mut res;
+            blst::blst_p1_add_or_double(res_ptr, res_ptr, &product);
+        }
+    }
+    Some(encode_p1(&res))
+}
+
+const G2_MULTIEXP_ELEMENT_SIZE: usize = G2_SIZE + SCALAR_SIZE;
+
+/// Computes `\sum_i s_i p_i` for points in the G2 subgroup.
+pub(crate) fn g2_multiexp(
+    elements: ArrayChunks<'_, G2_MULTIEXP_ELEMENT_SIZE>,
+) -> Option<[u8; G2_SIZE]> {
+    let mut res = blst_p2::default();
+    for chunk in elements {
+        let (point, scalar): (_, &[u8; SCALAR_SIZE]) = stdx::split_array(chunk);
+        let point = decode_p2(point)?;
+        if !unsafe { blst::blst_p2_affine_in_g2(&point) } {
+            return None;
+        }
+        let point = p2_from_affine(&point);
+        let mut product = blst_p2::default();
+        unsafe {
+            blst::blst_p2_mult(&mut product, &point, scalar.as_ptr(), 8 * SCALAR_SIZE);
+            let res_ptr: *mut blst_p2 = &mut res;
+            blst::blst_p2_add_or_double(res_ptr, res_ptr, &product);
+        }
+    }
+    Some(encode_p2(&res))
+}
+
+/// Maps each Fp element to a point in G1, as specified by the `map_to_curve`
+/// and `clear_cofactor` steps of the BLS12381G1_XMD:SHA-256_SSWU_RO_ suite in
+/// the hash-to-curve RFC. Returns the concatenation of the points.
+pub(crate) fn map_fp_to_g1(elements: ArrayChunks<'_, FP_SIZE>) -> Option<Vec<u8>> {
+    let mut res = Vec::with_capacity(elements.len() * G1_SIZE);
+    for chunk in elements {
+        let fp = decode_fp(chunk)?;
+        let mut point = blst_p1::default();
+        unsafe { blst::blst_map_to_g1(&mut point, &fp, std::ptr::null()) };
+        res.extend_from_slice(&encode_p1(&point));
+    }
+    Some(res)
+}
+
+/// Maps each Fp2 element to a point in G2, as specified by the
+/// `map_to_curve` and `clear_cofactor` steps of the
+/// BLS12381G2_XMD:SHA-256_SSWU_RO_ suite in the hash-to-curve RFC. Returns the
+/// concatenation of the points.
+pub(crate) fn map_fp2_to_g2(elements: ArrayChunks<'_, FP2_SIZE>) -> Option<Vec<u8>> {
+    let mut res = Vec::with_capacity(elements.len() * G2_SIZE);
+    for chunk in elements {
+        let fp2 = decode_fp2(chunk)?;
+        let mut point = blst_p2::default();
+        unsafe { blst::blst_map_to_g2(&mut point, &fp2, std::ptr::null()) };
+        res.extend_from_slice(&encode_p2(&point));
+    }
+    Some(res)
+}
+
+const PAIRING_CHECK_ELEMENT_SIZE: usize = G1_SIZE + G2_SIZE;
+
+/// Checks whether `\prod_i e(p_i, q_i)` is one, for points `p_i` in G1 and
+/// `q_i` in G2.
+pub(crate) fn pairing_check(elements: ArrayChunks<'_, PAIRING_CHECK_ELEMENT_SIZE>) -> Option<bool> {
+    let mut acc = unsafe { *blst::blst_fp12_one() };
+    for chunk in elements {
+        let (p, q) = stdx::split_array(chunk);
+        let p = decode_p1(p)?;
+        let q = decode_p2(q)?;
+        if !unsafe { blst::blst_p1_affine_in_g1(&p) && blst::blst_p2_affine_in_g2(&q) } {
+            return None;
+        }
+        // The pairing with the point at infinity is one.
+        if unsafe { blst::blst_p1_affine_is_inf(&p) || blst::blst_p2_affine_is_inf(&q) } {
+            continue;
+        }
+        let mut miller_loop = blst_fp12::default();
+        unsafe {
+            blst::blst_miller_loop(&mut miller_loop, &q, &p);
+            let acc_ptr: *mut blst_fp12 = &mut acc;
+            blst::blst_fp12_mul(acc_ptr, acc_ptr, &miller_loop);
+        }
+    }
+    let mut res = blst_fp12::default();
+    unsafe { blst::blst_final_exp(&mut res, &acc) };
+    Some(unsafe { blst::blst_fp12_is_one(&res) })
+}
+
+/// Decompresses G1 points, which must be on the curve but are not required to
+/// be in the subgroup. Returns the concatenation of the uncompressed points.
+pub(crate) fn p1_decompress(elements: ArrayChunks<'_, G1_COMPRESSED_SIZE>) -> Option<Vec<u8>> {
+    let mut res = Vec::with_capacity(elements.len() * G1_SIZE);
+    for chunk in elements {
+        let mut point = blst_p1_affine::default();
+        let err = unsafe { blst::blst_p1_uncompress(&mut point, chunk.as_ptr()) };
+        if err != BLST_ERROR::BLST_SUCCESS {
+            return None;
+        }
+        let mut out = [0u8; G1_SIZE];
+        unsafe { blst::blst_p1_affine_serialize(out.as_mut_ptr(), &point) };
+        res.extend_from_slice(&out);
+    }
+    Some(res)
+}
+
+/// Decompresses G2 points, which must be on the curve but are not required to
+/// be in the subgroup. Returns the concatenation of the uncompressed points.
+pub(crate) fn p2_decompress(elements: ArrayChunks<'_, G2_COMPRESSED_SIZE>) -> Option<Vec<u8>> {
+    let mut res = Vec::with_capacity(elements.len() * G2_SIZE);
+    for chunk in elements {
+        let mut point = blst_p2_affine::default();
+        let err = unsafe { blst::blst_p2_uncompress(&mut point, chunk.as_ptr()) };
+        if err != BLST_ERROR::BLST_SUCCESS {
+            return None;
+        }
+        let mut out = [0u8; G2_SIZE];
+        unsafe { blst::blst_p2_affine_serialize(out.as_mut_ptr(), &point) };
+        res.extend_from_slice(&out);
+    }
+    Some(res)
+}
+
+fn p1_from_affine(point: &blst_p1_affine) -> blst_p1 {
+    let mut res = blst_p1::default();
+    unsafe { blst::blst_p1_from_affine(&mut res, point) };
+    res
+}
+
+fn p2_from_affine(point: &blst_p2_affine) -> blst_p2 {
+    let mut res = blst_p2::default();
+    unsafe { blst::blst_p2_from_affine(&mut res, point) };
+    res
+}
+
+fn encode_p1(point: &blst_p1) -> [u8; G1_SIZE] {
+    let mut res = [0u8; G1_SIZE];
+    unsafe { blst::blst_p1_serialize(res.as_mut_ptr(), point) };
+    res
+}
+
+fn encode_p2(point: &blst_p2) -> [u8; G2_SIZE] {
+    let mut res = [0u8; G2_SIZE];
+    unsafe { blst::blst_p2_serialize(res.as_mut_ptr(), point) };
+    res
+}
+
+/// Decodes an uncompressed G1 point, checking that it is on the curve.
+fn decode_p1(raw: &[u8; G1_SIZE]) -> Option<blst_p1_affine> {
+    // `blst` would also accept a compressed point here.
+    if raw[0] & COMPRESSION_FLAG != 0 {
+        return None;
+    }
+    let mut point = blst_p1_affine::default();
+    let err = unsafe { blst::blst_p1_deserialize(&mut point, raw.as_ptr()) };
+    (err == BLST_ERROR::BLST_SUCCESS).then_some(point)
+}
+
+/// Decodes an uncompressed G2 point, checking that it is on the curve.
+fn decode_p2(raw: &[u8; G2_SIZE]) -> Option<blst_p2_affine> {
+    if raw[0] & COMPRESSION_FLAG != 0 {
+        return None;
+    }
+    let mut point = blst_p2_affine::default();
+    let err = unsafe { blst::blst_p2_deserialize(&mut point, raw.as_ptr()) };
+    (err == BLST_ERROR::BLST_SUCCESS).then_some(point)
+}
+
+fn decode_fp(raw: &[u8; FP_SIZE]) -> Option<blst_fp> {
+    // Big-endian byte arrays of equal length compare like the integers.
+    if raw >= &MODULUS {
+        return None;
+    }
+    let mut fp = blst_fp::default();
+    unsafe { blst::blst_fp_from_bendian(&mut fp, raw.as_ptr()) };
+    Some(fp)
+}
+
+fn decode_fp2(raw: &[u8; FP2_SIZE]) -> Option<blst_fp2> {
+    let (c1, c0) = stdx::split_array(raw);
+    Some(blst_fp2 { fp: [decode_fp(c0)?, decode_fp(c1)?] })
+}
+
+fn decode_bool(raw: &[u8; BOOL_SIZE]) -> Option<bool> {
+    match raw {
+        [0] => Some(false),
+        [1] => Some(true),
+        _ => None,
+    }
+}
@@ -6,7 +6,7 @@ use near_primitives_core::runtime::fees::Fee;
 use near_primitives_core::{
     config::{ActionCosts, ExtCosts, ExtCostsConfig},
     profile::ProfileData,
-    types::Gas,
+    types::{Compute, Gas},
 };
 use std::collections::HashMap;
 use std::fmt;
@@ -186,13 +186,13 @@ impl GasCounter {
     }
 
     #[inline]
-    fn update_profile_host(&mut self, cost: ExtCosts, value: u64) {
-        self.profile.add_ext_cost(cost, value)
+    fn update_profile_host(&mut self, cost: ExtCosts, value: u64, compute: Compute) {
+        self.profile.add_ext_cost(cost, value, compute)
     }
 
     #[inline]
-    fn update_profile_action(&mut self, action: ActionCosts, value: u64) {
-        self.profile.add_action_cost(action, value)
+    fn update_profile_action(&mut self, action: ActionCosts, value: u64, compute: Compute) {
+        self.profile.add_action_cost(action, value, compute)
     }
 
     /// A helper function to pay a multiple of a cost.
@@ -200,29 +200,35 @@ impl GasCounter {
         let use_gas = num
             .checked_mul(cost.value(&self.ext_costs_config))
             .ok_or(HostError::IntegerOverflow)?;
+        let compute = num
+            .checked_mul(cost.compute(&self.ext_costs_config))
+            .ok_or(HostError::IntegerOverflow)?;
 
         self.inc_ext_costs_counter(cost, num);
-        self.update_profile_host(cost, use_gas);
+        self.update_profile_host(cost, use_gas, compute);
         self.burn_gas(use_gas)
     }
 
     /// A helper function to pay base cost gas.
     pub fn pay_base(&mut self, cost: ExtCosts) -> Result<()> {
         let base_fee = cost.value(&self.ext_costs_config);
+        let base_compute = cost.compute(&self.ext_costs_config);
         self.inc_ext_costs_counter(cost, 1);
-        self.update_profile_host(cost, base_fee);
+        self.update_profile_host(cost, base_fee, base_compute);
         self.burn_gas(base_fee)
     }
 
     /// A helper function to pay per byte gas fee for batching an action.
     /// # Args:
     /// * `per_byte_fee`: the fee per byte;
+    /// * `per_byte_compute`: the compute usage of the fee per byte;
     /// * `num_bytes`: the number of bytes;
     /// * `sir`: whether the receiver_id is same as the current account ID;
     /// * `action`: what kind of action is charged for;
     pub fn pay_action_per_byte(
         &mut self,
         per_byte_fee: &Fee,
+        per_byte_compute: &Fee,
         num_bytes: u64,
         sir: bool,
         action: ActionCosts,
@@ -234,25 +240,30 @@ impl GasCounter {
                 num_bytes.checked_mul(per_byte_fee.exec_fee()).ok_or(HostError::IntegerOverflow)?,
             )
             .ok_or(HostError::IntegerOverflow)?;
-        self.update_profile_action(action, burn_gas);
+        let burn_compute = num_bytes
+            .checked_mul(per_byte_compute.send_fee(sir))
+            .ok_or(HostError::IntegerOverflow)?;
+        self.update_profile_action(action, burn_gas, burn_compute);
         self.deduct_gas(burn_gas, use_gas)
     }
 
     /// A helper function to pay base cost gas fee for batching an action.
     /// # Args:
     /// * `base_fee`: base fee for the action;
+    /// * `base_compute`: the compute usage of the base fee;
     /// * `sir`: whether the receiver_id is same as the current account ID;
     /// * `action`: what kind of action is charged for;
     pub fn pay_action_base(
         &mut self,
         base_fee: &Fee,
+        base_compute: &Fee,
         sir: bool,
         action: ActionCosts,
     ) -> Result<()> {
         let burn_gas = base_fee.send_fee(sir);
         let use_gas =
             burn_gas.checked_add(base_fee.exec_fee()).ok_or(HostError::IntegerOverflow)?;
-        self.update_profile_action(action, burn_gas);
+        self.update_profile_action(action, burn_gas, base_compute.send_fee(sir));
         self.deduct_gas(burn_gas, use_gas)
     }
 
@@ -260,14 +271,16 @@ impl GasCounter {
     /// # Args:
     /// * `burn_gas`: amount of gas to burn;
     /// * `use_gas`: amount of gas to reserve;
+    /// * `burn_compute`: compute usage of the burnt gas;
     /// * `action`: what kind of action is charged for;
     pub fn pay_action_accumulated(
         &mut self,
         burn_gas: Gas,
         use_gas: Gas,
+        burn_compute: Compute,
         action: ActionCosts,
     ) -> Result<()> {
-        self.update_profile_action(action, burn_gas);
+        self.update_profile_action(action, burn_gas, burn_compute);
         self.deduct_gas(burn_gas, use_gas)
     }
 
@@ -2,6 +2,8 @@
 
 mod alt_bn128;
 mod array_utils;
+#[cfg(feature = "protocol_feature_bls12381")]
+mod bls12381;
 mod context;
 mod dependencies;
 pub mod gas_counter;
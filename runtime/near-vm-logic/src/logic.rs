@@ -17,11 +17,12 @@ use near_primitives_core::runtime::fees::{
     transfer_exec_fee, transfer_send_fee, RuntimeFeesConfig,
 };
 use near_primitives_core::types::{
-    AccountId, Balance, EpochHeight, Gas, ProtocolVersion, StorageUsage,
+    AccountId, Balance, Compute, EpochHeight, Gas, ProtocolVersion, StorageUsage,
 };
 use near_primitives_core::types::{GasDistribution, GasWeight};
 use near_vm_errors::{FunctionCallError, InconsistentStateError};
 use near_vm_errors::{HostError, VMLogicError};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::mem::size_of;
 
@@ -37,6 +38,8 @@ pub struct VMLogic<'a> {
     config: &'a VMConfig,
     /// Fees for creating (async) actions on runtime.
     fees_config: &'a RuntimeFeesConfig,
+    /// Compute usage of the fees, see `RuntimeFeesConfig::compute_fees`.
+    compute_fees_config: Cow<'a, RuntimeFeesConfig>,
     /// If this method execution is invoked directly as a callback by one or more contract calls the
     /// results of the methods that made the callback are stored in this collection.
     promise_results: &'a [PromiseResult],
@@ -133,6 +136,7 @@ impl<'a> VMLogic<'a> {
             context,
             config,
             fees_config,
+            compute_fees_config: fees_config.compute_fees(),
             promise_results,
             memory,
             current_account_balance,
@@ -920,6 +924,377 @@ impl<'a> VMLogic<'a> {
         Ok(res as u64)
     }
 
+    /// Computes the sum of signed points on the BLS12-381 G1 curve,
+    /// `\sum_i (-1)^{sign_i} p_i`, and writes it into the register.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of `(sign: u8, p: G1)`, where `sign` is `0` for a
+    ///   positive and `1` for a negative sign and `p` is an uncompressed G1
+    ///   point in the ZCash format (96 bytes). The points must be on the curve,
+    ///   but are not required to be in the prime order subgroup.
+    ///
+    /// Returns `0` on success and `1` if any of the elements is invalid, in
+    /// which case the register is not written.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If `value.len() % 97 != 0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    ///  bls12381_p1_sum_base + bls12381_p1_sum_element * num_elements`
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub fn bls12381_p1_sum(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(bls12381_p1_sum_base)?;
+        let data = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+
+        let elements = crate::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_p1_sum_element, elements.len() as u64)?;
+
+        let res = crate::bls12381::p1_sum(elements).map(Vec::from);
+        self.bls12381_write_result(register_id, res)
+    }
+
+    /// Computes the sum of signed points on the BLS12-381 G2 curve,
+    /// `\sum_i (-1)^{sign_i} p_i`, and writes it into the register.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of `(sign: u8, p: G2)`, encoded as for
+    ///   [`VMLogic::bls12381_p1_sum`] except that `p` is an uncompressed G2
+    ///   point (192 bytes) with the coordinates encoded as `c1 || c0`.
+    ///
+    /// Returns `0` on success and `1` if any of the elements is invalid.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If `value.len() % 193 != 0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    ///  bls12381_p2_sum_base + bls12381_p2_sum_element * num_elements`
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub fn bls12381_p2_sum(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(bls12381_p2_sum_base)?;
+        let data = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+
+        let elements = crate::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_p2_sum_element, elements.len() as u64)?;
+
+        let res = crate::bls12381::p2_sum(elements).map(Vec::from);
+        self.bls12381_write_result(register_id, res)
+    }
+
+    /// Computes multiexp `\sum_i s_i p_i` on the BLS12-381 G1 subgroup and
+    /// writes it into the register.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of `(p: G1, s: u256)`, where `p` is an uncompressed
+    ///   G1 point in the ZCash format (96 bytes) and `s` is a little-endian
+    ///   scalar. The points must be in the prime order subgroup.
+    ///
+    /// Returns `0` on success and `1` if any of the elements is invalid.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If `value.len() % 128 != 0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    ///  bls12381_g1_multiexp_base + bls12381_g1_multiexp_element * num_elements`
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub fn bls12381_g1_multiexp(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(bls12381_g1_multiexp_base)?;
+        let data = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+
+        let elements = crate::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_g1_multiexp_element, elements.len() as u64)?;
+
+        let res = crate::bls12381::g1_multiexp(elements).map(Vec::from);
+        self.bls12381_write_result(register_id, res)
+    }
+
+    /// Computes multiexp `\sum_i s_i p_i` on the BLS12-381 G2 subgroup and
+    /// writes it into the register.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of `(p: G2, s: u256)`, encoded as for
+    ///   [`VMLogic::bls12381_g1_multiexp`] except that `p` is an uncompressed
+    ///   G2 point (192 bytes).
+    ///
+    /// Returns `0` on success and `1` if any of the elements is invalid.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If `value.len() % 224 != 0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    ///  bls12381_g2_multiexp_base + bls12381_g2_multiexp_element * num_elements`
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub fn bls12381_g2_multiexp(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(bls12381_g2_multiexp_base)?;
+        let data = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+
+        let elements = crate::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_g2_multiexp_element, elements.len() as u64)?;
+
+        let res = crate::bls12381::g2_multiexp(elements).map(Vec::from);
+        self.bls12381_write_result(register_id, res)
+    }
+
+    /// Maps Fp elements to points in the BLS12-381 G1 subgroup, using the
+    /// `map_to_curve` and `clear_cofactor` steps of hash-to-curve (RFC 9380),
+    /// and writes the concatenation of the points into the register.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of big-endian Fp elements (48 bytes each), which
+    ///   must be smaller than the field modulus.
+    ///
+    /// Returns `0` on success and `1` if any of the elements is invalid.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If `value.len() % 48 != 0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    ///  bls12381_map_fp_to_g1_base + bls12381_map_fp_to_g1_element * num_elements`
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub fn bls12381_map_fp_to_g1(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(bls12381_map_fp_to_g1_base)?;
+        let data = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+
+        let elements = crate::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_map_fp_to_g1_element, elements.len() as u64)?;
+
+        let res = crate::bls12381::map_fp_to_g1(elements);
+        self.bls12381_write_result(register_id, res)
+    }
+
+    /// Maps Fp2 elements to points in the BLS12-381 G2 subgroup, using the
+    /// `map_to_curve` and `clear_cofactor` steps of hash-to-curve (RFC 9380),
+    /// and writes the concatenation of the points into the register.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of Fp2 elements encoded as `c1 || c0` (96 bytes
+    ///   each), where both parts are as in [`VMLogic::bls12381_map_fp_to_g1`].
+    ///
+    /// Returns `0` on success and `1` if any of the elements is invalid.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If `value.len() % 96 != 0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    ///  bls12381_map_fp2_to_g2_base + bls12381_map_fp2_to_g2_element * num_elements`
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub fn bls12381_map_fp2_to_g2(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(bls12381_map_fp2_to_g2_base)?;
+        let data = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+
+        let elements = crate::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_map_fp2_to_g2_element, elements.len() as u64)?;
+
+        let res = crate::bls12381::map_fp2_to_g2(elements);
+        self.bls12381_write_result(register_id, res)
+    }
+
+    /// Computes pairing check on the BLS12-381 curve, that is whether
+    /// `\prod_i e(p_i, q_i)` equals one.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of `(p: G1, q: G2)` uncompressed points in the
+    ///   ZCash format (96 and 192 bytes). The points must be in the prime order
+    ///   subgroups.
+    ///
+    /// Returns `0` if the pairing check passes, `2` if it fails and `1` if any
+    /// of the elements is invalid.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory, the function
+    /// returns `MemoryAccessViolation`.
+    ///
+    /// If `value.len() % 288 != 0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + bls12381_pairing_base + bls12381_pairing_element * num_elements`
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub fn bls12381_pairing_check(&mut self, value_len: u64, value_ptr: u64) -> Result<u64> {
+        self.gas_counter.pay_base(bls12381_pairing_base)?;
+        let data = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+
+        let elements = crate::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_pairing_element, elements.len() as u64)?;
+
+        match crate::bls12381::pairing_check(elements) {
+            Some(true) => Ok(0),
+            Some(false) => Ok(2),
+            None => Ok(1),
+        }
+    }
+
+    /// Decompresses points on the BLS12-381 G1 curve and writes the
+    /// concatenation of the uncompressed points into the register.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of compressed G1 points in the ZCash format (48
+    ///   bytes each). The points must be on the curve, but are not required to
+    ///   be in the prime order subgroup.
+    ///
+    /// Returns `0` on success and `1` if any of the elements is invalid.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If `value.len() % 48 != 0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    ///  bls12381_p1_decompress_base + bls12381_p1_decompress_element * num_elements`
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub fn bls12381_p1_decompress(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(bls12381_p1_decompress_base)?;
+        let data = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+
+        let elements = crate::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_p1_decompress_element, elements.len() as u64)?;
+
+        let res = crate::bls12381::p1_decompress(elements);
+        self.bls12381_write_result(register_id, res)
+    }
+
+    /// Decompresses points on the BLS12-381 G2 curve and writes the
+    /// concatenation of the uncompressed points into the register.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of compressed G2 points in the ZCash format (96
+    ///   bytes each), otherwise as for [`VMLogic::bls12381_p1_decompress`].
+    ///
+    /// Returns `0` on success and `1` if any of the elements is invalid.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If `value.len() % 96 != 0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    ///  bls12381_p2_decompress_base + bls12381_p2_decompress_element * num_elements`
+    #[cfg(feature = "protocol_feature_bls12381")]
+    pub fn bls12381_p2_decompress(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(bls12381_p2_decompress_base)?;
+        let data = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+
+        let elements = crate::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_p2_decompress_element, elements.len() as u64)?;
+
+        let res = crate::bls12381::p2_decompress(elements);
+        self.bls12381_write_result(register_id, res)
+    }
+
+    /// Writes the result of a BLS12-381 function into the register and
+    /// returns `0`, or returns `1` if the input was invalid.
+    #[cfg(feature = "protocol_feature_bls12381")]
+    fn bls12381_write_result(&mut self, register_id: u64, res: Option<Vec<u8>>) -> Result<u64> {
+        match res {
+            Some(res) => {
+                self.internal_write_register(register_id, res)?;
+                Ok(0)
+            }
+            None => Ok(1),
+        }
+    }
+
     /// Writes random seed into the register.
     ///
     /// # Errors
@@ -1194,6 +1569,68 @@ impl<'a> VMLogic<'a> {
         }
     }
 
+    /// Verify an ECDSA signature over the NIST P-256 curve (`secp256r1`) given
+    /// a message and a public key. The message is hashed with SHA-256 as part
+    /// of the verification.
+    ///
+    /// The signature is the 64 bytes `r || s` and the public key is the
+    /// uncompressed point `x || y` without the SEC1 tag, also 64 bytes.
+    ///
+    /// Returns a bool indicating success (1) or failure (0) as a `u64`.
+    ///
+    /// # Errors
+    ///
+    /// * If the public key's size is not equal to 64, or signature size is not
+    ///   equal to 64, returns [HostError::P256VerifyInvalidInput].
+    /// * If any of the signature, message or public key arguments are out of
+    ///   memory bounds, returns [`HostError::MemoryAccessViolation`]
+    ///
+    /// # Cost
+    ///
+    /// `input_cost(num_bytes_signature) + input_cost(num_bytes_message) +
+    ///  input_cost(num_bytes_public_key) + p256_verify_base +
+    ///  p256_verify_byte * num_bytes_message`
+    ///
+    /// See [`VMLogic::ed25519_verify`] for the definition of `input_cost`.
+    #[cfg(feature = "protocol_feature_secp256r1")]
+    pub fn p256_verify(
+        &mut self,
+        signature_len: u64,
+        signature_ptr: u64,
+        message_len: u64,
+        message_ptr: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(p256_verify_base)?;
+
+        let signature = {
+            let vec = self.get_vec_from_memory_or_register(signature_ptr, signature_len)?;
+            near_crypto::Secp256R1Signature::try_from(vec.as_slice()).map_err(|_| {
+                VMLogicError::HostError(HostError::P256VerifyInvalidInput {
+                    msg: "invalid signature length".to_string(),
+                })
+            })?
+        };
+
+        let message = self.get_vec_from_memory_or_register(message_ptr, message_len)?;
+        self.gas_counter.pay_per(p256_verify_byte, message.len() as u64)?;
+
+        let public_key = {
+            let vec = self.get_vec_from_memory_or_register(public_key_ptr, public_key_len)?;
+            near_crypto::Secp256R1PublicKey::try_from(vec.as_slice()).map_err(|_| {
+                VMLogicError::HostError(HostError::P256VerifyInvalidInput {
+                    msg: "invalid public key length".to_string(),
+                })
+            })?
+        };
+
+        // Points which are not on the curve and out of range scalars simply
+        // fail the verification.
+        let signature = near_crypto::Signature::SECP256R1(signature);
+        Ok(signature.verify(&message, &near_crypto::PublicKey::SECP256R1(public_key)) as u64)
+    }
+
     /// Called by gas metering injected into Wasm. Counts both towards `burnt_gas` and `used_gas`.
     ///
     /// # Errors
@@ -1224,24 +1661,32 @@ impl<'a> VMLogic<'a> {
     /// pay for the content transmitted through the dependency upon the actual creation of the
     /// DataReceipt.
     fn pay_gas_for_new_receipt(&mut self, sir: bool, data_dependencies: &[bool]) -> Result<()> {
-        let fees_config_cfg = &self.fees_config;
         self.gas_counter.pay_action_base(
-            &fees_config_cfg.action_receipt_creation_config,
+            &self.fees_config.action_receipt_creation_config,
+            &self.compute_fees_config.action_receipt_creation_config,
             sir,
             ActionCosts::new_action_receipt,
         )?;
-        let mut burn_gas = 0u64;
-        for dep in data_dependencies {
-            // Both creation and execution for data receipts are considered burnt gas.
-            burn_gas = burn_gas
-                .checked_add(fees_config_cfg.data_receipt_creation_config.base_cost.send_fee(*dep))
-                .ok_or(HostError::IntegerOverflow)?
-                .checked_add(fees_config_cfg.data_receipt_creation_config.base_cost.exec_fee())
-                .ok_or(HostError::IntegerOverflow)?;
-        }
+        let data_receipts_fee = |fees_config_cfg: &RuntimeFeesConfig| -> Result<Gas> {
+            let mut burn_gas = 0u64;
+            for dep in data_dependencies {
+                // Both creation and execution for data receipts are considered burnt gas.
+                burn_gas = burn_gas
+                    .checked_add(
+                        fees_config_cfg.data_receipt_creation_config.base_cost.send_fee(*dep),
+                    )
+                    .ok_or(HostError::IntegerOverflow)?
+                    .checked_add(fees_config_cfg.data_receipt_creation_config.base_cost.exec_fee())
+                    .ok_or(HostError::IntegerOverflow)?;
+            }
+            Ok(burn_gas)
+        };
+        let burn_gas = data_receipts_fee(self.fees_config)?;
+        let burn_compute = data_receipts_fee(&self.compute_fees_config)?;
         self.gas_counter.pay_action_accumulated(
             burn_gas,
             burn_gas,
+            burn_compute,
             ActionCosts::new_data_receipt_base,
         )
     }
@@ -1566,6 +2011,7 @@ impl<'a> VMLogic<'a> {
 
         self.gas_counter.pay_action_base(
             &self.fees_config.action_creation_config.create_account_cost,
+            &self.compute_fees_config.action_creation_config.create_account_cost,
             sir,
             ActionCosts::create_account,
         )?;
@@ -1618,11 +2064,13 @@ impl<'a> VMLogic<'a> {
         let num_bytes = code.len() as u64;
         self.gas_counter.pay_action_base(
             &self.fees_config.action_creation_config.deploy_contract_cost,
+            &self.compute_fees_config.action_creation_config.deploy_contract_cost,
             sir,
             ActionCosts::deploy_contract_base,
         )?;
         self.gas_counter.pay_action_per_byte(
             &self.fees_config.action_creation_config.deploy_contract_cost_per_byte,
+            &self.compute_fees_config.action_creation_config.deploy_contract_cost_per_byte,
             num_bytes,
             sir,
             ActionCosts::deploy_contract_byte,
@@ -1739,11 +2187,13 @@ impl<'a> VMLogic<'a> {
         let num_bytes = method_name.len() as u64 + arguments.len() as u64;
         self.gas_counter.pay_action_base(
             &self.fees_config.action_creation_config.function_call_cost,
+            &self.compute_fees_config.action_creation_config.function_call_cost,
             sir,
             ActionCosts::function_call_base,
         )?;
         self.gas_counter.pay_action_per_byte(
             &self.fees_config.action_creation_config.function_call_cost_per_byte,
+            &self.compute_fees_config.action_creation_config.function_call_cost_per_byte,
             num_bytes,
             sir,
             ActionCosts::function_call_byte,
@@ -1805,7 +2255,17 @@ impl<'a> VMLogic<'a> {
             transfer_exec_fee(&self.fees_config.action_creation_config, is_receiver_implicit);
         let burn_gas = send_fee;
         let use_gas = burn_gas.checked_add(exec_fee).ok_or(HostError::IntegerOverflow)?;
-        self.gas_counter.pay_action_accumulated(burn_gas, use_gas, ActionCosts::transfer)?;
+        let burn_compute = transfer_send_fee(
+            &self.compute_fees_config.action_creation_config,
+            sir,
+            is_receiver_implicit,
+        );
+        self.gas_counter.pay_action_accumulated(
+            burn_gas,
+            use_gas,
+            burn_compute,
+            ActionCosts::transfer,
+        )?;
 
         self.deduct_balance(amount)?;
 
@@ -1851,6 +2311,7 @@ impl<'a> VMLogic<'a> {
 
         self.gas_counter.pay_action_base(
             &self.fees_config.action_creation_config.stake_cost,
+            &self.compute_fees_config.action_creation_config.stake_cost,
             sir,
             ActionCosts::stake,
         )?;
@@ -1896,6 +2357,7 @@ impl<'a> VMLogic<'a> {
 
         self.gas_counter.pay_action_base(
             &self.fees_config.action_creation_config.add_key_cost.full_access_cost,
+            &self.compute_fees_config.action_creation_config.add_key_cost.full_access_cost,
             sir,
             ActionCosts::add_full_access_key,
         )?;
@@ -1960,11 +2422,17 @@ impl<'a> VMLogic<'a> {
         let num_bytes = method_names.iter().map(|v| v.len() as u64 + 1).sum::<u64>();
         self.gas_counter.pay_action_base(
             &self.fees_config.action_creation_config.add_key_cost.function_call_cost,
+            &self.compute_fees_config.action_creation_config.add_key_cost.function_call_cost,
             sir,
             ActionCosts::add_function_call_key_base,
         )?;
         self.gas_counter.pay_action_per_byte(
             &self.fees_config.action_creation_config.add_key_cost.function_call_cost_per_byte,
+            &self
+                .compute_fees_config
+                .action_creation_config
+                .add_key_cost
+                .function_call_cost_per_byte,
             num_bytes,
             sir,
             ActionCosts::add_function_call_key_byte,
@@ -2017,6 +2485,7 @@ impl<'a> VMLogic<'a> {
 
         self.gas_counter.pay_action_base(
             &self.fees_config.action_creation_config.delete_key_cost,
+            &self.compute_fees_config.action_creation_config.delete_key_cost,
             sir,
             ActionCosts::delete_key,
         )?;
@@ -2060,6 +2529,7 @@ impl<'a> VMLogic<'a> {
         let (receipt_idx, sir) = self.promise_idx_to_receipt_idx_with_sir(promise_idx)?;
         self.gas_counter.pay_action_base(
             &self.fees_config.action_creation_config.delete_account_cost,
+            &self.compute_fees_config.action_creation_config.delete_account_cost,
             sir,
             ActionCosts::delete_account,
         )?;
@@ -2186,7 +2656,6 @@ impl<'a> VMLogic<'a> {
     pub fn value_return(&mut self, value_len: u64, value_ptr: u64) -> Result<()> {
         self.gas_counter.pay_base(base)?;
         let return_val = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
-        let mut burn_gas: Gas = 0;
         let num_bytes = return_val.len() as u64;
         if num_bytes > self.config.limit_config.max_length_returned_data {
             return Err(HostError::ReturnedValueLengthExceeded {
@@ -2195,31 +2664,40 @@ impl<'a> VMLogic<'a> {
             }
             .into());
         }
-        let data_cfg = &self.fees_config.data_receipt_creation_config;
-        for data_receiver in &self.context.output_data_receivers {
-            let sir = data_receiver == &self.context.current_account_id;
-            // We deduct for execution here too, because if we later have an OR combinator
-            // for promises then we might have some valid data receipts that arrive too late
-            // to be picked up by the execution that waits on them (because it has started
-            // after it receives the first data receipt) and then we need to issue a special
-            // refund in this situation. Which we avoid by just paying for execution of
-            // data receipt that might not be performed.
-            // The gas here is considered burnt, cause we'll prepay for it upfront.
-            burn_gas = burn_gas
-                .checked_add(
-                    data_cfg
-                        .cost_per_byte
-                        .send_fee(sir)
-                        .checked_add(data_cfg.cost_per_byte.exec_fee())
-                        .ok_or(HostError::IntegerOverflow)?
-                        .checked_mul(num_bytes)
-                        .ok_or(HostError::IntegerOverflow)?,
-                )
-                .ok_or(HostError::IntegerOverflow)?;
-        }
+        let output_data_receivers = &self.context.output_data_receivers;
+        let current_account_id = &self.context.current_account_id;
+        let data_receipts_fee = |fees_config: &RuntimeFeesConfig| -> Result<Gas> {
+            let data_cfg = &fees_config.data_receipt_creation_config;
+            let mut burn_gas: Gas = 0;
+            for data_receiver in output_data_receivers {
+                let sir = data_receiver == current_account_id;
+                // We deduct for execution here too, because if we later have an OR combinator
+                // for promises then we might have some valid data receipts that arrive too late
+                // to be picked up by the execution that waits on them (because it has started
+                // after it receives the first data receipt) and then we need to issue a special
+                // refund in this situation. Which we avoid by just paying for execution of
+                // data receipt that might not be performed.
+                // The gas here is considered burnt, cause we'll prepay for it upfront.
+                burn_gas = burn_gas
+                    .checked_add(
+                        data_cfg
+                            .cost_per_byte
+                            .send_fee(sir)
+                            .checked_add(data_cfg.cost_per_byte.exec_fee())
+                            .ok_or(HostError::IntegerOverflow)?
+                            .checked_mul(num_bytes)
+                            .ok_or(HostError::IntegerOverflow)?,
+                    )
+                    .ok_or(HostError::IntegerOverflow)?;
+            }
+            Ok(burn_gas)
+        };
+        let burn_gas = data_receipts_fee(self.fees_config)?;
+        let burn_compute = data_receipts_fee(&self.compute_fees_config)?;
         self.gas_counter.pay_action_accumulated(
             burn_gas,
             burn_gas,
+            burn_compute,
             ActionCosts::new_data_receipt_byte,
         )?;
         self.return_data = ReturnData::Value(return_val);
@@ -2787,6 +3265,7 @@ impl<'a> VMLogic<'a> {
 
         let mut profile = self.gas_counter.profile_data();
         profile.compute_wasm_instruction_cost(burnt_gas);
+        let compute_usage = profile.total_compute_usage();
 
         VMOutcome {
             balance: self.current_account_balance,
@@ -2794,6 +3273,7 @@ impl<'a> VMLogic<'a> {
             return_data: self.return_data,
             burnt_gas,
             used_gas,
+            compute_usage,
             logs: self.logs,
             profile,
             action_receipts: self.receipt_manager.action_receipts,
@@ -2885,6 +3365,9 @@ pub struct VMOutcome {
     pub return_data: ReturnData,
     pub burnt_gas: Gas,
     pub used_gas: Gas,
+    /// Compute time used by the execution, which limits the execution of a
+    /// chunk instead of the burnt gas.
+    pub compute_usage: Compute,
     pub logs: Vec<String>,
     /// Data collected from making a contract call
     pub profile: ProfileData,
@@ -2918,6 +3401,7 @@ impl VMOutcome {
             return_data: ReturnData::None,
             burnt_gas: 0,
             used_gas: 0,
+            compute_usage: 0,
             logs: Vec::new(),
             profile: ProfileData::default(),
             action_receipts: Vec::new(),
@@ -0,0 +1,402 @@
+use crate::tests::fixtures::get_context;
+use crate::tests::helpers::*;
+use crate::tests::vm_logic_builder::VMLogicBuilder;
+use crate::{map, ExtCosts, VMLogic};
+use near_vm_errors::{HostError, VMLogicError};
+
+const G1_GENERATOR: [u8; 96] = hex_literal(
+    "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb\
+     08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1",
+);
+
+const G1_GENERATOR_COMPRESSED: [u8; 48] = hex_literal(
+    "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb",
+);
+
+const G1_INFINITY: [u8; 96] = {
+    let mut res = [0; 96];
+    res[0] = 0x40;
+    res
+};
+
+const G2_INFINITY: [u8; 192] = {
+    let mut res = [0; 192];
+    res[0] = 0x40;
+    res
+};
+
+/// Decodes a hex string at compile time.
+const fn hex_literal<const N: usize>(hex: &str) -> [u8; N] {
+    const fn digit(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            _ => panic!("invalid hex digit"),
+        }
+    }
+    let hex = hex.as_bytes();
+    assert!(hex.len() == 2 * N);
+    let mut res = [0; N];
+    let mut i = 0;
+    while i < N {
+        res[i] = digit(hex[2 * i]) << 4 | digit(hex[2 * i + 1]);
+        i += 1;
+    }
+    res
+}
+
+fn g1_generator_times(scalar: u64) -> [u8; 96] {
+    let mut point = blst::blst_p1::default();
+    let mut res = [0; 96];
+    unsafe {
+        blst::blst_p1_mult(
+            &mut point,
+            blst::blst_p1_generator(),
+            scalar.to_le_bytes().as_ptr(),
+            64,
+        );
+        blst::blst_p1_serialize(res.as_mut_ptr(), &point);
+    }
+    res
+}
+
+fn g2_generator_times(scalar: u64) -> [u8; 192] {
+    let mut point = blst::blst_p2::default();
+    let mut res = [0; 192];
+    unsafe {
+        blst::blst_p2_mult(
+            &mut point,
+            blst::blst_p2_generator(),
+            scalar.to_le_bytes().as_ptr(),
+            64,
+        );
+        blst::blst_p2_serialize(res.as_mut_ptr(), &point);
+    }
+    res
+}
+
+/// Returns a point which is on the G1 curve, but not in the prime order
+/// subgroup.
+fn g1_point_not_in_subgroup() -> [u8; 96] {
+    for x in 1u8.. {
+        let mut compressed = [0; 48];
+        compressed[0] = 0x80;
+        compressed[47] = x;
+        let mut point = blst::blst_p1_affine::default();
+        let err = unsafe { blst::blst_p1_uncompress(&mut point, compressed.as_ptr()) };
+        if err == blst::BLST_ERROR::BLST_SUCCESS && !unsafe { blst::blst_p1_affine_in_g1(&point) } {
+            let mut res = [0; 96];
+            unsafe { blst::blst_p1_affine_serialize(res.as_mut_ptr(), &point) };
+            return res;
+        }
+    }
+    unreachable!()
+}
+
+/// A BLS12-381 host function, with the pairing check adapted to take a
+/// register id.
+type HostFn = fn(&mut VMLogic, u64, u64, u64) -> Result<u64, VMLogicError>;
+
+fn concat(parts: &[&[u8]]) -> Vec<u8> {
+    parts.concat()
+}
+
+/// Calls a BLS12-381 host function writing into a register and returns its
+/// result together with the register content.
+#[track_caller]
+fn call(input: &[u8], f: HostFn) -> Result<(u64, Option<Vec<u8>>), VMLogicError> {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+    let res = f(&mut logic, input.len() as _, input.as_ptr() as _, 0)?;
+    let value = logic.register_len(0).ok().filter(|&len| len != u64::MAX).map(|len| {
+        let mut value = vec![0u8; len as usize];
+        logic.read_register(0, value.as_mut_ptr() as _).unwrap();
+        value
+    });
+    Ok((res, value))
+}
+
+#[track_caller]
+fn check_ok(input: &[u8], f: HostFn, expected: &[u8]) {
+    assert_eq!(call(input, f).unwrap(), (0, Some(expected.to_vec())));
+}
+
+#[track_caller]
+fn check_invalid_element(input: &[u8], f: HostFn) {
+    assert_eq!(call(input, f).unwrap(), (1, None));
+}
+
+#[track_caller]
+fn check_invalid_length(input: &[u8], f: HostFn) {
+    match call(input, f) {
+        Err(VMLogicError::HostError(HostError::Bls12381InvalidInput { msg })) => {
+            assert!(msg.contains("invalid array"), "unexpected error: {msg}")
+        }
+        res => panic!("expected invalid input error, got {res:?}"),
+    }
+}
+
+#[test]
+fn test_bls12381_p1_sum() {
+    let f: HostFn = |logic, len, ptr, register_id| logic.bls12381_p1_sum(len, ptr, register_id);
+    let g = G1_GENERATOR;
+    assert_eq!(g1_generator_times(1), g);
+
+    check_ok(&[], f, &G1_INFINITY);
+    check_ok(&concat(&[&[0], &g]), f, &g);
+    check_ok(&concat(&[&[0], &g, &[0], &g]), f, &g1_generator_times(2));
+    check_ok(&concat(&[&[0], &g, &[1], &g]), f, &G1_INFINITY);
+    let neg_g = call(&concat(&[&[1], &g]), f).unwrap().1.unwrap();
+    assert_eq!(neg_g[..48], g[..48]);
+    assert_ne!(neg_g[48..], g[48..]);
+    check_ok(&concat(&[&[1], &g1_generator_times(2), &[0], &g]), f, &neg_g);
+    check_ok(&concat(&[&[0], &G1_INFINITY, &[1], &G1_INFINITY]), f, &G1_INFINITY);
+
+    // Points outside of the subgroup are fine as long as they are on the curve.
+    let p = g1_point_not_in_subgroup();
+    let (res, _) = call(&concat(&[&[0], &p, &[0], &g]), f).unwrap();
+    assert_eq!(res, 0);
+
+    check_invalid_element(&concat(&[&[2], &g]), f);
+    check_invalid_element(
+        &concat(&[&[0], &g, &[0], &{
+            let mut p = g;
+            p[95] ^= 1;
+            p
+        }]),
+        f,
+    );
+    check_invalid_element(
+        &concat(&[&[0], &{
+            let mut p = [0; 96];
+            p[..48].copy_from_slice(&G1_GENERATOR_COMPRESSED);
+            p
+        }]),
+        f,
+    );
+    check_invalid_length(&concat(&[&[0], &g, &[0]]), f);
+}
+
+#[test]
+fn test_bls12381_p2_sum() {
+    let f: HostFn = |logic, len, ptr, register_id| logic.bls12381_p2_sum(len, ptr, register_id);
+    let g = g2_generator_times(1);
+
+    check_ok(&[], f, &G2_INFINITY);
+    check_ok(&concat(&[&[0], &g]), f, &g);
+    check_ok(&concat(&[&[0], &g, &[0], &g]), f, &g2_generator_times(2));
+    check_ok(&concat(&[&[0], &g2_generator_times(5), &[1], &g]), f, &g2_generator_times(4));
+    check_ok(&concat(&[&[0], &g, &[1], &g]), f, &G2_INFINITY);
+
+    check_invalid_element(
+        &concat(&[&[1], &{
+            let mut p = g;
+            p[191] ^= 1;
+            p
+        }]),
+        f,
+    );
+    check_invalid_length(&g, f);
+}
+
+#[test]
+fn test_bls12381_g1_multiexp() {
+    let f: HostFn =
+        |logic, len, ptr, register_id| logic.bls12381_g1_multiexp(len, ptr, register_id);
+    let g = G1_GENERATOR;
+    let scalar = |s: u64| {
+        let mut res = [0; 32];
+        res[..8].copy_from_slice(&s.to_le_bytes());
+        res
+    };
+
+    check_ok(&[], f, &G1_INFINITY);
+    check_ok(&concat(&[&g, &scalar(1)]), f, &g);
+    check_ok(&concat(&[&g, &scalar(0)]), f, &G1_INFINITY);
+    check_ok(&concat(&[&g, &scalar(7)]), f, &g1_generator_times(7));
+    check_ok(
+        &concat(&[&g, &scalar(3), &g1_generator_times(2), &scalar(5), &G1_INFINITY, &scalar(9)]),
+        f,
+        &g1_generator_times(13),
+    );
+    // Scalars larger than the group order are allowed.
+    let mut order =
+        hex_literal::<32>("73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001");
+    order.reverse();
+    check_ok(&concat(&[&g, &order]), f, &G1_INFINITY);
+    order[0] += 1;
+    check_ok(&concat(&[&g, &order]), f, &g);
+
+    check_invalid_element(&concat(&[&g1_point_not_in_subgroup(), &scalar(1)]), f);
+    check_invalid_length(&concat(&[&g]), f);
+}
+
+#[test]
+fn test_bls12381_g2_multiexp() {
+    let f: HostFn =
+        |logic, len, ptr, register_id| logic.bls12381_g2_multiexp(len, ptr, register_id);
+    let g = g2_generator_times(1);
+    let scalar = |s: u64| {
+        let mut res = [0; 32];
+        res[..8].copy_from_slice(&s.to_le_bytes());
+        res
+    };
+
+    check_ok(&[], f, &G2_INFINITY);
+    check_ok(&concat(&[&g, &scalar(1)]), f, &g);
+    check_ok(
+        &concat(&[&g, &scalar(3), &g2_generator_times(2), &scalar(5)]),
+        f,
+        &g2_generator_times(13),
+    );
+
+    check_invalid_length(&concat(&[&g, &scalar(1)[..31]]), f);
+}
+
+#[test]
+fn test_bls12381_map_fp_to_g1() {
+    let f: HostFn =
+        |logic, len, ptr, register_id| logic.bls12381_map_fp_to_g1(len, ptr, register_id);
+
+    check_ok(&[], f, &[]);
+    let (res, value) = call(&[[0u8; 48], [7u8; 48]].concat(), f).unwrap();
+    assert_eq!(res, 0);
+    let value = value.unwrap();
+    assert_eq!(value.len(), 2 * 96);
+    for point in value.chunks(96) {
+        let mut affine = blst::blst_p1_affine::default();
+        unsafe {
+            assert_eq!(
+                blst::blst_p1_deserialize(&mut affine, point.as_ptr()),
+                blst::BLST_ERROR::BLST_SUCCESS
+            );
+            assert!(blst::blst_p1_affine_in_g1(&affine));
+        }
+    }
+
+    // The field element must be smaller than the modulus.
+    let modulus = hex_literal::<48>(
+        "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab",
+    );
+    check_invalid_element(&modulus, f);
+    let mut modulus_minus_one = modulus;
+    modulus_minus_one[47] -= 1;
+    assert_eq!(call(&modulus_minus_one, f).unwrap().0, 0);
+    check_invalid_length(&[0; 47], f);
+}
+
+#[test]
+fn test_bls12381_map_fp2_to_g2() {
+    let f: HostFn =
+        |logic, len, ptr, register_id| logic.bls12381_map_fp2_to_g2(len, ptr, register_id);
+
+    check_ok(&[], f, &[]);
+    let (res, value) = call(&[1u8; 96], f).unwrap();
+    assert_eq!(res, 0);
+    let value = value.unwrap();
+    let mut affine = blst::blst_p2_affine::default();
+    unsafe {
+        assert_eq!(
+            blst::blst_p2_deserialize(&mut affine, value.as_ptr()),
+            blst::BLST_ERROR::BLST_SUCCESS
+        );
+        assert!(blst::blst_p2_affine_in_g2(&affine));
+    }
+
+    check_invalid_element(&[0xff; 96], f);
+    check_invalid_length(&[0; 48], f);
+}
+
+#[test]
+fn test_bls12381_pairing_check() {
+    #[track_caller]
+    fn check(input: &[u8], expected: u64) {
+        let res = call(input, |logic, len, ptr, _| logic.bls12381_pairing_check(len, ptr));
+        assert_eq!(res.unwrap(), (expected, None));
+    }
+    let g1 = G1_GENERATOR;
+    let g2 = g2_generator_times(1);
+    let neg_g1 = {
+        let (_, value) = call(&concat(&[&[1], &g1]), |logic, len, ptr, register_id| {
+            logic.bls12381_p1_sum(len, ptr, register_id)
+        })
+        .unwrap();
+        value.unwrap()
+    };
+
+    check(&[], 0);
+    check(&concat(&[&g1, &g2]), 2);
+    check(&concat(&[&g1, &g2, &neg_g1, &g2]), 0);
+    // e(2 * g1, 3 * g2) = e(6 * g1, g2)
+    check(
+        &concat(&[&g1_generator_times(2), &g2_generator_times(3), &neg_g1, &g2_generator_times(6)]),
+        0,
+    );
+    check(
+        &concat(&[&g1_generator_times(2), &g2_generator_times(3), &neg_g1, &g2_generator_times(5)]),
+        2,
+    );
+    check(&concat(&[&G1_INFINITY, &g2]), 0);
+    check(&concat(&[&g1, &G2_INFINITY]), 0);
+
+    check(&concat(&[&g1_point_not_in_subgroup(), &g2]), 1);
+    check_invalid_length(&g1, |logic, len, ptr, _| logic.bls12381_pairing_check(len, ptr));
+}
+
+#[test]
+fn test_bls12381_p1_decompress() {
+    let f: HostFn =
+        |logic, len, ptr, register_id| logic.bls12381_p1_decompress(len, ptr, register_id);
+
+    check_ok(&[], f, &[]);
+    check_ok(&G1_GENERATOR_COMPRESSED, f, &G1_GENERATOR);
+    let mut infinity = [0; 48];
+    infinity[0] = 0xc0;
+    check_ok(
+        &[G1_GENERATOR_COMPRESSED, infinity].concat(),
+        f,
+        &[G1_GENERATOR, G1_INFINITY].concat(),
+    );
+
+    // Without the compression flag.
+    let mut uncompressed_flag = G1_GENERATOR_COMPRESSED;
+    uncompressed_flag[0] &= !0x80;
+    check_invalid_element(&uncompressed_flag, f);
+    check_invalid_length(&G1_GENERATOR[..47], f);
+}
+
+#[test]
+fn test_bls12381_p2_decompress() {
+    let f: HostFn =
+        |logic, len, ptr, register_id| logic.bls12381_p2_decompress(len, ptr, register_id);
+    let g = g2_generator_times(1);
+    let mut compressed = [0; 96];
+    unsafe { blst::blst_p2_compress(compressed.as_mut_ptr(), blst::blst_p2_generator()) };
+
+    check_ok(&compressed, f, &g);
+    check_ok(&[compressed, compressed].concat(), f, &[g, g].concat());
+    check_invalid_element(&[0xff; 96], f);
+    check_invalid_length(&g[..95], f);
+}
+
+#[test]
+fn test_bls12381_costs() {
+    let g = G1_GENERATOR;
+    let input = concat(&[&[0], &g, &[1], &g]);
+    reset_costs_counter();
+    let (res, _) =
+        call(&input, |logic, len, ptr, register_id| logic.bls12381_p1_sum(len, ptr, register_id))
+            .unwrap();
+    assert_eq!(res, 0);
+    assert_costs(map! {
+        ExtCosts::base: 1,
+        ExtCosts::read_memory_base: 1,
+        ExtCosts::read_memory_byte: 194,
+        ExtCosts::write_register_base: 2,
+        ExtCosts::write_register_byte: 96,
+        ExtCosts::read_register_base: 1,
+        ExtCosts::read_register_byte: 96,
+        ExtCosts::bls12381_p1_sum_base: 1,
+        ExtCosts::bls12381_p1_sum_element: 2,
+    });
+}
@@ -1,4 +1,6 @@
 mod alt_bn128;
+#[cfg(feature = "protocol_feature_bls12381")]
+mod bls12381;
 mod context;
 #[cfg(feature = "protocol_feature_ed25519_verify")]
 mod ed25519_verify;
@@ -7,6 +9,8 @@ mod gas_counter;
 mod helpers;
 mod iterators;
 mod miscs;
+#[cfg(feature = "protocol_feature_secp256r1")]
+mod p256_verify;
 mod promises;
 mod registers;
 mod storage_read_write;
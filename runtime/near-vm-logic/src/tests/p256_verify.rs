@@ -0,0 +1,206 @@
+use crate::tests::fixtures::get_context;
+use crate::tests::helpers::*;
+use crate::tests::vm_logic_builder::VMLogicBuilder;
+use crate::{map, ExtCosts};
+use near_vm_errors::HostError;
+use near_vm_errors::VMLogicError;
+use std::collections::HashMap;
+
+const SIGNATURE: [u8; 64] = [
+    11, 82, 242, 26, 104, 16, 136, 16, 158, 24, 146, 163, 16, 99, 181, 99, 205, 20, 219, 179, 142,
+    175, 40, 196, 102, 30, 206, 26, 18, 253, 136, 24, 23, 2, 15, 28, 252, 209, 225, 195, 118, 116,
+    39, 174, 46, 160, 93, 123, 70, 164, 14, 126, 217, 151, 181, 225, 160, 240, 12, 71, 168, 121,
+    83, 181,
+];
+
+// `r` and `s` must be smaller than the order of the curve.
+const BAD_SIGNATURE: [u8; 64] = [255; 64];
+
+const PUBLIC_KEY: [u8; 64] = [
+    79, 102, 159, 152, 202, 247, 235, 87, 249, 10, 191, 61, 188, 92, 218, 65, 44, 173, 153, 7, 68,
+    178, 47, 12, 18, 0, 124, 23, 59, 93, 92, 254, 69, 86, 228, 179, 99, 126, 124, 202, 215, 152,
+    36, 108, 103, 116, 173, 12, 92, 45, 214, 35, 5, 50, 115, 124, 102, 135, 172, 95, 216, 237, 9,
+    133,
+];
+
+// change the `y` coordinate so that the point is not on the curve
+const FORGED_PUBLIC_KEY: [u8; 64] = {
+    let mut key = PUBLIC_KEY;
+    key[63] ^= 1;
+    key
+};
+
+// 32 bytes message
+const MESSAGE: [u8; 32] = [
+    107, 97, 106, 100, 108, 102, 107, 106, 97, 108, 107, 102, 106, 97, 107, 108, 102, 106, 100,
+    107, 108, 97, 100, 106, 102, 107, 108, 106, 97, 100, 115, 107,
+];
+
+const OTHER_MESSAGE: [u8; 32] = {
+    let mut message = MESSAGE;
+    message[0] ^= 1;
+    message
+};
+
+#[track_caller]
+fn check_p256_verify(
+    signature_len: u64,
+    signature: &[u8],
+    message_len: u64,
+    message: &[u8],
+    public_key_len: u64,
+    public_key: &[u8],
+    want: Result<u64, HostError>,
+    want_costs: HashMap<ExtCosts, u64>,
+) {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+
+    let signature_ptr = if signature_len == u64::MAX {
+        logic.wrapped_internal_write_register(1, &signature).unwrap();
+        1
+    } else {
+        signature.as_ptr() as u64
+    };
+
+    let message_ptr = if message_len == u64::MAX {
+        logic.wrapped_internal_write_register(2, &message).unwrap();
+        2
+    } else {
+        message.as_ptr() as u64
+    };
+
+    let public_key_ptr = if public_key_len == u64::MAX {
+        logic.wrapped_internal_write_register(3, &public_key).unwrap();
+        3
+    } else {
+        public_key.as_ptr() as u64
+    };
+
+    let result = logic.p256_verify(
+        signature_len,
+        signature_ptr,
+        message_len,
+        message_ptr,
+        public_key_len,
+        public_key_ptr,
+    );
+
+    let want = want.map_err(VMLogicError::HostError);
+    assert_eq!(want, result);
+    assert_costs(want_costs);
+}
+
+#[test]
+fn test_p256_verify_behavior_and_errors() {
+    check_p256_verify(
+        SIGNATURE.len() as u64,
+        &SIGNATURE,
+        MESSAGE.len() as u64,
+        &MESSAGE,
+        PUBLIC_KEY.len() as u64,
+        &PUBLIC_KEY,
+        Ok(1),
+        map! {
+            ExtCosts::read_memory_byte: 160,
+            ExtCosts::read_memory_base: 3,
+            ExtCosts::p256_verify_base: 1,
+            ExtCosts::p256_verify_byte: 32,
+        },
+    );
+    check_p256_verify(
+        SIGNATURE.len() as u64,
+        &SIGNATURE,
+        OTHER_MESSAGE.len() as u64,
+        &OTHER_MESSAGE,
+        PUBLIC_KEY.len() as u64,
+        &PUBLIC_KEY,
+        Ok(0),
+        map! {
+            ExtCosts::read_memory_byte: 160,
+            ExtCosts::read_memory_base: 3,
+            ExtCosts::p256_verify_base: 1,
+            ExtCosts::p256_verify_byte: 32,
+        },
+    );
+    check_p256_verify(
+        SIGNATURE.len() as u64,
+        &SIGNATURE,
+        MESSAGE.len() as u64,
+        &MESSAGE,
+        PUBLIC_KEY.len() as u64,
+        &FORGED_PUBLIC_KEY,
+        Ok(0),
+        map! {
+            ExtCosts::read_memory_byte: 160,
+            ExtCosts::read_memory_base: 3,
+            ExtCosts::p256_verify_base: 1,
+            ExtCosts::p256_verify_byte: 32,
+        },
+    );
+    check_p256_verify(
+        BAD_SIGNATURE.len() as u64,
+        &BAD_SIGNATURE,
+        MESSAGE.len() as u64,
+        &MESSAGE,
+        PUBLIC_KEY.len() as u64,
+        &PUBLIC_KEY,
+        Ok(0),
+        map! {
+            ExtCosts::read_memory_byte: 160,
+            ExtCosts::read_memory_base: 3,
+            ExtCosts::p256_verify_base: 1,
+            ExtCosts::p256_verify_byte: 32,
+        },
+    );
+    check_p256_verify(
+        SIGNATURE.len() as u64 - 1,
+        &SIGNATURE,
+        MESSAGE.len() as u64,
+        &MESSAGE,
+        PUBLIC_KEY.len() as u64,
+        &PUBLIC_KEY,
+        Err(HostError::P256VerifyInvalidInput { msg: "invalid signature length".to_string() }),
+        map! {
+            ExtCosts::read_memory_byte: 63,
+            ExtCosts::read_memory_base: 1,
+            ExtCosts::p256_verify_base: 1,
+        },
+    );
+    check_p256_verify(
+        SIGNATURE.len() as u64,
+        &SIGNATURE,
+        MESSAGE.len() as u64,
+        &MESSAGE,
+        33,
+        &PUBLIC_KEY,
+        Err(HostError::P256VerifyInvalidInput { msg: "invalid public key length".to_string() }),
+        map! {
+            ExtCosts::read_memory_byte: 129,
+            ExtCosts::read_memory_base: 3,
+            ExtCosts::p256_verify_base: 1,
+            ExtCosts::p256_verify_byte: 32,
+        },
+    );
+}
+
+#[test]
+fn test_p256_verify_check_registers() {
+    check_p256_verify(
+        u64::MAX,
+        &SIGNATURE,
+        u64::MAX,
+        &MESSAGE,
+        u64::MAX,
+        &PUBLIC_KEY,
+        Ok(1),
+        map! {
+            ExtCosts::write_register_base: 3,
+            ExtCosts::write_register_byte: 160,
+            ExtCosts::read_register_base: 3,
+            ExtCosts::read_register_byte: 160,
+            ExtCosts::p256_verify_base: 1,
+            ExtCosts::p256_verify_byte: 32,
+        },
+    );
+}
@@ -110,6 +110,14 @@ imports! {
         pub_key_len: u64,
         pub_key_ptr: u64
     ] -> [u64]>,
+    #["protocol_feature_secp256r1", Secp256r1] p256_verify<[
+        sig_len: u64,
+        sig_ptr: u64,
+        msg_len: u64,
+        msg_ptr: u64,
+        pub_key_len: u64,
+        pub_key_ptr: u64
+    ] -> [u64]>,
     #[MathExtension] ripemd160<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
     #[MathExtension] ecrecover<[hash_len: u64, hash_ptr: u64, sign_len: u64, sig_ptr: u64, v: u64, malleability_flag: u64, register_id: u64] -> [u64]>,
     // #####################
@@ -236,6 +244,18 @@ imports! {
     #[AltBn128] alt_bn128_g1_sum<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
     #[AltBn128] alt_bn128_pairing_check<[value_len: u64, value_ptr: u64] -> [u64]>,
     // #############
+    // # BLS12-381 #
+    // #############
+    #["protocol_feature_bls12381", Bls12381] bls12381_p1_sum<[value_len: u64, value_ptr: u64, register_id: u64] -> [u64]>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_p2_sum<[value_len: u64, value_ptr: u64, register_id: u64] -> [u64]>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_g1_multiexp<[value_len: u64, value_ptr: u64, register_id: u64] -> [u64]>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_g2_multiexp<[value_len: u64, value_ptr: u64, register_id: u64] -> [u64]>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_map_fp_to_g1<[value_len: u64, value_ptr: u64, register_id: u64] -> [u64]>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_map_fp2_to_g2<[value_len: u64, value_ptr: u64, register_id: u64] -> [u64]>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_pairing_check<[value_len: u64, value_ptr: u64] -> [u64]>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_p1_decompress<[value_len: u64, value_ptr: u64, register_id: u64] -> [u64]>,
+    #["protocol_feature_bls12381", Bls12381] bls12381_p2_decompress<[value_len: u64, value_ptr: u64, register_id: u64] -> [u64]>,
+    // #############
     // #  Sandbox  #
     // #############
     ##["sandbox"] sandbox_debug_log<[len: u64, ptr: u64] -> []>,
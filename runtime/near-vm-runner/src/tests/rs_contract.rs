@@ -192,6 +192,86 @@ def_test_ext!(
     vec![("alice", 100), ("bob", 1)]
 );
 
+/// Checks that the BLS12-381 functions give the same results on every VM.
+#[cfg(feature = "nightly")]
+#[test]
+fn ext_bls12381() {
+    // Points and field elements in the uncompressed ZCash encoding, as hex.
+    const G1: &str = "\
+        17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb\
+        08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1";
+    const G1_COMPRESSED: &str = "\
+        97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb";
+    const G1_TIMES_TWO: &str = "\
+        0572cbea904d67468808c8eb50a9450c9721db309128012543902d0ac358a62ae28f75bb8f1c7c42c39a8c5529bf0f4e\
+        166a9d8cabc673a322fda673779d8e3822ba3ecb8670e461f73bb9021d5fd76a4c56d9d4cd16bd1bba86881979749d28";
+    const G2: &str = "\
+        13e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e\
+        024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8\
+        0606c4a02ea734cc32acd2b02bc28b99cb3e287e85a763af267492ab572e99ab3f370d275cec1da1aaa9075ff05f79be\
+        0ce5d527727d6e118cc9cdc6da2e351aadfd9baa8cbdd3a76d429a695160d12c923ac9cc3baca289e193548608b82801";
+    const MAP_ZERO_TO_G1: &str = "\
+        11a9a0372b8f332d5c30de9ad14e50372a73fa4c45d5f2fa5097f2d6fb93bcac592f2e1711ac43db0519870c7d0ea415\
+        092c0f994164a0719f51c24ba3788de240ff926b55f58c445116e8bc6a47cd63392fd4e8e22bdf9feaa96ee773222133";
+
+    fn run(vm_kind: VMKind, method: &str, input: &[u8]) -> Vec<u8> {
+        let code = ContractCode::new(near_test_contracts::nightly_rs_contract().to_vec(), None);
+        let mut fake_external = MockedExternal::new();
+        let config = VMConfig::test();
+        let fees = RuntimeFeesConfig::test();
+        let context = create_context(input.to_vec());
+        let runtime = vm_kind.runtime(config).expect("runtime has not been compiled");
+        let outcome = runtime
+            .run(
+                &code,
+                method,
+                &mut fake_external,
+                context,
+                &fees,
+                &[],
+                LATEST_PROTOCOL_VERSION,
+                None,
+            )
+            .unwrap_or_else(|err| panic!("Failed execution: {:?}", err));
+        match outcome.return_data {
+            ReturnData::Value(value) => value,
+            other => panic!("Value was not returned: {other:?}"),
+        }
+    }
+
+    with_vm_variants(|vm_kind: VMKind| {
+        let hex = |s: &str| hex::decode(s).unwrap();
+        let ok = |value: &str| [&0u64.to_le_bytes()[..], &hex(value)].concat();
+        let g1_infinity = [&[0x40][..], &[0; 95]].concat();
+        let scalar_two = [&[2][..], &[0; 31]].concat();
+
+        let cases = [
+            (
+                "ext_bls12381_p1_sum",
+                [&[0][..], &hex(G1), &[0], &hex(G1)].concat(),
+                ok(G1_TIMES_TWO),
+            ),
+            ("ext_bls12381_p1_sum", [&[2][..], &hex(G1)].concat(), 1u64.to_le_bytes().to_vec()),
+            ("ext_bls12381_g1_multiexp", [hex(G1), scalar_two].concat(), ok(G1_TIMES_TWO)),
+            ("ext_bls12381_map_fp_to_g1", vec![0; 48], ok(MAP_ZERO_TO_G1)),
+            ("ext_bls12381_p1_decompress", hex(G1_COMPRESSED), ok(G1)),
+            (
+                "ext_bls12381_pairing_check",
+                [hex(G1), hex(G2)].concat(),
+                2u64.to_le_bytes().to_vec(),
+            ),
+            (
+                "ext_bls12381_pairing_check",
+                [g1_infinity, hex(G2)].concat(),
+                0u64.to_le_bytes().to_vec(),
+            ),
+        ];
+        for (method, input, expected) in cases {
+            assert_eq!(run(vm_kind, method, &input), expected, "{method} on {vm_kind:?}");
+        }
+    });
+}
+
 #[test]
 pub fn test_out_of_memory() {
     with_vm_variants(|vm_kind: VMKind| {
@@ -171,6 +171,23 @@ impl EstimationRow {
 }
 
 impl ParameterRow {
+    pub(crate) fn insert(&self, db: &Db) -> anyhow::Result<()> {
+        db.conn.execute(
+            "INSERT INTO parameter(name,gas,protocol_version) values (?1,?2,?3)",
+            params![self.name, self.gas, self.protocol_version],
+        )?;
+        Ok(())
+    }
+    /// Returns the parameter value of the highest protocol version recorded.
+    pub fn get_latest(db: &Db, name: &str) -> anyhow::Result<Option<Self>> {
+        let mut stmt = db.conn.prepare(
+            "SELECT name,gas,protocol_version FROM parameter WHERE name = ?1 ORDER BY protocol_version DESC LIMIT 1;",
+        )?;
+        let mut rows = stmt.query_map([name], |row| {
+            Ok(Self { name: row.get(0)?, gas: row.get(1)?, protocol_version: row.get(2)? })
+        })?;
+        Ok(rows.next().transpose()?)
+    }
     pub fn count(db: &Db) -> anyhow::Result<u64> {
         let sql = "SELECT COUNT(*) FROM parameter;";
         let count = db.conn.query_row::<u64, _, _>(sql, [], |row| row.get(0))?;
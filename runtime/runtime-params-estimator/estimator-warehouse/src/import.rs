@@ -1,4 +1,4 @@
-use crate::db::{Db, EstimationRow};
+use crate::db::{Db, EstimationRow, ParameterRow};
 use anyhow::Context;
 use clap::Parser;
 use serde::Deserialize;
@@ -33,6 +33,12 @@ struct EstimationResult {
     io_w_bytes: Option<f64>,
     uncertain_reason: Option<String>,
 }
+/// Parameter value as produced by the params-estimator with `--json-parameters`
+#[derive(Deserialize, Debug, PartialEq)]
+struct ParameterOutput {
+    name: String,
+    parameter_gas: f64,
+}
 
 impl Db {
     pub(crate) fn import_json_lines(&self, info: &ImportConfig, input: &str) -> anyhow::Result<()> {
@@ -59,6 +65,16 @@ impl Db {
                 commit_hash: commit_hash.clone(),
             };
             row.insert(self)?;
+        } else if let Ok(parameter_output) = serde_json::from_str::<ParameterOutput>(line) {
+            let protocol_version = info.protocol_version.with_context(|| {
+                "Missing --protocol-version argument while importing parameter data".to_owned()
+            })?;
+            let row = ParameterRow {
+                name: parameter_output.name,
+                gas: parameter_output.parameter_gas,
+                protocol_version,
+            };
+            row.insert(self)?;
         }
         Ok(())
     }
@@ -66,7 +82,7 @@ impl Db {
 
 #[cfg(test)]
 mod test {
-    use crate::db::{Db, EstimationRow};
+    use crate::db::{Db, EstimationRow, ParameterRow};
     use crate::import::ImportConfig;
     use crate::Metric;
 
@@ -142,6 +158,20 @@ mod test {
         };
         assert_import(input, &info, &expected, Metric::ICount);
     }
+    #[test]
+    fn test_import_parameters() {
+        let input = r#"
+            {"name":"LogBase","parameter_gas":3543313050}
+            {"name":"LogByte","parameter_gas":13198791}
+        "#;
+        let info = ImportConfig { commit_hash: None, protocol_version: Some(57) };
+        let db = Db::test();
+        db.import_json_lines(&info, input).unwrap();
+        let expected =
+            ParameterRow { name: "LogBase".to_owned(), gas: 3543313050.0, protocol_version: 57 };
+        assert_eq!(Some(expected), ParameterRow::get_latest(&db, "LogBase").unwrap());
+        assert_eq!(None, ParameterRow::get_latest(&db, "WasmInstruction").unwrap());
+    }
     #[track_caller]
     fn assert_import(
         input: &str,
@@ -11,7 +11,7 @@ CREATE TABLE IF NOT EXISTS estimation (
     commit_hash TEXT NOT NULL                       -- which git commit this has been estimated on
 );
 CREATE TABLE IF NOT EXISTS parameter (
-    name TEXT NOT NULL,                             -- parameter name as recorded in runtime_config.json
+    name TEXT NOT NULL,                             -- name of the estimation the parameter value corresponds to
     gas REAL NOT NULL,                              -- gas cost
     protocol_version INTEGER                       -- protocol version for which the parameter is valid
 );
@@ -3,6 +3,7 @@ use clap::{Parser, Subcommand};
 use db::{Db, EstimationRow, ParameterRow};
 use estimate::{run_estimation, EstimateConfig};
 use import::ImportConfig;
+use report::{report, ReportConfig};
 use std::fmt::Write;
 use std::io::{self, Read};
 use std::path::PathBuf;
@@ -11,6 +12,7 @@ mod check;
 mod db;
 mod estimate;
 mod import;
+mod report;
 mod zulip;
 
 #[derive(clap::Parser)]
@@ -33,6 +35,10 @@ enum SubCommand {
     /// Reports any deviations from the norm to STDOUT. Combine with `--zulip`
     /// to send notifications to a Zulip stream
     Check(CheckConfig),
+    /// Renders a static HTML or Markdown report with the estimations of the
+    /// most recent commits, compared to each other and to the parameter values.
+    /// Uncertain estimations and regressions are highlighted.
+    Report(ReportConfig),
     /// Prints a summary of the current data in the warehouse.
     Stats,
 }
@@ -53,6 +59,9 @@ fn main() -> anyhow::Result<()> {
         SubCommand::Check(config) => {
             check(&db, &config)?;
         }
+        SubCommand::Report(config) => {
+            report(&db, &config)?;
+        }
         SubCommand::Stats => {
             let stats = generate_stats(&db)?;
             eprintln!("{stats}");
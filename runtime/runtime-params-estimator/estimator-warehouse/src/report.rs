@@ -0,0 +1,485 @@
+use crate::db::{Db, EstimationRow, ParameterRow};
+use crate::zulip::format_gas;
+use crate::Metric;
+use chrono::NaiveDateTime;
+use clap::Parser;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+pub(crate) struct ReportConfig {
+    /// Format of the generated report.
+    #[clap(long, arg_enum, default_value = "html")]
+    format: ReportFormat,
+    /// File to write the report to. Printed to STDOUT if left unspecified.
+    #[clap(long)]
+    output: Option<PathBuf>,
+    /// Metrics to include in the report. Leave empty to include all metrics.
+    #[clap(long, arg_enum)]
+    metrics: Vec<Metric>,
+    /// How many of the most recent commits to include for each metric.
+    #[clap(long, default_value = "10")]
+    commits: usize,
+    /// Relative increase of an estimation compared to the previous commit,
+    /// above which the estimation is highlighted as regression.
+    #[clap(long, default_value = "0.1")]
+    tolerance: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ArgEnum)]
+enum ReportFormat {
+    Html,
+    Markdown,
+}
+
+/// Estimations of all recorded commits, for each metric, compared to each
+/// other and to the protocol parameters.
+pub(crate) struct Report {
+    tolerance: f64,
+    sections: Vec<MetricSection>,
+}
+
+struct MetricSection {
+    metric: Metric,
+    /// Commits sorted by date, in ascending order.
+    commits: Vec<(String, NaiveDateTime)>,
+    series: Vec<EstimationSeries>,
+}
+
+/// Values of one estimation, one for each commit of a [`MetricSection`].
+struct EstimationSeries {
+    estimation: String,
+    /// Parameter value of the latest protocol version recorded.
+    parameter: Option<f64>,
+    values: Vec<Option<Measurement>>,
+}
+
+struct Measurement {
+    gas: f64,
+    uncertain_reason: Option<String>,
+    /// Relative change compared to the previous commit with a value.
+    change: Option<f64>,
+}
+
+pub(crate) fn report(db: &Db, config: &ReportConfig) -> anyhow::Result<()> {
+    let report = create_report(db, config)?;
+    let output = match config.format {
+        ReportFormat::Html => report.to_html()?,
+        ReportFormat::Markdown => report.to_markdown()?,
+    };
+    match &config.output {
+        Some(path) => std::fs::write(path, output)?,
+        None => println!("{output}"),
+    }
+    Ok(())
+}
+
+pub(crate) fn create_report(db: &Db, config: &ReportConfig) -> anyhow::Result<Report> {
+    let metrics = if config.metrics.len() > 0 {
+        config.metrics.clone()
+    } else {
+        vec![Metric::ICount, Metric::Time]
+    };
+    let mut sections = vec![];
+    for metric in metrics {
+        let mut commits = EstimationRow::commits_sorted_by_date(db, Some(metric))?;
+        commits.drain(..commits.len().saturating_sub(config.commits));
+
+        let mut values: BTreeMap<String, Vec<Option<Measurement>>> = BTreeMap::new();
+        for (i, (commit, _date)) in commits.iter().enumerate() {
+            for row in EstimationRow::select_by_commit_and_metric(db, commit, metric)? {
+                let series = values.entry(row.name).or_insert_with(Vec::new);
+                // Estimations can be recorded more than once per commit, only
+                // the first one is used, consistent with `check`.
+                if series.len() > i {
+                    continue;
+                }
+                series.resize_with(i, || None);
+                let previous = series.iter().rev().flatten().next();
+                let change = previous.map(|prev| (row.gas - prev.gas) / prev.gas);
+                series.push(Some(Measurement {
+                    gas: row.gas,
+                    uncertain_reason: row.uncertain_reason,
+                    change,
+                }));
+            }
+        }
+
+        let mut series = vec![];
+        for (estimation, mut values) in values {
+            values.resize_with(commits.len(), || None);
+            let parameter = ParameterRow::get_latest(db, &estimation)?.map(|row| row.gas);
+            series.push(EstimationSeries { estimation, parameter, values });
+        }
+        sections.push(MetricSection { metric, commits, series });
+    }
+    Ok(Report { tolerance: config.tolerance, sections })
+}
+
+impl Report {
+    pub(crate) fn to_markdown(&self) -> anyhow::Result<String> {
+        let mut buf = String::new();
+        writeln!(buf, "# Estimator Report")?;
+        writeln!(buf)?;
+        writeln!(
+            buf,
+            "Regressions (**bold**) are increases above {:.2}% compared to the previous commit. \
+            Uncertain estimations are marked with `?`. \
+            Estimations above the parameter value are marked with `!`.",
+            100.0 * self.tolerance
+        )?;
+        for section in &self.sections {
+            writeln!(buf)?;
+            writeln!(buf, "## Metric: {}", metric_name(section.metric))?;
+            writeln!(buf)?;
+            if section.commits.is_empty() {
+                writeln!(buf, "No estimations recorded.")?;
+                continue;
+            }
+            writeln!(buf, "| commit | date |")?;
+            writeln!(buf, "| --- | --- |")?;
+            for (commit, date) in &section.commits {
+                writeln!(buf, "| `{}` | {date} |", commit.trim())?;
+            }
+
+            writeln!(buf)?;
+            writeln!(buf, "### Regressions")?;
+            writeln!(buf)?;
+            let regressions = section.regressions(self.tolerance);
+            if regressions.is_empty() {
+                writeln!(buf, "None.")?;
+            } else {
+                writeln!(buf, "| estimation | commit | before | after | change |")?;
+                writeln!(buf, "| --- | --- | ---: | ---: | ---: |")?;
+                for (estimation, commit, before, after) in regressions {
+                    writeln!(
+                        buf,
+                        "| {estimation} | `{}` | {} | {} | {} |",
+                        commit.trim(),
+                        format_gas(before),
+                        format_gas(after),
+                        format_change((after - before) / before),
+                    )?;
+                }
+            }
+
+            writeln!(buf)?;
+            writeln!(buf, "### Uncertain estimations")?;
+            writeln!(buf)?;
+            let uncertain = section.uncertain();
+            if uncertain.is_empty() {
+                writeln!(buf, "None.")?;
+            } else {
+                writeln!(buf, "| estimation | commit | reason |")?;
+                writeln!(buf, "| --- | --- | --- |")?;
+                for (estimation, commit, reason) in uncertain {
+                    writeln!(buf, "| {estimation} | `{}` | {reason} |", commit.trim())?;
+                }
+            }
+
+            writeln!(buf)?;
+            writeln!(buf, "### Estimations")?;
+            writeln!(buf)?;
+            write!(buf, "| estimation | parameter |")?;
+            for (commit, _date) in &section.commits {
+                write!(buf, " `{}` |", commit.trim())?;
+            }
+            writeln!(buf, " latest / parameter |")?;
+            writeln!(buf, "| --- | ---: |{} ---: |", " ---: |".repeat(section.commits.len()))?;
+            for series in &section.series {
+                write!(
+                    buf,
+                    "| {} | {} |",
+                    series.estimation,
+                    series.parameter.map(format_gas).unwrap_or_default()
+                )?;
+                for value in &series.values {
+                    match value {
+                        Some(value) => {
+                            let mut cell = format_gas(value.gas);
+                            if value.uncertain_reason.is_some() {
+                                cell.push_str(" ?");
+                            }
+                            if value.is_regression(self.tolerance) {
+                                cell = format!("**{cell}**");
+                            }
+                            write!(buf, " {cell} |")?;
+                        }
+                        None => write!(buf, " |")?,
+                    }
+                }
+                match series.parameter_ratio() {
+                    Some(ratio) if ratio > 1.0 => writeln!(buf, " {ratio:.2} ! |")?,
+                    Some(ratio) => writeln!(buf, " {ratio:.2} |")?,
+                    None => writeln!(buf, " |")?,
+                }
+            }
+        }
+        Ok(buf)
+    }
+
+    pub(crate) fn to_html(&self) -> anyhow::Result<String> {
+        let mut buf = String::new();
+        writeln!(buf, "<!DOCTYPE html>")?;
+        writeln!(buf, "<html>")?;
+        writeln!(buf, "<head>")?;
+        writeln!(buf, "<meta charset=\"utf-8\">")?;
+        writeln!(buf, "<title>Estimator Report</title>")?;
+        writeln!(buf, "<style>")?;
+        writeln!(buf, "body {{ font-family: sans-serif; }}")?;
+        writeln!(buf, "table {{ border-collapse: collapse; margin-bottom: 1em; }}")?;
+        writeln!(buf, "th, td {{ border: 1px solid #ccc; padding: 2px 6px; }}")?;
+        writeln!(buf, "td.number {{ text-align: right; }}")?;
+        writeln!(buf, ".regression {{ background: #f8c0c0; font-weight: bold; }}")?;
+        writeln!(buf, ".uncertain {{ color: #999; font-style: italic; }}")?;
+        writeln!(buf, ".above-parameter {{ background: #f8e0a0; }}")?;
+        writeln!(buf, "</style>")?;
+        writeln!(buf, "</head>")?;
+        writeln!(buf, "<body>")?;
+        writeln!(buf, "<h1>Estimator Report</h1>")?;
+        writeln!(
+            buf,
+            "<p>Regressions are <span class=\"regression\">increases above {:.2}%</span> \
+            compared to the previous commit. \
+            <span class=\"uncertain\">Uncertain estimations</span> show the reason on hover. \
+            <span class=\"above-parameter\">Estimations above the parameter value</span> \
+            are highlighted in the last column.</p>",
+            100.0 * self.tolerance
+        )?;
+        for section in &self.sections {
+            writeln!(buf, "<h2>Metric: {}</h2>", metric_name(section.metric))?;
+            if section.commits.is_empty() {
+                writeln!(buf, "<p>No estimations recorded.</p>")?;
+                continue;
+            }
+
+            writeln!(buf, "<h3>Regressions</h3>")?;
+            let regressions = section.regressions(self.tolerance);
+            if regressions.is_empty() {
+                writeln!(buf, "<p>None.</p>")?;
+            } else {
+                writeln!(buf, "<table>")?;
+                writeln!(
+                    buf,
+                    "<tr><th>estimation</th><th>commit</th><th>before</th><th>after</th><th>change</th></tr>"
+                )?;
+                for (estimation, commit, before, after) in regressions {
+                    writeln!(
+                        buf,
+                        "<tr><td>{}</td><td><code>{}</code></td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number regression\">{}</td></tr>",
+                        html_escape(estimation),
+                        html_escape(commit.trim()),
+                        format_gas(before),
+                        format_gas(after),
+                        format_change((after - before) / before),
+                    )?;
+                }
+                writeln!(buf, "</table>")?;
+            }
+
+            writeln!(buf, "<h3>Estimations</h3>")?;
+            writeln!(buf, "<table>")?;
+            write!(buf, "<tr><th>estimation</th><th>parameter</th>")?;
+            for (commit, date) in &section.commits {
+                write!(
+                    buf,
+                    "<th title=\"{date}\"><code>{}</code></th>",
+                    html_escape(commit.trim())
+                )?;
+            }
+            writeln!(buf, "<th>latest / parameter</th></tr>")?;
+            for series in &section.series {
+                write!(
+                    buf,
+                    "<tr><td>{}</td><td class=\"number\">{}</td>",
+                    html_escape(&series.estimation),
+                    series.parameter.map(format_gas).unwrap_or_default()
+                )?;
+                for value in &series.values {
+                    let value = match value {
+                        Some(value) => value,
+                        None => {
+                            write!(buf, "<td></td>")?;
+                            continue;
+                        }
+                    };
+                    let mut class = "number".to_owned();
+                    if value.is_regression(self.tolerance) {
+                        class.push_str(" regression");
+                    }
+                    if value.uncertain_reason.is_some() {
+                        class.push_str(" uncertain");
+                    }
+                    let mut title = value.change.map(format_change).unwrap_or_default();
+                    if let Some(reason) = &value.uncertain_reason {
+                        write!(title, " {}", html_escape(reason))?;
+                    }
+                    write!(
+                        buf,
+                        "<td class=\"{class}\" title=\"{}\">{}</td>",
+                        title.trim(),
+                        format_gas(value.gas)
+                    )?;
+                }
+                match series.parameter_ratio() {
+                    Some(ratio) if ratio > 1.0 => {
+                        writeln!(buf, "<td class=\"number above-parameter\">{ratio:.2}</td></tr>")?
+                    }
+                    Some(ratio) => writeln!(buf, "<td class=\"number\">{ratio:.2}</td></tr>")?,
+                    None => writeln!(buf, "<td></td></tr>")?,
+                }
+            }
+            writeln!(buf, "</table>")?;
+        }
+        writeln!(buf, "</body>")?;
+        writeln!(buf, "</html>")?;
+        Ok(buf)
+    }
+}
+
+impl MetricSection {
+    /// Returns (estimation, commit, before, after) for every increase above
+    /// the tolerance between two consecutive values.
+    fn regressions(&self, tolerance: f64) -> Vec<(&str, &str, f64, f64)> {
+        let mut res = vec![];
+        for series in &self.series {
+            let mut previous = None;
+            for ((commit, _date), value) in self.commits.iter().zip(&series.values) {
+                let value = match value {
+                    Some(value) => value,
+                    None => continue,
+                };
+                if let (true, Some(before)) = (value.is_regression(tolerance), previous) {
+                    res.push((series.estimation.as_str(), commit.as_str(), before, value.gas));
+                }
+                previous = Some(value.gas);
+            }
+        }
+        res
+    }
+
+    /// Returns (estimation, commit, reason) for every uncertain estimation of
+    /// the latest commit.
+    fn uncertain(&self) -> Vec<(&str, &str, &str)> {
+        let (commit, _date) = match self.commits.last() {
+            Some(last) => last,
+            None => return vec![],
+        };
+        self.series
+            .iter()
+            .filter_map(|series| {
+                let reason = series.values.last()?.as_ref()?.uncertain_reason.as_deref()?;
+                Some((series.estimation.as_str(), commit.as_str(), reason))
+            })
+            .collect()
+    }
+}
+
+impl EstimationSeries {
+    /// Ratio of the estimation of the latest commit to the parameter value.
+    fn parameter_ratio(&self) -> Option<f64> {
+        let latest = self.values.last()?.as_ref()?;
+        let parameter = self.parameter.filter(|&gas| gas > 0.0)?;
+        Some(latest.gas / parameter)
+    }
+}
+
+impl Measurement {
+    fn is_regression(&self, tolerance: f64) -> bool {
+        self.change.map_or(false, |change| change > tolerance)
+    }
+}
+
+fn metric_name(metric: Metric) -> &'static str {
+    match metric {
+        Metric::ICount => "icount",
+        Metric::Time => "time",
+    }
+}
+
+fn format_change(change: f64) -> String {
+    format!("{}{:.2}%", if change >= 0.0 { "+" } else { "" }, 100.0 * change)
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db() -> Db {
+        let input = r#"0000a
+{"computed_in":{"nanos":800,"secs":44},"name":"LogBase","result":{"gas":1000000000.0,"instructions":8000.0,"io_r_bytes":0.0,"io_w_bytes":0.0,"metric":"icount","uncertain_reason":null}}
+{"computed_in":{"nanos":809,"secs":26},"name":"LogByte","result":{"gas":1000000000.0,"instructions":8000.0,"io_r_bytes":0.0,"io_w_bytes":0.0,"metric":"icount","uncertain_reason":null}}
+{"computed_in":{"nanos":655,"secs":56},"name":"LogByte","result":{"gas":20000000.0,"time_ns":20,"metric":"time","uncertain_reason":null}}
+
+WAIT
+
+0001a
+{"computed_in":{"nanos":814,"secs":9},"name":"LogBase","result":{"gas":2000000000.0,"instructions":16000.0,"io_r_bytes":0.0,"io_w_bytes":0.0,"metric":"icount","uncertain_reason":null}}
+{"computed_in":{"nanos":694,"secs":33},"name":"LogByte","result":{"gas":1002000000.0,"instructions":8016.0,"io_r_bytes":0.0,"io_w_bytes":0.0,"metric":"icount","uncertain_reason":"HIGH-VARIANCE"}}
+{"computed_in":{"nanos":633,"secs":7},"name":"UncertainTest","result":{"gas":4000000000.0,"instructions":32000.0,"io_r_bytes":0.0,"io_w_bytes":0.0,"metric":"icount","uncertain_reason":"NEGATIVE-COST"}}
+
+WAIT
+
+0002a
+{"computed_in":{"nanos":331,"secs":24},"name":"LogBase","result":{"gas":1900000000.0,"instructions":15200.0,"io_r_bytes":0.0,"io_w_bytes":0.0,"metric":"icount","uncertain_reason":null}}
+{"computed_in":{"nanos":511,"secs":52},"name":"LogByte","result":{"gas":1500000000.0,"instructions":12000.0,"io_r_bytes":0.0,"io_w_bytes":0.0,"metric":"icount","uncertain_reason":"HIGH-VARIANCE"}}
+{"computed_in":{"nanos":661,"secs":11},"name":"LogByte","result":{"gas":15000000.0,"time_ns":15,"metric":"time","uncertain_reason":null}}"#;
+        let db = Db::test_with_data(input);
+        for (name, gas) in [("LogBase", 3543313050.0), ("LogByte", 13198791.0)] {
+            ParameterRow { name: name.to_owned(), gas, protocol_version: 57 }.insert(&db).unwrap();
+        }
+        ParameterRow { name: "LogBase".to_owned(), gas: 1500000000.0, protocol_version: 58 }
+            .insert(&db)
+            .unwrap();
+        db
+    }
+
+    fn test_config(metrics: &[Metric], commits: usize) -> ReportConfig {
+        ReportConfig {
+            format: ReportFormat::Markdown,
+            output: None,
+            metrics: metrics.to_vec(),
+            commits,
+            tolerance: 0.1,
+        }
+    }
+
+    #[test]
+    fn test_report_markdown() {
+        let report = create_report(&test_db(), &test_config(&[], 10)).unwrap();
+        insta::assert_snapshot!(report.to_markdown().unwrap());
+    }
+
+    #[test]
+    fn test_report_html() {
+        let report = create_report(&test_db(), &test_config(&[Metric::ICount], 10)).unwrap();
+        insta::assert_snapshot!(report.to_html().unwrap());
+    }
+
+    #[test]
+    fn test_report_regressions() {
+        let db = test_db();
+        let report = create_report(&db, &test_config(&[Metric::ICount], 10)).unwrap();
+        assert_eq!(
+            report.sections[0].regressions(0.1),
+            vec![
+                ("LogBase", "0001a", 1000000000.0, 2000000000.0),
+                ("LogByte", "0002a", 1002000000.0, 1500000000.0),
+            ]
+        );
+        assert_eq!(report.sections[0].uncertain(), vec![("LogByte", "0002a", "HIGH-VARIANCE")]);
+
+        // Only the most recent commits are included.
+        let report = create_report(&db, &test_config(&[Metric::ICount], 2)).unwrap();
+        assert_eq!(report.sections[0].commits.len(), 2);
+        assert_eq!(
+            report.sections[0].regressions(0.1),
+            vec![("LogByte", "0002a", 1002000000.0, 1500000000.0)]
+        );
+    }
+}
@@ -121,7 +121,7 @@ impl std::fmt::Display for ZulipReport {
     }
 }
 
-fn format_gas(gas: f64) -> String {
+pub(crate) fn format_gas(gas: f64) -> String {
     match gas {
         n if n > 1e12 => format!("{:.2} Tgas", n / 1e12),
         n if n > 1e9 => format!("{:.2} Ggas", n / 1e9),
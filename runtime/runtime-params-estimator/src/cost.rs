@@ -387,6 +387,23 @@ pub enum Cost {
     /// In the end, the cost should be low enough, compared to the base cost,
     /// that it does not matter all that much if we overestimate it a bit.
     Ed25519VerifyByte,
+    /// Estimates `p256_verify_base`, which covers the base cost of the host
+    /// function `p256_verify` to verify an ECDSA signature over the NIST P-256
+    /// curve.
+    ///
+    /// Estimation: Same as for `Ed25519VerifyBase`, verify a fixed signature
+    /// embedded in the test contract `N` times in a loop and divide by `N`.
+    /// The cost is dominated by the two scalar multiplications and the
+    /// inversion of `s` performed for the verification.
+    P256VerifyBase,
+    /// Estimates `p256_verify_byte`, the cost charged per input byte in calls
+    /// to the p256_verify host function.
+    ///
+    /// Estimation: Same as for `Ed25519VerifyByte`, verify a signature for a
+    /// large message many times, subtract the base cost and divide the
+    /// remainder by the total bytes of the message. The cost per byte is the
+    /// cost of hashing the message with SHA-256.
+    P256VerifyByte,
     // `storage_write` records a single key-value pair, initially in the
     // prospective changes in-memory hash map, and then once a full block has
     // been processed, in the on-disk trie. If there was already a value
@@ -566,6 +583,42 @@ pub enum Cost {
     AltBn128PairingCheckElement,
     AltBn128G1SumBase,
     AltBn128G1SumElement,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381P1SumBase,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381P1SumElement,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381P2SumBase,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381P2SumElement,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381G1MultiexpBase,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381G1MultiexpElement,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381G2MultiexpBase,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381G2MultiexpElement,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381MapFpToG1Base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381MapFpToG1Element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381MapFp2ToG2Base,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381MapFp2ToG2Element,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381PairingCheckBase,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381PairingCheckElement,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381P1DecompressBase,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381P1DecompressElement,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381P2DecompressBase,
+    #[cfg(feature = "protocol_feature_bls12381")]
+    Bls12381P2DecompressElement,
 
     // Costs used only in estimator
     //
@@ -28,6 +28,9 @@ impl CostTable {
     pub(crate) fn get(&self, cost: Cost) -> Option<Gas> {
         self.map.get(&cost).copied()
     }
+    pub fn iter(&self) -> impl Iterator<Item = (Cost, Gas)> + '_ {
+        self.map.iter().map(|(&cost, &gas)| (cost, gas))
+    }
     pub fn diff(&self, other: &CostTable) -> CostTableDiff {
         let mut res = CostTableDiff::default();
         for (&cost, &x) in &self.map {
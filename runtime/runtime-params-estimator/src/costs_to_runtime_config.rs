@@ -1,4 +1,4 @@
-use near_primitives::runtime::config::AccountCreationConfig;
+use near_primitives::runtime::config::{AccountCreationConfig, CongestionControlConfig};
 use near_primitives::runtime::config_store::RuntimeConfigStore;
 use near_primitives::runtime::fees::{
     AccessKeyCreationConfig, ActionCreationConfig, DataReceiptCreationConfig, Fee,
@@ -43,6 +43,7 @@ pub fn costs_to_runtime_config(cost_table: &CostTable) -> anyhow::Result<Runtime
             limit_config: vm_limit_config,
         },
         account_creation_config: AccountCreationConfig::default(),
+        congestion_control_config: CongestionControlConfig::default(),
     };
     Ok(res)
 }
@@ -78,7 +79,11 @@ fn runtime_fees_config(cost_table: &CostTable) -> anyhow::Result<RuntimeFeesConf
             },
             delete_key_cost: fee(Cost::ActionDeleteKey)?,
             delete_account_cost: fee(Cost::ActionDeleteAccount)?,
+            // Not estimated yet, keep the value from the current config.
+            delegate_cost: actual_fees_config.action_creation_config.delegate_cost.clone(),
         },
+        // The estimator measures gas only, compute costs are set manually.
+        compute_costs: Default::default(),
         ..actual_fees_config.clone()
     };
     Ok(res)
@@ -118,6 +123,10 @@ fn ext_costs_config(cost_table: &CostTable) -> anyhow::Result<ExtCostsConfig> {
         ed25519_verify_base: get(Cost::Ed25519VerifyBase)?,
         #[cfg(feature = "protocol_feature_ed25519_verify")]
         ed25519_verify_byte: get(Cost::Ed25519VerifyByte)?,
+        #[cfg(feature = "protocol_feature_secp256r1")]
+        p256_verify_base: get(Cost::P256VerifyBase)?,
+        #[cfg(feature = "protocol_feature_secp256r1")]
+        p256_verify_byte: get(Cost::P256VerifyByte)?,
         log_base: get(Cost::LogBase)?,
         log_byte: get(Cost::LogByte)?,
         storage_write_base: get(Cost::StorageWriteBase)?,
@@ -158,7 +167,190 @@ fn ext_costs_config(cost_table: &CostTable) -> anyhow::Result<ExtCostsConfig> {
         alt_bn128_g1_multiexp_element: get(Cost::AltBn128G1MultiexpElement)?,
         alt_bn128_pairing_check_base: get(Cost::AltBn128PairingCheckBase)?,
         alt_bn128_pairing_check_element: get(Cost::AltBn128PairingCheckElement)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_p1_sum_base: get(Cost::Bls12381P1SumBase)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_p1_sum_element: get(Cost::Bls12381P1SumElement)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_p2_sum_base: get(Cost::Bls12381P2SumBase)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_p2_sum_element: get(Cost::Bls12381P2SumElement)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_g1_multiexp_base: get(Cost::Bls12381G1MultiexpBase)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_g1_multiexp_element: get(Cost::Bls12381G1MultiexpElement)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_g2_multiexp_base: get(Cost::Bls12381G2MultiexpBase)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_g2_multiexp_element: get(Cost::Bls12381G2MultiexpElement)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_map_fp_to_g1_base: get(Cost::Bls12381MapFpToG1Base)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_map_fp_to_g1_element: get(Cost::Bls12381MapFpToG1Element)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_map_fp2_to_g2_base: get(Cost::Bls12381MapFp2ToG2Base)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_map_fp2_to_g2_element: get(Cost::Bls12381MapFp2ToG2Element)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_pairing_base: get(Cost::Bls12381PairingCheckBase)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_pairing_element: get(Cost::Bls12381PairingCheckElement)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_p1_decompress_base: get(Cost::Bls12381P1DecompressBase)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_p1_decompress_element: get(Cost::Bls12381P1DecompressElement)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_p2_decompress_base: get(Cost::Bls12381P2DecompressBase)?,
+        #[cfg(feature = "protocol_feature_bls12381")]
+        bls12381_p2_decompress_element: get(Cost::Bls12381P2DecompressElement)?,
+        // The estimator measures gas only, compute costs are set manually.
+        compute_costs: Default::default(),
     };
 
     Ok(res)
 }
+
+/// The inverse of [`costs_to_runtime_config`]: for each [`Cost`] that is used
+/// to set a parameter, the gas value of that parameter in the given config.
+///
+/// Fees are split evenly between send and execution fee by
+/// [`costs_to_runtime_config`], hence they are mapped back to the sum of both.
+pub fn runtime_config_to_costs(runtime_config: &RuntimeConfig) -> CostTable {
+    let fee = |fee: &Fee| fee.send_fee(true) + fee.exec_fee();
+    let fees = &runtime_config.transaction_costs;
+    let actions = &fees.action_creation_config;
+    let ext_costs = &runtime_config.wasm_config.ext_costs;
+
+    let parameters = [
+        (Cost::WasmInstruction, runtime_config.wasm_config.regular_op_cost as Gas),
+        (Cost::ActionReceiptCreation, fee(&fees.action_receipt_creation_config)),
+        (Cost::DataReceiptCreationBase, fee(&fees.data_receipt_creation_config.base_cost)),
+        (Cost::DataReceiptCreationPerByte, fee(&fees.data_receipt_creation_config.cost_per_byte)),
+        (Cost::ActionCreateAccount, fee(&actions.create_account_cost)),
+        (Cost::ActionDeployContractBase, fee(&actions.deploy_contract_cost)),
+        (Cost::ActionDeployContractPerByte, fee(&actions.deploy_contract_cost_per_byte)),
+        (Cost::ActionFunctionCallBase, fee(&actions.function_call_cost)),
+        (Cost::ActionFunctionCallPerByte, fee(&actions.function_call_cost_per_byte)),
+        (Cost::ActionTransfer, fee(&actions.transfer_cost)),
+        (Cost::ActionStake, fee(&actions.stake_cost)),
+        (Cost::ActionAddFullAccessKey, fee(&actions.add_key_cost.full_access_cost)),
+        (Cost::ActionAddFunctionAccessKeyBase, fee(&actions.add_key_cost.function_call_cost)),
+        (
+            Cost::ActionAddFunctionAccessKeyPerByte,
+            fee(&actions.add_key_cost.function_call_cost_per_byte),
+        ),
+        (Cost::ActionDeleteKey, fee(&actions.delete_key_cost)),
+        (Cost::ActionDeleteAccount, fee(&actions.delete_account_cost)),
+        (Cost::HostFunctionCall, ext_costs.base),
+        (Cost::ReadMemoryBase, ext_costs.read_memory_base),
+        (Cost::ReadMemoryByte, ext_costs.read_memory_byte),
+        (Cost::WriteMemoryBase, ext_costs.write_memory_base),
+        (Cost::WriteMemoryByte, ext_costs.write_memory_byte),
+        (Cost::ReadRegisterBase, ext_costs.read_register_base),
+        (Cost::ReadRegisterByte, ext_costs.read_register_byte),
+        (Cost::WriteRegisterBase, ext_costs.write_register_base),
+        (Cost::WriteRegisterByte, ext_costs.write_register_byte),
+        (Cost::Utf8DecodingBase, ext_costs.utf8_decoding_base),
+        (Cost::Utf8DecodingByte, ext_costs.utf8_decoding_byte),
+        (Cost::Utf16DecodingBase, ext_costs.utf16_decoding_base),
+        (Cost::Utf16DecodingByte, ext_costs.utf16_decoding_byte),
+        (Cost::Sha256Base, ext_costs.sha256_base),
+        (Cost::Sha256Byte, ext_costs.sha256_byte),
+        (Cost::Keccak256Base, ext_costs.keccak256_base),
+        (Cost::Keccak256Byte, ext_costs.keccak256_byte),
+        (Cost::Keccak512Base, ext_costs.keccak512_base),
+        (Cost::Keccak512Byte, ext_costs.keccak512_byte),
+        (Cost::Ripemd160Base, ext_costs.ripemd160_base),
+        (Cost::Ripemd160Block, ext_costs.ripemd160_block),
+        (Cost::EcrecoverBase, ext_costs.ecrecover_base),
+        #[cfg(feature = "protocol_feature_ed25519_verify")]
+        (Cost::Ed25519VerifyBase, ext_costs.ed25519_verify_base),
+        #[cfg(feature = "protocol_feature_ed25519_verify")]
+        (Cost::Ed25519VerifyByte, ext_costs.ed25519_verify_byte),
+        #[cfg(feature = "protocol_feature_secp256r1")]
+        (Cost::P256VerifyBase, ext_costs.p256_verify_base),
+        #[cfg(feature = "protocol_feature_secp256r1")]
+        (Cost::P256VerifyByte, ext_costs.p256_verify_byte),
+        (Cost::LogBase, ext_costs.log_base),
+        (Cost::LogByte, ext_costs.log_byte),
+        (Cost::StorageWriteBase, ext_costs.storage_write_base),
+        (Cost::StorageWriteKeyByte, ext_costs.storage_write_key_byte),
+        (Cost::StorageWriteValueByte, ext_costs.storage_write_value_byte),
+        (Cost::StorageWriteEvictedByte, ext_costs.storage_write_evicted_byte),
+        (Cost::StorageReadBase, ext_costs.storage_read_base),
+        (Cost::StorageReadKeyByte, ext_costs.storage_read_key_byte),
+        (Cost::StorageReadValueByte, ext_costs.storage_read_value_byte),
+        (Cost::StorageRemoveBase, ext_costs.storage_remove_base),
+        (Cost::StorageRemoveKeyByte, ext_costs.storage_remove_key_byte),
+        (Cost::StorageRemoveRetValueByte, ext_costs.storage_remove_ret_value_byte),
+        (Cost::StorageHasKeyBase, ext_costs.storage_has_key_base),
+        (Cost::StorageHasKeyByte, ext_costs.storage_has_key_byte),
+        (Cost::TouchingTrieNode, ext_costs.touching_trie_node),
+        (Cost::ReadCachedTrieNode, ext_costs.read_cached_trie_node),
+        (Cost::PromiseAndBase, ext_costs.promise_and_base),
+        (Cost::PromiseAndPerPromise, ext_costs.promise_and_per_promise),
+        (Cost::PromiseReturn, ext_costs.promise_return),
+        (Cost::AltBn128G1SumBase, ext_costs.alt_bn128_g1_sum_base),
+        (Cost::AltBn128G1SumElement, ext_costs.alt_bn128_g1_sum_element),
+        (Cost::AltBn128G1MultiexpBase, ext_costs.alt_bn128_g1_multiexp_base),
+        (Cost::AltBn128G1MultiexpElement, ext_costs.alt_bn128_g1_multiexp_element),
+        (Cost::AltBn128PairingCheckBase, ext_costs.alt_bn128_pairing_check_base),
+        (Cost::AltBn128PairingCheckElement, ext_costs.alt_bn128_pairing_check_element),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381P1SumBase, ext_costs.bls12381_p1_sum_base),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381P1SumElement, ext_costs.bls12381_p1_sum_element),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381P2SumBase, ext_costs.bls12381_p2_sum_base),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381P2SumElement, ext_costs.bls12381_p2_sum_element),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381G1MultiexpBase, ext_costs.bls12381_g1_multiexp_base),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381G1MultiexpElement, ext_costs.bls12381_g1_multiexp_element),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381G2MultiexpBase, ext_costs.bls12381_g2_multiexp_base),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381G2MultiexpElement, ext_costs.bls12381_g2_multiexp_element),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381MapFpToG1Base, ext_costs.bls12381_map_fp_to_g1_base),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381MapFpToG1Element, ext_costs.bls12381_map_fp_to_g1_element),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381MapFp2ToG2Base, ext_costs.bls12381_map_fp2_to_g2_base),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381MapFp2ToG2Element, ext_costs.bls12381_map_fp2_to_g2_element),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381PairingCheckBase, ext_costs.bls12381_pairing_base),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381PairingCheckElement, ext_costs.bls12381_pairing_element),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381P1DecompressBase, ext_costs.bls12381_p1_decompress_base),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381P1DecompressElement, ext_costs.bls12381_p1_decompress_element),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381P2DecompressBase, ext_costs.bls12381_p2_decompress_base),
+        #[cfg(feature = "protocol_feature_bls12381")]
+        (Cost::Bls12381P2DecompressElement, ext_costs.bls12381_p2_decompress_element),
+    ];
+
+    let mut res = CostTable::default();
+    for (cost, gas) in parameters {
+        res.add(cost, gas);
+    }
+    res
+}
+
+#[test]
+fn test_runtime_config_to_costs_round_trip() {
+    // Fees are split in halves, so use even values to make the round trip exact.
+    let mut costs = CostTable::default();
+    let config = RuntimeConfigStore::new(None).get_config(PROTOCOL_VERSION).clone();
+    for (i, (cost, _)) in runtime_config_to_costs(&config).iter().enumerate() {
+        costs.add(cost, 2 * (i as Gas + 1));
+    }
+
+    let runtime_config = costs_to_runtime_config(&costs).unwrap();
+    let round_trip = runtime_config_to_costs(&runtime_config);
+    assert_eq!(costs.iter().collect::<Vec<_>>(), round_trip.iter().collect::<Vec<_>>());
+}
@@ -35,6 +35,8 @@ pub(crate) struct CachedCosts {
     pub(crate) touching_trie_node_write: Option<GasCost>,
     #[cfg(feature = "protocol_feature_ed25519_verify")]
     pub(crate) ed25519_verify_base: Option<GasCost>,
+    #[cfg(feature = "protocol_feature_secp256r1")]
+    pub(crate) p256_verify_base: Option<GasCost>,
 }
 
 impl<'c> EstimatorContext<'c> {
@@ -122,7 +122,7 @@ use crate::vm_estimator::create_context;
 
 pub use crate::cost::Cost;
 pub use crate::cost_table::CostTable;
-pub use crate::costs_to_runtime_config::costs_to_runtime_config;
+pub use crate::costs_to_runtime_config::{costs_to_runtime_config, runtime_config_to_costs};
 pub use crate::qemu::QemuCommandBuilder;
 pub use crate::rocksdb::RocksDBTestConfig;
 
@@ -172,12 +172,52 @@ static ALL_COSTS: &[(Cost, fn(&mut EstimatorContext) -> GasCost)] = &[
     (Cost::Ed25519VerifyBase, ed25519_verify_base),
     #[cfg(feature = "protocol_feature_ed25519_verify")]
     (Cost::Ed25519VerifyByte, ed25519_verify_byte),
+    #[cfg(feature = "protocol_feature_secp256r1")]
+    (Cost::P256VerifyBase, p256_verify_base),
+    #[cfg(feature = "protocol_feature_secp256r1")]
+    (Cost::P256VerifyByte, p256_verify_byte),
     (Cost::AltBn128G1MultiexpBase, alt_bn128g1_multiexp_base),
     (Cost::AltBn128G1MultiexpElement, alt_bn128g1_multiexp_element),
     (Cost::AltBn128G1SumBase, alt_bn128g1_sum_base),
     (Cost::AltBn128G1SumElement, alt_bn128g1_sum_element),
     (Cost::AltBn128PairingCheckBase, alt_bn128_pairing_check_base),
     (Cost::AltBn128PairingCheckElement, alt_bn128_pairing_check_element),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381P1SumBase, bls12381_p1_sum_base),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381P1SumElement, bls12381_p1_sum_element),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381P2SumBase, bls12381_p2_sum_base),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381P2SumElement, bls12381_p2_sum_element),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381G1MultiexpBase, bls12381_g1_multiexp_base),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381G1MultiexpElement, bls12381_g1_multiexp_element),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381G2MultiexpBase, bls12381_g2_multiexp_base),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381G2MultiexpElement, bls12381_g2_multiexp_element),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381MapFpToG1Base, bls12381_map_fp_to_g1_base),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381MapFpToG1Element, bls12381_map_fp_to_g1_element),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381MapFp2ToG2Base, bls12381_map_fp2_to_g2_base),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381MapFp2ToG2Element, bls12381_map_fp2_to_g2_element),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381PairingCheckBase, bls12381_pairing_check_base),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381PairingCheckElement, bls12381_pairing_check_element),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381P1DecompressBase, bls12381_p1_decompress_base),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381P1DecompressElement, bls12381_p1_decompress_element),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381P2DecompressBase, bls12381_p2_decompress_base),
+    #[cfg(feature = "protocol_feature_bls12381")]
+    (Cost::Bls12381P2DecompressElement, bls12381_p2_decompress_element),
     (Cost::StorageHasKeyBase, storage_has_key_base),
     (Cost::StorageHasKeyByte, storage_has_key_byte),
     (Cost::StorageReadBase, storage_read_base),
@@ -953,6 +993,28 @@ fn ed25519_verify_byte(ctx: &mut EstimatorContext) -> GasCost {
     byte - base / iteration_bytes
 }
 
+#[cfg(feature = "protocol_feature_secp256r1")]
+fn p256_verify_base(ctx: &mut EstimatorContext) -> GasCost {
+    if ctx.cached.p256_verify_base.is_none() {
+        let cost = fn_cost(ctx, "p256_verify_32b_500", ExtCosts::p256_verify_base, 500);
+        ctx.cached.p256_verify_base = Some(cost);
+    }
+    ctx.cached.p256_verify_base.clone().unwrap()
+}
+
+#[cfg(feature = "protocol_feature_secp256r1")]
+fn p256_verify_byte(ctx: &mut EstimatorContext) -> GasCost {
+    let base = p256_verify_base(ctx);
+    // inside the WASM function, there are 64 calls to `p256_verify`.
+    let base_call_num = 64;
+    // each call checks a message of size 16kiB
+    let iteration_bytes = 16384;
+    let total_bytes = base_call_num * iteration_bytes;
+    let byte = fn_cost(ctx, "p256_verify_16kib_64", ExtCosts::p256_verify_byte, total_bytes);
+    // need to subtract the base cost, which has already been divided by the number of bytes per iteration
+    byte - base / iteration_bytes
+}
+
 fn alt_bn128g1_multiexp_base(ctx: &mut EstimatorContext) -> GasCost {
     fn_cost(ctx, "alt_bn128_g1_multiexp_1_10", ExtCosts::alt_bn128_g1_multiexp_base, 10)
 }
@@ -979,6 +1041,106 @@ fn alt_bn128_pairing_check_element(ctx: &mut EstimatorContext) -> GasCost {
     )
 }
 
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_p1_sum_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_p1_sum_1_100", ExtCosts::bls12381_p1_sum_base, 100)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_p1_sum_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_p1_sum_10_100", ExtCosts::bls12381_p1_sum_element, 10 * 100)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_p2_sum_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_p2_sum_1_100", ExtCosts::bls12381_p2_sum_base, 100)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_p2_sum_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_p2_sum_10_100", ExtCosts::bls12381_p2_sum_element, 10 * 100)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_g1_multiexp_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_g1_multiexp_1_10", ExtCosts::bls12381_g1_multiexp_base, 10)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_g1_multiexp_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_g1_multiexp_10_10", ExtCosts::bls12381_g1_multiexp_element, 10 * 10)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_g2_multiexp_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_g2_multiexp_1_10", ExtCosts::bls12381_g2_multiexp_base, 10)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_g2_multiexp_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_g2_multiexp_10_10", ExtCosts::bls12381_g2_multiexp_element, 10 * 10)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_map_fp_to_g1_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_map_fp_to_g1_1_10", ExtCosts::bls12381_map_fp_to_g1_base, 10)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_map_fp_to_g1_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_map_fp_to_g1_10_10", ExtCosts::bls12381_map_fp_to_g1_element, 10 * 10)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_map_fp2_to_g2_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_map_fp2_to_g2_1_10", ExtCosts::bls12381_map_fp2_to_g2_base, 10)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_map_fp2_to_g2_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_map_fp2_to_g2_10_10", ExtCosts::bls12381_map_fp2_to_g2_element, 10 * 10)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_pairing_check_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_pairing_check_1_10", ExtCosts::bls12381_pairing_base, 10)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_pairing_check_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_pairing_check_10_10", ExtCosts::bls12381_pairing_element, 10 * 10)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_p1_decompress_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_p1_decompress_1_100", ExtCosts::bls12381_p1_decompress_base, 100)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_p1_decompress_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(
+        ctx,
+        "bls12381_p1_decompress_10_100",
+        ExtCosts::bls12381_p1_decompress_element,
+        10 * 100,
+    )
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_p2_decompress_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_p2_decompress_1_100", ExtCosts::bls12381_p2_decompress_base, 100)
+}
+
+#[cfg(feature = "protocol_feature_bls12381")]
+fn bls12381_p2_decompress_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(
+        ctx,
+        "bls12381_p2_decompress_10_100",
+        ExtCosts::bls12381_p2_decompress_element,
+        10 * 100,
+    )
+}
+
 fn storage_has_key_base(ctx: &mut EstimatorContext) -> GasCost {
     fn_cost_with_setup(
         ctx,
@@ -4,12 +4,14 @@ use anyhow::Context;
 use clap::Parser;
 use genesis_populate::GenesisBuilder;
 use near_chain_configs::GenesisValidationMode;
+use near_primitives::runtime::config_store::RuntimeConfigStore;
 use near_primitives::version::PROTOCOL_VERSION;
 use near_vm_runner::internal::VMKind;
 use replay::ReplayCmd;
 use runtime_params_estimator::config::{Config, GasMetric};
 use runtime_params_estimator::{
-    costs_to_runtime_config, CostTable, QemuCommandBuilder, RocksDBTestConfig,
+    costs_to_runtime_config, runtime_config_to_costs, CostTable, QemuCommandBuilder,
+    RocksDBTestConfig,
 };
 use std::env;
 use std::fmt::Write;
@@ -85,6 +87,11 @@ struct CliArgs {
     /// object per estimation.
     #[clap(long)]
     json_output: bool,
+    /// Print the protocol parameter values of the current protocol version in
+    /// JSON format, one line per estimation they correspond to, and exit.
+    /// Import the output with `estimator-warehouse import --protocol-version`.
+    #[clap(long)]
+    json_parameters: bool,
     /// Prints hierarchical execution-timing information using the tracing-span-tree crate.
     #[clap(long)]
     tracing_span_tree: bool,
@@ -117,6 +124,15 @@ fn main() -> anyhow::Result<()> {
         };
     }
 
+    if cli_args.json_parameters {
+        let config_store = RuntimeConfigStore::new(None);
+        let parameters = runtime_config_to_costs(config_store.get_config(PROTOCOL_VERSION));
+        for (cost, gas) in parameters.iter() {
+            println!("{}", serde_json::json!({ "name": cost.to_string(), "parameter_gas": gas }));
+        }
+        return Ok(());
+    }
+
     let temp_dir;
     let state_dump_path = match cli_args.home {
         Some(it) => it,
@@ -1,7 +1,9 @@
 use genesis_populate::state_dump::StateDump;
+use near_primitives::congestion_info::BlockCongestionInfo;
 use near_primitives::receipt::Receipt;
 use near_primitives::runtime::config_store::RuntimeConfigStore;
 use near_primitives::runtime::migration_data::{MigrationData, MigrationFlags};
+use near_primitives::shard_layout::ShardLayout;
 use near_primitives::test_utils::MockEpochInfoProvider;
 use near_primitives::transaction::{ExecutionStatus, SignedTransaction};
 use near_primitives::types::{Gas, MerkleHash};
@@ -89,6 +91,8 @@ impl RuntimeTestbed {
             is_new_chunk: true,
             migration_data: Arc::new(MigrationData::default()),
             migration_flags: MigrationFlags::default(),
+            congestion_info: BlockCongestionInfo::default(),
+            shard_layout: ShardLayout::v0_single_shard(),
         };
 
         Self {
@@ -1,5 +1,9 @@
-use crate::config::{safe_add_gas, RuntimeConfig};
+use crate::config::{
+    safe_add_compute, safe_add_gas, total_prepaid_exec_fees, total_prepaid_gas,
+    total_prepaid_send_fees, RuntimeConfig,
+};
 use crate::ext::{ExternalError, RuntimeExt};
+use crate::verifier::check_and_count_access_key_use;
 use crate::{metrics, ActionResult, ApplyState};
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::PublicKey;
@@ -7,25 +11,25 @@ use near_primitives::account::{AccessKey, AccessKeyPermission, Account};
 use near_primitives::checked_feature;
 use near_primitives::config::ViewConfig;
 use near_primitives::contract::ContractCode;
-use near_primitives::errors::{ActionError, ActionErrorKind, RuntimeError};
+use near_primitives::errors::{ActionError, ActionErrorKind, InvalidAccessKeyError, RuntimeError};
 use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::{ActionReceipt, Receipt, ReceiptEnum};
 use near_primitives::runtime::config::AccountCreationConfig;
 use near_primitives::runtime::fees::RuntimeFeesConfig;
 use near_primitives::transaction::{
-    Action, AddKeyAction, DeleteAccountAction, DeleteKeyAction, DeployContractAction,
-    FunctionCallAction, StakeAction, TransferAction,
+    Action, AddKeyAction, DelegateAction, DeleteAccountAction, DeleteKeyAction,
+    DeployContractAction, FunctionCallAction, SignedDelegateAction, StakeAction, TransferAction,
 };
 use near_primitives::types::validator_stake::ValidatorStake;
-use near_primitives::types::{AccountId, BlockHeight, EpochInfoProvider, TrieCacheMode};
+use near_primitives::types::{AccountId, BlockHeight, EpochInfoProvider, Gas, TrieCacheMode};
 use near_primitives::utils::create_random_seed;
 use near_primitives::version::{
     is_implicit_account_creation_enabled, ProtocolFeature, ProtocolVersion,
     DELETE_KEY_STORAGE_USAGE_PROTOCOL_VERSION,
 };
 use near_store::{
-    get_access_key, get_code, remove_access_key, remove_account, set_access_key, set_code,
-    StorageError, TrieUpdate,
+    get_access_key, get_code, remove_access_key, remove_account, remove_code, set_access_key,
+    set_code, set_code_by_hash, StorageError, TrieUpdate,
 };
 use near_vm_errors::{
     CompilationError, FunctionCallError, FunctionCallErrorSer, InconsistentStateError,
@@ -245,6 +249,7 @@ pub(crate) fn action_function_call(
     result.gas_burnt = safe_add_gas(result.gas_burnt, outcome.burnt_gas)?;
     result.gas_burnt_for_function_call =
         safe_add_gas(result.gas_burnt_for_function_call, outcome.burnt_gas)?;
+    result.compute_usage = safe_add_compute(result.compute_usage, outcome.compute_usage)?;
     // Runtime in `generate_refund_receipts` takes care of using proper value for refunds.
     // It uses `gas_used` for success and `gas_burnt` for failures. So it's not an issue to
     // return a real `gas_used` instead of the `gas_burnt` into `ActionResult` even for
@@ -344,8 +349,7 @@ pub(crate) fn try_refund_allowance(
 ) -> Result<(), StorageError> {
     if let Some(mut access_key) = get_access_key(state_update, account_id, public_key)? {
         let mut updated = false;
-        if let AccessKeyPermission::FunctionCall(function_call_permission) =
-            &mut access_key.permission
+        if let Some(function_call_permission) = access_key.permission.function_call_permission_mut()
         {
             if let Some(allowance) = function_call_permission.allowance.as_mut() {
                 let new_allowance = allowance.saturating_add(transfer.deposit);
@@ -485,8 +489,16 @@ pub(crate) fn action_deploy_contract(
             ))
         })?,
     );
+    if checked_feature!(
+        "protocol_feature_contract_code_dedup",
+        ContractCodeDeduplication,
+        current_protocol_version
+    ) {
+        set_code_by_hash(state_update, account_id, account.code_hash(), &code)?;
+    } else {
+        set_code(state_update, account_id.clone(), &code);
+    }
     account.set_code_hash(*code.hash());
-    set_code(state_update, account_id.clone(), &code);
     // Precompile the contract and store result (compiled code or error) in the database.
     // Note, that contract compilation costs are already accounted in deploy cost using
     // special logic in estimator (see get_runtime_config() function).
@@ -535,6 +547,13 @@ pub(crate) fn action_delete_account(
             .new_receipts
             .push(Receipt::new_balance_refund(&delete_account.beneficiary_id, account_balance));
     }
+    if checked_feature!(
+        "protocol_feature_contract_code_dedup",
+        ContractCodeDeduplication,
+        current_protocol_version
+    ) {
+        remove_code(state_update, account_id, account.as_ref().unwrap().code_hash())?;
+    }
     remove_account(state_update, account_id)?;
     *actor_id = receipt.predecessor_id.clone();
     *account = None;
@@ -592,19 +611,19 @@ pub(crate) fn action_add_key(
         .into());
         return Ok(());
     }
+    let mut access_key = add_key.access_key.clone();
     if checked_feature!("stable", AccessKeyNonceRange, apply_state.current_protocol_version) {
-        let mut access_key = add_key.access_key.clone();
         access_key.nonce = (apply_state.block_height - 1)
             * near_primitives::account::AccessKey::ACCESS_KEY_NONCE_RANGE_MULTIPLIER;
-        set_access_key(state_update, account_id.clone(), add_key.public_key.clone(), &access_key);
-    } else {
-        set_access_key(
-            state_update,
-            account_id.clone(),
-            add_key.public_key.clone(),
-            &add_key.access_key,
-        );
-    };
+    }
+    // The usage counter of a new key always starts from zero.
+    if let AccessKeyPermission::FunctionCallV2(permission) = &mut access_key.permission {
+        if let Some(usage_limit) = &mut permission.usage_limit {
+            usage_limit.epoch_height = apply_state.epoch_height;
+            usage_limit.uses = 0;
+        }
+    }
+    set_access_key(state_update, account_id.clone(), add_key.public_key.clone(), &access_key);
     let storage_config = &apply_state.config.transaction_costs.storage_usage_config;
     account.set_storage_usage(
         account
@@ -624,6 +643,219 @@ pub(crate) fn action_add_key(
     Ok(())
 }
 
+/// Applies a delegate action signed by `sender_id`: checks the signature, the
+/// expiration and the access key of the sender, and then creates a new receipt
+/// with the inner actions which is paid for by the signer of `action_receipt`
+/// (the relayer).
+pub(crate) fn apply_delegate_action(
+    state_update: &mut TrieUpdate,
+    apply_state: &ApplyState,
+    action_receipt: &ActionReceipt,
+    sender_id: &AccountId,
+    signed_delegate_action: &SignedDelegateAction,
+    result: &mut ActionResult,
+) -> Result<(), RuntimeError> {
+    let delegate_action = &signed_delegate_action.delegate_action;
+
+    if !signed_delegate_action.verify() {
+        result.result = Err(ActionErrorKind::DelegateActionInvalidSignature.into());
+        return Ok(());
+    }
+    if apply_state.block_height > delegate_action.max_block_height {
+        result.result = Err(ActionErrorKind::DelegateActionExpired.into());
+        return Ok(());
+    }
+    if &delegate_action.sender_id != sender_id {
+        result.result = Err(ActionErrorKind::DelegateActionSenderDoesNotMatchTxReceiver {
+            sender_id: delegate_action.sender_id.clone(),
+            receiver_id: sender_id.clone(),
+        }
+        .into());
+        return Ok(());
+    }
+
+    validate_delegate_action_key(state_update, apply_state, delegate_action, result)?;
+    if result.result.is_err() {
+        return Ok(());
+    }
+
+    let actions = delegate_action.get_actions();
+    let fees_config = &apply_state.config.transaction_costs;
+    // The relayer has prepaid the fees and the attached gas and deposits of
+    // the inner actions. Deposits are refunded to the predecessor, i.e. the
+    // sender of the delegate action, if the new receipt fails. Gas is refunded
+    // to the relayer as the signer of the new receipt.
+    let new_receipt = Receipt {
+        predecessor_id: sender_id.clone(),
+        receiver_id: delegate_action.receiver_id.clone(),
+        receipt_id: CryptoHash::default(),
+        receipt: ReceiptEnum::Action(ActionReceipt {
+            signer_id: action_receipt.signer_id.clone(),
+            signer_public_key: action_receipt.signer_public_key.clone(),
+            gas_price: action_receipt.gas_price,
+            output_data_receivers: vec![],
+            input_data_ids: vec![],
+            actions,
+        }),
+    };
+    let prepaid_send_fees = total_prepaid_send_fees(
+        fees_config,
+        delegate_action,
+        apply_state.current_protocol_version,
+    )?;
+    let required_gas = receipt_required_gas(apply_state, &new_receipt)?;
+    // The gas required by the new receipt is used here and burnt by its
+    // receiver, so it must not be refunded.
+    result.gas_used = safe_add_gas(result.gas_used, required_gas)?;
+    // The send fees of the inner actions are burnt right away as the new
+    // receipt is sent.
+    result.gas_used = safe_add_gas(result.gas_used, prepaid_send_fees)?;
+    result.gas_burnt = safe_add_gas(result.gas_burnt, prepaid_send_fees)?;
+    let prepaid_send_compute = total_prepaid_send_fees(
+        &fees_config.compute_fees(),
+        delegate_action,
+        apply_state.current_protocol_version,
+    )?;
+    result.compute_usage = safe_add_compute(result.compute_usage, prepaid_send_compute)?;
+    result.new_receipts.push(new_receipt);
+
+    Ok(())
+}
+
+/// Returns the amount of gas the given receipt needs to be executed: the
+/// execution fees of the receipt and its actions plus the attached gas.
+pub(crate) fn receipt_required_gas(
+    apply_state: &ApplyState,
+    receipt: &Receipt,
+) -> Result<Gas, RuntimeError> {
+    Ok(match &receipt.receipt {
+        ReceiptEnum::Action(action_receipt) => {
+            let fees_config = &apply_state.config.transaction_costs;
+            let mut required_gas = safe_add_gas(
+                total_prepaid_exec_fees(
+                    fees_config,
+                    &action_receipt.actions,
+                    &receipt.receiver_id,
+                    apply_state.current_protocol_version,
+                )?,
+                total_prepaid_gas(&action_receipt.actions)?,
+            )?;
+            required_gas =
+                safe_add_gas(required_gas, fees_config.action_receipt_creation_config.exec_fee())?;
+            required_gas
+        }
+        ReceiptEnum::Data(_) => 0,
+    })
+}
+
+/// Checks the access key which signed the delegate action and updates its
+/// nonce. Function call access keys may only be used for a single function
+/// call without a deposit, the same as for transactions, and `FunctionCallV2`
+/// keys must neither be expired nor over their usage limit. Allowance is not
+/// charged because the relayer pays for the gas.
+fn validate_delegate_action_key(
+    state_update: &mut TrieUpdate,
+    apply_state: &ApplyState,
+    delegate_action: &DelegateAction,
+    result: &mut ActionResult,
+) -> Result<(), RuntimeError> {
+    // The existence of the sender account is checked by the caller.
+    let mut access_key = match get_access_key(
+        state_update,
+        &delegate_action.sender_id,
+        &delegate_action.public_key,
+    )? {
+        Some(access_key) => access_key,
+        None => {
+            result.result = Err(ActionErrorKind::DelegateActionAccessKeyError(
+                InvalidAccessKeyError::AccessKeyNotFound {
+                    account_id: delegate_action.sender_id.clone(),
+                    public_key: delegate_action.public_key.clone(),
+                },
+            )
+            .into());
+            return Ok(());
+        }
+    };
+
+    if delegate_action.nonce <= access_key.nonce {
+        result.result = Err(ActionErrorKind::DelegateActionInvalidNonce {
+            delegate_nonce: delegate_action.nonce,
+            ak_nonce: access_key.nonce,
+        }
+        .into());
+        return Ok(());
+    }
+    let upper_bound = apply_state.block_height * AccessKey::ACCESS_KEY_NONCE_RANGE_MULTIPLIER;
+    if delegate_action.nonce >= upper_bound {
+        result.result = Err(ActionErrorKind::DelegateActionNonceTooLarge {
+            delegate_nonce: delegate_action.nonce,
+            upper_bound,
+        }
+        .into());
+        return Ok(());
+    }
+    access_key.nonce = delegate_action.nonce;
+
+    if let AccessKeyPermission::FunctionCallV2(ref mut permission) = access_key.permission {
+        if let Err(access_key_error) = check_and_count_access_key_use(
+            permission,
+            apply_state.block_height,
+            apply_state.block_timestamp,
+            apply_state.epoch_height,
+        ) {
+            result.result =
+                Err(ActionErrorKind::DelegateActionAccessKeyError(access_key_error).into());
+            return Ok(());
+        }
+    }
+
+    if let Some(function_call_permission) = access_key.permission.function_call_permission() {
+        let access_key_error = match delegate_action.actions.as_slice() {
+            [action] => match Action::from(action.clone()) {
+                Action::FunctionCall(function_call) => {
+                    if function_call.deposit > 0 {
+                        Some(InvalidAccessKeyError::DepositWithFunctionCall)
+                    } else if delegate_action.receiver_id.as_ref()
+                        != function_call_permission.receiver_id
+                    {
+                        Some(InvalidAccessKeyError::ReceiverMismatch {
+                            tx_receiver: delegate_action.receiver_id.clone(),
+                            ak_receiver: function_call_permission.receiver_id.clone(),
+                        })
+                    } else if !function_call_permission.method_names.is_empty()
+                        && function_call_permission
+                            .method_names
+                            .iter()
+                            .all(|method_name| &function_call.method_name != method_name)
+                    {
+                        Some(InvalidAccessKeyError::MethodNameMismatch {
+                            method_name: function_call.method_name,
+                        })
+                    } else {
+                        None
+                    }
+                }
+                _ => Some(InvalidAccessKeyError::RequiresFullAccess),
+            },
+            _ => Some(InvalidAccessKeyError::RequiresFullAccess),
+        };
+        if let Some(access_key_error) = access_key_error {
+            result.result =
+                Err(ActionErrorKind::DelegateActionAccessKeyError(access_key_error).into());
+            return Ok(());
+        }
+    }
+
+    set_access_key(
+        state_update,
+        delegate_action.sender_id.clone(),
+        delegate_action.public_key.clone(),
+        &access_key,
+    );
+    Ok(())
+}
+
 pub(crate) fn check_actor_permissions(
     action: &Action,
     account: &Option<Account>,
@@ -656,7 +888,10 @@ pub(crate) fn check_actor_permissions(
                 .into());
             }
         }
-        Action::CreateAccount(_) | Action::FunctionCall(_) | Action::Transfer(_) => (),
+        Action::CreateAccount(_)
+        | Action::FunctionCall(_)
+        | Action::Transfer(_)
+        | Action::Delegate(_) => (),
     };
     Ok(())
 }
@@ -723,7 +958,8 @@ pub(crate) fn check_account_existence(
         | Action::Stake(_)
         | Action::AddKey(_)
         | Action::DeleteKey(_)
-        | Action::DeleteAccount(_) => {
+        | Action::DeleteAccount(_)
+        | Action::Delegate(_) => {
             if account.is_none() {
                 return Err(ActionErrorKind::AccountDoesNotExist {
                     account_id: account_id.clone(),
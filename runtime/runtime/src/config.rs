@@ -4,16 +4,15 @@ use num_bigint::BigUint;
 use num_traits::cast::ToPrimitive;
 use num_traits::pow::Pow;
 
-use near_primitives::account::AccessKeyPermission;
 use near_primitives::errors::IntegerOverflowError;
 // Just re-exporting RuntimeConfig for backwards compatibility.
 pub use near_primitives::num_rational::Rational;
 pub use near_primitives::runtime::config::RuntimeConfig;
 use near_primitives::runtime::fees::{transfer_exec_fee, transfer_send_fee, RuntimeFeesConfig};
 use near_primitives::transaction::{
-    Action, AddKeyAction, DeployContractAction, FunctionCallAction, Transaction,
+    Action, AddKeyAction, DelegateAction, DeployContractAction, FunctionCallAction, Transaction,
 };
-use near_primitives::types::{AccountId, Balance, Gas};
+use near_primitives::types::{AccountId, Balance, Compute, Gas};
 use near_primitives::version::{is_implicit_account_creation_enabled, ProtocolVersion};
 
 /// Describes the cost of converting this transaction into a receipt.
@@ -21,6 +20,8 @@ use near_primitives::version::{is_implicit_account_creation_enabled, ProtocolVer
 pub struct TransactionCost {
     /// Total amount of gas burnt for converting this transaction into a receipt.
     pub gas_burnt: Gas,
+    /// Compute usage of converting this transaction into a receipt.
+    pub compute_usage: Compute,
     /// The remaining amount of gas used for converting this transaction into a receipt.
     /// It includes gas that is not yet spent, e.g. prepaid gas for function calls and
     /// future execution fees.
@@ -54,6 +55,10 @@ pub fn safe_add_gas(a: Gas, b: Gas) -> Result<Gas, IntegerOverflowError> {
     a.checked_add(b).ok_or_else(|| IntegerOverflowError {})
 }
 
+pub fn safe_add_compute(a: Compute, b: Compute) -> Result<Compute, IntegerOverflowError> {
+    a.checked_add(b).ok_or_else(|| IntegerOverflowError {})
+}
+
 pub fn safe_add_balance(a: Balance, b: Balance) -> Result<Balance, IntegerOverflowError> {
     a.checked_add(b).ok_or_else(|| IntegerOverflowError {})
 }
@@ -99,27 +104,31 @@ pub fn total_send_fees(
                 transfer_send_fee(cfg, sender_is_receiver, is_receiver_implicit)
             }
             Stake(_) => cfg.stake_cost.send_fee(sender_is_receiver),
-            AddKey(AddKeyAction { access_key, .. }) => match &access_key.permission {
-                AccessKeyPermission::FunctionCall(call_perm) => {
-                    let num_bytes = call_perm
-                        .method_names
-                        .iter()
-                        // Account for null-terminating characters.
-                        .map(|name| name.as_bytes().len() as u64 + 1)
-                        .sum::<u64>();
-                    cfg.add_key_cost.function_call_cost.send_fee(sender_is_receiver)
-                        + num_bytes
-                            * cfg
-                                .add_key_cost
-                                .function_call_cost_per_byte
-                                .send_fee(sender_is_receiver)
+            AddKey(AddKeyAction { access_key, .. }) => {
+                match access_key.permission.function_call_permission() {
+                    Some(call_perm) => {
+                        let num_bytes = call_perm
+                            .method_names
+                            .iter()
+                            // Account for null-terminating characters.
+                            .map(|name| name.as_bytes().len() as u64 + 1)
+                            .sum::<u64>();
+                        cfg.add_key_cost.function_call_cost.send_fee(sender_is_receiver)
+                            + num_bytes
+                                * cfg
+                                    .add_key_cost
+                                    .function_call_cost_per_byte
+                                    .send_fee(sender_is_receiver)
+                    }
+                    None => cfg.add_key_cost.full_access_cost.send_fee(sender_is_receiver),
                 }
-                AccessKeyPermission::FullAccess => {
-                    cfg.add_key_cost.full_access_cost.send_fee(sender_is_receiver)
-                }
-            },
+            }
             DeleteKey(_) => cfg.delete_key_cost.send_fee(sender_is_receiver),
             DeleteAccount(_) => cfg.delete_account_cost.send_fee(sender_is_receiver),
+            // The send fees of the inner actions are prepaid, see
+            // `total_prepaid_exec_fees`, and burnt once the delegate action is
+            // applied.
+            Delegate(_) => cfg.delegate_cost.send_fee(sender_is_receiver),
         };
         result = safe_add_gas(result, delta)?;
     }
@@ -155,21 +164,24 @@ pub fn exec_fee(
             transfer_exec_fee(cfg, is_receiver_implicit)
         }
         Stake(_) => cfg.stake_cost.exec_fee(),
-        AddKey(AddKeyAction { access_key, .. }) => match &access_key.permission {
-            AccessKeyPermission::FunctionCall(call_perm) => {
-                let num_bytes = call_perm
-                    .method_names
-                    .iter()
-                    // Account for null-terminating characters.
-                    .map(|name| name.as_bytes().len() as u64 + 1)
-                    .sum::<u64>();
-                cfg.add_key_cost.function_call_cost.exec_fee()
-                    + num_bytes * cfg.add_key_cost.function_call_cost_per_byte.exec_fee()
+        AddKey(AddKeyAction { access_key, .. }) => {
+            match access_key.permission.function_call_permission() {
+                Some(call_perm) => {
+                    let num_bytes = call_perm
+                        .method_names
+                        .iter()
+                        // Account for null-terminating characters.
+                        .map(|name| name.as_bytes().len() as u64 + 1)
+                        .sum::<u64>();
+                    cfg.add_key_cost.function_call_cost.exec_fee()
+                        + num_bytes * cfg.add_key_cost.function_call_cost_per_byte.exec_fee()
+                }
+                None => cfg.add_key_cost.full_access_cost.exec_fee(),
             }
-            AccessKeyPermission::FullAccess => cfg.add_key_cost.full_access_cost.exec_fee(),
-        },
+        }
         DeleteKey(_) => cfg.delete_key_cost.exec_fee(),
         DeleteAccount(_) => cfg.delete_account_cost.exec_fee(),
+        Delegate(_) => cfg.delegate_cost.exec_fee(),
     }
 }
 
@@ -181,17 +193,20 @@ pub fn tx_cost(
     sender_is_receiver: bool,
     current_protocol_version: ProtocolVersion,
 ) -> Result<TransactionCost, IntegerOverflowError> {
-    let mut gas_burnt: Gas = config.action_receipt_creation_config.send_fee(sender_is_receiver);
-    gas_burnt = safe_add_gas(
-        gas_burnt,
-        total_send_fees(
-            config,
-            sender_is_receiver,
-            &transaction.actions,
-            &transaction.receiver_id,
-            current_protocol_version,
-        )?,
-    )?;
+    let send_fees = |config: &RuntimeFeesConfig| -> Result<Gas, IntegerOverflowError> {
+        safe_add_gas(
+            config.action_receipt_creation_config.send_fee(sender_is_receiver),
+            total_send_fees(
+                config,
+                sender_is_receiver,
+                &transaction.actions,
+                &transaction.receiver_id,
+                current_protocol_version,
+            )?,
+        )
+    };
+    let gas_burnt = send_fees(config)?;
+    let compute_usage = send_fees(&config.compute_fees())?;
     let prepaid_gas = total_prepaid_gas(&transaction.actions)?;
     // If signer is equals to receiver the receipt will be processed at the same block as this
     // transaction. Otherwise it will processed in the next block and the gas might be inflated.
@@ -227,10 +242,21 @@ pub fn tx_cost(
     let remaining_gas_amount = safe_gas_to_balance(receipt_gas_price, gas_remaining)?;
     let mut total_cost = safe_add_balance(burnt_amount, remaining_gas_amount)?;
     total_cost = safe_add_balance(total_cost, total_deposit(&transaction.actions)?)?;
-    Ok(TransactionCost { gas_burnt, gas_remaining, receipt_gas_price, total_cost, burnt_amount })
+    Ok(TransactionCost {
+        gas_burnt,
+        compute_usage,
+        gas_remaining,
+        receipt_gas_price,
+        total_cost,
+        burnt_amount,
+    })
 }
 
 /// Total sum of gas that would need to be burnt before we start executing the given actions.
+///
+/// For a delegate action this also covers the receipt it produces: the send
+/// fees of the inner actions, the creation of the new action receipt and the
+/// execution fees of the inner actions.
 pub fn total_prepaid_exec_fees(
     config: &RuntimeFeesConfig,
     actions: &[Action],
@@ -239,23 +265,74 @@ pub fn total_prepaid_exec_fees(
 ) -> Result<Gas, IntegerOverflowError> {
     let mut result = 0;
     for action in actions {
-        let delta = exec_fee(config, action, receiver_id, current_protocol_version);
+        let mut delta = exec_fee(config, action, receiver_id, current_protocol_version);
+        if let Action::Delegate(signed_delegate_action) = action {
+            let delegate_action = &signed_delegate_action.delegate_action;
+            let inner_actions = delegate_action.get_actions();
+            delta = safe_add_gas(
+                delta,
+                total_prepaid_send_fees(config, delegate_action, current_protocol_version)?,
+            )?;
+            delta = safe_add_gas(delta, config.action_receipt_creation_config.exec_fee())?;
+            delta = safe_add_gas(
+                delta,
+                total_prepaid_exec_fees(
+                    config,
+                    &inner_actions,
+                    &delegate_action.receiver_id,
+                    current_protocol_version,
+                )?,
+            )?;
+        }
         result = safe_add_gas(result, delta)?;
     }
     Ok(result)
 }
+
+/// Send fees of the actions inside of a delegate action. They are burnt when
+/// the delegate action produces the receipt with these actions.
+pub fn total_prepaid_send_fees(
+    config: &RuntimeFeesConfig,
+    delegate_action: &DelegateAction,
+    current_protocol_version: ProtocolVersion,
+) -> Result<Gas, IntegerOverflowError> {
+    total_send_fees(
+        config,
+        delegate_action.sender_id == delegate_action.receiver_id,
+        &delegate_action.get_actions(),
+        &delegate_action.receiver_id,
+        current_protocol_version,
+    )
+}
+
 /// Get the total sum of deposits for given actions.
 pub fn total_deposit(actions: &[Action]) -> Result<Balance, IntegerOverflowError> {
     let mut total_balance: Balance = 0;
     for action in actions {
-        total_balance = safe_add_balance(total_balance, action.get_deposit_balance())?;
+        let action_balance = match action {
+            Action::Delegate(signed_delegate_action) => {
+                total_deposit(&signed_delegate_action.delegate_action.get_actions())?
+            }
+            _ => action.get_deposit_balance(),
+        };
+        total_balance = safe_add_balance(total_balance, action_balance)?;
     }
     Ok(total_balance)
 }
 
 /// Get the total sum of prepaid gas for given actions.
 pub fn total_prepaid_gas(actions: &[Action]) -> Result<Gas, IntegerOverflowError> {
-    actions.iter().try_fold(0, |acc, action| safe_add_gas(acc, action.get_prepaid_gas()))
+    let mut total_gas: Gas = 0;
+    for action in actions {
+        let action_gas = match action {
+            Action::Delegate(signed_delegate_action) => {
+                total_prepaid_gas(&signed_delegate_action.delegate_action.get_actions())?
+            }
+            _ => action.get_prepaid_gas(),
+        };
+        total_gas = safe_add_gas(total_gas, action_gas)?;
+    }
+    Ok(total_gas)
 }
 
 #[cfg(test)]
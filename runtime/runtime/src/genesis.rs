@@ -65,6 +65,7 @@ impl<'a> StorageComputer<'a> {
             StateRecord::PostponedReceipt(_) => None,
             StateRecord::ReceivedData { .. } => None,
             StateRecord::DelayedReceipt(_) => None,
+            StateRecord::BufferedReceipt { .. } => None,
         };
         if let Some((account_id, storage_usage)) = account_and_storage {
             *self.result.entry(account_id).or_default() += storage_usage;
@@ -155,7 +156,11 @@ impl GenesisStateApplier {
                         &ReceivedData { data },
                     );
                 }
-                StateRecord::DelayedReceipt(receipt) => {
+                // Records are grouped by the receiver of the receipt, so a
+                // buffered receipt lands in the receiving shard, where it is
+                // delayed until the shard can execute it.
+                StateRecord::DelayedReceipt(receipt)
+                | StateRecord::BufferedReceipt { receipt, .. } => {
                     Runtime::delay_receipt(
                         &mut state_update,
                         delayed_receipts_indices,
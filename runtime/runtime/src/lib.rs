@@ -1,12 +1,15 @@
 use std::cmp::max;
-use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Instant;
 
+use borsh::BorshSerialize;
 use near_primitives::sandbox::state_patch::SandboxStatePatch;
 use tracing::debug;
 
-use near_chain_configs::Genesis;
+use near_chain_configs::{Genesis, SimulateTransactionConfig};
 pub use near_crypto;
 use near_crypto::PublicKey;
 pub use near_primitives;
@@ -23,11 +26,16 @@ use near_primitives::version::{
 use near_primitives::{
     account::Account,
     checked_feature,
-    errors::{ActionError, ActionErrorKind, RuntimeError, TxExecutionError},
+    congestion_info::CongestionInfo,
+    errors::{
+        ActionError, ActionErrorKind, InvalidAccessKeyError, InvalidTxError, RuntimeError,
+        TxExecutionError,
+    },
     hash::CryptoHash,
     receipt::{
         ActionReceipt, DataReceipt, DelayedReceiptIndices, Receipt, ReceiptEnum, ReceivedData,
     },
+    shard_layout::{account_id_to_shard_id, ShardLayout, ShardUId},
     state_record::StateRecord,
     transaction::{
         Action, ExecutionOutcome, ExecutionOutcomeWithId, ExecutionStatus, LogEntry,
@@ -35,7 +43,7 @@ use near_primitives::{
     },
     trie_key::TrieKey,
     types::{
-        validator_stake::ValidatorStake, AccountId, Balance, EpochInfoProvider, Gas,
+        validator_stake::ValidatorStake, AccountId, Balance, Compute, EpochInfoProvider, Gas,
         RawStateChangesWithTrieKey, ShardId, StateChangeCause, StateRoot,
     },
     utils::{
@@ -55,13 +63,15 @@ pub use near_vm_runner::with_ext_cost_counter;
 use crate::actions::*;
 use crate::balance_checker::check_balance;
 use crate::config::{
-    exec_fee, safe_add_balance, safe_add_gas, safe_gas_to_balance, total_deposit,
+    exec_fee, safe_add_balance, safe_add_compute, safe_add_gas, safe_gas_to_balance, total_deposit,
     total_prepaid_exec_fees, total_prepaid_gas, RuntimeConfig,
 };
 use crate::genesis::{GenesisStateApplier, StorageComputer};
 use crate::prefetch::TriePrefetcher;
 use crate::verifier::validate_receipt;
-pub use crate::verifier::{validate_transaction, verify_and_charge_transaction};
+pub use crate::verifier::{
+    validate_transaction, verify_and_charge_transaction, TransactionBlockInfo,
+};
 
 mod actions;
 pub mod adapter;
@@ -95,6 +105,8 @@ pub struct ValidatorAccountsUpdate {
 pub struct VerificationResult {
     /// The amount gas that was burnt to convert the transaction into a receipt and send it.
     pub gas_burnt: Gas,
+    /// Compute usage of converting the transaction into a receipt and sending it.
+    pub compute_usage: Compute,
     /// The remaining amount of gas in the receipt.
     pub gas_remaining: Gas,
     /// The gas price at which the gas was purchased in the receipt.
@@ -123,6 +135,32 @@ pub struct ApplyResult {
     pub stats: ApplyStats,
     pub processed_delayed_receipts: Vec<Receipt>,
     pub proof: Option<PartialStorage>,
+    /// Congestion info of the shard after the chunk is applied, only set since
+    /// `ProtocolFeature::CongestionControl`.
+    pub congestion_info: Option<CongestionInfo>,
+}
+
+/// Result of [`Runtime::simulate_transaction`].
+#[derive(Debug)]
+pub struct SimulationResult {
+    /// Outcome of the transaction followed by the outcomes of all the receipts
+    /// it produced, in the order they were executed.
+    pub outcomes: Vec<ExecutionOutcomeWithId>,
+    /// All the receipts produced by the transaction, including the one the
+    /// transaction was converted to, in the order they were processed.
+    pub receipts: Vec<Receipt>,
+    /// Set if the simulation was stopped before all the receipts were
+    /// processed.
+    pub limit_exceeded: Option<SimulationLimitExceeded>,
+}
+
+/// Limit of [`SimulateTransactionConfig`] which stopped a simulation.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum SimulationLimitExceeded {
+    #[error("simulation burnt more than {limit} gas")]
+    GasBurnt { limit: Gas },
+    #[error("simulation took longer than {timeout:?}")]
+    Timeout { timeout: std::time::Duration },
 }
 
 #[derive(Debug)]
@@ -130,6 +168,7 @@ pub struct ActionResult {
     pub gas_burnt: Gas,
     pub gas_burnt_for_function_call: Gas,
     pub gas_used: Gas,
+    pub compute_usage: Compute,
     pub result: Result<ReturnData, ActionError>,
     pub logs: Vec<LogEntry>,
     pub new_receipts: Vec<Receipt>,
@@ -152,6 +191,7 @@ impl ActionResult {
             next_result.gas_burnt_for_function_call,
         )?;
         self.gas_used = safe_add_gas(self.gas_used, next_result.gas_used)?;
+        self.compute_usage = safe_add_compute(self.compute_usage, next_result.compute_usage)?;
         self.profile.merge(&next_result.profile);
         self.result = next_result.result;
         self.logs.append(&mut next_result.logs);
@@ -176,6 +216,7 @@ impl Default for ActionResult {
             gas_burnt: 0,
             gas_burnt_for_function_call: 0,
             gas_used: 0,
+            compute_usage: 0,
             result: Ok(ReturnData::None),
             logs: vec![],
             new_receipts: vec![],
@@ -220,6 +261,7 @@ impl Runtime {
         state_update: &mut TrieUpdate,
         apply_state: &ApplyState,
         signed_transaction: &SignedTransaction,
+        verify_signature: bool,
         stats: &mut ApplyStats,
     ) -> Result<(Receipt, ExecutionOutcomeWithId), RuntimeError> {
         let _span = tracing::debug_span!(target: "runtime", "process_transaction", tx_hash = %signed_transaction.get_hash()).entered();
@@ -230,8 +272,12 @@ impl Runtime {
             state_update,
             apply_state.gas_price,
             signed_transaction,
-            true,
-            Some(apply_state.block_height),
+            verify_signature,
+            Some(TransactionBlockInfo {
+                height: apply_state.block_height,
+                timestamp: apply_state.block_timestamp,
+                epoch_height: apply_state.epoch_height,
+            }),
             apply_state.current_protocol_version,
         ) {
             Ok(verification_result) => {
@@ -268,6 +314,7 @@ impl Runtime {
                         logs: vec![],
                         receipt_ids: vec![receipt.receipt_id],
                         gas_burnt: verification_result.gas_burnt,
+                        compute_usage: Some(verification_result.compute_usage),
                         tokens_burnt: verification_result.burnt_amount,
                         executor_id: transaction.signer_id.clone(),
                         // TODO: profile data is only counted in apply_action, which only happened at process_receipt
@@ -310,6 +357,12 @@ impl Runtime {
         );
         result.gas_burnt += exec_fees;
         result.gas_used += exec_fees;
+        result.compute_usage += exec_fee(
+            &apply_state.config.transaction_costs.compute_fees(),
+            action,
+            &receipt.receiver_id,
+            apply_state.current_protocol_version,
+        );
         let account_id = &receipt.receiver_id;
         let is_the_only_action = actions.len() == 1;
         let is_refund = AccountId::is_system(&receipt.predecessor_id);
@@ -443,6 +496,16 @@ impl Runtime {
                     apply_state.current_protocol_version,
                 )?;
             }
+            Action::Delegate(signed_delegate_action) => {
+                apply_delegate_action(
+                    state_update,
+                    apply_state,
+                    action_receipt,
+                    account_id,
+                    signed_delegate_action,
+                    &mut result,
+                )?;
+            }
         };
         Ok(result)
     }
@@ -494,10 +557,12 @@ impl Runtime {
         let mut account = get_account(state_update, account_id)?;
         let mut actor_id = receipt.predecessor_id.clone();
         let mut result = ActionResult::default();
-        let exec_fee =
-            apply_state.config.transaction_costs.action_receipt_creation_config.exec_fee();
+        let transaction_costs = &apply_state.config.transaction_costs;
+        let exec_fee = transaction_costs.action_receipt_creation_config.exec_fee();
         result.gas_used = exec_fee;
         result.gas_burnt = exec_fee;
+        result.compute_usage =
+            transaction_costs.compute_fees().action_receipt_creation_config.exec_fee();
         // Executing actions one by one
         for (action_index, action) in action_receipt.actions.iter().enumerate() {
             let action_hash = create_action_hash(
@@ -523,7 +588,11 @@ impl Runtime {
             )?;
             if new_result.result.is_ok() {
                 if let Err(e) = new_result.new_receipts.iter().try_for_each(|receipt| {
-                    validate_receipt(&apply_state.config.wasm_config.limit_config, receipt)
+                    validate_receipt(
+                        &apply_state.config.wasm_config.limit_config,
+                        receipt,
+                        apply_state.current_protocol_version,
+                    )
                 }) {
                     new_result.result = Err(ActionErrorKind::NewReceiptValidationError(e).into());
                 }
@@ -569,6 +638,7 @@ impl Runtime {
             ) {
                 result.gas_burnt = 0;
                 result.gas_used = 0;
+                result.compute_usage = 0;
             }
 
             // If the refund fails tokens are burned.
@@ -731,6 +801,7 @@ impl Runtime {
                 logs: result.logs,
                 receipt_ids,
                 gas_burnt: result.gas_burnt,
+                compute_usage: Some(result.compute_usage),
                 tokens_burnt,
                 executor_id: account_id.clone(),
                 metadata: ExecutionMetadata::V2(result.profile),
@@ -1138,7 +1209,9 @@ impl Runtime {
     /// given trie and the given state root.
     /// If the validator accounts update is provided, updates validators accounts.
     /// All new signed transactions should be valid and already verified by the chunk producer.
-    /// If any transaction is invalid, it would return an `InvalidTxError`.
+    /// If any transaction is invalid, it would return an `InvalidTxError`, except for
+    /// transactions signed with an access key that has expired by this block, which are
+    /// dropped.
     /// Returns an `ApplyResult` that contains the new state root, trie changes,
     /// new outgoing receipts, execution outcomes for
     /// all transactions, local action receipts (generated from transactions with signer ==
@@ -1215,6 +1288,7 @@ impl Runtime {
                 stats,
                 processed_delayed_receipts: vec![],
                 proof,
+                congestion_info: None,
             });
         }
 
@@ -1227,14 +1301,30 @@ impl Runtime {
         // charge any gas for refund receipts, we still count the gas use towards the block gas
         // limit
         let mut total_gas_burnt = gas_used_for_migrations;
+        let mut total_compute_usage = total_gas_burnt;
 
         for signed_transaction in transactions {
-            let (receipt, outcome_with_id) = self.process_transaction(
+            let (receipt, outcome_with_id) = match self.process_transaction(
                 &mut state_update,
                 apply_state,
                 signed_transaction,
+                true,
                 &mut stats,
-            )?;
+            ) {
+                Ok(result) => result,
+                // The chunk producer checked the expiry of the access key
+                // against the next block height and the previous block
+                // timestamp, either of which may be behind the block the
+                // chunk ends up in. Such a transaction is dropped instead of
+                // invalidating the whole chunk.
+                Err(RuntimeError::InvalidTxError(InvalidTxError::InvalidAccessKeyError(
+                    InvalidAccessKeyError::AccessKeyExpired { .. },
+                ))) => {
+                    tracing::debug!(target: "runtime", tx_hash = %signed_transaction.get_hash(), "dropping transaction signed with an expired access key");
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
             if receipt.receiver_id == signed_transaction.transaction.signer_id {
                 local_receipts.push(receipt);
             } else {
@@ -1242,6 +1332,13 @@ impl Runtime {
             }
 
             total_gas_burnt += outcome_with_id.outcome.gas_burnt;
+            total_compute_usage = safe_add_compute(
+                total_compute_usage,
+                outcome_with_id
+                    .outcome
+                    .compute_usage
+                    .expect("`process_transaction` must populate compute usage"),
+            )?;
 
             outcomes.push(outcome_with_id);
         }
@@ -1250,9 +1347,22 @@ impl Runtime {
             get(&state_update, &TrieKey::DelayedReceiptIndices)?.unwrap_or_default();
         let initial_delayed_receipt_indices = delayed_receipts_indices.clone();
 
+        let is_congestion_control_enabled = checked_feature!(
+            "protocol_feature_congestion_control",
+            CongestionControl,
+            apply_state.current_protocol_version
+        );
+        let mut congestion_info: Option<CongestionInfo> = if is_congestion_control_enabled {
+            Some(get(&state_update, &TrieKey::CongestionInfo)?.unwrap_or_default())
+        } else {
+            None
+        };
+        let initial_congestion_info = congestion_info;
+
         let mut process_receipt = |receipt: &Receipt,
                                    state_update: &mut TrieUpdate,
-                                   total_gas_burnt: &mut Gas|
+                                   total_gas_burnt: &mut Gas,
+                                   total_compute_usage: &mut Compute|
          -> Result<_, RuntimeError> {
             let _span = tracing::debug_span!(
                 target: "runtime",
@@ -1277,12 +1387,33 @@ impl Runtime {
             if let Some(outcome_with_id) = result? {
                 *total_gas_burnt =
                     safe_add_gas(*total_gas_burnt, outcome_with_id.outcome.gas_burnt)?;
+                *total_compute_usage = safe_add_compute(
+                    *total_compute_usage,
+                    outcome_with_id
+                        .outcome
+                        .compute_usage
+                        .expect("`process_receipt` must populate compute usage"),
+                )?;
                 outcomes.push(outcome_with_id);
             }
             Ok(())
         };
 
         let gas_limit = apply_state.gas_limit.unwrap_or(Gas::max_value());
+        let is_compute_costs_enabled = checked_feature!(
+            "protocol_feature_compute_costs",
+            ComputeCosts,
+            apply_state.current_protocol_version
+        );
+        // With compute costs the chunk is limited by the compute usage of the
+        // receipts rather than by the gas they burn.
+        let is_limit_reached = |total_gas_burnt: Gas, total_compute_usage: Compute| {
+            if is_compute_costs_enabled {
+                total_compute_usage >= gas_limit
+            } else {
+                total_gas_burnt >= gas_limit
+            }
+        };
 
         // We first process local receipts. They contain staking, local contract calls, etc.
         if let Some(prefetcher) = &mut prefetcher {
@@ -1290,18 +1421,27 @@ impl Runtime {
             let _queue_full = prefetcher.input_receipts(&local_receipts);
         }
         for receipt in local_receipts.iter() {
-            if total_gas_burnt < gas_limit {
+            if !is_limit_reached(total_gas_burnt, total_compute_usage) {
                 // NOTE: We don't need to validate the local receipt, because it's just validated in
                 // the `verify_and_charge_transaction`.
-                process_receipt(receipt, &mut state_update, &mut total_gas_burnt)?;
+                process_receipt(
+                    receipt,
+                    &mut state_update,
+                    &mut total_gas_burnt,
+                    &mut total_compute_usage,
+                )?;
             } else {
                 Self::delay_receipt(&mut state_update, &mut delayed_receipts_indices, receipt)?;
+                if let Some(congestion_info) = &mut congestion_info {
+                    let (gas, size) = receipt_congestion_gas_and_size(apply_state, receipt)?;
+                    congestion_info.add_delayed_receipt(gas, size)?;
+                }
             }
         }
 
         // Then we process the delayed receipts. It's a backlog of receipts from the past blocks.
         while delayed_receipts_indices.first_index < delayed_receipts_indices.next_available_index {
-            if total_gas_burnt >= gas_limit {
+            if is_limit_reached(total_gas_burnt, total_compute_usage) {
                 break;
             }
             let key = TrieKey::DelayedReceipt { index: delayed_receipts_indices.first_index };
@@ -1318,19 +1458,31 @@ impl Runtime {
             }
 
             // Validating the delayed receipt. If it fails, it's likely the state is inconsistent.
-            validate_receipt(&apply_state.config.wasm_config.limit_config, &receipt).map_err(
-                |e| {
-                    StorageError::StorageInconsistentState(format!(
-                        "Delayed receipt #{} in the state is invalid: {}",
-                        delayed_receipts_indices.first_index, e
-                    ))
-                },
-            )?;
+            validate_receipt(
+                &apply_state.config.wasm_config.limit_config,
+                &receipt,
+                apply_state.current_protocol_version,
+            )
+            .map_err(|e| {
+                StorageError::StorageInconsistentState(format!(
+                    "Delayed receipt #{} in the state is invalid: {}",
+                    delayed_receipts_indices.first_index, e
+                ))
+            })?;
 
             state_update.remove(key);
             // Math checked above: first_index is less than next_available_index
             delayed_receipts_indices.first_index += 1;
-            process_receipt(&receipt, &mut state_update, &mut total_gas_burnt)?;
+            if let Some(congestion_info) = &mut congestion_info {
+                let (gas, size) = receipt_congestion_gas_and_size(apply_state, &receipt)?;
+                congestion_info.remove_delayed_receipt(gas, size);
+            }
+            process_receipt(
+                &receipt,
+                &mut state_update,
+                &mut total_gas_burnt,
+                &mut total_compute_usage,
+            )?;
             processed_delayed_receipts.push(receipt);
         }
 
@@ -1342,12 +1494,25 @@ impl Runtime {
         for receipt in incoming_receipts.iter() {
             // Validating new incoming no matter whether we have available gas or not. We don't
             // want to store invalid receipts in state as delayed.
-            validate_receipt(&apply_state.config.wasm_config.limit_config, receipt)
-                .map_err(RuntimeError::ReceiptValidationError)?;
-            if total_gas_burnt < gas_limit {
-                process_receipt(receipt, &mut state_update, &mut total_gas_burnt)?;
+            validate_receipt(
+                &apply_state.config.wasm_config.limit_config,
+                receipt,
+                apply_state.current_protocol_version,
+            )
+            .map_err(RuntimeError::ReceiptValidationError)?;
+            if !is_limit_reached(total_gas_burnt, total_compute_usage) {
+                process_receipt(
+                    receipt,
+                    &mut state_update,
+                    &mut total_gas_burnt,
+                    &mut total_compute_usage,
+                )?;
             } else {
                 Self::delay_receipt(&mut state_update, &mut delayed_receipts_indices, receipt)?;
+                if let Some(congestion_info) = &mut congestion_info {
+                    let (gas, size) = receipt_congestion_gas_and_size(apply_state, receipt)?;
+                    congestion_info.add_delayed_receipt(gas, size)?;
+                }
             }
         }
 
@@ -1360,6 +1525,12 @@ impl Runtime {
             set(&mut state_update, TrieKey::DelayedReceiptIndices, &delayed_receipts_indices);
         }
 
+        if congestion_info != initial_congestion_info {
+            if let Some(congestion_info) = &congestion_info {
+                set(&mut state_update, TrieKey::CongestionInfo, congestion_info);
+            }
+        }
+
         check_balance(
             &apply_state.config.transaction_costs,
             &state_update,
@@ -1371,6 +1542,14 @@ impl Runtime {
             apply_state.current_protocol_version,
         )?;
 
+        // Buffering only changes where the outgoing receipts are stored, so it
+        // is done after the balance check.
+        let outgoing_receipts = if is_congestion_control_enabled {
+            Self::buffer_outgoing_receipts(&mut state_update, apply_state, outgoing_receipts)?
+        } else {
+            outgoing_receipts
+        };
+
         state_update.commit(StateChangeCause::UpdatedDelayedReceipts);
         self.apply_state_patch(&mut state_update, state_patch);
         let (trie_changes, state_changes) = state_update.finalize()?;
@@ -1399,9 +1578,99 @@ impl Runtime {
             stats,
             processed_delayed_receipts,
             proof,
+            congestion_info,
         })
     }
 
+    /// Applies the transaction and all the receipts it produces, including the
+    /// receipts sent to other shards, on top of the given state without
+    /// committing anything to the storage.
+    ///
+    /// Every shard touched by the transaction gets its own copy-on-write
+    /// `TrieUpdate` created from the trie returned by `get_trie`.  Receipts are
+    /// executed one by one as soon as they are produced, ignoring the gas limit
+    /// and the delayed receipts queue, so the order of execution may differ from
+    /// the one on chain.  Signature of the transaction is only checked if
+    /// `verify_signature` is set which allows simulating unsigned transactions.
+    ///
+    /// No further receipts are processed once the gas burnt so far or the time
+    /// spent exceeds `limits`.  The result then has `limit_exceeded` set.
+    pub fn simulate_transaction(
+        &self,
+        get_trie: &dyn Fn(ShardUId) -> Result<Trie, StorageError>,
+        shard_layout: &ShardLayout,
+        apply_state: &ApplyState,
+        signed_transaction: &SignedTransaction,
+        verify_signature: bool,
+        epoch_info_provider: &dyn EpochInfoProvider,
+        limits: &SimulateTransactionConfig,
+    ) -> Result<SimulationResult, RuntimeError> {
+        let _span = tracing::debug_span!(
+            target: "runtime",
+            "simulate_transaction",
+            tx_hash = %signed_transaction.get_hash())
+        .entered();
+
+        let deadline = Instant::now() + limits.timeout;
+        let mut state_updates = HashMap::new();
+        let mut stats = ApplyStats::default();
+        let mut outcomes = vec![];
+        let mut receipts = vec![];
+        let mut validator_proposals = vec![];
+
+        let (receipt, outcome_with_id) = self.process_transaction(
+            simulation_state_update(
+                &mut state_updates,
+                get_trie,
+                shard_layout,
+                &signed_transaction.transaction.signer_id,
+            )?,
+            apply_state,
+            signed_transaction,
+            verify_signature,
+            &mut stats,
+        )?;
+        let mut gas_burnt = outcome_with_id.outcome.gas_burnt;
+        outcomes.push(outcome_with_id);
+
+        let mut limit_exceeded = None;
+        let mut pending_receipts = VecDeque::from([receipt]);
+        while let Some(receipt) = pending_receipts.pop_front() {
+            if gas_burnt > limits.max_gas_burnt {
+                limit_exceeded =
+                    Some(SimulationLimitExceeded::GasBurnt { limit: limits.max_gas_burnt });
+                break;
+            }
+            if Instant::now() >= deadline {
+                limit_exceeded = Some(SimulationLimitExceeded::Timeout { timeout: limits.timeout });
+                break;
+            }
+            let mut new_receipts = vec![];
+            let outcome_with_id = self.process_receipt(
+                simulation_state_update(
+                    &mut state_updates,
+                    get_trie,
+                    shard_layout,
+                    &receipt.receiver_id,
+                )?,
+                apply_state,
+                &receipt,
+                &mut new_receipts,
+                &mut validator_proposals,
+                &mut stats,
+                epoch_info_provider,
+            )?;
+            if let Some(outcome_with_id) = outcome_with_id {
+                gas_burnt = gas_burnt.saturating_add(outcome_with_id.outcome.gas_burnt);
+                outcomes.push(outcome_with_id);
+            }
+            receipts.push(receipt);
+            pending_receipts.extend(new_receipts);
+        }
+
+        Ok(SimulationResult { outcomes, receipts, limit_exceeded })
+    }
+
     // Adds the given receipt into the end of the delayed receipt queue in the state.
     pub fn delay_receipt(
         state_update: &mut TrieUpdate,
@@ -1423,6 +1692,93 @@ impl Runtime {
         Ok(())
     }
 
+    /// Applies backpressure to the congested shards. Receipts buffered for the
+    /// shards which are no longer congested are sent first. New receipts are
+    /// buffered if their receiving shard is congested or still has buffered
+    /// receipts, so that receipts are delivered in the order they were produced.
+    /// Returns the receipts to send in this chunk.
+    fn buffer_outgoing_receipts(
+        state_update: &mut TrieUpdate,
+        apply_state: &ApplyState,
+        new_receipts: Vec<Receipt>,
+    ) -> Result<Vec<Receipt>, RuntimeError> {
+        let config = &apply_state.config.congestion_control_config;
+        let mut outgoing_receipts = vec![];
+        let mut buffers = BTreeMap::new();
+        for shard_id in 0..apply_state.shard_layout.num_shards() {
+            let indices_key = TrieKey::BufferedReceiptIndices { receiving_shard: shard_id };
+            let initial_indices: DelayedReceiptIndices =
+                get(state_update, &indices_key)?.unwrap_or_default();
+            let mut indices = initial_indices.clone();
+            let is_congested = apply_state.congestion_info.is_congested(shard_id, config);
+            if !is_congested {
+                while indices.first_index < indices.next_available_index {
+                    let key = TrieKey::BufferedReceipt {
+                        receiving_shard: shard_id,
+                        index: indices.first_index,
+                    };
+                    let receipt: Receipt = get(state_update, &key)?.ok_or_else(|| {
+                        StorageError::StorageInconsistentState(format!(
+                            "Buffered receipt #{} for shard {} should be in the state",
+                            indices.first_index, shard_id
+                        ))
+                    })?;
+                    state_update.remove(key);
+                    // Math checked above: first_index is less than next_available_index
+                    indices.first_index += 1;
+                    outgoing_receipts.push(receipt);
+                }
+                metrics::CONGESTION_FORWARDED_RECEIPTS_TOTAL
+                    .with_label_values(&[&shard_id.to_string()])
+                    .inc_by(indices.first_index - initial_indices.first_index);
+            }
+            buffers.insert(shard_id, (is_congested, initial_indices, indices));
+        }
+
+        for receipt in new_receipts {
+            let shard_id = account_id_to_shard_id(&receipt.receiver_id, &apply_state.shard_layout);
+            let (is_congested, _, indices) = buffers.get_mut(&shard_id).ok_or_else(|| {
+                StorageError::StorageInconsistentState(format!(
+                    "Receipt {} is sent to unknown shard {}",
+                    receipt.receipt_id, shard_id
+                ))
+            })?;
+            if !*is_congested && indices.first_index == indices.next_available_index {
+                outgoing_receipts.push(receipt);
+                continue;
+            }
+            set(
+                state_update,
+                TrieKey::BufferedReceipt {
+                    receiving_shard: shard_id,
+                    index: indices.next_available_index,
+                },
+                &receipt,
+            );
+            indices.next_available_index =
+                indices.next_available_index.checked_add(1).ok_or_else(|| {
+                    StorageError::StorageInconsistentState(
+                        "Next available index for buffered receipt exceeded the integer limit"
+                            .to_string(),
+                    )
+                })?;
+            metrics::CONGESTION_BUFFERED_RECEIPTS_TOTAL
+                .with_label_values(&[&shard_id.to_string()])
+                .inc();
+        }
+
+        for (shard_id, (_, initial_indices, indices)) in buffers {
+            if indices != initial_indices {
+                set(
+                    state_update,
+                    TrieKey::BufferedReceiptIndices { receiving_shard: shard_id },
+                    &indices,
+                );
+            }
+        }
+        Ok(outgoing_receipts)
+    }
+
     fn apply_state_patch(&self, state_update: &mut TrieUpdate, state_patch: SandboxStatePatch) {
         if state_patch.is_empty() {
             return;
@@ -1489,11 +1845,46 @@ impl Runtime {
     }
 }
 
+/// Returns the state update of the shard `account_id` belongs to, creating it
+/// on first access.  Used by [`Runtime::simulate_transaction`].
+fn simulation_state_update<'a>(
+    state_updates: &'a mut HashMap<ShardUId, TrieUpdate>,
+    get_trie: &dyn Fn(ShardUId) -> Result<Trie, StorageError>,
+    shard_layout: &ShardLayout,
+    account_id: &AccountId,
+) -> Result<&'a mut TrieUpdate, StorageError> {
+    let shard_uid = ShardUId::from_shard_id_and_layout(
+        account_id_to_shard_id(account_id, shard_layout),
+        shard_layout,
+    );
+    Ok(match state_updates.entry(shard_uid) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => entry.insert(TrieUpdate::new(Rc::new(get_trie(shard_uid)?))),
+    })
+}
+
+/// Returns the gas and the size of a delayed receipt as accounted for in the
+/// `CongestionInfo` of the shard.
+fn receipt_congestion_gas_and_size(
+    apply_state: &ApplyState,
+    receipt: &Receipt,
+) -> Result<(Gas, u64), RuntimeError> {
+    let gas = receipt_required_gas(apply_state, receipt)?;
+    let size = receipt.try_to_vec().expect("Borsh serializer is not expected to ever fail").len();
+    Ok((gas, size as u64))
+}
+
 #[cfg(test)]
 mod tests {
-    use near_crypto::{InMemorySigner, KeyType, Signer};
-    use near_primitives::account::AccessKey;
+    use assert_matches::assert_matches;
+    use near_crypto::{InMemorySigner, KeyType, Signature, Signer};
+    use near_primitives::account::{
+        AccessKey, AccessKeyExpiry, AccessKeyPermission, FunctionCallPermission,
+        FunctionCallPermissionV2,
+    };
+    use near_primitives::congestion_info::BlockCongestionInfo;
     use near_primitives::contract::ContractCode;
+    use near_primitives::errors::InvalidTxError;
     use near_primitives::hash::hash;
     use near_primitives::shard_layout::ShardUId;
     use near_primitives::test_utils::{account_new, MockEpochInfoProvider};
@@ -1622,6 +2013,8 @@ mod tests {
             is_new_chunk: true,
             migration_data: Arc::new(MigrationData::default()),
             migration_flags: MigrationFlags::default(),
+            congestion_info: BlockCongestionInfo::default(),
+            shard_layout: ShardLayout::v0_single_shard(),
         };
 
         (runtime, tries, root, apply_state, signer, MockEpochInfoProvider::default())
@@ -1644,6 +2037,60 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn test_apply_drops_tx_with_expired_access_key() {
+        let (runtime, tries, root, apply_state, signer, epoch_info_provider) =
+            setup_runtime(to_yocto(1_000_000), 0, 10u64.pow(15));
+
+        // The key expires exactly at the timestamp of the applied block.
+        let mut state_update = tries.new_trie_update(ShardUId::single_shard(), root);
+        let access_key = AccessKey {
+            nonce: 0,
+            permission: AccessKeyPermission::FunctionCallV2(FunctionCallPermissionV2 {
+                function_call: FunctionCallPermission {
+                    allowance: None,
+                    receiver_id: bob_account().into(),
+                    method_names: vec![],
+                },
+                expiry: Some(AccessKeyExpiry::Timestamp(apply_state.block_timestamp)),
+                usage_limit: None,
+            }),
+        };
+        set_access_key(&mut state_update, alice_account(), signer.public_key(), &access_key);
+        state_update.commit(StateChangeCause::InitialState);
+        let trie_changes = state_update.finalize().unwrap().0;
+        let mut store_update = tries.store_update();
+        let root = tries.apply_all(&trie_changes, ShardUId::single_shard(), &mut store_update);
+        store_update.commit().unwrap();
+
+        let transaction = SignedTransaction::from_actions(
+            1,
+            alice_account(),
+            bob_account(),
+            &*signer,
+            vec![Action::FunctionCall(FunctionCallAction {
+                method_name: "hello".to_string(),
+                args: vec![],
+                gas: 100,
+                deposit: 0,
+            })],
+            CryptoHash::default(),
+        );
+        let apply_result = runtime
+            .apply(
+                tries.get_trie_for_shard(ShardUId::single_shard(), root),
+                &None,
+                &apply_state,
+                &[],
+                &[transaction],
+                &epoch_info_provider,
+                Default::default(),
+            )
+            .unwrap();
+        assert!(apply_result.outcomes.is_empty());
+        assert!(apply_result.outgoing_receipts.is_empty());
+    }
+
     #[test]
     fn test_apply_check_balance_validation_rewards() {
         let initial_locked = to_yocto(500_000);
@@ -2366,6 +2813,220 @@ mod tests {
         assert_eq!(initial_account_state.storage_usage(), final_account_state.storage_usage());
     }
 
+    #[test]
+    fn test_simulate_transaction() {
+        let initial_balance = to_yocto(1_000_000);
+        let (runtime, tries, root, apply_state, signer, epoch_info_provider) =
+            setup_runtime(initial_balance, 0, 10u64.pow(15));
+        let get_trie = |shard_uid| Ok(tries.get_trie_for_shard(shard_uid, root));
+        let shard_layout = ShardLayout::v0_single_shard();
+        let limits = SimulateTransactionConfig::default();
+
+        let mut transaction = SignedTransaction::from_actions(
+            1,
+            alice_account(),
+            alice_account(),
+            &*signer,
+            vec![Action::Transfer(TransferAction { deposit: to_yocto(1) })],
+            CryptoHash::default(),
+        );
+        let result = runtime
+            .simulate_transaction(
+                &get_trie,
+                &shard_layout,
+                &apply_state,
+                &transaction,
+                true,
+                &epoch_info_provider,
+                &limits,
+            )
+            .unwrap();
+        // The transaction, its receipt and the gas refund.
+        assert_eq!(result.outcomes.len(), 3);
+        assert_eq!(result.receipts.len(), 2);
+        assert_eq!(result.outcomes[0].outcome.receipt_ids, vec![result.receipts[0].receipt_id]);
+        assert_eq!(result.outcomes[1].outcome.status, ExecutionStatus::SuccessValue(vec![]));
+        assert_eq!(result.limit_exceeded, None);
+
+        // Nothing is committed to the state.
+        let state_update = tries.new_trie_update(ShardUId::single_shard(), root);
+        let account = get_account(&state_update, &alice_account()).unwrap().unwrap();
+        assert_eq!(account.amount(), initial_balance);
+
+        // Signature is only checked if requested.
+        transaction.signature = Signature::empty(KeyType::ED25519);
+        assert_matches!(
+            runtime.simulate_transaction(
+                &get_trie,
+                &shard_layout,
+                &apply_state,
+                &transaction,
+                true,
+                &epoch_info_provider,
+                &limits,
+            ),
+            Err(RuntimeError::InvalidTxError(InvalidTxError::InvalidSignature))
+        );
+        runtime
+            .simulate_transaction(
+                &get_trie,
+                &shard_layout,
+                &apply_state,
+                &transaction,
+                false,
+                &epoch_info_provider,
+                &limits,
+            )
+            .unwrap();
+
+        // The simulation stops once any of the limits is exceeded, keeping the
+        // outcomes computed so far.
+        let gas_limits = SimulateTransactionConfig { max_gas_burnt: 0, ..limits.clone() };
+        let result = runtime
+            .simulate_transaction(
+                &get_trie,
+                &shard_layout,
+                &apply_state,
+                &transaction,
+                false,
+                &epoch_info_provider,
+                &gas_limits,
+            )
+            .unwrap();
+        assert_eq!(result.limit_exceeded, Some(SimulationLimitExceeded::GasBurnt { limit: 0 }));
+        assert_eq!(result.outcomes.len(), 1);
+        assert!(result.receipts.is_empty());
+
+        let time_limits =
+            SimulateTransactionConfig { timeout: std::time::Duration::ZERO, ..limits.clone() };
+        let result = runtime
+            .simulate_transaction(
+                &get_trie,
+                &shard_layout,
+                &apply_state,
+                &transaction,
+                false,
+                &epoch_info_provider,
+                &time_limits,
+            )
+            .unwrap();
+        assert_eq!(
+            result.limit_exceeded,
+            Some(SimulationLimitExceeded::Timeout { timeout: std::time::Duration::ZERO })
+        );
+        assert_eq!(result.outcomes.len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_congestion_control")]
+    fn test_apply_delayed_receipts_congestion_info() {
+        let initial_balance = to_yocto(1_000_000);
+        let initial_locked = to_yocto(500_000);
+        let (runtime, tries, mut root, apply_state, _, epoch_info_provider) =
+            setup_runtime(initial_balance, initial_locked, 1);
+
+        let n = 5;
+        let receipts = generate_receipts(to_yocto(10_000), n);
+        let mut expected = CongestionInfo::default();
+        for receipt in &receipts[1..] {
+            let (gas, size) = receipt_congestion_gas_and_size(&apply_state, receipt).unwrap();
+            expected.add_delayed_receipt(gas, size).unwrap();
+        }
+
+        // Only the first receipt fits into the gas limit, the rest is delayed.
+        for i in 0..n {
+            let prev_receipts: &[Receipt] = if i == 0 { &receipts } else { &[] };
+            let apply_result = runtime
+                .apply(
+                    tries.get_trie_for_shard(ShardUId::single_shard(), root),
+                    &None,
+                    &apply_state,
+                    prev_receipts,
+                    &[],
+                    &epoch_info_provider,
+                    Default::default(),
+                )
+                .unwrap();
+            let mut store_update = tries.store_update();
+            root = tries.apply_all(
+                &apply_result.trie_changes,
+                ShardUId::single_shard(),
+                &mut store_update,
+            );
+            store_update.commit().unwrap();
+            let state = tries.new_trie_update(ShardUId::single_shard(), root);
+            let congestion_info: CongestionInfo =
+                get(&state, &TrieKey::CongestionInfo).unwrap().unwrap();
+            assert_eq!(apply_result.congestion_info, Some(congestion_info));
+            if i == 0 {
+                assert_eq!(congestion_info, expected);
+            }
+        }
+        assert_eq!(
+            get::<CongestionInfo>(
+                &tries.new_trie_update(ShardUId::single_shard(), root),
+                &TrieKey::CongestionInfo
+            )
+            .unwrap(),
+            Some(CongestionInfo::default())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_congestion_control")]
+    fn test_apply_buffers_receipts_to_congested_shard() {
+        let (runtime, tries, root, mut apply_state, signer, epoch_info_provider) =
+            setup_runtime(to_yocto(1_000_000), 0, 10u64.pow(15));
+        let congested =
+            CongestionInfo { delayed_receipts_gas: u128::MAX, delayed_receipts_bytes: u64::MAX };
+        apply_state.congestion_info = BlockCongestionInfo::new([(0, congested)].into());
+
+        let transaction = SignedTransaction::from_actions(
+            1,
+            alice_account(),
+            bob_account(),
+            &*signer,
+            vec![Action::Transfer(TransferAction { deposit: to_yocto(1) })],
+            CryptoHash::default(),
+        );
+        let apply_result = runtime
+            .apply(
+                tries.get_trie_for_shard(ShardUId::single_shard(), root),
+                &None,
+                &apply_state,
+                &[],
+                &[transaction],
+                &epoch_info_provider,
+                Default::default(),
+            )
+            .unwrap();
+        assert!(apply_result.outgoing_receipts.is_empty());
+        let receipt_id = apply_result.outcomes[0].outcome.receipt_ids[0];
+        let mut store_update = tries.store_update();
+        let root = tries.apply_all(
+            &apply_result.trie_changes,
+            ShardUId::single_shard(),
+            &mut store_update,
+        );
+        store_update.commit().unwrap();
+
+        // The buffered receipt is sent once the shard is no longer congested.
+        apply_state.congestion_info = BlockCongestionInfo::default();
+        let apply_result = runtime
+            .apply(
+                tries.get_trie_for_shard(ShardUId::single_shard(), root),
+                &None,
+                &apply_state,
+                &[],
+                &[],
+                &epoch_info_provider,
+                Default::default(),
+            )
+            .unwrap();
+        assert_eq!(apply_result.outgoing_receipts.len(), 1);
+        assert_eq!(apply_result.outgoing_receipts[0].receipt_id, receipt_id);
+    }
+
     #[test]
     fn test_delete_key_underflow() {
         let initial_locked = to_yocto(500_000);
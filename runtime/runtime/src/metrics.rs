@@ -105,3 +105,19 @@ pub static FUNCTION_CALL_PROCESSED_CACHE_ERRORS: Lazy<IntCounterVec> = Lazy::new
     )
     .unwrap()
 });
+pub static CONGESTION_BUFFERED_RECEIPTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_congestion_buffered_receipts_total",
+        "The number of outgoing receipts buffered because their receiving shard was congested",
+        &["receiving_shard_id"],
+    )
+    .unwrap()
+});
+pub static CONGESTION_FORWARDED_RECEIPTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_congestion_forwarded_receipts_total",
+        "The number of buffered receipts sent once their receiving shard was no longer congested",
+        &["receiving_shard_id"],
+    )
+    .unwrap()
+});
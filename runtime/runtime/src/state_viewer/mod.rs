@@ -5,6 +5,7 @@ use near_primitives::runtime::config_store::RuntimeConfigStore;
 use near_primitives::{
     account::{AccessKey, Account},
     borsh::BorshDeserialize,
+    congestion_info::BlockCongestionInfo,
     contract::ContractCode,
     hash::CryptoHash,
     receipt::ActionReceipt,
@@ -12,6 +13,7 @@ use near_primitives::{
         apply_state::ApplyState,
         migration_data::{MigrationData, MigrationFlags},
     },
+    shard_layout::ShardLayout,
     transaction::FunctionCallAction,
     trie_key::trie_key_parsers,
     types::{AccountId, EpochInfoProvider, Gas},
@@ -208,6 +210,8 @@ impl TrieViewer {
             is_new_chunk: false,
             migration_data: Arc::new(MigrationData::default()),
             migration_flags: MigrationFlags::default(),
+            congestion_info: BlockCongestionInfo::default(),
+            shard_layout: ShardLayout::v0_single_shard(),
         };
         let action_receipt = ActionReceipt {
             signer_id: originator_id.clone(),
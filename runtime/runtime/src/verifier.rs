@@ -1,7 +1,8 @@
 use near_crypto::key_conversion::is_valid_staking_key;
+use near_crypto::{KeyType, PublicKey};
 use near_primitives::runtime::get_insufficient_storage_stake;
 use near_primitives::{
-    account::AccessKeyPermission,
+    account::{AccessKeyPermission, FunctionCallPermissionV2},
     config::VMLimitConfig,
     errors::{
         ActionsValidationError, InvalidAccessKeyError, InvalidTxError, ReceiptValidationError,
@@ -9,8 +10,8 @@ use near_primitives::{
     },
     receipt::{ActionReceipt, DataReceipt, Receipt, ReceiptEnum},
     transaction::{
-        Action, AddKeyAction, DeployContractAction, FunctionCallAction, SignedTransaction,
-        StakeAction,
+        Action, AddKeyAction, DeployContractAction, FunctionCallAction, SignedDelegateAction,
+        SignedTransaction, StakeAction,
     },
     types::{AccountId, Balance},
     version::ProtocolVersion,
@@ -23,7 +24,17 @@ use crate::config::{total_prepaid_gas, tx_cost, TransactionCost};
 use crate::VerificationResult;
 use near_primitives::checked_feature;
 use near_primitives::runtime::config::RuntimeConfig;
-use near_primitives::types::BlockHeight;
+use near_primitives::types::{BlockHeight, EpochHeight};
+
+/// The block a transaction is verified for, as far as it is known at the time
+/// of the verification.
+#[derive(Clone, Copy, Debug)]
+pub struct TransactionBlockInfo {
+    pub height: BlockHeight,
+    /// Timestamp of the block in nanoseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub epoch_height: EpochHeight,
+}
 
 /// Validates the transaction without using the state. It allows any node to validate a
 /// transaction before forwarding it to the node that tracks the `signer_id` account.
@@ -37,6 +48,11 @@ pub fn validate_transaction(
     let transaction = &signed_transaction.transaction;
     let signer_id = &transaction.signer_id;
 
+    // Signatures of key types which are not enabled yet are never valid.
+    if !is_key_type_enabled(&transaction.public_key, current_protocol_version) {
+        return Err(InvalidTxError::InvalidSignature.into());
+    }
+
     if verify_signature
         && !signed_transaction
             .signature
@@ -55,8 +71,12 @@ pub fn validate_transaction(
         .into());
     }
 
-    validate_actions(&config.wasm_config.limit_config, &transaction.actions)
-        .map_err(InvalidTxError::ActionsValidation)?;
+    validate_actions(
+        &config.wasm_config.limit_config,
+        &transaction.actions,
+        current_protocol_version,
+    )
+    .map_err(InvalidTxError::ActionsValidation)?;
 
     let sender_is_receiver = &transaction.receiver_id == signer_id;
 
@@ -78,17 +98,23 @@ pub fn verify_and_charge_transaction(
     gas_price: Balance,
     signed_transaction: &SignedTransaction,
     verify_signature: bool,
-    #[allow(unused)] block_height: Option<BlockHeight>,
+    block_info: Option<TransactionBlockInfo>,
     current_protocol_version: ProtocolVersion,
 ) -> Result<VerificationResult, RuntimeError> {
-    let TransactionCost { gas_burnt, gas_remaining, receipt_gas_price, total_cost, burnt_amount } =
-        validate_transaction(
-            config,
-            gas_price,
-            signed_transaction,
-            verify_signature,
-            current_protocol_version,
-        )?;
+    let TransactionCost {
+        gas_burnt,
+        compute_usage,
+        gas_remaining,
+        receipt_gas_price,
+        total_cost,
+        burnt_amount,
+    } = validate_transaction(
+        config,
+        gas_price,
+        signed_transaction,
+        verify_signature,
+        current_protocol_version,
+    )?;
     let transaction = &signed_transaction.transaction;
     let signer_id = &transaction.signer_id;
 
@@ -119,9 +145,9 @@ pub fn verify_and_charge_transaction(
         .into());
     }
     if checked_feature!("stable", AccessKeyNonceRange, current_protocol_version) {
-        if let Some(height) = block_height {
-            let upper_bound =
-                height * near_primitives::account::AccessKey::ACCESS_KEY_NONCE_RANGE_MULTIPLIER;
+        if let Some(block_info) = block_info {
+            let upper_bound = block_info.height
+                * near_primitives::account::AccessKey::ACCESS_KEY_NONCE_RANGE_MULTIPLIER;
             if transaction.nonce >= upper_bound {
                 return Err(InvalidTxError::NonceTooLarge {
                     tx_nonce: transaction.nonce,
@@ -134,6 +160,18 @@ pub fn verify_and_charge_transaction(
 
     access_key.nonce = transaction.nonce;
 
+    if let AccessKeyPermission::FunctionCallV2(ref mut permission) = access_key.permission {
+        if let Some(block_info) = block_info {
+            check_and_count_access_key_use(
+                permission,
+                block_info.height,
+                block_info.timestamp,
+                block_info.epoch_height,
+            )
+            .map_err(InvalidTxError::InvalidAccessKeyError)?;
+        }
+    }
+
     signer.set_amount(signer.amount().checked_sub(total_cost).ok_or_else(|| {
         InvalidTxError::NotEnoughBalance {
             signer_id: signer_id.clone(),
@@ -142,9 +180,7 @@ pub fn verify_and_charge_transaction(
         }
     })?);
 
-    if let AccessKeyPermission::FunctionCall(ref mut function_call_permission) =
-        access_key.permission
-    {
+    if let Some(function_call_permission) = access_key.permission.function_call_permission_mut() {
         if let Some(ref mut allowance) = function_call_permission.allowance {
             *allowance = allowance.checked_sub(total_cost).ok_or_else(|| {
                 InvalidTxError::InvalidAccessKeyError(InvalidAccessKeyError::NotEnoughAllowance {
@@ -171,7 +207,7 @@ pub fn verify_and_charge_transaction(
         }
     };
 
-    if let AccessKeyPermission::FunctionCall(ref function_call_permission) = access_key.permission {
+    if let Some(function_call_permission) = access_key.permission.function_call_permission() {
         if transaction.actions.len() != 1 {
             return Err(InvalidTxError::InvalidAccessKeyError(
                 InvalidAccessKeyError::RequiresFullAccess,
@@ -218,13 +254,43 @@ pub fn verify_and_charge_transaction(
     set_access_key(state_update, signer_id.clone(), transaction.public_key.clone(), &access_key);
     set_account(state_update, signer_id.clone(), &signer);
 
-    Ok(VerificationResult { gas_burnt, gas_remaining, receipt_gas_price, burnt_amount })
+    Ok(VerificationResult {
+        gas_burnt,
+        compute_usage,
+        gas_remaining,
+        receipt_gas_price,
+        burnt_amount,
+    })
+}
+
+/// Checks that a `FunctionCallV2` access key hasn't expired and hasn't reached
+/// its usage limit in the given epoch, and counts the use of the key.
+pub(crate) fn check_and_count_access_key_use(
+    permission: &mut FunctionCallPermissionV2,
+    block_height: BlockHeight,
+    block_timestamp: u64,
+    epoch_height: EpochHeight,
+) -> Result<(), InvalidAccessKeyError> {
+    if let Some(expiry) = &permission.expiry {
+        if expiry.is_expired(block_height, block_timestamp) {
+            return Err(InvalidAccessKeyError::AccessKeyExpired { expiry: expiry.clone() });
+        }
+    }
+    if let Some(usage_limit) = &mut permission.usage_limit {
+        if !usage_limit.try_use(epoch_height) {
+            return Err(InvalidAccessKeyError::UsageLimitExceeded {
+                max_uses_per_epoch: usage_limit.max_uses_per_epoch,
+            });
+        }
+    }
+    Ok(())
 }
 
 /// Validates a given receipt. Checks validity of the Action or Data receipt.
 pub(crate) fn validate_receipt(
     limit_config: &VMLimitConfig,
     receipt: &Receipt,
+    current_protocol_version: ProtocolVersion,
 ) -> Result<(), ReceiptValidationError> {
     // We retain these checks here as to maintain backwards compatibility
     // with AccountId validation since we illegally parse an AccountId
@@ -240,7 +306,7 @@ pub(crate) fn validate_receipt(
 
     match &receipt.receipt {
         ReceiptEnum::Action(action_receipt) => {
-            validate_action_receipt(limit_config, action_receipt)
+            validate_action_receipt(limit_config, action_receipt, current_protocol_version)
         }
         ReceiptEnum::Data(data_receipt) => validate_data_receipt(limit_config, data_receipt),
     }
@@ -250,6 +316,7 @@ pub(crate) fn validate_receipt(
 fn validate_action_receipt(
     limit_config: &VMLimitConfig,
     receipt: &ActionReceipt,
+    current_protocol_version: ProtocolVersion,
 ) -> Result<(), ReceiptValidationError> {
     if receipt.input_data_ids.len() as u64 > limit_config.max_number_input_data_dependencies {
         return Err(ReceiptValidationError::NumberInputDataDependenciesExceeded {
@@ -257,7 +324,7 @@ fn validate_action_receipt(
             limit: limit_config.max_number_input_data_dependencies,
         });
     }
-    validate_actions(limit_config, &receipt.actions)
+    validate_actions(limit_config, &receipt.actions, current_protocol_version)
         .map_err(ReceiptValidationError::ActionsValidation)
 }
 
@@ -281,10 +348,12 @@ fn validate_data_receipt(
 /// - Checks limits if applicable.
 /// - Checks that the total number of actions doesn't exceed the limit.
 /// - Validates each individual action.
+/// - Checks that there is at most one delegate action.
 /// - Checks that the total prepaid gas doesn't exceed the limit.
 pub(crate) fn validate_actions(
     limit_config: &VMLimitConfig,
     actions: &[Action],
+    current_protocol_version: ProtocolVersion,
 ) -> Result<(), ActionsValidationError> {
     if actions.len() as u64 > limit_config.max_actions_per_receipt {
         return Err(ActionsValidationError::TotalNumberOfActionsExceeded {
@@ -293,6 +362,7 @@ pub(crate) fn validate_actions(
         });
     }
 
+    let mut found_delegate_action = false;
     let mut iter = actions.iter().peekable();
     while let Some(action) = iter.next() {
         if let Action::DeleteAccount(_) = action {
@@ -300,7 +370,13 @@ pub(crate) fn validate_actions(
                 return Err(ActionsValidationError::DeleteActionMustBeFinal);
             }
         }
-        validate_action(limit_config, action)?;
+        if let Action::Delegate(_) = action {
+            if found_delegate_action {
+                return Err(ActionsValidationError::DelegateActionMustBeOnlyOne);
+            }
+            found_delegate_action = true;
+        }
+        validate_action(limit_config, action, current_protocol_version)?;
     }
 
     let total_prepaid_gas =
@@ -319,6 +395,7 @@ pub(crate) fn validate_actions(
 pub fn validate_action(
     limit_config: &VMLimitConfig,
     action: &Action,
+    current_protocol_version: ProtocolVersion,
 ) -> Result<(), ActionsValidationError> {
     match action {
         Action::CreateAccount(_) => Ok(()),
@@ -326,10 +403,63 @@ pub fn validate_action(
         Action::FunctionCall(a) => validate_function_call_action(limit_config, a),
         Action::Transfer(_) => Ok(()),
         Action::Stake(a) => validate_stake_action(a),
-        Action::AddKey(a) => validate_add_key_action(limit_config, a),
+        Action::AddKey(a) => validate_add_key_action(limit_config, a, current_protocol_version),
         Action::DeleteKey(_) => Ok(()),
         Action::DeleteAccount(_) => Ok(()),
+        Action::Delegate(a) => validate_delegate_action(limit_config, a, current_protocol_version),
+    }
+}
+
+/// Validates `SignedDelegateAction`. Checks that delegate actions are enabled
+/// in the given protocol version and validates the inner actions.
+///
+/// The signature, nonce and expiration are checked when the action is applied
+/// because they depend on the state of the sender account.
+fn validate_delegate_action(
+    limit_config: &VMLimitConfig,
+    signed_delegate_action: &SignedDelegateAction,
+    current_protocol_version: ProtocolVersion,
+) -> Result<(), ActionsValidationError> {
+    if !checked_feature!(
+        "protocol_feature_nep366_delegate_action",
+        DelegateAction,
+        current_protocol_version
+    ) {
+        return Err(ActionsValidationError::UnsupportedProtocolFeature {
+            protocol_feature: String::from("DelegateAction"),
+            version: current_protocol_version,
+        });
+    }
+    let delegate_action = &signed_delegate_action.delegate_action;
+    validate_key_type(&delegate_action.public_key, current_protocol_version)?;
+    let actions = delegate_action.get_actions();
+    validate_actions(limit_config, &actions, current_protocol_version)
+}
+
+/// Whether keys of the type of `public_key` can be used in the given protocol
+/// version.
+fn is_key_type_enabled(public_key: &PublicKey, current_protocol_version: ProtocolVersion) -> bool {
+    match public_key.key_type() {
+        KeyType::ED25519 | KeyType::SECP256K1 => true,
+        KeyType::SECP256R1 => {
+            checked_feature!("protocol_feature_secp256r1", Secp256r1, current_protocol_version)
+        }
+    }
+}
+
+/// Checks that keys of the type of `public_key` are enabled in the given
+/// protocol version.
+fn validate_key_type(
+    public_key: &PublicKey,
+    current_protocol_version: ProtocolVersion,
+) -> Result<(), ActionsValidationError> {
+    if !is_key_type_enabled(public_key, current_protocol_version) {
+        return Err(ActionsValidationError::UnsupportedProtocolFeature {
+            protocol_feature: String::from("Secp256r1"),
+            version: current_protocol_version,
+        });
     }
+    Ok(())
 }
 
 /// Validates `DeployContractAction`. Checks that the given contract size doesn't exceed the limit.
@@ -385,14 +515,39 @@ fn validate_stake_action(action: &StakeAction) -> Result<(), ActionsValidationEr
     Ok(())
 }
 
-/// Validates `AddKeyAction`. If the access key permission is `FunctionCall`, checks that the
+/// Validates `AddKeyAction`. Checks that the key type and the permission are enabled in the given
+/// protocol version. If the access key permission is `FunctionCall`, checks that the
 /// total number of bytes of the method names doesn't exceed the limit and
 /// every method name length doesn't exceed the limit.
 fn validate_add_key_action(
     limit_config: &VMLimitConfig,
     action: &AddKeyAction,
+    current_protocol_version: ProtocolVersion,
 ) -> Result<(), ActionsValidationError> {
-    if let AccessKeyPermission::FunctionCall(fc) = &action.access_key.permission {
+    validate_key_type(&action.public_key, current_protocol_version)?;
+    if let AccessKeyPermission::FunctionCallV2(permission) = &action.access_key.permission {
+        if !checked_feature!(
+            "protocol_feature_bounded_access_keys",
+            BoundedAccessKeys,
+            current_protocol_version
+        ) {
+            return Err(ActionsValidationError::UnsupportedProtocolFeature {
+                protocol_feature: String::from("BoundedAccessKeys"),
+                version: current_protocol_version,
+            });
+        }
+        if let Some(usage_limit) = &permission.usage_limit {
+            if usage_limit.max_uses_per_epoch == 0
+                || usage_limit.uses > usage_limit.max_uses_per_epoch
+            {
+                return Err(ActionsValidationError::InvalidAccessKeyUsageLimit {
+                    max_uses_per_epoch: usage_limit.max_uses_per_epoch,
+                    uses: usage_limit.uses,
+                });
+            }
+        }
+    }
+    if let Some(fc) = action.access_key.permission.function_call_permission() {
         // Check whether `receiver_id` is a valid account_id. Historically, we
         // allowed arbitrary strings there!
         match limit_config.account_id_validity_rules_version {
@@ -459,12 +614,15 @@ fn test_truncate_string() {
 mod tests {
     use std::sync::Arc;
 
-    use near_crypto::{InMemorySigner, KeyType, PublicKey, Signer};
-    use near_primitives::account::{AccessKey, Account, FunctionCallPermission};
+    use near_crypto::{InMemorySigner, KeyType, PublicKey, SecretKey, Signer};
+    use near_primitives::account::{
+        AccessKey, AccessKeyExpiry, AccessKeyUsageLimit, Account, FunctionCallPermission,
+    };
     use near_primitives::hash::{hash, CryptoHash};
     use near_primitives::test_utils::account_new;
     use near_primitives::transaction::{
-        CreateAccountAction, DeleteAccountAction, DeleteKeyAction, StakeAction, TransferAction,
+        CreateAccountAction, DelegateAction, DeleteAccountAction, DeleteKeyAction, StakeAction,
+        TransferAction,
     };
     use near_primitives::types::{AccountId, Balance, MerkleHash, StateChangeCause};
     use near_primitives::version::PROTOCOL_VERSION;
@@ -1136,6 +1294,130 @@ mod tests {
         );
     }
 
+    fn bounded_function_call_key(
+        expiry: Option<AccessKeyExpiry>,
+        usage_limit: Option<AccessKeyUsageLimit>,
+    ) -> AccessKey {
+        AccessKey {
+            nonce: 0,
+            permission: AccessKeyPermission::FunctionCallV2(FunctionCallPermissionV2 {
+                function_call: FunctionCallPermission {
+                    allowance: None,
+                    receiver_id: bob_account().into(),
+                    method_names: vec![],
+                },
+                expiry,
+                usage_limit,
+            }),
+        }
+    }
+
+    fn verify_function_call(
+        state_update: &mut TrieUpdate,
+        signer: &dyn Signer,
+        nonce: u64,
+        block_info: TransactionBlockInfo,
+    ) -> Result<VerificationResult, RuntimeError> {
+        verify_and_charge_transaction(
+            &RuntimeConfig::test(),
+            state_update,
+            100,
+            &SignedTransaction::from_actions(
+                nonce,
+                alice_account(),
+                bob_account(),
+                signer,
+                vec![Action::FunctionCall(FunctionCallAction {
+                    method_name: "hello".to_string(),
+                    args: b"abc".to_vec(),
+                    gas: 100,
+                    deposit: 0,
+                })],
+                CryptoHash::default(),
+            ),
+            true,
+            Some(block_info),
+            PROTOCOL_VERSION,
+        )
+    }
+
+    #[test]
+    fn test_validate_transaction_expired_access_key() {
+        let expiry = AccessKeyExpiry::BlockHeight(10);
+        let (signer, mut state_update, _) = setup_common(
+            TESTING_INIT_BALANCE,
+            0,
+            Some(bounded_function_call_key(Some(expiry.clone()), None)),
+        );
+
+        let before_expiry = TransactionBlockInfo { height: 9, timestamp: 0, epoch_height: 1 };
+        verify_function_call(&mut state_update, &*signer, 1, before_expiry)
+            .expect("valid transaction");
+        let expired = TransactionBlockInfo { height: 10, ..before_expiry };
+        assert_eq!(
+            verify_function_call(&mut state_update, &*signer, 2, expired)
+                .expect_err("expected an error"),
+            RuntimeError::InvalidTxError(InvalidTxError::InvalidAccessKeyError(
+                InvalidAccessKeyError::AccessKeyExpired { expiry },
+            )),
+        );
+    }
+
+    #[test]
+    fn test_validate_transaction_expired_access_key_timestamp() {
+        let expiry = AccessKeyExpiry::Timestamp(1_000);
+        let (signer, mut state_update, _) = setup_common(
+            TESTING_INIT_BALANCE,
+            0,
+            Some(bounded_function_call_key(Some(expiry.clone()), None)),
+        );
+
+        let before_expiry = TransactionBlockInfo { height: 9, timestamp: 999, epoch_height: 1 };
+        verify_function_call(&mut state_update, &*signer, 1, before_expiry)
+            .expect("valid transaction");
+        let expired = TransactionBlockInfo { timestamp: 1_000, ..before_expiry };
+        assert_eq!(
+            verify_function_call(&mut state_update, &*signer, 2, expired)
+                .expect_err("expected an error"),
+            RuntimeError::InvalidTxError(InvalidTxError::InvalidAccessKeyError(
+                InvalidAccessKeyError::AccessKeyExpired { expiry },
+            )),
+        );
+    }
+
+    #[test]
+    fn test_validate_transaction_access_key_usage_limit() {
+        let usage_limit = AccessKeyUsageLimit { max_uses_per_epoch: 2, epoch_height: 0, uses: 0 };
+        let (signer, mut state_update, _) = setup_common(
+            TESTING_INIT_BALANCE,
+            0,
+            Some(bounded_function_call_key(None, Some(usage_limit))),
+        );
+
+        let epoch_1 = TransactionBlockInfo { height: 10, timestamp: 0, epoch_height: 1 };
+        verify_function_call(&mut state_update, &*signer, 1, epoch_1).expect("valid transaction");
+        verify_function_call(&mut state_update, &*signer, 2, epoch_1).expect("valid transaction");
+        assert_eq!(
+            verify_function_call(&mut state_update, &*signer, 3, epoch_1)
+                .expect_err("expected an error"),
+            RuntimeError::InvalidTxError(InvalidTxError::InvalidAccessKeyError(
+                InvalidAccessKeyError::UsageLimitExceeded { max_uses_per_epoch: 2 },
+            )),
+        );
+
+        // The usage counter starts over in the next epoch.
+        let epoch_2 = TransactionBlockInfo { epoch_height: 2, ..epoch_1 };
+        verify_function_call(&mut state_update, &*signer, 3, epoch_2).expect("valid transaction");
+        let access_key =
+            get_access_key(&state_update, &alice_account(), &signer.public_key()).unwrap().unwrap();
+        let mut expected_access_key = bounded_function_call_key(
+            None,
+            Some(AccessKeyUsageLimit { max_uses_per_epoch: 2, epoch_height: 2, uses: 1 }),
+        );
+        expected_access_key.nonce = 3;
+        assert_eq!(access_key, expected_access_key);
+    }
+
     #[test]
     fn test_validate_transaction_exceeding_tx_size_limit() {
         let (signer, mut state_update, gas_price) =
@@ -1190,8 +1472,12 @@ mod tests {
     #[test]
     fn test_validate_receipt_valid() {
         let limit_config = VMLimitConfig::test();
-        validate_receipt(&limit_config, &Receipt::new_balance_refund(&alice_account(), 10))
-            .expect("valid receipt");
+        validate_receipt(
+            &limit_config,
+            &Receipt::new_balance_refund(&alice_account(), 10),
+            PROTOCOL_VERSION,
+        )
+        .expect("valid receipt");
     }
 
     #[test]
@@ -1208,7 +1494,8 @@ mod tests {
                     output_data_receivers: vec![],
                     input_data_ids: vec![CryptoHash::default(), CryptoHash::default()],
                     actions: vec![]
-                }
+                },
+                PROTOCOL_VERSION
             )
             .expect_err("expected an error"),
             ReceiptValidationError::NumberInputDataDependenciesExceeded {
@@ -1259,7 +1546,7 @@ mod tests {
     #[test]
     fn test_validate_actions_empty() {
         let limit_config = VMLimitConfig::test();
-        validate_actions(&limit_config, &[]).expect("empty actions");
+        validate_actions(&limit_config, &[], PROTOCOL_VERSION).expect("empty actions");
     }
 
     #[test]
@@ -1273,6 +1560,7 @@ mod tests {
                 gas: 100,
                 deposit: 0,
             })],
+            PROTOCOL_VERSION,
         )
         .expect("valid function call action");
     }
@@ -1297,7 +1585,8 @@ mod tests {
                         gas: 150,
                         deposit: 0,
                     })
-                ]
+                ],
+                PROTOCOL_VERSION
             )
             .expect_err("expected an error"),
             ActionsValidationError::TotalPrepaidGasExceeded { total_prepaid_gas: 250, limit: 220 }
@@ -1324,7 +1613,8 @@ mod tests {
                         gas: u64::max_value() / 2 + 1,
                         deposit: 0,
                     })
-                ]
+                ],
+                PROTOCOL_VERSION
             )
             .expect_err("Expected an error"),
             ActionsValidationError::IntegerOverflow,
@@ -1341,7 +1631,8 @@ mod tests {
                 &[
                     Action::CreateAccount(CreateAccountAction {}),
                     Action::CreateAccount(CreateAccountAction {}),
-                ]
+                ],
+                PROTOCOL_VERSION
             )
             .expect_err("Expected an error"),
             ActionsValidationError::TotalNumberOfActionsExceeded {
@@ -1363,7 +1654,8 @@ mod tests {
                         beneficiary_id: "bob".parse().unwrap()
                     }),
                     Action::CreateAccount(CreateAccountAction {}),
-                ]
+                ],
+                PROTOCOL_VERSION
             )
             .expect_err("Expected an error"),
             ActionsValidationError::DeleteActionMustBeFinal,
@@ -1382,7 +1674,8 @@ mod tests {
                     Action::DeleteAccount(DeleteAccountAction {
                         beneficiary_id: "bob".parse().unwrap()
                     }),
-                ]
+                ],
+                PROTOCOL_VERSION
             ),
             Ok(()),
         );
@@ -1392,8 +1685,12 @@ mod tests {
 
     #[test]
     fn test_validate_action_valid_create_account() {
-        validate_action(&VMLimitConfig::test(), &Action::CreateAccount(CreateAccountAction {}))
-            .expect("valid action");
+        validate_action(
+            &VMLimitConfig::test(),
+            &Action::CreateAccount(CreateAccountAction {}),
+            PROTOCOL_VERSION,
+        )
+        .expect("valid action");
     }
 
     #[test]
@@ -1406,6 +1703,7 @@ mod tests {
                 gas: 100,
                 deposit: 0,
             }),
+            PROTOCOL_VERSION,
         )
         .expect("valid action");
     }
@@ -1421,6 +1719,7 @@ mod tests {
                     gas: 0,
                     deposit: 0,
                 }),
+                PROTOCOL_VERSION,
             )
             .expect_err("expected an error"),
             ActionsValidationError::FunctionCallZeroAttachedGas,
@@ -1429,8 +1728,12 @@ mod tests {
 
     #[test]
     fn test_validate_action_valid_transfer() {
-        validate_action(&VMLimitConfig::test(), &Action::Transfer(TransferAction { deposit: 10 }))
-            .expect("valid action");
+        validate_action(
+            &VMLimitConfig::test(),
+            &Action::Transfer(TransferAction { deposit: 10 }),
+            PROTOCOL_VERSION,
+        )
+        .expect("valid action");
     }
 
     #[test]
@@ -1441,6 +1744,7 @@ mod tests {
                 stake: 100,
                 public_key: "ed25519:KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7".parse().unwrap(),
             }),
+            PROTOCOL_VERSION,
         )
         .expect("valid action");
     }
@@ -1454,6 +1758,7 @@ mod tests {
                     stake: 100,
                     public_key: PublicKey::empty(KeyType::ED25519),
                 }),
+                PROTOCOL_VERSION,
             )
             .expect_err("Expected an error"),
             ActionsValidationError::UnsuitableStakingKey {
@@ -1470,6 +1775,7 @@ mod tests {
                 public_key: PublicKey::empty(KeyType::ED25519),
                 access_key: AccessKey::full_access(),
             }),
+            PROTOCOL_VERSION,
         )
         .expect("valid action");
     }
@@ -1489,15 +1795,123 @@ mod tests {
                     }),
                 },
             }),
+            PROTOCOL_VERSION,
         )
         .expect("valid action");
     }
 
+    fn create_secp256r1_add_key_action() -> Action {
+        Action::AddKey(AddKeyAction {
+            public_key: SecretKey::from_seed(KeyType::SECP256R1, "test").public_key(),
+            access_key: AccessKey::full_access(),
+        })
+    }
+
+    #[test]
+    #[cfg(not(feature = "protocol_feature_secp256r1"))]
+    fn test_validate_action_add_key_secp256r1_unsupported() {
+        assert_eq!(
+            validate_action(
+                &VMLimitConfig::test(),
+                &create_secp256r1_add_key_action(),
+                PROTOCOL_VERSION
+            )
+            .expect_err("expected an error"),
+            ActionsValidationError::UnsupportedProtocolFeature {
+                protocol_feature: "Secp256r1".to_string(),
+                version: PROTOCOL_VERSION,
+            },
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_secp256r1")]
+    fn test_validate_action_add_key_secp256r1() {
+        use near_primitives::version::ProtocolFeature;
+
+        let version = ProtocolFeature::Secp256r1.protocol_version();
+        validate_action(&VMLimitConfig::test(), &create_secp256r1_add_key_action(), version)
+            .expect("valid action");
+        assert_eq!(
+            validate_action(
+                &VMLimitConfig::test(),
+                &create_secp256r1_add_key_action(),
+                version - 1
+            )
+            .expect_err("expected an error"),
+            ActionsValidationError::UnsupportedProtocolFeature {
+                protocol_feature: "Secp256r1".to_string(),
+                version: version - 1,
+            },
+        );
+    }
+
+    fn create_bounded_add_key_action() -> Action {
+        Action::AddKey(AddKeyAction {
+            public_key: PublicKey::empty(KeyType::ED25519),
+            access_key: bounded_function_call_key(
+                Some(AccessKeyExpiry::BlockHeight(100)),
+                Some(AccessKeyUsageLimit { max_uses_per_epoch: 10, epoch_height: 0, uses: 0 }),
+            ),
+        })
+    }
+
+    #[test]
+    #[cfg(not(feature = "protocol_feature_bounded_access_keys"))]
+    fn test_validate_action_add_key_bounded_unsupported() {
+        assert_eq!(
+            validate_action(
+                &VMLimitConfig::test(),
+                &create_bounded_add_key_action(),
+                PROTOCOL_VERSION
+            )
+            .expect_err("expected an error"),
+            ActionsValidationError::UnsupportedProtocolFeature {
+                protocol_feature: "BoundedAccessKeys".to_string(),
+                version: PROTOCOL_VERSION,
+            },
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_bounded_access_keys")]
+    fn test_validate_action_add_key_bounded() {
+        use near_primitives::version::ProtocolFeature;
+
+        let version = ProtocolFeature::BoundedAccessKeys.protocol_version();
+        validate_action(&VMLimitConfig::test(), &create_bounded_add_key_action(), version)
+            .expect("valid action");
+        assert_eq!(
+            validate_action(&VMLimitConfig::test(), &create_bounded_add_key_action(), version - 1)
+                .expect_err("expected an error"),
+            ActionsValidationError::UnsupportedProtocolFeature {
+                protocol_feature: "BoundedAccessKeys".to_string(),
+                version: version - 1,
+            },
+        );
+
+        for (max_uses_per_epoch, uses) in [(0, 0), (2, 3)] {
+            let action = Action::AddKey(AddKeyAction {
+                public_key: PublicKey::empty(KeyType::ED25519),
+                access_key: bounded_function_call_key(
+                    None,
+                    Some(AccessKeyUsageLimit { max_uses_per_epoch, epoch_height: 0, uses }),
+                ),
+            });
+            assert_eq!(
+                validate_action(&VMLimitConfig::test(), &action, version)
+                    .expect_err("expected an error"),
+                ActionsValidationError::InvalidAccessKeyUsageLimit { max_uses_per_epoch, uses },
+            );
+        }
+    }
+
     #[test]
     fn test_validate_action_valid_delete_key() {
         validate_action(
             &VMLimitConfig::test(),
             &Action::DeleteKey(DeleteKeyAction { public_key: PublicKey::empty(KeyType::ED25519) }),
+            PROTOCOL_VERSION,
         )
         .expect("valid action");
     }
@@ -1507,7 +1921,78 @@ mod tests {
         validate_action(
             &VMLimitConfig::test(),
             &Action::DeleteAccount(DeleteAccountAction { beneficiary_id: alice_account() }),
+            PROTOCOL_VERSION,
         )
         .expect("valid action");
     }
+
+    fn create_delegate_action(actions: Vec<Action>) -> Action {
+        let signer =
+            InMemorySigner::from_seed(alice_account(), KeyType::ED25519, alice_account().as_ref());
+        let delegate_action = DelegateAction {
+            sender_id: alice_account(),
+            receiver_id: bob_account(),
+            actions: actions.into_iter().map(|a| a.try_into().unwrap()).collect(),
+            nonce: 1,
+            max_block_height: 100,
+            public_key: signer.public_key(),
+        };
+        Action::Delegate(SignedDelegateAction {
+            signature: signer.sign(delegate_action.get_hash().as_ref()),
+            delegate_action,
+        })
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_nep366_delegate_action")]
+    fn test_validate_delegate_action_unsupported_version() {
+        let version =
+            near_primitives::version::ProtocolFeature::DelegateAction.protocol_version() - 1;
+        assert_eq!(
+            validate_action(
+                &VMLimitConfig::test(),
+                &create_delegate_action(vec![Action::Transfer(TransferAction { deposit: 1 })]),
+                version,
+            )
+            .expect_err("expected an error"),
+            ActionsValidationError::UnsupportedProtocolFeature {
+                protocol_feature: "DelegateAction".to_string(),
+                version,
+            },
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_nep366_delegate_action")]
+    fn test_validate_delegate_action() {
+        let limit_config = VMLimitConfig::test();
+        let delegate_action =
+            create_delegate_action(vec![Action::Transfer(TransferAction { deposit: 1 })]);
+        validate_actions(&limit_config, &[delegate_action.clone()], PROTOCOL_VERSION)
+            .expect("valid action");
+        assert_eq!(
+            validate_actions(
+                &limit_config,
+                &[delegate_action.clone(), delegate_action],
+                PROTOCOL_VERSION
+            )
+            .expect_err("expected an error"),
+            ActionsValidationError::DelegateActionMustBeOnlyOne,
+        );
+        // Inner actions are validated as well.
+        assert_eq!(
+            validate_action(
+                &limit_config,
+                &create_delegate_action(vec![Action::FunctionCall(FunctionCallAction {
+                    method_name: "new".to_string(),
+                    args: vec![],
+                    gas: 0,
+                    deposit: 0,
+                })]),
+                PROTOCOL_VERSION,
+            )
+            .expect_err("expected an error"),
+            ActionsValidationError::FunctionCallZeroAttachedGas,
+        );
+    }
 }
@@ -661,6 +661,11 @@ impl Account {
                         function_call_keys.push(key.signer.clone())
                     }
                 }
+                AccessKeyPermission::FunctionCallV2(permission) => {
+                    if permission.function_call.receiver_id == receiver_id {
+                        function_call_keys.push(key.signer.clone())
+                    }
+                }
             }
         }
         function_call_keys
@@ -44,6 +44,7 @@ fn main() {
         store,
         false,
     );
+    store_validator.set_retained_accounts(near_config.client_config.gc.retained_accounts.clone());
     store_validator.validate();
 
     if store_validator.tests_done() == 0 {
@@ -0,0 +1,33 @@
+use crate::compact::CompactCommand;
+use anyhow::Context;
+use clap::Parser;
+use std::path::Path;
+
+#[derive(Parser)]
+pub struct DatabaseCommand {
+    #[clap(subcommand)]
+    subcmd: SubCommand,
+}
+
+#[derive(Parser)]
+#[clap(subcommand_required = true, arg_required_else_help = true)]
+enum SubCommand {
+    /// Run manual compaction of the database or of a single column.
+    ///
+    /// The node must be stopped while the command is running.  Compaction is
+    /// blocking and may take hours on large columns such as `State`.
+    Compact(CompactCommand),
+}
+
+impl DatabaseCommand {
+    pub fn run(&self, home_dir: &Path) -> anyhow::Result<()> {
+        let near_config = nearcore::config::load_config(
+            &home_dir,
+            near_chain_configs::GenesisValidationMode::UnsafeFast,
+        )
+        .context("Error loading config")?;
+        match &self.subcmd {
+            SubCommand::Compact(cmd) => cmd.run(home_dir, &near_config),
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use anyhow::Context;
+use clap::Parser;
+use near_store::{DBCol, Mode, NodeStorage, Temperature};
+use nearcore::NearConfig;
+use std::path::Path;
+use strum::IntoEnumIterator;
+
+#[derive(Parser)]
+pub(crate) struct CompactCommand {
+    /// Name of the column to compact, e.g. `State`.  If not given, all
+    /// columns are compacted.
+    #[clap(long)]
+    column: Option<String>,
+}
+
+impl CompactCommand {
+    pub(crate) fn run(&self, home_dir: &Path, near_config: &NearConfig) -> anyhow::Result<()> {
+        let column = self.column.as_deref().map(parse_column).transpose()?;
+        let storage = NodeStorage::opener(home_dir, &near_config.config.store, None)
+            .open_in_mode(Mode::ReadWriteExisting)
+            .context("Error opening storage")?;
+        let store = storage.get_store(Temperature::Hot);
+        match column {
+            Some(col) => {
+                eprintln!("Compacting {col}...");
+                store.compact_column(col)?;
+            }
+            None => {
+                eprintln!("Compacting all columns...");
+                store.compact()?;
+            }
+        }
+        eprintln!("Done");
+        Ok(())
+    }
+}
+
+fn parse_column(name: &str) -> anyhow::Result<DBCol> {
+    DBCol::iter().find(|col| <&str>::from(*col) == name).ok_or_else(|| {
+        let known = DBCol::iter().map(<&str>::from).collect::<Vec<_>>().join(", ");
+        anyhow::anyhow!("Unknown column {name}; expected one of: {known}")
+    })
+}
@@ -0,0 +1,4 @@
+pub mod commands;
+mod compact;
+
+pub use commands::DatabaseCommand;
@@ -67,7 +67,7 @@ pub fn map_records<P: AsRef<Path>>(
                 }
                 records_seq.serialize_element(&r).unwrap();
             }
-            StateRecord::DelayedReceipt(receipt) => {
+            StateRecord::DelayedReceipt(receipt) | StateRecord::BufferedReceipt { receipt, .. } => {
                 if receipt.predecessor_id.is_implicit() || receipt.receiver_id.is_implicit() {
                     receipt.predecessor_id =
                         crate::key_mapping::map_account(&receipt.predecessor_id, secret.as_ref());
@@ -1,6 +1,9 @@
 use borsh::BorshDeserialize;
 use hkdf::Hkdf;
-use near_crypto::{ED25519PublicKey, ED25519SecretKey, PublicKey, Secp256K1PublicKey, SecretKey};
+use near_crypto::{
+    ED25519PublicKey, ED25519SecretKey, PublicKey, Secp256K1PublicKey, Secp256R1PublicKey,
+    SecretKey,
+};
 use near_primitives::types::AccountId;
 use sha2::Sha256;
 
@@ -69,6 +72,33 @@ fn map_secp256k1(
     secp256k1_from_slice(&mut buf, public)
 }
 
+fn map_secp256r1(
+    public: &Secp256R1PublicKey,
+    secret: Option<&[u8; crate::secret::SECRET_LEN]>,
+) -> p256::ecdsa::SigningKey {
+    let mut buf = [0; 32];
+
+    match secret {
+        Some(secret) => {
+            let hk = Hkdf::<Sha256>::new(None, secret);
+            hk.expand(public.as_ref(), &mut buf).unwrap();
+        }
+        None => {
+            buf.copy_from_slice(&public.as_ref()[..32]);
+        }
+    };
+
+    match p256::ecdsa::SigningKey::from_bytes(&buf) {
+        Ok(s) => s,
+        Err(_) => {
+            tracing::warn!(target: "mirror", "Something super unlikely occurred! SECP256R1 key mapped from {:?} is too large. Flipping most significant bit.", public);
+            // Same as for secp256k1, the order of the P-256 curve starts with 0xFF.
+            buf[0] ^= 0x80;
+            p256::ecdsa::SigningKey::from_bytes(&buf).unwrap()
+        }
+    }
+}
+
 // This maps the public key to a secret key so that we can sign
 // transactions on the target chain.  If secret is None, then we just
 // use the bytes of the public key directly, otherwise we feed the
@@ -80,6 +110,7 @@ pub(crate) fn map_key(
     match key {
         PublicKey::ED25519(k) => SecretKey::ED25519(map_ed25519(k, secret)),
         PublicKey::SECP256K1(k) => SecretKey::SECP256K1(map_secp256k1(k, secret)),
+        PublicKey::SECP256R1(k) => SecretKey::SECP256R1(map_secp256r1(k, secret)),
     }
 }
 
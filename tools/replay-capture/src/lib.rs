@@ -0,0 +1,70 @@
+use anyhow::Context;
+use clap::Parser;
+use near_network::capture;
+use near_network::raw::Connection;
+use near_network::time;
+use near_network::types::PeerInfo;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::BlockHeight;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Replays network traffic captured by a node (see `experimental.capture` in
+/// the network config) to another node, preserving the original timing.
+#[derive(Parser)]
+pub struct ReplayCaptureCommand {
+    /// Capture of a single peer: either a directory with capture files or a
+    /// single capture file.
+    #[clap(long)]
+    capture: PathBuf,
+    /// Public key and socket address of the node to replay the capture to, in
+    /// the format {pub key}@{socket addr}.
+    #[clap(long)]
+    peer: String,
+    #[clap(long)]
+    chain_id: String,
+    /// Genesis hash to use in the Handshake we send.
+    #[clap(long)]
+    genesis_hash: String,
+    /// Head height to use in the Handshake we send.
+    #[clap(long, default_value = "0")]
+    head_height: BlockHeight,
+    /// Protocol version to advertise in our handshake.
+    #[clap(long)]
+    protocol_version: Option<u32>,
+    /// Timeout for the handshake and for receiving messages from the node.
+    #[clap(long, default_value = "5")]
+    recv_timeout_seconds: u32,
+}
+
+impl ReplayCaptureCommand {
+    pub fn run(&self) -> anyhow::Result<()> {
+        let records = if self.capture.is_dir() {
+            capture::read_dir(&self.capture)?
+        } else {
+            capture::read_file(&self.capture)?
+        };
+        let genesis_hash = CryptoHash::from_str(&self.genesis_hash)
+            .map_err(|err| anyhow::anyhow!("--genesis-hash: {err}"))?;
+        let peer = PeerInfo::from_str(&self.peer).context("--peer")?;
+        let addr = peer.addr.context("--peer should be in the form [public key]@[socket addr]")?;
+        tracing::info!(target: "replay", records = records.len(), "loaded the capture");
+
+        let runtime = tokio::runtime::Runtime::new()?;
+        runtime.block_on(async {
+            let mut conn = Connection::connect(
+                addr,
+                peer.id.clone(),
+                self.protocol_version,
+                &self.chain_id,
+                genesis_hash,
+                self.head_height,
+                time::Duration::seconds(self.recv_timeout_seconds.into()),
+            )
+            .await?;
+            let sent = capture::replay(&time::Clock::real(), &mut conn, &records).await?;
+            println!("replayed {sent} messages to {peer}");
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,252 @@
+use clap::Parser;
+use near_primitives::runtime::config_store::{resolve_parameters, ResolvedParameter};
+use near_primitives::types::ProtocolVersion;
+use near_primitives::version::PROTOCOL_VERSION;
+use near_primitives_core::parameter::Parameter;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Inspect the runtime parameters, i.e. fees and limits, of a protocol version.
+#[derive(Parser)]
+pub struct RuntimeParamsCommand {
+    #[clap(subcommand)]
+    subcmd: SubCommand,
+}
+
+#[derive(Parser)]
+#[clap(subcommand_required = true, arg_required_else_help = true)]
+enum SubCommand {
+    /// Print the value of every parameter at the given protocol version,
+    /// together with the config file which set it.
+    Show(ShowCmd),
+    /// Print the parameters whose values differ between two protocol versions,
+    /// together with the config file which changed them.
+    Diff(DiffCmd),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ArgEnum)]
+enum Format {
+    Table,
+    Json,
+}
+
+#[derive(Parser)]
+struct ShowCmd {
+    /// Protocol version, by default the latest one supported by this binary.
+    #[clap(long, default_value_t = PROTOCOL_VERSION)]
+    protocol_version: ProtocolVersion,
+    /// Chain whose parameters to resolve.  Only `testnet` has parameters
+    /// which differ from the ones of `mainnet`, and only for old versions.
+    #[clap(long, default_value = "mainnet")]
+    chain_id: String,
+    #[clap(long, arg_enum, default_value = "table")]
+    format: Format,
+}
+
+#[derive(Parser)]
+struct DiffCmd {
+    /// Protocol version to compare from.
+    from: ProtocolVersion,
+    /// Protocol version to compare to.
+    to: ProtocolVersion,
+    /// Chain whose parameters to resolve.
+    #[clap(long, default_value = "mainnet")]
+    chain_id: String,
+    #[clap(long, arg_enum, default_value = "table")]
+    format: Format,
+}
+
+impl RuntimeParamsCommand {
+    pub fn run(self) -> anyhow::Result<()> {
+        let output = match self.subcmd {
+            SubCommand::Show(cmd) => {
+                let params = resolve_parameters(&cmd.chain_id, cmd.protocol_version);
+                match cmd.format {
+                    Format::Table => show_table(&params),
+                    Format::Json => serde_json::to_string_pretty(&show_json(&params))?,
+                }
+            }
+            SubCommand::Diff(cmd) => {
+                let changes = diff_parameters(&cmd.chain_id, cmd.from, cmd.to);
+                match cmd.format {
+                    Format::Table => diff_table(&changes),
+                    Format::Json => serde_json::to_string_pretty(&changes)?,
+                }
+            }
+        };
+        println!("{output}");
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct ParameterJson<'a> {
+    value: &'a serde_json::Value,
+    file: &'a str,
+}
+
+fn show_json(params: &BTreeMap<Parameter, ResolvedParameter>) -> BTreeMap<String, ParameterJson> {
+    params
+        .iter()
+        .map(|(param, resolved)| {
+            (param.to_string(), ParameterJson { value: &resolved.value, file: &resolved.file })
+        })
+        .collect()
+}
+
+fn show_table(params: &BTreeMap<Parameter, ResolvedParameter>) -> String {
+    let rows = params
+        .iter()
+        .map(|(param, resolved)| {
+            [param.to_string(), display_value(Some(&resolved.value)), resolved.file.clone()]
+        })
+        .collect::<Vec<_>>();
+    format_table(["parameter", "value", "file"], &rows)
+}
+
+/// A parameter whose value differs between two protocol versions.
+#[derive(Debug, PartialEq, Serialize)]
+struct ParameterChange {
+    parameter: String,
+    /// Value in the `from` version, `None` if the parameter is not defined.
+    from: Option<serde_json::Value>,
+    /// Value in the `to` version, `None` if the parameter is not defined.
+    to: Option<serde_json::Value>,
+    /// Config file of the later of both versions which set the value.
+    file: String,
+}
+
+fn diff_parameters(
+    chain_id: &str,
+    from: ProtocolVersion,
+    to: ProtocolVersion,
+) -> Vec<ParameterChange> {
+    let from_params = resolve_parameters(chain_id, from);
+    let to_params = resolve_parameters(chain_id, to);
+    let later_params = if from <= to { &to_params } else { &from_params };
+
+    let mut all_params = from_params.keys().chain(to_params.keys()).collect::<Vec<_>>();
+    all_params.sort();
+    all_params.dedup();
+
+    let mut changes = vec![];
+    for param in all_params {
+        let from_value = from_params.get(param).map(|resolved| &resolved.value);
+        let to_value = to_params.get(param).map(|resolved| &resolved.value);
+        if from_value == to_value {
+            continue;
+        }
+        // A parameter missing in the later version was removed by a diff which
+        // is not recorded, thus only the earlier file can be shown then.
+        let file = later_params
+            .get(param)
+            .or_else(|| from_params.get(param))
+            .or_else(|| to_params.get(param))
+            .map(|resolved| resolved.file.clone())
+            .unwrap_or_default();
+        changes.push(ParameterChange {
+            parameter: param.to_string(),
+            from: from_value.cloned(),
+            to: to_value.cloned(),
+            file,
+        });
+    }
+    changes
+}
+
+fn diff_table(changes: &[ParameterChange]) -> String {
+    let rows = changes
+        .iter()
+        .map(|change| {
+            [
+                change.parameter.clone(),
+                display_value(change.from.as_ref()),
+                display_value(change.to.as_ref()),
+                change.file.clone(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    format_table(["parameter", "from", "to", "file"], &rows)
+}
+
+/// Formats a value the same way as in the parameter files.
+fn display_value(value: Option<&serde_json::Value>) -> String {
+    match value {
+        None => "-".to_owned(),
+        Some(serde_json::Value::Null) => String::new(),
+        Some(serde_json::Value::String(value)) => value.clone(),
+        Some(serde_json::Value::Object(cost)) => {
+            format!("{{ gas: {}, compute: {} }}", cost["gas"], cost["compute"])
+        }
+        Some(value) => value.to_string(),
+    }
+}
+
+fn format_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) -> String {
+    let mut widths = header.map(str::len);
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let format_row = |cells: &mut dyn Iterator<Item = &str>| {
+        let line = cells
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        line.trim_end().to_owned()
+    };
+
+    let separator = widths.map(|width| "-".repeat(width));
+    let mut lines = vec![
+        format_row(&mut header.into_iter()),
+        format_row(&mut separator.iter().map(String::as_str)),
+    ];
+    lines.extend(rows.iter().map(|row| format_row(&mut row.iter().map(String::as_str))));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_parameters() {
+        assert_eq!(
+            diff_parameters("mainnet", 52, 53),
+            diff_parameters("mainnet", 53, 52)
+                .into_iter()
+                .map(|change| ParameterChange { from: change.to, to: change.from, ..change })
+                .collect::<Vec<_>>()
+        );
+
+        let changes = diff_parameters("mainnet", 52, 53);
+        let change =
+            changes.iter().find(|change| change.parameter == "max_length_storage_key").unwrap();
+        assert_eq!(
+            change,
+            &ParameterChange {
+                parameter: "max_length_storage_key".to_owned(),
+                from: Some(4_194_304.into()),
+                to: Some(2_048.into()),
+                file: "53.txt".to_owned(),
+            }
+        );
+        let change =
+            changes.iter().find(|change| change.parameter == "max_locals_per_contract").unwrap();
+        assert_eq!((&change.from, &change.file), (&None, &"53.txt".to_owned()));
+        assert!(changes.iter().all(|change| change.file == "53.txt"));
+
+        assert_eq!(diff_parameters("mainnet", 53, 53), vec![]);
+    }
+
+    #[test]
+    fn test_format_table() {
+        let rows = [["a".to_owned(), "1".to_owned()], ["long_name".to_owned(), "".to_owned()]];
+        assert_eq!(
+            format_table(["parameter", "value"], &rows),
+            "parameter  value\n---------  -----\na          1\nlong_name"
+        );
+    }
+}
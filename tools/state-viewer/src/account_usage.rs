@@ -0,0 +1,422 @@
+use anyhow::Context;
+use near_chain::{ChainStore, ChainStoreAccess};
+use near_epoch_manager::EpochManagerAdapter;
+use near_primitives::block::Block;
+use near_primitives::serialize::dec_format;
+use near_primitives::types::{AccountId, Balance, BlockHeight, Gas, ShardId, StateChangeValue};
+use near_store::Store;
+use nearcore::{NearConfig, NightshadeRuntime};
+use node_runtime::adapter::ViewRuntimeAdapter;
+use node_runtime::state_viewer::errors::ViewAccountError;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Gas, tokens, receipts and storage attributed to a single account over a
+/// range of blocks.
+#[derive(Serialize, Default, Debug, Clone, PartialEq)]
+pub(crate) struct AccountUsage {
+    /// Gas burnt by receipts executed on the account.
+    pub gas_burnt_as_receiver: Gas,
+    /// Gas burnt by receipts the account sent and by transactions it signed.
+    pub gas_burnt_as_predecessor: Gas,
+    #[serde(with = "dec_format")]
+    pub tokens_burnt_as_receiver: Balance,
+    #[serde(with = "dec_format")]
+    pub tokens_burnt_as_predecessor: Balance,
+    pub receipts_received: u64,
+    pub receipts_sent: u64,
+    pub transactions_signed: u64,
+    /// Sum of all increases of the account's `storage_usage`.
+    pub storage_bytes_added: u64,
+    /// Sum of all decreases of the account's `storage_usage`, including the
+    /// whole storage of deleted accounts.
+    pub storage_bytes_removed: u64,
+}
+
+#[derive(clap::ArgEnum, Debug, Clone, Copy, PartialEq)]
+pub(crate) enum SortBy {
+    GasBurntAsReceiver,
+    GasBurntAsPredecessor,
+    TokensBurntAsReceiver,
+    TokensBurntAsPredecessor,
+    ReceiptsReceived,
+    ReceiptsSent,
+    TransactionsSigned,
+    StorageBytesAdded,
+    StorageBytesRemoved,
+}
+
+#[derive(clap::ArgEnum, Debug, Clone, Copy, PartialEq)]
+pub(crate) enum OutputFormat {
+    Csv,
+    Json,
+}
+
+/// Aggregates [`AccountUsage`] of all accounts seen in the processed blocks.
+#[derive(Default)]
+pub(crate) struct AccountUsageTable {
+    accounts: HashMap<AccountId, AccountUsage>,
+    /// Last known `storage_usage` of every account whose state changed.
+    storage_usage: HashMap<AccountId, u64>,
+}
+
+impl AccountUsageTable {
+    /// Records the outcome of a receipt sent by `predecessor_id` to `receiver_id`.
+    pub(crate) fn add_receipt_outcome(
+        &mut self,
+        receiver_id: &AccountId,
+        predecessor_id: Option<&AccountId>,
+        gas_burnt: Gas,
+        tokens_burnt: Balance,
+    ) {
+        let receiver = self.accounts.entry(receiver_id.clone()).or_default();
+        receiver.gas_burnt_as_receiver += gas_burnt;
+        receiver.tokens_burnt_as_receiver += tokens_burnt;
+        receiver.receipts_received += 1;
+        if let Some(predecessor_id) = predecessor_id {
+            let predecessor = self.accounts.entry(predecessor_id.clone()).or_default();
+            predecessor.gas_burnt_as_predecessor += gas_burnt;
+            predecessor.tokens_burnt_as_predecessor += tokens_burnt;
+            predecessor.receipts_sent += 1;
+        }
+    }
+
+    /// Records the outcome of converting a transaction into a receipt.  The
+    /// signer pays for it, hence the costs count as the signer's as predecessor.
+    pub(crate) fn add_transaction_outcome(
+        &mut self,
+        signer_id: &AccountId,
+        gas_burnt: Gas,
+        tokens_burnt: Balance,
+    ) {
+        let signer = self.accounts.entry(signer_id.clone()).or_default();
+        signer.gas_burnt_as_predecessor += gas_burnt;
+        signer.tokens_burnt_as_predecessor += tokens_burnt;
+        signer.transactions_signed += 1;
+    }
+
+    /// Returns the last recorded `storage_usage` of the account, if any.
+    pub(crate) fn storage_usage(&self, account_id: &AccountId) -> Option<u64> {
+        self.storage_usage.get(account_id).copied()
+    }
+
+    /// Records a change of the account's `storage_usage`.
+    pub(crate) fn update_storage_usage(&mut self, account_id: AccountId, before: u64, after: u64) {
+        let usage = self.accounts.entry(account_id.clone()).or_default();
+        if after > before {
+            usage.storage_bytes_added += after - before;
+        } else {
+            usage.storage_bytes_removed += before - after;
+        }
+        self.storage_usage.insert(account_id, after);
+    }
+
+    /// Returns all accounts sorted by the given column in descending order.
+    pub(crate) fn sorted(&self, sort_by: SortBy) -> Vec<(&AccountId, &AccountUsage)> {
+        let mut res = self.accounts.iter().collect::<Vec<_>>();
+        res.sort_by(|(a_id, a), (b_id, b)| {
+            sort_key(b, sort_by).cmp(&sort_key(a, sort_by)).then_with(|| a_id.cmp(b_id))
+        });
+        res
+    }
+}
+
+fn sort_key(usage: &AccountUsage, sort_by: SortBy) -> u128 {
+    match sort_by {
+        SortBy::GasBurntAsReceiver => usage.gas_burnt_as_receiver.into(),
+        SortBy::GasBurntAsPredecessor => usage.gas_burnt_as_predecessor.into(),
+        SortBy::TokensBurntAsReceiver => usage.tokens_burnt_as_receiver,
+        SortBy::TokensBurntAsPredecessor => usage.tokens_burnt_as_predecessor,
+        SortBy::ReceiptsReceived => usage.receipts_received.into(),
+        SortBy::ReceiptsSent => usage.receipts_sent.into(),
+        SortBy::TransactionsSigned => usage.transactions_signed.into(),
+        SortBy::StorageBytesAdded => usage.storage_bytes_added.into(),
+        SortBy::StorageBytesRemoved => usage.storage_bytes_removed.into(),
+    }
+}
+
+pub(crate) fn format_csv(rows: &[(&AccountId, &AccountUsage)]) -> String {
+    let mut res = String::from(
+        "account_id,gas_burnt_as_receiver,gas_burnt_as_predecessor,\
+        tokens_burnt_as_receiver,tokens_burnt_as_predecessor,\
+        receipts_received,receipts_sent,transactions_signed,\
+        storage_bytes_added,storage_bytes_removed\n",
+    );
+    for (account_id, usage) in rows {
+        writeln!(
+            res,
+            "{},{},{},{},{},{},{},{},{},{}",
+            account_id,
+            usage.gas_burnt_as_receiver,
+            usage.gas_burnt_as_predecessor,
+            usage.tokens_burnt_as_receiver,
+            usage.tokens_burnt_as_predecessor,
+            usage.receipts_received,
+            usage.receipts_sent,
+            usage.transactions_signed,
+            usage.storage_bytes_added,
+            usage.storage_bytes_removed,
+        )
+        .unwrap();
+    }
+    res
+}
+
+pub(crate) fn format_json(rows: &[(&AccountId, &AccountUsage)]) -> serde_json::Result<String> {
+    #[derive(Serialize)]
+    struct Row<'a> {
+        account_id: &'a AccountId,
+        #[serde(flatten)]
+        usage: &'a AccountUsage,
+    }
+    let rows = rows.iter().map(|(account_id, usage)| Row { account_id, usage }).collect::<Vec<_>>();
+    serde_json::to_string_pretty(&rows)
+}
+
+/// Walks the blocks from `start_height` to `end_height`, inclusive, and
+/// aggregates the execution outcomes and account state changes of all shards
+/// per account.  Requires an archival node, as both are garbage collected
+/// otherwise.
+pub(crate) fn account_usage(
+    start_height: BlockHeight,
+    end_height: BlockHeight,
+    home_dir: &Path,
+    near_config: NearConfig,
+    store: Store,
+) -> anyhow::Result<AccountUsageTable> {
+    let chain_store = ChainStore::new(
+        store.clone(),
+        near_config.genesis.config.genesis_height,
+        !near_config.client_config.archive,
+    );
+    let runtime = NightshadeRuntime::from_config(home_dir, store, &near_config);
+    collect_account_usage(start_height, end_height, &chain_store, &runtime)
+}
+
+fn collect_account_usage(
+    start_height: BlockHeight,
+    end_height: BlockHeight,
+    chain_store: &ChainStore,
+    runtime: &NightshadeRuntime,
+) -> anyhow::Result<AccountUsageTable> {
+    let mut table = AccountUsageTable::default();
+    for height in start_height..=end_height {
+        let block_hash = match chain_store.get_block_hash_by_height(height) {
+            Ok(block_hash) => block_hash,
+            // Skipped height.
+            Err(near_chain::Error::DBNotFoundErr(_)) => continue,
+            Err(err) => return Err(err.into()),
+        };
+        let block = chain_store.get_block(&block_hash)?;
+
+        for shard_id in 0..block.chunks().len() as ShardId {
+            let outcome_ids =
+                chain_store.get_outcomes_by_block_hash_and_shard_id(&block_hash, shard_id)?;
+            for outcome_id in outcome_ids {
+                let outcome = chain_store
+                    .get_outcome_by_id_and_block_hash(&outcome_id, &block_hash)?
+                    .with_context(|| format!("missing outcome {outcome_id} in {block_hash}"))?
+                    .outcome;
+                if chain_store.get_transaction(&outcome_id)?.is_some() {
+                    table.add_transaction_outcome(
+                        &outcome.executor_id,
+                        outcome.gas_burnt,
+                        outcome.tokens_burnt,
+                    );
+                } else {
+                    // The predecessor is unknown if the receipt itself is
+                    // missing, the costs are then only counted for the receiver.
+                    let receipt = chain_store.get_receipt(&outcome_id)?;
+                    table.add_receipt_outcome(
+                        &outcome.executor_id,
+                        receipt.as_ref().map(|receipt| &receipt.predecessor_id),
+                        outcome.gas_burnt,
+                        outcome.tokens_burnt,
+                    );
+                }
+            }
+        }
+
+        for change in chain_store.get_state_changes_with_cause_in_block(&block_hash)? {
+            let (account_id, after) = match change.value {
+                StateChangeValue::AccountUpdate { account_id, account } => {
+                    (account_id, account.storage_usage())
+                }
+                StateChangeValue::AccountDeletion { account_id } => (account_id, 0),
+                _ => continue,
+            };
+            let before = match table.storage_usage(&account_id) {
+                Some(before) => before,
+                None => storage_usage_before_block(chain_store, runtime, &block, &account_id)?,
+            };
+            table.update_storage_usage(account_id, before, after);
+        }
+    }
+    Ok(table)
+}
+
+/// Reads the `storage_usage` of the account from the state the block was
+/// applied on.  Accounts which didn't exist yet have no storage.
+fn storage_usage_before_block(
+    chain_store: &ChainStore,
+    runtime: &NightshadeRuntime,
+    block: &Block,
+    account_id: &AccountId,
+) -> anyhow::Result<u64> {
+    let prev_hash = block.header().prev_hash();
+    let prev_epoch_id = chain_store.get_block_header(prev_hash)?.epoch_id().clone();
+    let shard_id = runtime.account_id_to_shard_id(account_id, &prev_epoch_id)?;
+    let shard_uid = runtime.shard_id_to_uid(shard_id, &prev_epoch_id)?;
+    let state_root = *chain_store.get_chunk_extra(prev_hash, &shard_uid)?.state_root();
+    match runtime.view_account(&shard_uid, state_root, account_id) {
+        Ok(account) => Ok(account.storage_usage()),
+        Err(ViewAccountError::AccountDoesNotExist { .. }) => Ok(0),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_chain::ChainGenesis;
+    use near_chain_configs::Genesis;
+    use near_client::test_utils::TestEnv;
+    use near_crypto::{InMemorySigner, KeyType};
+    use near_primitives::shard_layout::ShardUId;
+    use near_primitives::transaction::SignedTransaction;
+    use near_store::test_utils::create_test_store;
+    use nearcore::config::GenesisExt;
+    use std::sync::Arc;
+
+    fn account(id: &str) -> AccountId {
+        id.parse().unwrap()
+    }
+
+    #[test]
+    fn test_account_usage_table() {
+        let mut table = AccountUsageTable::default();
+        table.add_transaction_outcome(&account("alice.near"), 100, 10);
+        table.add_receipt_outcome(&account("app.near"), Some(&account("alice.near")), 500, 50);
+        table.add_receipt_outcome(&account("app.near"), None, 200, 20);
+        table.add_receipt_outcome(&account("alice.near"), Some(&account("system")), 20, 0);
+
+        assert_eq!(table.storage_usage(&account("app.near")), None);
+        table.update_storage_usage(account("app.near"), 1000, 1500);
+        table.update_storage_usage(account("app.near"), 1500, 1200);
+        table.update_storage_usage(account("bob.near"), 300, 0);
+        assert_eq!(table.storage_usage(&account("app.near")), Some(1200));
+
+        let rows = table.sorted(SortBy::GasBurntAsReceiver);
+        let ids = rows.iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, ["app.near", "alice.near", "bob.near", "system"]);
+        assert_eq!(
+            rows[0].1,
+            &AccountUsage {
+                gas_burnt_as_receiver: 700,
+                tokens_burnt_as_receiver: 70,
+                receipts_received: 2,
+                storage_bytes_added: 500,
+                storage_bytes_removed: 300,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            rows[1].1,
+            &AccountUsage {
+                gas_burnt_as_receiver: 20,
+                gas_burnt_as_predecessor: 600,
+                tokens_burnt_as_predecessor: 60,
+                receipts_received: 1,
+                receipts_sent: 1,
+                transactions_signed: 1,
+                ..Default::default()
+            }
+        );
+
+        let rows = table.sorted(SortBy::StorageBytesRemoved);
+        let ids = rows.iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, ["app.near", "bob.near", "alice.near", "system"]);
+    }
+
+    #[test]
+    fn test_format_account_usage() {
+        let mut table = AccountUsageTable::default();
+        table.add_receipt_outcome(&account("app.near"), Some(&account("alice.near")), 500, 50);
+        let rows = table.sorted(SortBy::GasBurntAsReceiver);
+
+        assert_eq!(
+            format_csv(&rows),
+            "account_id,gas_burnt_as_receiver,gas_burnt_as_predecessor,\
+            tokens_burnt_as_receiver,tokens_burnt_as_predecessor,\
+            receipts_received,receipts_sent,transactions_signed,\
+            storage_bytes_added,storage_bytes_removed\n\
+            app.near,500,0,50,0,1,0,0,0,0\n\
+            alice.near,0,500,0,50,0,1,0,0,0\n"
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&format_json(&rows).unwrap()).unwrap();
+        assert_eq!(json[0]["account_id"], "app.near");
+        assert_eq!(json[0]["tokens_burnt_as_receiver"], "50");
+        assert_eq!(json[1]["gas_burnt_as_predecessor"], 500);
+    }
+
+    #[test]
+    fn test_collect_account_usage() {
+        let genesis = Genesis::test(vec![account("test0"), account("test1")], 1);
+        let store = create_test_store();
+        let runtime = NightshadeRuntime::test(Path::new("."), store.clone(), &genesis);
+        let mut chain_genesis = ChainGenesis::test();
+        chain_genesis.gas_limit = genesis.config.gas_limit;
+        let mut env =
+            TestEnv::builder(chain_genesis).runtime_adapters(vec![Arc::new(runtime)]).build();
+
+        let genesis_hash = *env.clients[0].chain.genesis().hash();
+        let signer = InMemorySigner::from_seed(account("test1"), KeyType::ED25519, "test1");
+        let tx = SignedTransaction::create_account(
+            1,
+            account("test1"),
+            account("alice.test1"),
+            10u128.pow(24),
+            signer.public_key.clone(),
+            &signer,
+            genesis_hash,
+        );
+        env.clients[0].process_tx(tx, false, false);
+        // Enough blocks for the transaction, its receipt and the gas refund.
+        for height in 1..=8 {
+            env.produce_block(0, height);
+        }
+
+        let chain_store = ChainStore::new(store.clone(), genesis.config.genesis_height, true);
+        let runtime = NightshadeRuntime::test(Path::new("."), store, &genesis);
+        // Heights past the head have no blocks and are skipped.
+        let table = collect_account_usage(1, 12, &chain_store, &runtime).unwrap();
+
+        let signer_usage = &table.accounts[&account("test1")];
+        assert_eq!(signer_usage.transactions_signed, 1);
+        assert_eq!(signer_usage.receipts_sent, 1);
+        // The gas refund.
+        assert_eq!(signer_usage.receipts_received, 1);
+        assert!(signer_usage.gas_burnt_as_predecessor > 0);
+        assert_eq!(signer_usage.storage_bytes_added, 0);
+        assert_eq!(signer_usage.storage_bytes_removed, 0);
+
+        let head = env.clients[0].chain.head().unwrap();
+        let state_root = *chain_store
+            .get_chunk_extra(&head.last_block_hash, &ShardUId::single_shard())
+            .unwrap()
+            .state_root();
+        let new_account = runtime
+            .view_account(&ShardUId::single_shard(), state_root, &account("alice.test1"))
+            .unwrap();
+        let new_account_usage = &table.accounts[&account("alice.test1")];
+        assert_eq!(new_account_usage.receipts_received, 1);
+        assert_eq!(new_account_usage.receipts_sent, 0);
+        assert!(new_account_usage.gas_burnt_as_receiver > 0);
+        assert_eq!(new_account_usage.gas_burnt_as_predecessor, 0);
+        assert_eq!(new_account_usage.storage_bytes_added, new_account.storage_usage());
+        assert_eq!(new_account_usage.storage_bytes_removed, 0);
+    }
+}
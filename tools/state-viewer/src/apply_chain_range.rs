@@ -11,6 +11,7 @@ use near_chain::types::ApplyTransactionResult;
 use near_chain::{ChainStore, ChainStoreAccess, ChainStoreUpdate, RuntimeAdapter};
 use near_chain_configs::Genesis;
 use near_primitives::borsh::maybestd::sync::Arc;
+use near_primitives::congestion_info::BlockCongestionInfo;
 use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::DelayedReceiptIndices;
 use near_primitives::transaction::{Action, ExecutionOutcomeWithId, ExecutionOutcomeWithProof};
@@ -242,6 +243,7 @@ fn apply_block_from_range(
                 prev_block.header().gas_price(),
                 chunk_inner.gas_limit(),
                 block.header().challenges_result(),
+                &BlockCongestionInfo::from_chunk_headers(block.chunks().iter()),
                 *block.header().random_value(),
                 true,
                 is_first_block_with_chunk_of_version,
@@ -269,6 +271,7 @@ fn apply_block_from_range(
                 block.header().gas_price(),
                 chunk_extra.gas_limit(),
                 block.header().challenges_result(),
+                &BlockCongestionInfo::from_chunk_headers(block.chunks().iter()),
                 *block.header().random_value(),
                 false,
                 false,
@@ -4,6 +4,7 @@ use near_chain::chain::collect_receipts_from_response;
 use near_chain::migrations::check_if_block_is_first_with_chunk_of_version;
 use near_chain::types::ApplyTransactionResult;
 use near_chain::{ChainStore, ChainStoreAccess, RuntimeAdapter};
+use near_primitives::congestion_info::BlockCongestionInfo;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::combine_hash;
 use near_primitives::receipt::Receipt;
@@ -131,6 +132,7 @@ pub(crate) fn apply_chunk(
             gas_price,
             chunk_header.gas_limit(),
             &vec![],
+            &BlockCongestionInfo::from_chunk_headers(prev_block.chunks().iter()),
             hash("random seed".as_ref()),
             true,
             is_first_block_with_chunk_of_version,
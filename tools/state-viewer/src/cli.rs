@@ -1,7 +1,8 @@
+use crate::account_usage::{account_usage, format_csv, format_json, OutputFormat, SortBy};
 use crate::commands::*;
 use crate::dump_state_parts::dump_state_parts;
 use crate::epoch_info;
-use crate::rocksdb_stats::get_rocksdb_stats;
+use crate::rocksdb_stats::{get_rocksdb_column_properties, get_rocksdb_stats};
 use clap::{Args, Parser, Subcommand};
 use near_chain_configs::{GenesisChangeConfig, GenesisValidationMode};
 use near_primitives::account::id::AccountId;
@@ -73,10 +74,23 @@ pub enum StateViewerSubCommand {
     ViewTrie(ViewTrieCmd),
     /// Dump all or a single state part of a shard.
     DumpStateParts(DumpStatePartsCmd),
+    /// Walk the whole trie of a shard at given height recomputing node hashes
+    /// and report missing or corrupted `State` column entries.
+    #[clap(alias = "verify_state_root")]
+    VerifyStateRoot(VerifyStateRootCmd),
+    /// Aggregate gas and tokens burnt, receipts and storage changes per
+    /// account over a range of blocks of an archival node.
+    #[clap(alias = "account_usage")]
+    AccountUsage(AccountUsageCmd),
 }
 
 impl StateViewerSubCommand {
-    pub fn run(self, home_dir: &Path, genesis_validation: GenesisValidationMode, mode: Mode) {
+    pub fn run(
+        self,
+        home_dir: &Path,
+        genesis_validation: GenesisValidationMode,
+        mode: Mode,
+    ) -> anyhow::Result<()> {
         let near_config = load_config(home_dir, genesis_validation)
             .unwrap_or_else(|e| panic!("Error loading config: {:#}", e));
         let store_opener =
@@ -93,13 +107,13 @@ impl StateViewerSubCommand {
             StateViewerSubCommand::Chain(cmd) => cmd.run(home_dir, near_config, hot),
             StateViewerSubCommand::Replay(cmd) => cmd.run(home_dir, near_config, hot),
             StateViewerSubCommand::ApplyRange(cmd) => cmd.run(home_dir, near_config, hot),
-            StateViewerSubCommand::Apply(cmd) => cmd.run(home_dir, near_config, hot),
+            StateViewerSubCommand::Apply(cmd) => cmd.run(home_dir, near_config, hot)?,
             StateViewerSubCommand::ViewChain(cmd) => cmd.run(near_config, hot),
             StateViewerSubCommand::CheckBlock => check_block_chunk_existence(near_config, hot),
             StateViewerSubCommand::DumpCode(cmd) => cmd.run(home_dir, near_config, hot),
             StateViewerSubCommand::DumpAccountStorage(cmd) => cmd.run(home_dir, near_config, hot),
             StateViewerSubCommand::EpochInfo(cmd) => cmd.run(home_dir, near_config, hot),
-            StateViewerSubCommand::RocksDBStats(cmd) => cmd.run(store_opener.path()),
+            StateViewerSubCommand::RocksDBStats(cmd) => cmd.run(store_opener.path(), hot),
             StateViewerSubCommand::Receipts(cmd) => cmd.run(near_config, hot),
             StateViewerSubCommand::Chunks(cmd) => cmd.run(near_config, hot),
             StateViewerSubCommand::PartialChunks(cmd) => cmd.run(near_config, hot),
@@ -107,7 +121,10 @@ impl StateViewerSubCommand {
             StateViewerSubCommand::ApplyTx(cmd) => cmd.run(home_dir, near_config, hot),
             StateViewerSubCommand::ApplyReceipt(cmd) => cmd.run(home_dir, near_config, hot),
             StateViewerSubCommand::ViewTrie(cmd) => cmd.run(hot),
+            StateViewerSubCommand::VerifyStateRoot(cmd) => cmd.run(home_dir, near_config, hot)?,
+            StateViewerSubCommand::AccountUsage(cmd) => cmd.run(home_dir, near_config, hot),
         }
+        Ok(())
     }
 }
 
@@ -283,8 +300,8 @@ pub struct ApplyCmd {
 }
 
 impl ApplyCmd {
-    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Store) {
-        apply_block_at_height(self.height, self.shard_id, home_dir, near_config, store);
+    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Store) -> anyhow::Result<()> {
+        apply_block_at_height(self.height, self.shard_id, home_dir, near_config, store)
     }
 }
 
@@ -369,11 +386,20 @@ pub struct RocksDBStatsCmd {
     /// Location of the dumped Rocks DB stats.
     #[clap(long, parse(from_os_str))]
     file: Option<PathBuf>,
+    /// Instead of running `sst_dump` on every SST file, report per-column
+    /// SST files sizes and pending compaction bytes as tracked by RocksDB.
+    #[clap(long)]
+    column_properties: bool,
 }
 
 impl RocksDBStatsCmd {
-    pub fn run(self, store_dir: &Path) {
-        get_rocksdb_stats(store_dir, self.file).expect("Couldn't get RocksDB stats");
+    pub fn run(self, store_dir: &Path, store: Store) {
+        if self.column_properties {
+            get_rocksdb_column_properties(&store, self.file)
+                .expect("Couldn't get RocksDB column properties");
+        } else {
+            get_rocksdb_stats(store_dir, self.file).expect("Couldn't get RocksDB stats");
+        }
     }
 }
 
@@ -504,3 +530,60 @@ impl DumpStatePartsCmd {
         );
     }
 }
+
+#[derive(Parser)]
+pub struct VerifyStateRootCmd {
+    /// Height of the block whose chunk’s `prev_state_root` is verified.
+    #[clap(long)]
+    height: BlockHeight,
+    /// Shard id.
+    #[clap(long)]
+    shard: ShardId,
+}
+
+impl VerifyStateRootCmd {
+    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Store) -> anyhow::Result<()> {
+        verify_state_root(self.height, self.shard, home_dir, near_config, store)
+    }
+}
+
+#[derive(Parser)]
+pub struct AccountUsageCmd {
+    /// Height of the first block to process, inclusive.
+    #[clap(long)]
+    start_height: BlockHeight,
+    /// Height of the last block to process, inclusive.
+    #[clap(long)]
+    end_height: BlockHeight,
+    /// Column by which the accounts are sorted, in descending order.
+    #[clap(long, arg_enum, default_value = "gas-burnt-as-receiver")]
+    sort_by: SortBy,
+    /// Output format of the report.
+    #[clap(long, arg_enum, default_value = "csv")]
+    format: OutputFormat,
+    /// Only print the given number of accounts with the highest values.
+    #[clap(long)]
+    limit: Option<usize>,
+    /// Path of the report. Printed to STDOUT if not set.
+    #[clap(long, parse(from_os_str))]
+    output: Option<PathBuf>,
+}
+
+impl AccountUsageCmd {
+    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Store) {
+        let table = account_usage(self.start_height, self.end_height, home_dir, near_config, store)
+            .unwrap();
+        let mut rows = table.sorted(self.sort_by);
+        if let Some(limit) = self.limit {
+            rows.truncate(limit);
+        }
+        let report = match self.format {
+            OutputFormat::Csv => format_csv(&rows),
+            OutputFormat::Json => format_json(&rows).unwrap(),
+        };
+        match self.output {
+            Some(path) => std::fs::write(path, report).unwrap(),
+            None => print!("{report}"),
+        }
+    }
+}
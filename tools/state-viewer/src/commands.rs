@@ -4,6 +4,7 @@ use crate::state_dump::state_dump_redis;
 use crate::tx_dump::dump_tx_from_block;
 use crate::{apply_chunk, epoch_info};
 use ansi_term::Color::Red;
+use anyhow::Context;
 use near_chain::chain::collect_receipts_from_response;
 use near_chain::migrations::check_if_block_is_first_with_chunk_of_version;
 use near_chain::types::{ApplyTransactionResult, BlockHeaderInfo};
@@ -15,14 +16,16 @@ use near_epoch_manager::EpochManagerAdapter;
 use near_network::iter_peers_from_store;
 use near_primitives::account::id::AccountId;
 use near_primitives::block::{Block, BlockHeader};
+use near_primitives::congestion_info::BlockCongestionInfo;
 use near_primitives::hash::CryptoHash;
 use near_primitives::shard_layout::ShardUId;
-use near_primitives::sharding::ChunkHash;
+use near_primitives::sharding::{ChunkHash, ShardChunkHeader};
 use near_primitives::state_record::StateRecord;
 use near_primitives::trie_key::TrieKey;
 use near_primitives::types::chunk_extra::ChunkExtra;
 use near_primitives::types::{BlockHeight, ShardId, StateRoot};
 use near_primitives_core::types::Gas;
+use near_store::get_deduplicated_code;
 use near_store::test_utils::create_test_store;
 use near_store::Trie;
 use near_store::TrieCache;
@@ -55,6 +58,17 @@ pub(crate) fn state(home_dir: &Path, near_config: NearConfig, store: Store) {
             let (key, value) = item.unwrap();
             if let Some(state_record) = StateRecord::from_raw_key_value(key, value) {
                 println!("{}", state_record);
+                if let StateRecord::Account { account_id, account } = &state_record {
+                    if let Some(code) =
+                        get_deduplicated_code(&trie, account_id, account.code_hash()).unwrap()
+                    {
+                        let contract = StateRecord::Contract {
+                            account_id: account_id.clone(),
+                            code: code.code().to_vec(),
+                        };
+                        println!("{}", contract);
+                    }
+                }
             }
         }
     }
@@ -444,6 +458,7 @@ pub(crate) fn apply_block(
                 prev_block.header().gas_price(),
                 chunk_inner.gas_limit(),
                 block.header().challenges_result(),
+                &BlockCongestionInfo::from_chunk_headers(block.chunks().iter()),
                 *block.header().random_value(),
                 true,
                 is_first_block_with_chunk_of_version,
@@ -469,6 +484,7 @@ pub(crate) fn apply_block(
                 block.header().gas_price(),
                 chunk_extra.gas_limit(),
                 block.header().challenges_result(),
+                &BlockCongestionInfo::from_chunk_headers(block.chunks().iter()),
                 *block.header().random_value(),
                 false,
                 false,
@@ -507,13 +523,27 @@ pub(crate) fn print_apply_block_result(
     }
 }
 
+/// Returns the header of the chunk of `shard_id` in `block`.  Shard ids given on
+/// the command line aren't validated, hence an error rather than a panic.
+fn get_chunk_header(block: &Block, shard_id: ShardId) -> anyhow::Result<ShardChunkHeader> {
+    let chunks = block.chunks();
+    chunks.get(shard_id as usize).cloned().with_context(|| {
+        format!(
+            "invalid shard id {}, block {} only has shards 0..{}",
+            shard_id,
+            block.hash(),
+            chunks.len()
+        )
+    })
+}
+
 pub(crate) fn apply_block_at_height(
     height: BlockHeight,
     shard_id: ShardId,
     home_dir: &Path,
     near_config: NearConfig,
     store: Store,
-) {
+) -> anyhow::Result<()> {
     let mut chain_store = ChainStore::new(
         store.clone(),
         near_config.genesis.config.genesis_height,
@@ -521,7 +551,8 @@ pub(crate) fn apply_block_at_height(
     );
     let runtime_adapter: Arc<dyn RuntimeAdapter> =
         Arc::new(NightshadeRuntime::from_config(home_dir, store, &near_config));
-    let block_hash = chain_store.get_block_hash_by_height(height).unwrap();
+    let block_hash = chain_store.get_block_hash_by_height(height)?;
+    get_chunk_header(&chain_store.get_block(&block_hash)?, shard_id)?;
     let (block, apply_result) =
         apply_block(block_hash, shard_id, runtime_adapter.as_ref(), &mut chain_store);
     print_apply_block_result(
@@ -531,6 +562,46 @@ pub(crate) fn apply_block_at_height(
         &mut chain_store,
         shard_id,
     );
+    Ok(())
+}
+
+pub(crate) fn verify_state_root(
+    height: BlockHeight,
+    shard_id: ShardId,
+    home_dir: &Path,
+    near_config: NearConfig,
+    store: Store,
+) -> anyhow::Result<()> {
+    let chain_store = ChainStore::new(
+        store.clone(),
+        near_config.genesis.config.genesis_height,
+        !near_config.client_config.archive,
+    );
+    let runtime = NightshadeRuntime::from_config(home_dir, store.clone(), &near_config);
+    let block_hash = chain_store.get_block_hash_by_height(height)?;
+    let block = chain_store.get_block(&block_hash)?;
+    let shard_uid = runtime.shard_id_to_uid(shard_id, block.header().epoch_id())?;
+    let state_root = get_chunk_header(&block, shard_id)?.prev_state_root();
+    println!("Verifying state root {} of shard {} at block {}", state_root, shard_uid, block_hash);
+
+    let started = std::time::Instant::now();
+    let report = near_store::verify::verify_state_root(&store, shard_uid, &state_root)?;
+    for issue in report.issues.iter() {
+        println!("{}", Red.paint(issue.to_string()));
+    }
+    println!(
+        "Checked {} nodes and {} values ({} shared references) in {:?}, found {} issues",
+        report.nodes,
+        report.values,
+        report.shared_references,
+        started.elapsed(),
+        report.issues.len()
+    );
+    if let Some(memory_usage) = report.memory_usage {
+        println!("Computed memory usage of the trie: {}", memory_usage);
+    }
+    anyhow::ensure!(report.is_ok(), "found {} issues in the state", report.issues.len());
+    Ok(())
 }
 
 pub(crate) fn view_chain(
@@ -1,5 +1,6 @@
 #![doc = include_str!("../README.md")]
 
+mod account_usage;
 mod apply_chain_range;
 mod apply_chunk;
 pub mod cli;
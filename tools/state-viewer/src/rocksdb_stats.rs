@@ -1,3 +1,6 @@
+use anyhow::Context;
+use near_store::db::StatsValue;
+use near_store::Store;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -114,10 +117,110 @@ pub fn get_rocksdb_stats(store_dir: &Path, file: Option<PathBuf>) -> anyhow::Res
     }
 
     eprintln!("Dumping stats ...");
-    let result = serde_json::to_string_pretty(&data.into_vec()).unwrap();
+    write_result(&data.into_vec(), file)
+}
+
+/// Size and compaction statistics of a single column as tracked by RocksDB.
+#[derive(Serialize, Debug, Default, PartialEq)]
+struct ColumnProperties {
+    col: String,
+    // Total size of SST files which belong to the latest version of the column.
+    live_sst_files_size: i64,
+    // Total size of all SST files of the column, including obsolete ones.
+    total_sst_files_size: i64,
+    // Estimated number of bytes compaction needs to rewrite to bring all
+    // levels down under their target size.
+    estimate_pending_compaction_bytes: i64,
+}
+
+impl ColumnProperties {
+    /// Collects per-column properties out of store statistics.
+    fn from_statistics(data: &[(String, Vec<StatsValue>)]) -> Vec<Self> {
+        let mut columns: HashMap<&'static str, Self> = HashMap::new();
+        for (stat_name, values) in data {
+            for value in values {
+                let (col, value) = match value {
+                    StatsValue::ColumnValue(col, value) => (<&str>::from(*col), *value),
+                    _ => continue,
+                };
+                let entry = columns
+                    .entry(col)
+                    .or_insert_with(|| Self { col: col.to_owned(), ..Self::default() });
+                match stat_name.as_str() {
+                    "rocksdb.live-sst-files-size" => entry.live_sst_files_size = value,
+                    "rocksdb.total-sst-files-size" => entry.total_sst_files_size = value,
+                    "rocksdb.estimate-pending-compaction-bytes" => {
+                        entry.estimate_pending_compaction_bytes = value
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let mut values: Vec<_> = columns.into_values().collect();
+        values.sort_by_key(|data| std::cmp::Reverse(data.total_sst_files_size));
+        values
+    }
+}
+
+pub fn get_rocksdb_column_properties(store: &Store, file: Option<PathBuf>) -> anyhow::Result<()> {
+    let stats = store.get_store_statistics().context("Store statistics are not available")?;
+    write_result(&ColumnProperties::from_statistics(&stats.data), file)
+}
+
+fn write_result<T: Serialize>(data: &T, file: Option<PathBuf>) -> anyhow::Result<()> {
+    let result = serde_json::to_string_pretty(data).unwrap();
     match file {
         None => println!("{}", result),
         Some(file) => std::fs::write(file, result)?,
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ColumnProperties;
+    use near_store::db::StatsValue;
+    use near_store::DBCol;
+
+    #[test]
+    fn test_column_properties_from_statistics() {
+        let data = vec![
+            ("rocksdb.block.cache.miss".to_string(), vec![StatsValue::Count(3)]),
+            (
+                "rocksdb.live-sst-files-size".to_string(),
+                vec![
+                    StatsValue::ColumnValue(DBCol::State, 100),
+                    StatsValue::ColumnValue(DBCol::Block, 7),
+                ],
+            ),
+            (
+                "rocksdb.total-sst-files-size".to_string(),
+                vec![
+                    StatsValue::ColumnValue(DBCol::State, 120),
+                    StatsValue::ColumnValue(DBCol::Block, 8),
+                ],
+            ),
+            (
+                "rocksdb.estimate-pending-compaction-bytes".to_string(),
+                vec![StatsValue::ColumnValue(DBCol::State, 42)],
+            ),
+        ];
+        assert_eq!(
+            ColumnProperties::from_statistics(&data),
+            vec![
+                ColumnProperties {
+                    col: "State".to_string(),
+                    live_sst_files_size: 100,
+                    total_sst_files_size: 120,
+                    estimate_pending_compaction_bytes: 42,
+                },
+                ColumnProperties {
+                    col: "Block".to_string(),
+                    live_sst_files_size: 7,
+                    total_sst_files_size: 8,
+                    estimate_pending_compaction_bytes: 0,
+                },
+            ]
+        );
+    }
+}
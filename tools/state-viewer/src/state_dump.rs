@@ -9,6 +9,7 @@ use near_primitives::state_record::state_record_to_account_id;
 use near_primitives::state_record::StateRecord;
 use near_primitives::time::Utc;
 use near_primitives::types::{AccountInfo, Balance, StateRoot};
+use near_store::get_deduplicated_code;
 use nearcore::config::NearConfig;
 use nearcore::NightshadeRuntime;
 use redis::Commands;
@@ -166,6 +167,22 @@ pub fn state_dump_redis(
                         value,
                     )?;
                     println!("Account written: {}", account_id);
+
+                    if let Some(code) =
+                        get_deduplicated_code(&trie, account_id, account.code_hash()).unwrap()
+                    {
+                        let redis_key = [b"code:", account_id.as_ref().as_bytes()].concat();
+                        redis_connection.zadd(
+                            redis_key.clone(),
+                            block_hash.as_ref(),
+                            block_height,
+                        )?;
+                        redis_connection.set(
+                            [redis_key, b":".to_vec(), block_hash.0.to_vec()].concat(),
+                            code.code(),
+                        )?;
+                        println!("Contract written: {}", account_id);
+                    }
                 }
 
                 if let StateRecord::Data { account_id, data_key, value } = &sr {
@@ -259,8 +276,22 @@ fn iterate_over_records(
                         account.set_locked(stake);
                     }
                 }
+                let deduplicated_code = match &sr {
+                    StateRecord::Account { account_id, account } => {
+                        get_deduplicated_code(&trie, account_id, account.code_hash()).unwrap().map(
+                            |code| StateRecord::Contract {
+                                account_id: account_id.clone(),
+                                code: code.code().to_vec(),
+                            },
+                        )
+                    }
+                    _ => None,
+                };
                 change_state_record(&mut sr, change_config);
                 callback(sr);
+                if let Some(contract) = deduplicated_code {
+                    callback(contract);
+                }
             }
         }
     }